@@ -0,0 +1,33 @@
+use apca::api::v2::account::{Account as AlpacaAccount, Get as GetAccount};
+use apca::Client;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use tokio::runtime::Runtime;
+
+use model::Account;
+use std::error::Error;
+
+use crate::keys;
+
+/// Read the buying power of the account in Alpaca.
+/// The keys of the environment of the account are used, so a paper account never reads a live one.
+pub fn read(account: &Account) -> Result<Decimal, Box<dyn Error>> {
+    let api_info = keys::read_api_key(&account.environment, account)?;
+    let client = Client::new(api_info);
+
+    let alpaca_account = Runtime::new().unwrap().block_on(read_account(&client))?;
+
+    let buying_power = Decimal::from_str(alpaca_account.buying_power.to_string().as_str())?;
+    Ok(buying_power)
+}
+
+async fn read_account(client: &Client) -> Result<AlpacaAccount, Box<dyn Error>> {
+    let result = client.issue::<GetAccount>(&()).await;
+    match result {
+        Ok(account) => Ok(account),
+        Err(e) => {
+            eprintln!("Error reading account: {:?}", e);
+            Err(Box::new(e))
+        }
+    }
+}
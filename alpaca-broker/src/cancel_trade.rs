@@ -1,7 +1,8 @@
 use crate::keys;
-use apca::api::v2::order::{Delete, Id};
-use apca::Client;
-use model::{Account, Trade};
+use apca::api::v2::order::{Delete, DeleteError, Id};
+use apca::api::v2::orders::{Get, OrdersReq, Status as AlpacaRequestStatus};
+use apca::{Client, RequestError};
+use model::{Account, BrokerError, BrokerErrorCode, Order, OrderCategory, Trade};
 use std::error::Error;
 use tokio::runtime::Runtime;
 use uuid::Uuid;
@@ -22,9 +23,81 @@ pub fn cancel(trade: &Trade, account: &Account) -> Result<(), Box<dyn Error>> {
         .unwrap()
         .block_on(cancel_entry(&client, trade.entry.broker_order_id.unwrap()))?;
 
+    // A trailing stop is not a leg of the entry, so it is canceled on its own.
+    if let (OrderCategory::TrailingStop { .. }, Some(stop_id)) = (
+        trade.safety_stop.category,
+        trade.safety_stop.broker_order_id,
+    ) {
+        Runtime::new()
+            .unwrap()
+            .block_on(cancel_entry(&client, stop_id))?;
+    }
+
     Ok(())
 }
 
+pub fn cancel_order(order: &Order, account: &Account) -> Result<(), Box<dyn Error>> {
+    let broker_order_id = order.broker_order_id.ok_or_else(|| not_working(order))?;
+
+    let api_info = keys::read_api_key(&account.environment, account)?;
+    let client = Client::new(api_info);
+
+    // Cancel the order. Alpaca answers that an order is not found or can not be canceled
+    // once it is not open anymore, for example when it was canceled with its OCO sibling.
+    let result = Runtime::new()
+        .unwrap()
+        .block_on(client.issue::<Delete>(&Id(broker_order_id)));
+    match result {
+        Ok(_) => Ok(()),
+        Err(RequestError::Endpoint(DeleteError::NotFound(_)))
+        | Err(RequestError::Endpoint(DeleteError::NotCancelable(_))) => {
+            Err(Box::new(not_working(order)))
+        }
+        Err(e) => {
+            eprintln!("Error cancel order: {:?}", e);
+            Err(Box::new(e))
+        }
+    }
+}
+
+fn not_working(order: &Order) -> BrokerError {
+    BrokerError {
+        code: BrokerErrorCode::OrderNotWorking,
+        message: format!("Order {} is not working in Alpaca", order.id),
+    }
+}
+
+/// Cancel all the open orders of the account in Alpaca.
+/// The legs of a bracket are canceled with their parent, so their ids are returned as well.
+pub fn cancel_all_orders(account: &Account) -> Result<Vec<Uuid>, Box<dyn Error>> {
+    let api_info = keys::read_api_key(&account.environment, account)?;
+    let client = Client::new(api_info);
+
+    Runtime::new()
+        .unwrap()
+        .block_on(cancel_open_orders(&client))
+}
+
+async fn cancel_open_orders(client: &Client) -> Result<Vec<Uuid>, Box<dyn Error>> {
+    let request = OrdersReq {
+        status: AlpacaRequestStatus::Open,
+        nested: true,
+        ..Default::default()
+    };
+    let orders = client.issue::<Get>(&request).await.map_err(|e| {
+        eprintln!("Error reading open orders: {:?}", e);
+        Box::new(e)
+    })?;
+
+    let mut canceled = Vec::new();
+    for order in orders {
+        cancel_entry(client, order.id.0).await?;
+        canceled.push(order.id.0);
+        canceled.extend(order.legs.iter().map(|leg| leg.id.0));
+    }
+    Ok(canceled)
+}
+
 async fn cancel_entry(client: &Client, order_id: Uuid) -> Result<(), Box<dyn Error>> {
     let result = client.issue::<Delete>(&Id(order_id)).await;
     match result {
@@ -35,3 +108,21 @@ async fn cancel_entry(client: &Client, order_id: Uuid) -> Result<(), Box<dyn Err
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_order_not_submitted() {
+        let order = Order {
+            broker_order_id: None,
+            ..Default::default()
+        };
+        let error = cancel_order(&order, &Account::default()).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<BrokerError>().unwrap().code,
+            BrokerErrorCode::OrderNotWorking
+        );
+    }
+}
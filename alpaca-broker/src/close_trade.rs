@@ -1,13 +1,14 @@
 use crate::keys;
 use apca::api::v2::order::{
-    Amount, Class, Delete, Id, Order as AlpacaOrder, OrderReq, OrderReqInit, Post, Side,
-    TimeInForce, Type,
+    Amount, Class, Order as AlpacaOrder, OrderReq, OrderReqInit, Post, Side, TimeInForce, Type,
 };
 use apca::Client;
-use model::{Account, BrokerLog, Order, Trade, TradeCategory};
+use model::{Account, BrokerLog, Order, OrderCategory, Trade, TradeCategory};
+use num_decimal::Num;
+use rust_decimal::Decimal;
 use std::error::Error;
+use std::str::FromStr;
 use tokio::runtime::Runtime;
-use uuid::Uuid;
 
 pub fn close(trade: &Trade, account: &Account) -> Result<(Order, BrokerLog), Box<dyn Error>> {
     assert!(trade.account_id == account.id); // Verify that the trade is for the account
@@ -15,40 +16,56 @@ pub fn close(trade: &Trade, account: &Account) -> Result<(Order, BrokerLog), Box
     let api_info = keys::read_api_key(&account.environment, account)?;
     let client = Client::new(api_info);
 
-    // 1. Cancel the target order.
-    Runtime::new().unwrap().block_on(cancel_target(
-        &client,
-        trade.target.broker_order_id.unwrap(),
-    ))?;
-
-    // 2. Submit a market order to close the trade.
+    // 1. Submit a market order to close the trade.
+    // The working stop and target are canceled before with `cancel_order`.
     let request = new_request(trade);
     let alpaca_order = Runtime::new()
         .unwrap()
         .block_on(submit_market_order(client, request))?;
 
-    // 3. Log the Alpaca order.
+    // 2. Log the Alpaca order.
     let log = BrokerLog {
         trade_id: trade.id,
         log: serde_json::to_string(&alpaca_order)?,
         ..Default::default()
     };
 
-    // 4. Map the Alpaca order to a Trust order.
+    // 3. Map the Alpaca order to a Trust order.
     let order: Order = crate::order_mapper::map_close_order(&alpaca_order, trade.target.clone());
 
     Ok((order, log))
 }
 
-async fn cancel_target(client: &Client, order_id: Uuid) -> Result<(), Box<dyn Error>> {
-    let result = client.issue::<Delete>(&Id(order_id)).await;
-    match result {
-        Ok(_) => Ok(()),
-        Err(e) => {
-            eprintln!("Error cancel target: {:?}", e);
-            Err(Box::new(e))
-        }
-    }
+pub fn close_at_limit(
+    trade: &Trade,
+    account: &Account,
+    limit_price: Decimal,
+) -> Result<(Order, BrokerLog), Box<dyn Error>> {
+    assert!(trade.account_id == account.id); // Verify that the trade is for the account
+
+    let api_info = keys::read_api_key(&account.environment, account)?;
+    let client = Client::new(api_info);
+
+    // 1. Submit a limit order to close the trade.
+    let request = new_limit_request(trade, limit_price)?;
+    let alpaca_order = Runtime::new()
+        .unwrap()
+        .block_on(submit_market_order(client, request))?;
+
+    // 2. Log the Alpaca order.
+    let log = BrokerLog {
+        trade_id: trade.id,
+        log: serde_json::to_string(&alpaca_order)?,
+        ..Default::default()
+    };
+
+    // 3. Map the Alpaca order to a Trust order.
+    let mut order: Order =
+        crate::order_mapper::map_close_order(&alpaca_order, trade.target.clone());
+    order.category = OrderCategory::Limit;
+    order.unit_price = limit_price;
+
+    Ok((order, log))
 }
 
 async fn submit_market_order(
@@ -81,6 +98,22 @@ fn new_request(trade: &Trade) -> OrderReq {
     )
 }
 
+fn new_limit_request(trade: &Trade, limit_price: Decimal) -> Result<OrderReq, Box<dyn Error>> {
+    Ok(OrderReqInit {
+        class: Class::Simple,
+        type_: Type::Limit,
+        limit_price: Some(Num::from_str(&limit_price.to_string())?),
+        time_in_force: TimeInForce::UntilCanceled,
+        extended_hours: trade.target.extended_hours,
+        ..Default::default()
+    }
+    .init(
+        trade.trading_vehicle.symbol.to_uppercase(),
+        side(trade),
+        Amount::quantity(trade.entry.quantity),
+    ))
+}
+
 pub fn side(trade: &Trade) -> Side {
     match trade.category {
         TradeCategory::Long => Side::Sell,
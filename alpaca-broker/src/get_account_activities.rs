@@ -0,0 +1,157 @@
+use crate::keys;
+use apca::api::v2::account_activities::{
+    Activity, ActivityReq, ActivityType, Get, NonTradeActivity, Side, TradeActivity,
+};
+use apca::Client;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use model::{Account, BrokerActivity, BrokerActivityCategory, Currency, ExecutionSide};
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+pub fn get(
+    account: &Account,
+    since: NaiveDateTime,
+    until: NaiveDateTime,
+) -> Result<Vec<BrokerActivity>, Box<dyn Error>> {
+    let api_info = keys::read_api_key(&account.environment, account)?;
+    let client = Client::new(api_info);
+
+    let activities = Runtime::new()
+        .unwrap()
+        .block_on(get_activities(&client, since, until))?;
+
+    Ok(activities.iter().map(map_activity).collect())
+}
+
+async fn get_activities(
+    client: &Client,
+    since: NaiveDateTime,
+    until: NaiveDateTime,
+) -> Result<Vec<Activity>, Box<dyn Error>> {
+    let request = ActivityReq {
+        types: vec![
+            ActivityType::Fill,
+            ActivityType::Fee,
+            ActivityType::PassThruCharge,
+        ],
+        after: Some(DateTime::<Utc>::from_naive_utc_and_offset(since, Utc)),
+        until: Some(DateTime::<Utc>::from_naive_utc_and_offset(until, Utc)),
+        ..Default::default()
+    };
+
+    let result = client.issue::<Get>(&request).await;
+    match result {
+        Ok(activities) => Ok(activities),
+        Err(e) => {
+            eprintln!("Error getting account activities: {:?}", e);
+            Err(Box::new(e))
+        }
+    }
+}
+
+fn map_activity(activity: &Activity) -> BrokerActivity {
+    match activity {
+        Activity::Trade(trade) => map_trade(trade),
+        Activity::NonTrade(fee) => map_non_trade(fee),
+    }
+}
+
+fn map_trade(activity: &TradeActivity) -> BrokerActivity {
+    BrokerActivity {
+        id: activity.id.clone(),
+        category: BrokerActivityCategory::Fill,
+        order_id: Some(Uuid::parse_str(&activity.order_id.to_string()).unwrap()),
+        side: Some(match activity.side {
+            Side::Buy => ExecutionSide::Buy,
+            _ => ExecutionSide::Sell,
+        }),
+        price: Some(Decimal::from_str(&activity.price.to_string()).unwrap()),
+        quantity: activity.quantity.to_u64(),
+        amount: None,
+        currency: Currency::USD,
+        transaction_time: activity.transaction_time.naive_utc(),
+    }
+}
+
+fn map_non_trade(activity: &NonTradeActivity) -> BrokerActivity {
+    let category = match activity.type_ {
+        ActivityType::Fee | ActivityType::PassThruCharge => BrokerActivityCategory::Fee,
+        _ => BrokerActivityCategory::Other,
+    };
+
+    // Alpaca reports the fees as a negative net amount.
+    let amount = Decimal::from_str(&activity.net_amount.to_string()).unwrap();
+
+    BrokerActivity {
+        id: activity.id.clone(),
+        category,
+        order_id: None,
+        side: None,
+        price: None,
+        quantity: None,
+        amount: Some(amount.abs()),
+        currency: Currency::USD,
+        transaction_time: activity.date.naive_utc(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn activities() -> Vec<Activity> {
+        let data = r#"
+        [
+            {
+                "activity_type": "FILL",
+                "cum_qty": "100",
+                "id": "20230612153406977::8efc7b9a-8b2b-4000-9955-d36e7db0df74",
+                "leaves_qty": "0",
+                "price": "40.1",
+                "qty": "100",
+                "side": "buy",
+                "symbol": "TSLA",
+                "transaction_time": "2023-06-12T15:34:06.977Z",
+                "order_id": "b6b12dc0-8e21-4d2e-8315-907d3116a6b8",
+                "type": "fill"
+            },
+            {
+                "activity_type": "FEE",
+                "id": "20230612000000000::5f596936-6f23-4cef-bdf1-3806aae57dbf",
+                "date": "2023-06-12",
+                "net_amount": "-0.02",
+                "description": "REG FEE",
+                "status": "executed"
+            }
+        ]"#;
+        serde_json::from_str(data).unwrap()
+    }
+
+    #[test]
+    fn test_map_fill() {
+        let activity = map_activity(&activities()[0]);
+
+        assert_eq!(activity.category, BrokerActivityCategory::Fill);
+        assert_eq!(
+            activity.order_id,
+            Some(Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap())
+        );
+        assert_eq!(activity.side, Some(ExecutionSide::Buy));
+        assert_eq!(activity.price, Some(dec!(40.1)));
+        assert_eq!(activity.quantity, Some(100));
+        assert_eq!(activity.amount, None);
+    }
+
+    #[test]
+    fn test_map_fee() {
+        let activity = map_activity(&activities()[1]);
+
+        assert_eq!(activity.category, BrokerActivityCategory::Fee);
+        assert_eq!(activity.order_id, None);
+        assert_eq!(activity.amount, Some(dec!(0.02)));
+    }
+}
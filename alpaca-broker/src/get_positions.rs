@@ -0,0 +1,85 @@
+use crate::keys;
+use apca::api::v2::position::{Position, Side};
+use apca::api::v2::positions::Get;
+use apca::Client;
+use model::{Account, BrokerPosition, TradeCategory};
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+use tokio::runtime::Runtime;
+
+/// Read the open positions of the account in Alpaca.
+pub fn get(account: &Account) -> Result<Vec<BrokerPosition>, Box<dyn Error>> {
+    let api_info = keys::read_api_key(&account.environment, account)?;
+    let client = Client::new(api_info);
+
+    let positions = Runtime::new().unwrap().block_on(get_positions(&client))?;
+
+    positions.iter().map(map_position).collect()
+}
+
+async fn get_positions(client: &Client) -> Result<Vec<Position>, Box<dyn Error>> {
+    let result = client.issue::<Get>(&()).await;
+    match result {
+        Ok(positions) => Ok(positions),
+        Err(e) => {
+            eprintln!("Error getting positions: {:?}", e);
+            Err(Box::new(e))
+        }
+    }
+}
+
+fn map_position(position: &Position) -> Result<BrokerPosition, Box<dyn Error>> {
+    Ok(BrokerPosition {
+        symbol: position.symbol.clone(),
+        category: match position.side {
+            Side::Long => TradeCategory::Long,
+            Side::Short => TradeCategory::Short,
+        },
+        quantity: Decimal::from_str(&position.quantity.to_string())?,
+        average_entry_price: Decimal::from_str(&position.average_entry_price.to_string())?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_map_position() {
+        let data = r#"
+        [
+            {
+                "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+                "symbol": "TSLA",
+                "exchange": "NASDAQ",
+                "asset_class": "us_equity",
+                "avg_entry_price": "40.1",
+                "qty": "-100",
+                "qty_available": "-100",
+                "side": "short",
+                "market_value": "-4000",
+                "cost_basis": "-4010",
+                "unrealized_pl": "10",
+                "unrealized_plpc": "0.0025",
+                "unrealized_intraday_pl": "10",
+                "unrealized_intraday_plpc": "0.0025",
+                "current_price": "40",
+                "lastday_price": "40.2",
+                "change_today": "-0.005"
+            }
+        ]"#;
+        let positions: Vec<Position> = serde_json::from_str(data).unwrap();
+
+        assert_eq!(
+            map_position(&positions[0]).unwrap(),
+            BrokerPosition {
+                symbol: "TSLA".to_string(),
+                category: TradeCategory::Short,
+                quantity: dec!(100),
+                average_entry_price: dec!(40.1),
+            }
+        );
+    }
+}
@@ -1,15 +1,24 @@
-use model::{Account, Broker, BrokerLog, Environment, Order, OrderIds, Status, Trade};
+use chrono::NaiveDateTime;
+use model::{
+    Account, Broker, BrokerActivity, BrokerLog, BrokerPosition, BrokerValidation, Environment,
+    Order, OrderIds, Status, Trade,
+};
 use std::error::Error;
 use uuid::Uuid;
 
+mod account_buying_power;
 mod cancel_trade;
 mod close_trade;
+mod get_account_activities;
+mod get_positions;
 mod keys;
 mod modify_stop;
 mod modify_target;
 mod order_mapper;
+mod replace_order;
 mod submit_trade;
 mod sync_trade;
+mod validate_trade;
 pub use keys::Keys;
 
 #[derive(Default)]
@@ -25,6 +34,14 @@ impl Broker for AlpacaBroker {
         submit_trade::submit_sync(trade, account)
     }
 
+    fn validate_trade(
+        &self,
+        trade: &Trade,
+        account: &Account,
+    ) -> Result<BrokerValidation, Box<dyn Error>> {
+        validate_trade::validate(trade, account)
+    }
+
     fn sync_trade(
         &self,
         trade: &Trade,
@@ -41,11 +58,38 @@ impl Broker for AlpacaBroker {
         close_trade::close(trade, account)
     }
 
+    fn close_trade_limit(
+        &self,
+        trade: &Trade,
+        account: &Account,
+        limit_price: rust_decimal::Decimal,
+    ) -> Result<(Order, BrokerLog), Box<dyn Error>> {
+        close_trade::close_at_limit(trade, account, limit_price)
+    }
+
     fn cancel_trade(&self, trade: &Trade, account: &Account) -> Result<(), Box<dyn Error>> {
         println!("Canceling trade: {:?}", trade);
         cancel_trade::cancel(trade, account)
     }
 
+    fn cancel_order(&self, order: &Order, account: &Account) -> Result<(), Box<dyn Error>> {
+        cancel_trade::cancel_order(order, account)
+    }
+
+    fn cancel_all_orders(&self, account: &Account) -> Result<Vec<Uuid>, Box<dyn Error>> {
+        cancel_trade::cancel_all_orders(account)
+    }
+
+    fn replace_order(
+        &self,
+        order: &Order,
+        account: &Account,
+        new_price: rust_decimal::Decimal,
+        new_quantity: u64,
+    ) -> Result<Uuid, Box<dyn Error>> {
+        replace_order::replace(order, account, new_price, new_quantity)
+    }
+
     fn modify_stop(
         &self,
         trade: &Trade,
@@ -63,6 +107,26 @@ impl Broker for AlpacaBroker {
     ) -> Result<Uuid, Box<dyn Error>> {
         modify_target::modify(trade, account, new_target_price)
     }
+
+    fn get_account_activities(
+        &self,
+        account: &Account,
+        since: NaiveDateTime,
+        until: NaiveDateTime,
+    ) -> Result<Vec<BrokerActivity>, Box<dyn Error>> {
+        get_account_activities::get(account, since, until)
+    }
+
+    fn account_buying_power(
+        &self,
+        account: &Account,
+    ) -> Result<rust_decimal::Decimal, Box<dyn Error>> {
+        account_buying_power::read(account)
+    }
+
+    fn get_positions(&self, account: &Account) -> Result<Vec<BrokerPosition>, Box<dyn Error>> {
+        get_positions::get(account)
+    }
 }
 
 /// Alpaca-specific Broker API
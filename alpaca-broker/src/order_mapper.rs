@@ -3,6 +3,7 @@ use chrono::DateTime;
 use chrono::NaiveDateTime;
 use chrono::Utc;
 use model::{Order, OrderCategory, OrderStatus, Status, Trade};
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use std::error::Error;
 use std::str::FromStr;
@@ -27,7 +28,9 @@ pub fn map_entry(alpaca_order: AlpacaOrder, trade: &Trade) -> Result<Vec<Order>,
                     None
                 }
             }
-            id if id == trade.safety_stop.broker_order_id.unwrap().to_string() => {
+            id if Some(id.to_string())
+                == trade.safety_stop.broker_order_id.map(|id| id.to_string()) =>
+            {
                 // 1. Map stop order to our domain model.
                 let order = map(order, trade.safety_stop.clone());
 
@@ -57,6 +60,25 @@ pub fn map_target(alpaca_order: AlpacaOrder, trade: &Trade) -> Result<Vec<Order>
     Ok(vec![map(&alpaca_order, trade.target.clone())])
 }
 
+/// Maps a trailing stop, that is submitted on its own and not as a leg of the entry.
+/// The stop is submitted once the entry is filled, so it is linked to its Alpaca order the first time.
+pub fn map_trailing_stop(
+    alpaca_order: AlpacaOrder,
+    trade: &Trade,
+) -> Result<Vec<Order>, Box<dyn Error>> {
+    let mut stop = trade.safety_stop.clone();
+    if stop.broker_order_id.is_none() {
+        stop.broker_order_id = Some(Uuid::parse_str(&alpaca_order.id.to_string())?);
+        stop.submitted_at = map_date(alpaca_order.submitted_at);
+    }
+    let order = map(&alpaca_order, stop);
+    if order != trade.safety_stop {
+        Ok(vec![order])
+    } else {
+        Ok(vec![])
+    }
+}
+
 pub fn map_trade_status(trade: &Trade, updated_orders: &[Order]) -> Status {
     if updated_orders
         .iter()
@@ -93,7 +115,13 @@ fn map(alpaca_order: &AlpacaOrder, order: Order) -> Order {
     );
 
     let mut order = order;
-    order.filled_quantity = alpaca_order.filled_quantity.to_u64().unwrap();
+    let filled_quantity =
+        Decimal::from_str(alpaca_order.filled_quantity.to_string().as_str()).unwrap();
+    order.filled_quantity = filled_quantity.trunc().to_u64().unwrap();
+    // An order with a fractional quantity is filled in fractions as well.
+    if order.quantity_decimal.is_some() {
+        order.filled_quantity_decimal = Some(filled_quantity);
+    }
     order.average_filled_price = alpaca_order
         .average_fill_price
         .clone()
@@ -102,6 +130,13 @@ fn map(alpaca_order: &AlpacaOrder, order: Order) -> Order {
     order.filled_at = map_date(alpaca_order.filled_at);
     order.expired_at = map_date(alpaca_order.expired_at);
     order.cancelled_at = map_date(alpaca_order.canceled_at);
+
+    // The stop price of a trailing stop moves with the price, so the current one is read back.
+    if let OrderCategory::TrailingStop { .. } = order.category {
+        if let Some(stop_price) = alpaca_order.stop_price.clone() {
+            order.unit_price = Decimal::from_str(stop_price.to_string().as_str()).unwrap();
+        }
+    }
     order
 }
 
@@ -244,6 +279,37 @@ mod tests {
         assert_eq!(result[0].average_filled_price, Some(dec!(10)));
     }
 
+    #[test]
+    fn test_map_orders_returns_entry_with_fractional_fill() {
+        let entry_id = Uuid::new_v4();
+
+        let alpaca_order = AlpacaOrder {
+            id: Id(entry_id),
+            filled_at: Some(Utc::now()),
+            filled_quantity: Num::new(1, 4),
+            status: AlpacaStatus::Filled,
+            average_fill_price: Some(Num::from(40000)),
+            ..default()
+        };
+
+        let trade = Trade {
+            entry: Order {
+                broker_order_id: Some(entry_id),
+                quantity: 0,
+                quantity_decimal: Some(dec!(0.25)),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let result = map_entry(alpaca_order, &trade).unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].filled_quantity, 0);
+        assert_eq!(result[0].filled_quantity_decimal, Some(dec!(0.25)));
+        assert_eq!(result[0].decimal_filled_quantity(), dec!(0.25));
+    }
+
     #[test]
     fn test_map_orders_returns_entry_and_target() {
         let entry_id = Uuid::new_v4();
@@ -0,0 +1,123 @@
+use crate::keys;
+use apca::api::v2::order::{ChangeReqInit, Id, Order as AlpacaOrder, Patch};
+use apca::Client;
+use model::{Account, BrokerError, BrokerErrorCode, Order, OrderCategory};
+use num_decimal::Num;
+use rust_decimal::Decimal;
+use std::{error::Error, str::FromStr};
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+pub fn replace(
+    order: &Order,
+    account: &Account,
+    price: Decimal,
+    quantity: u64,
+) -> Result<Uuid, Box<dyn Error>> {
+    let request = change_request(order, price, quantity);
+    let order_id = order.broker_order_id.ok_or_else(|| BrokerError {
+        code: BrokerErrorCode::OrderNotWorking,
+        message: format!("Order {} was not submitted to Alpaca", order.id),
+    })?;
+
+    let api_info = keys::read_api_key(&account.environment, account)?;
+    let client = Client::new(api_info);
+
+    // Replace the order, Alpaca cancels the old one once the new one is accepted.
+    let alpaca_order = Runtime::new()
+        .unwrap()
+        .block_on(submit(&client, order_id, request))?;
+
+    Ok(alpaca_order.id.0)
+}
+
+/// Trust saves the safety stop as a market order, because it is sold at market once the
+/// stop price is reached. In Alpaca it is a stop order, so its stop price is replaced.
+/// A trailing stop follows the price on its own, so it keeps its trail.
+fn change_request(order: &Order, price: Decimal, quantity: u64) -> ChangeReqInit {
+    let price = Some(Num::from_str(price.to_string().as_str()).unwrap());
+    let quantity = Some(Num::from(quantity));
+
+    match order.category {
+        OrderCategory::Limit => ChangeReqInit {
+            quantity,
+            limit_price: price,
+            ..Default::default()
+        },
+        OrderCategory::Market | OrderCategory::Stop => ChangeReqInit {
+            quantity,
+            stop_price: price,
+            ..Default::default()
+        },
+        OrderCategory::TrailingStop {
+            trail_percent,
+            trail_price,
+        } => ChangeReqInit {
+            quantity,
+            trail: trail_percent
+                .or(trail_price)
+                .map(|trail| Num::from_str(trail.to_string().as_str()).unwrap()),
+            ..Default::default()
+        },
+    }
+}
+
+async fn submit(
+    client: &Client,
+    order_id: Uuid,
+    request: ChangeReqInit,
+) -> Result<AlpacaOrder, Box<dyn Error>> {
+    let result = client.issue::<Patch>(&(Id(order_id), request.init())).await;
+    match result {
+        Ok(order) => Ok(order),
+        Err(e) => {
+            eprintln!("Error replacing order: {:?}", e);
+            Err(Box::new(e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn order(category: OrderCategory) -> Order {
+        Order {
+            category,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_change_request_stop() {
+        for category in [OrderCategory::Market, OrderCategory::Stop] {
+            let request = change_request(&order(category), dec!(38.5), 100);
+            assert_eq!(request.stop_price, Some(Num::from_str("38.5").unwrap()));
+            assert_eq!(request.limit_price, None);
+            assert_eq!(request.quantity, Some(Num::from(100)));
+        }
+    }
+
+    #[test]
+    fn test_change_request_limit() {
+        let request = change_request(&order(OrderCategory::Limit), dec!(50), 100);
+        assert_eq!(request.limit_price, Some(Num::from(50)));
+        assert_eq!(request.stop_price, None);
+    }
+
+    #[test]
+    fn test_replace_order_not_submitted() {
+        let error = replace(
+            &order(OrderCategory::Limit),
+            &Account::default(),
+            dec!(50),
+            100,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<BrokerError>().unwrap().code,
+            BrokerErrorCode::OrderNotWorking
+        );
+    }
+}
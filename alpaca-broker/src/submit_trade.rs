@@ -9,7 +9,8 @@ use std::str::FromStr;
 use tokio::runtime::Runtime;
 use uuid::Uuid;
 
-use model::{Account, BrokerLog, Order, OrderIds, Trade, TradeCategory};
+use model::{Account, BrokerLog, Order, OrderCategory, OrderIds, Trade, TradeCategory};
+use rust_decimal::Decimal;
 use std::error::Error;
 
 use crate::keys;
@@ -23,8 +24,8 @@ pub fn submit_sync(
     let api_info = keys::read_api_key(&account.environment, account)?;
     let client = Client::new(api_info);
 
-    let request = new_request(trade);
-    let order = Runtime::new().unwrap().block_on(submit(client, request))?;
+    let request = new_request(trade)?;
+    let order = Runtime::new().unwrap().block_on(submit(&client, request))?;
 
     let log = BrokerLog {
         trade_id: trade.id,
@@ -35,8 +36,21 @@ pub fn submit_sync(
     Ok((log, ids))
 }
 
+/// Submits the trailing stop of a trade whose entry is filled.
+pub fn submit_trailing_stop(
+    trade: &Trade,
+    account: &Account,
+) -> Result<AlpacaOrder, Box<dyn Error>> {
+    let request = trailing_stop_request(trade).ok_or("The safety stop is not a trailing stop")?;
+
+    let api_info = keys::read_api_key(&account.environment, account)?;
+    let client = Client::new(api_info);
+
+    Runtime::new().unwrap().block_on(submit(&client, request))
+}
+
 async fn submit(
-    client: Client,
+    client: &Client,
     request: OrderReq,
 ) -> Result<apca::api::v2::order::Order, Box<dyn Error>> {
     let result = client.issue::<Post>(&request).await;
@@ -70,28 +84,40 @@ fn extract_ids(order: &AlpacaOrder, trade: &Trade) -> OrderIds {
         }
     }
 
-    let stop_id = stop_id.expect("Stop ID not found");
+    // A trailing stop is not a leg of the entry, it is submitted once the entry is filled.
+    let stop_id = match trade.safety_stop.category {
+        OrderCategory::TrailingStop { .. } => None,
+        _ => Some(stop_id.expect("Stop ID not found")),
+    };
     let target_id = target_id.expect("Target ID not found");
 
     OrderIds {
-        stop: Uuid::from_str(&stop_id.to_string()).unwrap(),
+        stop: stop_id.map(|stop_id| Uuid::from_str(&stop_id.to_string()).unwrap()),
         entry: Uuid::from_str(&order.id.to_string()).unwrap(),
         target: Uuid::from_str(&target_id.to_string()).unwrap(),
     }
 }
 
-fn new_request(trade: &Trade) -> OrderReq {
+/// The entry is submitted with its target and its stop as a bracket. Alpaca does not accept a
+/// trailing stop in a bracket, so the entry of a trade with a trailing stop only carries its target
+/// and the trailing stop is submitted on its own once the entry is filled.
+fn new_request(trade: &Trade) -> Result<OrderReq, Box<dyn Error>> {
     let entry = Num::from_str(trade.entry.unit_price.to_string().as_str()).unwrap();
     let stop = Num::from_str(trade.safety_stop.unit_price.to_string().as_str()).unwrap();
     let target = Num::from_str(trade.target.unit_price.to_string().as_str()).unwrap();
 
-    OrderReqInit {
-        class: Class::Bracket,
+    let (class, stop_loss) = match trade.safety_stop.category {
+        OrderCategory::TrailingStop { .. } => (Class::OneTriggersOther, None),
+        _ => (Class::Bracket, Some(StopLoss::Stop(stop))),
+    };
+
+    Ok(OrderReqInit {
+        class,
         type_: Type::Limit,
         limit_price: Some(entry),
         take_profit: Some(TakeProfit::Limit(target)),
-        stop_loss: Some(StopLoss::Stop(stop)),
-        time_in_force: time_in_force(&trade.entry),
+        stop_loss,
+        time_in_force: time_in_force(&trade.entry)?,
         extended_hours: trade.entry.extended_hours,
         client_order_id: Some(trade.entry.id.to_string()),
         ..Default::default()
@@ -100,15 +126,53 @@ fn new_request(trade: &Trade) -> OrderReq {
         trade.trading_vehicle.symbol.to_uppercase(),
         side(trade),
         Amount::quantity(trade.entry.quantity),
+    ))
+}
+
+/// A trailing stop is submitted on its own once the entry is filled. Submitting it earlier would open
+/// a position in the opposite direction if it triggers before the entry is filled.
+/// It is canceled once the target is filled.
+fn trailing_stop_request(trade: &Trade) -> Option<OrderReq> {
+    let OrderCategory::TrailingStop {
+        trail_percent,
+        trail_price,
+    } = trade.safety_stop.category
+    else {
+        return None;
+    };
+    let num = |value: Decimal| Num::from_str(value.to_string().as_str()).unwrap();
+
+    Some(
+        OrderReqInit {
+            class: Class::Simple,
+            type_: Type::TrailingStop,
+            trail_percent: trail_percent.map(num),
+            trail_price: trail_price.map(num),
+            time_in_force: TimeInForce::UntilCanceled,
+            client_order_id: Some(trade.safety_stop.id.to_string()),
+            ..Default::default()
+        }
+        .init(
+            trade.trading_vehicle.symbol.to_uppercase(),
+            stop_side(trade),
+            Amount::quantity(trade.safety_stop.quantity),
+        ),
     )
 }
 
-fn time_in_force(entry: &Order) -> TimeInForce {
+/// The time in force of the entry in Alpaca. The entry is a bracket, where Alpaca does not accept
+/// orders that are canceled if they are not filled immediately.
+fn time_in_force(entry: &Order) -> Result<TimeInForce, Box<dyn Error>> {
     match entry.time_in_force {
-        model::TimeInForce::Day => TimeInForce::Day,
-        model::TimeInForce::UntilCanceled => TimeInForce::UntilCanceled,
-        model::TimeInForce::UntilMarketClose => TimeInForce::UntilMarketClose,
-        model::TimeInForce::UntilMarketOpen => TimeInForce::UntilMarketOpen,
+        model::TimeInForce::Day => Ok(TimeInForce::Day),
+        model::TimeInForce::UntilCanceled => Ok(TimeInForce::UntilCanceled),
+        model::TimeInForce::UntilMarketClose => Ok(TimeInForce::UntilMarketClose),
+        model::TimeInForce::UntilMarketOpen => Ok(TimeInForce::UntilMarketOpen),
+        model::TimeInForce::ImmediateOrCancel | model::TimeInForce::FillOrKill => Err(format!(
+            "Alpaca does not accept an entry with time in force {}",
+            entry.time_in_force
+        )
+        .into()),
     }
 }
 
@@ -119,6 +183,13 @@ pub fn side(trade: &Trade) -> Side {
     }
 }
 
+fn stop_side(trade: &Trade) -> Side {
+    match trade.category {
+        TradeCategory::Long => Side::Sell,
+        TradeCategory::Short => Side::Buy,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -235,7 +306,7 @@ mod tests {
         };
 
         // Call the new_request function with the sample trade object
-        let order_req = new_request(&trade);
+        let order_req = new_request(&trade).unwrap();
 
         // Check if the returned OrderReq object has the correct values
         assert_eq!(order_req.client_order_id, Some(trade.entry.id.to_string())); // The client_order_id should be the same as the entry order id.
@@ -259,10 +330,143 @@ mod tests {
         );
         assert_eq!(order_req.side, side(&trade));
         assert_eq!(order_req.amount, Amount::quantity(trade.entry.quantity));
-        assert_eq!(order_req.time_in_force, time_in_force(&trade.entry));
+        assert_eq!(order_req.time_in_force, TimeInForce::UntilCanceled);
         assert_eq!(order_req.extended_hours, trade.entry.extended_hours);
     }
 
+    #[test]
+    fn test_new_request_with_trailing_stop() {
+        let trade = Trade {
+            safety_stop: Order {
+                unit_price: dec!(38),
+                quantity: 100,
+                category: OrderCategory::TrailingStop {
+                    trail_percent: Some(dec!(2.5)),
+                    trail_price: None,
+                },
+                ..Default::default()
+            },
+            entry: Order {
+                unit_price: dec!(40),
+                quantity: 100,
+                ..Default::default()
+            },
+            target: Order {
+                unit_price: dec!(50),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // The entry only carries the target
+        let order_req = new_request(&trade).unwrap();
+        assert_eq!(order_req.class, Class::OneTriggersOther);
+        assert_eq!(
+            order_req.take_profit.unwrap(),
+            TakeProfit::Limit(Num::from_str("50").unwrap())
+        );
+        assert_eq!(order_req.stop_loss, None);
+
+        // The trailing stop is submitted on its own
+        let stop_req = trailing_stop_request(&trade).unwrap();
+        assert_eq!(
+            stop_req.client_order_id,
+            Some(trade.safety_stop.id.to_string())
+        );
+        assert_eq!(stop_req.class, Class::Simple);
+        assert_eq!(stop_req.type_, Type::TrailingStop);
+        assert_eq!(stop_req.trail_percent, Some(Num::from_str("2.5").unwrap()));
+        assert_eq!(stop_req.trail_price, None);
+        assert_eq!(stop_req.side, Side::Sell);
+        assert_eq!(stop_req.amount, Amount::quantity(100));
+        assert_eq!(stop_req.time_in_force, TimeInForce::UntilCanceled);
+
+        // A stop that does not trail is a leg of the bracket
+        let trade = Trade {
+            safety_stop: Order {
+                unit_price: dec!(38),
+                ..Default::default()
+            },
+            ..trade
+        };
+        assert!(trailing_stop_request(&trade).is_none());
+    }
+
+    #[test]
+    fn test_extract_ids_trailing_stop_is_not_submitted_with_the_entry() {
+        let mut entry = default();
+        entry.legs.retain(|leg| leg.type_ == Type::Limit);
+        let trade = Trade {
+            safety_stop: Order {
+                unit_price: dec!(12.52),
+                category: OrderCategory::TrailingStop {
+                    trail_percent: Some(dec!(2.5)),
+                    trail_price: None,
+                },
+                ..Default::default()
+            },
+            target: Order {
+                unit_price: dec!(12.58),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ids = extract_ids(&entry, &trade);
+
+        assert_eq!(ids.stop, None);
+        assert_eq!(
+            ids.target,
+            Uuid::parse_str("90e41b1e-9089-444d-9f68-c204a4d32914").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_time_in_force() {
+        let entry = |time_in_force| Order {
+            time_in_force,
+            ..Default::default()
+        };
+
+        for (tif, expected) in [
+            (model::TimeInForce::Day, TimeInForce::Day),
+            (
+                model::TimeInForce::UntilCanceled,
+                TimeInForce::UntilCanceled,
+            ),
+            (
+                model::TimeInForce::UntilMarketOpen,
+                TimeInForce::UntilMarketOpen,
+            ),
+            (
+                model::TimeInForce::UntilMarketClose,
+                TimeInForce::UntilMarketClose,
+            ),
+        ] {
+            assert_eq!(time_in_force(&entry(tif)).unwrap(), expected);
+        }
+        for tif in [
+            model::TimeInForce::ImmediateOrCancel,
+            model::TimeInForce::FillOrKill,
+        ] {
+            assert!(time_in_force(&entry(tif)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_new_request_with_day_entry() {
+        let trade = Trade {
+            entry: Order {
+                unit_price: dec!(40),
+                time_in_force: model::TimeInForce::Day,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(new_request(&trade).unwrap().time_in_force, TimeInForce::Day);
+    }
+
     #[test]
     fn test_extract_ids_stop_order() {
         // Create a sample AlpacaOrder with a Stop type
@@ -291,7 +495,7 @@ mod tests {
         // Check that the stop ID is correct and the target ID is a new UUID
         assert_eq!(
             result.stop,
-            Uuid::parse_str("8654f70e-3b42-4014-a9ac-5a7101989aad").unwrap()
+            Some(Uuid::parse_str("8654f70e-3b42-4014-a9ac-5a7101989aad").unwrap())
         );
         assert_eq!(
             result.entry,
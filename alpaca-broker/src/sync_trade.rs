@@ -1,9 +1,11 @@
+use crate::cancel_trade;
 use crate::keys;
 use crate::order_mapper;
+use crate::submit_trade;
 use apca::api::v2::order::Order as AlpacaOrder;
 use apca::api::v2::orders::{Get, OrdersReq, Status as AlpacaRequestStatus};
 use apca::Client;
-use model::{Account, BrokerLog, Order, Status, Trade};
+use model::{Account, BrokerLog, Order, OrderCategory, OrderStatus, Status, Trade};
 use std::error::Error;
 use tokio::runtime::Runtime;
 
@@ -26,7 +28,28 @@ pub fn sync(
         ..Default::default()
     };
 
-    let (status, updated_orders) = sync_trade(trade, orders)?;
+    let (status, mut updated_orders) = sync_trade(trade, orders)?;
+
+    // A trailing stop is submitted on its own once the entry is filled.
+    if trailing_stop_pending(trade, status, &updated_orders) {
+        let stop = submit_trade::submit_trailing_stop(trade, account).map_err(|error| {
+            format!(
+                "The entry of trade {} is filled but its trailing stop could not be submitted, the position is not protected: {}",
+                trade.id, error
+            )
+        })?;
+        updated_orders.retain(|updated| updated.id != trade.safety_stop.id);
+        updated_orders.extend(order_mapper::map_trailing_stop(stop, trade)?);
+    }
+
+    // A trailing stop is not linked to the target in Alpaca, so the exit that is still
+    // working is canceled once the other one closes the trade.
+    if let Some(mut order) = exit_to_cancel(trade, status, &updated_orders) {
+        cancel_trade::cancel_order(&order, account)?;
+        order.status = OrderStatus::Canceled;
+        updated_orders.retain(|updated| updated.id != order.id);
+        updated_orders.push(order);
+    }
     Ok((status, updated_orders, log))
 }
 
@@ -36,10 +59,19 @@ fn sync_trade(
     orders: Vec<AlpacaOrder>,
 ) -> Result<(Status, Vec<Order>), Box<dyn Error>> {
     let updated_orders = match trade.status {
-        Status::Canceled => {
+        Status::Canceled | Status::ClosingPending => {
             find_target(orders, trade).and_then(|order| order_mapper::map_target(order, trade))
         }
-        _ => find_entry(orders, trade).and_then(|order| order_mapper::map_entry(order, trade)),
+        _ => {
+            let mut updated_orders = find_entry(orders.clone(), trade)
+                .and_then(|order| order_mapper::map_entry(order, trade))?;
+            if is_trailing(&trade.safety_stop) {
+                if let Ok(stop) = find_stop(orders, trade) {
+                    updated_orders.extend(order_mapper::map_trailing_stop(stop, trade)?);
+                }
+            }
+            Ok(updated_orders)
+        }
     }?;
 
     let status = order_mapper::map_trade_status(trade, &updated_orders);
@@ -52,9 +84,14 @@ async fn get_closed_orders(
     client: &Client,
     trade: &Trade,
 ) -> Result<Vec<AlpacaOrder>, Box<dyn Error>> {
+    // A trailing stop is working on its own while the trade is open, so the open orders are read too.
+    let status = match is_trailing(&trade.safety_stop) {
+        true => AlpacaRequestStatus::All,
+        false => AlpacaRequestStatus::Closed,
+    };
     let request: OrdersReq = OrdersReq {
         symbols: vec![trade.trading_vehicle.symbol.to_string()],
-        status: AlpacaRequestStatus::Closed,
+        status,
         ..Default::default()
     };
 
@@ -70,6 +107,53 @@ pub fn find_entry(orders: Vec<AlpacaOrder>, trade: &Trade) -> Result<AlpacaOrder
         .ok_or_else(|| "Entry order not found, it can be that is not filled yet".into())
 }
 
+/// Find the trailing stop, that is submitted on its own with the id of the stop
+pub fn find_stop(orders: Vec<AlpacaOrder>, trade: &Trade) -> Result<AlpacaOrder, Box<dyn Error>> {
+    orders
+        .into_iter()
+        .find(|x| x.client_order_id == trade.safety_stop.id.to_string())
+        .ok_or_else(|| "Trailing stop order not found".into())
+}
+
+/// Whether the trailing stop of a trade has to be submitted: the entry is filled and the stop
+/// was not submitted yet.
+fn trailing_stop_pending(trade: &Trade, status: Status, updated_orders: &[Order]) -> bool {
+    let stop = updated_orders
+        .iter()
+        .find(|order| order.id == trade.safety_stop.id)
+        .unwrap_or(&trade.safety_stop);
+    is_trailing(stop) && status == Status::Filled && stop.broker_order_id.is_none()
+}
+
+/// The target or the trailing stop that is still working once the other one closed the trade.
+fn exit_to_cancel(trade: &Trade, status: Status, updated_orders: &[Order]) -> Option<Order> {
+    if !is_trailing(&trade.safety_stop) {
+        return None;
+    }
+    let exit = match status {
+        Status::ClosedTarget => &trade.safety_stop,
+        Status::ClosedStopLoss => &trade.target,
+        _ => return None,
+    };
+    let exit = updated_orders
+        .iter()
+        .find(|order| order.id == exit.id)
+        .unwrap_or(exit);
+    let working = exit.broker_order_id.is_some()
+        && !matches!(
+            exit.status,
+            OrderStatus::Filled
+                | OrderStatus::Canceled
+                | OrderStatus::Expired
+                | OrderStatus::Rejected
+        );
+    working.then(|| exit.clone())
+}
+
+fn is_trailing(order: &Order) -> bool {
+    matches!(order.category, OrderCategory::TrailingStop { .. })
+}
+
 /// Find the target order that is on the first level of the JSON
 pub fn find_target(orders: Vec<AlpacaOrder>, trade: &Trade) -> Result<AlpacaOrder, Box<dyn Error>> {
     orders
@@ -87,6 +171,7 @@ mod tests {
     use chrono::Utc;
     use num_decimal::Num;
     use rust_decimal_macros::dec;
+    use std::str::FromStr;
     use uuid::Uuid;
 
     fn default() -> AlpacaOrder {
@@ -454,6 +539,140 @@ mod tests {
         assert_eq!(updated_orders.len(), 3);
     }
 
+    #[test]
+    fn test_sync_trade_with_trailing_stop() {
+        let entry_broker_id = Uuid::new_v4();
+        let target_broker_id = Uuid::new_v4();
+        let stop_broker_id = Uuid::new_v4();
+        let trade = Trade {
+            entry: Order {
+                broker_order_id: Some(entry_broker_id),
+                unit_price: dec!(40),
+                ..Default::default()
+            },
+            target: Order {
+                broker_order_id: Some(target_broker_id),
+                unit_price: dec!(50),
+                ..Default::default()
+            },
+            safety_stop: Order {
+                broker_order_id: Some(stop_broker_id),
+                unit_price: dec!(38),
+                category: OrderCategory::TrailingStop {
+                    trail_percent: Some(dec!(2.5)),
+                    trail_price: None,
+                },
+                ..Default::default()
+            },
+            status: Status::Filled,
+            ..Default::default()
+        };
+
+        // The entry is filled and its target is held
+        let mut target = default();
+        target.id = Id(target_broker_id);
+        target.status = AlpacaStatus::Held;
+        target.limit_price = Some(Num::from(50));
+        let mut entry = default();
+        entry.id = Id(entry_broker_id);
+        entry.client_order_id = trade.entry.id.to_string();
+        entry.status = AlpacaStatus::Filled;
+        entry.filled_quantity = Num::from(10);
+        entry.legs = vec![target];
+
+        // The trailing stop is working and Alpaca moved it up after the price
+        let mut stop = default();
+        stop.id = Id(stop_broker_id);
+        stop.client_order_id = trade.safety_stop.id.to_string();
+        stop.type_ = Type::TrailingStop;
+        stop.trail_percent = Some(Num::from_str("2.5").unwrap());
+        stop.stop_price = Some(Num::from_str("41.34").unwrap());
+
+        let (status, updated_orders) = sync_trade(&trade, vec![stop, entry]).unwrap();
+
+        assert_eq!(status, Status::Filled);
+        let stop = updated_orders
+            .iter()
+            .find(|order| order.id == trade.safety_stop.id)
+            .expect("The trailing stop should be updated");
+        assert_eq!(stop.unit_price, dec!(41.34));
+        assert_eq!(stop.category, trade.safety_stop.category);
+        assert_eq!(exit_to_cancel(&trade, status, &updated_orders), None);
+
+        // Once the target is filled, the trailing stop is still working
+        let filled_target = Order {
+            status: OrderStatus::Filled,
+            ..trade.target.clone()
+        };
+        let exit = exit_to_cancel(&trade, Status::ClosedTarget, &[filled_target]).unwrap();
+        assert_eq!(exit.id, trade.safety_stop.id);
+    }
+
+    #[test]
+    fn test_trailing_stop_pending() {
+        let entry_broker_id = Uuid::new_v4();
+        let target_broker_id = Uuid::new_v4();
+        let trade = Trade {
+            entry: Order {
+                broker_order_id: Some(entry_broker_id),
+                unit_price: dec!(40),
+                ..Default::default()
+            },
+            target: Order {
+                broker_order_id: Some(target_broker_id),
+                unit_price: dec!(50),
+                ..Default::default()
+            },
+            safety_stop: Order {
+                unit_price: dec!(38),
+                category: OrderCategory::TrailingStop {
+                    trail_percent: Some(dec!(2.5)),
+                    trail_price: None,
+                },
+                ..Default::default()
+            },
+            status: Status::Submitted,
+            ..Default::default()
+        };
+        let entry = |status| {
+            let mut target = default();
+            target.id = Id(target_broker_id);
+            target.status = AlpacaStatus::Held;
+            let mut entry = default();
+            entry.id = Id(entry_broker_id);
+            entry.client_order_id = trade.entry.id.to_string();
+            entry.status = status;
+            entry.legs = vec![target];
+            entry
+        };
+
+        // No exit is submitted while the entry is not filled
+        let (status, updated_orders) = sync_trade(&trade, vec![entry(AlpacaStatus::New)]).unwrap();
+        assert_eq!(status, Status::Submitted);
+        assert!(!trailing_stop_pending(&trade, status, &updated_orders));
+
+        // The trailing stop is submitted once the entry is filled
+        let (status, updated_orders) =
+            sync_trade(&trade, vec![entry(AlpacaStatus::Filled)]).unwrap();
+        assert_eq!(status, Status::Filled);
+        assert!(trailing_stop_pending(&trade, status, &updated_orders));
+
+        // A trailing stop that was found in Alpaca is linked to its order and not submitted again
+        let stop_broker_id = Uuid::new_v4();
+        let mut stop = default();
+        stop.id = Id(stop_broker_id);
+        stop.client_order_id = trade.safety_stop.id.to_string();
+        stop.type_ = Type::TrailingStop;
+        let (status, updated_orders) =
+            sync_trade(&trade, vec![stop, entry(AlpacaStatus::Filled)]).unwrap();
+        let linked = updated_orders
+            .iter()
+            .find(|order| order.id == trade.safety_stop.id)
+            .unwrap();
+        assert_eq!(linked.broker_order_id, Some(stop_broker_id));
+        assert!(!trailing_stop_pending(&trade, status, &updated_orders));
+    }
+
     #[test]
     fn test_sync_trade_manually_closed() {
         let target_id = Uuid::parse_str("6a3a0ab0-8846-4369-b9f5-2351a316ae0f").unwrap();
@@ -0,0 +1,77 @@
+use apca::api::v2::account::{Account as AlpacaAccount, Get as GetAccount};
+use apca::api::v2::asset::{Asset, Get as GetAsset, Symbol};
+use apca::Client;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use tokio::runtime::Runtime;
+
+use model::{Account, BrokerValidation, Trade, TradeCategory};
+use std::error::Error;
+
+use crate::keys;
+
+/// Alpaca does not offer an endpoint to validate orders without placing them.
+/// Instead, the account and the asset are read to check the same conditions
+/// that make Alpaca reject an order. No order is created.
+pub fn validate(trade: &Trade, account: &Account) -> Result<BrokerValidation, Box<dyn Error>> {
+    assert!(trade.account_id == account.id); // Verify that the trade is for the account
+
+    let api_info = keys::read_api_key(&account.environment, account)?;
+    let client = Client::new(api_info);
+
+    let runtime = Runtime::new().unwrap();
+    let alpaca_account = runtime.block_on(read_account(&client))?;
+    let asset = runtime.block_on(read_asset(&client, trade))?;
+
+    Ok(validation(trade, &alpaca_account, &asset))
+}
+
+async fn read_account(client: &Client) -> Result<AlpacaAccount, Box<dyn Error>> {
+    let result = client.issue::<GetAccount>(&()).await;
+    match result {
+        Ok(account) => Ok(account),
+        Err(e) => {
+            eprintln!("Error reading account: {:?}", e);
+            Err(Box::new(e))
+        }
+    }
+}
+
+async fn read_asset(client: &Client, trade: &Trade) -> Result<Asset, Box<dyn Error>> {
+    let symbol = Symbol::Sym(trade.trading_vehicle.symbol.to_uppercase());
+    let result = client.issue::<GetAsset>(&symbol).await;
+    match result {
+        Ok(asset) => Ok(asset),
+        Err(e) => {
+            eprintln!("Error reading asset: {:?}", e);
+            Err(Box::new(e))
+        }
+    }
+}
+
+fn validation(trade: &Trade, account: &AlpacaAccount, asset: &Asset) -> BrokerValidation {
+    let buying_power_impact = trade.entry.unit_price * trade.entry.decimal_quantity();
+    let buying_power = Decimal::from_str(account.buying_power.to_string().as_str()).unwrap();
+
+    let mut rejections = Vec::new();
+    if account.trading_blocked {
+        rejections.push("Trading is blocked for the account".to_string());
+    }
+    if !asset.tradable {
+        rejections.push(format!("{} is not tradable", asset.symbol));
+    }
+    if trade.category == TradeCategory::Short && !asset.shortable {
+        rejections.push(format!("{} is not shortable", asset.symbol));
+    }
+    if buying_power_impact > buying_power {
+        rejections.push(format!(
+            "Insufficient buying power: the trade needs {} and the account has {}",
+            buying_power_impact, buying_power
+        ));
+    }
+
+    BrokerValidation {
+        buying_power_impact,
+        rejections,
+    }
+}
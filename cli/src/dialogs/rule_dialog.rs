@@ -102,8 +102,18 @@ impl RuleDialogBuilder {
             .name
             .expect("Did you forget to select the rule name first?");
 
+        if name == RuleName::OnePositionPerSymbolSide {
+            return self; // This rule does not have any risk.
+        }
+
+        let prompt = match name {
+            RuleName::MinRewardRiskRatio(_) => "Minimum reward/risk ratio",
+            RuleName::MaxConsecutiveLosses(_) => "Maximum consecutive losses",
+            _ => "% of risk",
+        };
+
         let risk = Input::with_theme(&ColorfulTheme::default())
-            .with_prompt("% of risk")
+            .with_prompt(prompt)
             .validate_with({
                 |input: &String| -> Result<(), &str> {
                     match input.parse::<f32>() {
@@ -127,6 +137,10 @@ impl RuleDialogBuilder {
         self.name = Some(match name {
             RuleName::RiskPerMonth(_) => RuleName::RiskPerMonth(risk),
             RuleName::RiskPerTrade(_) => RuleName::RiskPerTrade(risk),
+            RuleName::MaxPositionConcentration(_) => RuleName::MaxPositionConcentration(risk),
+            RuleName::OnePositionPerSymbolSide => RuleName::OnePositionPerSymbolSide,
+            RuleName::MinRewardRiskRatio(_) => RuleName::MinRewardRiskRatio(risk),
+            RuleName::MaxConsecutiveLosses(_) => RuleName::MaxConsecutiveLosses(risk as u32),
         });
         self
     }
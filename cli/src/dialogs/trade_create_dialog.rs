@@ -5,7 +5,7 @@ use crate::{
 };
 use core::TrustFacade;
 use dialoguer::{theme::ColorfulTheme, FuzzySelect, Input};
-use model::{Account, Currency, DraftTrade, Trade, TradeCategory, TradingVehicle};
+use model::{Account, Currency, DraftTradeBuilder, Trade, TradeCategory, TradingVehicle};
 use rust_decimal::Decimal;
 use std::error::Error;
 
@@ -42,20 +42,21 @@ impl TradeDialogBuilder {
             .clone()
             .expect("Did you forget to specify trading vehicle");
 
-        let draft = DraftTrade {
-            account: self.account.clone().unwrap(),
-            trading_vehicle,
-            quantity: self.quantity.unwrap(),
-            currency: self.currency.unwrap(),
-            category: self.category.unwrap(),
-        };
-
-        self.result = Some(trust.create_trade(
-            draft,
-            self.stop_price.unwrap(),
-            self.entry_price.unwrap(),
-            self.target_price.unwrap(),
-        ));
+        let draft = DraftTradeBuilder::new(self.account.clone().unwrap(), trading_vehicle)
+            .quantity(self.quantity.unwrap())
+            .currency(self.currency.unwrap())
+            .category(self.category.unwrap())
+            .build();
+
+        self.result = Some(match draft {
+            Ok(draft) => trust.create_trade(
+                draft,
+                self.stop_price.unwrap(),
+                self.entry_price.unwrap(),
+                self.target_price.unwrap(),
+            ),
+            Err(error) => Err(error),
+        });
         self
     }
 
@@ -33,7 +33,7 @@ impl FillTradeDialogBuilder {
         let fee = self
             .fee
             .expect("No fee found, did you forget to specify a fee?");
-        self.result = Some(trust.fill_trade(&trade, fee));
+        self.result = Some(trust.fill_trade(&trade, Some(fee)));
         self
     }
 
@@ -1,12 +1,11 @@
 use crate::views::{AccountBalanceView, TradeBalanceView, TradeView};
 use crate::{dialogs::AccountSearchDialog, views::TransactionView};
-use core::TrustFacade;
+use core::{FundedTrade, TrustFacade};
 use dialoguer::{theme::ColorfulTheme, FuzzySelect};
-use model::{Account, AccountBalance, Status, Trade, TradeBalance, Transaction};
+use model::{Account, Status, Trade};
 use std::error::Error;
 
-type TradeDialogApproverBuilderResult =
-    Option<Result<(Trade, Transaction, AccountBalance, TradeBalance), Box<dyn Error>>>;
+type TradeDialogApproverBuilderResult = Option<Result<FundedTrade, Box<dyn Error>>>;
 
 pub struct FundingDialogBuilder {
     account: Option<Account>,
@@ -34,7 +33,7 @@ impl FundingDialogBuilder {
             .result
             .expect("No result found, did you forget to call search?")
         {
-            Ok((trade, tx, account_balance, trade_balance)) => {
+            Ok((trade, tx, account_balance, trade_balance, warnings)) => {
                 let account = self.account.clone().unwrap().name;
 
                 println!("Trade approved:");
@@ -47,6 +46,10 @@ impl FundingDialogBuilder {
 
                 println!("Account balance after funding trade:");
                 AccountBalanceView::display(account_balance, account.as_str());
+
+                for warning in warnings {
+                    println!("Warning from rule {}: {}", warning.rule, warning.message);
+                }
             }
             Err(error) => println!("Error approving trade: {:?}", error),
         }
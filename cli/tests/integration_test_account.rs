@@ -1,13 +1,21 @@
-use core::TrustFacade;
+use chrono::{Datelike, NaiveDateTime, Utc};
+use core::analytics::{ConcentrationGroup, MetadataField};
+use core::{
+    ActivityKind, AdvisoryKind, ConfigDifference, TradesExport, TrustFacade, TRADES_EXPORT_VERSION,
+};
 use db_sqlite::SqliteDatabase;
-use model::Broker;
 use model::{
-    Account, BrokerLog, Currency, Order, OrderIds, RuleLevel, RuleName, Status, Trade,
+    Account, AccountType, BrokerLog, Currency, DraftTrade, ExecutionSide, ExecutionSource, Level,
+    LevelAdjustmentRules, LevelTrigger, Order, OrderCategory, OrderIds, RuleLevel, RuleName,
+    Status, TickSizePolicy, TimeInForce, Trade, TradeCategory, TradingVehicleCategory,
     TransactionCategory,
 };
+use model::{Broker, BrokerActivity, BrokerPosition, BrokerValidation, DatabaseFactory};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::error::Error;
+use std::thread::sleep;
+use std::time::Duration;
 use uuid::Uuid;
 
 fn create_trust() -> TrustFacade {
@@ -37,6 +45,53 @@ fn test_account_creation() {
     assert_eq!(accounts.len(), 1);
 }
 
+#[test]
+fn test_create_trading_vehicle_normalizes_symbol() {
+    let mut trust = create_trust();
+
+    let trading_vehicle = trust
+        .create_trading_vehicle(
+            " tsla ",
+            "US88160R1014",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
+        )
+        .unwrap();
+    assert_eq!(trading_vehicle.symbol, "TSLA");
+
+    let duplicated = trust
+        .create_trading_vehicle(
+            "TSLA",
+            "US88160R1014",
+            &TradingVehicleCategory::Stock,
+            "nasdaq",
+        )
+        .unwrap();
+    assert_eq!(duplicated.id, trading_vehicle.id);
+    assert_eq!(trust.search_trading_vehicles().unwrap().len(), 1);
+}
+
+#[test]
+fn test_create_trading_vehicle_with_invalid_input() {
+    let mut trust = create_trust();
+    let category = TradingVehicleCategory::Stock;
+
+    for (symbol, broker) in [
+        ("", "NASDAQ"),
+        ("   ", "NASDAQ"),
+        ("ABCDEFGHIJKLM", "NASDAQ"),
+        ("TS LA", "NASDAQ"),
+        ("TSLA$", "NASDAQ"),
+        ("TSLA", ""),
+        ("TSLA", "  "),
+    ] {
+        trust
+            .create_trading_vehicle(symbol, "US88160R1014", &category, broker)
+            .expect_err("The trading vehicle is not valid");
+    }
+    assert!(trust.search_trading_vehicles().unwrap().is_empty());
+}
+
 #[test]
 fn test_transactions() {
     let mut trust = create_trust();
@@ -72,6 +127,27 @@ fn test_transactions() {
     assert_eq!(balance.taxed, dec!(0));
 }
 
+#[test]
+fn test_transactions_without_positive_amount() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+
+    for (category, amount) in [
+        (TransactionCategory::Deposit, dec!(0)),
+        (TransactionCategory::Deposit, dec!(-100)),
+        (TransactionCategory::Withdrawal, dec!(0)),
+    ] {
+        let error = trust
+            .create_transaction(&account, &category, amount, &Currency::USD)
+            .expect_err("The amount of a deposit or a withdrawal must be positive");
+        assert!(error.to_string().contains("must be positive"));
+    }
+
+    // No transaction was created after the initial deposit
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(100000));
+}
+
 #[test]
 fn test_multiple_transactions() {
     let mut trust = create_trust();
@@ -195,61 +271,2532 @@ fn test_risk_rules() {
     assert_eq!(quantity, 500);
 }
 
-struct MockBroker;
-impl Broker for MockBroker {
-    fn submit_trade(
-        &self,
-        _trade: &Trade,
-        _account: &Account,
-    ) -> Result<(BrokerLog, OrderIds), Box<dyn Error>> {
-        unimplemented!()
-    }
+#[test]
+fn test_what_if_quantity() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
 
-    fn sync_trade(
-        &self,
-        _trade: &Trade,
-        _account: &Account,
-    ) -> Result<(Status, Vec<Order>, BrokerLog), Box<dyn Error>> {
-        unimplemented!()
-    }
+    // Without a risk per month rule there is no monthly budget
+    let plan = trust
+        .what_if_quantity(account.id, dec!(40), dec!(38), dec!(50), &Currency::USD)
+        .unwrap();
+    assert_eq!(plan.quantity, 2500); // All the capital: 100000 / 40
+    assert_eq!(plan.remaining_risk_budget, None);
 
-    fn close_trade(
-        &self,
-        _trade: &Trade,
-        _account: &Account,
-    ) -> Result<(Order, BrokerLog), Box<dyn Error>> {
-        unimplemented!()
+    trust
+        .create_rule(
+            &account,
+            &RuleName::RiskPerMonth(6.0),
+            "description",
+            &RuleLevel::Error,
+        )
+        .unwrap();
+    trust
+        .create_rule(
+            &account,
+            &RuleName::RiskPerTrade(2.0),
+            "description",
+            &RuleLevel::Error,
+        )
+        .unwrap();
+
+    let plan = trust
+        .what_if_quantity(account.id, dec!(40), dec!(38), dec!(50), &Currency::USD)
+        .unwrap();
+    assert_eq!(
+        plan.quantity,
+        trust
+            .calculate_maximum_quantity(account.id, dec!(40), dec!(38), &Currency::USD)
+            .unwrap()
+    );
+    assert_eq!(plan.quantity, 1000);
+    assert_eq!(plan.capital_required, dec!(40000));
+    assert_eq!(plan.risk, dec!(2000)); // 2% of 100000
+    assert_eq!(plan.reward_risk_ratio, dec!(5));
+    assert_eq!(plan.remaining_risk_budget, Some(dec!(4000))); // 6000 - 2000
+
+    // A short trade needs the capital to buy back at the stop
+    let plan = trust
+        .what_if_quantity(account.id, dec!(40), dec!(42), dec!(37), &Currency::USD)
+        .unwrap();
+    assert_eq!(
+        plan.capital_required,
+        dec!(42) * Decimal::from(plan.quantity)
+    );
+    assert_eq!(plan.reward_risk_ratio, dec!(1.5));
+
+    trust
+        .what_if_quantity(account.id, dec!(40), dec!(38), dec!(35), &Currency::USD)
+        .expect_err("The target is below the entry of a long trade");
+
+    // Nothing is persisted
+    assert!(trust
+        .search_trades(account.id, Status::New)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        trust
+            .risk_budget_remaining(account.id, &Currency::USD)
+            .unwrap(),
+        dec!(6000)
+    );
+}
+
+#[test]
+fn test_position_sizing_kelly_with_an_edge() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+
+    // Kelly of 55% of wins at 2:1 is 0.325, a quarter of it risks 8125 of 100000 at 2 per share
+    let quantity = trust
+        .position_sizing_kelly(
+            account.id,
+            dec!(0.55),
+            dec!(2),
+            dec!(0.25),
+            dec!(40),
+            dec!(38),
+            &Currency::USD,
+        )
+        .unwrap();
+    assert_eq!(quantity, 2500); // 4062 shares would need more than the 100000 available
+
+    let quantity = trust
+        .position_sizing_kelly(
+            account.id,
+            dec!(0.55),
+            dec!(2),
+            dec!(0.1),
+            dec!(40),
+            dec!(30),
+            &Currency::USD,
+        )
+        .unwrap();
+    assert_eq!(quantity, 325); // 3250 of risk at 10 per share
+
+    // The money of a funded trade is still part of the equity
+    let trade = create_new_trade(&mut trust, &account, "TSLA", 1000);
+    trust.fund_trade(&trade).unwrap();
+    let quantity = trust
+        .position_sizing_kelly(
+            account.id,
+            dec!(0.55),
+            dec!(2),
+            dec!(0.1),
+            dec!(40),
+            dec!(30),
+            &Currency::USD,
+        )
+        .unwrap();
+    assert_eq!(quantity, 325);
+
+    for (win_prob, reward_risk, fraction) in [
+        (dec!(0), dec!(2), dec!(0.25)),
+        (dec!(1), dec!(2), dec!(0.25)),
+        (dec!(0.55), dec!(0), dec!(0.25)),
+        (dec!(0.55), dec!(2), dec!(0)),
+        (dec!(0.55), dec!(2), dec!(1.5)),
+    ] {
+        trust
+            .position_sizing_kelly(
+                account.id,
+                win_prob,
+                reward_risk,
+                fraction,
+                dec!(40),
+                dec!(38),
+                &Currency::USD,
+            )
+            .expect_err("The inputs are out of range");
     }
+    trust
+        .position_sizing_kelly(
+            account.id,
+            dec!(0.55),
+            dec!(2),
+            dec!(0.25),
+            dec!(40),
+            dec!(40),
+            &Currency::USD,
+        )
+        .expect_err("A trade without risk per share can not be sized");
+}
 
-    fn cancel_trade(&self, trade: &Trade, account: &Account) -> Result<(), Box<dyn Error>> {
-        unimplemented!("Cancel trade: {:?} {:?}", trade, account)
+#[test]
+fn test_position_sizing_kelly_without_an_edge() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+
+    // Kelly of 30% of wins at 1:1 is -0.4
+    for win_prob in [dec!(0.3), dec!(0.5)] {
+        let quantity = trust
+            .position_sizing_kelly(
+                account.id,
+                win_prob,
+                dec!(1),
+                dec!(0.5),
+                dec!(40),
+                dec!(38),
+                &Currency::USD,
+            )
+            .unwrap();
+        assert_eq!(quantity, 0);
     }
+}
 
-    fn modify_stop(
-        &self,
-        trade: &Trade,
-        account: &Account,
-        new_stop_price: Decimal,
-    ) -> Result<Uuid, Box<dyn Error>> {
-        unimplemented!(
-            "Modify stop: {:?} {:?} {:?}",
-            trade,
-            account,
-            new_stop_price
+#[test]
+fn test_trade_confirmation_above_notional() {
+    let mut trust = create_trust();
+
+    let account = trust
+        .create_account(
+            "alpaca",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
         )
-    }
+        .unwrap();
+    let account = trust
+        .set_confirm_above_notional(&account, Some(dec!(10000)))
+        .unwrap();
+    let tv = trust
+        .create_trading_vehicle(
+            "TSLA",
+            "US88160R1014",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
+        )
+        .unwrap();
 
-    fn modify_target(
-        &self,
-        trade: &Trade,
-        account: &Account,
-        new_target_price: rust_decimal::Decimal,
-    ) -> Result<Uuid, Box<dyn Error>> {
-        unimplemented!(
-            "Modify target: {:?} {:?} {:?}",
-            trade,
-            account,
-            new_target_price
+    let draft = |quantity| DraftTrade {
+        account: account.clone(),
+        trading_vehicle: tv.clone(),
+        quantity,
+        quantity_decimal: None,
+        currency: Currency::USD,
+        category: TradeCategory::Long,
+        strategy_id: None,
+        reference: None,
+        thesis: None,
+        context: None,
+    };
+
+    // A trade below the threshold does not need any confirmation
+    trust
+        .create_trade(draft(100), dec!(38), dec!(40), dec!(50))
+        .expect("Trade below the threshold should be created");
+
+    // A trade above the threshold needs the confirmation token
+    trust
+        .create_trade(draft(500), dec!(38), dec!(40), dec!(50))
+        .expect_err("Trade above the threshold should require a confirmation");
+    let token = trust.trade_confirmation_token(&draft(500), dec!(40));
+    trust
+        .create_confirmed_trade(draft(500), dec!(38), dec!(40), dec!(50), &token)
+        .expect("Confirmed trade should be created");
+
+    let trades = trust.search_trades(account.id, Status::New).unwrap();
+    assert_eq!(trades.len(), 2);
+}
+
+#[test]
+fn test_trade_confirmation_uses_the_stored_threshold() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    let tv = trust
+        .create_trading_vehicle(
+            "TSLA",
+            "US88160R1014",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
         )
-    }
+        .unwrap();
+
+    // The draft keeps a copy of the account from before the threshold was set
+    trust
+        .set_confirm_above_notional(&account, Some(dec!(10000)))
+        .unwrap();
+    let draft = DraftTrade {
+        account: account.clone(),
+        trading_vehicle: tv,
+        quantity: 500,
+        quantity_decimal: None,
+        currency: Currency::USD,
+        category: TradeCategory::Long,
+        strategy_id: None,
+        reference: None,
+        thesis: None,
+        context: None,
+    };
+    assert_eq!(draft.account.confirm_above_notional, None);
+
+    let error = trust
+        .create_trade(draft, dec!(38), dec!(40), dec!(50))
+        .expect_err("The threshold of the stored account should be used");
+    assert_eq!(
+        error.to_string(),
+        "TradeValidationError: Trade notional 20000 is above the confirmation threshold 10000 of the account, the trade must be confirmed"
+    );
+}
+
+fn create_funded_account(trust: &mut TrustFacade) -> Account {
+    let account = trust
+        .create_account(
+            "alpaca",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(100000),
+            &Currency::USD,
+        )
+        .unwrap();
+    account
+}
+
+fn create_new_trade(
+    trust: &mut TrustFacade,
+    account: &Account,
+    symbol: &str,
+    quantity: i64,
+) -> Trade {
+    let tv = trust
+        .create_trading_vehicle(symbol, symbol, &TradingVehicleCategory::Stock, "NASDAQ")
+        .unwrap();
+    let draft = DraftTrade {
+        account: account.clone(),
+        trading_vehicle: tv,
+        quantity,
+        quantity_decimal: None,
+        currency: Currency::USD,
+        category: TradeCategory::Long,
+        strategy_id: None,
+        reference: None,
+        thesis: None,
+        context: None,
+    };
+    trust
+        .create_trade(draft, dec!(38), dec!(40), dec!(50))
+        .unwrap()
+}
+
+#[test]
+fn test_fund_first_position_above_max_concentration() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    trust
+        .create_rule(
+            &account,
+            &RuleName::MaxPositionConcentration(40.0),
+            "Diversify",
+            &RuleLevel::Error,
+        )
+        .unwrap();
+
+    // The first position is 100% of the capital in open trades
+    let trade = create_new_trade(&mut trust, &account, "TSLA", 100);
+    trust
+        .fund_trade(&trade)
+        .expect_err("First position should be above the max concentration");
+
+    let trades = trust.search_trades(account.id, Status::Funded).unwrap();
+    assert!(trades.is_empty());
+}
+
+#[test]
+fn test_fund_trade_with_warning_rule_breach() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    trust
+        .create_rule(
+            &account,
+            &RuleName::RiskPerTrade(1.0),
+            "Soft guidance",
+            &RuleLevel::Warning,
+        )
+        .unwrap();
+
+    // The trade risks 2000, above the 1000 of the rule
+    let trade = create_new_trade(&mut trust, &account, "TSLA", 1000);
+    let (_, _, _, _, warnings) = trust.fund_trade(&trade).unwrap();
+
+    let trades = trust.search_trades(account.id, Status::Funded).unwrap();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].rule, RuleName::RiskPerTrade(1.0));
+    assert_eq!(
+        warnings[0].message,
+        "Risk per trade exceeded for risk per trade rule, maximum that can be at risk is 1000.00, trade is attempting to risk 2000"
+    );
+}
+
+#[test]
+fn test_fund_trade_with_error_rule_breach() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    trust
+        .create_rule(
+            &account,
+            &RuleName::RiskPerTrade(1.0),
+            "Hard limit",
+            &RuleLevel::Error,
+        )
+        .unwrap();
+
+    let trade = create_new_trade(&mut trust, &account, "TSLA", 1000);
+    trust
+        .fund_trade(&trade)
+        .expect_err("The trade risks more than the rule allows");
+
+    let trades = trust.search_trades(account.id, Status::Funded).unwrap();
+    assert!(trades.is_empty());
+}
+
+#[test]
+fn test_account_activity_feed() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    sleep(Duration::from_millis(5));
+    let trade = create_new_trade(&mut trust, &account, "TSLA", 100);
+    sleep(Duration::from_millis(5));
+    trust.fund_trade(&trade).unwrap();
+    sleep(Duration::from_millis(5));
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(500),
+            &Currency::USD,
+        )
+        .unwrap();
+
+    // The newest activity comes first
+    let feed = trust.account_activity_feed(account.id, 10, None).unwrap();
+    let kinds: Vec<ActivityKind> = feed.iter().map(|item| item.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ActivityKind::Transaction(TransactionCategory::Deposit),
+            ActivityKind::Transaction(TransactionCategory::FundTrade(trade.id)),
+            ActivityKind::TradeStatus(Status::New),
+            ActivityKind::Transaction(TransactionCategory::Deposit),
+        ]
+    );
+    assert_eq!(feed[0].summary, "deposit of 500 USD");
+    assert_eq!(feed[2].summary, "The long trade of TSLA was created");
+    assert_eq!(feed[2].trade_id, Some(trade.id));
+
+    // The last item of a page is the cursor of the next one
+    let first = trust.account_activity_feed(account.id, 2, None).unwrap();
+    let second = trust
+        .account_activity_feed(account.id, 2, Some(first[1].timestamp))
+        .unwrap();
+    let third = trust
+        .account_activity_feed(account.id, 2, Some(second[1].timestamp))
+        .unwrap();
+    assert_eq!(first, feed[..2]);
+    assert_eq!(second, feed[2..]);
+    assert!(third.is_empty());
+}
+
+#[test]
+fn test_create_adjustment() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+
+    let authorization = trust.authorize_protected_mutation();
+    let (adjustment, balance) = trust
+        .create_adjustment(
+            &account,
+            dec!(150),
+            &Currency::USD,
+            "Missing interest",
+            &authorization,
+        )
+        .unwrap();
+    assert_eq!(
+        adjustment.category,
+        TransactionCategory::Adjustment {
+            reason: "Missing interest".to_string()
+        }
+    );
+    assert_eq!(balance.total_available, dec!(100150));
+
+    let authorization = trust.authorize_protected_mutation();
+    let (adjustment, balance) = trust
+        .create_adjustment(
+            &account,
+            dec!(-50),
+            &Currency::USD,
+            "Fee charged twice",
+            &authorization,
+        )
+        .unwrap();
+    assert_eq!(adjustment.amount, dec!(-50));
+    assert_eq!(balance.total_available, dec!(100100));
+    assert_eq!(balance.total_balance, dec!(100100));
+
+    // Adjustments are reported apart from deposits
+    let now = Utc::now().naive_utc();
+    let statement = trust
+        .account_statement(account.id, now.year(), now.month(), Some(&Currency::USD))
+        .unwrap();
+    assert_eq!(statement.deposits, dec!(100000));
+    assert_eq!(statement.adjustments, dec!(100));
+}
+
+#[test]
+fn test_account_statement_closes_at_the_stored_balance() {
+    let database = SqliteDatabase::new_in_memory();
+    let mut trust = TrustFacade::new(Box::new(database.clone()), Box::new(MockBroker));
+    let account = create_funded_account(&mut trust);
+
+    let now = Utc::now().naive_utc();
+    let statement = trust
+        .account_statement(account.id, now.year(), now.month(), Some(&Currency::USD))
+        .unwrap();
+    assert_eq!(statement.closing_balance, dec!(100000));
+
+    // A deposit that is written without calculating the balance of the account again
+    database
+        .transaction_write()
+        .create_transaction(
+            &account,
+            dec!(10),
+            &Currency::USD,
+            TransactionCategory::Deposit,
+        )
+        .unwrap();
+
+    let error = trust
+        .account_statement(account.id, now.year(), now.month(), Some(&Currency::USD))
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .ends_with("the closing balance 100010 is not the balance 100000 of the account"));
+}
+
+#[test]
+fn test_create_adjustment_requires_protected_mode() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+
+    // A token replaced by a newer authorization is not valid anymore
+    let replaced = trust.authorize_protected_mutation();
+    trust.authorize_protected_mutation();
+    trust
+        .create_adjustment(
+            &account,
+            dec!(150),
+            &Currency::USD,
+            "Missing interest",
+            &replaced,
+        )
+        .expect_err("The adjustment is not authorized");
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Adjustment {
+                reason: "Missing interest".to_string(),
+            },
+            dec!(150),
+            &Currency::USD,
+        )
+        .expect_err("Adjustments are not created as ordinary transactions");
+
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(100000));
+}
+
+#[test]
+fn test_protected_authorization_is_consumed_on_commit() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    let authorization = trust.authorize_protected_mutation();
+
+    // A failing mutation keeps the token
+    trust
+        .create_adjustment(&account, dec!(150), &Currency::USD, " ", &authorization)
+        .expect_err("An adjustment must have a reason");
+
+    // A succeeding mutation consumes it
+    trust
+        .create_adjustment(
+            &account,
+            dec!(150),
+            &Currency::USD,
+            "Missing interest",
+            &authorization,
+        )
+        .unwrap();
+
+    // A second mutation needs another authorization
+    trust
+        .create_adjustment(
+            &account,
+            dec!(150),
+            &Currency::USD,
+            "Missing interest",
+            &authorization,
+        )
+        .expect_err("The token was already used");
+
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(100150));
+}
+
+#[test]
+fn test_clone_account_rules() {
+    let mut trust = create_trust();
+    let source = create_funded_account(&mut trust);
+    let target = trust
+        .create_account(
+            "target",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    for name in [RuleName::RiskPerTrade(2.0), RuleName::RiskPerMonth(6.0)] {
+        trust
+            .create_rule(&source, &name, "Source rule", &RuleLevel::Error)
+            .unwrap();
+    }
+    let deactivated = trust
+        .create_rule(
+            &source,
+            &RuleName::MaxConsecutiveLosses(3),
+            "Deactivated rule",
+            &RuleLevel::Error,
+        )
+        .unwrap();
+    trust.deactivate_rule(&deactivated).unwrap();
+    trust
+        .create_rule(
+            &target,
+            &RuleName::RiskPerMonth(10.0),
+            "Target rule",
+            &RuleLevel::Warning,
+        )
+        .unwrap();
+
+    let report = trust.clone_account_rules(source.id, target.id).unwrap();
+
+    assert_eq!(report.created.len(), 1);
+    assert_eq!(report.created[0].name, RuleName::RiskPerTrade(2.0));
+    assert_eq!(report.created[0].account_id, target.id);
+    assert_eq!(report.created[0].description, "Source rule");
+    assert_eq!(report.skipped.len(), 1);
+    assert_eq!(report.skipped[0].name, RuleName::RiskPerMonth(6.0));
+
+    // The target keeps its own rule of the same type and the deactivated rule is not copied
+    let mut names: Vec<String> = trust
+        .search_rules(target.id)
+        .unwrap()
+        .iter()
+        .map(|rule| rule.name.to_string())
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec![
+            RuleName::RiskPerMonth(10.0).to_string(),
+            RuleName::RiskPerTrade(2.0).to_string()
+        ]
+    );
+}
+
+#[test]
+fn test_trail_stop() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    let trade = create_new_trade(&mut trust, &account, "TSLA", 100);
+
+    // The stop is 2 away from the entry of 40
+    trust
+        .trail_stop(&trade, Some(dec!(2.5)), Some(dec!(1)))
+        .expect_err("Only one of the percent and the price can be set");
+    trust
+        .trail_stop(&trade, None, None)
+        .expect_err("The percent or the price must be set");
+    trust
+        .trail_stop(&trade, None, Some(dec!(3)))
+        .expect_err("The trail can not give more room than the stop");
+
+    let trade = trust.trail_stop(&trade, Some(dec!(2.5)), None).unwrap();
+    let trailing = OrderCategory::TrailingStop {
+        trail_percent: Some(dec!(2.5)),
+        trail_price: None,
+    };
+    assert_eq!(trade.safety_stop.category, trailing);
+    assert_eq!(trade.safety_stop.unit_price, dec!(38));
+
+    // The stop keeps trailing once the trade is funded
+    trust.fund_trade(&trade).unwrap();
+    let trade = trust.search_trades(account.id, Status::Funded).unwrap()[0].clone();
+    assert_eq!(trade.safety_stop.category, trailing);
+    assert_eq!(trade.safety_stop.trailing_percent, Some(dec!(2.5)));
+}
+
+#[test]
+fn test_set_entry_time_in_force() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    let trade = create_new_trade(&mut trust, &account, "TSLA", 100);
+    assert_eq!(trade.entry.time_in_force, TimeInForce::UntilCanceled);
+
+    // The stop of the trade rests in the broker while the entry is working
+    for time_in_force in [TimeInForce::ImmediateOrCancel, TimeInForce::FillOrKill] {
+        trust
+            .set_entry_time_in_force(&trade, time_in_force)
+            .expect_err("The entry can not be canceled before the stop rests");
+    }
+
+    let trade = trust
+        .set_entry_time_in_force(&trade, TimeInForce::Day)
+        .unwrap();
+    assert_eq!(trade.entry.time_in_force, TimeInForce::Day);
+    assert_eq!(trade.safety_stop.time_in_force, TimeInForce::UntilCanceled);
+
+    // The time in force is kept once the trade is funded
+    trust.fund_trade(&trade).unwrap();
+    let trade = trust.search_trades(account.id, Status::Funded).unwrap()[0].clone();
+    assert_eq!(trade.entry.time_in_force, TimeInForce::Day);
+}
+
+#[test]
+fn test_withdraw_earnings_keeps_the_reserve_of_open_trades() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+
+    // 40000 in the trade and 2000 at risk between the entry and the stop
+    let trade = create_new_trade(&mut trust, &account, "TSLA", 1000);
+    trust.fund_trade(&trade).unwrap();
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(60000));
+
+    // The available capital covers it, but it would take the reserve
+    trust
+        .withdraw_earnings(account.id, dec!(59000), &Currency::USD)
+        .expect_err("Withdrawal should not take the reserve of the open trades");
+    trust
+        .withdraw_earnings(account.id, dec!(0), &Currency::USD)
+        .expect_err("Withdrawal should be positive");
+
+    let (transaction, balance) = trust
+        .withdraw_earnings(account.id, dec!(58000), &Currency::USD)
+        .unwrap();
+    assert_eq!(
+        transaction.category,
+        TransactionCategory::WithdrawalEarnings
+    );
+    assert_eq!(transaction.amount, dec!(58000));
+    assert_eq!(balance.total_available, dec!(2000));
+    assert_eq!(balance.total_balance, dec!(42000));
+
+    trust
+        .withdraw_earnings(account.id, dec!(1), &Currency::USD)
+        .expect_err("Nothing is left to withdraw");
+
+    // Undoing the withdrawal deposits the earnings back
+    let reversal = trust
+        .undo_last_transaction(account.id, &Currency::USD)
+        .unwrap();
+    assert_eq!(reversal.category, TransactionCategory::Deposit);
+    assert_eq!(reversal.reverses, Some(transaction.id));
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(60000));
+}
+
+#[test]
+fn test_get_account_transactions_of_every_currency() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    let (eur, _) = trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(10000),
+            &Currency::EUR,
+        )
+        .unwrap();
+
+    let transactions = trust.get_account_transactions(account.id, None).unwrap();
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(transactions[0].currency, Currency::USD);
+    assert_eq!(transactions[0].amount, dec!(100000));
+    assert_eq!(transactions[1], eur);
+
+    let transactions = trust
+        .get_account_transactions(account.id, Some(&Currency::EUR))
+        .unwrap();
+    assert_eq!(transactions, vec![eur]);
+    let transactions = trust
+        .get_account_transactions(account.id, Some(&Currency::USD))
+        .unwrap();
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].currency, Currency::USD);
+    assert!(trust
+        .get_account_transactions(account.id, Some(&Currency::BTC))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_account_base_currency() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(10000),
+            &Currency::EUR,
+        )
+        .unwrap();
+    assert_eq!(account.base_currency, Currency::USD);
+    assert_eq!(
+        trust.total_equity_in(account.id, None).unwrap(),
+        dec!(100000)
+    );
+
+    let account = trust
+        .set_account_base_currency(&account, &Currency::EUR)
+        .unwrap();
+    assert_eq!(account.base_currency, Currency::EUR);
+
+    // The calculations without a currency are in EUR
+    assert_eq!(
+        trust.total_equity_in(account.id, None).unwrap(),
+        dec!(10000)
+    );
+    assert_eq!(
+        trust
+            .total_equity_in(account.id, Some(&Currency::USD))
+            .unwrap(),
+        dec!(100000)
+    );
+    let now = Utc::now().naive_utc();
+    let statement = trust
+        .account_statement(account.id, now.year(), now.month(), None)
+        .unwrap();
+    assert_eq!(statement.currency, Currency::EUR);
+    assert_eq!(statement.deposits, dec!(10000));
+
+    // The balances are not changed
+    let balances = trust.search_all_balances(account.id).unwrap();
+    assert_eq!(balances.len(), 2);
+    assert_eq!(
+        trust
+            .search_balance(account.id, &Currency::USD)
+            .unwrap()
+            .total_balance,
+        dec!(100000)
+    );
+    assert_eq!(
+        trust
+            .search_balance(account.id, &Currency::EUR)
+            .unwrap()
+            .total_balance,
+        dec!(10000)
+    );
+}
+
+#[test]
+fn test_risk_per_month_is_enforced_per_currency() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(10000),
+            &Currency::EUR,
+        )
+        .unwrap();
+    for rule in [RuleName::RiskPerMonth(6.0), RuleName::RiskPerTrade(5.0)] {
+        trust
+            .create_rule(&account, &rule, "description", &RuleLevel::Error)
+            .unwrap();
+    }
+    let trade =
+        |trust: &mut TrustFacade, symbol: &str, currency: Currency, stop, entry, quantity| {
+            let trading_vehicle = trust
+                .create_trading_vehicle(symbol, symbol, &TradingVehicleCategory::Stock, "XETRA")
+                .unwrap();
+            let draft = DraftTrade {
+                account: account.clone(),
+                trading_vehicle,
+                quantity,
+                quantity_decimal: None,
+                currency,
+                category: TradeCategory::Long,
+                strategy_id: None,
+                reference: None,
+                thesis: None,
+                context: None,
+            };
+            let trade = trust
+                .create_trade(draft, stop, entry, entry * dec!(2))
+                .unwrap();
+            trust.fund_trade(&trade)
+        };
+
+    // The EUR budget is 600 and the first trade commits 400 of it
+    trade(&mut trust, "SAP", Currency::EUR, dec!(36), dec!(40), 100).unwrap();
+
+    // The second trade is below the risk per trade, but it does not fit in what is left of the EUR budget
+    let error = trade(&mut trust, "SIE", Currency::EUR, dec!(19), dec!(20), 250)
+        .expect_err("The EUR budget of this month is spent");
+    assert!(error
+        .to_string()
+        .contains("Risk per month exceeded for EUR"));
+
+    // The USD budget is 6000 and it was not touched by the EUR trades
+    assert_eq!(
+        trust
+            .risk_budget_remaining(account.id, &Currency::USD)
+            .unwrap(),
+        dec!(6000)
+    );
+    trade(&mut trust, "TSLA", Currency::USD, dec!(36), dec!(40), 1000).unwrap();
+    assert_eq!(
+        trust
+            .risk_budget_remaining(account.id, &Currency::USD)
+            .unwrap(),
+        dec!(2000)
+    );
+    assert_eq!(
+        trust
+            .risk_budget_remaining(account.id, &Currency::EUR)
+            .unwrap(),
+        dec!(200)
+    );
+}
+
+#[test]
+fn test_fund_diversified_position_below_max_concentration() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+
+    // Open four positions of 4000 each before creating the rule
+    for symbol in ["AAPL", "MSFT", "TSLA", "NVDA"] {
+        let trade = create_new_trade(&mut trust, &account, symbol, 100);
+        trust.fund_trade(&trade).unwrap();
+    }
+
+    trust
+        .create_rule(
+            &account,
+            &RuleName::MaxPositionConcentration(40.0),
+            "Diversify",
+            &RuleLevel::Error,
+        )
+        .unwrap();
+
+    // AMZN would be 4000 of 20000 in open trades (20%)
+    let trade = create_new_trade(&mut trust, &account, "AMZN", 100);
+    trust
+        .fund_trade(&trade)
+        .expect("Diversified position should be funded");
+
+    // GOOG would be 12000 of 32000 in open trades (37.5%)
+    let trade = create_new_trade(&mut trust, &account, "GOOG", 300);
+    trust
+        .fund_trade(&trade)
+        .expect("Position below the max concentration should be funded");
+
+    // META would be 40000 of 72000 in open trades (55.5%)
+    let trade = create_new_trade(&mut trust, &account, "META", 1000);
+    trust
+        .fund_trade(&trade)
+        .expect_err("Concentrated position should not be funded");
+
+    let trades = trust.search_trades(account.id, Status::Funded).unwrap();
+    assert_eq!(trades.len(), 6);
+}
+
+#[test]
+fn test_max_additional_quantity() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+
+    // Open four positions of 4000 each before creating the rule
+    let mut trades = Vec::new();
+    for symbol in ["AAPL", "MSFT", "TSLA", "NVDA"] {
+        let trade = create_new_trade(&mut trust, &account, symbol, 100);
+        trust.fund_trade(&trade).unwrap();
+        trades.push(trade);
+    }
+    let add_to_aapl = |quantity| DraftTrade {
+        account: account.clone(),
+        trading_vehicle: trades[0].trading_vehicle.clone(),
+        quantity,
+        quantity_decimal: None,
+        currency: Currency::USD,
+        category: TradeCategory::Long,
+        strategy_id: None,
+        reference: None,
+        thesis: None,
+        context: None,
+    };
+
+    // Only the capital limits the quantity: 84000 / 40
+    assert_eq!(
+        trust
+            .max_additional_quantity(account.id, "AAPL", dec!(40), dec!(38), &Currency::USD)
+            .unwrap(),
+        2100
+    );
+
+    trust
+        .create_rule(
+            &account,
+            &RuleName::MaxPositionConcentration(40.0),
+            "Diversify",
+            &RuleLevel::Error,
+        )
+        .unwrap();
+
+    // AAPL already has 4000 of the 16000 in open trades
+    let aapl = trust
+        .max_additional_quantity(account.id, "AAPL", dec!(40), dec!(38), &Currency::USD)
+        .unwrap();
+    let amzn = trust
+        .max_additional_quantity(account.id, "AMZN", dec!(40), dec!(38), &Currency::USD)
+        .unwrap();
+    assert_eq!(aapl, 100);
+    assert_eq!(amzn, 266);
+
+    // The quantity can be funded, one more share breaches the rule
+    let trade = trust
+        .create_trade(add_to_aapl(aapl + 1), dec!(38), dec!(40), dec!(50))
+        .unwrap();
+    trust
+        .fund_trade(&trade)
+        .expect_err("The position would be above the max concentration");
+    let trade = trust
+        .create_trade(add_to_aapl(aapl), dec!(38), dec!(40), dec!(50))
+        .unwrap();
+    trust.fund_trade(&trade).unwrap();
+    assert_eq!(
+        trust
+            .max_additional_quantity(account.id, "AAPL", dec!(40), dec!(38), &Currency::USD)
+            .unwrap(),
+        0
+    );
+
+    trust
+        .create_rule(
+            &account,
+            &RuleName::OnePositionPerSymbolSide,
+            "Do not double a bet",
+            &RuleLevel::Error,
+        )
+        .unwrap();
+    assert_eq!(
+        trust
+            .max_additional_quantity(account.id, "MSFT", dec!(40), dec!(38), &Currency::USD)
+            .unwrap(),
+        0
+    );
+}
+
+#[test]
+fn test_fund_one_position_per_symbol_side() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    trust
+        .create_rule(
+            &account,
+            &RuleName::OnePositionPerSymbolSide,
+            "Do not double a bet",
+            &RuleLevel::Error,
+        )
+        .unwrap();
+    let tv = trust
+        .create_trading_vehicle(
+            "AAPL",
+            "US0378331005",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
+        )
+        .unwrap();
+    let draft = |category| DraftTrade {
+        account: account.clone(),
+        trading_vehicle: tv.clone(),
+        quantity: 100,
+        quantity_decimal: None,
+        currency: Currency::USD,
+        category,
+        strategy_id: None,
+        reference: None,
+        thesis: None,
+        context: None,
+    };
+
+    // The first long in AAPL is funded
+    let trade = trust
+        .create_trade(draft(TradeCategory::Long), dec!(38), dec!(40), dec!(50))
+        .unwrap();
+    trust.fund_trade(&trade).unwrap();
+
+    // A second long in AAPL is rejected
+    let trade = trust
+        .create_trade(draft(TradeCategory::Long), dec!(38), dec!(40), dec!(50))
+        .unwrap();
+    trust
+        .fund_trade(&trade)
+        .expect_err("A second long in the same symbol should not be funded");
+
+    // A short in AAPL hedges the long, so it is funded
+    let trade = trust
+        .create_trade(draft(TradeCategory::Short), dec!(42), dec!(40), dec!(30))
+        .unwrap();
+    trust
+        .fund_trade(&trade)
+        .expect("A short in the same symbol should be funded");
+
+    let trades = trust.search_trades(account.id, Status::Funded).unwrap();
+    assert_eq!(trades.len(), 2);
+}
+
+#[test]
+fn test_equity_snapshots() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Withdrawal,
+            dec!(25000),
+            &Currency::USD,
+        )
+        .unwrap();
+
+    // A withdrawal is not a loss, it only changes the capital of an account that did not trade yet
+    let snapshots = trust.rebuild_equity_snapshots(account.id).unwrap();
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].balance, dec!(75000));
+
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(5000),
+            &Currency::USD,
+        )
+        .unwrap();
+    let drawdown = trust.realized_drawdown(account.id, &Currency::USD).unwrap();
+    assert_eq!(drawdown.peak, dec!(80000));
+    assert_eq!(drawdown.max_drawdown, dec!(0));
+    assert_eq!(drawdown.current_drawdown, dec!(0));
+
+    // Rebuilding replaces the previous snapshots
+    let snapshots = trust.rebuild_equity_snapshots(account.id).unwrap();
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(
+        trust.realized_drawdown(account.id, &Currency::USD).unwrap(),
+        drawdown
+    );
+}
+
+#[test]
+fn test_risk_budget_remaining() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+
+    trust
+        .risk_budget_remaining(account.id, &Currency::USD)
+        .expect_err("Risk budget requires a risk per month rule");
+
+    trust
+        .create_rule(
+            &account,
+            &RuleName::RiskPerMonth(6.0),
+            "description",
+            &RuleLevel::Error,
+        )
+        .unwrap();
+    let budget = trust
+        .risk_budget_remaining(account.id, &Currency::USD)
+        .unwrap();
+    assert_eq!(budget, dec!(6000)); // 6% of 100000
+
+    // The open trade risks (40 - 38) * 100
+    let trade = create_new_trade(&mut trust, &account, "TSLA", 100);
+    trust.fund_trade(&trade).unwrap();
+    let budget = trust
+        .risk_budget_remaining(account.id, &Currency::USD)
+        .unwrap();
+    assert_eq!(budget, dec!(5800));
+}
+
+#[test]
+fn test_trade_quote_currency() {
+    let mut trust = create_trust();
+    let account = trust
+        .create_account(
+            "alpaca",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    let tv = trust
+        .create_trading_vehicle(
+            "SAP",
+            "DE0007164600",
+            &TradingVehicleCategory::Stock,
+            "XETRA",
+        )
+        .unwrap();
+    let tv = trust.set_quote_currency(&tv, Some(Currency::EUR)).unwrap();
+    assert_eq!(tv.quote_currency, Some(Currency::EUR));
+
+    let draft = |currency| DraftTrade {
+        account: account.clone(),
+        trading_vehicle: tv.clone(),
+        quantity: 10,
+        quantity_decimal: None,
+        currency,
+        category: TradeCategory::Long,
+        strategy_id: None,
+        reference: None,
+        thesis: None,
+        context: None,
+    };
+
+    trust
+        .create_trade(draft(Currency::USD), dec!(95), dec!(100), dec!(120))
+        .expect_err("A EUR trading vehicle can not be traded in USD");
+    trust
+        .create_trade(draft(Currency::EUR), dec!(95), dec!(100), dec!(120))
+        .expect("A EUR trading vehicle can be traded in EUR");
+
+    let trades = trust.search_trades(account.id, Status::New).unwrap();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(
+        trades[0].trading_vehicle.quote_currency,
+        Some(Currency::EUR)
+    );
+}
+
+#[test]
+fn test_create_trade_min_reward_risk_ratio() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    trust
+        .create_rule(
+            &account,
+            &RuleName::MinRewardRiskRatio(2.0),
+            "Never take trades below 2:1",
+            &RuleLevel::Error,
+        )
+        .unwrap();
+    let tv = trust
+        .create_trading_vehicle(
+            "AAPL",
+            "US0378331005",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
+        )
+        .unwrap();
+    let draft = || DraftTrade {
+        account: account.clone(),
+        trading_vehicle: tv.clone(),
+        quantity: 100,
+        quantity_decimal: None,
+        currency: Currency::USD,
+        category: TradeCategory::Long,
+        strategy_id: None,
+        reference: None,
+        thesis: None,
+        context: None,
+    };
+
+    // Risk 2 and reward 3
+    let error = trust
+        .create_trade(draft(), dec!(38), dec!(40), dec!(43))
+        .expect_err("A 1.5:1 trade is below the minimum ratio");
+    assert!(error.to_string().contains("1.5:1"));
+
+    // Risk 2 and reward 6
+    trust
+        .create_trade(draft(), dec!(38), dec!(40), dec!(46))
+        .expect("A 3:1 trade meets the minimum ratio");
+    assert_eq!(
+        trust.search_trades(account.id, Status::New).unwrap().len(),
+        1
+    );
+}
+
+#[test]
+fn test_trade_tick_size() {
+    let mut trust = create_trust();
+    let account = trust
+        .create_account(
+            "alpaca",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    let tv = trust
+        .create_trading_vehicle(
+            "TSLA",
+            "US88160R1014",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
+        )
+        .unwrap();
+    let tv = trust.set_tick_size(&tv, Some(dec!(0.05))).unwrap();
+    assert_eq!(tv.tick_size, Some(dec!(0.05)));
+
+    let draft = |account: &Account| DraftTrade {
+        account: account.clone(),
+        trading_vehicle: tv.clone(),
+        quantity: 10,
+        quantity_decimal: None,
+        currency: Currency::USD,
+        category: TradeCategory::Long,
+        strategy_id: None,
+        reference: None,
+        thesis: None,
+        context: None,
+    };
+
+    trust
+        .create_trade(draft(&account), dec!(38), dec!(40), dec!(50.03))
+        .expect_err("A target off the tick grid can never be filled");
+    trust
+        .create_trade(draft(&account), dec!(38), dec!(40), dec!(50.05))
+        .expect("A target on the tick grid is accepted");
+
+    let account = trust
+        .set_tick_size_policy(&account, TickSizePolicy::Round)
+        .unwrap();
+    assert_eq!(account.tick_size_policy, TickSizePolicy::Round);
+    trust
+        .create_trade(draft(&account), dec!(38.01), dec!(40), dec!(50.03))
+        .expect("Prices off the tick grid are rounded");
+
+    let trades = trust.search_trades(account.id, Status::New).unwrap();
+    assert_eq!(trades.len(), 2);
+    for trade in trades {
+        assert_eq!(trade.safety_stop.unit_price, dec!(38));
+        assert_eq!(trade.target.unit_price, dec!(50.05));
+    }
+}
+
+#[test]
+fn test_fund_fractional_crypto_trade() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    let tv = trust
+        .create_trading_vehicle("BTC", "BTC", &TradingVehicleCategory::Crypto, "alpaca")
+        .unwrap();
+    let draft = DraftTrade {
+        account: account.clone(),
+        trading_vehicle: tv,
+        quantity: 0,
+        quantity_decimal: Some(dec!(0.25)),
+        currency: Currency::USD,
+        category: TradeCategory::Long,
+        strategy_id: None,
+        reference: None,
+        thesis: None,
+        context: None,
+    };
+    let trade = trust
+        .create_trade(draft, dec!(29000.5), dec!(30123.45), dec!(35000))
+        .unwrap();
+    assert_eq!(trade.entry.quantity_decimal, Some(dec!(0.25)));
+
+    let (trade, tx, balance, trade_balance, _) = trust.fund_trade(&trade).unwrap();
+
+    // 30123.45 * 0.25
+    assert_eq!(trade.status, Status::Funded);
+    assert_eq!(tx.amount, dec!(7530.8625));
+    assert_eq!(trade_balance.funding, dec!(7530.8625));
+    assert_eq!(balance.total_available, dec!(92469.1375));
+    assert_eq!(balance.total_in_trade, dec!(7530.8625));
+}
+
+#[test]
+fn test_concentration_timeseries() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    let trade = create_new_trade(&mut trust, &account, "AAPL", 100);
+    trust.fund_trade(&trade).unwrap();
+
+    let series = trust
+        .concentration_timeseries(account.id, MetadataField::AssetClass, 5)
+        .unwrap();
+
+    assert_eq!(series.len(), 5);
+    assert_eq!(series[4].0, Utc::now().date_naive());
+    assert!(series[..4].iter().all(|(_, groups)| groups.is_empty()));
+    assert_eq!(
+        series[4].1,
+        vec![ConcentrationGroup {
+            name: "stock".to_string(),
+            notional: dec!(4000),
+            percentage: dec!(100),
+        }]
+    );
+}
+
+#[test]
+fn test_search_trades_paged() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+
+    let before_all = Utc::now().naive_utc();
+    let first = create_new_trade(&mut trust, &account, "AAPL", 1);
+    sleep(Duration::from_millis(5));
+    let second = create_new_trade(&mut trust, &account, "TSLA", 1);
+    sleep(Duration::from_millis(5));
+    let middle = Utc::now().naive_utc();
+    let third = create_new_trade(&mut trust, &account, "NVDA", 1);
+    let after_all = Utc::now().naive_utc();
+
+    let (page, total) = trust
+        .search_trades_paged(account.id, Status::New, 2, 0, None, None)
+        .unwrap();
+    assert_eq!(total, 3);
+    assert_eq!(page, vec![first.clone(), second.clone()]);
+
+    let (page, total) = trust
+        .search_trades_paged(account.id, Status::New, 2, 2, None, None)
+        .unwrap();
+    assert_eq!(total, 3);
+    assert_eq!(page, vec![third.clone()]);
+
+    let (page, total) = trust
+        .search_trades_paged(account.id, Status::New, 2, 3, None, None)
+        .unwrap();
+    assert_eq!(total, 3);
+    assert!(page.is_empty());
+
+    // The date filters exclude the trades out of the range
+    let (page, total) = trust
+        .search_trades_paged(account.id, Status::New, 10, 0, Some(middle), None)
+        .unwrap();
+    assert_eq!(total, 1);
+    assert_eq!(page, vec![third]);
+
+    let (page, total) = trust
+        .search_trades_paged(
+            account.id,
+            Status::New,
+            10,
+            0,
+            Some(before_all),
+            Some(middle),
+        )
+        .unwrap();
+    assert_eq!(total, 2);
+    assert_eq!(page, vec![first, second]);
+
+    let (page, total) = trust
+        .search_trades_paged(account.id, Status::New, 10, 0, Some(after_all), None)
+        .unwrap();
+    assert_eq!(total, 0);
+    assert!(page.is_empty());
+}
+
+#[test]
+fn test_search_trades_by_symbol() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    let tsla = trust
+        .create_trading_vehicle(
+            "TSLA",
+            "US88160R1014",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
+        )
+        .unwrap();
+    let create = |trust: &mut TrustFacade, trading_vehicle: &model::TradingVehicle| {
+        sleep(Duration::from_millis(5));
+        let draft = DraftTrade {
+            account: account.clone(),
+            trading_vehicle: trading_vehicle.clone(),
+            quantity: 10,
+            quantity_decimal: None,
+            currency: Currency::USD,
+            category: TradeCategory::Long,
+            strategy_id: None,
+            reference: None,
+            thesis: None,
+            context: None,
+        };
+        trust
+            .create_trade(draft, dec!(38), dec!(40), dec!(50))
+            .unwrap()
+    };
+
+    let canceled = create(&mut trust, &tsla);
+    trust.fund_trade(&canceled).unwrap();
+    let funded_trade = trust.search_trades(account.id, Status::Funded).unwrap()[0].clone();
+    trust.cancel_funded_trade(&funded_trade).unwrap();
+    let funded = create(&mut trust, &tsla);
+    trust.fund_trade(&funded).unwrap();
+    let new = create(&mut trust, &tsla);
+    let aapl = create_new_trade(&mut trust, &account, "AAPL", 10);
+
+    // Every status, from the newest to the oldest
+    let trades = trust
+        .search_trades_by_symbol(account.id, " tsla ", &[])
+        .unwrap();
+    assert_eq!(
+        trades.iter().map(|trade| trade.id).collect::<Vec<Uuid>>(),
+        vec![new.id, funded.id, canceled.id]
+    );
+    assert_eq!(trades[1].status, Status::Funded);
+    assert_eq!(trades[2].status, Status::Canceled);
+
+    // Only the statuses that are given
+    let trades = trust
+        .search_trades_by_symbol(account.id, "TSLA", &[Status::New, Status::Canceled])
+        .unwrap();
+    assert_eq!(
+        trades.iter().map(|trade| trade.id).collect::<Vec<Uuid>>(),
+        vec![new.id, canceled.id]
+    );
+
+    let trades = trust
+        .search_trades_by_symbol(account.id, "Aapl", &[])
+        .unwrap();
+    assert_eq!(trades, vec![aapl]);
+    assert!(trust
+        .search_trades_by_symbol(account.id, "NVDA", &[])
+        .unwrap()
+        .is_empty());
+    assert!(trust
+        .search_trades_by_symbol(Uuid::new_v4(), "TSLA", &[])
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_fundable_trades() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+
+    // The first trade uses 80000 of the 100000 deposited
+    let funded = create_new_trade(&mut trust, &account, "AAPL", 2000);
+    trust.fund_trade(&funded).unwrap();
+
+    let fits = create_new_trade(&mut trust, &account, "TSLA", 500); // 20000
+    let too_big = create_new_trade(&mut trust, &account, "NVDA", 501); // 20040
+
+    let fundable = trust.fundable_trades(account.id).unwrap();
+
+    assert_eq!(fundable.len(), 2);
+    let result = |id| {
+        fundable
+            .iter()
+            .find(|(trade_id, _, _)| *trade_id == id)
+            .unwrap()
+    };
+    assert_eq!(result(fits.id), &(fits.id, true, None));
+
+    let (_, can_fund, reason) = result(too_big.id);
+    assert!(!can_fund);
+    assert!(reason.as_ref().unwrap().contains("Not enough funds"));
+
+    // Nothing was funded
+    let trades = trust.search_trades(account.id, Status::New).unwrap();
+    assert_eq!(trades.len(), 2);
+}
+
+#[test]
+fn test_undo_last_transaction_reverses_deposit() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    let before = trust.search_balance(account.id, &Currency::USD).unwrap();
+
+    let (deposit, _) = trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(5000),
+            &Currency::USD,
+        )
+        .unwrap();
+
+    let reversal = trust
+        .undo_last_transaction(account.id, &Currency::USD)
+        .unwrap();
+
+    assert_eq!(reversal.category, TransactionCategory::Withdrawal);
+    assert_eq!(reversal.amount, dec!(5000));
+    assert_eq!(reversal.reverses, Some(deposit.id));
+
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_balance, before.total_balance);
+    assert_eq!(balance.total_available, before.total_available);
+
+    // The next undo reverses the first deposit, not the reversal
+    let reversal = trust
+        .undo_last_transaction(account.id, &Currency::USD)
+        .unwrap();
+    assert_ne!(reversal.reverses, Some(deposit.id));
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_balance, dec!(0));
+}
+
+#[test]
+fn test_undo_last_transaction_refuses_trade_funding() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    let trade = create_new_trade(&mut trust, &account, "AAPL", 100);
+    trust.fund_trade(&trade).unwrap();
+
+    let error = trust
+        .undo_last_transaction(account.id, &Currency::USD)
+        .expect_err("The funding of an open trade can not be undone");
+    assert!(error.to_string().contains("still open"));
+
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(96000));
+    assert_eq!(balance.total_in_trade, dec!(4000));
+}
+
+fn create_unfunded_account(trust: &mut TrustFacade) -> Account {
+    trust
+        .create_account(
+            "alpaca",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap()
+}
+
+#[test]
+fn test_import_transactions_csv() {
+    let mut trust = create_trust();
+    let account = create_unfunded_account(&mut trust);
+    let csv = "date,category,amount,currency
+2023-03-01,withdrawal,2500,USD
+2023-01-15,deposit,10000,USD
+
+2023-02-01 09:30:00,dividend,120.50,USD
+";
+
+    let report = trust
+        .import_transactions_csv(account.id, csv.as_bytes(), false)
+        .unwrap();
+
+    assert!(report.rejected.is_empty());
+    let categories: Vec<TransactionCategory> = report
+        .accepted
+        .iter()
+        .map(|transaction| transaction.category.clone())
+        .collect();
+    assert_eq!(
+        categories,
+        vec![
+            TransactionCategory::Deposit,
+            TransactionCategory::Dividend(None),
+            TransactionCategory::Withdrawal,
+        ]
+    );
+    assert_eq!(
+        report.accepted[1].created_at,
+        NaiveDateTime::parse_from_str("2023-02-01 09:30:00", "%Y-%m-%d %H:%M:%S").unwrap()
+    );
+
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_balance, dec!(7620.50));
+    assert_eq!(balance.total_available, dec!(7620.50));
+}
+
+#[test]
+fn test_import_transactions_csv_rejects_interleaved_overdraft() {
+    let mut trust = create_trust();
+    let account = create_unfunded_account(&mut trust);
+    let csv = "date,category,amount,currency
+2023-01-01,deposit,1000,USD
+2023-01-02,withdrawal,1500,USD
+2023-01-03,deposit,2000,USD
+2023-01-04,withdrawal,1500,USD
+2023-01-05,fee,10,USD
+";
+
+    let report = trust
+        .import_transactions_csv(account.id, csv.as_bytes(), false)
+        .unwrap();
+
+    assert_eq!(report.accepted.len(), 3);
+    assert_eq!(
+        report
+            .rejected
+            .iter()
+            .map(|(line, _)| *line)
+            .collect::<Vec<usize>>(),
+        vec![3, 6]
+    );
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_balance, dec!(1500));
+}
+
+#[test]
+fn test_import_transactions_csv_strict_writes_nothing() {
+    let mut trust = create_trust();
+    let account = create_unfunded_account(&mut trust);
+    let csv = "date,category,amount,currency
+2023-01-01,deposit,1000,USD
+2023-01-02,withdrawal,1500,USD
+2023-01-03,deposit,2000,USD
+";
+
+    let error = trust
+        .import_transactions_csv(account.id, csv.as_bytes(), true)
+        .expect_err("The overdraft aborts a strict import");
+    assert!(error.to_string().starts_with("Line 3"));
+
+    // Not even the deposit before the overdraft was written
+    assert!(trust.search_balance(account.id, &Currency::USD).is_err());
+}
+
+#[test]
+fn test_backfill_executions() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    let trade = create_new_trade(&mut trust, &account, "AAPL", 150);
+    let executed_at = Utc::now().naive_utc();
+
+    trust
+        .backfill_execution(
+            trade.id,
+            ExecutionSide::Buy,
+            dec!(40),
+            100,
+            executed_at,
+            ExecutionSource::Manual,
+        )
+        .unwrap();
+    let execution = trust
+        .backfill_execution(
+            trade.id,
+            ExecutionSide::Buy,
+            dec!(43),
+            50,
+            executed_at,
+            ExecutionSource::Manual,
+        )
+        .unwrap();
+    assert_eq!(execution.source, ExecutionSource::Manual);
+    assert_eq!(execution.order_id, trade.entry.id);
+
+    // (100 * 40 + 50 * 43) / 150
+    let orders = trust.trade_orders(trade.id).unwrap();
+    assert_eq!(orders.entry.filled_quantity, 150);
+    assert_eq!(orders.entry.average_filled_price, Some(dec!(41)));
+
+    let error = trust
+        .backfill_execution(
+            trade.id,
+            ExecutionSide::Sell,
+            dec!(45),
+            150,
+            executed_at,
+            ExecutionSource::Manual,
+        )
+        .expect_err("A long trade that is not closed can not have a sell execution");
+    assert!(error.to_string().contains("long trade with status"));
+}
+
+#[test]
+fn test_export_trades_json() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    let trade = create_new_trade(&mut trust, &account, "AAPL", 150);
+    trust.fund_trade(&trade).unwrap();
+    create_new_trade(&mut trust, &account, "TSLA", 10);
+    let executed_at = Utc::now().naive_utc();
+    for (price, quantity) in [(dec!(40.01), 100), (dec!(43.125), 50)] {
+        trust
+            .backfill_execution(
+                trade.id,
+                ExecutionSide::Buy,
+                price,
+                quantity,
+                executed_at,
+                ExecutionSource::Manual,
+            )
+            .unwrap();
+    }
+    let trade = trust.search_trades(account.id, Status::Funded).unwrap()[0].clone();
+
+    let json = trust
+        .export_trades_json(account.id, Some(Status::Funded))
+        .unwrap();
+
+    // Decimals are strings, so no precision is lost
+    assert!(json.starts_with(r#"{"version":1,"trades":["#));
+    assert!(json.contains(r#""price":"40.01""#));
+    assert!(json.contains(r#""price":"43.125""#));
+    assert!(json.contains(r#""funding":"6000""#));
+    assert!(json.contains(r#""status":"funded""#));
+
+    let export: TradesExport = serde_json::from_str(&json).unwrap();
+    assert_eq!(export.version, TRADES_EXPORT_VERSION);
+    assert_eq!(export.trades.len(), 1);
+    assert_eq!(export.trades[0].trade, trade);
+    let executions = &export.trades[0].executions;
+    assert_eq!(executions.len(), 2);
+    assert!(executions
+        .iter()
+        .all(|execution| execution.order_id == trade.entry.id));
+    assert_eq!(
+        executions
+            .iter()
+            .map(|execution| execution.price)
+            .sum::<Decimal>(),
+        dec!(83.135)
+    );
+
+    // Without a status, the trades of every status are exported
+    let json = trust.export_trades_json(account.id, None).unwrap();
+    let export: TradesExport = serde_json::from_str(&json).unwrap();
+    assert_eq!(export.trades.len(), 2);
+}
+
+#[test]
+fn test_create_account_with_hierarchy() {
+    let mut trust = create_trust();
+    let parent = create_funded_account(&mut trust);
+
+    trust
+        .create_account_with_hierarchy(
+            "hedge",
+            "hedges alpaca",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+            AccountType::Hedge,
+            Some(parent.id),
+        )
+        .unwrap();
+
+    // The type and the parent are persisted
+    let hedge = trust.search_account("hedge").unwrap();
+    assert_eq!(hedge.account_type, AccountType::Hedge);
+    assert_eq!(hedge.parent_account_id, Some(parent.id));
+    assert_eq!(
+        trust.search_account("alpaca").unwrap().account_type,
+        AccountType::Primary
+    );
+
+    // Only primary accounts have a level
+    assert!(trust.level(parent.id).is_ok());
+    trust
+        .level(hedge.id)
+        .expect_err("A hedge account does not have a level");
+
+    let error = trust
+        .create_account_with_hierarchy(
+            "orphan",
+            "",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+            AccountType::Hedge,
+            Some(Uuid::new_v4()),
+        )
+        .expect_err("The parent account must exist");
+    assert!(error.to_string().contains("does not exist"));
+    assert!(trust.search_account("orphan").is_err());
+}
+
+#[test]
+fn test_diff_account_config() {
+    let mut trust = create_trust();
+    let mut accounts = Vec::new();
+    for (name, risk_per_trade) in [("first", 2.0), ("second", 1.5)] {
+        let account = trust
+            .create_account(
+                name,
+                "default",
+                model::Environment::Paper,
+                dec!(20),
+                dec!(10),
+            )
+            .unwrap();
+        for rule in [
+            RuleName::RiskPerTrade(risk_per_trade),
+            RuleName::RiskPerMonth(6.0),
+        ] {
+            trust
+                .create_rule(&account, &rule, "description", &RuleLevel::Error)
+                .unwrap();
+        }
+        accounts.push(account);
+    }
+
+    let diff = trust
+        .diff_account_config(accounts[0].id, accounts[0].id)
+        .unwrap();
+    assert!(diff.is_empty());
+
+    let diff = trust
+        .diff_account_config(accounts[0].id, accounts[1].id)
+        .unwrap();
+    assert_eq!(
+        diff.differences,
+        vec![ConfigDifference {
+            setting: "rule.risk_per_trade".to_string(),
+            a: Some("2 error".to_string()),
+            b: Some("1.5 error".to_string()),
+        }]
+    );
+}
+
+#[test]
+fn test_merge_accounts() {
+    let mut trust = create_trust();
+    let target = create_funded_account(&mut trust);
+    let source = trust
+        .create_account(
+            "second",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    trust
+        .create_transaction(
+            &source,
+            &TransactionCategory::Deposit,
+            dec!(2500),
+            &Currency::USD,
+        )
+        .unwrap();
+    trust
+        .create_rule(
+            &target,
+            &RuleName::RiskPerTrade(2.0),
+            "description",
+            &RuleLevel::Error,
+        )
+        .unwrap();
+    for rule in [RuleName::RiskPerTrade(1.0), RuleName::RiskPerMonth(6.0)] {
+        trust
+            .create_rule(&source, &rule, "description", &RuleLevel::Error)
+            .unwrap();
+    }
+    let trade = create_new_trade(&mut trust, &source, "AAPL", 100);
+
+    let report = trust.merge_accounts(source.id, target.id).unwrap();
+
+    assert_eq!(report.trades, vec![trade.id]);
+    assert_eq!(report.transactions, 1);
+    assert_eq!(report.rules, 1);
+    assert_eq!(report.balances.len(), 1);
+    assert_eq!(report.balances[0].total_available, dec!(102500));
+
+    // The balances are summed
+    let balance = trust.search_balance(target.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_balance, dec!(102500));
+    assert_eq!(balance.total_available, dec!(102500));
+
+    // The trades are reassigned
+    let trades = trust.search_trades(target.id, Status::New).unwrap();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].id, trade.id);
+    assert_eq!(trades[0].account_id, target.id);
+
+    // The rules of the target are kept and the source is deleted
+    let rules = trust.search_all_rules(target.id).unwrap();
+    assert_eq!(rules.len(), 2);
+    assert!(rules
+        .iter()
+        .any(|rule| rule.name == RuleName::RiskPerTrade(2.0)));
+    assert!(rules
+        .iter()
+        .any(|rule| rule.name == RuleName::RiskPerMonth(6.0)));
+    let accounts = trust.search_all_accounts().unwrap();
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0].id, target.id);
+    assert!(trust.search_account("second").is_err());
+}
+
+#[test]
+fn test_merge_accounts_refuses_different_environments() {
+    let mut trust = create_trust();
+    let target = create_funded_account(&mut trust);
+    let source = trust
+        .create_account(
+            "live",
+            "default",
+            model::Environment::Live,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+
+    let error = trust.merge_accounts(source.id, target.id).unwrap_err();
+
+    assert!(error.to_string().contains("paper"));
+    assert_eq!(trust.search_all_accounts().unwrap().len(), 2);
+}
+
+#[test]
+fn test_merge_accounts_refuses_open_trades() {
+    let mut trust = create_trust();
+    let target = create_funded_account(&mut trust);
+    let source = trust
+        .create_account(
+            "second",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    trust
+        .create_transaction(
+            &source,
+            &TransactionCategory::Deposit,
+            dec!(10000),
+            &Currency::USD,
+        )
+        .unwrap();
+    let trade = create_new_trade(&mut trust, &source, "AAPL", 100);
+    trust.fund_trade(&trade).unwrap();
+
+    let error = trust.merge_accounts(source.id, target.id).unwrap_err();
+
+    assert!(error.to_string().contains("open trades"));
+    let balance = trust.search_balance(target.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(100000));
+    assert_eq!(
+        trust
+            .search_trades(source.id, Status::Funded)
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_merge_accounts_refuses_duplicated_references() {
+    let mut trust = create_trust();
+    let target = create_funded_account(&mut trust);
+    let source = trust
+        .create_account(
+            "second",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    let tv = trust
+        .create_trading_vehicle("AAPL", "AAPL", &TradingVehicleCategory::Stock, "NASDAQ")
+        .unwrap();
+    for account in [&target, &source] {
+        let draft = DraftTrade {
+            account: account.clone(),
+            trading_vehicle: tv.clone(),
+            quantity: 100,
+            quantity_decimal: None,
+            currency: Currency::USD,
+            category: TradeCategory::Long,
+            strategy_id: None,
+            reference: Some("breakout-1".to_string()),
+            thesis: None,
+            context: None,
+        };
+        trust
+            .create_trade(draft, dec!(38), dec!(40), dec!(50))
+            .unwrap();
+    }
+
+    let error = trust.merge_accounts(source.id, target.id).unwrap_err();
+
+    assert!(error.to_string().contains("breakout-1"));
+    assert_eq!(trust.search_all_accounts().unwrap().len(), 2);
+    assert_eq!(
+        trust.search_trades(source.id, Status::New).unwrap().len(),
+        1
+    );
+    assert_eq!(
+        trust.search_trades(target.id, Status::New).unwrap().len(),
+        1
+    );
+}
+
+#[test]
+fn test_advisory_thresholds_out_of_range() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+
+    for (risk, concentration) in [(dec!(-1), dec!(25)), (dec!(2), dec!(101))] {
+        let error = trust
+            .configure_advisory_thresholds(account.id, risk, concentration)
+            .unwrap_err();
+        assert!(error.to_string().contains("between 0% and 100%"));
+    }
+
+    // Nothing is stored, so the account keeps the default thresholds
+    let thresholds = trust.advisory_thresholds(account.id).unwrap();
+    assert_eq!(thresholds.max_risk_per_trade_percent, dec!(2));
+    assert_eq!(thresholds.max_position_concentration_percent, dec!(25));
+}
+
+#[test]
+fn test_advisory_thresholds_are_persisted() {
+    let database = SqliteDatabase::new_in_memory();
+    let mut trust = TrustFacade::new(Box::new(database.clone()), Box::new(MockBroker));
+    let account = create_funded_account(&mut trust);
+    let trade = create_new_trade(&mut trust, &account, "AAPL", 100);
+
+    // The only position is all of the capital in open trades, above the default 25%
+    let advisories = trust.advisory_check_trade(&trade).unwrap();
+    assert_eq!(advisories.len(), 1);
+    assert_eq!(advisories[0].kind, AdvisoryKind::PositionConcentration);
+    assert_eq!(advisories[0].value, dec!(100));
+
+    // The trade risks 200 of 100000, which is above 0.1% and below 2%
+    trust
+        .configure_advisory_thresholds(account.id, dec!(0.1), dec!(100))
+        .unwrap();
+    let advisories = trust.advisory_check_trade(&trade).unwrap();
+    assert_eq!(advisories.len(), 1);
+    assert_eq!(advisories[0].kind, AdvisoryKind::RiskPerTrade);
+    assert_eq!(advisories[0].value, dec!(0.2));
+    assert_eq!(advisories[0].threshold, dec!(0.1));
+
+    // A fresh facade over the same database reads the stored thresholds
+    let mut trust = TrustFacade::new(Box::new(database), Box::new(MockBroker));
+    let thresholds = trust.advisory_thresholds(account.id).unwrap();
+    assert_eq!(thresholds.max_risk_per_trade_percent, dec!(0.1));
+    assert_eq!(thresholds.max_position_concentration_percent, dec!(100));
+    trust
+        .configure_advisory_thresholds(account.id, dec!(2), dec!(100))
+        .unwrap();
+    assert!(trust.advisory_check_trade(&trade).unwrap().is_empty());
+}
+
+#[test]
+fn test_validate_trade_with_broker() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+
+    // Not funded trades can not be validated
+    let trade = create_new_trade(&mut trust, &account, "TSLA", 500);
+    trust
+        .validate_trade_with_broker(&trade, &account)
+        .expect_err("A trade that is not funded can not be validated");
+
+    trust.fund_trade(&trade).unwrap();
+    let trade = trust.search_trades(account.id, Status::Funded).unwrap()[0].clone();
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+
+    let validation = trust
+        .validate_trade_with_broker(&trade, &account)
+        .expect("Failed to validate trade");
+    assert!(!validation.is_valid());
+    assert_eq!(validation.buying_power_impact, dec!(20000));
+    assert_eq!(validation.rejections, vec!["Insufficient buying power"]);
+
+    // The dry run does not change the trade nor the account
+    let trades = trust.search_trades(account.id, Status::Funded).unwrap();
+    assert_eq!(trades, vec![trade]);
+    assert_eq!(trades[0].entry.broker_order_id, None);
+    assert_eq!(trades[0].safety_stop.broker_order_id, None);
+    assert_eq!(trades[0].target.broker_order_id, None);
+    assert_eq!(
+        trust.search_balance(account.id, &Currency::USD).unwrap(),
+        balance
+    );
+}
+
+#[test]
+fn test_reconcile_account_balance_reports_the_delta() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+
+    let reconciliation = trust
+        .reconcile_account_balance(account.id, false, None)
+        .unwrap();
+
+    assert_eq!(reconciliation.broker_buying_power, dec!(100250));
+    assert_eq!(reconciliation.total_available, dec!(100000));
+    assert_eq!(reconciliation.delta, dec!(250));
+    assert_eq!(reconciliation.adjustment, None);
+
+    // Nothing was written
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(100000));
+}
+
+#[test]
+fn test_reconcile_account_balance_writes_an_adjustment() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Withdrawal,
+            dec!(1000),
+            &Currency::USD,
+        )
+        .unwrap();
+
+    // The adjustment is a protected mutation
+    let error = trust
+        .reconcile_account_balance(account.id, true, None)
+        .unwrap_err();
+    assert!(error.to_string().contains("protected-mode authorization"));
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(99000));
+
+    let authorization = trust.authorize_protected_mutation();
+    let reconciliation = trust
+        .reconcile_account_balance(account.id, true, Some(&authorization))
+        .unwrap();
+
+    assert_eq!(reconciliation.total_available, dec!(99000));
+    assert_eq!(reconciliation.delta, dec!(1250));
+    let adjustment = reconciliation.adjustment.unwrap();
+    assert_eq!(
+        adjustment.category,
+        TransactionCategory::Adjustment {
+            reason: "Reconciliation with the buying power of the broker".to_string()
+        }
+    );
+    assert_eq!(adjustment.amount, dec!(1250));
+
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(100250));
+    assert_eq!(balance.total_balance, dec!(100250));
+
+    // The token was consumed, and with a new one nothing else is adjusted
+    assert!(trust
+        .reconcile_account_balance(account.id, true, Some(&authorization))
+        .is_err());
+    let authorization = trust.authorize_protected_mutation();
+    let reconciliation = trust
+        .reconcile_account_balance(account.id, true, Some(&authorization))
+        .unwrap();
+    assert_eq!(reconciliation.delta, dec!(0));
+    assert_eq!(reconciliation.adjustment, None);
+}
+
+#[test]
+fn test_reconcile_account_balance_in_the_base_currency() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    let account = trust
+        .set_account_base_currency(&account, &Currency::EUR)
+        .unwrap();
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(100200),
+            &Currency::EUR,
+        )
+        .unwrap();
+
+    let authorization = trust.authorize_protected_mutation();
+    let reconciliation = trust
+        .reconcile_account_balance(account.id, true, Some(&authorization))
+        .unwrap();
+
+    // The EUR balance is compared and adjusted, the USD one is left as it is
+    assert_eq!(reconciliation.total_available, dec!(100200));
+    assert_eq!(reconciliation.delta, dec!(50));
+    let adjustment = reconciliation.adjustment.unwrap();
+    assert_eq!(adjustment.currency, Currency::EUR);
+    assert_eq!(adjustment.amount, dec!(50));
+    let balance = trust.search_balance(account.id, &Currency::EUR).unwrap();
+    assert_eq!(balance.total_available, dec!(100250));
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(100000));
+}
+
+struct MockBroker;
+impl Broker for MockBroker {
+    fn submit_trade(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+    ) -> Result<(BrokerLog, OrderIds), Box<dyn Error>> {
+        unimplemented!()
+    }
+
+    /// The mocked account has a buying power of 10000.
+    fn validate_trade(
+        &self,
+        trade: &Trade,
+        _account: &Account,
+    ) -> Result<BrokerValidation, Box<dyn Error>> {
+        let buying_power_impact = trade.entry.unit_price * Decimal::from(trade.entry.quantity);
+        let rejections = if buying_power_impact > dec!(10000) {
+            vec!["Insufficient buying power".to_string()]
+        } else {
+            vec![]
+        };
+        Ok(BrokerValidation {
+            buying_power_impact,
+            rejections,
+        })
+    }
+
+    fn sync_trade(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+    ) -> Result<(Status, Vec<Order>, BrokerLog), Box<dyn Error>> {
+        unimplemented!()
+    }
+
+    fn close_trade(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+    ) -> Result<(Order, BrokerLog), Box<dyn Error>> {
+        unimplemented!()
+    }
+
+    fn close_trade_limit(
+        &self,
+        trade: &Trade,
+        account: &Account,
+        limit_price: Decimal,
+    ) -> Result<(Order, BrokerLog), Box<dyn Error>> {
+        unimplemented!(
+            "Close trade at limit: {:?} {:?} {:?}",
+            trade,
+            account,
+            limit_price
+        )
+    }
+
+    fn cancel_trade(&self, trade: &Trade, account: &Account) -> Result<(), Box<dyn Error>> {
+        unimplemented!("Cancel trade: {:?} {:?}", trade, account)
+    }
+
+    fn cancel_order(&self, order: &Order, account: &Account) -> Result<(), Box<dyn Error>> {
+        unimplemented!("Cancel order: {:?} {:?}", order, account)
+    }
+
+    fn cancel_all_orders(&self, account: &Account) -> Result<Vec<Uuid>, Box<dyn Error>> {
+        unimplemented!("Cancel all orders: {:?}", account)
+    }
+
+    fn replace_order(
+        &self,
+        order: &Order,
+        account: &Account,
+        new_price: Decimal,
+        new_quantity: u64,
+    ) -> Result<Uuid, Box<dyn Error>> {
+        unimplemented!(
+            "Replace order: {:?} {:?} {:?} {:?}",
+            order,
+            account,
+            new_price,
+            new_quantity
+        )
+    }
+
+    fn modify_stop(
+        &self,
+        trade: &Trade,
+        account: &Account,
+        new_stop_price: Decimal,
+    ) -> Result<Uuid, Box<dyn Error>> {
+        unimplemented!(
+            "Modify stop: {:?} {:?} {:?}",
+            trade,
+            account,
+            new_stop_price
+        )
+    }
+
+    fn modify_target(
+        &self,
+        trade: &Trade,
+        account: &Account,
+        new_target_price: rust_decimal::Decimal,
+    ) -> Result<Uuid, Box<dyn Error>> {
+        unimplemented!(
+            "Modify target: {:?} {:?} {:?}",
+            trade,
+            account,
+            new_target_price
+        )
+    }
+
+    fn get_account_activities(
+        &self,
+        account: &Account,
+        since: NaiveDateTime,
+        until: NaiveDateTime,
+    ) -> Result<Vec<BrokerActivity>, Box<dyn Error>> {
+        unimplemented!(
+            "Get account activities: {:?} {:?} {:?}",
+            account,
+            since,
+            until
+        )
+    }
+    fn account_buying_power(&self, _account: &Account) -> Result<Decimal, Box<dyn Error>> {
+        Ok(dec!(100250))
+    }
+
+    fn get_positions(&self, account: &Account) -> Result<Vec<BrokerPosition>, Box<dyn Error>> {
+        unimplemented!("Get positions: {:?}", account)
+    }
+}
+
+#[test]
+fn test_evaluate_level_adjustments_withdrawal_is_not_a_drawdown() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+
+    // Withdrawing 10% of the account is not a loss
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Withdrawal,
+            dec!(10000),
+            &Currency::USD,
+        )
+        .unwrap();
+
+    assert_eq!(trust.evaluate_level_adjustments(account.id).unwrap(), None);
+    assert_eq!(
+        trust.level(account.id).unwrap().current_level,
+        Level::DEFAULT
+    );
+    assert!(trust.level_history(account.id).unwrap().is_empty());
+}
+
+#[test]
+fn test_evaluate_level_adjustments_healthy_account() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+    trust
+        .update_level_adjustment_rules(
+            account.id,
+            &LevelAdjustmentRules {
+                monthly_loss_downgrade_pct: dec!(20),
+            },
+        )
+        .unwrap();
+
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Withdrawal,
+            dec!(10000),
+            &Currency::USD,
+        )
+        .unwrap();
+
+    assert_eq!(trust.evaluate_level_adjustments(account.id).unwrap(), None);
+    assert_eq!(
+        trust.level(account.id).unwrap().current_level,
+        Level::DEFAULT
+    );
+    assert!(trust.level_history(account.id).unwrap().is_empty());
+}
+
+#[test]
+fn test_change_level() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+
+    let change = trust
+        .change_level(
+            account.id,
+            Level::MAX,
+            "Three months without breaking a rule",
+            &LevelTrigger::ManualOverride,
+        )
+        .unwrap();
+    assert_eq!(change.old_level, Level::DEFAULT);
+    assert_eq!(change.new_level, Level::MAX);
+    assert_eq!(trust.level(account.id).unwrap().current_level, Level::MAX);
+
+    // Invalid changes are rejected and nothing is recorded
+    for (level, reason, trigger) in [
+        (Level::MAX + 1, "reason", LevelTrigger::ManualOverride),
+        (Level::MAX, "reason", LevelTrigger::ManualOverride),
+        (Level::MIN, "  ", LevelTrigger::ManualOverride),
+        (Level::MIN, "reason", LevelTrigger::Custom("".to_string())),
+    ] {
+        trust
+            .change_level(account.id, level, reason, &trigger)
+            .expect_err("The level change must be rejected");
+    }
+    assert_eq!(trust.level(account.id).unwrap().current_level, Level::MAX);
+    assert_eq!(trust.level_history(account.id).unwrap(), vec![change]);
+}
+
+#[test]
+fn test_create_rule_with_invalid_percentage() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+
+    for rule in [
+        RuleName::RiskPerTrade(-1.0),
+        RuleName::RiskPerTrade(101.0),
+        RuleName::RiskPerMonth(f32::NAN),
+        RuleName::RiskPerMonth(f32::INFINITY),
+    ] {
+        trust
+            .create_rule(&account, &rule, "Invalid risk", &RuleLevel::Error)
+            .expect_err("The percentage is not between 0 and 100");
+    }
+    assert!(trust.search_all_rules(account.id).unwrap().is_empty());
+}
+
+#[test]
+fn test_create_rule_of_the_same_type_twice() {
+    let mut trust = create_trust();
+    let account = create_funded_account(&mut trust);
+
+    trust
+        .create_rule(
+            &account,
+            &RuleName::RiskPerTrade(2.0),
+            "Risk 2% per trade",
+            &RuleLevel::Error,
+        )
+        .unwrap();
+    trust
+        .create_rule(
+            &account,
+            &RuleName::RiskPerTrade(3.0),
+            "Risk 3% per trade",
+            &RuleLevel::Error,
+        )
+        .expect_err("There is already a risk per trade rule");
+
+    let rules = trust.search_all_rules(account.id).unwrap();
+    assert_eq!(rules.len(), 1);
+    assert_eq!(rules[0].name, RuleName::RiskPerTrade(2.0));
 }
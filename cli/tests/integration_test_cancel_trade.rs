@@ -1,10 +1,11 @@
+use chrono::NaiveDateTime;
 use core::TrustFacade;
 use db_sqlite::SqliteDatabase;
-use model::Broker;
 use model::{
     Account, BrokerLog, Currency, DraftTrade, Order, OrderIds, Status, Trade, TradeCategory,
     TradingVehicleCategory, TransactionCategory,
 };
+use model::{Broker, BrokerActivity, BrokerPosition, BrokerValidation};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::error::Error;
@@ -56,8 +57,13 @@ fn test_cancel_of_funded_trade() {
         account: account.clone(),
         trading_vehicle: tv,
         quantity: 500,
+        quantity_decimal: None,
         currency: Currency::USD,
         category: TradeCategory::Long,
+        strategy_id: None,
+        reference: None,
+        thesis: None,
+        context: None,
     };
 
     trust
@@ -110,6 +116,14 @@ impl Broker for MockBroker {
         unimplemented!()
     }
 
+    fn validate_trade(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+    ) -> Result<BrokerValidation, Box<dyn Error>> {
+        unimplemented!()
+    }
+
     fn sync_trade(
         &self,
         _trade: &Trade,
@@ -126,10 +140,37 @@ impl Broker for MockBroker {
         unimplemented!()
     }
 
+    fn close_trade_limit(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+        _limit_price: Decimal,
+    ) -> Result<(Order, BrokerLog), Box<dyn Error>> {
+        unimplemented!("Close trade at limit not implemented")
+    }
+
     fn cancel_trade(&self, _trade: &Trade, _account: &Account) -> Result<(), Box<dyn Error>> {
         unimplemented!("Cancel trade not implemented")
     }
 
+    fn cancel_order(&self, _order: &Order, _account: &Account) -> Result<(), Box<dyn Error>> {
+        unimplemented!("Cancel order not implemented")
+    }
+
+    fn cancel_all_orders(&self, _account: &Account) -> Result<Vec<Uuid>, Box<dyn Error>> {
+        unimplemented!("Cancel all orders not implemented")
+    }
+
+    fn replace_order(
+        &self,
+        _order: &Order,
+        _account: &Account,
+        _new_price: Decimal,
+        _new_quantity: u64,
+    ) -> Result<Uuid, Box<dyn Error>> {
+        unimplemented!("Replace order not implemented")
+    }
+
     fn modify_stop(
         &self,
         trade: &Trade,
@@ -157,4 +198,25 @@ impl Broker for MockBroker {
             new_target_price
         )
     }
+
+    fn get_account_activities(
+        &self,
+        account: &Account,
+        since: NaiveDateTime,
+        until: NaiveDateTime,
+    ) -> Result<Vec<BrokerActivity>, Box<dyn Error>> {
+        unimplemented!(
+            "Get account activities: {:?} {:?} {:?}",
+            account,
+            since,
+            until
+        )
+    }
+    fn account_buying_power(&self, account: &Account) -> Result<Decimal, Box<dyn Error>> {
+        unimplemented!("Account buying power: {:?}", account)
+    }
+
+    fn get_positions(&self, account: &Account) -> Result<Vec<BrokerPosition>, Box<dyn Error>> {
+        unimplemented!("Get positions: {:?}", account)
+    }
 }
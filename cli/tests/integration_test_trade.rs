@@ -1,26 +1,59 @@
-use chrono::Utc;
-use core::TrustFacade;
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Utc};
+use core::{CorrelationCluster, TimelineEventKind, TrustFacade};
 use db_sqlite::SqliteDatabase;
 use model::{
-    Account, BrokerLog, Currency, Order, OrderCategory, OrderIds, RuleLevel, RuleName, Status,
-    Trade, TradeCategory, TradingVehicleCategory, TransactionCategory,
+    Account, AccountType, BrokerLog, Currency, LevelAdjustmentRules, LevelTrigger, Order,
+    OrderCategory, OrderIds, RuleLevel, RuleName, Status, Trade, TradeCategory,
+    TradingVehicleCategory, TransactionCategory,
+};
+use model::{
+    Broker, BrokerActivity, BrokerActivityCategory, BrokerError, BrokerErrorCode, BrokerPosition,
+    BrokerValidation, Clock, DatabaseFactory, DraftExecution, DraftTrade, ExecutionSide,
+    ExecutionSource, FeeModel, LotMethod, MarketBar, MarketPriceProvider, OrderStatus, RealizedLot,
+    SteppableClock, TradingVehicle, Transaction, WriteTransactionDB,
 };
-use model::{Broker, DraftTrade, OrderStatus};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
 fn create_trade(
     broker_response: fn(trade: &Trade) -> (Status, Vec<Order>),
     closed_order: Option<fn(trade: &Trade) -> Option<Order>>,
 ) -> (TrustFacade, Account, Trade) {
-    let db = SqliteDatabase::new_in_memory();
-    let mut trust = TrustFacade::new(
-        Box::new(db),
-        Box::new(MockBroker::new(broker_response, closed_order)),
+    create_trade_with_database(
+        Box::new(SqliteDatabase::new_in_memory()),
+        broker_response,
+        closed_order,
+    )
+}
+
+fn create_trade_with_database(
+    db: Box<dyn DatabaseFactory>,
+    broker_response: fn(trade: &Trade) -> (Status, Vec<Order>),
+    closed_order: Option<fn(trade: &Trade) -> Option<Order>>,
+) -> (TrustFacade, Account, Trade) {
+    let trust = TrustFacade::new(db, Box::new(MockBroker::new(broker_response, closed_order)));
+    submit_new_trade(trust)
+}
+
+fn create_trade_with_clock(
+    broker_response: fn(trade: &Trade) -> (Status, Vec<Order>),
+    clock: Arc<dyn Clock>,
+) -> (TrustFacade, Account, Trade) {
+    let trust = TrustFacade::new_with_clock(
+        Box::new(SqliteDatabase::new_in_memory()),
+        Box::new(MockBroker::new(broker_response, None)),
+        clock,
     );
+    submit_new_trade(trust)
+}
 
+fn submit_new_trade(mut trust: TrustFacade) -> (TrustFacade, Account, Trade) {
     // 1. Create account and deposit money
     trust
         .create_account(
@@ -72,8 +105,13 @@ fn create_trade(
         account: account.clone(),
         trading_vehicle: tv,
         quantity: 500,
+        quantity_decimal: None,
         currency: Currency::USD,
         category: TradeCategory::Long,
+        strategy_id: None,
+        reference: None,
+        thesis: None,
+        context: None,
     };
 
     trust
@@ -148,6 +186,59 @@ fn test_trade_submit_entry_accepted_multiple_times() {
     assert_entry_accepted(&trade, &mut trust);
 }
 
+#[test]
+fn test_trade_orders_submitted() {
+    let (mut trust, _, trade) = create_trade(BrokerResponse::orders_accepted, None);
+
+    let orders = trust.trade_orders(trade.id).unwrap();
+
+    // Assert Entry
+    assert_eq!(orders.entry.id, trade.entry.id);
+    assert_eq!(
+        orders.entry.broker_order_id,
+        Some(Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap())
+    );
+    assert_eq!(orders.entry.status, OrderStatus::New);
+    assert!(orders.entry.submitted_at.is_some());
+    assert_eq!(orders.entry.filled_quantity, 0);
+
+    // Assert Target
+    assert_eq!(orders.target.id, trade.target.id);
+    assert_eq!(
+        orders.target.broker_order_id,
+        Some(Uuid::parse_str("90e41b1e-9089-444d-9f68-c204a4d32914").unwrap())
+    );
+    assert_eq!(orders.target.status, OrderStatus::New);
+    assert!(orders.target.submitted_at.is_some());
+
+    // Assert Stop
+    assert_eq!(orders.stop.id, trade.safety_stop.id);
+    assert_eq!(
+        orders.stop.broker_order_id,
+        Some(Uuid::parse_str("8654f70e-3b42-4014-a9ac-5a7101989aad").unwrap())
+    );
+    assert_eq!(orders.stop.status, OrderStatus::New);
+    assert!(orders.stop.submitted_at.is_some());
+}
+
+#[test]
+fn test_trade_orders_entry_accepted() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_accepted, None);
+    let mut trust = trust;
+    trust
+        .sync_trade(&trade, &account)
+        .expect("Failed to sync trade with broker when entry is accepted");
+
+    let orders = trust.trade_orders(trade.id).unwrap();
+
+    assert_eq!(orders.entry.status, OrderStatus::Accepted);
+    assert_eq!(orders.target.status, OrderStatus::Held);
+    assert_eq!(orders.stop.status, OrderStatus::Held);
+    assert!(orders.entry.broker_order_id.is_some());
+    assert!(orders.target.broker_order_id.is_some());
+    assert!(orders.stop.broker_order_id.is_some());
+}
+
 fn assert_entry_accepted(trade: &Trade, trust: &mut TrustFacade) {
     assert_eq!(trade.status, Status::Submitted);
 
@@ -202,6 +293,72 @@ fn test_trade_entry_filled() {
     assert_entry_filled(&trade, &mut trust);
 }
 
+#[test]
+fn test_reconcile_positions() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_entry_filled, None);
+    let mut trust = trust;
+
+    // A submitted trade does not hold a position yet
+    let reconciliation = trust.reconcile_positions(account.id).unwrap();
+    assert!(reconciliation.orphan_trades.is_empty());
+    assert_eq!(reconciliation.orphan_broker_positions.len(), 1);
+
+    // The broker does not show the filled TSLA trade and holds NVDA that is not tracked
+    trust.sync_trade(&trade, &account).unwrap();
+    let reconciliation = trust.reconcile_positions(account.id).unwrap();
+
+    assert_eq!(reconciliation.orphan_trades.len(), 1);
+    assert_eq!(reconciliation.orphan_trades[0].id, trade.id);
+    assert_eq!(
+        reconciliation.orphan_broker_positions,
+        vec![BrokerPosition {
+            symbol: "NVDA".to_string(),
+            category: TradeCategory::Long,
+            quantity: dec!(10),
+            average_entry_price: dec!(420),
+        }]
+    );
+    assert!(reconciliation.quantity_mismatches.is_empty());
+
+    // Nothing is corrected
+    let trades = trust.search_trades(account.id, Status::Filled).unwrap();
+    assert_eq!(trades.len(), 1);
+}
+
+#[test]
+fn test_trade_mae_mfe() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_entry_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+    let trade = trust.search_trades(account.id, Status::Filled).unwrap()[0].clone();
+    let filled_at = trade.entry.filled_at.unwrap();
+    let bar = |hours: i64, low: Decimal, high: Decimal| MarketBar {
+        timestamp: filled_at + chrono::Duration::hours(hours),
+        open: low,
+        high,
+        low,
+        close: high,
+        volume: dec!(1000),
+    };
+
+    trust
+        .trade_mae_mfe(trade.id, &[])
+        .expect_err("A trade without bars has no excursions");
+
+    // The entry filled at 39.9 risking 2 per share. The price dipped and then rallied.
+    let bars = [
+        bar(-1, dec!(30), dec!(60)),
+        bar(1, dec!(38.9), dec!(40)),
+        bar(2, dec!(40), dec!(44.9)),
+    ];
+    let excursion = trust.trade_mae_mfe(trade.id, &bars).unwrap();
+
+    assert_eq!(excursion.adverse, dec!(1));
+    assert_eq!(excursion.favorable, dec!(5));
+    assert_eq!(excursion.adverse_r, Some(dec!(0.5)));
+    assert_eq!(excursion.favorable_r, Some(dec!(2.5)));
+}
+
 #[test]
 fn test_trade_entry_filled_multiple_times() {
     let (trust, account, trade) = create_trade(BrokerResponse::orders_entry_filled, None);
@@ -224,6 +381,299 @@ fn test_trade_entry_filled_multiple_times() {
     assert_entry_filled(&trade, &mut trust);
 }
 
+struct StaticPrices(Option<Decimal>);
+
+impl MarketPriceProvider for StaticPrices {
+    fn latest_price(&self, _trading_vehicle: &TradingVehicle) -> Option<Decimal> {
+        self.0
+    }
+}
+
+#[test]
+fn test_directional_exposure_entry_filled() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_entry_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+
+    let exposure = trust
+        .directional_exposure(account.id, &Currency::USD, &StaticPrices(Some(dec!(45))))
+        .unwrap();
+    assert_eq!(exposure.long_notional, dec!(22500));
+    assert_eq!(exposure.short_notional, dec!(0));
+    assert_eq!(exposure.net, dec!(22500));
+    assert!(!exposure.uses_entry_prices);
+
+    // Without a market price the average filled price of the entry is used
+    let exposure = trust
+        .directional_exposure(account.id, &Currency::USD, &StaticPrices(None))
+        .unwrap();
+    assert_eq!(exposure.long_notional, dec!(19950));
+    assert!(exposure.uses_entry_prices);
+
+    let exposure = trust
+        .directional_exposure(account.id, &Currency::EUR, &StaticPrices(None))
+        .unwrap();
+    assert_eq!(exposure.net, dec!(0));
+}
+
+#[test]
+fn test_fractional_crypto_position_exposure_and_concentration() {
+    let mut trust = TrustFacade::new(
+        Box::new(SqliteDatabase::new_in_memory()),
+        Box::new(MockBroker::new(
+            BrokerResponse::orders_fractional_entry_filled,
+            None,
+        )),
+    );
+    trust
+        .create_account(
+            "alpaca",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    let account = trust.search_account("alpaca").unwrap();
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(50000),
+            &Currency::USD,
+        )
+        .unwrap();
+    let tv = trust
+        .create_trading_vehicle("BTC", "BTC", &TradingVehicleCategory::Crypto, "alpaca")
+        .unwrap();
+    let draft = DraftTrade {
+        account: account.clone(),
+        trading_vehicle: tv,
+        quantity: 0,
+        quantity_decimal: Some(dec!(0.25)),
+        currency: Currency::USD,
+        category: TradeCategory::Long,
+        strategy_id: None,
+        reference: None,
+        thesis: None,
+        context: None,
+    };
+    let trade = trust
+        .create_trade(draft, dec!(29000), dec!(30000), dec!(35000))
+        .unwrap();
+    let (trade, _, _, _, _) = trust.fund_trade(&trade).unwrap();
+    trust.submit_trade(&trade).unwrap();
+    let trade = trust.search_trades(account.id, Status::Submitted).unwrap()[0].clone();
+    trust.sync_trade(&trade, &account).unwrap();
+
+    let positions = trust.open_positions(account.id).unwrap();
+    assert_eq!(positions.len(), 1);
+    assert_eq!(positions[0].quantity, dec!(0.25));
+    assert_eq!(positions[0].capital_at_risk(), dec!(250)); // (30000 - 29000) * 0.25
+    assert_eq!(trust.net_exposure(account.id, "BTC").unwrap(), dec!(0.25));
+
+    let exposure = trust
+        .directional_exposure(account.id, &Currency::USD, &StaticPrices(Some(dec!(40000))))
+        .unwrap();
+    assert_eq!(exposure.long_notional, dec!(10000));
+    let exposure = trust
+        .directional_exposure(account.id, &Currency::USD, &StaticPrices(None))
+        .unwrap();
+    assert_eq!(exposure.long_notional, dec!(7500));
+
+    let clusters = trust
+        .correlated_clusters(account.id, &HashMap::new(), dec!(0.7))
+        .unwrap();
+    assert_eq!(
+        clusters,
+        vec![CorrelationCluster {
+            symbols: vec!["BTC".to_string()],
+            exposure: dec!(7500),
+        }]
+    );
+}
+
+#[test]
+fn test_portfolio_beta_entry_filled() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_entry_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+
+    let benchmark = vec![dec!(0.02), dec!(-0.01), dec!(0.03)];
+    let returns = HashMap::from([(
+        "TSLA".to_string(),
+        vec![dec!(0.03), dec!(-0.015), dec!(0.045)],
+    )]);
+    let beta = trust
+        .portfolio_beta(account.id, &benchmark, returns.clone())
+        .unwrap();
+    assert_eq!(beta, dec!(1.5));
+
+    trust
+        .portfolio_beta(account.id, &benchmark[..2], returns)
+        .expect_err("The return series have different lengths");
+}
+
+#[test]
+fn test_value_at_risk_entry_filled() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_entry_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+
+    // The position has a notional of 19950 at its average filled price.
+    let mut returns = vec![dec!(0.01); 18];
+    returns.extend([dec!(-0.02), dec!(-0.03)]);
+    let returns = HashMap::from([("TSLA".to_string(), returns)]);
+    let var = trust
+        .value_at_risk(account.id, returns.clone(), dec!(0.95))
+        .unwrap();
+    assert_eq!(var, dec!(408.975));
+
+    trust
+        .value_at_risk(account.id, returns, dec!(1))
+        .expect_err("The confidence must be below 1");
+}
+
+#[test]
+fn test_simulate_trade_outcomes_entry_filled() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_entry_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+
+    // The position wins 5050 at the target and loses 950 at the stop.
+    let result = trust
+        .simulate_trade_outcomes(account.id, &Currency::USD, 200, dec!(0.5), 42)
+        .unwrap();
+    assert_eq!(result.starting_equity, dec!(50000));
+    assert_eq!(result.percentile(0), dec!(49050));
+    assert_eq!(result.percentile(100), dec!(55050));
+    assert_eq!(result.probability_of_drawdown(dec!(2)), dec!(0));
+    assert_eq!(
+        result,
+        trust
+            .simulate_trade_outcomes(account.id, &Currency::USD, 200, dec!(0.5), 42)
+            .unwrap()
+    );
+
+    trust
+        .simulate_trade_outcomes(account.id, &Currency::USD, 200, dec!(2), 42)
+        .expect_err("The win probability must be below 1");
+}
+
+#[test]
+fn test_aggregated_positions_entry_filled() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_entry_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+
+    let positions = trust.aggregated_positions(account.id).unwrap();
+
+    assert_eq!(positions.len(), 1);
+    assert_eq!(positions[0].trading_vehicle.symbol, "TSLA");
+    assert_eq!(positions[0].category, TradeCategory::Long);
+    assert_eq!(positions[0].quantity, dec!(500));
+    assert_eq!(positions[0].average_entry_price, dec!(39.9));
+    assert_eq!(positions[0].capital_at_risk, dec!(950));
+}
+
+#[test]
+fn test_dividend_does_not_change_capital_at_risk() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_entry_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+
+    let (transaction, balance) = trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Dividend(Some(trade.trading_vehicle.id)),
+            dec!(100),
+            &Currency::USD,
+        )
+        .unwrap();
+
+    assert_eq!(
+        transaction.category,
+        TransactionCategory::Dividend(Some(trade.trading_vehicle.id))
+    );
+    assert_eq!(balance.total_available, dec!(30150)); // 30050 + 100 (dividend)
+    assert_eq!(balance.total_balance, dec!(30150));
+    assert_eq!(balance.total_in_trade, dec!(19950)); // The trade is not affected
+
+    let positions = trust.aggregated_positions(account.id).unwrap();
+    assert_eq!(positions[0].capital_at_risk, dec!(950));
+}
+
+#[test]
+fn test_interest_does_not_change_trade_performance() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_target_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+    let closed = trust
+        .search_trades(account.id, Status::ClosedTarget)
+        .unwrap()[0]
+        .clone();
+    let before = trust.search_balance(account.id, &Currency::USD).unwrap();
+
+    // A credit of the broker on the cash of the account
+    let (transaction, balance) = trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Interest,
+            dec!(25),
+            &Currency::USD,
+        )
+        .unwrap();
+    assert_eq!(transaction.amount, dec!(25));
+    assert_eq!(balance.total_available, before.total_available + dec!(25));
+    assert_eq!(balance.total_balance, before.total_balance + dec!(25));
+
+    // A charge of the broker, for example for a margin loan
+    let (transaction, balance) = trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Interest,
+            dec!(-10),
+            &Currency::USD,
+        )
+        .unwrap();
+    assert_eq!(transaction.amount, dec!(-10));
+    assert_eq!(balance.total_available, before.total_available + dec!(15));
+    assert_eq!(balance.total_balance, before.total_balance + dec!(15));
+
+    // The interest is in the statement of the account but not in the performance of the trade
+    let now = Utc::now().naive_utc();
+    let statement = trust
+        .account_statement(account.id, now.year(), now.month(), Some(&Currency::USD))
+        .unwrap();
+    assert_eq!(statement.interest, dec!(15));
+    let trade = trust
+        .search_trades(account.id, Status::ClosedTarget)
+        .unwrap()[0]
+        .clone();
+    assert_eq!(
+        trade.balance.total_performance,
+        closed.balance.total_performance
+    );
+    assert_eq!(trust.risk_of_ruin(account.id, dec!(0.02)).unwrap(), dec!(0));
+
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Interest,
+            dec!(0),
+            &Currency::USD,
+        )
+        .expect_err("Interest of zero is not valid");
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Interest,
+            -(balance.total_available + dec!(1)),
+            &Currency::USD,
+        )
+        .expect_err("The charge is greater than the available money");
+}
+
 fn assert_entry_filled(trade: &Trade, trust: &mut TrustFacade) {
     // Assert Status
     assert_eq!(trade.status, Status::Filled);
@@ -281,293 +731,2593 @@ fn test_trade_target_filled() {
 }
 
 #[test]
-fn test_trade_target_filled_multiple_times() {
+fn test_clone_closed_trade() {
     let (trust, account, trade) = create_trade(BrokerResponse::orders_target_filled, None);
     let mut trust = trust;
-
-    // 9. Sync trade with the Broker - Target is filled
-    for _ in 0..10 {
-        trust.sync_trade(&trade, &account).unwrap();
-    }
-
-    let trade = trust
+    trust
+        .revise_trade_thesis(
+            trade.id,
+            Some("Breakout above the range".to_string()),
+            Some("Index in an uptrend".to_string()),
+        )
+        .unwrap();
+    trust.sync_trade(&trade, &account).unwrap();
+    trust
+        .set_trade_sector(trade.id, Some("Technology"))
+        .unwrap();
+    let source = trust
         .search_trades(account.id, Status::ClosedTarget)
         .unwrap()
         .first()
         .unwrap()
         .clone();
 
-    assert_target_filled(&trade, &mut trust);
-}
-
-fn assert_target_filled(trade: &Trade, trust: &mut TrustFacade) {
-    assert_eq!(trade.status, Status::ClosedTarget);
-
-    // Assert Entry
-    assert_eq!(trade.entry.quantity, 500);
-    assert_eq!(trade.entry.unit_price, dec!(40));
-    assert_eq!(trade.entry.average_filled_price, Some(dec!(39.9)));
-    assert_eq!(trade.entry.filled_quantity, 500);
-    assert_eq!(trade.entry.status, OrderStatus::Filled);
-
-    // Assert Target
-    assert_eq!(trade.target.quantity, 500);
-    assert_eq!(trade.target.unit_price, dec!(50));
-    assert_eq!(trade.target.average_filled_price, Some(dec!(52.9)));
-    assert_eq!(trade.target.filled_quantity, 500);
-    assert_eq!(trade.target.status, OrderStatus::Filled);
-
-    // Assert Stop
-    assert_eq!(trade.safety_stop.quantity, 500);
-    assert_eq!(trade.safety_stop.unit_price, dec!(38));
-    assert_eq!(trade.safety_stop.average_filled_price, None);
-    assert_eq!(trade.safety_stop.filled_quantity, 0);
-    assert_eq!(trade.safety_stop.status, OrderStatus::Canceled);
+    let trade = trust.clone_trade(source.id).unwrap();
+
+    assert_eq!(trade.status, Status::New);
+    assert_ne!(trade.id, source.id);
+    assert_eq!(trade.trading_vehicle, source.trading_vehicle);
+    assert_eq!(trade.category, source.category);
+    assert_eq!(trade.currency, source.currency);
+    assert_eq!(trade.strategy_id, source.strategy_id);
+    assert_eq!(trade.sector, Some("technology".to_string()));
+    assert_eq!(trade.sector, source.sector);
+    assert_eq!(trade.thesis, Some("Breakout above the range".to_string()));
+    assert_eq!(trade.thesis, source.thesis);
+    assert_eq!(trade.context, Some("Index in an uptrend".to_string()));
+    assert_eq!(trade.context, source.context);
+    for (order, source_order) in [
+        (&trade.safety_stop, &source.safety_stop),
+        (&trade.entry, &source.entry),
+        (&trade.target, &source.target),
+    ] {
+        assert_ne!(order.id, source_order.id);
+        assert_eq!(order.unit_price, source_order.unit_price);
+        assert_eq!(order.quantity, source_order.quantity);
+        assert_eq!(order.broker_order_id, None);
+        assert_eq!(order.filled_quantity, 0);
+        assert_eq!(order.average_filled_price, None);
+    }
 
-    // Assert Account Overview
-    let account = trust.search_account("alpaca").unwrap();
-    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
-    assert_eq!(balance.currency, Currency::USD);
-    assert_eq!(balance.total_available, dec!(56500.0)); // Including the 50 USD from the difference of the target unit price and average filled price
-    assert_eq!(balance.total_balance, dec!(56500.0));
-    assert_eq!(balance.total_in_trade, dec!(0));
-    assert_eq!(balance.taxed, dec!(0));
+    // Nothing of the source trade is copied into the balance of the new trade
+    assert_eq!(trade.balance.funding, dec!(0));
+    assert_eq!(trade.balance.total_performance, dec!(0));
+    assert_eq!(
+        trust.search_trades(account.id, Status::New).unwrap(),
+        vec![trade]
+    );
 }
 
 #[test]
-fn test_trade_stop_filled() {
-    let (trust, account, trade) = create_trade(BrokerResponse::orders_stop_filled, None);
+fn test_record_rebate() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_target_filled, None);
     let mut trust = trust;
-
-    // 9. Sync trade with the Broker - Target is filled
     trust.sync_trade(&trade, &account).unwrap();
-
     let trade = trust
-        .search_trades(account.id, Status::ClosedStopLoss)
+        .search_trades(account.id, Status::ClosedTarget)
         .unwrap()
         .first()
         .unwrap()
         .clone();
 
-    assert_stop_filled(&trade, &mut trust);
-}
+    let (tx, balance) = trust.record_rebate(&trade, dec!(3)).unwrap();
+    assert_eq!(tx.category, TransactionCategory::FeeRebate(trade.id));
+    assert_eq!(balance.total_available, dec!(56503));
+    assert_eq!(balance.total_balance, dec!(56503));
 
-#[test]
-fn test_trade_stop_filled_multiple_times() {
-    let (trust, account, trade) = create_trade(BrokerResponse::orders_stop_filled, None);
-    let mut trust = trust;
+    // The rebate makes the net fees negative and the net P&L exceeds the gross by the rebate.
+    assert_eq!(trust.trade_net_fees(&trade).unwrap(), dec!(-3));
+    let trade = trust
+        .search_trades(account.id, Status::ClosedTarget)
+        .unwrap()
+        .first()
+        .unwrap()
+        .clone();
+    assert_eq!(trade.balance.total_performance, dec!(6503));
 
-    // 9. Sync trade with the Broker - Target is filled
-    for _ in 0..10 {
-        trust.sync_trade(&trade, &account).unwrap();
-    }
+    trust
+        .record_rebate(&trade, dec!(0))
+        .expect_err("A rebate must be positive");
+}
+
+#[test]
+fn test_fee_model_is_applied_on_fills() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_target_filled, None);
+    let mut trust = trust;
+    let account = trust
+        .set_fee_model(&account, FeeModel::PerShare(dec!(0.005)))
+        .unwrap();
 
+    trust.sync_trade(&trade, &account).unwrap();
     let trade = trust
-        .search_trades(account.id, Status::ClosedStopLoss)
+        .search_trades(account.id, Status::ClosedTarget)
         .unwrap()
         .first()
         .unwrap()
         .clone();
 
-    assert_stop_filled(&trade, &mut trust);
-}
+    // 500 shares are charged when the entry and the target are filled.
+    assert_eq!(trust.trade_net_fees(&trade).unwrap(), dec!(5));
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(56495));
 
-fn assert_stop_filled(trade: &Trade, trust: &mut TrustFacade) {
-    assert_eq!(trade.status, Status::ClosedStopLoss);
+    trust
+        .set_fee_model(&account, FeeModel::Fixed(dec!(-1)))
+        .expect_err("A fee model can not pay the fills");
+}
 
-    // Assert Entry
-    assert_eq!(trade.entry.quantity, 500);
-    assert_eq!(trade.entry.unit_price, dec!(40));
-    assert_eq!(trade.entry.average_filled_price, Some(dec!(39.9)));
-    assert_eq!(trade.entry.filled_quantity, 500);
-    assert_eq!(trade.entry.status, OrderStatus::Filled);
+#[test]
+fn test_realized_drawdown_of_a_losing_trade() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_stop_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
 
-    // Assert Target
-    assert_eq!(trade.target.quantity, 500);
-    assert_eq!(trade.target.unit_price, dec!(50));
-    assert_eq!(trade.target.average_filled_price, None);
-    assert_eq!(trade.target.filled_quantity, 0);
-    assert_eq!(trade.target.status, OrderStatus::Canceled);
+    let snapshots = trust.rebuild_equity_snapshots(account.id).unwrap();
+    assert_eq!(snapshots.len(), 2);
+    assert_eq!(snapshots[0].balance, dec!(50000));
+    assert_eq!(snapshots[1].balance, dec!(49550)); // 500 * (39 - 39.9)
 
-    // Assert Stop
-    assert_eq!(trade.safety_stop.quantity, 500);
-    assert_eq!(trade.safety_stop.unit_price, dec!(38));
-    assert_eq!(trade.safety_stop.average_filled_price, Some(dec!(39)));
-    assert_eq!(trade.safety_stop.filled_quantity, 500);
-    assert_eq!(trade.safety_stop.status, OrderStatus::Filled);
+    let drawdown = trust.realized_drawdown(account.id, &Currency::USD).unwrap();
+    assert_eq!(drawdown.peak, dec!(50000));
+    assert_eq!(drawdown.max_drawdown, dec!(450));
+    assert_eq!(drawdown.current_drawdown, dec!(450));
 
-    // Assert Account Overview
-    let account = trust.search_account("alpaca").unwrap();
-    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
-    assert_eq!(balance.currency, Currency::USD);
-    assert_eq!(balance.total_available, dec!(49550.0)); // Including the 50 USD from the difference of the target unit price and average filled price
-    assert_eq!(balance.total_balance, dec!(49550.0));
-    assert_eq!(balance.total_in_trade, dec!(0));
-    assert_eq!(balance.taxed, dec!(0));
+    // Withdrawals after the last snapshot are not losses
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Withdrawal,
+            dec!(10000),
+            &Currency::USD,
+        )
+        .unwrap();
+    assert_eq!(
+        trust.realized_drawdown(account.id, &Currency::USD).unwrap(),
+        drawdown
+    );
 }
 
 #[test]
-fn test_trade_stop_filled_slippage() {
-    let (trust, account, trade) = create_trade(BrokerResponse::orders_stop_filled_slippage, None);
+fn test_evaluate_level_adjustments_after_a_losing_trade() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_stop_filled, None);
     let mut trust = trust;
-
-    // 9. Sync trade with the Broker - Target is filled
+    trust
+        .update_level_adjustment_rules(
+            account.id,
+            &LevelAdjustmentRules {
+                monthly_loss_downgrade_pct: dec!(0.5),
+            },
+        )
+        .unwrap();
     trust.sync_trade(&trade, &account).unwrap();
 
+    let change = trust
+        .evaluate_level_adjustments(account.id)
+        .unwrap()
+        .expect("A realized loss of 0.9% must downgrade the account");
+    assert_eq!(change.new_level, change.old_level - 1);
+    assert_eq!(change.trigger, LevelTrigger::DrawdownBreach);
+    assert_eq!(
+        change.reason,
+        "Drawdown of 0.90% in USD exceeds the maximum of 0.5%"
+    );
+
+    // Evaluating the same breach again does not downgrade the account twice
+    assert_eq!(trust.evaluate_level_adjustments(account.id).unwrap(), None);
+    assert_eq!(trust.level_history(account.id).unwrap(), vec![change]);
+}
+
+#[test]
+fn test_execute_distribution() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_target_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
     let trade = trust
-        .search_trades(account.id, Status::ClosedStopLoss)
+        .search_trades(account.id, Status::ClosedTarget)
         .unwrap()
         .first()
         .unwrap()
         .clone();
 
-    assert_eq!(trade.status, Status::ClosedStopLoss);
-
-    // Assert Stop
-    assert_eq!(trade.safety_stop.quantity, 500);
-    assert_eq!(trade.safety_stop.unit_price, dec!(38));
-    assert_eq!(trade.safety_stop.average_filled_price, Some(dec!(30.2)));
-    assert_eq!(trade.safety_stop.filled_quantity, 500);
-    assert_eq!(trade.safety_stop.status, OrderStatus::Filled);
+    trust
+        .configure_distribution(account.id, dec!(0.3), dec!(0.2), dec!(0.5), dec!(0))
+        .unwrap();
+    let history = trust.execute_distribution(&trade).unwrap();
+
+    assert_eq!(history.trade_id, trade.id);
+    assert_eq!(history.original_amount, dec!(6500)); // 26450 - 19950
+    assert_eq!(history.earnings_amount, dec!(1950));
+    assert_eq!(history.tax_amount, dec!(1300));
+    assert_eq!(history.reinvestment_amount, dec!(3250));
+    assert_eq!(
+        trust.distribution_history(account.id).unwrap(),
+        vec![history]
+    );
 
-    // Assert Account Overview
-    let account = trust.search_account("alpaca").unwrap();
     let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
-    assert_eq!(balance.currency, Currency::USD);
-    assert_eq!(balance.total_available, dec!(45150.0)); // Including the 50 USD from the difference of the target unit price and average filled price
-    assert_eq!(balance.total_balance, dec!(45150.0));
-    assert_eq!(balance.total_in_trade, dec!(0));
-    assert_eq!(balance.taxed, dec!(0));
+    assert_eq!(balance.taxed, dec!(1300));
+    assert_eq!(balance.total_balance, dec!(56500)); // The legs earmark the profit, it is still in the account.
+
+    trust
+        .execute_distribution(&trade)
+        .expect_err("The profit of a trade can only be distributed once");
 }
 
 #[test]
-fn test_trade_close() {
-    let (trust, account, trade) = create_trade(
-        BrokerResponse::orders_entry_filled,
-        Some(BrokerResponse::closed_order),
-    );
+fn test_configure_distribution_with_tolerance() {
+    let (trust, account, _) = create_trade(BrokerResponse::orders_target_filled, None);
     let mut trust = trust;
 
-    // 1. Sync trade with the Broker - Entry is filled
+    let rules = trust
+        .configure_distribution(
+            account.id,
+            dec!(0.3333),
+            dec!(0.3333),
+            dec!(0.3334),
+            dec!(0.001),
+        )
+        .unwrap();
+    assert_eq!(rules.earnings_percent, dec!(0.3333));
+    assert_eq!(rules.tax_percent, dec!(0.3333));
+    assert_eq!(rules.reinvestment_percent, dec!(0.3334));
+
+    // The remainder goes to the largest percentage
+    let rules = trust
+        .configure_distribution(
+            account.id,
+            dec!(0.3333),
+            dec!(0.3333),
+            dec!(0.333),
+            dec!(0.001),
+        )
+        .unwrap();
+    assert_eq!(rules.earnings_percent, dec!(0.3337));
+    assert_eq!(rules.tax_percent, dec!(0.3333));
+    assert_eq!(rules.reinvestment_percent, dec!(0.333));
+
     trust
-        .sync_trade(&trade, &account)
-        .expect("Failed to sync trade with broker when entry is filled");
+        .configure_distribution(account.id, dec!(0.30), dec!(0.25), dec!(0.40), dec!(0.001))
+        .expect_err("The percentages sum 0.95");
+}
+
+#[test]
+fn test_close_trade_with_auto_distribution() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_target_filled, None);
+    let mut trust = trust;
+    trust
+        .close_trade_with_auto_distribution(&trade)
+        .expect_err("The trade is not closed yet");
+
+    trust.sync_trade(&trade, &account).unwrap();
+    trust
+        .configure_distribution(account.id, dec!(0.3), dec!(0.2), dec!(0.5), dec!(0))
+        .unwrap();
+    let (history, grade) = trust.close_trade_with_auto_distribution(&trade).unwrap();
+
+    assert_eq!(history.unwrap().original_amount, dec!(6500));
+    assert_eq!(grade.trade_id, trade.id);
+    assert_eq!(grade.outcome, 100);
+    assert_eq!(grade.documentation, 0); // It has no thesis, strategy or reference
+    assert_eq!(grade.overall, 80);
+    assert_eq!(trust.trade_grade(trade.id).unwrap(), grade);
+}
+
+#[test]
+fn test_close_trade_with_auto_distribution_stop_filled() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_stop_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+    trust
+        .configure_distribution(account.id, dec!(0.3), dec!(0.2), dec!(0.5), dec!(0))
+        .unwrap();
+
+    // There is no profit to distribute, but the trade is graded
+    let (history, grade) = trust.close_trade_with_auto_distribution(&trade).unwrap();
+    assert!(history.is_none());
+    assert_eq!(grade.outcome, 0);
+    assert!(grade.overall < 80);
+    assert!(trust.distribution_history(account.id).unwrap().is_empty());
+}
+
+#[test]
+fn test_promote_account_with_min_paper_trades() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_target_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+
+    // The paper account has one closed trade
+    let account = trust
+        .set_min_paper_trades_before_live(&account, Some(2))
+        .unwrap();
+    let error = trust
+        .promote_account(&account, "live", "real money")
+        .expect_err("The paper account has not closed enough trades");
+    assert!(error.to_string().contains("1 closed trades and it needs 2"));
+    assert!(trust.search_account("live").is_err());
+
+    let account = trust
+        .set_min_paper_trades_before_live(&account, Some(1))
+        .unwrap();
+    let live = trust
+        .promote_account(&account, "live", "real money")
+        .unwrap();
+    assert_eq!(live.environment, model::Environment::Live);
+    assert_eq!(live.paper_account_id, Some(account.id));
+    assert_eq!(live.taxes_percentage, account.taxes_percentage);
+    assert_eq!(live.earnings_percentage, account.earnings_percentage);
+}
 
+#[test]
+fn test_execute_distribution_failing_leg_rolls_back() {
+    let (trust, account, trade) = create_trade_with_database(
+        Box::new(FailingReinvestmentDatabase {
+            database: SqliteDatabase::new_in_memory(),
+        }),
+        BrokerResponse::orders_target_filled,
+        None,
+    );
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
     let trade = trust
-        .search_trades(account.id, Status::Filled)
-        .expect("Failed to find trade with status submitted 2")
+        .search_trades(account.id, Status::ClosedTarget)
+        .unwrap()
         .first()
         .unwrap()
         .clone();
 
-    // 2. Close the trade at market price
-    let (_, _log) = trust.close_trade(&trade).unwrap();
+    trust
+        .configure_distribution(account.id, dec!(0.3), dec!(0.2), dec!(0.5), dec!(0))
+        .unwrap();
+    trust
+        .execute_distribution(&trade)
+        .expect_err("The reinvestment leg fails");
+
+    // The earnings and tax legs were written before the failure and they are rolled back.
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.taxed, dec!(0));
+    assert_eq!(balance.total_available, dec!(56500));
+    assert_eq!(balance.total_balance, dec!(56500));
 
     let trade = trust
-        .search_trades(account.id, Status::Canceled)
-        .expect("Failed to find trade with status submitted 2")
+        .search_trades(account.id, Status::ClosedTarget)
+        .unwrap()
         .first()
         .unwrap()
         .clone();
+    assert_eq!(trade.balance.taxed, dec!(0));
+    assert_eq!(trade.balance.total_performance, dec!(6500));
 
-    // Assert Trade Overview
-    assert_eq!(trade.status, Status::Canceled); // The trade is still filled, but the target was changed to a market order
+    assert!(trust.distribution_history(account.id).unwrap().is_empty());
+}
 
-    // Assert Entry
-    assert_eq!(trade.entry.quantity, 500);
-    assert_eq!(trade.entry.unit_price, dec!(40));
-    assert_eq!(trade.entry.average_filled_price, Some(dec!(39.9)));
-    assert_eq!(trade.entry.filled_quantity, 500);
-    assert_eq!(trade.entry.status, OrderStatus::Filled);
+#[test]
+fn test_recompute_trade_balance() {
+    let database = SqliteDatabase::new_in_memory();
+    let (mut trust, account, trade) = create_trade_with_database(
+        Box::new(database.clone()),
+        BrokerResponse::orders_target_filled,
+        None,
+    );
+    trust.sync_trade(&trade, &account).unwrap();
+    let trade = trust
+        .search_trades(account.id, Status::ClosedTarget)
+        .unwrap()[0]
+        .clone();
+    let consistent = trade.balance.clone();
+
+    // The stored funding is doubled, like a trade that was funded twice by a bug
+    database
+        .trade_balance_write()
+        .update_trade_balance(
+            &trade,
+            consistent.funding * dec!(2),
+            consistent.capital_in_market,
+            consistent.capital_out_market,
+            consistent.taxed,
+            consistent.total_performance,
+        )
+        .unwrap();
+
+    let repair = trust.recompute_trade_balance(trade.id).unwrap();
+    assert_eq!(repair.balance.funding, dec!(20000));
+    assert_eq!(repair.funding_delta, dec!(-20000));
+    assert_eq!(repair.capital_in_market_delta, dec!(0));
+    assert_eq!(repair.capital_out_market_delta, dec!(0));
+    assert_eq!(repair.taxed_delta, dec!(0));
+    assert_eq!(repair.total_performance_delta, dec!(0));
+    assert_eq!(repair.balance.total_performance, dec!(6500));
+
+    // The corrected balance is stored, so recomputing it again does not change anything
+    let trade = trust
+        .search_trades(account.id, Status::ClosedTarget)
+        .unwrap()[0]
+        .clone();
+    assert_eq!(trade.balance.funding, consistent.funding);
+    let repair = trust.recompute_trade_balance(trade.id).unwrap();
+    assert_eq!(repair.funding_delta, dec!(0));
+    assert_eq!(repair.balance.funding, consistent.funding);
+
+    assert!(trust.recompute_trade_balance(Uuid::new_v4()).is_err());
+}
+
+#[test]
+fn test_trades_needing_sync() {
+    let clock = SteppableClock::new(
+        NaiveDate::from_ymd_opt(2023, 1, 20)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap(),
+    );
+    let (mut trust, account, trade) =
+        create_trade_with_clock(BrokerResponse::orders_accepted, Arc::new(clock.clone()));
+    let staleness = chrono::Duration::minutes(5);
+
+    // A trade that was never synced needs a sync
+    assert_eq!(trade.last_synced_at, None);
+    let trades = trust.trades_needing_sync(account.id, staleness).unwrap();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].id, trade.id);
+
+    // A trade that was just synced does not
+    trust.sync_trade(&trade, &account).unwrap();
+    let synced = trust.search_trades(account.id, Status::Submitted).unwrap()[0].clone();
+    assert_eq!(synced.last_synced_at, Some(clock.now()));
+    clock.advance(chrono::Duration::minutes(4));
+    assert!(trust
+        .trades_needing_sync(account.id, staleness)
+        .unwrap()
+        .is_empty());
+
+    // Once the last sync is older than the staleness, it needs a sync again
+    clock.advance(chrono::Duration::minutes(2));
+    let trades = trust.trades_needing_sync(account.id, staleness).unwrap();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].id, trade.id);
+}
+
+#[test]
+fn test_writes_are_stamped_with_the_clock() {
+    let start = NaiveDate::from_ymd_opt(2023, 1, 20)
+        .unwrap()
+        .and_hms_opt(10, 0, 0)
+        .unwrap();
+    let clock = SteppableClock::new(start);
+    let (mut trust, account, trade) =
+        create_trade_with_clock(BrokerResponse::orders_entry_filled, Arc::new(clock.clone()));
+
+    assert_eq!(account.created_at, start);
+    assert_eq!(trade.created_at, start);
+    assert_eq!(trade.updated_at, start);
+    assert_eq!(trade.balance.updated_at, start);
+    assert!(trust
+        .search_all_rules(account.id)
+        .unwrap()
+        .iter()
+        .all(|rule| rule.created_at == start));
+    assert_eq!(
+        trust
+            .search_balance(account.id, &Currency::USD)
+            .unwrap()
+            .updated_at,
+        start
+    );
+
+    clock.advance(chrono::Duration::hours(1));
+    trust.sync_trade(&trade, &account).unwrap();
+
+    let filled = trust.search_trades(account.id, Status::Filled).unwrap()[0].clone();
+    assert_eq!(filled.created_at, start);
+    assert_eq!(filled.updated_at, clock.now());
+    assert_eq!(filled.balance.updated_at, clock.now());
+}
+
+#[test]
+fn test_closed_trades_do_not_need_sync() {
+    let (mut trust, account, trade) = create_trade(BrokerResponse::orders_target_filled, None);
+    trust.sync_trade(&trade, &account).unwrap();
+
+    assert!(trust
+        .trades_needing_sync(account.id, chrono::Duration::zero())
+        .unwrap()
+        .is_empty());
+}
+
+thread_local! {
+    static SYNCS: Cell<u32> = const { Cell::new(0) };
+    static CANCELED_ORDERS: RefCell<Vec<Uuid>> = const { RefCell::new(Vec::new()) };
+    static NOT_WORKING_ORDERS: RefCell<Vec<Uuid>> = const { RefCell::new(Vec::new()) };
+    static REPLACE_ERROR: Cell<Option<BrokerErrorCode>> = const { Cell::new(None) };
+    static BROKER_CALLS: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+    static HALTED_TRADES: RefCell<Vec<Uuid>> = const { RefCell::new(Vec::new()) };
+    static TRADE_WINS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// The target is filled on the third sync with the broker.
+fn orders_target_filled_on_third_sync(trade: &Trade) -> (Status, Vec<Order>) {
+    let syncs = SYNCS.with(|syncs| {
+        syncs.set(syncs.get() + 1);
+        syncs.get()
+    });
+    if syncs < 3 {
+        BrokerResponse::orders_accepted(trade)
+    } else {
+        BrokerResponse::orders_target_filled(trade)
+    }
+}
+
+#[test]
+fn test_poll_until_settled() {
+    let (trust, account, trade) = create_trade(orders_target_filled_on_third_sync, None);
+    let mut trust = trust;
+
+    let trade = trust
+        .poll_until_settled(
+            &trade,
+            &account,
+            Duration::from_millis(1),
+            Duration::from_secs(5),
+        )
+        .unwrap();
+
+    assert_eq!(SYNCS.with(|syncs| syncs.get()), 3);
+    assert_target_filled(&trade, &mut trust);
+}
+
+/// The entry is filled while the trade is submitted, and the target once the trade is filled.
+fn orders_target_filled_after_the_entry(trade: &Trade) -> (Status, Vec<Order>) {
+    if trade.status == Status::Filled {
+        BrokerResponse::orders_target_filled(trade)
+    } else {
+        BrokerResponse::orders_entry_filled(trade)
+    }
+}
+
+#[test]
+fn test_poll_until_settled_syncs_the_updated_trade() {
+    let (trust, account, trade) = create_trade(orders_target_filled_after_the_entry, None);
+    let mut trust = trust;
+
+    let trade = trust
+        .poll_until_settled(
+            &trade,
+            &account,
+            Duration::from_millis(1),
+            Duration::from_secs(5),
+        )
+        .unwrap();
+
+    assert_target_filled(&trade, &mut trust);
+}
+
+/// The entry is filled on the first sync with the broker and the target on the second one.
+fn orders_target_filled_on_second_sync(trade: &Trade) -> (Status, Vec<Order>) {
+    let syncs = SYNCS.with(|syncs| {
+        syncs.set(syncs.get() + 1);
+        syncs.get()
+    });
+    if syncs < 2 {
+        BrokerResponse::orders_entry_filled(trade)
+    } else {
+        BrokerResponse::orders_target_filled(trade)
+    }
+}
+
+#[test]
+fn test_trade_timeline() {
+    let (trust, account, trade) = create_trade(orders_target_filled_on_second_sync, None);
+    let mut trust = trust;
+
+    // The entry is partially filled before the broker reports it as filled
+    trust
+        .backfill_execution(
+            trade.id,
+            ExecutionSide::Buy,
+            dec!(39.8),
+            250,
+            Utc::now().naive_utc(),
+            ExecutionSource::Manual,
+        )
+        .unwrap();
+    trust.sync_trade(&trade, &account).unwrap();
+    let trade = trust.search_trades(account.id, Status::Filled).unwrap()[0].clone();
+    trust.modify_stop(&trade, &account, dec!(39)).unwrap();
+    trust.sync_trade(&trade, &account).unwrap();
+
+    let timeline = trust.trade_timeline(trade.id).unwrap();
+    let kinds: Vec<&TimelineEventKind> = timeline
+        .iter()
+        .map(|event| &event.kind)
+        .filter(|kind| !matches!(kind, TimelineEventKind::BrokerLog(_)))
+        .collect();
+
+    assert_eq!(kinds.len(), 6);
+    assert_eq!(kinds[0], &TimelineEventKind::Created);
+    assert_eq!(kinds[1], &TimelineEventKind::Funded);
+    assert_eq!(kinds[2], &TimelineEventKind::Submitted);
+    assert!(
+        matches!(kinds[3], TimelineEventKind::Execution(execution) if execution.quantity == 250)
+    );
+    assert_eq!(kinds[4], &TimelineEventKind::StopModified(dec!(39)));
+    assert_eq!(kinds[5], &TimelineEventKind::ClosedTarget);
+
+    // The two syncs are in the timeline as well
+    assert_eq!(timeline.len(), 8);
+    assert!(timeline
+        .windows(2)
+        .all(|events| events[0].timestamp <= events[1].timestamp));
+}
+
+#[test]
+fn test_revise_trade_thesis() {
+    let (trust, _, trade) = create_trade(BrokerResponse::orders_accepted, None);
+    let mut trust = trust;
+
+    let revised = trust
+        .revise_trade_thesis(
+            trade.id,
+            Some("Breakout above the range of the last month".to_string()),
+            Some("Earnings next week".to_string()),
+        )
+        .unwrap();
+    assert_eq!(
+        revised.thesis.as_deref(),
+        Some("Breakout above the range of the last month")
+    );
+    assert_eq!(revised.context.as_deref(), Some("Earnings next week"));
+    assert_eq!(revised.status, trade.status);
+    assert_eq!(revised.entry, trade.entry);
+
+    // The context is kept if it is not given
+    let revised = trust
+        .revise_trade_thesis(trade.id, Some("Failed breakout".to_string()), None)
+        .unwrap();
+    assert_eq!(revised.thesis.as_deref(), Some("Failed breakout"));
+    assert_eq!(revised.context.as_deref(), Some("Earnings next week"));
+
+    let history = trust.thesis_history(trade.id).unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].thesis, None);
+    assert_eq!(history[0].context, None);
+    assert_eq!(
+        history[1].thesis.as_deref(),
+        Some("Breakout above the range of the last month")
+    );
+    assert_eq!(history[1].context.as_deref(), Some("Earnings next week"));
+}
+
+#[test]
+fn test_revise_trade_thesis_too_long() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_accepted, None);
+    let mut trust = trust;
+
+    trust
+        .revise_trade_thesis(trade.id, Some("a".repeat(201)), None)
+        .expect_err("The thesis can have at most 200 characters");
+
+    let trade = trust.search_trades(account.id, Status::Submitted).unwrap()[0].clone();
+    assert_eq!(trade.thesis, None);
+    assert!(trust.thesis_history(trade.id).unwrap().is_empty());
+}
+
+#[test]
+fn test_revise_thesis_of_closed_trade() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_target_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+
+    trust
+        .revise_trade_thesis(trade.id, Some("Too late".to_string()), None)
+        .expect_err("The thesis of a closed trade can not be revised");
+}
+
+#[test]
+fn test_poll_until_settled_timeout() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_accepted, None);
+    let mut trust = trust;
+
+    let trade = trust
+        .poll_until_settled(
+            &trade,
+            &account,
+            Duration::from_millis(5),
+            Duration::from_millis(20),
+        )
+        .unwrap();
+
+    assert_eq!(trade.status, Status::Submitted);
+}
+
+#[test]
+fn test_trade_target_filled_multiple_times() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_target_filled, None);
+    let mut trust = trust;
+
+    // 9. Sync trade with the Broker - Target is filled
+    for _ in 0..10 {
+        trust.sync_trade(&trade, &account).unwrap();
+    }
+
+    let trade = trust
+        .search_trades(account.id, Status::ClosedTarget)
+        .unwrap()
+        .first()
+        .unwrap()
+        .clone();
+
+    assert_target_filled(&trade, &mut trust);
+}
+
+#[test]
+fn test_suggested_quantity_profitable_system() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_target_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+
+    let size = trust
+        .suggested_quantity(account.id, dec!(40), dec!(38), &Currency::USD)
+        .unwrap();
+    let maximum = trust
+        .calculate_maximum_quantity(account.id, dec!(40), dec!(38), &Currency::USD)
+        .unwrap();
+
+    // A system without losses has a Kelly criterion of 1, so half Kelly is used
+    assert_eq!(size.conservative, maximum);
+    assert_eq!(size.kelly_capped, maximum / 2);
+    assert!(size.kelly_capped <= size.conservative);
+}
+
+#[test]
+fn test_risk_of_ruin_without_losses() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_target_filled, None);
+    let mut trust = trust;
+    trust
+        .risk_of_ruin(account.id, dec!(0.02))
+        .expect_err("There are no closed trades yet");
+
+    trust.sync_trade(&trade, &account).unwrap();
+
+    // A system that never lost can not be ruined
+    assert_eq!(trust.risk_of_ruin(account.id, dec!(0.02)).unwrap(), dec!(0));
+}
+
+fn assert_target_filled(trade: &Trade, trust: &mut TrustFacade) {
+    assert_eq!(trade.status, Status::ClosedTarget);
+
+    // Assert Entry
+    assert_eq!(trade.entry.quantity, 500);
+    assert_eq!(trade.entry.unit_price, dec!(40));
+    assert_eq!(trade.entry.average_filled_price, Some(dec!(39.9)));
+    assert_eq!(trade.entry.filled_quantity, 500);
+    assert_eq!(trade.entry.status, OrderStatus::Filled);
+
+    // Assert Target
+    assert_eq!(trade.target.quantity, 500);
+    assert_eq!(trade.target.unit_price, dec!(50));
+    assert_eq!(trade.target.average_filled_price, Some(dec!(52.9)));
+    assert_eq!(trade.target.filled_quantity, 500);
+    assert_eq!(trade.target.status, OrderStatus::Filled);
+
+    // Assert Stop
+    assert_eq!(trade.safety_stop.quantity, 500);
+    assert_eq!(trade.safety_stop.unit_price, dec!(38));
+    assert_eq!(trade.safety_stop.average_filled_price, None);
+    assert_eq!(trade.safety_stop.filled_quantity, 0);
+    assert_eq!(trade.safety_stop.status, OrderStatus::Canceled);
+
+    // Assert Account Overview
+    let account = trust.search_account("alpaca").unwrap();
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.currency, Currency::USD);
+    assert_eq!(balance.total_available, dec!(56500.0)); // Including the 50 USD from the difference of the target unit price and average filled price
+    assert_eq!(balance.total_balance, dec!(56500.0));
+    assert_eq!(balance.total_in_trade, dec!(0));
+    assert_eq!(balance.taxed, dec!(0));
+}
+
+#[test]
+fn test_trade_stop_filled() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_stop_filled, None);
+    let mut trust = trust;
+
+    // 9. Sync trade with the Broker - Target is filled
+    trust.sync_trade(&trade, &account).unwrap();
+
+    let trade = trust
+        .search_trades(account.id, Status::ClosedStopLoss)
+        .unwrap()
+        .first()
+        .unwrap()
+        .clone();
+
+    assert_stop_filled(&trade, &mut trust);
+}
+
+#[test]
+fn test_trade_stop_filled_multiple_times() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_stop_filled, None);
+    let mut trust = trust;
+
+    // 9. Sync trade with the Broker - Target is filled
+    for _ in 0..10 {
+        trust.sync_trade(&trade, &account).unwrap();
+    }
+
+    let trade = trust
+        .search_trades(account.id, Status::ClosedStopLoss)
+        .unwrap()
+        .first()
+        .unwrap()
+        .clone();
+
+    assert_stop_filled(&trade, &mut trust);
+}
+
+fn assert_stop_filled(trade: &Trade, trust: &mut TrustFacade) {
+    assert_eq!(trade.status, Status::ClosedStopLoss);
+
+    // Assert Entry
+    assert_eq!(trade.entry.quantity, 500);
+    assert_eq!(trade.entry.unit_price, dec!(40));
+    assert_eq!(trade.entry.average_filled_price, Some(dec!(39.9)));
+    assert_eq!(trade.entry.filled_quantity, 500);
+    assert_eq!(trade.entry.status, OrderStatus::Filled);
+
+    // Assert Target
+    assert_eq!(trade.target.quantity, 500);
+    assert_eq!(trade.target.unit_price, dec!(50));
+    assert_eq!(trade.target.average_filled_price, None);
+    assert_eq!(trade.target.filled_quantity, 0);
+    assert_eq!(trade.target.status, OrderStatus::Canceled);
+
+    // Assert Stop
+    assert_eq!(trade.safety_stop.quantity, 500);
+    assert_eq!(trade.safety_stop.unit_price, dec!(38));
+    assert_eq!(trade.safety_stop.average_filled_price, Some(dec!(39)));
+    assert_eq!(trade.safety_stop.filled_quantity, 500);
+    assert_eq!(trade.safety_stop.status, OrderStatus::Filled);
+
+    // Assert Account Overview
+    let account = trust.search_account("alpaca").unwrap();
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.currency, Currency::USD);
+    assert_eq!(balance.total_available, dec!(49550.0)); // Including the 50 USD from the difference of the target unit price and average filled price
+    assert_eq!(balance.total_balance, dec!(49550.0));
+    assert_eq!(balance.total_in_trade, dec!(0));
+    assert_eq!(balance.taxed, dec!(0));
+}
+
+#[test]
+fn test_trade_stop_filled_slippage() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_stop_filled_slippage, None);
+    let mut trust = trust;
+
+    // 9. Sync trade with the Broker - Target is filled
+    trust.sync_trade(&trade, &account).unwrap();
+
+    let trade = trust
+        .search_trades(account.id, Status::ClosedStopLoss)
+        .unwrap()
+        .first()
+        .unwrap()
+        .clone();
+
+    assert_eq!(trade.status, Status::ClosedStopLoss);
+
+    // Assert Stop
+    assert_eq!(trade.safety_stop.quantity, 500);
+    assert_eq!(trade.safety_stop.unit_price, dec!(38));
+    assert_eq!(trade.safety_stop.average_filled_price, Some(dec!(30.2)));
+    assert_eq!(trade.safety_stop.filled_quantity, 500);
+    assert_eq!(trade.safety_stop.status, OrderStatus::Filled);
+
+    // Assert Account Overview
+    let account = trust.search_account("alpaca").unwrap();
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.currency, Currency::USD);
+    assert_eq!(balance.total_available, dec!(45150.0)); // Including the 50 USD from the difference of the target unit price and average filled price
+    assert_eq!(balance.total_balance, dec!(45150.0));
+    assert_eq!(balance.total_in_trade, dec!(0));
+    assert_eq!(balance.taxed, dec!(0));
+}
+
+#[test]
+fn test_trade_close() {
+    let (trust, account, trade) = create_trade(
+        BrokerResponse::orders_entry_filled,
+        Some(BrokerResponse::closed_order),
+    );
+    let mut trust = trust;
+
+    // 1. Sync trade with the Broker - Entry is filled
+    trust
+        .sync_trade(&trade, &account)
+        .expect("Failed to sync trade with broker when entry is filled");
+
+    let trade = trust
+        .search_trades(account.id, Status::Filled)
+        .expect("Failed to find trade with status submitted 2")
+        .first()
+        .unwrap()
+        .clone();
+
+    // 2. Close the trade at market price
+    let (_, _log) = trust.close_trade(&trade).unwrap();
+
+    let trade = trust
+        .search_trades(account.id, Status::Canceled)
+        .expect("Failed to find trade with status submitted 2")
+        .first()
+        .unwrap()
+        .clone();
+
+    // Assert Trade Overview
+    assert_eq!(trade.status, Status::Canceled); // The trade is still filled, but the target was changed to a market order
+
+    // Assert Entry
+    assert_eq!(trade.entry.quantity, 500);
+    assert_eq!(trade.entry.unit_price, dec!(40));
+    assert_eq!(trade.entry.average_filled_price, Some(dec!(39.9)));
+    assert_eq!(trade.entry.filled_quantity, 500);
+    assert_eq!(trade.entry.status, OrderStatus::Filled);
+
+    // Assert Target
+    assert_eq!(trade.target.quantity, 500);
+    assert_eq!(trade.target.unit_price, dec!(50));
+    assert_eq!(trade.target.average_filled_price, None);
+    assert_eq!(trade.target.category, OrderCategory::Market);
+    assert_eq!(trade.target.filled_quantity, 0);
+    assert_eq!(trade.target.status, OrderStatus::PendingNew);
+}
+
+#[test]
+fn test_trade_close_cancels_working_orders() {
+    let (mut trust, account, trade) = create_trade(
+        BrokerResponse::orders_entry_filled,
+        Some(BrokerResponse::closed_order),
+    );
+    trust.sync_trade(&trade, &account).unwrap();
+    let trade = trust.search_trades(account.id, Status::Filled).unwrap()[0].clone();
+
+    trust.close_trade(&trade).unwrap();
+
+    // The stop and the target are canceled in the broker before closing at market
+    assert_eq!(
+        CANCELED_ORDERS.with(|orders| orders.borrow().clone()),
+        vec![trade.safety_stop.id, trade.target.id]
+    );
+    let orders = trust.trade_orders(trade.id).unwrap();
+    assert_eq!(orders.stop.status, OrderStatus::Canceled);
+    assert!(orders.stop.cancelled_at.is_some());
+    assert_eq!(orders.target.category, OrderCategory::Market);
+}
+
+#[test]
+fn test_trade_close_when_the_broker_already_canceled_a_leg() {
+    let (mut trust, account, trade) = create_trade(
+        BrokerResponse::orders_entry_filled,
+        Some(BrokerResponse::closed_order),
+    );
+    trust.sync_trade(&trade, &account).unwrap();
+    let trade = trust.search_trades(account.id, Status::Filled).unwrap()[0].clone();
+
+    // The broker cancels the target with the stop, its OCO sibling
+    NOT_WORKING_ORDERS.with(|orders| orders.borrow_mut().push(trade.target.id));
+    trust.close_trade(&trade).unwrap();
+
+    assert_eq!(
+        CANCELED_ORDERS.with(|orders| orders.borrow().clone()),
+        vec![trade.safety_stop.id]
+    );
+    let orders = trust.trade_orders(trade.id).unwrap();
+    assert_eq!(orders.stop.status, OrderStatus::Canceled);
+    assert_eq!(orders.target.category, OrderCategory::Market);
+}
+
+#[test]
+fn test_trade_close_at_limit_validation() {
+    let (mut trust, account, trade) = create_trade(
+        BrokerResponse::orders_closed_at_limit,
+        Some(BrokerResponse::closed_order),
+    );
+
+    // A trade that is not filled can not be closed
+    let error = trust
+        .close_trade_at_limit(&trade, &account, dec!(45))
+        .err()
+        .unwrap();
+    assert!(error.to_string().contains("not filled"), "{}", error);
+
+    // The limit must be positive and above the stop of a long
+    trust.sync_trade(&trade, &account).unwrap();
+    let trade = trust.search_trades(account.id, Status::Filled).unwrap()[0].clone();
+    for limit in [dec!(0), dec!(-45), dec!(37), dec!(38)] {
+        assert!(trust.close_trade_at_limit(&trade, &account, limit).is_err());
+    }
+    assert!(CANCELED_ORDERS.with(|orders| orders.borrow().is_empty()));
+    assert_eq!(
+        trust.search_trades(account.id, Status::Filled).unwrap()[0].status,
+        Status::Filled
+    );
+}
+
+#[test]
+fn test_trade_close_at_limit_pending_then_filled() {
+    let (mut trust, account, trade) = create_trade(
+        BrokerResponse::orders_closed_at_limit,
+        Some(BrokerResponse::closed_order),
+    );
+    trust.sync_trade(&trade, &account).unwrap();
+    let trade = trust.search_trades(account.id, Status::Filled).unwrap()[0].clone();
+
+    // 1. Close at a limit: the trade is closing until the limit order is filled
+    trust
+        .close_trade_at_limit(&trade, &account, dec!(45))
+        .unwrap();
+    let trade = trust
+        .search_trades(account.id, Status::ClosingPending)
+        .unwrap()[0]
+        .clone();
+    assert_eq!(trade.target.category, OrderCategory::Limit);
+    assert_eq!(trade.target.unit_price, dec!(45));
+    assert_eq!(trade.target.status, OrderStatus::Accepted);
+    assert_eq!(trade.safety_stop.status, OrderStatus::Canceled);
+    assert_eq!(
+        CANCELED_ORDERS.with(|orders| orders.borrow().clone()),
+        vec![trade.safety_stop.id, trade.target.id]
+    );
+    assert!(trust
+        .search_trades(account.id, Status::ClosedTarget)
+        .unwrap()
+        .is_empty());
+
+    // 2. The sync finds the limit order filled and the trade is closed
+    trust.sync_trade(&trade, &account).unwrap();
+    assert!(trust
+        .search_trades(account.id, Status::ClosingPending)
+        .unwrap()
+        .is_empty());
+    let trade = trust
+        .search_trades(account.id, Status::ClosedTarget)
+        .unwrap()[0]
+        .clone();
+    assert_eq!(trade.target.average_filled_price, Some(dec!(45)));
+    assert_eq!(trade.target.status, OrderStatus::Filled);
+    assert_eq!(trade.balance.total_performance, dec!(2550));
+
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(52550));
+}
+
+#[test]
+fn test_trade_modify_stop_long() {
+    let (trust, account, trade) = create_trade(
+        BrokerResponse::orders_entry_filled,
+        Some(BrokerResponse::closed_order),
+    );
+    let mut trust = trust;
+
+    // 1. Sync trade with the Broker - Entry is filled
+    trust
+        .sync_trade(&trade, &account)
+        .expect("Failed to sync trade with broker when entry is filled");
+
+    let trade = trust
+        .search_trades(account.id, Status::Filled)
+        .expect("Failed to find trade with status submitted 2")
+        .first()
+        .unwrap()
+        .clone();
+
+    // 7. Modify stop
+    trust
+        .modify_stop(&trade, &account, dec!(39))
+        .expect("Failed to modify stop");
+
+    let trade = trust
+        .search_trades(account.id, Status::Filled)
+        .expect("Failed to find trade with status filled")
+        .first()
+        .unwrap()
+        .clone();
+
+    // Assert Trade Overview
+    assert_eq!(trade.status, Status::Filled); // The trade is still filled, but the stop was changed
+    assert_eq!(trade.safety_stop.unit_price, dec!(39));
+    assert_eq!(
+        trade.safety_stop.broker_order_id.unwrap(),
+        Uuid::parse_str("7654f70e-3b42-4014-a9ac-5a7101989aad").unwrap()
+    );
+}
+
+/// Syncs the trade created by `create_trade` until its entry is filled, and modifies its stop.
+fn modify_filled_stop(replace_error: Option<BrokerErrorCode>) -> (TrustFacade, Account, Trade) {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_entry_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+    let trade = trust.search_trades(account.id, Status::Filled).unwrap()[0].clone();
+
+    REPLACE_ERROR.with(|error| error.set(replace_error));
+    BROKER_CALLS.with(|calls| calls.borrow_mut().clear());
+    let _ = trust.modify_stop(&trade, &account, dec!(39));
+
+    let trade = trust.search_trades(account.id, Status::Filled).unwrap()[0].clone();
+    (trust, account, trade)
+}
+
+fn naked_window_logs(trust: &mut TrustFacade, trade: &Trade) -> Vec<String> {
+    trust
+        .trade_timeline(trade.id)
+        .unwrap()
+        .into_iter()
+        .filter_map(|event| match event.kind {
+            TimelineEventKind::BrokerLog(log) if log.starts_with("naked_window") => Some(log),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_trade_modify_stop_replaces_order() {
+    let (mut trust, _, trade) = modify_filled_stop(None);
+
+    assert_eq!(
+        BROKER_CALLS.with(|calls| calls.borrow().clone()),
+        vec!["replace_order"]
+    );
+    assert_eq!(trade.safety_stop.unit_price, dec!(39));
+    assert!(naked_window_logs(&mut trust, &trade).is_empty());
+}
+
+#[test]
+fn test_trade_modify_stop_when_replace_is_not_supported() {
+    let (mut trust, _, trade) = modify_filled_stop(Some(BrokerErrorCode::ReplaceNotSupported));
+
+    assert_eq!(
+        BROKER_CALLS.with(|calls| calls.borrow().clone()),
+        vec!["replace_order", "modify_stop"]
+    );
+    assert_eq!(trade.safety_stop.unit_price, dec!(39));
+
+    let logs = naked_window_logs(&mut trust, &trade);
+    assert_eq!(logs.len(), 1);
+    assert!(logs[0].starts_with(&format!("naked_window {} ", trade.safety_stop.id)));
+    assert!(logs[0].ends_with("ms"));
+}
+
+#[test]
+fn test_trade_modify_stop_when_replace_fails() {
+    let (mut trust, _, trade) = modify_filled_stop(Some(BrokerErrorCode::OrderNotWorking));
+
+    // The stop is only modified if the broker can not replace it, other errors are returned
+    assert_eq!(
+        BROKER_CALLS.with(|calls| calls.borrow().clone()),
+        vec!["replace_order"]
+    );
+    assert_eq!(trade.safety_stop.unit_price, dec!(38));
+    assert!(naked_window_logs(&mut trust, &trade).is_empty());
+}
+
+#[test]
+fn test_trade_modify_target() {
+    let (trust, account, trade) = create_trade(
+        BrokerResponse::orders_entry_filled,
+        Some(BrokerResponse::closed_order),
+    );
+    let mut trust = trust;
+
+    // 1. Sync trade with the Broker - Entry is filled
+    trust
+        .sync_trade(&trade, &account)
+        .expect("Failed to sync trade with broker when entry is filled");
+
+    let trade = trust
+        .search_trades(account.id, Status::Filled)
+        .expect("Failed to find trade with status submitted 2")
+        .first()
+        .unwrap()
+        .clone();
+
+    // 7. Modify stop
+    trust
+        .modify_target(&trade, &account, dec!(100.1))
+        .expect("Failed to modify stop");
+
+    let trade = trust
+        .search_trades(account.id, Status::Filled)
+        .expect("Failed to find trade with status filled")
+        .first()
+        .unwrap()
+        .clone();
+
+    // Assert Trade Overview
+    assert_eq!(trade.status, Status::Filled); // The trade is still filled, but the stop was changed
+    assert_eq!(trade.target.unit_price, dec!(100.1));
+    assert_eq!(
+        trade.target.broker_order_id.unwrap(),
+        Uuid::parse_str("5654f70e-3b42-4014-a9ac-5a7101989aad").unwrap()
+    );
+}
+
+#[test]
+fn test_reconcile_fees() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_entry_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+
+    let since = NaiveDate::from_ymd_opt(2023, 6, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    let until = NaiveDate::from_ymd_opt(2023, 6, 30)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    let reconciliation = trust.reconcile_fees(&account, since, until).unwrap();
+
+    // The fill is stored as an execution of the entry
+    assert_eq!(reconciliation.executions.len(), 1);
+    let execution = reconciliation.executions.first().unwrap();
+    assert_eq!(execution.trade_id, trade.id);
+    assert_eq!(execution.order_id, trade.entry.id);
+    assert_eq!(execution.price, dec!(39.9));
+    assert_eq!(execution.quantity, 500);
+
+    // The fee of the same day is allocated to the trade
+    assert_eq!(reconciliation.allocated_fees.len(), 1);
+    let fee = reconciliation.allocated_fees.first().unwrap();
+    assert_eq!(fee.category, TransactionCategory::FeeOpen(trade.id));
+    assert_eq!(fee.amount, dec!(0.02));
+
+    // The orphan fee is charged to the account
+    assert_eq!(reconciliation.unallocated_fees.len(), 1);
+    let fee = reconciliation.unallocated_fees.first().unwrap();
+    assert_eq!(fee.category, TransactionCategory::Fee);
+    assert_eq!(fee.amount, dec!(0.05));
+
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(30049.93)); // 30050 - 0.02 - 0.05
+}
+
+#[test]
+fn test_reconcile_fees_skips_fills_that_were_ingested() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_entry_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+    let since = NaiveDate::from_ymd_opt(2023, 6, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    let until = NaiveDate::from_ymd_opt(2023, 6, 30)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+
+    let first = trust.reconcile_fees(&account, since, until).unwrap();
+    let second = trust.reconcile_fees(&account, since, until).unwrap();
+
+    assert_eq!(first.executions.len(), 1);
+    assert_eq!(first.skipped_duplicates, 0);
+    assert!(second.executions.is_empty());
+    assert_eq!(second.skipped_duplicates, 1);
+    assert_eq!(
+        trust
+            .trade_timeline(trade.id)
+            .unwrap()
+            .iter()
+            .filter(|event| matches!(event.kind, TimelineEventKind::Execution(_)))
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn test_reconcile_fees_twice_charges_each_fee_once() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_entry_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+    let since = NaiveDate::from_ymd_opt(2023, 6, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    let until = NaiveDate::from_ymd_opt(2023, 6, 30)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+
+    let first = trust.reconcile_fees(&account, since, until).unwrap();
+    let second = trust.reconcile_fees(&account, since, until).unwrap();
+
+    assert_eq!(first.allocated_fees.len(), 1);
+    assert_eq!(first.unallocated_fees.len(), 1);
+    assert_eq!(first.skipped_fees, 0);
+    assert!(second.allocated_fees.is_empty());
+    assert!(second.unallocated_fees.is_empty());
+    assert_eq!(second.skipped_fees, 2);
+
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(30049.93)); // 30050 - 0.02 - 0.05
+}
+
+#[test]
+fn test_ingest_the_same_fill_twice() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_entry_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+    let fill = || DraftExecution {
+        broker_execution_id: None,
+        trade_id: trade.id,
+        order_id: trade.entry.id,
+        side: ExecutionSide::Buy,
+        price: dec!(39.9),
+        quantity: 500,
+        executed_at: NaiveDate::from_ymd_opt(2023, 6, 12)
+            .unwrap()
+            .and_hms_opt(14, 30, 0)
+            .unwrap(),
+        source: ExecutionSource::Broker,
+    };
+
+    let first = trust.ingest_executions(&[fill(), fill()]).unwrap();
+    let second = trust.ingest_executions(&[fill()]).unwrap();
+
+    // The fill has no id in the broker, so its order, price, quantity and time identify it
+    assert_eq!(first.executions.len(), 1);
+    assert_eq!(first.skipped_duplicates, 1);
+    assert!(second.executions.is_empty());
+    assert_eq!(second.skipped_duplicates, 1);
+
+    let executions: Vec<_> = trust
+        .trade_timeline(trade.id)
+        .unwrap()
+        .into_iter()
+        .filter(|event| matches!(event.kind, TimelineEventKind::Execution(_)))
+        .collect();
+    assert_eq!(executions.len(), 1);
+}
+
+#[test]
+fn test_monthly_risk_resets_when_the_clock_crosses_the_month() {
+    let clock = SteppableClock::new(
+        NaiveDate::from_ymd_opt(2023, 1, 20)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap(),
+    );
+    let (trust, account, trade) =
+        create_trade_with_clock(BrokerResponse::orders_stop_filled, Arc::new(clock.clone()));
+    let mut trust = trust;
+
+    // The stop is hit in January
+    trust.sync_trade(&trade, &account).unwrap();
+    let trade = trust
+        .search_trades(account.id, Status::ClosedStopLoss)
+        .unwrap()
+        .first()
+        .unwrap()
+        .clone();
+    assert_eq!(trade.closed_at(), clock.now());
+    assert_eq!(trade.balance.total_performance, dec!(-450));
+
+    // 6% of 49550 minus the 450 lost this month
+    let january = trust
+        .risk_budget_remaining(account.id, &Currency::USD)
+        .unwrap();
+    assert_eq!(january, dec!(2523));
+
+    // The loss of January does not count in February
+    clock.set(
+        NaiveDate::from_ymd_opt(2023, 2, 1)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap(),
+    );
+    let february = trust
+        .risk_budget_remaining(account.id, &Currency::USD)
+        .unwrap();
+    assert_eq!(february, dec!(2973));
+}
+
+#[test]
+fn test_rolling_performance() {
+    let day = |month, day| NaiveDate::from_ymd_opt(2023, month, day).unwrap();
+    let clock = SteppableClock::new(day(1, 20).and_hms_opt(10, 0, 0).unwrap());
+    let (trust, account, trade) =
+        create_trade_with_clock(BrokerResponse::orders_stop_filled, Arc::new(clock.clone()));
+    let mut trust = trust;
+
+    // The first stop is hit on January 20, then one trade is stopped on March 1 and on April 10.
+    trust.sync_trade(&trade, &account).unwrap();
+    for date in [day(3, 1), day(4, 10)] {
+        clock.set(date.and_hms_opt(10, 0, 0).unwrap());
+        let draft = DraftTrade {
+            account: account.clone(),
+            trading_vehicle: trade.trading_vehicle.clone(),
+            quantity: 100,
+            quantity_decimal: None,
+            currency: Currency::USD,
+            category: TradeCategory::Long,
+            strategy_id: None,
+            reference: None,
+            thesis: None,
+            context: None,
+        };
+        let new = trust
+            .create_trade(draft, dec!(38), dec!(40), dec!(50))
+            .unwrap();
+        trust.fund_trade(&new).unwrap();
+        let funded = trust.search_trades(account.id, Status::Funded).unwrap()[0].clone();
+        trust.submit_trade(&funded).unwrap();
+        let submitted = trust.search_trades(account.id, Status::Submitted).unwrap()[0].clone();
+        trust.sync_trade(&submitted, &account).unwrap();
+    }
+    let closed = trust
+        .search_trades(account.id, Status::ClosedStopLoss)
+        .unwrap();
+    assert_eq!(closed.len(), 3);
+
+    // 30 days until April 10 only include the last trade
+    let month = trust
+        .rolling_performance(account.id, 30, day(4, 10))
+        .unwrap();
+    assert_eq!(month.total_trades, 1);
+    assert_eq!(month.losing_trades, 1);
+
+    // 90 days until April 10 start on January 11
+    let quarter = trust
+        .rolling_performance(account.id, 90, day(4, 10))
+        .unwrap();
+    assert_eq!(quarter.total_trades, 3);
+    assert_eq!(
+        quarter.net_profit,
+        closed
+            .iter()
+            .map(|trade| trade.balance.total_performance)
+            .sum::<Decimal>()
+    );
+
+    // The trade of January 20 is out of 80 days until April 10, but not out of 81.
+    assert_eq!(
+        trust
+            .rolling_performance(account.id, 80, day(4, 10))
+            .unwrap()
+            .total_trades,
+        2
+    );
+    assert_eq!(
+        trust
+            .rolling_performance(account.id, 81, day(4, 10))
+            .unwrap()
+            .total_trades,
+        3
+    );
+
+    // A window without closed trades has zeroed statistics
+    let empty = trust
+        .rolling_performance(account.id, 30, day(6, 30))
+        .unwrap();
+    assert_eq!(empty.total_trades, 0);
+    assert_eq!(empty.win_rate, dec!(0));
+    assert_eq!(empty.net_profit, dec!(0));
+    assert_eq!(empty.average_loss, dec!(0));
+
+    trust
+        .rolling_performance(account.id, 0, day(4, 10))
+        .expect_err("A window must have at least one day");
+}
+
+/// Closes a trade that was opened with 100 shares at 40 and 50 shares at 43
+/// with one sale of 120 shares at 45, and returns the realized lots of 2023.
+fn realized_lots_of_two_lots_and_one_sale(method: LotMethod) -> Vec<RealizedLot> {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_target_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+    trust.set_lot_method(&account, method).unwrap();
+
+    let day = |day| {
+        NaiveDate::from_ymd_opt(2023, 6, day)
+            .unwrap()
+            .and_hms_opt(15, 0, 0)
+            .unwrap()
+    };
+    for (side, price, quantity, executed_at) in [
+        (ExecutionSide::Buy, dec!(40), 100, day(1)),
+        (ExecutionSide::Buy, dec!(43), 50, day(2)),
+        (ExecutionSide::Sell, dec!(45), 120, day(10)),
+    ] {
+        trust
+            .backfill_execution(
+                trade.id,
+                side,
+                price,
+                quantity,
+                executed_at,
+                ExecutionSource::Manual,
+            )
+            .unwrap();
+    }
+
+    assert!(trust.realized_lots(account.id, 2022).unwrap().is_empty());
+    trust.realized_lots(account.id, 2023).unwrap()
+}
+
+#[test]
+fn test_realized_lots_fifo() {
+    let lots = realized_lots_of_two_lots_and_one_sale(LotMethod::Fifo);
+
+    // The oldest lot is closed first and the newest one is split.
+    assert_eq!(lots.len(), 2);
+    assert_eq!(lots[0].quantity, 100);
+    assert_eq!(lots[0].cost_basis, dec!(4000));
+    assert_eq!(lots[0].proceeds, dec!(4500));
+    assert_eq!(lots[0].gain, dec!(500));
+    assert_eq!(lots[1].quantity, 20);
+    assert_eq!(lots[1].cost_basis, dec!(860));
+    assert_eq!(lots[1].proceeds, dec!(900));
+    assert_eq!(lots[1].gain, dec!(40));
+}
+
+#[test]
+fn test_realized_lots_lifo() {
+    let lots = realized_lots_of_two_lots_and_one_sale(LotMethod::Lifo);
+
+    // The newest lot is closed first and the oldest one is split.
+    assert_eq!(lots.len(), 2);
+    assert_eq!(lots[0].quantity, 50);
+    assert_eq!(lots[0].cost_basis, dec!(2150));
+    assert_eq!(lots[0].proceeds, dec!(2250));
+    assert_eq!(lots[0].gain, dec!(100));
+    assert_eq!(lots[1].quantity, 70);
+    assert_eq!(lots[1].cost_basis, dec!(2800));
+    assert_eq!(lots[1].proceeds, dec!(3150));
+    assert_eq!(lots[1].gain, dec!(350));
+}
+
+#[test]
+fn test_performance_by_strategy() {
+    let db = SqliteDatabase::new_in_memory();
+    let mut trust = TrustFacade::new(
+        Box::new(db),
+        Box::new(MockBroker::new(BrokerResponse::orders_target_filled, None)),
+    );
+    trust
+        .create_account(
+            "alpaca",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    let account = trust.search_account("alpaca").unwrap();
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(50000),
+            &Currency::USD,
+        )
+        .unwrap();
+    let tv = trust
+        .create_trading_vehicle(
+            "TSLA",
+            "US88160R1014",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
+        )
+        .unwrap();
+
+    let breakout = trust
+        .create_strategy(
+            "breakout",
+            "",
+            1,
+            "Buy the breakout",
+            "Below the range",
+            "2R",
+        )
+        .unwrap();
+    let pullback = trust
+        .create_strategy("pullback", "", 1, "Buy the pullback", "Below the low", "1R")
+        .unwrap();
+    assert_eq!(
+        trust.search_strategies().unwrap(),
+        vec![breakout.clone(), pullback.clone()]
+    );
+
+    for strategy_id in [
+        Some(breakout.id),
+        Some(breakout.id),
+        Some(pullback.id),
+        None,
+    ] {
+        let draft = DraftTrade {
+            account: account.clone(),
+            trading_vehicle: tv.clone(),
+            quantity: 500,
+            quantity_decimal: None,
+            currency: Currency::USD,
+            category: TradeCategory::Long,
+            strategy_id,
+            reference: None,
+            thesis: None,
+            context: None,
+        };
+        trust
+            .create_trade(draft, dec!(38), dec!(40), dec!(50))
+            .unwrap();
+        let trade = trust.search_trades(account.id, Status::New).unwrap()[0].clone();
+        assert_eq!(trade.strategy_id, strategy_id);
+        trust.fund_trade(&trade).unwrap();
+        let trade = trust.search_trades(account.id, Status::Funded).unwrap()[0].clone();
+        trust.submit_trade(&trade).unwrap();
+        let trade = trust.search_trades(account.id, Status::Submitted).unwrap()[0].clone();
+        trust.sync_trade(&trade, &account).unwrap();
+    }
+
+    let performance = trust.performance_by_strategy(account.id).unwrap();
+    assert_eq!(performance.len(), 2);
+
+    let (strategy, stats) = &performance[0];
+    assert_eq!(strategy, &breakout);
+    assert_eq!(stats.total_trades, 2);
+    assert_eq!(stats.winning_trades, 2);
+    assert_eq!(stats.win_rate, dec!(100));
+
+    let (strategy, pullback_stats) = &performance[1];
+    assert_eq!(strategy, &pullback);
+    assert_eq!(pullback_stats.total_trades, 1);
+    assert_eq!(pullback_stats.losing_trades, 0);
+    assert!(pullback_stats.net_profit > dec!(0));
+    assert_eq!(stats.net_profit, pullback_stats.net_profit * dec!(2));
+}
+
+#[test]
+fn test_average_r_by_strategy() {
+    let db = SqliteDatabase::new_in_memory();
+    let mut trust = TrustFacade::new(
+        Box::new(db),
+        Box::new(MockBroker::new(BrokerResponse::orders_target_filled, None)),
+    );
+    let account = trust
+        .create_account(
+            "alpaca",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(50000),
+            &Currency::USD,
+        )
+        .unwrap();
+    let tv = trust
+        .create_trading_vehicle(
+            "TSLA",
+            "US88160R1014",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
+        )
+        .unwrap();
+    let breakout = trust
+        .create_strategy("breakout", "", 1, "Buy the breakout", "Tight", "2R")
+        .unwrap();
+    let pullback = trust
+        .create_strategy("pullback", "", 1, "Buy the pullback", "Wide", "1R")
+        .unwrap();
+    let unused = trust
+        .create_strategy("reversal", "", 1, "Buy the reversal", "Low", "3R")
+        .unwrap();
+
+    // All the trades close at the target with the same profit, but the pullback
+    // trades risk more money because their stop is further away.
+    for (strategy_id, stop) in [
+        (breakout.id, dec!(38)),
+        (breakout.id, dec!(38)),
+        (pullback.id, dec!(35)),
+    ] {
+        let draft = DraftTrade {
+            account: account.clone(),
+            trading_vehicle: tv.clone(),
+            quantity: 500,
+            quantity_decimal: None,
+            currency: Currency::USD,
+            category: TradeCategory::Long,
+            strategy_id: Some(strategy_id),
+            reference: None,
+            thesis: None,
+            context: None,
+        };
+        let trade = trust.create_trade(draft, stop, dec!(40), dec!(50)).unwrap();
+        trust.fund_trade(&trade).unwrap();
+        let trade = trust.search_trades(account.id, Status::Funded).unwrap()[0].clone();
+        trust.submit_trade(&trade).unwrap();
+        let trade = trust.search_trades(account.id, Status::Submitted).unwrap()[0].clone();
+        trust.sync_trade(&trade, &account).unwrap();
+    }
+
+    let average_r = trust.average_r_by_strategy(account.id).unwrap();
+    assert_eq!(average_r.len(), 2);
+    assert_eq!(average_r[&breakout.id], Some(dec!(6.5))); // 6500 / (2 * 500)
+    assert_eq!(average_r[&pullback.id], Some(dec!(2.6))); // 6500 / (5 * 500)
+    assert!(!average_r.contains_key(&unused.id));
+}
+
+#[test]
+fn test_pnl_by_asset_class() {
+    let db = SqliteDatabase::new_in_memory();
+    let mut trust = TrustFacade::new(
+        Box::new(db),
+        Box::new(MockBroker::new(BrokerResponse::orders_target_filled, None)),
+    );
+    let account = trust
+        .create_account(
+            "alpaca",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(50000),
+            &Currency::USD,
+        )
+        .unwrap();
+    let stock = trust
+        .create_trading_vehicle(
+            "TSLA",
+            "US88160R1014",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
+        )
+        .unwrap();
+    let crypto = trust
+        .create_trading_vehicle("BTC", "BTC", &TradingVehicleCategory::Crypto, "COINBASE")
+        .unwrap();
+
+    // Two stock trades and a crypto trade close at the target with the same profit
+    for tv in [&stock, &crypto, &stock] {
+        let draft = DraftTrade {
+            account: account.clone(),
+            trading_vehicle: tv.clone(),
+            quantity: 500,
+            quantity_decimal: None,
+            currency: Currency::USD,
+            category: TradeCategory::Long,
+            strategy_id: None,
+            reference: None,
+            thesis: None,
+            context: None,
+        };
+        let trade = trust
+            .create_trade(draft, dec!(38), dec!(40), dec!(50))
+            .unwrap();
+        trust.fund_trade(&trade).unwrap();
+        let trade = trust.search_trades(account.id, Status::Funded).unwrap()[0].clone();
+        trust.submit_trade(&trade).unwrap();
+        let trade = trust.search_trades(account.id, Status::Submitted).unwrap()[0].clone();
+        trust.sync_trade(&trade, &account).unwrap();
+    }
+
+    assert_eq!(
+        trust
+            .pnl_by_asset_class(account.id, &Currency::USD)
+            .unwrap(),
+        vec![
+            ("stock".to_string(), dec!(13000), 2),
+            ("crypto".to_string(), dec!(6500), 1),
+        ]
+    );
+    assert!(trust
+        .pnl_by_asset_class(account.id, &Currency::EUR)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_bulk_cancel_trades() {
+    let db = SqliteDatabase::new_in_memory();
+    let mut trust = TrustFacade::new(
+        Box::new(db),
+        Box::new(MockBroker::new(BrokerResponse::orders_accepted, None)),
+    );
+    let account = trust
+        .create_account(
+            "alpaca",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(100000),
+            &Currency::USD,
+        )
+        .unwrap();
+    let tv = trust
+        .create_trading_vehicle(
+            "TSLA",
+            "US88160R1014",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
+        )
+        .unwrap();
+
+    // Four trades of 4000 are funded and three of them are submitted
+    for _ in 0..4 {
+        let draft = DraftTrade {
+            account: account.clone(),
+            trading_vehicle: tv.clone(),
+            quantity: 100,
+            quantity_decimal: None,
+            currency: Currency::USD,
+            category: TradeCategory::Long,
+            strategy_id: None,
+            reference: None,
+            thesis: None,
+            context: None,
+        };
+        let trade = trust
+            .create_trade(draft, dec!(38), dec!(40), dec!(50))
+            .unwrap();
+        trust.fund_trade(&trade).unwrap();
+    }
+    for _ in 0..3 {
+        let trade = trust.search_trades(account.id, Status::Funded).unwrap()[0].clone();
+        trust.submit_trade(&trade).unwrap();
+    }
+    let submitted = trust.search_trades(account.id, Status::Submitted).unwrap();
+    let halted = submitted[1].id;
+    HALTED_TRADES.with(|trades| trades.borrow_mut().push(halted));
+
+    let results = trust
+        .bulk_cancel_trades(account.id, Status::Submitted)
+        .unwrap();
+    assert_eq!(results.len(), 3);
+    for (trade_id, result) in &results {
+        assert_eq!(result.is_err(), *trade_id == halted);
+    }
+    assert_eq!(
+        trust
+            .search_trades(account.id, Status::Canceled)
+            .unwrap()
+            .len(),
+        2
+    );
+    assert_eq!(
+        trust.search_trades(account.id, Status::Submitted).unwrap()[0].id,
+        halted
+    );
+
+    // The funds of the canceled trades are back in the account
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(92000));
+
+    let results = trust
+        .bulk_cancel_trades(account.id, Status::Funded)
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(results[0].1.is_ok());
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(96000));
+
+    trust
+        .bulk_cancel_trades(account.id, Status::Filled)
+        .expect_err("Filled trades can not be canceled");
+}
+
+/// The trade is closed at the target when it wins and at the stop when it loses.
+fn orders_filled_by_outcome(trade: &Trade) -> (Status, Vec<Order>) {
+    if TRADE_WINS.with(|wins| wins.get()) {
+        BrokerResponse::orders_target_filled(trade)
+    } else {
+        BrokerResponse::orders_stop_filled(trade)
+    }
+}
+
+#[test]
+fn test_max_consecutive_losses() {
+    let db = SqliteDatabase::new_in_memory();
+    let mut trust = TrustFacade::new(
+        Box::new(db),
+        Box::new(MockBroker::new(orders_filled_by_outcome, None)),
+    );
+    let account = trust
+        .create_account(
+            "alpaca",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(100000),
+            &Currency::USD,
+        )
+        .unwrap();
+    trust
+        .create_rule(
+            &account,
+            &RuleName::MaxConsecutiveLosses(3),
+            "Take a break after three losses in a row",
+            &RuleLevel::Error,
+        )
+        .unwrap();
+    let tv = trust
+        .create_trading_vehicle(
+            "TSLA",
+            "US88160R1014",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
+        )
+        .unwrap();
+
+    let create_trade = |trust: &mut TrustFacade| {
+        let draft = DraftTrade {
+            account: account.clone(),
+            trading_vehicle: tv.clone(),
+            quantity: 100,
+            quantity_decimal: None,
+            currency: Currency::USD,
+            category: TradeCategory::Long,
+            strategy_id: None,
+            reference: None,
+            thesis: None,
+            context: None,
+        };
+        trust
+            .create_trade(draft, dec!(38), dec!(40), dec!(50))
+            .unwrap()
+    };
+    let close_trade = |trust: &mut TrustFacade, wins: bool| {
+        let trade = create_trade(trust);
+        trust.fund_trade(&trade).unwrap();
+        let trade = trust.search_trades(account.id, Status::Funded).unwrap()[0].clone();
+        trust.submit_trade(&trade).unwrap();
+        let trade = trust.search_trades(account.id, Status::Submitted).unwrap()[0].clone();
+        TRADE_WINS.with(|trade_wins| trade_wins.set(wins));
+        trust.sync_trade(&trade, &account).unwrap();
+    };
+
+    // A win resets the losing streak
+    for wins in [false, false, true, false, false] {
+        close_trade(&mut trust, wins);
+    }
+    let trade = create_trade(&mut trust);
+    trust
+        .fund_trade(&trade)
+        .expect("Two losses in a row are below the limit");
+    let trade = trust.search_trades(account.id, Status::Funded).unwrap()[0].clone();
+    trust.cancel_funded_trade(&trade).unwrap();
+
+    // The third loss in a row blocks the funding of the next trade
+    close_trade(&mut trust, false);
+    let trade = create_trade(&mut trust);
+    let error = trust
+        .fund_trade(&trade)
+        .expect_err("Three losses in a row reach the limit");
+    assert!(error.to_string().contains("Max consecutive losses reached"));
+}
+
+#[test]
+fn test_account_risk_heatmap() {
+    let db = SqliteDatabase::new_in_memory();
+    let mut trust = TrustFacade::new(
+        Box::new(db),
+        Box::new(MockBroker::new(BrokerResponse::orders_entry_filled, None)),
+    );
+    let account = trust
+        .create_account(
+            "alpaca",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(100000),
+            &Currency::USD,
+        )
+        .unwrap();
+    let tv = trust
+        .create_trading_vehicle(
+            "TSLA",
+            "US88160R1014",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
+        )
+        .unwrap();
+
+    // The entries are filled at 39.9, so the risk of every trade is (39.9 - stop) * 500
+    for (sector, stop) in [
+        (Some("Technology"), dec!(38)),
+        (Some("technology"), dec!(38)),
+        (Some("energy"), dec!(35)),
+        (None, dec!(39)),
+    ] {
+        let draft = DraftTrade {
+            account: account.clone(),
+            trading_vehicle: tv.clone(),
+            quantity: 500,
+            quantity_decimal: None,
+            currency: Currency::USD,
+            category: TradeCategory::Long,
+            strategy_id: None,
+            reference: None,
+            thesis: None,
+            context: None,
+        };
+        let trade = trust.create_trade(draft, stop, dec!(40), dec!(50)).unwrap();
+        trust.set_trade_sector(trade.id, sector).unwrap();
+        trust.fund_trade(&trade).unwrap();
+        let trade = trust.search_trades(account.id, Status::Funded).unwrap()[0].clone();
+        trust.submit_trade(&trade).unwrap();
+        let trade = trust.search_trades(account.id, Status::Submitted).unwrap()[0].clone();
+        trust.sync_trade(&trade, &account).unwrap();
+    }
+
+    let heatmap = trust.account_risk_heatmap(account.id).unwrap();
+    assert_eq!(
+        heatmap,
+        vec![
+            ("energy".to_string(), dec!(2450)),
+            ("technology".to_string(), dec!(1900)),
+            ("unclassified".to_string(), dec!(450)),
+        ]
+    );
+}
+
+#[test]
+fn test_create_trade_with_unknown_strategy() {
+    let (mut trust, account, trade) = create_trade(BrokerResponse::orders_accepted, None);
+
+    let draft = DraftTrade {
+        account,
+        trading_vehicle: trade.trading_vehicle,
+        quantity: 10,
+        quantity_decimal: None,
+        currency: Currency::USD,
+        category: TradeCategory::Long,
+        strategy_id: Some(Uuid::new_v4()),
+        reference: None,
+        thesis: None,
+        context: None,
+    };
+    trust
+        .create_trade(draft, dec!(38), dec!(40), dec!(50))
+        .expect_err("A trade can not reference a strategy that does not exist");
+}
+
+#[test]
+fn test_net_exposure_with_hedge_account() {
+    let (mut trust, account, trade) = create_trade(BrokerResponse::orders_entry_filled, None);
+    trust.sync_trade(&trade, &account).unwrap();
+
+    trust
+        .create_account_with_hierarchy(
+            "hedge",
+            "hedges alpaca",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+            AccountType::Hedge,
+            None,
+        )
+        .expect_err("A hedge account must have a parent");
+    let hedge = trust
+        .create_account_with_hierarchy(
+            "hedge",
+            "hedges alpaca",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+            AccountType::Hedge,
+            Some(account.id),
+        )
+        .unwrap();
+    assert_eq!(hedge.parent_account_id, Some(account.id));
+    trust
+        .create_transaction(
+            &hedge,
+            &TransactionCategory::Deposit,
+            dec!(50000),
+            &Currency::USD,
+        )
+        .unwrap();
+
+    // Short 200 TSLA in the hedge account against the long 500 of the parent
+    let draft = DraftTrade {
+        account: hedge.clone(),
+        trading_vehicle: trade.trading_vehicle.clone(),
+        quantity: 200,
+        quantity_decimal: None,
+        currency: Currency::USD,
+        category: TradeCategory::Short,
+        strategy_id: None,
+        reference: None,
+        thesis: None,
+        context: None,
+    };
+    let short = trust
+        .create_trade(draft, dec!(50), dec!(40), dec!(30))
+        .unwrap();
+    trust.fund_trade(&short).unwrap();
+    let short = trust.search_trades(hedge.id, Status::Funded).unwrap()[0].clone();
+    trust.submit_trade(&short).unwrap();
+    let short = trust.search_trades(hedge.id, Status::Submitted).unwrap()[0].clone();
+    trust.sync_trade(&short, &hedge).unwrap();
+    assert_eq!(
+        trust.search_trades(hedge.id, Status::Filled).unwrap().len(),
+        1
+    );
+
+    assert_eq!(trust.net_exposure(account.id, "TSLA").unwrap(), dec!(300));
+    assert_eq!(trust.net_exposure(hedge.id, "TSLA").unwrap(), dec!(-200));
+    assert_eq!(trust.net_exposure(account.id, "AAPL").unwrap(), dec!(0));
+}
+
+#[test]
+fn test_account_margin_status() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_entry_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+
+    // 500 TSLA filled at 39.9 and 30050 of cash.
+    let status = trust
+        .account_margin_status(account.id, &Currency::USD, dec!(25), &HashMap::new())
+        .unwrap();
+    assert_eq!(status.position_value, dec!(19950));
+    assert_eq!(status.maintenance_margin, dec!(4987.5));
+    assert_eq!(status.equity, dec!(50000));
+    assert_eq!(status.excess, dec!(45012.5));
+    assert!(!status.margin_call);
+
+    let marks = HashMap::from([("TSLA".to_string(), dec!(30))]);
+    let status = trust
+        .account_margin_status(account.id, &Currency::USD, dec!(25), &marks)
+        .unwrap();
+    assert_eq!(status.position_value, dec!(15000));
+    assert_eq!(status.equity, dec!(45050));
+    assert!(!status.margin_call);
+
+    trust
+        .account_margin_status(account.id, &Currency::USD, dec!(120), &marks)
+        .expect_err("The maintenance percentage can not be above 100");
+}
+
+#[test]
+fn test_account_margin_status_in_margin_call() {
+    let (trust, _, trade) = create_trade(BrokerResponse::orders_entry_filled, None);
+    let mut trust = trust;
+    let account = trust
+        .create_account(
+            "short",
+            "short account",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(25000),
+            &Currency::USD,
+        )
+        .unwrap();
+    let draft = DraftTrade {
+        account: account.clone(),
+        trading_vehicle: trade.trading_vehicle.clone(),
+        quantity: 500,
+        quantity_decimal: None,
+        currency: Currency::USD,
+        category: TradeCategory::Short,
+        strategy_id: None,
+        reference: None,
+        thesis: None,
+        context: None,
+    };
+    let short = trust
+        .create_trade(draft, dec!(50), dec!(40), dec!(30))
+        .unwrap();
+    trust.fund_trade(&short).unwrap();
+    let short = trust.search_trades(account.id, Status::Funded).unwrap()[0].clone();
+    trust.submit_trade(&short).unwrap();
+    let short = trust.search_trades(account.id, Status::Submitted).unwrap()[0].clone();
+    trust.sync_trade(&short, &account).unwrap();
+
+    // 500 TSLA shorted at 39.9 and 5050 of cash. TSLA doubled against the short.
+    let marks = HashMap::from([("TSLA".to_string(), dec!(80))]);
+    let status = trust
+        .account_margin_status(account.id, &Currency::USD, dec!(30), &marks)
+        .unwrap();
+    assert_eq!(status.position_value, dec!(40000));
+    assert_eq!(status.maintenance_margin, dec!(12000));
+    assert_eq!(status.equity, dec!(4950)); // 5050 + 19950 - 20050 of loss
+    assert_eq!(status.excess, dec!(-7050));
+    assert!(status.margin_call);
+}
+
+#[test]
+fn test_fund_short_trade_exceeding_risk_per_trade() {
+    let (mut trust, account, trade) = create_trade(BrokerResponse::orders_accepted, None);
+    let draft = |category| DraftTrade {
+        account: account.clone(),
+        trading_vehicle: trade.trading_vehicle.clone(),
+        quantity: 500,
+        quantity_decimal: None,
+        currency: Currency::USD,
+        category,
+        strategy_id: None,
+        reference: None,
+        thesis: None,
+        context: None,
+    };
+
+    // 30000 are available, so 2% is 600 and both trades risk 1000.
+    let long = trust
+        .create_trade(draft(TradeCategory::Long), dec!(38), dec!(40), dec!(50))
+        .unwrap();
+    let short = trust
+        .create_trade(draft(TradeCategory::Short), dec!(42), dec!(40), dec!(30))
+        .unwrap();
+
+    let long_error = trust.fund_trade(&long).unwrap_err().to_string();
+    let short_error = trust.fund_trade(&short).unwrap_err().to_string();
+    assert!(long_error.contains("Risk per trade exceeded"));
+    assert_eq!(short_error, long_error);
+    assert_eq!(
+        trust.search_trades(account.id, Status::New).unwrap().len(),
+        2
+    );
+}
+
+#[test]
+fn test_submit_duplicate_trade() {
+    let (mut trust, account, trade) = create_trade(BrokerResponse::orders_accepted, None);
+    let account = trust
+        .set_duplicate_trade_window(&account, Some(300))
+        .unwrap();
+    assert_eq!(account.duplicate_trade_window_seconds, Some(300));
+    let funded = |trust: &mut TrustFacade, quantity: i64| {
+        let draft = DraftTrade {
+            account: account.clone(),
+            trading_vehicle: trade.trading_vehicle.clone(),
+            quantity,
+            quantity_decimal: None,
+            currency: Currency::USD,
+            category: TradeCategory::Long,
+            strategy_id: None,
+            reference: None,
+            thesis: None,
+            context: None,
+        };
+        let new = trust
+            .create_trade(draft, dec!(38), dec!(40), dec!(50))
+            .unwrap();
+        trust.fund_trade(&new).unwrap();
+        trust.search_trades(account.id, Status::Funded).unwrap()[0].clone()
+    };
+
+    // A trade with a different quantity is not a duplicate
+    let first = funded(&mut trust, 100);
+    trust.submit_trade(&first).unwrap();
+
+    // The same trade as the one that was just submitted is blocked
+    let duplicate = funded(&mut trust, 100);
+    let error = trust.submit_trade(&duplicate).err().unwrap();
+    assert!(error.to_string().contains(&first.id.to_string()));
+    assert_eq!(
+        trust.search_trades(account.id, Status::Funded).unwrap()[0].id,
+        duplicate.id
+    );
+
+    // Unless it is forced
+    trust.force_submit_trade(&duplicate).unwrap();
+    assert_eq!(
+        trust
+            .search_trades(account.id, Status::Submitted)
+            .unwrap()
+            .len(),
+        3
+    );
+}
+
+#[test]
+fn test_orphan_order_cleanup() {
+    let (mut trust, account, trade) =
+        create_trade(BrokerResponse::orders_closed_with_canceled_stop, None);
+    trust.sync_trade(&trade, &account).unwrap();
+    let trade = trust.search_trades(account.id, Status::Filled).unwrap()[0].clone();
+    assert_eq!(trade.safety_stop.status, OrderStatus::Held);
+
+    let repaired = trust.orphan_order_cleanup(account.id).unwrap();
+
+    // Only the canceled stop is repaired, the filled target is left for the sync
+    assert_eq!(repaired, vec![trade.safety_stop.id]);
+    let trade = trust.search_trades(account.id, Status::Filled).unwrap()[0].clone();
+    assert_eq!(trade.safety_stop.status, OrderStatus::Canceled);
+    assert!(trade.safety_stop.cancelled_at.is_some());
+    assert_eq!(trade.target.status, OrderStatus::Accepted);
+    assert!(CANCELED_ORDERS.with(|orders| orders.borrow().is_empty()));
+
+    // Nothing is left to repair
+    assert!(trust.orphan_order_cleanup(account.id).unwrap().is_empty());
+}
+
+#[test]
+fn test_orphan_order_cleanup_cancels_the_legs_of_closed_trades() {
+    let (mut trust, account, trade) =
+        create_trade(BrokerResponse::orders_stop_filled_without_target, None);
+    trust.sync_trade(&trade, &account).unwrap();
+    let trade = trust.search_trades(account.id, Status::Filled).unwrap()[0].clone();
+
+    let repaired = trust.orphan_order_cleanup(account.id).unwrap();
+
+    // The target still works in the broker although the stop closed the trade
+    assert_eq!(repaired, vec![trade.target.id]);
+    assert_eq!(
+        CANCELED_ORDERS.with(|orders| orders.borrow().clone()),
+        vec![trade.target.id]
+    );
+    let trade = trust.search_trades(account.id, Status::Filled).unwrap()[0].clone();
+    assert_eq!(trade.target.status, OrderStatus::Canceled);
+    assert_eq!(trade.safety_stop.status, OrderStatus::Held);
+}
+
+#[test]
+fn test_close_all_positions_market() {
+    let (mut trust, account, trade) = create_trade(
+        BrokerResponse::orders_entry_filled,
+        Some(BrokerResponse::closed_order),
+    );
+    let draft = || DraftTrade {
+        account: account.clone(),
+        trading_vehicle: trade.trading_vehicle.clone(),
+        quantity: 100,
+        quantity_decimal: None,
+        currency: Currency::USD,
+        category: TradeCategory::Long,
+        strategy_id: None,
+        reference: None,
+        thesis: None,
+        context: None,
+    };
+
+    // A second filled trade that the broker can not close, and a funded trade that is not in the market
+    let halted = trust
+        .create_trade(draft(), dec!(38), dec!(40), dec!(50))
+        .unwrap();
+    trust.fund_trade(&halted).unwrap();
+    let halted = trust.search_trades(account.id, Status::Funded).unwrap()[0].clone();
+    trust.submit_trade(&halted).unwrap();
+    let funded = trust
+        .create_trade(draft(), dec!(38), dec!(40), dec!(50))
+        .unwrap();
+    trust.fund_trade(&funded).unwrap();
+    for submitted in trust.search_trades(account.id, Status::Submitted).unwrap() {
+        trust.sync_trade(&submitted, &account).unwrap();
+    }
+    assert_eq!(
+        trust
+            .search_trades(account.id, Status::Filled)
+            .unwrap()
+            .len(),
+        2
+    );
+    HALTED_TRADES.with(|trades| trades.borrow_mut().push(halted.id));
 
-    // Assert Target
-    assert_eq!(trade.target.quantity, 500);
-    assert_eq!(trade.target.unit_price, dec!(50));
-    assert_eq!(trade.target.average_filled_price, None);
-    assert_eq!(trade.target.category, OrderCategory::Market);
-    assert_eq!(trade.target.filled_quantity, 0);
-    assert_eq!(trade.target.status, OrderStatus::PendingNew);
+    let results = trust.close_all_positions_market(account.id).unwrap();
+
+    assert_eq!(results.len(), 2);
+    let closed = results.iter().find(|(id, _)| *id == trade.id).unwrap();
+    assert!(closed.1.is_ok());
+    let failed = results.iter().find(|(id, _)| *id == halted.id).unwrap();
+    assert_eq!(
+        failed.1,
+        Err(format!(
+            "Trade {} can not be closed during the halt",
+            halted.id
+        ))
+    );
+
+    // The other trade is closing, the failed one is still in the market
+    let filled = trust.search_trades(account.id, Status::Filled).unwrap();
+    assert_eq!(filled.len(), 1);
+    assert_eq!(filled[0].id, halted.id);
+    assert_eq!(
+        trust.search_trades(account.id, Status::Canceled).unwrap()[0].id,
+        trade.id
+    );
+    assert_eq!(
+        trust.search_trades(account.id, Status::Funded).unwrap()[0].id,
+        funded.id
+    );
 }
 
 #[test]
-fn test_trade_modify_stop_long() {
-    let (trust, account, trade) = create_trade(
-        BrokerResponse::orders_entry_filled,
+fn test_emergency_cancel_all_closing_trade() {
+    let (mut trust, account, trade) = create_trade(
+        BrokerResponse::orders_closing_canceled,
         Some(BrokerResponse::closed_order),
     );
-    let mut trust = trust;
-
-    // 1. Sync trade with the Broker - Entry is filled
+    trust.sync_trade(&trade, &account).unwrap();
+    let trade = trust.search_trades(account.id, Status::Filled).unwrap()[0].clone();
     trust
-        .sync_trade(&trade, &account)
-        .expect("Failed to sync trade with broker when entry is filled");
-
+        .close_trade_at_limit(&trade, &account, dec!(45))
+        .unwrap();
     let trade = trust
-        .search_trades(account.id, Status::Filled)
-        .expect("Failed to find trade with status submitted 2")
-        .first()
-        .unwrap()
+        .search_trades(account.id, Status::ClosingPending)
+        .unwrap()[0]
         .clone();
 
-    // 7. Modify stop
-    trust
-        .modify_stop(&trade, &account, dec!(39))
-        .expect("Failed to modify stop");
+    let report = trust.emergency_cancel_all(account.id).unwrap();
 
-    let trade = trust
-        .search_trades(account.id, Status::Filled)
-        .expect("Failed to find trade with status filled")
-        .first()
+    // The closing order is canceled, so the trade is still in the market
+    assert_eq!(report.trades, vec![(trade.id, Ok(Status::Filled))]);
+    assert!(trust
+        .search_trades(account.id, Status::ClosingPending)
         .unwrap()
-        .clone();
+        .is_empty());
+    let trade = trust.search_trades(account.id, Status::Filled).unwrap()[0].clone();
+    assert_eq!(trade.target.status, OrderStatus::Canceled);
+}
 
-    // Assert Trade Overview
-    assert_eq!(trade.status, Status::Filled); // The trade is still filled, but the stop was changed
-    assert_eq!(trade.safety_stop.unit_price, dec!(39));
+#[test]
+fn test_emergency_cancel_all() {
+    let (mut trust, account, trade) = create_trade(BrokerResponse::orders_canceled, None);
+    let draft = || DraftTrade {
+        account: account.clone(),
+        trading_vehicle: trade.trading_vehicle.clone(),
+        quantity: 100,
+        quantity_decimal: None,
+        currency: Currency::USD,
+        category: TradeCategory::Long,
+        strategy_id: None,
+        reference: None,
+        thesis: None,
+        context: None,
+    };
+
+    // A second submitted trade and a funded trade that has no orders in the broker
+    let second = trust
+        .create_trade(draft(), dec!(38), dec!(40), dec!(50))
+        .unwrap();
+    trust.fund_trade(&second).unwrap();
+    let second = trust.search_trades(account.id, Status::Funded).unwrap()[0].clone();
+    trust.submit_trade(&second).unwrap();
+    let funded = trust
+        .create_trade(draft(), dec!(38), dec!(40), dec!(50))
+        .unwrap();
+    trust.fund_trade(&funded).unwrap();
+    let before = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(before.total_available, dec!(22000)); // 20000 and 4000 submitted, 4000 funded
+
+    let report = trust.emergency_cancel_all(account.id).unwrap();
+
+    assert_eq!(report.canceled_orders.len(), 4);
+    let mut affected: Vec<Uuid> = report.trades.iter().map(|(id, _)| *id).collect();
+    let mut expected = vec![trade.id, second.id];
+    affected.sort();
+    expected.sort();
+    assert_eq!(affected, expected);
+    assert!(report
+        .trades
+        .iter()
+        .all(|(_, status)| *status == Ok(Status::Canceled)));
+
+    // The submitted trades are canceled locally and their funds are back in the account
     assert_eq!(
-        trade.safety_stop.broker_order_id.unwrap(),
-        Uuid::parse_str("7654f70e-3b42-4014-a9ac-5a7101989aad").unwrap()
+        trust
+            .search_trades(account.id, Status::Canceled)
+            .unwrap()
+            .len(),
+        2
+    );
+    assert!(trust
+        .search_trades(account.id, Status::Submitted)
+        .unwrap()
+        .is_empty());
+    let canceled = trust.search_trades(account.id, Status::Canceled).unwrap();
+    assert!(canceled
+        .iter()
+        .all(|trade| trade.entry.status == OrderStatus::Canceled));
+    assert_eq!(
+        trust.search_trades(account.id, Status::Funded).unwrap()[0].id,
+        funded.id
     );
+    let after = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(after.total_available, dec!(46000)); // Only the funded trade keeps its money
 }
 
 #[test]
-fn test_trade_modify_target() {
-    let (trust, account, trade) = create_trade(
-        BrokerResponse::orders_entry_filled,
-        Some(BrokerResponse::closed_order),
+fn test_create_trade_with_reference() {
+    let (mut trust, account, trade) = create_trade(BrokerResponse::orders_accepted, None);
+    let draft = |account: &model::Account, reference: &str| DraftTrade {
+        account: account.clone(),
+        trading_vehicle: trade.trading_vehicle.clone(),
+        quantity: 10,
+        quantity_decimal: None,
+        currency: Currency::USD,
+        category: TradeCategory::Long,
+        strategy_id: None,
+        reference: Some(reference.to_string()),
+        thesis: None,
+        context: None,
+    };
+
+    let referenced = trust
+        .create_trade(
+            draft(&account, "AAPL-2024-001"),
+            dec!(38),
+            dec!(40),
+            dec!(50),
+        )
+        .unwrap();
+    assert_eq!(referenced.reference, Some("AAPL-2024-001".to_string()));
+    assert_eq!(
+        trust
+            .trade_by_reference(account.id, "AAPL-2024-001")
+            .unwrap(),
+        referenced
     );
-    let mut trust = trust;
+    assert!(trust
+        .trade_by_reference(account.id, "AAPL-2024-002")
+        .is_err());
 
-    // 1. Sync trade with the Broker - Entry is filled
     trust
-        .sync_trade(&trade, &account)
-        .expect("Failed to sync trade with broker when entry is filled");
-
-    let trade = trust
-        .search_trades(account.id, Status::Filled)
-        .expect("Failed to find trade with status submitted 2")
-        .first()
-        .unwrap()
-        .clone();
+        .create_trade(
+            draft(&account, "AAPL-2024-001"),
+            dec!(38),
+            dec!(40),
+            dec!(50),
+        )
+        .expect_err("A reference can not be used twice in the same account");
 
-    // 7. Modify stop
+    // The reference is only unique per account
     trust
-        .modify_target(&trade, &account, dec!(100.1))
-        .expect("Failed to modify stop");
-
-    let trade = trust
-        .search_trades(account.id, Status::Filled)
-        .expect("Failed to find trade with status filled")
-        .first()
-        .unwrap()
-        .clone();
-
-    // Assert Trade Overview
-    assert_eq!(trade.status, Status::Filled); // The trade is still filled, but the stop was changed
-    assert_eq!(trade.target.unit_price, dec!(100.1));
+        .create_account(
+            "other",
+            "other",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    let other = trust.search_account("other").unwrap();
+    let other_trade = trust
+        .create_trade(draft(&other, "AAPL-2024-001"), dec!(38), dec!(40), dec!(50))
+        .unwrap();
     assert_eq!(
-        trade.target.broker_order_id.unwrap(),
-        Uuid::parse_str("5654f70e-3b42-4014-a9ac-5a7101989aad").unwrap()
+        trust.trade_by_reference(other.id, "AAPL-2024-001").unwrap(),
+        other_trade
+    );
+    assert_eq!(
+        trust
+            .trade_by_reference(account.id, "AAPL-2024-001")
+            .unwrap(),
+        referenced
     );
 }
 
@@ -614,6 +3364,46 @@ impl BrokerResponse {
         (Status::Submitted, vec![entry, target, stop])
     }
 
+    fn orders_canceled(trade: &Trade) -> (Status, Vec<Order>) {
+        let orders = [&trade.entry, &trade.target, &trade.safety_stop]
+            .into_iter()
+            .map(|order| Order {
+                id: order.id,
+                broker_order_id: order.broker_order_id,
+                filled_quantity: 0,
+                average_filled_price: None,
+                status: OrderStatus::Canceled,
+                filled_at: None,
+                expired_at: None,
+                cancelled_at: Some(Utc::now().naive_utc()),
+                ..Default::default()
+            })
+            .collect();
+
+        (Status::Submitted, orders)
+    }
+
+    /// The entry is filled, and once the trade is closing its closing order is canceled.
+    fn orders_closing_canceled(trade: &Trade) -> (Status, Vec<Order>) {
+        if trade.status != Status::ClosingPending {
+            return BrokerResponse::orders_entry_filled(trade);
+        }
+
+        let target = Order {
+            id: trade.target.id,
+            broker_order_id: trade.target.broker_order_id,
+            filled_quantity: 0,
+            average_filled_price: None,
+            status: OrderStatus::Canceled,
+            filled_at: None,
+            expired_at: None,
+            cancelled_at: Some(Utc::now().naive_utc()),
+            ..Default::default()
+        };
+
+        (Status::ClosingPending, vec![target])
+    }
+
     fn orders_entry_filled(trade: &Trade) -> (Status, Vec<Order>) {
         let entry = Order {
             id: trade.entry.id,
@@ -654,6 +3444,14 @@ impl BrokerResponse {
         (Status::Filled, vec![entry, target, stop])
     }
 
+    fn orders_fractional_entry_filled(trade: &Trade) -> (Status, Vec<Order>) {
+        let (status, mut orders) = BrokerResponse::orders_entry_filled(trade);
+        orders[0].filled_quantity = 0;
+        orders[0].filled_quantity_decimal = Some(dec!(0.25));
+        orders[0].average_filled_price = Some(dec!(30000));
+        (status, orders)
+    }
+
     fn orders_target_filled(trade: &Trade) -> (Status, Vec<Order>) {
         let entry = Order {
             id: trade.entry.id,
@@ -762,6 +3560,74 @@ impl BrokerResponse {
         (Status::ClosedStopLoss, vec![entry, stop])
     }
 
+    /// The entry is filled, and on the next sync the broker closed the trade at the target
+    /// and canceled the stop.
+    fn orders_closed_with_canceled_stop(trade: &Trade) -> (Status, Vec<Order>) {
+        if trade.status != Status::Filled {
+            return BrokerResponse::orders_entry_filled(trade);
+        }
+
+        let target = Order {
+            id: trade.target.id,
+            broker_order_id: trade.target.broker_order_id,
+            filled_quantity: 500,
+            average_filled_price: Some(dec!(50)),
+            status: OrderStatus::Filled,
+            filled_at: Some(Utc::now().naive_utc()),
+            ..Default::default()
+        };
+        let stop = Order {
+            id: trade.safety_stop.id,
+            broker_order_id: trade.safety_stop.broker_order_id,
+            status: OrderStatus::Canceled,
+            cancelled_at: Some(Utc::now().naive_utc()),
+            ..Default::default()
+        };
+
+        (Status::ClosedTarget, vec![target, stop])
+    }
+
+    /// The entry is filled, and on the next sync the broker closed the trade at the stop
+    /// without reporting the target.
+    fn orders_stop_filled_without_target(trade: &Trade) -> (Status, Vec<Order>) {
+        if trade.status != Status::Filled {
+            return BrokerResponse::orders_entry_filled(trade);
+        }
+
+        let stop = Order {
+            id: trade.safety_stop.id,
+            broker_order_id: trade.safety_stop.broker_order_id,
+            filled_quantity: 500,
+            average_filled_price: Some(dec!(38)),
+            status: OrderStatus::Filled,
+            filled_at: Some(Utc::now().naive_utc()),
+            ..Default::default()
+        };
+
+        (Status::ClosedStopLoss, vec![stop])
+    }
+
+    /// The entry is filled, and once the trade is closing at a limit the limit order is filled at 45.
+    fn orders_closed_at_limit(trade: &Trade) -> (Status, Vec<Order>) {
+        if trade.status != Status::ClosingPending {
+            return BrokerResponse::orders_entry_filled(trade);
+        }
+
+        let target = Order {
+            id: trade.target.id,
+            broker_order_id: trade.target.broker_order_id,
+            filled_quantity: 500,
+            average_filled_price: Some(dec!(45)),
+            status: OrderStatus::Filled,
+            filled_at: Some(Utc::now().naive_utc()),
+            expired_at: None,
+            cancelled_at: None,
+            ..Default::default()
+        };
+
+        (Status::ClosedTarget, vec![target])
+    }
+
     fn closed_order(trade: &Trade) -> Option<Order> {
         Some(Order {
             id: trade.target.id,
@@ -803,11 +3669,19 @@ impl Broker for MockBroker {
         let ids = OrderIds {
             entry: Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap(),
             target: Uuid::parse_str("90e41b1e-9089-444d-9f68-c204a4d32914").unwrap(),
-            stop: Uuid::parse_str("8654f70e-3b42-4014-a9ac-5a7101989aad").unwrap(),
+            stop: Some(Uuid::parse_str("8654f70e-3b42-4014-a9ac-5a7101989aad").unwrap()),
         };
         Ok((log, ids))
     }
 
+    fn validate_trade(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+    ) -> Result<BrokerValidation, Box<dyn Error>> {
+        unimplemented!()
+    }
+
     fn sync_trade(
         &self,
         trade: &Trade,
@@ -823,21 +3697,87 @@ impl Broker for MockBroker {
         _trade: &Trade,
         _account: &Account,
     ) -> Result<(Order, BrokerLog), Box<dyn Error>> {
+        if HALTED_TRADES.with(|trades| trades.borrow().contains(&_trade.id)) {
+            return Err(format!("Trade {} can not be closed during the halt", _trade.id).into());
+        }
         let order = (self.closed_order.unwrap())(_trade).unwrap();
         let log = BrokerLog::default();
         Ok((order, log))
     }
 
-    fn cancel_trade(&self, _trade: &Trade, _account: &Account) -> Result<(), Box<dyn Error>> {
+    fn close_trade_limit(
+        &self,
+        trade: &Trade,
+        _account: &Account,
+        limit_price: Decimal,
+    ) -> Result<(Order, BrokerLog), Box<dyn Error>> {
+        let mut order = (self.closed_order.unwrap())(trade).unwrap();
+        order.category = OrderCategory::Limit;
+        order.unit_price = limit_price;
+        order.status = OrderStatus::Accepted;
+        Ok((order, BrokerLog::default()))
+    }
+
+    fn cancel_trade(&self, trade: &Trade, _account: &Account) -> Result<(), Box<dyn Error>> {
+        if HALTED_TRADES.with(|trades| trades.borrow().contains(&trade.id)) {
+            return Err(format!("Trade {} can not be canceled during the halt", trade.id).into());
+        }
+        Ok(())
+    }
+
+    fn cancel_order(&self, order: &Order, _account: &Account) -> Result<(), Box<dyn Error>> {
+        if NOT_WORKING_ORDERS.with(|orders| orders.borrow().contains(&order.id)) {
+            return Err(Box::new(BrokerError {
+                code: BrokerErrorCode::OrderNotWorking,
+                message: format!("Order {} is not working", order.id),
+            }));
+        }
+        CANCELED_ORDERS.with(|orders| orders.borrow_mut().push(order.id));
         Ok(())
     }
 
+    /// The orders of the submitted trades and an order that was placed outside of Trust.
+    fn cancel_all_orders(&self, _account: &Account) -> Result<Vec<Uuid>, Box<dyn Error>> {
+        Ok(vec![
+            Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap(),
+            Uuid::parse_str("90e41b1e-9089-444d-9f68-c204a4d32914").unwrap(),
+            Uuid::parse_str("8654f70e-3b42-4014-a9ac-5a7101989aad").unwrap(),
+            Uuid::parse_str("1c4a8a9b-6f5e-4d2a-9b3c-7e8f9a0b1c2d").unwrap(),
+        ])
+    }
+
+    fn replace_order(
+        &self,
+        order: &Order,
+        _account: &Account,
+        _new_price: Decimal,
+        new_quantity: u64,
+    ) -> Result<Uuid, Box<dyn Error>> {
+        BROKER_CALLS.with(|calls| calls.borrow_mut().push("replace_order"));
+        assert_eq!(new_quantity, order.quantity);
+
+        if let Some(code) = REPLACE_ERROR.with(|error| error.get()) {
+            return Err(Box::new(BrokerError {
+                code,
+                message: format!("Order {} can not be replaced", order.id),
+            }));
+        }
+        // The trades are created with the stop at 38 and the target at 50
+        match order.unit_price {
+            price if price == dec!(50) => {
+                Ok(Uuid::parse_str("5654f70e-3b42-4014-a9ac-5a7101989aad").unwrap())
+            }
+            _ => Ok(Uuid::parse_str("7654f70e-3b42-4014-a9ac-5a7101989aad").unwrap()),
+        }
+    }
+
     fn modify_stop(
         &self,
         trade: &Trade,
         account: &Account,
         new_stop_price: Decimal,
     ) -> Result<Uuid, Box<dyn Error>> {
+        BROKER_CALLS.with(|calls| calls.borrow_mut().push("modify_stop"));
         assert_eq!(trade.account_id, account.id);
         assert_eq!(trade.safety_stop.unit_price, dec!(38));
         assert_eq!(new_stop_price, dec!(39));
@@ -851,10 +3791,245 @@ impl Broker for MockBroker {
         account: &Account,
         new_target_price: rust_decimal::Decimal,
     ) -> Result<Uuid, Box<dyn Error>> {
+        BROKER_CALLS.with(|calls| calls.borrow_mut().push("modify_target"));
         assert_eq!(trade.account_id, account.id);
         assert_eq!(trade.target.unit_price, dec!(50));
         assert_eq!(new_target_price, dec!(100.1));
 
         Ok(Uuid::parse_str("5654f70e-3b42-4014-a9ac-5a7101989aad").unwrap())
     }
+
+    fn get_account_activities(
+        &self,
+        _account: &Account,
+        _since: NaiveDateTime,
+        _until: NaiveDateTime,
+    ) -> Result<Vec<BrokerActivity>, Box<dyn Error>> {
+        let day = NaiveDate::from_ymd_opt(2023, 6, 12).unwrap();
+        Ok(vec![
+            BrokerActivity {
+                id: "20230612153406977::8efc7b9a-8b2b-4000-9955-d36e7db0df74".to_string(),
+                category: BrokerActivityCategory::Fill,
+                order_id: Some(Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap()),
+                side: Some(ExecutionSide::Buy),
+                price: Some(dec!(39.9)),
+                quantity: Some(500),
+                amount: None,
+                currency: Currency::USD,
+                transaction_time: day.and_hms_opt(15, 34, 6).unwrap(),
+            },
+            BrokerActivity {
+                id: "20230612000000000::5f596936-6f23-4cef-bdf1-3806aae57dbf".to_string(),
+                category: BrokerActivityCategory::Fee,
+                order_id: None,
+                side: None,
+                price: None,
+                quantity: None,
+                amount: Some(dec!(0.02)),
+                currency: Currency::USD,
+                transaction_time: day.and_hms_opt(20, 0, 0).unwrap(),
+            },
+            BrokerActivity {
+                id: "20230615000000000::0c1f6e1a-7d7e-4bd8-a4c2-f2a5c0e1a3b1".to_string(),
+                category: BrokerActivityCategory::Fee,
+                order_id: None,
+                side: None,
+                price: None,
+                quantity: None,
+                amount: Some(dec!(0.05)),
+                currency: Currency::USD,
+                transaction_time: NaiveDate::from_ymd_opt(2023, 6, 15)
+                    .unwrap()
+                    .and_hms_opt(20, 0, 0)
+                    .unwrap(),
+            },
+        ])
+    }
+
+    fn account_buying_power(&self, account: &Account) -> Result<Decimal, Box<dyn Error>> {
+        unimplemented!("Account buying power: {:?}", account)
+    }
+
+    /// The mocked broker only holds a position in NVDA.
+    fn get_positions(&self, _account: &Account) -> Result<Vec<BrokerPosition>, Box<dyn Error>> {
+        Ok(vec![BrokerPosition {
+            symbol: "NVDA".to_string(),
+            category: TradeCategory::Long,
+            quantity: dec!(10),
+            average_entry_price: dec!(420),
+        }])
+    }
+}
+
+/// Delegates to a SqliteDatabase but fails to write reinvestments,
+/// the third leg of a distribution.
+struct FailingReinvestmentDatabase {
+    database: SqliteDatabase,
+}
+
+impl DatabaseFactory for FailingReinvestmentDatabase {
+    fn account_read(&self) -> Box<dyn model::AccountRead> {
+        self.database.account_read()
+    }
+    fn account_write(&self) -> Box<dyn model::AccountWrite> {
+        self.database.account_write()
+    }
+    fn account_balance_read(&self) -> Box<dyn model::AccountBalanceRead> {
+        self.database.account_balance_read()
+    }
+    fn account_balance_write(&self) -> Box<dyn model::AccountBalanceWrite> {
+        self.database.account_balance_write()
+    }
+    fn order_read(&self) -> Box<dyn model::OrderRead> {
+        self.database.order_read()
+    }
+    fn order_write(&self) -> Box<dyn model::OrderWrite> {
+        self.database.order_write()
+    }
+    fn transaction_read(&self) -> Box<dyn model::ReadTransactionDB> {
+        self.database.transaction_read()
+    }
+    fn transaction_write(&self) -> Box<dyn WriteTransactionDB> {
+        Box::new(FailingReinvestmentWrite {
+            write: self.database.transaction_write(),
+        })
+    }
+    fn trade_read(&self) -> Box<dyn model::ReadTradeDB> {
+        self.database.trade_read()
+    }
+    fn trade_write(&self) -> Box<dyn model::WriteTradeDB> {
+        self.database.trade_write()
+    }
+    fn trade_balance_write(&self) -> Box<dyn model::database::WriteAccountBalanceDB> {
+        self.database.trade_balance_write()
+    }
+    fn rule_read(&self) -> Box<dyn model::ReadRuleDB> {
+        self.database.rule_read()
+    }
+    fn rule_write(&self) -> Box<dyn model::WriteRuleDB> {
+        self.database.rule_write()
+    }
+    fn trading_vehicle_read(&self) -> Box<dyn model::ReadTradingVehicleDB> {
+        self.database.trading_vehicle_read()
+    }
+    fn trading_vehicle_write(&self) -> Box<dyn model::WriteTradingVehicleDB> {
+        self.database.trading_vehicle_write()
+    }
+    fn log_read(&self) -> Box<dyn model::ReadBrokerLogsDB> {
+        self.database.log_read()
+    }
+    fn log_write(&self) -> Box<dyn model::WriteBrokerLogsDB> {
+        self.database.log_write()
+    }
+    fn execution_read(&self) -> Box<dyn model::ReadExecutionDB> {
+        self.database.execution_read()
+    }
+    fn execution_write(&self) -> Box<dyn model::WriteExecutionDB> {
+        self.database.execution_write()
+    }
+    fn equity_snapshot_read(&self) -> Box<dyn model::ReadEquitySnapshotDB> {
+        self.database.equity_snapshot_read()
+    }
+    fn equity_snapshot_write(&self) -> Box<dyn model::WriteEquitySnapshotDB> {
+        self.database.equity_snapshot_write()
+    }
+    fn level_read(&self) -> Box<dyn model::ReadLevelDB> {
+        self.database.level_read()
+    }
+    fn level_write(&self) -> Box<dyn model::WriteLevelDB> {
+        self.database.level_write()
+    }
+    fn strategy_read(&self) -> Box<dyn model::ReadStrategyDB> {
+        self.database.strategy_read()
+    }
+    fn strategy_write(&self) -> Box<dyn model::WriteStrategyDB> {
+        self.database.strategy_write()
+    }
+    fn distribution_read(&self) -> Box<dyn model::ReadDistributionDB> {
+        self.database.distribution_read()
+    }
+    fn distribution_write(&self) -> Box<dyn model::WriteDistributionDB> {
+        self.database.distribution_write()
+    }
+    fn trade_grade_read(&self) -> Box<dyn model::ReadTradeGradeDB> {
+        self.database.trade_grade_read()
+    }
+    fn trade_grade_write(&self) -> Box<dyn model::WriteTradeGradeDB> {
+        self.database.trade_grade_write()
+    }
+    fn realized_lot_read(&self) -> Box<dyn model::ReadRealizedLotDB> {
+        self.database.realized_lot_read()
+    }
+    fn realized_lot_write(&self) -> Box<dyn model::WriteRealizedLotDB> {
+        self.database.realized_lot_write()
+    }
+    fn advisory_read(&self) -> Box<dyn model::ReadAdvisoryDB> {
+        self.database.advisory_read()
+    }
+    fn advisory_write(&self) -> Box<dyn model::WriteAdvisoryDB> {
+        self.database.advisory_write()
+    }
+    fn clock(&self) -> Arc<dyn Clock> {
+        self.database.clock()
+    }
+    fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.database.set_clock(clock)
+    }
+    fn begin_transaction(&self) -> Result<(), Box<dyn Error>> {
+        self.database.begin_transaction()
+    }
+    fn commit_transaction(&self) -> Result<(), Box<dyn Error>> {
+        self.database.commit_transaction()
+    }
+    fn rollback_transaction(&self) -> Result<(), Box<dyn Error>> {
+        self.database.rollback_transaction()
+    }
+}
+
+struct FailingReinvestmentWrite {
+    write: Box<dyn WriteTransactionDB>,
+}
+
+impl WriteTransactionDB for FailingReinvestmentWrite {
+    fn create_transaction(
+        &mut self,
+        account: &Account,
+        amount: Decimal,
+        currency: &Currency,
+        category: TransactionCategory,
+    ) -> Result<Transaction, Box<dyn Error>> {
+        match category {
+            TransactionCategory::PaymentReinvestment(_) => {
+                Err("The reinvestment could not be written".into())
+            }
+            _ => self
+                .write
+                .create_transaction(account, amount, currency, category),
+        }
+    }
+
+    fn create_broker_transaction(
+        &mut self,
+        account: &Account,
+        amount: Decimal,
+        currency: &Currency,
+        category: TransactionCategory,
+        broker_activity_id: &str,
+    ) -> Result<Transaction, Box<dyn Error>> {
+        self.write.create_broker_transaction(
+            account,
+            amount,
+            currency,
+            category,
+            broker_activity_id,
+        )
+    }
+
+    fn create_reversal(
+        &mut self,
+        transaction: &Transaction,
+        category: TransactionCategory,
+    ) -> Result<Transaction, Box<dyn Error>> {
+        self.write.create_reversal(transaction, category)
+    }
 }
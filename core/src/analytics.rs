@@ -0,0 +1,33 @@
+mod annualized_return;
+mod capital_utilization;
+mod concentration_timeseries;
+mod drawdown;
+mod equity_curve;
+mod excursion;
+mod exposure;
+mod kelly;
+mod open_positions;
+mod performance_stats;
+mod pnl_by_asset_class;
+mod r_multiple;
+mod risk_heatmap;
+mod risk_of_ruin;
+
+pub use annualized_return::annualized_return;
+pub use capital_utilization::capital_utilization;
+pub use concentration_timeseries::{
+    concentration_timeseries, ConcentrationGroup, ConcentrationTimeseries, MetadataField,
+};
+pub use drawdown::{DrawdownMetrics, RealizedDrawdownCalculator};
+pub use equity_curve::{equity_curve, equity_curve_from};
+pub use excursion::{mae_mfe, MaeMfe};
+pub use exposure::{directional_exposure, DirectionalExposure};
+pub use kelly::kelly_criterion;
+pub use open_positions::{
+    aggregate_positions, calculate_open_positions, AggregatedPosition, OpenPosition,
+};
+pub use performance_stats::{performance_stats, PerformanceStats};
+pub use pnl_by_asset_class::{pnl_by_asset_class, AssetClassPnl};
+pub use r_multiple::{average_r_multiple, r_multiple};
+pub use risk_heatmap::{risk_heatmap, UNCLASSIFIED_SECTOR};
+pub use risk_of_ruin::risk_of_ruin;
@@ -0,0 +1,95 @@
+use chrono::NaiveDateTime;
+use rust_decimal::{Decimal, MathematicalOps};
+use rust_decimal_macros::dec;
+
+/// Calculates the compound annual growth rate (CAGR) as a percentage, using the
+/// first and the last point of the equity curve.
+///
+/// If the curve spans less than one day, the raw return is returned because
+/// annualizing it would produce meaningless numbers.
+/// Returns `None` if there are not enough points or the starting equity is zero.
+pub fn annualized_return(equity_snapshots: &[(NaiveDateTime, Decimal)]) -> Option<Decimal> {
+    let (start_date, start_equity) = equity_snapshots.first()?;
+    let (end_date, end_equity) = equity_snapshots.last()?;
+
+    if start_equity.is_zero() {
+        return None;
+    }
+
+    let growth = end_equity / start_equity;
+    let elapsed_seconds = Decimal::from((*end_date - *start_date).num_seconds());
+    let elapsed_days = elapsed_seconds / dec!(86400);
+
+    if elapsed_days < dec!(1) {
+        return Some((growth - dec!(1)) * dec!(100));
+    }
+
+    // A negative growth can not be compounded, the account was wiped out.
+    if growth <= dec!(0) {
+        return Some(dec!(-100));
+    }
+
+    let years = elapsed_days / dec!(365);
+    let annualized = growth.checked_powd(dec!(1) / years)?;
+
+    Some((annualized - dec!(1)) * dec!(100))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn test_annualized_return_empty() {
+        assert_eq!(annualized_return(&[]), None);
+    }
+
+    #[test]
+    fn test_annualized_return_zero_starting_equity() {
+        let now = Utc::now().naive_utc();
+        let snapshots = vec![(now, dec!(0)), (now + Duration::days(365), dec!(1000))];
+        assert_eq!(annualized_return(&snapshots), None);
+    }
+
+    #[test]
+    fn test_annualized_return_one_year_doubling() {
+        let now = Utc::now().naive_utc();
+        let snapshots = vec![
+            (now, dec!(1000)),
+            (now + Duration::days(180), dec!(1300)),
+            (now + Duration::days(365), dec!(2000)),
+        ];
+
+        let result = annualized_return(&snapshots).unwrap();
+
+        assert!((result - dec!(100)).abs() < dec!(0.01));
+    }
+
+    #[test]
+    fn test_annualized_return_two_years_doubling() {
+        let now = Utc::now().naive_utc();
+        let snapshots = vec![(now, dec!(1000)), (now + Duration::days(730), dec!(2000))];
+
+        let result = annualized_return(&snapshots).unwrap();
+
+        // sqrt(2) - 1
+        assert!((result - dec!(41.42)).abs() < dec!(0.01));
+    }
+
+    #[test]
+    fn test_annualized_return_less_than_one_day() {
+        let now = Utc::now().naive_utc();
+        let snapshots = vec![(now, dec!(1000)), (now + Duration::hours(5), dec!(1100))];
+
+        assert_eq!(annualized_return(&snapshots), Some(dec!(10)));
+    }
+
+    #[test]
+    fn test_annualized_return_wiped_out() {
+        let now = Utc::now().naive_utc();
+        let snapshots = vec![(now, dec!(1000)), (now + Duration::days(400), dec!(0))];
+
+        assert_eq!(annualized_return(&snapshots), Some(dec!(-100)));
+    }
+}
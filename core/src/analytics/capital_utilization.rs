@@ -0,0 +1,163 @@
+use chrono::NaiveDate;
+use model::{Transaction, TransactionCategory};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Calculates how much of the capital of an account was deployed in trades at the end of
+/// every day between `from` and `to`, both included.
+///
+/// The utilization is `in_trade / total_balance`, where:
+/// - `in_trade` is the money funded to trades that are not closed yet.
+///   It decreases when the money that was not used is paid back to the account.
+/// - `total_balance` is the money in the account plus `in_trade`.
+///
+/// Days without any balance have an utilization of 0.
+pub fn capital_utilization(
+    transactions: &[Transaction],
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<(NaiveDate, Decimal)> {
+    let mut sorted = transactions.to_vec();
+    sorted.sort_by_key(|tx| tx.created_at);
+
+    let mut cash = dec!(0);
+    let mut in_trade: HashMap<Uuid, Decimal> = HashMap::new();
+    let mut pending = sorted.into_iter().peekable();
+    let mut utilization = Vec::new();
+
+    for day in from.iter_days().take_while(|day| *day <= to) {
+        while let Some(tx) = pending.next_if(|tx| tx.created_at.date() <= day) {
+            match tx.category {
+                TransactionCategory::Deposit
+                | TransactionCategory::Dividend(_)
+                | TransactionCategory::Interest
+                | TransactionCategory::Adjustment { .. }
+                | TransactionCategory::FeeRebate(_) => cash += tx.amount,
+                TransactionCategory::Withdrawal
+                | TransactionCategory::WithdrawalTax
+                | TransactionCategory::WithdrawalEarnings
+                | TransactionCategory::FeeOpen(_)
+                | TransactionCategory::FeeClose(_)
+                | TransactionCategory::Fee => cash -= tx.amount,
+                TransactionCategory::FundTrade(trade_id) => {
+                    cash -= tx.amount;
+                    *in_trade.entry(trade_id).or_insert(dec!(0)) += tx.amount;
+                }
+                TransactionCategory::PaymentFromTrade(trade_id) => {
+                    cash += tx.amount;
+                    if let Some(funded) = in_trade.get_mut(&trade_id) {
+                        *funded = (*funded - tx.amount).max(dec!(0));
+                    }
+                }
+                TransactionCategory::CloseTarget(trade_id)
+                | TransactionCategory::CloseSafetyStop(trade_id)
+                | TransactionCategory::CloseSafetyStopSlippage(trade_id) => {
+                    in_trade.remove(&trade_id);
+                }
+                _ => {}
+            }
+        }
+
+        let deployed: Decimal = in_trade.values().sum();
+        let total_balance = cash + deployed;
+        let ratio = if total_balance > dec!(0) {
+            deployed / total_balance
+        } else {
+            dec!(0)
+        };
+        utilization.push((day, ratio));
+    }
+
+    utilization
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+    use model::Currency;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 6, day).unwrap()
+    }
+
+    fn at(day: u32) -> NaiveDateTime {
+        date(day).and_hms_opt(12, 0, 0).unwrap()
+    }
+
+    fn transaction(
+        category: TransactionCategory,
+        amount: Decimal,
+        created_at: NaiveDateTime,
+    ) -> Transaction {
+        Transaction {
+            created_at,
+            ..Transaction::new(Uuid::new_v4(), category, &Currency::USD, amount)
+        }
+    }
+
+    #[test]
+    fn test_capital_utilization_without_transactions() {
+        let utilization = capital_utilization(&[], date(1), date(3));
+
+        assert_eq!(
+            utilization,
+            vec![(date(1), dec!(0)), (date(2), dec!(0)), (date(3), dec!(0))]
+        );
+    }
+
+    #[test]
+    fn test_capital_utilization_funded_then_closed_trade() {
+        let trade_id = Uuid::new_v4();
+        let transactions = vec![
+            transaction(TransactionCategory::Deposit, dec!(10000), at(2)),
+            transaction(TransactionCategory::FundTrade(trade_id), dec!(4000), at(3)),
+            transaction(TransactionCategory::OpenTrade(trade_id), dec!(3800), at(4)),
+            transaction(
+                TransactionCategory::PaymentFromTrade(trade_id),
+                dec!(200),
+                at(4),
+            ),
+            transaction(
+                TransactionCategory::CloseTarget(trade_id),
+                dec!(4500),
+                at(6),
+            ),
+            transaction(
+                TransactionCategory::PaymentFromTrade(trade_id),
+                dec!(4500),
+                at(6),
+            ),
+        ];
+
+        let utilization = capital_utilization(&transactions, date(1), date(7));
+
+        assert_eq!(
+            utilization,
+            vec![
+                (date(1), dec!(0)),    // No balance yet
+                (date(2), dec!(0)),    // Only cash
+                (date(3), dec!(0.4)),  // 4000 of 10000 funded
+                (date(4), dec!(0.38)), // 200 were not used in the entry
+                (date(5), dec!(0.38)),
+                (date(6), dec!(0)), // The trade is closed
+                (date(7), dec!(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_capital_utilization_ignores_transactions_after_the_period() {
+        let trade_id = Uuid::new_v4();
+        let transactions = vec![
+            transaction(TransactionCategory::Deposit, dec!(1000), at(1)),
+            transaction(TransactionCategory::FundTrade(trade_id), dec!(500), at(5)),
+        ];
+
+        let utilization = capital_utilization(&transactions, date(1), date(2));
+
+        assert_eq!(utilization, vec![(date(1), dec!(0)), (date(2), dec!(0))]);
+    }
+}
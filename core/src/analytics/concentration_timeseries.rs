@@ -0,0 +1,257 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use model::{Status, Trade, Transaction, TransactionCategory};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// The field of a trade that is used to group the open positions.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MetadataField {
+    /// The category of the trading vehicle: stock, crypto or fiat.
+    AssetClass,
+    /// The symbol of the trading vehicle.
+    Symbol,
+    /// The direction of the trade: long or short.
+    Side,
+}
+
+/// The open positions that share the same value of a MetadataField.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ConcentrationGroup {
+    pub name: String,
+    pub notional: Decimal,
+
+    /// Percentage of the notional of all the open positions.
+    pub percentage: Decimal,
+}
+
+/// The concentration groups of the open positions at the end of every day.
+pub type ConcentrationTimeseries = Vec<(NaiveDate, Vec<ConcentrationGroup>)>;
+
+/// Calculates the concentration groups of the open positions at the end of every day
+/// between `from` and `to`, both included.
+///
+/// A trade is open since it is funded until it is closed by its target or its stop,
+/// or until its money is paid back if it was canceled. Days without open positions
+/// have no groups.
+pub fn concentration_timeseries(
+    trades: &[Trade],
+    transactions: &[Transaction],
+    field: MetadataField,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> ConcentrationTimeseries {
+    let windows = open_windows(trades, transactions);
+
+    from.iter_days()
+        .take_while(|day| *day <= to)
+        .map(|day| {
+            let open_trades: Vec<&Trade> = trades
+                .iter()
+                .filter(|trade| match windows.get(&trade.id) {
+                    Some((funded_at, closed_at)) => {
+                        funded_at.date() <= day
+                            && !closed_at.is_some_and(|closed_at| closed_at.date() <= day)
+                    }
+                    None => false,
+                })
+                .collect();
+            (day, groups(&open_trades, field))
+        })
+        .collect()
+}
+
+/// When every trade was funded and, if it is not open anymore, when it was closed.
+fn open_windows(
+    trades: &[Trade],
+    transactions: &[Transaction],
+) -> HashMap<Uuid, (NaiveDateTime, Option<NaiveDateTime>)> {
+    let mut sorted = transactions.to_vec();
+    sorted.sort_by_key(|tx| tx.created_at);
+
+    let canceled: Vec<Uuid> = trades
+        .iter()
+        .filter(|trade| trade.status == Status::Canceled)
+        .map(|trade| trade.id)
+        .collect();
+
+    let mut windows: HashMap<Uuid, (NaiveDateTime, Option<NaiveDateTime>)> = HashMap::new();
+    for tx in sorted {
+        match tx.category {
+            TransactionCategory::FundTrade(trade_id) => {
+                windows.entry(trade_id).or_insert((tx.created_at, None));
+            }
+            TransactionCategory::CloseTarget(trade_id)
+            | TransactionCategory::CloseSafetyStop(trade_id)
+            | TransactionCategory::CloseSafetyStopSlippage(trade_id) => {
+                close(&mut windows, trade_id, tx.created_at);
+            }
+            TransactionCategory::PaymentFromTrade(trade_id) if canceled.contains(&trade_id) => {
+                close(&mut windows, trade_id, tx.created_at);
+            }
+            _ => {}
+        }
+    }
+    windows
+}
+
+fn close(
+    windows: &mut HashMap<Uuid, (NaiveDateTime, Option<NaiveDateTime>)>,
+    trade_id: Uuid,
+    closed_at: NaiveDateTime,
+) {
+    if let Some((_, closed)) = windows.get_mut(&trade_id) {
+        closed.get_or_insert(closed_at);
+    }
+}
+
+fn groups(trades: &[&Trade], field: MetadataField) -> Vec<ConcentrationGroup> {
+    let mut notionals: HashMap<String, Decimal> = HashMap::new();
+    for trade in trades {
+        let name = match field {
+            MetadataField::AssetClass => trade.trading_vehicle.category.to_string(),
+            MetadataField::Symbol => trade.trading_vehicle.symbol.clone(),
+            MetadataField::Side => trade.category.to_string(),
+        };
+        *notionals.entry(name).or_insert(dec!(0)) +=
+            trade.entry.unit_price * trade.entry.decimal_quantity();
+    }
+
+    let total: Decimal = notionals.values().sum();
+    let mut groups: Vec<ConcentrationGroup> = notionals
+        .into_iter()
+        .map(|(name, notional)| ConcentrationGroup {
+            name,
+            notional,
+            percentage: if total > dec!(0) {
+                notional / total * dec!(100)
+            } else {
+                dec!(0)
+            },
+        })
+        .collect();
+
+    // The most crowded groups first
+    groups.sort_by(|a, b| b.notional.cmp(&a.notional).then(a.name.cmp(&b.name)));
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::{Currency, Order, TradingVehicle, TradingVehicleCategory};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 6, day).unwrap()
+    }
+
+    fn at(day: u32) -> NaiveDateTime {
+        date(day).and_hms_opt(12, 0, 0).unwrap()
+    }
+
+    fn transaction(category: TransactionCategory, created_at: NaiveDateTime) -> Transaction {
+        Transaction {
+            created_at,
+            ..Transaction::new(Uuid::new_v4(), category, &Currency::USD, dec!(4000))
+        }
+    }
+
+    fn trade(symbol: &str, category: TradingVehicleCategory) -> Trade {
+        Trade {
+            trading_vehicle: TradingVehicle {
+                symbol: symbol.to_string(),
+                category,
+                ..Default::default()
+            },
+            entry: Order {
+                unit_price: dec!(40),
+                quantity: 100,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_concentration_timeseries_position_opened_and_closed() {
+        let trade = trade("AAPL", TradingVehicleCategory::Stock);
+        let transactions = vec![
+            transaction(TransactionCategory::FundTrade(trade.id), at(2)),
+            transaction(TransactionCategory::OpenTrade(trade.id), at(2)),
+            transaction(TransactionCategory::CloseTarget(trade.id), at(4)),
+        ];
+
+        let series = concentration_timeseries(
+            &[trade],
+            &transactions,
+            MetadataField::AssetClass,
+            date(1),
+            date(5),
+        );
+
+        let stock = vec![ConcentrationGroup {
+            name: "stock".to_string(),
+            notional: dec!(4000),
+            percentage: dec!(100),
+        }];
+        assert_eq!(
+            series,
+            vec![
+                (date(1), vec![]),
+                (date(2), stock.clone()),
+                (date(3), stock),
+                (date(4), vec![]), // The trade is closed
+                (date(5), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_concentration_timeseries_groups_by_asset_class() {
+        let stock = trade("AAPL", TradingVehicleCategory::Stock);
+        let crypto = trade("BTC", TradingVehicleCategory::Crypto);
+        let other_crypto = trade("ETH", TradingVehicleCategory::Crypto);
+        let transactions = vec![
+            transaction(TransactionCategory::FundTrade(stock.id), at(1)),
+            transaction(TransactionCategory::FundTrade(crypto.id), at(1)),
+            transaction(TransactionCategory::FundTrade(other_crypto.id), at(1)),
+        ];
+
+        let series = concentration_timeseries(
+            &[stock, crypto, other_crypto],
+            &transactions,
+            MetadataField::AssetClass,
+            date(1),
+            date(1),
+        );
+
+        let (_, groups) = &series[0];
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].name, "crypto");
+        assert_eq!(groups[0].notional, dec!(8000));
+        assert_eq!(groups[1].name, "stock");
+        assert_eq!(groups[1].percentage.round_dp(2), dec!(33.33));
+    }
+
+    #[test]
+    fn test_concentration_timeseries_canceled_trade() {
+        let mut trade = trade("AAPL", TradingVehicleCategory::Stock);
+        trade.status = Status::Canceled;
+        let transactions = vec![
+            transaction(TransactionCategory::FundTrade(trade.id), at(1)),
+            transaction(TransactionCategory::PaymentFromTrade(trade.id), at(2)),
+        ];
+
+        let series = concentration_timeseries(
+            &[trade],
+            &transactions,
+            MetadataField::Symbol,
+            date(1),
+            date(2),
+        );
+
+        assert_eq!(series[0].1.len(), 1);
+        assert!(series[1].1.is_empty());
+    }
+}
@@ -0,0 +1,383 @@
+use chrono::NaiveDateTime;
+use model::{EquitySnapshot, Transaction, TransactionCategory};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Drawdown metrics of an equity curve.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DrawdownMetrics {
+    /// The highest balance of the curve.
+    pub peak: Decimal,
+
+    /// The biggest decline from a peak to a following trough.
+    pub max_drawdown: Decimal,
+
+    /// The biggest decline in percentage of the peak where it started.
+    pub max_drawdown_percentage: Decimal,
+
+    /// The decline from the highest balance to the latest one.
+    pub current_drawdown: Decimal,
+}
+
+pub struct RealizedDrawdownCalculator;
+
+impl RealizedDrawdownCalculator {
+    /// Calculates the realized equity curve from all the transactions of an account.
+    ///
+    /// The curve starts at the capital deposited before the first realized profit or loss, and
+    /// from there it only moves with the realized profit and loss of the trades: their closes,
+    /// fees, rebates and dividends. Deposits, withdrawals and the money moved into and out of
+    /// trades are not gains or losses, so they do not move the curve once the account trades.
+    pub fn calculate_equity_curve(transactions: &[Transaction]) -> Vec<(NaiveDateTime, Decimal)> {
+        let pnl = realized_pnl(transactions);
+        let first_pnl_at = pnl.first().map(|(timestamp, _)| *timestamp);
+
+        // 1. The capital deposited before the first realized profit or loss
+        let mut sorted = transactions.to_vec();
+        sorted.sort_by_key(|tx| tx.created_at);
+        let mut capital: Option<(NaiveDateTime, Decimal)> = None;
+        for tx in sorted
+            .iter()
+            .filter(|tx| first_pnl_at.is_none_or(|first| tx.created_at <= first))
+        {
+            let flow = match tx.category {
+                TransactionCategory::Deposit => tx.amount,
+                TransactionCategory::Withdrawal
+                | TransactionCategory::WithdrawalTax
+                | TransactionCategory::WithdrawalEarnings => -tx.amount,
+                _ => continue,
+            };
+            let balance = capital.map_or(dec!(0), |(_, balance)| balance);
+            capital = Some((tx.created_at, balance + flow));
+        }
+
+        // 2. The capital moved by the realized profit and loss
+        let mut balance = capital.map_or(dec!(0), |(_, balance)| balance);
+        let mut curve: Vec<(NaiveDateTime, Decimal)> = capital.into_iter().collect();
+        curve.extend(pnl.into_iter().map(|(timestamp, pnl)| {
+            balance += pnl;
+            (timestamp, balance)
+        }));
+        curve
+    }
+
+    /// Calculates the realized equity curve using the snapshots as a starting point.
+    /// Only the profit and loss realized after the last snapshot is folded into the curve.
+    ///
+    /// The transactions need to include the opening of the trades closed after the last
+    /// snapshot, even if they were opened before it.
+    pub fn calculate_equity_curve_from_snapshots(
+        snapshots: &[EquitySnapshot],
+        transactions: &[Transaction],
+    ) -> Vec<(NaiveDateTime, Decimal)> {
+        let last = match snapshots.iter().max_by_key(|snapshot| snapshot.timestamp) {
+            Some(last) => last,
+            None => return RealizedDrawdownCalculator::calculate_equity_curve(transactions),
+        };
+
+        let mut curve: Vec<(NaiveDateTime, Decimal)> = snapshots
+            .iter()
+            .map(|snapshot| (snapshot.timestamp, snapshot.balance))
+            .collect();
+        curve.sort_by_key(|point| point.0);
+
+        let mut balance = last.balance;
+        curve.extend(
+            realized_pnl(transactions)
+                .into_iter()
+                .filter(|(timestamp, _)| *timestamp > last.timestamp)
+                .map(|(timestamp, pnl)| {
+                    balance += pnl;
+                    (timestamp, balance)
+                }),
+        );
+        curve
+    }
+
+    /// Returns the points of the curve that need to be stored as snapshots.
+    /// Points that do not change the balance do not change the drawdown, so they are skipped.
+    pub fn checkpoints(curve: &[(NaiveDateTime, Decimal)]) -> Vec<(NaiveDateTime, Decimal)> {
+        let mut checkpoints: Vec<(NaiveDateTime, Decimal)> = Vec::new();
+        for (timestamp, balance) in curve {
+            match checkpoints.last() {
+                Some(last) if last.1 == *balance => {}
+                _ => checkpoints.push((*timestamp, *balance)),
+            }
+        }
+        checkpoints
+    }
+
+    pub fn calculate_metrics(curve: &[(NaiveDateTime, Decimal)]) -> DrawdownMetrics {
+        let mut peak = dec!(0);
+        let mut max_drawdown = dec!(0);
+        let mut max_drawdown_percentage = dec!(0);
+        let mut current_drawdown = dec!(0);
+
+        for (_, balance) in curve {
+            if *balance > peak {
+                peak = *balance;
+            }
+            current_drawdown = peak - balance;
+            if current_drawdown > max_drawdown {
+                max_drawdown = current_drawdown;
+            }
+            if peak > dec!(0) {
+                let percentage = current_drawdown / peak * dec!(100);
+                if percentage > max_drawdown_percentage {
+                    max_drawdown_percentage = percentage;
+                }
+            }
+        }
+
+        DrawdownMetrics {
+            peak,
+            max_drawdown,
+            max_drawdown_percentage,
+            current_drawdown,
+        }
+    }
+}
+
+/// The profit or loss realized by every close, fee, rebate and dividend of a trade.
+/// A close realizes the difference with the money that opened the trade.
+fn realized_pnl(transactions: &[Transaction]) -> Vec<(NaiveDateTime, Decimal)> {
+    let mut sorted = transactions.to_vec();
+    sorted.sort_by_key(|tx| tx.created_at);
+
+    let mut opened: HashMap<Uuid, Decimal> = HashMap::new();
+    sorted
+        .into_iter()
+        .filter_map(|tx| {
+            let pnl = match tx.category {
+                TransactionCategory::OpenTrade(trade_id) => {
+                    *opened.entry(trade_id).or_insert(dec!(0)) += tx.amount;
+                    return None;
+                }
+                TransactionCategory::CloseTarget(trade_id)
+                | TransactionCategory::CloseSafetyStop(trade_id)
+                | TransactionCategory::CloseSafetyStopSlippage(trade_id) => {
+                    tx.amount - opened.remove(&trade_id).unwrap_or(dec!(0))
+                }
+                TransactionCategory::FeeOpen(_) | TransactionCategory::FeeClose(_) => -tx.amount,
+                TransactionCategory::FeeRebate(_) | TransactionCategory::Dividend(_) => tx.amount,
+                _ => return None,
+            };
+            Some((tx.created_at, pnl))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use model::{Currency, TransactionCategory};
+    use uuid::Uuid;
+
+    fn transaction(
+        category: TransactionCategory,
+        amount: Decimal,
+        created_at: NaiveDateTime,
+    ) -> Transaction {
+        Transaction {
+            created_at,
+            ..Transaction::new(Uuid::new_v4(), category, &Currency::USD, amount)
+        }
+    }
+
+    fn transactions(now: NaiveDateTime) -> Vec<Transaction> {
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+        vec![
+            transaction(TransactionCategory::Deposit, dec!(10000), now),
+            transaction(
+                TransactionCategory::FundTrade(first),
+                dec!(2000),
+                now + Duration::days(1),
+            ),
+            transaction(
+                TransactionCategory::OpenTrade(first),
+                dec!(2000),
+                now + Duration::days(1),
+            ),
+            transaction(
+                TransactionCategory::CloseTarget(first),
+                dec!(3000),
+                now + Duration::days(2),
+            ),
+            transaction(
+                TransactionCategory::OpenTrade(second),
+                dec!(4000),
+                now + Duration::days(3),
+            ),
+            transaction(
+                TransactionCategory::CloseSafetyStop(second),
+                dec!(2500),
+                now + Duration::days(4),
+            ),
+            transaction(
+                TransactionCategory::Deposit,
+                dec!(500),
+                now + Duration::days(5),
+            ),
+        ]
+    }
+
+    fn snapshots(curve: &[(NaiveDateTime, Decimal)]) -> Vec<EquitySnapshot> {
+        RealizedDrawdownCalculator::checkpoints(curve)
+            .into_iter()
+            .map(|(timestamp, balance)| EquitySnapshot {
+                timestamp,
+                balance,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_calculate_metrics() {
+        let now = Utc::now().naive_utc();
+        let curve = RealizedDrawdownCalculator::calculate_equity_curve(&transactions(now));
+
+        let metrics = RealizedDrawdownCalculator::calculate_metrics(&curve);
+
+        assert_eq!(
+            metrics,
+            DrawdownMetrics {
+                peak: dec!(11000),
+                max_drawdown: dec!(1500),
+                max_drawdown_percentage: dec!(1500) / dec!(11000) * dec!(100),
+                current_drawdown: dec!(1500),
+            }
+        );
+    }
+
+    #[test]
+    fn test_calculate_equity_curve_ignores_cash_flows_and_funding() {
+        let now = Utc::now().naive_utc();
+        let mut transactions = transactions(now);
+        transactions.push(transaction(
+            TransactionCategory::Withdrawal,
+            dec!(5000),
+            now + Duration::days(6),
+        ));
+
+        let curve = RealizedDrawdownCalculator::calculate_equity_curve(&transactions);
+
+        assert_eq!(
+            curve,
+            vec![
+                (now, dec!(10000)),
+                (now + Duration::days(2), dec!(11000)),
+                (now + Duration::days(4), dec!(9500)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_calculate_equity_curve_with_fees_and_dividends() {
+        let now = Utc::now().naive_utc();
+        let trade = Uuid::new_v4();
+        let transactions = vec![
+            transaction(TransactionCategory::Deposit, dec!(1000), now),
+            transaction(TransactionCategory::Withdrawal, dec!(200), now),
+            transaction(
+                TransactionCategory::OpenTrade(trade),
+                dec!(500),
+                now + Duration::days(1),
+            ),
+            transaction(
+                TransactionCategory::FeeOpen(trade),
+                dec!(1),
+                now + Duration::days(1),
+            ),
+            transaction(
+                TransactionCategory::Dividend(Some(trade)),
+                dec!(10),
+                now + Duration::days(2),
+            ),
+            transaction(
+                TransactionCategory::CloseTarget(trade),
+                dec!(600),
+                now + Duration::days(3),
+            ),
+        ];
+
+        let curve = RealizedDrawdownCalculator::calculate_equity_curve(&transactions);
+
+        assert_eq!(
+            curve,
+            vec![
+                (now, dec!(800)),
+                (now + Duration::days(1), dec!(799)),
+                (now + Duration::days(2), dec!(809)),
+                (now + Duration::days(3), dec!(909)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_calculate_metrics_empty() {
+        let metrics = RealizedDrawdownCalculator::calculate_metrics(&[]);
+
+        assert_eq!(metrics.peak, dec!(0));
+        assert_eq!(metrics.max_drawdown, dec!(0));
+        assert_eq!(metrics.max_drawdown_percentage, dec!(0));
+        assert_eq!(metrics.current_drawdown, dec!(0));
+    }
+
+    #[test]
+    fn test_snapshots_and_from_scratch_produce_same_metrics() {
+        let now = Utc::now().naive_utc();
+        let transactions = transactions(now);
+
+        // Snapshots are taken after the first four transactions
+        let curve = RealizedDrawdownCalculator::calculate_equity_curve(&transactions[..4]);
+        let snapshots = snapshots(&curve);
+
+        let from_scratch = RealizedDrawdownCalculator::calculate_metrics(
+            &RealizedDrawdownCalculator::calculate_equity_curve(&transactions),
+        );
+        let from_snapshots = RealizedDrawdownCalculator::calculate_metrics(
+            &RealizedDrawdownCalculator::calculate_equity_curve_from_snapshots(
+                &snapshots,
+                &transactions,
+            ),
+        );
+
+        assert_eq!(from_snapshots, from_scratch);
+    }
+
+    #[test]
+    fn test_curve_from_snapshots_without_snapshots() {
+        let now = Utc::now().naive_utc();
+        let transactions = transactions(now);
+
+        assert_eq!(
+            RealizedDrawdownCalculator::calculate_equity_curve_from_snapshots(&[], &transactions),
+            RealizedDrawdownCalculator::calculate_equity_curve(&transactions)
+        );
+    }
+
+    #[test]
+    fn test_checkpoints_skip_points_without_changes() {
+        let now = Utc::now().naive_utc();
+        let curve = vec![
+            (now, dec!(100)),
+            (now + Duration::days(1), dec!(100)),
+            (now + Duration::days(2), dec!(90)),
+            (now + Duration::days(2), dec!(80)),
+        ];
+
+        assert_eq!(
+            RealizedDrawdownCalculator::checkpoints(&curve),
+            vec![
+                (now, dec!(100)),
+                (now + Duration::days(2), dec!(90)),
+                (now + Duration::days(2), dec!(80)),
+            ]
+        );
+    }
+}
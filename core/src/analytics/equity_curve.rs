@@ -0,0 +1,100 @@
+use chrono::NaiveDateTime;
+use model::{Transaction, TransactionCategory};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// Builds the equity curve of an account from its transactions.
+/// Every point is the account balance right after a transaction was created.
+/// The movements follow the same rules as `AccountCapitalBalance`.
+pub fn equity_curve(transactions: &[Transaction]) -> Vec<(NaiveDateTime, Decimal)> {
+    equity_curve_from(dec!(0), transactions)
+}
+
+/// Builds the equity curve of an account starting from a known balance.
+pub fn equity_curve_from(
+    starting_balance: Decimal,
+    transactions: &[Transaction],
+) -> Vec<(NaiveDateTime, Decimal)> {
+    let mut sorted = transactions.to_vec();
+    sorted.sort_by_key(|tx| tx.created_at);
+
+    let mut balance = starting_balance;
+    sorted
+        .into_iter()
+        .map(|tx| {
+            match tx.category {
+                TransactionCategory::Withdrawal
+                | TransactionCategory::WithdrawalTax
+                | TransactionCategory::WithdrawalEarnings
+                | TransactionCategory::FeeOpen(_)
+                | TransactionCategory::FeeClose(_)
+                | TransactionCategory::Fee
+                | TransactionCategory::OpenTrade(_) => balance -= tx.amount,
+                TransactionCategory::Deposit
+                | TransactionCategory::Dividend(_)
+                | TransactionCategory::Interest
+                | TransactionCategory::Adjustment { .. }
+                | TransactionCategory::FeeRebate(_)
+                | TransactionCategory::CloseSafetyStop(_)
+                | TransactionCategory::CloseTarget(_)
+                | TransactionCategory::CloseSafetyStopSlippage(_) => balance += tx.amount,
+                _ => {}
+            }
+            (tx.created_at, balance)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use model::Currency;
+    use uuid::Uuid;
+
+    fn transaction(
+        category: TransactionCategory,
+        amount: Decimal,
+        created_at: NaiveDateTime,
+    ) -> Transaction {
+        Transaction {
+            created_at,
+            ..Transaction::new(Uuid::new_v4(), category, &Currency::USD, amount)
+        }
+    }
+
+    #[test]
+    fn test_equity_curve_empty() {
+        assert!(equity_curve(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_equity_curve_is_sorted_and_accumulated() {
+        let now = Utc::now().naive_utc();
+        let trade_id = Uuid::new_v4();
+        let transactions = vec![
+            transaction(
+                TransactionCategory::CloseTarget(trade_id),
+                dec!(600),
+                now + Duration::days(2),
+            ),
+            transaction(TransactionCategory::Deposit, dec!(1000), now),
+            transaction(
+                TransactionCategory::OpenTrade(trade_id),
+                dec!(500),
+                now + Duration::days(1),
+            ),
+        ];
+
+        let curve = equity_curve(&transactions);
+
+        assert_eq!(
+            curve,
+            vec![
+                (now, dec!(1000)),
+                (now + Duration::days(1), dec!(500)),
+                (now + Duration::days(2), dec!(1100)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use model::{MarketBar, Trade, TradeCategory};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// The maximum adverse excursion (MAE) and the maximum favorable excursion (MFE) of a trade:
+/// how far the price moved against it and in its favor while it was open.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MaeMfe {
+    /// The worst move against the trade from its entry, per unit.
+    /// It is zero if the price never moved against the trade.
+    pub adverse: Decimal,
+
+    /// The best move in favor of the trade from its entry, per unit.
+    /// It is zero if the price never moved in favor of the trade.
+    pub favorable: Decimal,
+
+    /// The adverse excursion divided by the risk per unit between the entry and the safety stop.
+    /// `None` if the trade did not risk any money.
+    pub adverse_r: Option<Decimal>,
+
+    /// The favorable excursion divided by the risk per unit between the entry and the safety stop.
+    /// `None` if the trade did not risk any money.
+    pub favorable_r: Option<Decimal>,
+}
+
+/// Calculates the excursions of a trade from the bars of its trading vehicle.
+/// Only the bars from the fill of the entry to the close of the trade are used.
+/// An open trade uses every bar after the fill.
+/// Returns `None` if the entry was not filled or if no bar is inside the window.
+pub fn mae_mfe(trade: &Trade, bars: &[MarketBar]) -> Option<MaeMfe> {
+    let opened_at = trade.entry.filled_at?;
+    let closed_at = match trade.status.is_terminal() {
+        true => Some(trade.closed_at()),
+        false => None,
+    };
+    let bars: Vec<&MarketBar> = bars
+        .iter()
+        .filter(|bar| {
+            bar.timestamp >= opened_at
+                && closed_at.is_none_or(|closed_at| bar.timestamp <= closed_at)
+        })
+        .collect();
+    let low = bars.iter().map(|bar| bar.low).min()?;
+    let high = bars.iter().map(|bar| bar.high).max()?;
+
+    let entry = trade
+        .entry
+        .average_filled_price
+        .unwrap_or(trade.entry.unit_price);
+    let (adverse, favorable) = match trade.category {
+        TradeCategory::Long => (entry - low, high - entry),
+        TradeCategory::Short => (high - entry, entry - low),
+    };
+    let adverse = adverse.max(dec!(0));
+    let favorable = favorable.max(dec!(0));
+
+    let risk = (trade.entry.unit_price - trade.safety_stop.unit_price).abs();
+    let in_r = |excursion: Decimal| match risk == dec!(0) {
+        true => None,
+        false => Some(excursion / risk),
+    };
+
+    Some(MaeMfe {
+        adverse,
+        favorable,
+        adverse_r: in_r(adverse),
+        favorable_r: in_r(favorable),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, NaiveDateTime};
+    use model::{Order, Status};
+
+    fn day(day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2023, 8, day)
+            .unwrap()
+            .and_hms_opt(15, 0, 0)
+            .unwrap()
+    }
+
+    fn bar(day_of_month: u32, low: Decimal, high: Decimal) -> MarketBar {
+        MarketBar {
+            timestamp: day(day_of_month),
+            open: low,
+            high,
+            low,
+            close: high,
+            volume: dec!(1000),
+        }
+    }
+
+    fn trade(category: TradeCategory, stop: Decimal, entry: Decimal) -> Trade {
+        Trade {
+            category,
+            status: Status::ClosedTarget,
+            safety_stop: Order {
+                unit_price: stop,
+                ..Default::default()
+            },
+            entry: Order {
+                unit_price: entry,
+                filled_at: Some(day(2)),
+                ..Default::default()
+            },
+            target: Order {
+                closed_at: Some(day(6)),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_mae_mfe_long_that_dipped_then_rallied() {
+        let trade = trade(TradeCategory::Long, dec!(38), dec!(40));
+        let bars = vec![
+            bar(1, dec!(30), dec!(60)), // Before the entry
+            bar(2, dec!(39.5), dec!(40.5)),
+            bar(3, dec!(39), dec!(40)),
+            bar(4, dec!(40), dec!(43)),
+            bar(5, dec!(42), dec!(45)),
+            bar(6, dec!(44), dec!(44.5)),
+            bar(7, dec!(20), dec!(80)), // After the close
+        ];
+
+        let excursion = mae_mfe(&trade, &bars).unwrap();
+
+        assert_eq!(excursion.adverse, dec!(1));
+        assert_eq!(excursion.favorable, dec!(5));
+        assert_eq!(excursion.adverse_r, Some(dec!(0.5)));
+        assert_eq!(excursion.favorable_r, Some(dec!(2.5)));
+    }
+
+    #[test]
+    fn test_mae_mfe_short() {
+        let trade = trade(TradeCategory::Short, dec!(42), dec!(40));
+        let bars = vec![bar(3, dec!(37), dec!(41)), bar(4, dec!(36), dec!(39))];
+
+        let excursion = mae_mfe(&trade, &bars).unwrap();
+
+        assert_eq!(excursion.adverse, dec!(1));
+        assert_eq!(excursion.favorable, dec!(4));
+        assert_eq!(excursion.adverse_r, Some(dec!(0.5)));
+        assert_eq!(excursion.favorable_r, Some(dec!(2)));
+    }
+
+    #[test]
+    fn test_mae_mfe_without_bars_in_the_window() {
+        let trade = trade(TradeCategory::Long, dec!(38), dec!(40));
+
+        assert_eq!(mae_mfe(&trade, &[]), None);
+        assert_eq!(mae_mfe(&trade, &[bar(1, dec!(39), dec!(41))]), None);
+
+        let not_filled = Trade {
+            entry: Order {
+                unit_price: dec!(40),
+                ..Default::default()
+            },
+            ..trade
+        };
+        assert_eq!(mae_mfe(&not_filled, &[bar(3, dec!(39), dec!(41))]), None);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::analytics::OpenPosition;
+use model::{MarketPriceProvider, TradeCategory};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// Notional value of the open positions of an account by direction.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DirectionalExposure {
+    pub long_notional: Decimal,
+    pub short_notional: Decimal,
+
+    /// Long notional minus short notional. A positive number means a bullish bias.
+    pub net: Decimal,
+
+    /// True if the market price of any position was not known and its entry price was used.
+    pub uses_entry_prices: bool,
+}
+
+/// Calculates the notional of the open positions with their market price.
+/// If the market price of a position is not known, its entry price is used instead.
+pub fn directional_exposure(
+    positions: &[OpenPosition],
+    prices: &dyn MarketPriceProvider,
+) -> DirectionalExposure {
+    let mut long_notional = dec!(0);
+    let mut short_notional = dec!(0);
+    let mut uses_entry_prices = false;
+
+    for position in positions {
+        let price = match prices.latest_price(&position.trading_vehicle) {
+            Some(price) => price,
+            None => {
+                uses_entry_prices = true;
+                position.entry_price
+            }
+        };
+        let notional = price * position.quantity;
+
+        match position.category {
+            TradeCategory::Long => long_notional += notional,
+            TradeCategory::Short => short_notional += notional,
+        }
+    }
+
+    DirectionalExposure {
+        long_notional,
+        short_notional,
+        net: long_notional - short_notional,
+        uses_entry_prices,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::{Currency, TradingVehicle};
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    struct Prices(HashMap<String, Decimal>);
+
+    impl MarketPriceProvider for Prices {
+        fn latest_price(&self, trading_vehicle: &TradingVehicle) -> Option<Decimal> {
+            self.0.get(&trading_vehicle.symbol).cloned()
+        }
+    }
+
+    fn position(symbol: &str, category: TradeCategory, quantity: u64) -> OpenPosition {
+        OpenPosition {
+            trade_id: Uuid::new_v4(),
+            trading_vehicle: TradingVehicle {
+                symbol: symbol.to_string(),
+                ..Default::default()
+            },
+            category,
+            currency: Currency::USD,
+            quantity: Decimal::from(quantity),
+            entry_price: dec!(10),
+            stop_price: dec!(9),
+        }
+    }
+
+    #[test]
+    fn test_directional_exposure_long_and_short() {
+        let prices = Prices(HashMap::from([
+            ("AAPL".to_string(), dec!(150)),
+            ("TSLA".to_string(), dec!(200)),
+        ]));
+        let positions = vec![
+            position("AAPL", TradeCategory::Long, 100),
+            position("TSLA", TradeCategory::Short, 30),
+        ];
+
+        assert_eq!(
+            directional_exposure(&positions, &prices),
+            DirectionalExposure {
+                long_notional: dec!(15000),
+                short_notional: dec!(6000),
+                net: dec!(9000),
+                uses_entry_prices: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_directional_exposure_missing_price() {
+        let prices = Prices(HashMap::from([("AAPL".to_string(), dec!(150))]));
+        let positions = vec![
+            position("AAPL", TradeCategory::Long, 10),
+            position("TSLA", TradeCategory::Short, 300),
+        ];
+
+        assert_eq!(
+            directional_exposure(&positions, &prices),
+            DirectionalExposure {
+                long_notional: dec!(1500),
+                short_notional: dec!(3000),
+                net: dec!(-1500),
+                uses_entry_prices: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_directional_exposure_without_positions() {
+        let prices = Prices(HashMap::new());
+        let exposure = directional_exposure(&[], &prices);
+        assert_eq!(exposure.net, dec!(0));
+        assert!(!exposure.uses_entry_prices);
+    }
+}
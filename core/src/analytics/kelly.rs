@@ -0,0 +1,71 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// Calculates the Kelly criterion from the results of closed trades:
+/// `win_rate - (1 - win_rate) / payoff_ratio`, where the payoff ratio is the
+/// average win divided by the average loss.
+///
+/// The result is the fraction of capital that the system should risk.
+/// A negative result means that the system loses money and it should not be traded.
+/// Returns `None` if there are no results.
+pub fn kelly_criterion(results: &[Decimal]) -> Option<Decimal> {
+    if results.is_empty() {
+        return None;
+    }
+
+    let wins: Vec<Decimal> = results.iter().filter(|r| **r > dec!(0)).cloned().collect();
+    let losses: Vec<Decimal> = results.iter().filter(|r| **r < dec!(0)).cloned().collect();
+    let win_rate = Decimal::from(wins.len()) / Decimal::from(results.len());
+
+    // Without losses the payoff ratio is infinite, so the criterion is the win rate.
+    if losses.is_empty() {
+        return Some(win_rate);
+    }
+
+    // Without wins the system never made money.
+    if wins.is_empty() {
+        return Some(dec!(-1));
+    }
+
+    let average_win = wins.iter().sum::<Decimal>() / Decimal::from(wins.len());
+    let average_loss = -losses.iter().sum::<Decimal>() / Decimal::from(losses.len());
+    let payoff_ratio = average_win / average_loss;
+
+    Some(win_rate - (dec!(1) - win_rate) / payoff_ratio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kelly_criterion_empty() {
+        assert_eq!(kelly_criterion(&[]), None);
+    }
+
+    #[test]
+    fn test_kelly_criterion_profitable_system() {
+        // 60% win rate with a payoff ratio of 2
+        let results = vec![dec!(200), dec!(-100), dec!(200), dec!(200), dec!(-100)];
+        assert_eq!(kelly_criterion(&results), Some(dec!(0.4)));
+    }
+
+    #[test]
+    fn test_kelly_criterion_losing_system() {
+        // 25% win rate with a payoff ratio of 1
+        let results = vec![dec!(100), dec!(-100), dec!(-100), dec!(-100)];
+        assert_eq!(kelly_criterion(&results), Some(dec!(-0.5)));
+    }
+
+    #[test]
+    fn test_kelly_criterion_without_losses() {
+        let results = vec![dec!(100), dec!(0)];
+        assert_eq!(kelly_criterion(&results), Some(dec!(0.5)));
+    }
+
+    #[test]
+    fn test_kelly_criterion_without_wins() {
+        let results = vec![dec!(-100), dec!(-50)];
+        assert_eq!(kelly_criterion(&results), Some(dec!(-1)));
+    }
+}
@@ -0,0 +1,270 @@
+use model::{Currency, Status, Trade, TradeCategory, TradingVehicle};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+/// A position of a trade that is in the market.
+#[derive(Debug, PartialEq, Clone)]
+pub struct OpenPosition {
+    pub trade_id: Uuid,
+    pub trading_vehicle: TradingVehicle,
+    pub category: TradeCategory,
+    pub currency: Currency,
+
+    /// Quantity of the entry that was filled. It is fractional for fractional orders.
+    pub quantity: Decimal,
+
+    /// Average price of the entry fills, or the entry price if the broker did not report it.
+    pub entry_price: Decimal,
+
+    /// Price of the safety stop.
+    pub stop_price: Decimal,
+}
+
+impl OpenPosition {
+    /// The money that is lost if the position is closed at its safety stop.
+    pub fn capital_at_risk(&self) -> Decimal {
+        (self.entry_price - self.stop_price).abs() * self.quantity
+    }
+}
+
+/// Returns the positions of the trades that have shares in the market.
+/// Trades that are not filled, or that were already closed, do not have a position.
+pub fn calculate_open_positions(trades: &[Trade]) -> Vec<OpenPosition> {
+    trades
+        .iter()
+        .filter(|trade| {
+            matches!(
+                trade.status,
+                Status::PartiallyFilled | Status::Filled | Status::ClosingPending
+            )
+        })
+        .filter(|trade| trade.entry.decimal_filled_quantity() > Decimal::ZERO)
+        .map(|trade| OpenPosition {
+            trade_id: trade.id,
+            trading_vehicle: trade.trading_vehicle.clone(),
+            category: trade.category,
+            currency: trade.currency,
+            quantity: trade.entry.decimal_filled_quantity(),
+            entry_price: trade
+                .entry
+                .average_filled_price
+                .unwrap_or(trade.entry.unit_price),
+            stop_price: trade.safety_stop.unit_price,
+        })
+        .collect()
+}
+
+/// The open positions of a trading vehicle consolidated in one position.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AggregatedPosition {
+    pub trading_vehicle: TradingVehicle,
+    pub currency: Currency,
+
+    /// The direction of the net quantity.
+    pub category: TradeCategory,
+
+    /// Long quantity minus short quantity, in the direction of the category.
+    pub quantity: Decimal,
+
+    /// Volume weighted average entry price of the positions in the direction of the category.
+    pub average_entry_price: Decimal,
+
+    /// Sum of the capital at risk of every position until its safety stop.
+    pub capital_at_risk: Decimal,
+}
+
+/// Consolidates the open positions by trading vehicle and currency.
+/// Longs and shorts of the same trading vehicle net, and flat trading vehicles are omitted.
+pub fn aggregate_positions(positions: &[OpenPosition]) -> Vec<AggregatedPosition> {
+    let mut groups: Vec<Vec<&OpenPosition>> = Vec::new();
+    for position in positions {
+        let group = groups.iter_mut().find(|group| {
+            group[0].trading_vehicle.id == position.trading_vehicle.id
+                && group[0].currency == position.currency
+        });
+        match group {
+            Some(group) => group.push(position),
+            None => groups.push(vec![position]),
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter_map(|group| aggregate(&group))
+        .collect()
+}
+
+fn aggregate(positions: &[&OpenPosition]) -> Option<AggregatedPosition> {
+    let quantity = |category: TradeCategory| -> Decimal {
+        positions
+            .iter()
+            .filter(|position| position.category == category)
+            .map(|position| position.quantity)
+            .sum()
+    };
+    let long = quantity(TradeCategory::Long);
+    let short = quantity(TradeCategory::Short);
+
+    let (category, net_quantity) = match long.cmp(&short) {
+        std::cmp::Ordering::Greater => (TradeCategory::Long, long - short),
+        std::cmp::Ordering::Less => (TradeCategory::Short, short - long),
+        std::cmp::Ordering::Equal => return None,
+    };
+
+    let side: Vec<&&OpenPosition> = positions
+        .iter()
+        .filter(|position| position.category == category)
+        .collect();
+    let side_quantity: Decimal = side.iter().map(|position| position.quantity).sum();
+    let side_notional: Decimal = side
+        .iter()
+        .map(|position| position.entry_price * position.quantity)
+        .sum();
+
+    let capital_at_risk = positions
+        .iter()
+        .map(|position| position.capital_at_risk())
+        .sum();
+
+    Some(AggregatedPosition {
+        trading_vehicle: positions[0].trading_vehicle.clone(),
+        currency: positions[0].currency,
+        category,
+        quantity: net_quantity,
+        average_entry_price: side_notional / side_quantity,
+        capital_at_risk,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::Order;
+    use rust_decimal_macros::dec;
+
+    fn trade(status: Status, filled_quantity: u64, average_filled_price: Option<Decimal>) -> Trade {
+        Trade {
+            status,
+            entry: Order {
+                unit_price: dec!(40),
+                quantity: 100,
+                filled_quantity,
+                average_filled_price,
+                ..Default::default()
+            },
+            safety_stop: Order {
+                unit_price: dec!(38),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_calculate_open_positions() {
+        let filled = trade(Status::Filled, 100, Some(dec!(39.9)));
+        let partially_filled = trade(Status::PartiallyFilled, 50, None);
+        let trades = vec![
+            filled.clone(),
+            partially_filled.clone(),
+            trade(Status::Submitted, 0, None),
+            trade(Status::ClosedTarget, 100, Some(dec!(39.9))),
+        ];
+
+        let positions = calculate_open_positions(&trades);
+
+        assert_eq!(positions.len(), 2);
+        assert_eq!(positions[0].trade_id, filled.id);
+        assert_eq!(positions[0].quantity, dec!(100));
+        assert_eq!(positions[0].entry_price, dec!(39.9));
+        assert_eq!(positions[0].stop_price, dec!(38));
+        assert_eq!(positions[1].trade_id, partially_filled.id);
+        assert_eq!(positions[1].quantity, dec!(50));
+        assert_eq!(positions[1].entry_price, dec!(40));
+    }
+
+    fn position(
+        trading_vehicle: &TradingVehicle,
+        category: TradeCategory,
+        quantity: Decimal,
+        entry_price: Decimal,
+        stop_price: Decimal,
+    ) -> OpenPosition {
+        OpenPosition {
+            trade_id: Uuid::new_v4(),
+            trading_vehicle: trading_vehicle.clone(),
+            category,
+            currency: Currency::USD,
+            quantity,
+            entry_price,
+            stop_price,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_positions_two_longs() {
+        let aapl = TradingVehicle {
+            symbol: "AAPL".to_string(),
+            ..Default::default()
+        };
+        let positions = vec![
+            position(&aapl, TradeCategory::Long, dec!(100), dec!(10), dec!(9)),
+            position(&aapl, TradeCategory::Long, dec!(300), dec!(14), dec!(12)),
+        ];
+
+        assert_eq!(
+            aggregate_positions(&positions),
+            vec![AggregatedPosition {
+                trading_vehicle: aapl,
+                currency: Currency::USD,
+                category: TradeCategory::Long,
+                quantity: dec!(400),
+                average_entry_price: dec!(13),
+                capital_at_risk: dec!(700),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_aggregate_positions_long_and_short_net() {
+        let aapl = TradingVehicle {
+            symbol: "AAPL".to_string(),
+            ..Default::default()
+        };
+        let tsla = TradingVehicle {
+            symbol: "TSLA".to_string(),
+            ..Default::default()
+        };
+        let positions = vec![
+            position(&aapl, TradeCategory::Long, dec!(300), dec!(10), dec!(9)),
+            position(&tsla, TradeCategory::Long, dec!(50), dec!(200), dec!(190)),
+            position(&aapl, TradeCategory::Short, dec!(100), dec!(12), dec!(13)),
+            position(&tsla, TradeCategory::Short, dec!(50), dec!(210), dec!(220)),
+        ];
+
+        let aggregated = aggregate_positions(&positions);
+
+        // TSLA is flat, so it is omitted
+        assert_eq!(aggregated.len(), 1);
+        assert_eq!(aggregated[0].trading_vehicle, aapl);
+        assert_eq!(aggregated[0].category, TradeCategory::Long);
+        assert_eq!(aggregated[0].quantity, dec!(200));
+        assert_eq!(aggregated[0].average_entry_price, dec!(10));
+        assert_eq!(aggregated[0].capital_at_risk, dec!(400));
+    }
+
+    #[test]
+    fn test_aggregate_positions_net_short() {
+        let aapl = TradingVehicle::default();
+        let positions = vec![
+            position(&aapl, TradeCategory::Long, dec!(100), dec!(10), dec!(9)),
+            position(&aapl, TradeCategory::Short, dec!(150), dec!(12), dec!(13)),
+        ];
+
+        let aggregated = aggregate_positions(&positions);
+
+        assert_eq!(aggregated[0].category, TradeCategory::Short);
+        assert_eq!(aggregated[0].quantity, dec!(50));
+        assert_eq!(aggregated[0].average_entry_price, dec!(12));
+    }
+}
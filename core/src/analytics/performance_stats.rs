@@ -0,0 +1,115 @@
+use model::Trade;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// Performance statistics of a group of closed trades.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PerformanceStats {
+    pub total_trades: usize,
+    pub winning_trades: usize,
+    pub losing_trades: usize,
+
+    /// Percentage of the trades that were winners.
+    pub win_rate: Decimal,
+
+    /// Sum of the performance of all the trades.
+    pub net_profit: Decimal,
+
+    /// Average performance of the winning trades.
+    pub average_win: Decimal,
+
+    /// Average performance of the losing trades. It is a negative number.
+    pub average_loss: Decimal,
+}
+
+/// Calculates the performance statistics of closed trades.
+/// Trades that did not win or lose money are counted, but they are neither winners nor losers.
+pub fn performance_stats(trades: &[Trade]) -> PerformanceStats {
+    let performances: Vec<Decimal> = trades
+        .iter()
+        .map(|trade| trade.balance.total_performance)
+        .collect();
+    let wins: Vec<Decimal> = performances
+        .iter()
+        .filter(|p| **p > dec!(0))
+        .cloned()
+        .collect();
+    let losses: Vec<Decimal> = performances
+        .iter()
+        .filter(|p| **p < dec!(0))
+        .cloned()
+        .collect();
+
+    PerformanceStats {
+        total_trades: trades.len(),
+        winning_trades: wins.len(),
+        losing_trades: losses.len(),
+        win_rate: percentage(wins.len(), trades.len()),
+        net_profit: performances.iter().sum(),
+        average_win: average(&wins),
+        average_loss: average(&losses),
+    }
+}
+
+fn percentage(part: usize, total: usize) -> Decimal {
+    if total == 0 {
+        return dec!(0);
+    }
+    Decimal::from(part) / Decimal::from(total) * dec!(100)
+}
+
+fn average(values: &[Decimal]) -> Decimal {
+    if values.is_empty() {
+        return dec!(0);
+    }
+    values.iter().sum::<Decimal>() / Decimal::from(values.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::TradeBalance;
+
+    fn trade(performance: Decimal) -> Trade {
+        Trade {
+            balance: TradeBalance {
+                total_performance: performance,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_performance_stats_empty() {
+        let stats = performance_stats(&[]);
+        assert_eq!(stats.total_trades, 0);
+        assert_eq!(stats.win_rate, dec!(0));
+        assert_eq!(stats.net_profit, dec!(0));
+        assert_eq!(stats.average_win, dec!(0));
+        assert_eq!(stats.average_loss, dec!(0));
+    }
+
+    #[test]
+    fn test_performance_stats() {
+        let trades = vec![
+            trade(dec!(300)),
+            trade(dec!(-100)),
+            trade(dec!(100)),
+            trade(dec!(0)),
+        ];
+
+        assert_eq!(
+            performance_stats(&trades),
+            PerformanceStats {
+                total_trades: 4,
+                winning_trades: 2,
+                losing_trades: 1,
+                win_rate: dec!(50),
+                net_profit: dec!(300),
+                average_win: dec!(200),
+                average_loss: dec!(-100),
+            }
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use model::Trade;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// The asset class, its realized profit and loss and its number of trades.
+pub type AssetClassPnl = (String, Decimal, u32);
+
+/// Sums the realized profit and loss of the closed trades by the asset class of their trading vehicle,
+/// with the number of trades of each one. The asset class is the category of the trading vehicle,
+/// that every trading vehicle has. The asset classes that made more money go first,
+/// and by name if they made the same.
+pub fn pnl_by_asset_class(trades: &[Trade]) -> Vec<AssetClassPnl> {
+    let mut groups: HashMap<String, (Decimal, u32)> = HashMap::new();
+    for trade in trades {
+        let group = groups
+            .entry(trade.trading_vehicle.category.to_string())
+            .or_default();
+        group.0 += trade.balance.total_performance;
+        group.1 += 1;
+    }
+
+    let mut report: Vec<AssetClassPnl> = groups
+        .into_iter()
+        .map(|(asset_class, (pnl, count))| (asset_class, pnl, count))
+        .collect();
+    report.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::{Status, TradeBalance, TradingVehicle, TradingVehicleCategory};
+    use rust_decimal_macros::dec;
+
+    fn trade(category: TradingVehicleCategory, performance: Decimal) -> Trade {
+        Trade {
+            status: Status::ClosedTarget,
+            trading_vehicle: TradingVehicle {
+                category,
+                ..Default::default()
+            },
+            balance: TradeBalance {
+                total_performance: performance,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pnl_by_asset_class() {
+        let trades = vec![
+            trade(TradingVehicleCategory::Stock, dec!(300)),
+            trade(TradingVehicleCategory::Crypto, dec!(-150)),
+            trade(TradingVehicleCategory::Stock, dec!(-100)),
+            trade(TradingVehicleCategory::Fiat, dec!(500)),
+            trade(TradingVehicleCategory::Crypto, dec!(50)),
+        ];
+
+        assert_eq!(
+            pnl_by_asset_class(&trades),
+            vec![
+                ("fiat".to_string(), dec!(500), 1),
+                ("stock".to_string(), dec!(200), 2),
+                ("crypto".to_string(), dec!(-100), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pnl_by_asset_class_with_the_same_pnl() {
+        let trades = vec![
+            trade(TradingVehicleCategory::Stock, dec!(100)),
+            trade(TradingVehicleCategory::Crypto, dec!(100)),
+        ];
+
+        assert_eq!(
+            pnl_by_asset_class(&trades),
+            vec![
+                ("crypto".to_string(), dec!(100), 1),
+                ("stock".to_string(), dec!(100), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pnl_by_asset_class_without_trades() {
+        assert!(pnl_by_asset_class(&[]).is_empty());
+    }
+}
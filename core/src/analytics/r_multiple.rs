@@ -0,0 +1,82 @@
+use model::Trade;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// Calculates the R-multiple of a trade: its performance divided by the money that was
+/// risked between the entry and the safety stop.
+/// Returns `None` if the trade did not risk any money.
+pub fn r_multiple(trade: &Trade) -> Option<Decimal> {
+    let risk = (trade.entry.unit_price - trade.safety_stop.unit_price).abs()
+        * trade.entry.decimal_quantity();
+    if risk == dec!(0) {
+        return None;
+    }
+    Some(trade.balance.total_performance / risk)
+}
+
+/// Calculates the average R-multiple of a group of trades. Trades without risk are excluded.
+/// Returns `None` if none of the trades risked money.
+pub fn average_r_multiple(trades: &[Trade]) -> Option<Decimal> {
+    let r_multiples: Vec<Decimal> = trades.iter().filter_map(r_multiple).collect();
+    if r_multiples.is_empty() {
+        return None;
+    }
+    Some(r_multiples.iter().sum::<Decimal>() / Decimal::from(r_multiples.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::{Order, TradeBalance};
+
+    fn trade(stop: Decimal, entry: Decimal, quantity: u64, performance: Decimal) -> Trade {
+        Trade {
+            safety_stop: Order {
+                unit_price: stop,
+                ..Default::default()
+            },
+            entry: Order {
+                unit_price: entry,
+                quantity,
+                ..Default::default()
+            },
+            balance: TradeBalance {
+                total_performance: performance,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_r_multiple() {
+        // Long risking 2 * 10 = 20
+        assert_eq!(
+            r_multiple(&trade(dec!(38), dec!(40), 10, dec!(50))),
+            Some(dec!(2.5))
+        );
+        // Short risking 2 * 10 = 20
+        assert_eq!(
+            r_multiple(&trade(dec!(42), dec!(40), 10, dec!(-20))),
+            Some(dec!(-1))
+        );
+    }
+
+    #[test]
+    fn test_r_multiple_without_risk() {
+        assert_eq!(r_multiple(&trade(dec!(40), dec!(40), 10, dec!(50))), None);
+        assert_eq!(r_multiple(&trade(dec!(38), dec!(40), 0, dec!(50))), None);
+    }
+
+    #[test]
+    fn test_average_r_multiple() {
+        let trades = [
+            trade(dec!(38), dec!(40), 10, dec!(60)),
+            trade(dec!(38), dec!(40), 10, dec!(-20)),
+            trade(dec!(40), dec!(40), 10, dec!(1000)),
+        ];
+        assert_eq!(average_r_multiple(&trades), Some(dec!(1)));
+        assert_eq!(average_r_multiple(&trades[2..]), None);
+        assert_eq!(average_r_multiple(&[]), None);
+    }
+}
@@ -0,0 +1,90 @@
+use super::calculate_open_positions;
+use model::Trade;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// The sector of the trades that do not have one.
+pub const UNCLASSIFIED_SECTOR: &str = "unclassified";
+
+/// Sums the capital at risk of the open positions by the sector of their trades.
+/// The sectors are sorted from the most to the least risk, and by name if they risk the same.
+pub fn risk_heatmap(trades: &[Trade]) -> Vec<(String, Decimal)> {
+    let mut risk: HashMap<String, Decimal> = HashMap::new();
+    for position in calculate_open_positions(trades) {
+        let sector = trades
+            .iter()
+            .find(|trade| trade.id == position.trade_id)
+            .and_then(|trade| trade.sector.clone())
+            .unwrap_or_else(|| UNCLASSIFIED_SECTOR.to_string());
+        *risk.entry(sector).or_default() += position.capital_at_risk();
+    }
+
+    let mut heatmap: Vec<(String, Decimal)> = risk.into_iter().collect();
+    heatmap.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    heatmap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::{Order, Status};
+    use rust_decimal_macros::dec;
+
+    fn trade(sector: Option<&str>, stop: Decimal, quantity: u64) -> Trade {
+        Trade {
+            status: Status::Filled,
+            sector: sector.map(|sector| sector.to_string()),
+            entry: Order {
+                unit_price: dec!(40),
+                quantity,
+                filled_quantity: quantity,
+                ..Default::default()
+            },
+            safety_stop: Order {
+                unit_price: stop,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_risk_heatmap() {
+        let trades = vec![
+            trade(Some("technology"), dec!(38), 100),
+            trade(Some("energy"), dec!(39), 100),
+            trade(Some("technology"), dec!(39), 50),
+            trade(None, dec!(35), 100),
+            Trade {
+                status: Status::Submitted,
+                ..trade(Some("energy"), dec!(30), 100)
+            },
+        ];
+
+        assert_eq!(
+            risk_heatmap(&trades),
+            vec![
+                (UNCLASSIFIED_SECTOR.to_string(), dec!(500)),
+                ("technology".to_string(), dec!(250)),
+                ("energy".to_string(), dec!(100)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_risk_heatmap_same_risk() {
+        let trades = vec![
+            trade(Some("technology"), dec!(39), 100),
+            trade(Some("energy"), dec!(39), 100),
+        ];
+
+        let heatmap = risk_heatmap(&trades);
+        assert_eq!(heatmap[0].0, "energy");
+        assert_eq!(heatmap[1].0, "technology");
+    }
+
+    #[test]
+    fn test_risk_heatmap_without_positions() {
+        assert!(risk_heatmap(&[]).is_empty());
+    }
+}
@@ -0,0 +1,84 @@
+use rust_decimal::{Decimal, MathematicalOps};
+use rust_decimal_macros::dec;
+
+/// Estimates the probability of losing all the capital with the classic approximation
+/// `((1 - edge) / (1 + edge)) ^ (1 / risk_fraction)`, where the edge is
+/// `win_rate * payoff - (1 - win_rate)`: the expected result of a trade in units of risk.
+///
+/// The win rate and the risk fraction are fractions, not percentages.
+/// The payoff is the average win divided by the average loss.
+/// The result is clamped to 0..=1. Systems without edge are always ruined.
+pub fn risk_of_ruin(win_rate: Decimal, payoff: Decimal, risk_fraction: Decimal) -> Decimal {
+    // A system that never wins is ruined and a system that never loses can not be ruined.
+    if win_rate <= dec!(0) {
+        return dec!(1);
+    }
+    if win_rate >= dec!(1) || risk_fraction <= dec!(0) {
+        return dec!(0);
+    }
+
+    let edge = win_rate * payoff - (dec!(1) - win_rate);
+    if edge <= dec!(0) {
+        return dec!(1);
+    }
+    if edge >= dec!(1) {
+        return dec!(0);
+    }
+
+    let ratio = (dec!(1) - edge) / (dec!(1) + edge);
+    let units = dec!(1) / risk_fraction;
+
+    // The ratio is lower than 1, so it can only fail when the result is too small.
+    ratio
+        .checked_powd(units)
+        .unwrap_or(dec!(0))
+        .clamp(dec!(0), dec!(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_risk_of_ruin_half_of_wins() {
+        // Edge 0.25, ratio 0.6 and 4 units of capital: 0.6^4
+        assert_eq!(
+            risk_of_ruin(dec!(0.5), dec!(1.5), dec!(0.25)).round_dp(4),
+            dec!(0.1296)
+        );
+    }
+
+    #[test]
+    fn test_risk_of_ruin_profitable_system() {
+        // Edge 0.8, ratio 0.2 / 1.8 and 2 units of capital: (1/9)^2
+        assert_eq!(
+            risk_of_ruin(dec!(0.6), dec!(2), dec!(0.5)).round_dp(4),
+            dec!(0.0123)
+        );
+    }
+
+    #[test]
+    fn test_risk_of_ruin_without_edge() {
+        assert_eq!(risk_of_ruin(dec!(0.5), dec!(1), dec!(0.02)), dec!(1));
+        assert_eq!(risk_of_ruin(dec!(0.3), dec!(1), dec!(0.02)), dec!(1));
+    }
+
+    #[test]
+    fn test_risk_of_ruin_degenerate_win_rates() {
+        assert_eq!(risk_of_ruin(dec!(0), dec!(3), dec!(0.02)), dec!(1));
+        assert_eq!(risk_of_ruin(dec!(1), dec!(0.5), dec!(0.02)), dec!(0));
+    }
+
+    #[test]
+    fn test_risk_of_ruin_is_clamped() {
+        // The edge is greater than 1, so the ratio would be negative.
+        assert_eq!(risk_of_ruin(dec!(0.9), dec!(3), dec!(0.02)), dec!(0));
+    }
+
+    #[test]
+    fn test_risk_of_ruin_decreases_with_smaller_risk() {
+        let aggressive = risk_of_ruin(dec!(0.5), dec!(1.5), dec!(0.25));
+        let conservative = risk_of_ruin(dec!(0.5), dec!(1.5), dec!(0.05));
+        assert!(conservative < aggressive);
+    }
+}
@@ -19,10 +19,16 @@ impl AccountCapitalAvailable {
                 match transaction.category {
                     TransactionCategory::FundTrade(_) |
                     TransactionCategory::Withdrawal |
+                    TransactionCategory::WithdrawalEarnings |
                     TransactionCategory::FeeOpen(_) |
-                    TransactionCategory::FeeClose(_) => -transaction.amount,
+                    TransactionCategory::FeeClose(_) |
+                    TransactionCategory::Fee => -transaction.amount,
                     TransactionCategory::PaymentFromTrade(_) |
-                    TransactionCategory::Deposit => transaction.amount,
+                    TransactionCategory::Deposit |
+                    TransactionCategory::Dividend(_) |
+                    TransactionCategory::Interest |
+                    TransactionCategory::Adjustment { .. } |
+                    TransactionCategory::FeeRebate(_) => transaction.amount,
                     _ => panic!(
                         "capital_available: does not know how to calculate transaction with category: {}",
                         transaction.category
@@ -83,6 +89,19 @@ mod tests {
         assert_eq!(result.unwrap(), dec!(50));
     }
 
+    #[test]
+    fn test_capital_available_with_dividends() {
+        let mut database = MockDatabase::new();
+
+        // Transactions
+        database.set_transaction(TransactionCategory::Deposit, dec!(100));
+        database.set_transaction(TransactionCategory::Dividend(None), dec!(5));
+
+        let result =
+            AccountCapitalAvailable::calculate(Uuid::new_v4(), &Currency::USD, &mut database);
+        assert_eq!(result.unwrap(), dec!(105));
+    }
+
     #[test]
     fn test_capital_available_with_remaining_from_trade_entry() {
         let mut database = MockDatabase::new();
@@ -1,4 +1,4 @@
-use model::{Currency, ReadTransactionDB, TransactionCategory};
+use model::{Currency, ReadTransactionDB, Transaction, TransactionCategory};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use uuid::Uuid;
@@ -11,24 +11,30 @@ impl AccountCapitalBalance {
         currency: &Currency,
         database: &mut dyn ReadTransactionDB,
     ) -> Result<Decimal, Box<dyn std::error::Error>> {
-        let total = database
-            .all_transactions(account_id, currency)?
-            .into_iter()
-            .fold(dec!(0), |acc, tx| match tx.category {
-                TransactionCategory::Withdrawal
-                | TransactionCategory::WithdrawalTax
-                | TransactionCategory::WithdrawalEarnings
-                | TransactionCategory::FeeOpen(_)
-                | TransactionCategory::FeeClose(_)
-                | TransactionCategory::OpenTrade(_) => acc - tx.amount,
-                TransactionCategory::Deposit
-                | TransactionCategory::CloseSafetyStop(_)
-                | TransactionCategory::CloseTarget(_)
-                | TransactionCategory::CloseSafetyStopSlippage(_) => acc + tx.amount,
-                _ => acc,
-            });
-
-        Ok(total)
+        let transactions = database.all_transactions(account_id, currency)?;
+        Ok(AccountCapitalBalance::of(transactions.iter()))
+    }
+
+    /// The balance that the given transactions leave in the account.
+    pub fn of<'a>(transactions: impl Iterator<Item = &'a Transaction>) -> Decimal {
+        transactions.fold(dec!(0), |acc, tx| match tx.category {
+            TransactionCategory::Withdrawal
+            | TransactionCategory::WithdrawalTax
+            | TransactionCategory::WithdrawalEarnings
+            | TransactionCategory::FeeOpen(_)
+            | TransactionCategory::FeeClose(_)
+            | TransactionCategory::Fee
+            | TransactionCategory::OpenTrade(_) => acc - tx.amount,
+            TransactionCategory::Deposit
+            | TransactionCategory::Dividend(_)
+            | TransactionCategory::Interest
+            | TransactionCategory::Adjustment { .. }
+            | TransactionCategory::FeeRebate(_)
+            | TransactionCategory::CloseSafetyStop(_)
+            | TransactionCategory::CloseTarget(_)
+            | TransactionCategory::CloseSafetyStopSlippage(_) => acc + tx.amount,
+            _ => acc,
+        })
     }
 }
 
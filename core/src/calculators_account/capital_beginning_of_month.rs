@@ -16,17 +16,23 @@ impl AccountCapitalBeginningOfMonth {
         for transaction in
             database.all_transaction_excluding_current_month_and_taxes(account_id, currency)?
         {
-            match transaction.category {
+            match &transaction.category {
                 TransactionCategory::FundTrade(_)
                 | TransactionCategory::Withdrawal
+                | TransactionCategory::WithdrawalEarnings
                 | TransactionCategory::FeeOpen(_)
-                | TransactionCategory::FeeClose(_) => {
+                | TransactionCategory::FeeClose(_)
+                | TransactionCategory::Fee => {
                     total -= transaction.amount
                 }
                 TransactionCategory::PaymentFromTrade(_) => {
                     total += transaction.amount
                 }
-                TransactionCategory::Deposit => {
+                TransactionCategory::Deposit
+                | TransactionCategory::Dividend(_)
+                | TransactionCategory::Interest
+                | TransactionCategory::Adjustment { .. }
+                | TransactionCategory::FeeRebate(_) => {
                     total += transaction.amount
                 }
                 default => panic!(
@@ -16,7 +16,7 @@ impl AccountCapitalTaxable {
         // Sum all transactions
         let total: Decimal = transactions
             .iter()
-            .map(|transaction| match transaction.category {
+            .map(|transaction| match &transaction.category {
                 TransactionCategory::PaymentTax(_) => transaction.amount,
                 TransactionCategory::WithdrawalTax => -transaction.amount,
                 default => panic!(
@@ -0,0 +1,6 @@
+mod realized_volatility;
+mod risk_adjusted_return;
+mod sqrt;
+
+pub use realized_volatility::realized_volatility;
+pub use risk_adjusted_return::{sharpe_ratio, sortino_ratio};
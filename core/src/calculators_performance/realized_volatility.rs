@@ -0,0 +1,93 @@
+use super::risk_adjusted_return::standard_deviation;
+use super::sqrt::sqrt;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// The annualized volatility of an equity curve, like the one of `RealizedDrawdownCalculator`.
+///
+/// volatility = sqrt(Σ (r[t] - mean(r))² / (n - 1)) * sqrt(periods_per_year)
+///
+/// Where `r[t] = equity[t] / equity[t - 1] - 1` are the returns between consecutive points
+/// and `n` the number of returns. The points are expected to be one period apart.
+/// A period that starts without equity has no return and it is skipped.
+/// Returns `None` if there are less than two returns, which is less than three points.
+pub fn realized_volatility(
+    curve: &[(NaiveDateTime, Decimal)],
+    periods_per_year: u32,
+) -> Option<Decimal> {
+    let returns: Vec<Decimal> = curve
+        .windows(2)
+        .filter(|pair| pair[0].1 != dec!(0))
+        .map(|pair| pair[1].1 / pair[0].1 - dec!(1))
+        .collect();
+    let deviation = standard_deviation(&returns)?;
+
+    Some(deviation * sqrt(Decimal::from(periods_per_year))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn curve(equity: &[Decimal]) -> Vec<(NaiveDateTime, Decimal)> {
+        equity
+            .iter()
+            .enumerate()
+            .map(|(month, equity)| {
+                (
+                    NaiveDate::from_ymd_opt(2023, month as u32 + 1, 1)
+                        .unwrap()
+                        .and_hms_opt(0, 0, 0)
+                        .unwrap(),
+                    *equity,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_realized_volatility() {
+        // Returns of 10%, -10%, 10% and -10%: a monthly deviation of sqrt(0.04 / 3)
+        let curve = curve(&[dec!(100), dec!(110), dec!(99), dec!(108.9), dec!(98.01)]);
+
+        let volatility = realized_volatility(&curve, 12).unwrap();
+
+        assert_eq!(volatility.round_dp(12), dec!(0.4));
+    }
+
+    #[test]
+    fn test_realized_volatility_of_a_steady_curve() {
+        let curve = curve(&[dec!(100), dec!(101), dec!(102.01), dec!(103.0301)]);
+
+        assert_eq!(realized_volatility(&curve, 252), Some(dec!(0)));
+    }
+
+    #[test]
+    fn test_realized_volatility_skips_periods_without_equity() {
+        let curve = curve(&[
+            dec!(0),
+            dec!(100),
+            dec!(110),
+            dec!(99),
+            dec!(108.9),
+            dec!(98.01),
+        ]);
+
+        assert_eq!(
+            realized_volatility(&curve, 12).unwrap().round_dp(12),
+            dec!(0.4)
+        );
+    }
+
+    #[test]
+    fn test_realized_volatility_without_enough_points() {
+        assert_eq!(realized_volatility(&[], 12), None);
+        assert_eq!(realized_volatility(&curve(&[dec!(100)]), 12), None);
+        assert_eq!(
+            realized_volatility(&curve(&[dec!(100), dec!(110)]), 12),
+            None
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use super::sqrt::sqrt;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// The Sharpe ratio of a series of returns: the mean excess return over the
+/// risk free return, divided by the sample standard deviation of the excess returns.
+///
+/// sharpe = mean(e) / sqrt(Σ (e[t] - mean(e))² / (n - 1))
+///
+/// Where `e` are the returns minus the risk free return of the same period and `n`
+/// the number of periods. The ratio is not annualized.
+/// Returns `None` if there are less than two returns or if they have no variance.
+pub fn sharpe_ratio(returns: &[Decimal], risk_free: Decimal) -> Option<Decimal> {
+    let excess = excess_returns(returns, risk_free)?;
+    let deviation = standard_deviation(&excess)?;
+
+    ratio(mean(&excess), deviation)
+}
+
+/// The Sortino ratio of a series of returns: the mean excess return over the
+/// risk free return, divided by the downside deviation.
+///
+/// sortino = mean(e) / sqrt(Σ min(e[t], 0)² / n)
+///
+/// Only the periods that returned less than the risk free return are penalized,
+/// but every period counts in `n`. The ratio is not annualized.
+/// Returns `None` if there are less than two returns or if none of them is below
+/// the risk free return.
+pub fn sortino_ratio(returns: &[Decimal], risk_free: Decimal) -> Option<Decimal> {
+    let excess = excess_returns(returns, risk_free)?;
+    let squares: Decimal = excess
+        .iter()
+        .map(|e| (*e).min(dec!(0)))
+        .map(|downside| downside * downside)
+        .sum();
+    let deviation = sqrt(squares / Decimal::from(excess.len()))?;
+
+    ratio(mean(&excess), deviation)
+}
+
+fn excess_returns(returns: &[Decimal], risk_free: Decimal) -> Option<Vec<Decimal>> {
+    if returns.len() < 2 {
+        return None;
+    }
+    Some(returns.iter().map(|r| r - risk_free).collect())
+}
+
+fn mean(values: &[Decimal]) -> Decimal {
+    values.iter().sum::<Decimal>() / Decimal::from(values.len())
+}
+
+/// The sample standard deviation, dividing by n - 1. `None` if there are less than two values.
+pub(super) fn standard_deviation(values: &[Decimal]) -> Option<Decimal> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = mean(values);
+    let squares: Decimal = values.iter().map(|v| (v - mean) * (v - mean)).sum();
+    sqrt(squares / Decimal::from(values.len() - 1))
+}
+
+fn ratio(mean: Decimal, deviation: Decimal) -> Option<Decimal> {
+    match deviation == dec!(0) {
+        true => None,
+        false => Some(mean / deviation),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sharpe_ratio() {
+        let returns = vec![dec!(0.01), dec!(0.02), dec!(-0.01), dec!(0.03)];
+
+        let ratio = sharpe_ratio(&returns, dec!(0)).unwrap();
+
+        assert_eq!(ratio.round_dp(6), dec!(0.731925));
+    }
+
+    #[test]
+    fn test_sharpe_ratio_with_risk_free() {
+        let returns = vec![dec!(0.05), dec!(-0.02), dec!(0.03), dec!(-0.04), dec!(0.01)];
+
+        let ratio = sharpe_ratio(&returns, dec!(0.01)).unwrap();
+
+        assert_eq!(ratio.round_dp(6), dec!(-0.109682));
+    }
+
+    #[test]
+    fn test_sharpe_ratio_without_variance() {
+        let returns = vec![dec!(0.01), dec!(0.01), dec!(0.01)];
+
+        assert_eq!(sharpe_ratio(&returns, dec!(0)), None);
+    }
+
+    #[test]
+    fn test_sortino_ratio() {
+        let returns = vec![dec!(0.01), dec!(0.02), dec!(-0.01), dec!(0.03)];
+
+        // Only -0.01 is below zero: sqrt(0.0001 / 4) = 0.005
+        assert_eq!(sortino_ratio(&returns, dec!(0)), Some(dec!(2.5)));
+    }
+
+    #[test]
+    fn test_sortino_ratio_with_risk_free() {
+        let returns = vec![dec!(0.05), dec!(-0.02), dec!(0.03), dec!(-0.04), dec!(0.01)];
+
+        let ratio = sortino_ratio(&returns, dec!(0.01)).unwrap();
+
+        assert_eq!(ratio.round_dp(6), dec!(-0.153393));
+    }
+
+    #[test]
+    fn test_sortino_ratio_without_downside() {
+        let returns = vec![dec!(0.01), dec!(0.02), dec!(0.03)];
+
+        assert_eq!(sortino_ratio(&returns, dec!(0)), None);
+    }
+
+    #[test]
+    fn test_ratios_with_less_than_two_returns() {
+        assert_eq!(sharpe_ratio(&[], dec!(0)), None);
+        assert_eq!(sharpe_ratio(&[dec!(0.01)], dec!(0)), None);
+        assert_eq!(sortino_ratio(&[], dec!(0)), None);
+        assert_eq!(sortino_ratio(&[dec!(-0.01)], dec!(0)), None);
+    }
+}
@@ -0,0 +1,58 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+const MAX_ITERATIONS: usize = 100;
+
+/// The square root of a decimal with the Newton method, because `Decimal` does not have one.
+///
+/// Starting from a guess `x`, every iteration moves it to the root of the tangent of
+/// f(x) = x² - value:
+///
+/// x[n + 1] = (x[n] + value / x[n]) / 2
+///
+/// The method converges quadratically from any positive guess, so it stops as soon as an
+/// iteration does not change the result anymore, or after a fixed number of iterations.
+/// Returns `None` if the value is negative.
+pub fn sqrt(value: Decimal) -> Option<Decimal> {
+    if value < dec!(0) {
+        return None;
+    }
+    if value == dec!(0) {
+        return Some(dec!(0));
+    }
+
+    // Any positive guess converges, one above the root avoids overshooting to zero.
+    let mut root = value.max(dec!(1));
+    for _ in 0..MAX_ITERATIONS {
+        let next = (root + value / root) / dec!(2);
+        if next == root {
+            break;
+        }
+        root = next;
+    }
+    Some(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqrt_perfect_squares() {
+        assert_eq!(sqrt(dec!(0)), Some(dec!(0)));
+        assert_eq!(sqrt(dec!(1)), Some(dec!(1)));
+        assert_eq!(sqrt(dec!(144)), Some(dec!(12)));
+        assert_eq!(sqrt(dec!(0.000025)), Some(dec!(0.005)));
+    }
+
+    #[test]
+    fn test_sqrt_irrational() {
+        assert_eq!(sqrt(dec!(2)).unwrap().round_dp(12), dec!(1.414213562373));
+        assert_eq!(sqrt(dec!(0.5)).unwrap().round_dp(12), dec!(0.707106781187));
+    }
+
+    #[test]
+    fn test_sqrt_negative() {
+        assert_eq!(sqrt(dec!(-4)), None);
+    }
+}
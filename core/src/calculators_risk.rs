@@ -0,0 +1,10 @@
+mod beta;
+mod margin;
+mod monte_carlo;
+mod value_at_risk;
+mod weighted_returns;
+
+pub use beta::portfolio_beta;
+pub use margin::{margin_status, MarginStatus};
+pub use monte_carlo::{simulate_trade_outcomes, SimulationResult};
+pub use value_at_risk::value_at_risk;
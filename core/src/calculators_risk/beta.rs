@@ -0,0 +1,140 @@
+use super::weighted_returns::weighted_returns;
+use crate::analytics::OpenPosition;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+
+/// The beta of the open positions relative to a benchmark.
+///
+/// The returns of the portfolio are the returns of each position weighted by its notional
+/// for every period, and the beta is:
+///
+/// beta = cov(p, b) / var(b)
+/// cov(p, b) = (n * Σ p[t] * b[t] - Σ p[t] * Σ b[t]) / (n * (n - 1))
+/// var(b) = cov(b, b)
+///
+/// Where `p` are the returns of the portfolio, `b` the returns of the benchmark and `n`
+/// the number of periods. The sums are used instead of the means so the only rounding
+/// happens in the final division.
+pub fn portfolio_beta(
+    positions: &[OpenPosition],
+    benchmark_returns: &[Decimal],
+    position_returns: &HashMap<String, Vec<Decimal>>,
+) -> Result<Decimal, Box<dyn std::error::Error>> {
+    let periods = benchmark_returns.len();
+    if periods < 2 {
+        return Err("Beta needs at least two benchmark returns".into());
+    }
+
+    let portfolio_returns = weighted_returns(positions, position_returns, periods)?;
+
+    // The denominator n * (n - 1) is the same in both terms, so it cancels out.
+    let variance = scaled_covariance(benchmark_returns, benchmark_returns);
+    if variance == dec!(0) {
+        return Err("The benchmark returns have no variance".into());
+    }
+
+    Ok(scaled_covariance(&portfolio_returns, benchmark_returns) / variance)
+}
+
+/// Sample covariance of two series with the same length multiplied by n * (n - 1).
+fn scaled_covariance(a: &[Decimal], b: &[Decimal]) -> Decimal {
+    let n = Decimal::from(a.len());
+    let sum_of_products: Decimal = a.iter().zip(b).map(|(a, b)| a * b).sum();
+    let sum_a: Decimal = a.iter().sum();
+    let sum_b: Decimal = b.iter().sum();
+
+    n * sum_of_products - sum_a * sum_b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::{Currency, TradeCategory, TradingVehicle};
+    use uuid::Uuid;
+
+    fn position(symbol: &str, category: TradeCategory, quantity: u64) -> OpenPosition {
+        OpenPosition {
+            trade_id: Uuid::new_v4(),
+            trading_vehicle: TradingVehicle {
+                symbol: symbol.to_string(),
+                ..Default::default()
+            },
+            category,
+            currency: Currency::USD,
+            quantity: Decimal::from(quantity),
+            entry_price: dec!(10),
+            stop_price: dec!(9),
+        }
+    }
+
+    fn benchmark() -> Vec<Decimal> {
+        vec![dec!(0.01), dec!(-0.02), dec!(0.03), dec!(0.00)]
+    }
+
+    fn returns() -> HashMap<String, Vec<Decimal>> {
+        HashMap::from([
+            // Twice the benchmark, so the beta of AAPL is 2.
+            (
+                "AAPL".to_string(),
+                vec![dec!(0.02), dec!(-0.04), dec!(0.06), dec!(0.00)],
+            ),
+            // The benchmark plus a constant, so the beta of MSFT is 1.
+            (
+                "MSFT".to_string(),
+                vec![dec!(0.02), dec!(-0.01), dec!(0.04), dec!(0.01)],
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_portfolio_beta_with_two_stocks() {
+        let positions = vec![
+            position("AAPL", TradeCategory::Long, 10),
+            position("MSFT", TradeCategory::Long, 30),
+        ];
+
+        let beta = portfolio_beta(&positions, &benchmark(), &returns()).unwrap();
+        assert_eq!(beta, dec!(1.25));
+    }
+
+    #[test]
+    fn test_portfolio_beta_with_short_position() {
+        let positions = vec![
+            position("AAPL", TradeCategory::Long, 10),
+            position("MSFT", TradeCategory::Short, 10),
+        ];
+
+        let beta = portfolio_beta(&positions, &benchmark(), &returns()).unwrap();
+        assert_eq!(beta, dec!(0.5));
+    }
+
+    #[test]
+    fn test_portfolio_beta_with_mismatched_returns() {
+        let positions = vec![position("AAPL", TradeCategory::Long, 10)];
+        let mut returns = returns();
+        returns.get_mut("AAPL").unwrap().pop();
+
+        assert!(portfolio_beta(&positions, &benchmark(), &returns).is_err());
+    }
+
+    #[test]
+    fn test_portfolio_beta_without_returns_for_a_position() {
+        let positions = vec![position("TSLA", TradeCategory::Long, 10)];
+
+        assert!(portfolio_beta(&positions, &benchmark(), &returns()).is_err());
+    }
+
+    #[test]
+    fn test_portfolio_beta_without_benchmark_variance() {
+        let positions = vec![position("AAPL", TradeCategory::Long, 10)];
+        let benchmark = vec![dec!(0.01); 4];
+
+        assert!(portfolio_beta(&positions, &benchmark, &returns()).is_err());
+    }
+
+    #[test]
+    fn test_portfolio_beta_without_positions() {
+        assert!(portfolio_beta(&[], &benchmark(), &returns()).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+use crate::analytics::OpenPosition;
+use model::TradeCategory;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+
+/// The maintenance margin of the open positions of an account.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MarginStatus {
+    /// Notional of the open positions at their mark. Short positions count their absolute notional.
+    pub position_value: Decimal,
+
+    /// Equity that the account must keep to hold the open positions.
+    pub maintenance_margin: Decimal,
+
+    /// Cash of the account plus the money in the open positions and their unrealized profit or loss.
+    pub equity: Decimal,
+
+    /// Equity above the maintenance margin. It is negative in a margin call.
+    pub excess: Decimal,
+
+    /// True if the equity is below the maintenance margin.
+    pub margin_call: bool,
+}
+
+/// Calculates the maintenance margin of the open positions with a maintenance percentage between 0 and 100.
+/// The mark of a position is its price in the marks, by symbol, or its entry price if it is not there.
+///
+/// position_value = sum(|quantity| * mark)
+/// maintenance_margin = position_value * maintenance_pct / 100
+/// equity = cash + sum(quantity * entry + unrealized profit)
+pub fn margin_status(
+    positions: &[OpenPosition],
+    marks: &HashMap<String, Decimal>,
+    cash: Decimal,
+    maintenance_pct: Decimal,
+) -> Result<MarginStatus, Box<dyn std::error::Error>> {
+    if maintenance_pct < dec!(0) || maintenance_pct > dec!(100) {
+        return Err(format!(
+            "The maintenance percentage must be between 0 and 100 and it is {}",
+            maintenance_pct
+        )
+        .into());
+    }
+
+    let mut position_value = dec!(0);
+    let mut equity = cash;
+    for position in positions {
+        let mark = marks
+            .get(&position.trading_vehicle.symbol)
+            .cloned()
+            .unwrap_or(position.entry_price);
+        let quantity = position.quantity;
+        let unrealized = match position.category {
+            TradeCategory::Long => (mark - position.entry_price) * quantity,
+            TradeCategory::Short => (position.entry_price - mark) * quantity,
+        };
+
+        position_value += (mark * quantity).abs();
+        equity += position.entry_price * quantity + unrealized;
+    }
+
+    let maintenance_margin = position_value * maintenance_pct / dec!(100);
+    Ok(MarginStatus {
+        position_value,
+        maintenance_margin,
+        equity,
+        excess: equity - maintenance_margin,
+        margin_call: equity < maintenance_margin,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::{Currency, TradingVehicle};
+    use uuid::Uuid;
+
+    fn position(symbol: &str, category: TradeCategory, quantity: u64) -> OpenPosition {
+        OpenPosition {
+            trade_id: Uuid::new_v4(),
+            trading_vehicle: TradingVehicle {
+                symbol: symbol.to_string(),
+                ..Default::default()
+            },
+            category,
+            currency: Currency::USD,
+            quantity: Decimal::from(quantity),
+            entry_price: dec!(10),
+            stop_price: dec!(9),
+        }
+    }
+
+    #[test]
+    fn test_margin_status_at_entry_prices() {
+        let positions = vec![
+            position("AAPL", TradeCategory::Long, 100),
+            position("TSLA", TradeCategory::Short, 50),
+        ];
+
+        let status = margin_status(&positions, &HashMap::new(), dec!(500), dec!(25)).unwrap();
+
+        assert_eq!(status.position_value, dec!(1500));
+        assert_eq!(status.maintenance_margin, dec!(375));
+        assert_eq!(status.equity, dec!(2000));
+        assert_eq!(status.excess, dec!(1625));
+        assert!(!status.margin_call);
+    }
+
+    #[test]
+    fn test_margin_status_with_marks() {
+        let positions = vec![
+            position("AAPL", TradeCategory::Long, 100),
+            position("TSLA", TradeCategory::Short, 50),
+        ];
+        let marks = HashMap::from([
+            ("AAPL".to_string(), dec!(4)),
+            ("TSLA".to_string(), dec!(16)),
+        ]);
+
+        // The long loses 600 and the short loses 300.
+        let status = margin_status(&positions, &marks, dec!(0), dec!(50)).unwrap();
+
+        assert_eq!(status.position_value, dec!(1200));
+        assert_eq!(status.maintenance_margin, dec!(600));
+        assert_eq!(status.equity, dec!(600));
+        assert_eq!(status.excess, dec!(0));
+        assert!(!status.margin_call);
+
+        let status = margin_status(&positions, &marks, dec!(0), dec!(50.01)).unwrap();
+        assert!(status.margin_call);
+    }
+
+    #[test]
+    fn test_margin_status_without_positions() {
+        let status = margin_status(&[], &HashMap::new(), dec!(1000), dec!(25)).unwrap();
+
+        assert_eq!(status.position_value, dec!(0));
+        assert_eq!(status.maintenance_margin, dec!(0));
+        assert_eq!(status.excess, dec!(1000));
+        assert!(!status.margin_call);
+    }
+
+    #[test]
+    fn test_margin_status_with_invalid_percentage() {
+        for maintenance_pct in [dec!(-1), dec!(100.5)] {
+            assert!(margin_status(&[], &HashMap::new(), dec!(1000), maintenance_pct).is_err());
+        }
+    }
+}
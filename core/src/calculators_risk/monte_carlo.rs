@@ -0,0 +1,206 @@
+use crate::analytics::calculate_open_positions;
+use model::{Trade, TradeCategory};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+
+/// The ending equity of every iteration of a simulation of the open trades.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SimulationResult {
+    pub starting_equity: Decimal,
+
+    /// The ending equity of every iteration, from the worst to the best.
+    pub ending_equities: Vec<Decimal>,
+}
+
+impl SimulationResult {
+    /// The ending equity at a percentile from 0 to 100, using the nearest rank.
+    pub fn percentile(&self, percentile: u8) -> Decimal {
+        let count = self.ending_equities.len();
+        let rank = (usize::from(percentile.min(100)) * count).div_ceil(100);
+        self.ending_equities[rank.max(1) - 1]
+    }
+
+    /// The probability, from 0 to 1, that the ending equity is more than a percentage below the starting equity.
+    pub fn probability_of_drawdown(&self, percentage: Decimal) -> Decimal {
+        let threshold = self.starting_equity * (dec!(1) - percentage / dec!(100));
+        let drawdowns = self
+            .ending_equities
+            .iter()
+            .filter(|equity| **equity < threshold)
+            .count();
+        Decimal::from(drawdowns) / Decimal::from(self.ending_equities.len())
+    }
+}
+
+/// Simulates the outcome of the open trades many times.
+///
+/// In every iteration each trade in the market either hits its target, with a probability of `win_prob`,
+/// or its safety stop. The ending equity of the iteration is the starting equity plus the profit or loss
+/// of every trade. The same seed always returns the same result.
+pub fn simulate_trade_outcomes(
+    trades: &[Trade],
+    starting_equity: Decimal,
+    iterations: u32,
+    win_prob: Decimal,
+    seed: u64,
+) -> Result<SimulationResult, Box<dyn std::error::Error>> {
+    if iterations == 0 {
+        return Err("The simulation needs at least one iteration".into());
+    }
+    if win_prob < dec!(0) || win_prob > dec!(1) {
+        return Err(format!(
+            "The win probability must be between 0 and 1 and it is {}",
+            win_prob
+        )
+        .into());
+    }
+
+    // 1. The profit and the loss of every position
+    let targets: HashMap<_, _> = trades
+        .iter()
+        .map(|trade| (trade.id, trade.target.unit_price))
+        .collect();
+    let outcomes: Vec<(Decimal, Decimal)> = calculate_open_positions(trades)
+        .iter()
+        .map(|position| {
+            let quantity = position.quantity;
+            let move_to_target = targets[&position.trade_id] - position.entry_price;
+            let profit = match position.category {
+                TradeCategory::Long => move_to_target * quantity,
+                TradeCategory::Short => -move_to_target * quantity,
+            };
+            (profit, -position.capital_at_risk())
+        })
+        .collect();
+
+    // 2. A trade wins when a random number of 32 bits is below the threshold
+    let threshold = (win_prob * Decimal::from(1u64 << 32))
+        .to_u64()
+        .ok_or("The win probability can not be converted to a threshold")?;
+    let mut random = SplitMix64(seed);
+
+    // 3. Run the iterations
+    let mut ending_equities: Vec<Decimal> = (0..iterations)
+        .map(|_| {
+            outcomes
+                .iter()
+                .fold(starting_equity, |equity, (profit, loss)| {
+                    if random.next() >> 32 < threshold {
+                        equity + profit
+                    } else {
+                        equity + loss
+                    }
+                })
+        })
+        .collect();
+    ending_equities.sort();
+
+    Ok(SimulationResult {
+        starting_equity,
+        ending_equities,
+    })
+}
+
+/// A small pseudo-random generator, so the simulation is reproducible without a dependency.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::{Order, Status};
+
+    /// A filled trade of 100 shares that wins 1000 at the target and loses 200 at the stop.
+    fn trade(category: TradeCategory) -> Trade {
+        let (stop, target) = match category {
+            TradeCategory::Long => (dec!(38), dec!(50)),
+            TradeCategory::Short => (dec!(42), dec!(30)),
+        };
+        Trade {
+            status: Status::Filled,
+            category,
+            safety_stop: Order {
+                unit_price: stop,
+                ..Default::default()
+            },
+            entry: Order {
+                unit_price: dec!(40),
+                quantity: 100,
+                filled_quantity: 100,
+                ..Default::default()
+            },
+            target: Order {
+                unit_price: target,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_simulation_is_deterministic() {
+        let trades = [trade(TradeCategory::Long), trade(TradeCategory::Short)];
+
+        let first = simulate_trade_outcomes(&trades, dec!(10000), 500, dec!(0.4), 42).unwrap();
+        let second = simulate_trade_outcomes(&trades, dec!(10000), 500, dec!(0.4), 42).unwrap();
+        assert_eq!(first, second);
+
+        let other = simulate_trade_outcomes(&trades, dec!(10000), 500, dec!(0.4), 7).unwrap();
+        assert_ne!(first.ending_equities, other.ending_equities);
+    }
+
+    #[test]
+    fn test_simulation_outcomes() {
+        let trades = [trade(TradeCategory::Long), trade(TradeCategory::Short)];
+
+        let result = simulate_trade_outcomes(&trades, dec!(10000), 1000, dec!(0.5), 1).unwrap();
+        assert_eq!(result.ending_equities.len(), 1000);
+        assert_eq!(result.percentile(0), dec!(9600)); // Both trades lose
+        assert_eq!(result.percentile(50), dec!(10800)); // One trade wins and the other loses
+        assert_eq!(result.percentile(100), dec!(12000)); // Both trades win
+        assert_eq!(result.probability_of_drawdown(dec!(10)), dec!(0));
+        let both_lose = result.probability_of_drawdown(dec!(1));
+        assert!(both_lose > dec!(0.2) && both_lose < dec!(0.3));
+    }
+
+    #[test]
+    fn test_simulation_with_certain_outcomes() {
+        let trades = [trade(TradeCategory::Long)];
+
+        let wins = simulate_trade_outcomes(&trades, dec!(10000), 10, dec!(1), 1).unwrap();
+        assert_eq!(wins.ending_equities, vec![dec!(11000); 10]);
+
+        let losses = simulate_trade_outcomes(&trades, dec!(10000), 10, dec!(0), 1).unwrap();
+        assert_eq!(losses.ending_equities, vec![dec!(9800); 10]);
+        assert_eq!(losses.probability_of_drawdown(dec!(1)), dec!(1));
+        assert_eq!(losses.probability_of_drawdown(dec!(2)), dec!(0));
+    }
+
+    #[test]
+    fn test_simulation_without_open_trades() {
+        let trades = [Trade::default()];
+
+        let result = simulate_trade_outcomes(&trades, dec!(10000), 10, dec!(0.5), 1).unwrap();
+        assert_eq!(result.percentile(50), dec!(10000));
+    }
+
+    #[test]
+    fn test_simulation_with_invalid_parameters() {
+        let trades = [trade(TradeCategory::Long)];
+
+        assert!(simulate_trade_outcomes(&trades, dec!(10000), 0, dec!(0.5), 1).is_err());
+        assert!(simulate_trade_outcomes(&trades, dec!(10000), 10, dec!(-0.1), 1).is_err());
+        assert!(simulate_trade_outcomes(&trades, dec!(10000), 10, dec!(1.1), 1).is_err());
+    }
+}
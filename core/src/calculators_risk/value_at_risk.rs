@@ -0,0 +1,157 @@
+use super::weighted_returns::{gross_notional, weighted_returns};
+use crate::analytics::OpenPosition;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+
+/// The historical value at risk of the open positions.
+///
+/// The profit or loss of every period is the return of the portfolio, weighted by the notional
+/// of each position, multiplied by the gross notional. The value at risk is the loss at the
+/// 1 - confidence percentile of those profits and losses, interpolated linearly:
+///
+/// rank = (1 - confidence) * (n - 1)
+/// percentile = x[floor(rank)] + (rank - floor(rank)) * (x[floor(rank) + 1] - x[floor(rank)])
+///
+/// Where `x` are the profits and losses sorted from the worst to the best and `n` the number
+/// of periods. It is zero if the percentile is a profit.
+pub fn value_at_risk(
+    positions: &[OpenPosition],
+    historical_returns: &HashMap<String, Vec<Decimal>>,
+    confidence: Decimal,
+) -> Result<Decimal, Box<dyn std::error::Error>> {
+    if confidence <= dec!(0) || confidence >= dec!(1) {
+        return Err(format!(
+            "The confidence must be between 0 and 1 and it is {}",
+            confidence
+        )
+        .into());
+    }
+
+    let periods = positions
+        .first()
+        .and_then(|position| historical_returns.get(&position.trading_vehicle.symbol))
+        .map_or(0, |returns| returns.len());
+    if periods == 0 {
+        return Err("Value at risk needs at least one historical return".into());
+    }
+
+    let notional = gross_notional(positions);
+    let mut profits: Vec<Decimal> = weighted_returns(positions, historical_returns, periods)?
+        .into_iter()
+        .map(|value| value * notional)
+        .collect();
+    profits.sort();
+
+    let rank = (dec!(1) - confidence) * Decimal::from(periods - 1);
+    let lower = rank.floor();
+    let index = usize::try_from(lower)?;
+    let percentile = match profits.get(index + 1) {
+        Some(next) => profits[index] + (rank - lower) * (next - profits[index]),
+        None => profits[index],
+    };
+
+    Ok(dec!(0).max(-percentile))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::{Currency, TradeCategory, TradingVehicle};
+    use uuid::Uuid;
+
+    fn position(symbol: &str, category: TradeCategory, quantity: u64) -> OpenPosition {
+        OpenPosition {
+            trade_id: Uuid::new_v4(),
+            trading_vehicle: TradingVehicle {
+                symbol: symbol.to_string(),
+                ..Default::default()
+            },
+            category,
+            currency: Currency::USD,
+            quantity: Decimal::from(quantity),
+            entry_price: dec!(10),
+            stop_price: dec!(9),
+        }
+    }
+
+    /// Twenty periods where the two worst returns are -10% and -5%.
+    fn distribution() -> Vec<Decimal> {
+        let mut returns = vec![dec!(0.01); 18];
+        returns.insert(7, dec!(-0.10));
+        returns.insert(13, dec!(-0.05));
+        returns
+    }
+
+    #[test]
+    fn test_value_at_risk_at_95_confidence() {
+        let positions = vec![position("AAPL", TradeCategory::Long, 100)];
+        let returns = HashMap::from([("AAPL".to_string(), distribution())]);
+
+        // The rank is 0.05 * 19 = 0.95, between the losses of 100 and 50.
+        let var = value_at_risk(&positions, &returns, dec!(0.95)).unwrap();
+        assert_eq!(var, dec!(52.5));
+    }
+
+    #[test]
+    fn test_value_at_risk_with_two_stocks() {
+        let positions = vec![
+            position("AAPL", TradeCategory::Long, 100),
+            position("MSFT", TradeCategory::Long, 100),
+        ];
+        let returns = HashMap::from([
+            ("AAPL".to_string(), distribution()),
+            ("MSFT".to_string(), vec![dec!(0); 20]),
+        ]);
+
+        let var = value_at_risk(&positions, &returns, dec!(0.95)).unwrap();
+        assert_eq!(var, dec!(52.5));
+    }
+
+    #[test]
+    fn test_value_at_risk_of_a_hedged_book() {
+        let positions = vec![
+            position("AAPL", TradeCategory::Long, 100),
+            position("MSFT", TradeCategory::Short, 100),
+        ];
+        let returns = HashMap::from([
+            ("AAPL".to_string(), distribution()),
+            ("MSFT".to_string(), distribution()),
+        ]);
+
+        let var = value_at_risk(&positions, &returns, dec!(0.95)).unwrap();
+        assert_eq!(var, dec!(0));
+    }
+
+    #[test]
+    fn test_value_at_risk_with_invalid_confidence() {
+        let positions = vec![position("AAPL", TradeCategory::Long, 100)];
+        let returns = HashMap::from([("AAPL".to_string(), distribution())]);
+
+        for confidence in [dec!(0), dec!(1), dec!(-0.5), dec!(1.5)] {
+            assert!(value_at_risk(&positions, &returns, confidence).is_err());
+        }
+    }
+
+    #[test]
+    fn test_value_at_risk_with_mismatched_returns() {
+        let positions = vec![
+            position("AAPL", TradeCategory::Long, 100),
+            position("MSFT", TradeCategory::Long, 100),
+        ];
+        let returns = HashMap::from([
+            ("AAPL".to_string(), distribution()),
+            ("MSFT".to_string(), vec![dec!(0); 19]),
+        ]);
+
+        assert!(value_at_risk(&positions, &returns, dec!(0.95)).is_err());
+    }
+
+    #[test]
+    fn test_value_at_risk_without_returns() {
+        let positions = vec![position("AAPL", TradeCategory::Long, 100)];
+
+        assert!(value_at_risk(&positions, &HashMap::new(), dec!(0.95)).is_err());
+        assert!(value_at_risk(&[], &HashMap::new(), dec!(0.95)).is_err());
+    }
+}
@@ -0,0 +1,57 @@
+use crate::analytics::OpenPosition;
+use model::TradeCategory;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+
+/// The notional at entry of all the positions, without netting longs and shorts.
+pub fn gross_notional(positions: &[OpenPosition]) -> Decimal {
+    positions.iter().map(notional).sum()
+}
+
+/// The return of the portfolio for every period.
+///
+/// Every position is weighted by its notional at entry over the gross notional of all the
+/// positions, and short positions have a negative weight. The returns of a trading vehicle
+/// are looked up by its symbol and they must have the given number of periods.
+pub fn weighted_returns(
+    positions: &[OpenPosition],
+    position_returns: &HashMap<String, Vec<Decimal>>,
+    periods: usize,
+) -> Result<Vec<Decimal>, Box<dyn std::error::Error>> {
+    let gross_notional = gross_notional(positions);
+    if gross_notional <= dec!(0) {
+        return Err("There are no open positions to weight".into());
+    }
+
+    let mut portfolio_returns = vec![dec!(0); periods];
+    for position in positions {
+        let symbol = &position.trading_vehicle.symbol;
+        let returns = position_returns
+            .get(symbol)
+            .ok_or_else(|| format!("There are no returns for {}", symbol))?;
+        if returns.len() != periods {
+            return Err(format!(
+                "The returns of {} have {} periods and {} were expected",
+                symbol,
+                returns.len(),
+                periods
+            )
+            .into());
+        }
+
+        let weight = match position.category {
+            TradeCategory::Long => notional(position) / gross_notional,
+            TradeCategory::Short => -notional(position) / gross_notional,
+        };
+        for (total, value) in portfolio_returns.iter_mut().zip(returns) {
+            *total += weight * value;
+        }
+    }
+
+    Ok(portfolio_returns)
+}
+
+fn notional(position: &OpenPosition) -> Decimal {
+    position.entry_price * position.quantity
+}
@@ -3,15 +3,25 @@ mod capital_in_market;
 mod capital_not_at_risk;
 mod capital_out_of_market;
 mod capital_taxable;
+mod concentration;
+mod fees;
+mod grade;
 mod performance;
 mod quantity;
 mod risk;
+mod tax_lots;
+mod tick_size;
 
 pub use capital_funded::TradeCapitalFunded;
 pub use capital_in_market::TradeCapitalInMarket;
 pub use capital_not_at_risk::TradeCapitalNotAtRisk;
 pub use capital_out_of_market::TradeCapitalOutOfMarket;
 pub use capital_taxable::TradeCapitalTaxable;
+pub use concentration::{ConcentrationCalculator, CorrelationCluster};
+pub use fees::TradeFees;
+pub use grade::TradeGradeCalculator;
 pub use performance::TradePerformance;
-pub use quantity::QuantityCalculator;
+pub use quantity::{QuantityCalculator, SuggestedSize, TradePlan};
 pub use risk::RiskCalculator;
+pub use tax_lots::TaxLotCalculator;
+pub use tick_size::TickSizeCalculator;
@@ -26,7 +26,7 @@ impl TradeCapitalInMarket {
                 | TransactionCategory::CloseSafetyStopSlippage(_) => {
                     total = Decimal::from(0) // We have exited the market, so we have no money in the market.
                 },
-                TransactionCategory::FeeOpen(_) | TransactionCategory::FeeClose(_) | TransactionCategory::PaymentTax(_) | TransactionCategory::PaymentEarnings(_)  => {
+                TransactionCategory::FeeOpen(_) | TransactionCategory::FeeClose(_) | TransactionCategory::FeeRebate(_) | TransactionCategory::PaymentTax(_) | TransactionCategory::PaymentEarnings(_) | TransactionCategory::PaymentReinvestment(_)  => {
                     // We ignore the fees because they are charged from the account and not from the trade.
                 }
                 default => panic!(
@@ -29,7 +29,7 @@ impl TradeCapitalNotAtRisk {
             let risk_per_share = trade.entry.unit_price - trade.safety_stop.unit_price;
 
             // Calculate the total capital not at risk for the trade and add it to the accumulator.
-            acc + (trade.entry.unit_price - risk_per_share) * Decimal::from(trade.entry.quantity)
+            acc + (trade.entry.unit_price - risk_per_share) * trade.entry.decimal_quantity()
         });
 
         // Return the total capital not at risk as the result of the function.
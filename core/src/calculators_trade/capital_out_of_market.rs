@@ -38,7 +38,7 @@ impl TradeCapitalOutOfMarket {
                     // This is money that we have used to exit the market at a loss - slippage.
                     total += tx.amount
                 },
-                TransactionCategory::FeeOpen(_) | TransactionCategory::FeeClose(_) | TransactionCategory::PaymentTax(_) | TransactionCategory::PaymentEarnings(_) => {
+                TransactionCategory::FeeOpen(_) | TransactionCategory::FeeClose(_) | TransactionCategory::FeeRebate(_) | TransactionCategory::PaymentTax(_) | TransactionCategory::PaymentEarnings(_) | TransactionCategory::PaymentReinvestment(_) => {
                     // We ignore the fees because they are charged from the account and not from the trade.
                 }
                 default => panic!(
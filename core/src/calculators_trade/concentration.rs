@@ -0,0 +1,283 @@
+use crate::analytics::OpenPosition;
+use model::Trade;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+pub struct ConcentrationCalculator;
+
+/// Symbols whose prices move together, so their positions behave like a single position.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CorrelationCluster {
+    /// The symbols of the cluster, in alphabetical order.
+    pub symbols: Vec<String>,
+
+    /// The notional of the positions of all the symbols of the cluster.
+    pub exposure: Decimal,
+}
+
+impl ConcentrationCalculator {
+    /// This function calculates the percentage of the capital in open trades that the largest
+    /// position would use if the given trade is opened.
+    /// Trades in the same trading vehicle are added up into a single position.
+    ///
+    /// The notional of a trade is calculated as follows:
+    ///    entry price * quantity
+    pub fn max_concentration(trade: &Trade, open_trades: &[Trade]) -> Decimal {
+        let mut positions: HashMap<Uuid, Decimal> = HashMap::new();
+        for open_trade in open_trades
+            .iter()
+            .filter(|open_trade| open_trade.id != trade.id)
+            .chain(std::iter::once(trade))
+        {
+            *positions
+                .entry(open_trade.trading_vehicle.id)
+                .or_insert(dec!(0)) += ConcentrationCalculator::notional(open_trade);
+        }
+
+        let total: Decimal = positions.values().sum();
+        if total <= dec!(0) {
+            return dec!(0);
+        }
+
+        let largest = positions.values().max().copied().unwrap_or(dec!(0));
+        largest / total * dec!(100)
+    }
+
+    /// Groups the symbols of the positions whose pairwise correlation is above the threshold.
+    /// Correlation is transitive inside a cluster: if A moves with B and B with C, the three are
+    /// one cluster. A pair can be given in any order. Symbols without correlations are alone.
+    ///
+    /// The clusters are sorted by exposure, the largest first, and then by their first symbol.
+    pub fn calculate_correlated_clusters(
+        positions: &[OpenPosition],
+        correlation: &HashMap<(String, String), Decimal>,
+        threshold: Decimal,
+    ) -> Vec<CorrelationCluster> {
+        // 1. Add up the notional of the positions of every symbol
+        let mut exposures: BTreeMap<String, Decimal> = BTreeMap::new();
+        for position in positions {
+            *exposures
+                .entry(position.trading_vehicle.symbol.to_uppercase())
+                .or_insert(dec!(0)) += position.entry_price * position.quantity;
+        }
+        let symbols: Vec<&String> = exposures.keys().collect();
+
+        // 2. Link the symbols that are correlated. Every symbol points to the first symbol of its cluster
+        let mut cluster_of: Vec<usize> = (0..symbols.len()).collect();
+        for a in 0..symbols.len() {
+            for b in (a + 1)..symbols.len() {
+                let pair = (symbols[a].clone(), symbols[b].clone());
+                let reversed = (symbols[b].clone(), symbols[a].clone());
+                let correlated = correlation
+                    .get(&pair)
+                    .or_else(|| correlation.get(&reversed))
+                    .is_some_and(|value| *value > threshold);
+                if correlated {
+                    let (merged, kept) = (cluster_of[b], cluster_of[a]);
+                    for cluster in cluster_of.iter_mut() {
+                        if *cluster == merged {
+                            *cluster = kept;
+                        }
+                    }
+                }
+            }
+        }
+
+        // 3. Sum the exposure of every cluster
+        let mut clusters: BTreeMap<usize, CorrelationCluster> = BTreeMap::new();
+        for (index, symbol) in symbols.iter().enumerate() {
+            let cluster = clusters
+                .entry(cluster_of[index])
+                .or_insert(CorrelationCluster {
+                    symbols: Vec::new(),
+                    exposure: dec!(0),
+                });
+            cluster.symbols.push((*symbol).clone());
+            cluster.exposure += exposures[*symbol];
+        }
+
+        let mut clusters: Vec<CorrelationCluster> = clusters.into_values().collect();
+        clusters.sort_by(|a, b| {
+            b.exposure
+                .cmp(&a.exposure)
+                .then_with(|| a.symbols.cmp(&b.symbols))
+        });
+        clusters
+    }
+
+    fn notional(trade: &Trade) -> Decimal {
+        trade.entry.unit_price * trade.entry.decimal_quantity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::{Order, TradingVehicle};
+
+    fn trade(trading_vehicle_id: Uuid, unit_price: Decimal, quantity: u64) -> Trade {
+        Trade {
+            trading_vehicle: TradingVehicle {
+                id: trading_vehicle_id,
+                ..Default::default()
+            },
+            entry: Order {
+                unit_price,
+                quantity,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_max_concentration_first_position() {
+        let new_trade = trade(Uuid::new_v4(), dec!(40), 500);
+
+        let result = ConcentrationCalculator::max_concentration(&new_trade, &[]);
+
+        assert_eq!(result, dec!(100));
+    }
+
+    #[test]
+    fn test_max_concentration_diversified() {
+        let open_trades = vec![
+            trade(Uuid::new_v4(), dec!(100), 100),
+            trade(Uuid::new_v4(), dec!(50), 200),
+            trade(Uuid::new_v4(), dec!(10), 1000),
+        ];
+        let new_trade = trade(Uuid::new_v4(), dec!(20), 250);
+
+        let result = ConcentrationCalculator::max_concentration(&new_trade, &open_trades);
+
+        assert_eq!(result, dec!(10000) / dec!(35000) * dec!(100));
+    }
+
+    #[test]
+    fn test_max_concentration_adds_same_trading_vehicle() {
+        let aapl = Uuid::new_v4();
+        let open_trades = vec![
+            trade(aapl, dec!(100), 100),
+            trade(Uuid::new_v4(), dec!(100), 100),
+        ];
+        let new_trade = trade(aapl, dec!(100), 100);
+
+        let result = ConcentrationCalculator::max_concentration(&new_trade, &open_trades);
+
+        assert_eq!(result, dec!(20000) / dec!(30000) * dec!(100));
+    }
+
+    fn position(symbol: &str, entry_price: Decimal, quantity: u64) -> OpenPosition {
+        OpenPosition {
+            trade_id: Uuid::new_v4(),
+            trading_vehicle: TradingVehicle {
+                symbol: symbol.to_string(),
+                ..Default::default()
+            },
+            category: model::TradeCategory::Long,
+            currency: model::Currency::USD,
+            quantity: Decimal::from(quantity),
+            entry_price,
+            stop_price: dec!(0),
+        }
+    }
+
+    fn pair(a: &str, b: &str) -> (String, String) {
+        (a.to_string(), b.to_string())
+    }
+
+    #[test]
+    fn test_correlated_symbols_are_clustered() {
+        let positions = vec![
+            position("AMD", dec!(100), 50),
+            position("NVDA", dec!(400), 10),
+            position("XOM", dec!(100), 60),
+            position("NVDA", dec!(400), 5),
+        ];
+        let correlation = HashMap::from([
+            (pair("NVDA", "AMD"), dec!(0.85)),
+            (pair("AMD", "XOM"), dec!(0.1)),
+        ]);
+
+        let clusters = ConcentrationCalculator::calculate_correlated_clusters(
+            &positions,
+            &correlation,
+            dec!(0.7),
+        );
+
+        // AMD has 5000 and NVDA 6000, more than XOM with 6000 alone
+        assert_eq!(
+            clusters,
+            vec![
+                CorrelationCluster {
+                    symbols: vec!["AMD".to_string(), "NVDA".to_string()],
+                    exposure: dec!(11000),
+                },
+                CorrelationCluster {
+                    symbols: vec!["XOM".to_string()],
+                    exposure: dec!(6000),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_correlated_clusters_are_transitive() {
+        let positions = vec![
+            position("A", dec!(10), 10),
+            position("B", dec!(10), 10),
+            position("C", dec!(10), 10),
+            position("D", dec!(10), 10),
+        ];
+        let correlation = HashMap::from([
+            (pair("A", "B"), dec!(0.9)),
+            (pair("C", "B"), dec!(0.9)),
+            (pair("A", "D"), dec!(0.7)),
+        ]);
+
+        let clusters = ConcentrationCalculator::calculate_correlated_clusters(
+            &positions,
+            &correlation,
+            dec!(0.7),
+        );
+
+        // The correlation must be above the threshold, so D is alone
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].symbols, vec!["A", "B", "C"]);
+        assert_eq!(clusters[0].exposure, dec!(300));
+        assert_eq!(clusters[1].symbols, vec!["D"]);
+    }
+
+    #[test]
+    fn test_symbols_without_correlation_are_singletons() {
+        let positions = vec![position("B", dec!(10), 10), position("A", dec!(10), 10)];
+
+        let clusters = ConcentrationCalculator::calculate_correlated_clusters(
+            &positions,
+            &HashMap::new(),
+            dec!(0.5),
+        );
+
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].symbols, vec!["A"]);
+        assert_eq!(clusters[1].symbols, vec!["B"]);
+        assert!(ConcentrationCalculator::calculate_correlated_clusters(
+            &[],
+            &HashMap::new(),
+            dec!(0.5)
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_max_concentration_ignores_the_same_trade() {
+        let new_trade = trade(Uuid::new_v4(), dec!(40), 500);
+        let open_trades = vec![new_trade.clone(), trade(Uuid::new_v4(), dec!(40), 500)];
+
+        let result = ConcentrationCalculator::max_concentration(&new_trade, &open_trades);
+
+        assert_eq!(result, dec!(50));
+    }
+}
@@ -0,0 +1,228 @@
+use model::{FeeModel, Order, ReadTransactionDB, Trade, TradeCategory, TransactionCategory};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+pub struct TradeFees;
+
+impl TradeFees {
+    /// The fees paid to open and close a trade minus the rebates paid by the broker.
+    /// It is negative when the rebates are higher than the commissions.
+    pub fn calculate(
+        trade_id: Uuid,
+        database: &mut dyn ReadTransactionDB,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let mut total = dec!(0);
+
+        for tx in database.all_trade_transactions(trade_id)? {
+            match tx.category {
+                TransactionCategory::FeeOpen(_) | TransactionCategory::FeeClose(_) => {
+                    total += tx.amount
+                }
+                TransactionCategory::FeeRebate(_) => total -= tx.amount,
+                _ => {} // We only want to count the costs charged by the broker.
+            }
+        }
+
+        Ok(total)
+    }
+
+    /// The exit price at which the trade neither wins nor loses money once the net fees are paid.
+    /// A rebate moves the breakeven price below the entry price of a long trade.
+    pub fn breakeven(
+        trade: &Trade,
+        database: &mut dyn ReadTransactionDB,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let quantity = trade.entry.decimal_quantity();
+        if quantity <= dec!(0) {
+            return Err(format!("TradeFees: trade {} has no quantity", trade.id).into());
+        }
+
+        let entry_price = trade
+            .entry
+            .average_filled_price
+            .unwrap_or(trade.entry.unit_price);
+        let fees_per_unit = TradeFees::calculate(trade.id, database)? / quantity;
+
+        match trade.category {
+            TradeCategory::Long => Ok(entry_price + fees_per_unit),
+            TradeCategory::Short => Ok(entry_price - fees_per_unit),
+        }
+    }
+
+    /// The fee that the fee model of an account charges for the fill of an order.
+    /// The filled quantity and the average filled price are used when the broker reported them.
+    pub fn from_model(
+        model: &FeeModel,
+        order: &Order,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let quantity = match order.filled_quantity {
+            0 => order.decimal_quantity(),
+            filled => Decimal::from(filled),
+        };
+        let price = order.average_filled_price.unwrap_or(order.unit_price);
+
+        let fee = match *model {
+            FeeModel::None => dec!(0),
+            FeeModel::PerShare(amount) => amount * quantity,
+            FeeModel::Percent(percent) => quantity * price * percent / dec!(100),
+            FeeModel::Fixed(amount) => amount,
+        };
+
+        if fee < dec!(0) {
+            return Err(format!(
+                "TradeFees: the fee model {} {} calculated a negative fee of {}",
+                model,
+                model.amount().unwrap_or_default(),
+                fee
+            )
+            .into());
+        }
+        Ok(fee)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks::read_transaction_db_mocks::MockDatabase;
+    use model::Order;
+
+    fn trade(category: TradeCategory) -> Trade {
+        Trade {
+            category,
+            entry: Order {
+                unit_price: dec!(40),
+                quantity: 10,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_calculate_with_empty_transactions() {
+        let mut database = MockDatabase::new();
+
+        let result = TradeFees::calculate(Uuid::new_v4(), &mut database);
+        assert_eq!(result.unwrap(), dec!(0));
+    }
+
+    #[test]
+    fn test_calculate_with_fees() {
+        let mut database = MockDatabase::new();
+
+        database.set_transaction(TransactionCategory::OpenTrade(Uuid::new_v4()), dec!(100));
+        database.set_transaction(TransactionCategory::FeeOpen(Uuid::new_v4()), dec!(1));
+        database.set_transaction(TransactionCategory::FeeClose(Uuid::new_v4()), dec!(1.5));
+
+        let result = TradeFees::calculate(Uuid::new_v4(), &mut database);
+        assert_eq!(result.unwrap(), dec!(2.5));
+    }
+
+    #[test]
+    fn test_calculate_with_rebate_above_fees() {
+        let mut database = MockDatabase::new();
+
+        database.set_transaction(TransactionCategory::FeeOpen(Uuid::new_v4()), dec!(1));
+        database.set_transaction(TransactionCategory::FeeClose(Uuid::new_v4()), dec!(1));
+        database.set_transaction(TransactionCategory::FeeRebate(Uuid::new_v4()), dec!(3));
+
+        let result = TradeFees::calculate(Uuid::new_v4(), &mut database);
+        assert_eq!(result.unwrap(), dec!(-1));
+    }
+
+    #[test]
+    fn test_breakeven_with_fees() {
+        let mut database = MockDatabase::new();
+        database.set_transaction(TransactionCategory::FeeOpen(Uuid::new_v4()), dec!(2));
+        database.set_transaction(TransactionCategory::FeeClose(Uuid::new_v4()), dec!(3));
+
+        let long = TradeFees::breakeven(&trade(TradeCategory::Long), &mut database);
+        assert_eq!(long.unwrap(), dec!(40.5));
+
+        let short = TradeFees::breakeven(&trade(TradeCategory::Short), &mut database);
+        assert_eq!(short.unwrap(), dec!(39.5));
+    }
+
+    #[test]
+    fn test_breakeven_with_rebate_above_fees() {
+        let mut database = MockDatabase::new();
+        database.set_transaction(TransactionCategory::FeeOpen(Uuid::new_v4()), dec!(1));
+        database.set_transaction(TransactionCategory::FeeRebate(Uuid::new_v4()), dec!(3));
+
+        let result = TradeFees::breakeven(&trade(TradeCategory::Long), &mut database);
+        assert_eq!(result.unwrap(), dec!(39.8));
+    }
+
+    #[test]
+    fn test_breakeven_without_quantity() {
+        let mut database = MockDatabase::new();
+        let trade = Trade {
+            entry: Order {
+                quantity: 0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert!(TradeFees::breakeven(&trade, &mut database).is_err());
+    }
+
+    fn fill_of_100_shares() -> Order {
+        Order {
+            unit_price: dec!(40),
+            quantity: 100,
+            filled_quantity: 100,
+            average_filled_price: Some(dec!(40.50)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_from_model_without_fees() {
+        let fee = TradeFees::from_model(&FeeModel::None, &fill_of_100_shares());
+        assert_eq!(fee.unwrap(), dec!(0));
+    }
+
+    #[test]
+    fn test_from_model_per_share() {
+        let fee = TradeFees::from_model(&FeeModel::PerShare(dec!(0.005)), &fill_of_100_shares());
+        assert_eq!(fee.unwrap(), dec!(0.5));
+    }
+
+    #[test]
+    fn test_from_model_percent_of_the_filled_notional() {
+        let fee = TradeFees::from_model(&FeeModel::Percent(dec!(0.1)), &fill_of_100_shares());
+        assert_eq!(fee.unwrap(), dec!(4.05));
+    }
+
+    #[test]
+    fn test_from_model_fixed() {
+        let fee = TradeFees::from_model(&FeeModel::Fixed(dec!(1.99)), &fill_of_100_shares());
+        assert_eq!(fee.unwrap(), dec!(1.99));
+    }
+
+    #[test]
+    fn test_from_model_without_fill_uses_the_order() {
+        let order = Order {
+            unit_price: dec!(40),
+            quantity: 100,
+            ..Default::default()
+        };
+
+        let fee = TradeFees::from_model(&FeeModel::Percent(dec!(0.1)), &order);
+        assert_eq!(fee.unwrap(), dec!(4));
+    }
+
+    #[test]
+    fn test_from_model_rejects_negative_fees() {
+        for model in [
+            FeeModel::PerShare(dec!(-0.005)),
+            FeeModel::Percent(dec!(-0.1)),
+            FeeModel::Fixed(dec!(-1)),
+        ] {
+            assert!(TradeFees::from_model(&model, &fill_of_100_shares()).is_err());
+        }
+    }
+}
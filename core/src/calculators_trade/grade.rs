@@ -0,0 +1,153 @@
+use crate::analytics::r_multiple;
+use model::{GradingWeightsPermille, Trade, TradeCategory, TradeGrade};
+use rust_decimal_macros::dec;
+
+pub struct TradeGradeCalculator;
+
+impl TradeGradeCalculator {
+    /// Grades a closed trade from 0 to 100 in every area:
+    ///
+    /// 1. Risk management: the trade did not lose more than the money risked until the stop.
+    /// 2. Execution: the entry was filled at the planned price or better.
+    /// 3. Outcome: the R-multiple of the trade is positive.
+    /// 4. Documentation: the trade has a thesis, a strategy and a reference.
+    ///
+    /// Missing metadata only lowers the documentation score.
+    pub fn calculate(
+        trade: &Trade,
+        weights: &GradingWeightsPermille,
+    ) -> Result<TradeGrade, Box<dyn std::error::Error>> {
+        weights.validate()?;
+
+        let r = r_multiple(trade);
+        let risk_management = match r {
+            Some(r) if r >= dec!(-1) => 100,
+            _ => 0, // Without a risk there is no stop to respect.
+        };
+        let outcome = match r {
+            Some(r) if r > dec!(0) => 100,
+            _ => 0,
+        };
+
+        let planned = trade.entry.unit_price;
+        let slipped = match (trade.category, trade.entry.average_filled_price) {
+            (TradeCategory::Long, Some(price)) => price > planned,
+            (TradeCategory::Short, Some(price)) => price < planned,
+            (_, None) => false,
+        };
+        let execution = if slipped { 50 } else { 100 };
+
+        let mut documentation = 0;
+        if trade
+            .thesis
+            .as_deref()
+            .is_some_and(|thesis| !thesis.trim().is_empty())
+        {
+            documentation += 60;
+        }
+        if trade.strategy_id.is_some() {
+            documentation += 20;
+        }
+        if trade.reference.is_some() {
+            documentation += 20;
+        }
+
+        let mut grade = TradeGrade {
+            trade_id: trade.id,
+            risk_management,
+            execution,
+            outcome,
+            documentation,
+            ..Default::default()
+        };
+        // The weights sum 1000, so the overall score is at most 100.
+        grade.overall = weights.weigh(&grade) as u8;
+        Ok(grade)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::{Order, Status, TradeBalance};
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    fn trade(status: Status, performance: Decimal) -> Trade {
+        Trade {
+            status,
+            category: TradeCategory::Long,
+            safety_stop: Order {
+                unit_price: dec!(38),
+                ..Default::default()
+            },
+            entry: Order {
+                unit_price: dec!(40),
+                quantity: 500,
+                average_filled_price: Some(dec!(39.9)),
+                ..Default::default()
+            },
+            balance: TradeBalance {
+                total_performance: performance,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_grade_documented_winner() {
+        let trade = Trade {
+            thesis: Some("Breakout above the range of the last month".to_string()),
+            strategy_id: Some(Uuid::new_v4()),
+            reference: Some("TSLA-001".to_string()),
+            ..trade(Status::ClosedTarget, dec!(6500))
+        };
+
+        let grade =
+            TradeGradeCalculator::calculate(&trade, &GradingWeightsPermille::default()).unwrap();
+        assert_eq!(grade.trade_id, trade.id);
+        assert_eq!(grade.risk_management, 100);
+        assert_eq!(grade.execution, 100);
+        assert_eq!(grade.outcome, 100);
+        assert_eq!(grade.documentation, 100);
+        assert_eq!(grade.overall, 100);
+    }
+
+    #[test]
+    fn test_grade_undocumented_stop_loss() {
+        let trade = trade(Status::ClosedStopLoss, dec!(-1000));
+
+        let grade =
+            TradeGradeCalculator::calculate(&trade, &GradingWeightsPermille::default()).unwrap();
+        assert_eq!(grade.risk_management, 100);
+        assert_eq!(grade.outcome, 0);
+        assert_eq!(grade.documentation, 0);
+        assert_eq!(grade.overall, 50);
+    }
+
+    #[test]
+    fn test_grade_loss_beyond_the_stop() {
+        let mut trade = trade(Status::ClosedStopLoss, dec!(-1500));
+        trade.entry.average_filled_price = Some(dec!(40.5));
+
+        let grade =
+            TradeGradeCalculator::calculate(&trade, &GradingWeightsPermille::default()).unwrap();
+        assert_eq!(grade.risk_management, 0);
+        assert_eq!(grade.execution, 50);
+        assert_eq!(grade.overall, 10);
+    }
+
+    #[test]
+    fn test_grade_with_invalid_weights() {
+        let weights = GradingWeightsPermille {
+            risk_management: 1000,
+            ..Default::default()
+        };
+
+        assert!(
+            TradeGradeCalculator::calculate(&trade(Status::ClosedTarget, dec!(1)), &weights)
+                .is_err()
+        );
+    }
+}
@@ -21,7 +21,8 @@ impl TradePerformance {
 
                 TransactionCategory::CloseTarget(_)
                 | TransactionCategory::CloseSafetyStop(_)
-                | TransactionCategory::CloseSafetyStopSlippage(_) => total += tx.amount,
+                | TransactionCategory::CloseSafetyStopSlippage(_)
+                | TransactionCategory::FeeRebate(_) => total += tx.amount,
                 _ => {} // We don't want to count the transactions paid out of the trade or fund the trade.
             }
         }
@@ -112,4 +113,20 @@ mod tests {
         let result = TradePerformance::calculate(Uuid::new_v4(), &mut database);
         assert_eq!(result.unwrap(), dec!(-52));
     }
+
+    #[test]
+    fn test_calculate_with_rebate_above_fees() {
+        let mut database = MockDatabase::new();
+
+        database.set_transaction(TransactionCategory::FundTrade(Uuid::new_v4()), dec!(100));
+        database.set_transaction(TransactionCategory::OpenTrade(Uuid::new_v4()), dec!(100));
+        database.set_transaction(TransactionCategory::FeeOpen(Uuid::new_v4()), dec!(1));
+        database.set_transaction(TransactionCategory::FeeClose(Uuid::new_v4()), dec!(1));
+        database.set_transaction(TransactionCategory::FeeRebate(Uuid::new_v4()), dec!(3));
+        database.set_transaction(TransactionCategory::CloseTarget(Uuid::new_v4()), dec!(200));
+
+        // The gross profit is 100 and the net fees are -1.
+        let result = TradePerformance::calculate(Uuid::new_v4(), &mut database);
+        assert_eq!(result.unwrap(), dec!(101));
+    }
 }
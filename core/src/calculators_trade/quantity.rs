@@ -1,11 +1,48 @@
-use model::{Currency, DatabaseFactory, RuleName};
+use model::{Currency, DatabaseFactory, RuleLevel, RuleName, Status, Trade, TradeCategory};
 use rust_decimal::{prelude::ToPrimitive, Decimal};
 use rust_decimal_macros::dec;
+use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::analytics::kelly_criterion;
 use crate::calculators_account::AccountCapitalAvailable;
 use crate::calculators_trade::RiskCalculator;
 
+/// Fraction of the Kelly criterion used to size trades.
+/// Full Kelly is too aggressive for the estimation errors of a small sample of trades.
+const KELLY_FRACTION: Decimal = dec!(0.5);
+
+/// Quantities suggested for a new trade.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SuggestedSize {
+    /// The maximum quantity allowed by the risk rules of the account.
+    pub conservative: i64,
+
+    /// The conservative quantity scaled by a fraction of the Kelly criterion of the
+    /// closed trades of the account. It is never above the conservative quantity.
+    pub kelly_capped: i64,
+}
+
+/// A preview of a trade with candidate prices. Nothing of it is persisted.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TradePlan {
+    /// The maximum quantity allowed by the capital and the risk rules of the account.
+    pub quantity: i64,
+
+    /// The money needed to enter the maximum quantity at the worst of the entry and the stop.
+    pub capital_required: Decimal,
+
+    /// The money that is lost with the maximum quantity if the stop is hit.
+    pub risk: Decimal,
+
+    /// The reward of the trade per unit of risk, for example 2 for a 2:1 trade.
+    pub reward_risk_ratio: Decimal,
+
+    /// The money that could still be risked this month once the trade is funded.
+    /// It is None if the account does not have a risk per month rule.
+    pub remaining_risk_budget: Option<Decimal>,
+}
+
 pub struct QuantityCalculator;
 
 impl QuantityCalculator {
@@ -50,6 +87,10 @@ impl QuantityCalculator {
                         return Ok(risk_per_trade);
                     }
                 }
+                RuleName::MaxPositionConcentration(_)
+                | RuleName::OnePositionPerSymbolSide
+                | RuleName::MaxConsecutiveLosses(_) => {} // Validated when the trade is funded.
+                RuleName::MinRewardRiskRatio(_) => {} // Validated when the trade is created.
             }
         }
 
@@ -57,6 +98,318 @@ impl QuantityCalculator {
         Ok((total_available / entry_price).to_i64().unwrap())
     }
 
+    pub fn suggested_quantity(
+        account_id: Uuid,
+        entry_price: Decimal,
+        stop_price: Decimal,
+        currency: &Currency,
+        database: &mut dyn DatabaseFactory,
+    ) -> Result<SuggestedSize, Box<dyn std::error::Error>> {
+        // 1. Calculate the quantity allowed by the risk rules
+        let conservative = QuantityCalculator::maximum_quantity(
+            account_id,
+            entry_price,
+            stop_price,
+            currency,
+            database,
+        )?;
+
+        // 2. Calculate the Kelly criterion of the closed trades
+        let mut results = Vec::new();
+        for status in [Status::ClosedTarget, Status::ClosedStopLoss] {
+            results.extend(
+                database
+                    .trade_read()
+                    .read_trades_with_status(account_id, status)?
+                    .into_iter()
+                    .filter(|trade| trade.currency == *currency)
+                    .map(|trade| trade.balance.total_performance),
+            );
+        }
+        // Without closed trades there is no edge to size for.
+        let kelly = kelly_criterion(&results).unwrap_or(dec!(0));
+
+        // 3. Scale the conservative quantity
+        Ok(SuggestedSize {
+            conservative,
+            kelly_capped: QuantityCalculator::kelly_capped_quantity(conservative, kelly),
+        })
+    }
+
+    /// The quantity of a trade sized with a fraction of the Kelly criterion of a win probability
+    /// and a reward per unit of risk:
+    ///
+    /// kelly = (win_prob * reward_risk - (1 - win_prob)) / reward_risk
+    /// quantity = equity * kelly * fraction / |entry - stop|
+    ///
+    /// The equity is the money of the account in the currency, including the open positions.
+    /// Trust trades with cash, so the quantity is never above what the available capital can buy.
+    /// Without an edge the Kelly criterion is negative and the quantity is zero.
+    #[allow(clippy::too_many_arguments)]
+    pub fn kelly_quantity(
+        account_id: Uuid,
+        win_prob: Decimal,
+        reward_risk: Decimal,
+        fraction: Decimal,
+        entry_price: Decimal,
+        stop_price: Decimal,
+        currency: &Currency,
+        database: &mut dyn DatabaseFactory,
+    ) -> Result<i64, Box<dyn std::error::Error>> {
+        // 1. Validate the inputs
+        if win_prob <= dec!(0) || win_prob >= dec!(1) {
+            return Err(format!(
+                "The win probability must be between 0 and 1 and it is {}",
+                win_prob
+            )
+            .into());
+        }
+        if reward_risk <= dec!(0) {
+            return Err(
+                format!("The reward risk must be positive and it is {}", reward_risk).into(),
+            );
+        }
+        if fraction <= dec!(0) || fraction > dec!(1) {
+            return Err(format!(
+                "The fraction of Kelly must be above 0 and at most 1 and it is {}",
+                fraction
+            )
+            .into());
+        }
+        if entry_price <= dec!(0) || stop_price <= dec!(0) || entry_price == stop_price {
+            return Err(format!(
+                "The entry {} and the stop {} must be positive and different",
+                entry_price, stop_price
+            )
+            .into());
+        }
+
+        // 2. Calculate the Kelly criterion
+        let kelly = QuantityCalculator::kelly_fraction(win_prob, reward_risk);
+        if kelly <= dec!(0) {
+            return Ok(0);
+        }
+
+        // 3. Size the risk budget with the equity of the account. It is the cash of the account
+        // plus the positions at their entry price.
+        let account = database.account_read().id(account_id)?;
+        let (balance, equity) = crate::commands::balance::equity(database, &account, currency)?;
+        Ok(QuantityCalculator::kelly_risk_quantity(
+            equity,
+            balance.total_available,
+            kelly * fraction,
+            entry_price,
+            stop_price,
+        ))
+    }
+
+    // The Kelly criterion of a bet that wins `reward_risk` times what it loses with a probability.
+    fn kelly_fraction(win_prob: Decimal, reward_risk: Decimal) -> Decimal {
+        (win_prob * reward_risk - (dec!(1) - win_prob)) / reward_risk
+    }
+
+    // The shares that risk a part of the equity, limited to the shares that the available capital can buy.
+    fn kelly_risk_quantity(
+        equity: Decimal,
+        available: Decimal,
+        risk_fraction: Decimal,
+        entry_price: Decimal,
+        stop_price: Decimal,
+    ) -> i64 {
+        let risk_budget = equity * risk_fraction;
+        let quantity = (risk_budget / (entry_price - stop_price).abs())
+            .min(available / entry_price)
+            .floor()
+            .max(dec!(0));
+        quantity.to_i64().unwrap_or(i64::MAX)
+    }
+
+    /// Previews the quantity, capital and risk of a trade with candidate prices.
+    /// The direction of the trade is given by the stop: below the entry is long and above is short.
+    pub fn trade_plan(
+        account_id: Uuid,
+        entry_price: Decimal,
+        stop_price: Decimal,
+        target_price: Decimal,
+        currency: &Currency,
+        database: &mut dyn DatabaseFactory,
+    ) -> Result<TradePlan, Box<dyn std::error::Error>> {
+        // 1. Validate that the trade has a risk and a reward
+        let reward_risk_ratio =
+            QuantityCalculator::reward_risk_ratio(entry_price, stop_price, target_price)
+                .ok_or_else(|| {
+                    format!(
+                        "The entry {}, stop {} and target {} do not have a risk and a reward",
+                        entry_price, stop_price, target_price
+                    )
+                })?;
+
+        // 2. Calculate the quantity allowed by the capital and the risk rules
+        let quantity = QuantityCalculator::maximum_quantity(
+            account_id,
+            entry_price,
+            stop_price,
+            currency,
+            database,
+        )?;
+        let decimal_quantity = Decimal::from(quantity);
+        let risk = (entry_price - stop_price).abs() * decimal_quantity;
+
+        // 3. Calculate the monthly budget that is left after the trade
+        let has_monthly_risk = database
+            .rule_read()
+            .read_all_rules(account_id)?
+            .iter()
+            .any(|rule| matches!(rule.name, RuleName::RiskPerMonth(_)));
+        let remaining_risk_budget = match has_monthly_risk {
+            true => Some(
+                RiskCalculator::calculate_risk_budget_remaining(account_id, currency, database)?
+                    - risk,
+            ),
+            false => None,
+        };
+
+        Ok(TradePlan {
+            quantity,
+            capital_required: entry_price.max(stop_price) * decimal_quantity,
+            risk,
+            reward_risk_ratio,
+            remaining_risk_budget,
+        })
+    }
+
+    // The reward per unit of risk. It is None if the stop or the target are not on the right side of the entry.
+    fn reward_risk_ratio(
+        entry_price: Decimal,
+        stop_price: Decimal,
+        target_price: Decimal,
+    ) -> Option<Decimal> {
+        let risk = entry_price - stop_price;
+        let reward = target_price - entry_price;
+        if risk.is_zero()
+            || reward.is_zero()
+            || risk.is_sign_negative() != reward.is_sign_negative()
+        {
+            return None;
+        }
+        Some(reward / risk)
+    }
+
+    /// The largest quantity that could be added right now to the position of an account in a symbol.
+    ///
+    /// It is the maximum quantity allowed by the capital and the risk rules of the account, reduced by
+    /// the rules that are validated when the trade is funded against the exposure of the open trades.
+    /// Trust trades with cash, so there is no margin or leverage that could increase the quantity.
+    pub fn max_additional_quantity(
+        account_id: Uuid,
+        symbol: &str,
+        entry_price: Decimal,
+        stop_price: Decimal,
+        currency: &Currency,
+        database: &mut dyn DatabaseFactory,
+    ) -> Result<i64, Box<dyn std::error::Error>> {
+        // 1. Calculate the quantity allowed by the capital and the risk rules
+        let maximum = QuantityCalculator::maximum_quantity(
+            account_id,
+            entry_price,
+            stop_price,
+            currency,
+            database,
+        )?;
+
+        // 2. Calculate the exposure of the open trades
+        let open_trades = crate::commands::trade::read_open_trades(
+            account_id,
+            currency,
+            database.trade_read().as_mut(),
+        )?;
+        let category = if entry_price > stop_price {
+            TradeCategory::Long
+        } else {
+            TradeCategory::Short
+        };
+
+        // 3. Reduce the quantity with the rules that block the funding
+        let mut quantity = maximum;
+        for rule in database.rule_read().read_all_rules(account_id)? {
+            if rule.level != RuleLevel::Error {
+                continue; // Other levels just log a warning when the trade is funded.
+            }
+            match rule.name {
+                RuleName::OnePositionPerSymbolSide
+                    if open_trades.iter().any(|trade| {
+                        trade.trading_vehicle.symbol == symbol && trade.category == category
+                    }) =>
+                {
+                    return Ok(0);
+                }
+                RuleName::MaxPositionConcentration(limit) => {
+                    quantity = quantity.min(QuantityCalculator::max_quantity_per_concentration(
+                        &QuantityCalculator::positions(symbol, &open_trades),
+                        entry_price,
+                        Decimal::from_f32_retain(limit).unwrap(),
+                    ));
+                }
+                _ => {} // Already applied by the maximum quantity or validated when the trade is created.
+            }
+        }
+
+        Ok(quantity.max(0))
+    }
+
+    // Notional of the open position in the symbol, of the largest position in other symbols and of all of them.
+    fn positions(symbol: &str, open_trades: &[Trade]) -> (Decimal, Decimal, Decimal) {
+        let mut others: HashMap<Uuid, Decimal> = HashMap::new();
+        let mut position = dec!(0);
+        for trade in open_trades {
+            let notional = trade.entry.unit_price * trade.entry.decimal_quantity();
+            if trade.trading_vehicle.symbol == symbol {
+                position += notional;
+            } else {
+                *others.entry(trade.trading_vehicle.id).or_insert(dec!(0)) += notional;
+            }
+        }
+        let largest_other = others.values().max().copied().unwrap_or(dec!(0));
+        let total = position + others.values().sum::<Decimal>();
+        (position, largest_other, total)
+    }
+
+    // The position in the symbol after adding q units at the entry price must be at most the limit:
+    //    (position + q * entry) / (total + q * entry) <= limit
+    //    q <= (limit * total - position) / (entry * (1 - limit))
+    // The largest position in other symbols must be below the limit as well once the quantity is added.
+    fn max_quantity_per_concentration(
+        (position, largest_other, total): &(Decimal, Decimal, Decimal),
+        entry_price: Decimal,
+        limit: Decimal,
+    ) -> i64 {
+        let limit = limit / dec!(100);
+        if limit >= dec!(1) {
+            return i64::MAX;
+        }
+
+        let quantity = ((limit * total - position) / (entry_price * (dec!(1) - limit)))
+            .floor()
+            .max(dec!(0));
+        if quantity <= dec!(0) {
+            return 0;
+        }
+
+        if *largest_other > limit * (total + quantity * entry_price) {
+            return 0;
+        }
+
+        quantity.to_i64().unwrap_or(i64::MAX)
+    }
+
+    fn kelly_capped_quantity(conservative: i64, kelly: Decimal) -> i64 {
+        let scale = (kelly * KELLY_FRACTION).max(dec!(0)).min(dec!(1));
+        (Decimal::from(conservative) * scale)
+            .floor()
+            .to_i64()
+            .unwrap()
+    }
+
     fn max_quantity_per_trade(
         available: Decimal,
         entry_price: Decimal,
@@ -87,6 +440,128 @@ impl QuantityCalculator {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_reward_risk_ratio() {
+        assert_eq!(
+            QuantityCalculator::reward_risk_ratio(dec!(40), dec!(38), dec!(50)),
+            Some(dec!(5))
+        );
+        assert_eq!(
+            QuantityCalculator::reward_risk_ratio(dec!(40), dec!(42), dec!(37)),
+            Some(dec!(1.5))
+        );
+    }
+
+    #[test]
+    fn test_reward_risk_ratio_without_risk_or_reward() {
+        for (entry, stop, target) in [
+            (dec!(40), dec!(40), dec!(50)),
+            (dec!(40), dec!(38), dec!(40)),
+            (dec!(40), dec!(38), dec!(35)),
+            (dec!(40), dec!(42), dec!(45)),
+        ] {
+            assert_eq!(
+                QuantityCalculator::reward_risk_ratio(entry, stop, target),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn test_kelly_capped_quantity_profitable_system() {
+        // Half Kelly of 0.4 risks 20% of the conservative quantity
+        assert_eq!(
+            QuantityCalculator::kelly_capped_quantity(1000, dec!(0.4)),
+            200
+        );
+        assert_eq!(
+            QuantityCalculator::kelly_capped_quantity(999, dec!(0.4)),
+            199
+        );
+    }
+
+    #[test]
+    fn test_kelly_capped_quantity_is_bounded_by_conservative() {
+        for kelly in [dec!(0.1), dec!(0.5), dec!(1), dec!(2), dec!(10)] {
+            let quantity = QuantityCalculator::kelly_capped_quantity(1000, kelly);
+            assert!(quantity > 0);
+            assert!(quantity <= 1000);
+        }
+        assert_eq!(
+            QuantityCalculator::kelly_capped_quantity(1000, dec!(10)),
+            1000
+        );
+    }
+
+    #[test]
+    fn test_kelly_capped_quantity_losing_system() {
+        assert_eq!(
+            QuantityCalculator::kelly_capped_quantity(1000, dec!(-0.5)),
+            0
+        );
+        assert_eq!(QuantityCalculator::kelly_capped_quantity(1000, dec!(0)), 0);
+    }
+
+    #[test]
+    fn test_kelly_fraction() {
+        // 55% of wins that pay twice the risk
+        assert_eq!(
+            QuantityCalculator::kelly_fraction(dec!(0.55), dec!(2)),
+            dec!(0.325)
+        );
+        // A coin flip that pays the risk has no edge
+        assert_eq!(
+            QuantityCalculator::kelly_fraction(dec!(0.5), dec!(1)),
+            dec!(0)
+        );
+        // 30% of wins that pay the risk is a losing bet
+        assert_eq!(
+            QuantityCalculator::kelly_fraction(dec!(0.3), dec!(1)),
+            dec!(-0.4)
+        );
+    }
+
+    #[test]
+    fn test_kelly_risk_quantity() {
+        // A quarter of Kelly of 0.325 risks 8125 of 100000, at 2 per share
+        assert_eq!(
+            QuantityCalculator::kelly_risk_quantity(
+                dec!(100000),
+                dec!(200000),
+                dec!(0.325) * dec!(0.25),
+                dec!(40),
+                dec!(38)
+            ),
+            4062
+        );
+        // A short risks from the entry up to the stop
+        assert_eq!(
+            QuantityCalculator::kelly_risk_quantity(
+                dec!(100000),
+                dec!(200000),
+                dec!(0.08125),
+                dec!(40),
+                dec!(42)
+            ),
+            4062
+        );
+    }
+
+    #[test]
+    fn test_kelly_risk_quantity_is_bounded_by_available_capital() {
+        // Risking 8125 at 0.5 per share would need more than the 20000 that are available
+        assert_eq!(
+            QuantityCalculator::kelly_risk_quantity(
+                dec!(100000),
+                dec!(20000),
+                dec!(0.08125),
+                dec!(40),
+                dec!(39.5)
+            ),
+            500
+        );
+    }
+
     #[test]
     fn test_max_quantity_per_trade_default() {
         // Test case 1: The trade risk is within the available funds
@@ -153,4 +628,50 @@ mod tests {
             99
         );
     }
+
+    #[test]
+    fn test_max_quantity_per_concentration_without_positions() {
+        // The first position is always 100% of the capital in open trades
+        let positions = (dec!(0), dec!(0), dec!(0));
+        assert_eq!(
+            QuantityCalculator::max_quantity_per_concentration(&positions, dec!(40), dec!(40)),
+            0
+        );
+        assert_eq!(
+            QuantityCalculator::max_quantity_per_concentration(&positions, dec!(40), dec!(100)),
+            i64::MAX
+        );
+    }
+
+    #[test]
+    fn test_max_quantity_per_concentration_fresh_symbol() {
+        // Four positions of 4000 each, a new symbol can be 40% of the open trades:
+        // q * 40 <= 0.4 * (16000 + q * 40) => q <= 266
+        let positions = (dec!(0), dec!(4000), dec!(16000));
+        assert_eq!(
+            QuantityCalculator::max_quantity_per_concentration(&positions, dec!(40), dec!(40)),
+            266
+        );
+    }
+
+    #[test]
+    fn test_max_quantity_per_concentration_existing_position() {
+        // The symbol already has 4000 of the 16000 in open trades:
+        // 4000 + q * 40 <= 0.4 * (16000 + q * 40) => q <= 100
+        let positions = (dec!(4000), dec!(4000), dec!(16000));
+        assert_eq!(
+            QuantityCalculator::max_quantity_per_concentration(&positions, dec!(40), dec!(40)),
+            100
+        );
+    }
+
+    #[test]
+    fn test_max_quantity_per_concentration_other_position_above_limit() {
+        // Another symbol has 9000 of the 10000 in open trades and it would still be above the limit.
+        let positions = (dec!(1000), dec!(9000), dec!(10000));
+        assert_eq!(
+            QuantityCalculator::max_quantity_per_concentration(&positions, dec!(40), dec!(40)),
+            0
+        );
+    }
 }
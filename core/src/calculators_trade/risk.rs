@@ -1,4 +1,5 @@
-use model::{Currency, DatabaseFactory};
+use chrono::{Datelike, NaiveDateTime};
+use model::{Currency, DatabaseFactory, RuleName, Status, Trade};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use uuid::Uuid;
@@ -44,6 +45,126 @@ impl RiskCalculator {
         Ok((available_to_risk * dec!(100.0)) / total_available)
     }
 
+    /// Calculates how much money can still be risked in the current month.
+    ///
+    /// The monthly budget is the percentage of the `RiskPerMonth` rule applied to the current equity.
+    /// The money at risk in open trades and the losses of the trades closed this month are subtracted
+    /// from the budget. Returns an error if the account does not have a `RiskPerMonth` rule.
+    pub fn calculate_risk_budget_remaining(
+        account_id: Uuid,
+        currency: &Currency,
+        database: &mut dyn DatabaseFactory,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        RiskCalculator::risk_budget_remaining_without(account_id, currency, None, database)
+    }
+
+    /// Calculates how much money can still be risked in the current month by a trade.
+    /// The risk of the trade itself is not subtracted, even if it is already funded.
+    pub fn calculate_risk_budget_remaining_for_trade(
+        trade: &Trade,
+        database: &mut dyn DatabaseFactory,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        RiskCalculator::risk_budget_remaining_without(
+            trade.account_id,
+            &trade.currency,
+            Some(trade.id),
+            database,
+        )
+    }
+
+    fn risk_budget_remaining_without(
+        account_id: Uuid,
+        currency: &Currency,
+        excluded_trade_id: Option<Uuid>,
+        database: &mut dyn DatabaseFactory,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        // 1. Read the monthly risk of the account
+        let rule = database
+            .rule_read()
+            .rule_for_account(account_id, &RuleName::RiskPerMonth(0.0))
+            .map_err(|_| {
+                "risk_budget_remaining: the account does not have a risk per month rule"
+            })?;
+
+        // 2. Calculate the current equity: the capital available plus the capital in open trades
+        let open_trades = crate::commands::trade::read_open_trades(
+            account_id,
+            currency,
+            database.trade_read().as_mut(),
+        )?;
+        let total_available = AccountCapitalAvailable::calculate(
+            account_id,
+            currency,
+            database.transaction_read().as_mut(),
+        )?;
+        let equity = total_available
+            + open_trades
+                .iter()
+                .map(|trade| trade.balance.funding)
+                .sum::<Decimal>();
+
+        // 3. Read the trades closed this month
+        let mut closed_trades = Vec::new();
+        for status in [
+            Status::ClosedStopLoss,
+            Status::ClosedTarget,
+            Status::Canceled,
+        ] {
+            closed_trades.extend(
+                database
+                    .trade_read()
+                    .read_trades_with_status(account_id, status)?
+                    .into_iter()
+                    .filter(|trade| trade.currency == *currency),
+            );
+        }
+
+        // 4. The risk of the excluded trade is not committed yet
+        let open_trades: Vec<Trade> = open_trades
+            .into_iter()
+            .filter(|trade| Some(trade.id) != excluded_trade_id)
+            .collect();
+
+        Ok(RiskCalculator::remaining_risk_budget(
+            equity,
+            rule.name.risk(),
+            &open_trades,
+            &closed_trades,
+            database.clock().now(),
+        ))
+    }
+
+    fn remaining_risk_budget(
+        equity: Decimal,
+        risk: f32,
+        open_trades: &[Trade],
+        closed_trades: &[Trade],
+        now: NaiveDateTime,
+    ) -> Decimal {
+        let budget = equity * Decimal::from_f32_retain(risk).unwrap() / dec!(100.0);
+
+        // The money that would be lost if the stops of the open trades are hit.
+        let committed: Decimal = open_trades
+            .iter()
+            .map(|trade| {
+                (trade.entry.unit_price - trade.safety_stop.unit_price).abs()
+                    * trade.entry.decimal_quantity()
+            })
+            .sum();
+
+        // The money that was lost by the trades closed this month.
+        let realized: Decimal = closed_trades
+            .iter()
+            .filter(|trade| {
+                let closed_at = trade.closed_at();
+                closed_at.year() == now.year() && closed_at.month() == now.month()
+            })
+            .map(|trade| (-trade.balance.total_performance).max(dec!(0.0)))
+            .sum();
+
+        (budget - committed - realized).max(dec!(0.0))
+    }
+
     fn calculate_capital_allowed_to_risk(
         total_beginning_of_month: Decimal,
         total_balance_current_month: Decimal,
@@ -81,8 +202,88 @@ impl RiskCalculator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::{Duration, Utc};
+    use model::{Order, TradeBalance};
     use rust_decimal::Decimal;
 
+    fn trade(entry: Decimal, stop: Decimal, quantity: u64) -> Trade {
+        Trade {
+            entry: Order {
+                unit_price: entry,
+                quantity,
+                ..Default::default()
+            },
+            safety_stop: Order {
+                unit_price: stop,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn closed_trade(performance: Decimal, closed_at: NaiveDateTime) -> Trade {
+        Trade {
+            safety_stop: Order {
+                closed_at: Some(closed_at),
+                ..Default::default()
+            },
+            balance: TradeBalance {
+                total_performance: performance,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_remaining_risk_budget_without_trades() {
+        let now = Utc::now().naive_utc();
+        let result = RiskCalculator::remaining_risk_budget(dec!(50000), 6.0, &[], &[], now);
+        assert_eq!(result, dec!(3000));
+    }
+
+    #[test]
+    fn test_remaining_risk_budget_with_open_and_closed_losing_trade() {
+        let now = Utc::now().naive_utc();
+        let open_trades = vec![trade(dec!(40), dec!(38), 500)]; // 1000 at risk
+        let closed_trades = vec![closed_trade(dec!(-800), now)]; // 800 lost this month
+
+        let result = RiskCalculator::remaining_risk_budget(
+            dec!(50000),
+            6.0,
+            &open_trades,
+            &closed_trades,
+            now,
+        );
+
+        assert_eq!(result, dec!(1200)); // 3000 - 1000 - 800
+    }
+
+    #[test]
+    fn test_remaining_risk_budget_ignores_winners_and_previous_months() {
+        let now = Utc::now().naive_utc();
+        let closed_trades = vec![
+            closed_trade(dec!(500), now),
+            closed_trade(dec!(-800), now - Duration::days(62)),
+        ];
+
+        let result =
+            RiskCalculator::remaining_risk_budget(dec!(50000), 6.0, &[], &closed_trades, now);
+
+        assert_eq!(result, dec!(3000));
+    }
+
+    #[test]
+    fn test_remaining_risk_budget_is_floored_at_zero() {
+        let now = Utc::now().naive_utc();
+        let open_trades = vec![trade(dec!(40), dec!(30), 500)]; // 5000 at risk
+
+        let result =
+            RiskCalculator::remaining_risk_budget(dec!(50000), 6.0, &open_trades, &[], now);
+
+        assert_eq!(result, dec!(0));
+    }
+
     #[test]
     fn test_calculate_capital_allowed_to_risk_is_0() {
         let total_beginning_of_month = Decimal::new(0, 0);
@@ -0,0 +1,224 @@
+use model::{Execution, ExecutionSide, LotMethod, RealizedLot, Trade, TradeCategory};
+use rust_decimal::Decimal;
+use std::collections::VecDeque;
+
+pub struct TaxLotCalculator;
+
+impl TaxLotCalculator {
+    /// Matches the executions that close the position of a trade against the executions that opened it.
+    ///
+    /// Buys open long trades and sells open short trades. Every closing execution takes its quantity
+    /// from the oldest open lots with FIFO or from the newest ones with LIFO. An open lot that is closed
+    /// partially is split, and what is left stays open for the next closing execution.
+    /// The quantity that is closed without an open lot is not matched.
+    pub fn calculate(
+        trade: &Trade,
+        executions: &[Execution],
+        method: LotMethod,
+    ) -> Vec<RealizedLot> {
+        let opening_side = match trade.category {
+            TradeCategory::Long => ExecutionSide::Buy,
+            TradeCategory::Short => ExecutionSide::Sell,
+        };
+
+        let mut executions: Vec<&Execution> = executions.iter().collect();
+        executions.sort_by_key(|execution| execution.executed_at);
+
+        let mut open_lots: VecDeque<(&Execution, u64)> = VecDeque::new();
+        let mut lots = Vec::new();
+        for execution in executions {
+            if execution.side == opening_side {
+                open_lots.push_back((execution, execution.quantity));
+                continue;
+            }
+
+            let mut to_close = execution.quantity;
+            while to_close > 0 {
+                let open_lot = match method {
+                    LotMethod::Fifo => open_lots.front_mut(),
+                    LotMethod::Lifo => open_lots.back_mut(),
+                };
+                let (open, remaining) = match open_lot {
+                    Some(lot) => lot,
+                    None => break, // Nothing is left open to match.
+                };
+
+                let quantity = to_close.min(*remaining);
+                *remaining -= quantity;
+                to_close -= quantity;
+                lots.push(Self::lot(trade, open, execution, quantity));
+
+                if *remaining == 0 {
+                    match method {
+                        LotMethod::Fifo => open_lots.pop_front(),
+                        LotMethod::Lifo => open_lots.pop_back(),
+                    };
+                }
+            }
+        }
+        lots
+    }
+
+    fn lot(trade: &Trade, open: &Execution, close: &Execution, quantity: u64) -> RealizedLot {
+        let opening = open.price * Decimal::from(quantity);
+        let closing = close.price * Decimal::from(quantity);
+
+        // A short trade is sold first and it is bought back when it is closed.
+        let (cost_basis, proceeds) = match trade.category {
+            TradeCategory::Long => (opening, closing),
+            TradeCategory::Short => (closing, opening),
+        };
+
+        RealizedLot {
+            account_id: trade.account_id,
+            trade_id: trade.id,
+            open_execution_id: open.id,
+            close_execution_id: close.id,
+            opened_at: open.executed_at,
+            closed_at: close.executed_at,
+            quantity,
+            cost_basis,
+            proceeds,
+            gain: proceeds - cost_basis,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, NaiveDateTime};
+    use rust_decimal_macros::dec;
+
+    fn day(day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2023, 8, day)
+            .unwrap()
+            .and_hms_opt(15, 0, 0)
+            .unwrap()
+    }
+
+    fn execution(
+        side: ExecutionSide,
+        price: Decimal,
+        quantity: u64,
+        executed_at: u32,
+    ) -> Execution {
+        Execution {
+            side,
+            price,
+            quantity,
+            executed_at: day(executed_at),
+            ..Default::default()
+        }
+    }
+
+    fn trade(category: TradeCategory) -> Trade {
+        Trade {
+            category,
+            ..Default::default()
+        }
+    }
+
+    /// Opened with 100 shares at 40 and 50 shares at 43, and 120 shares closed in one sale at 45.
+    fn two_lots_one_sale() -> Vec<Execution> {
+        vec![
+            execution(ExecutionSide::Sell, dec!(45), 120, 10),
+            execution(ExecutionSide::Buy, dec!(40), 100, 1),
+            execution(ExecutionSide::Buy, dec!(43), 50, 2),
+        ]
+    }
+
+    #[test]
+    fn test_fifo_closes_the_oldest_lot_first() {
+        let executions = two_lots_one_sale();
+
+        let lots =
+            TaxLotCalculator::calculate(&trade(TradeCategory::Long), &executions, LotMethod::Fifo);
+
+        assert_eq!(lots.len(), 2);
+        assert_eq!(lots[0].open_execution_id, executions[1].id);
+        assert_eq!(lots[0].close_execution_id, executions[0].id);
+        assert_eq!(lots[0].quantity, 100);
+        assert_eq!(lots[0].cost_basis, dec!(4000));
+        assert_eq!(lots[0].proceeds, dec!(4500));
+        assert_eq!(lots[0].gain, dec!(500));
+        assert_eq!(lots[0].opened_at, day(1));
+        assert_eq!(lots[0].closed_at, day(10));
+
+        // The second lot is split and 30 shares stay open.
+        assert_eq!(lots[1].open_execution_id, executions[2].id);
+        assert_eq!(lots[1].quantity, 20);
+        assert_eq!(lots[1].cost_basis, dec!(860));
+        assert_eq!(lots[1].proceeds, dec!(900));
+        assert_eq!(lots[1].gain, dec!(40));
+    }
+
+    #[test]
+    fn test_lifo_closes_the_newest_lot_first() {
+        let executions = two_lots_one_sale();
+
+        let lots =
+            TaxLotCalculator::calculate(&trade(TradeCategory::Long), &executions, LotMethod::Lifo);
+
+        assert_eq!(lots.len(), 2);
+        assert_eq!(lots[0].open_execution_id, executions[2].id);
+        assert_eq!(lots[0].quantity, 50);
+        assert_eq!(lots[0].cost_basis, dec!(2150));
+        assert_eq!(lots[0].gain, dec!(100));
+
+        // The first lot is split and 30 shares stay open.
+        assert_eq!(lots[1].open_execution_id, executions[1].id);
+        assert_eq!(lots[1].quantity, 70);
+        assert_eq!(lots[1].cost_basis, dec!(2800));
+        assert_eq!(lots[1].proceeds, dec!(3150));
+        assert_eq!(lots[1].gain, dec!(350));
+    }
+
+    #[test]
+    fn test_partial_closes_split_the_open_lot() {
+        let executions = vec![
+            execution(ExecutionSide::Buy, dec!(40), 100, 1),
+            execution(ExecutionSide::Sell, dec!(45), 30, 2),
+            execution(ExecutionSide::Sell, dec!(35), 70, 3),
+        ];
+
+        let lots =
+            TaxLotCalculator::calculate(&trade(TradeCategory::Long), &executions, LotMethod::Fifo);
+
+        assert_eq!(lots.len(), 2);
+        assert!(lots
+            .iter()
+            .all(|lot| lot.open_execution_id == executions[0].id));
+        assert_eq!(lots[0].quantity, 30);
+        assert_eq!(lots[0].gain, dec!(150));
+        assert_eq!(lots[1].quantity, 70);
+        assert_eq!(lots[1].gain, dec!(-350));
+    }
+
+    #[test]
+    fn test_short_trade_proceeds_come_from_the_opening_sale() {
+        let executions = vec![
+            execution(ExecutionSide::Sell, dec!(50), 100, 1),
+            execution(ExecutionSide::Buy, dec!(45), 100, 2),
+        ];
+
+        let lots =
+            TaxLotCalculator::calculate(&trade(TradeCategory::Short), &executions, LotMethod::Fifo);
+
+        assert_eq!(lots.len(), 1);
+        assert_eq!(lots[0].cost_basis, dec!(4500));
+        assert_eq!(lots[0].proceeds, dec!(5000));
+        assert_eq!(lots[0].gain, dec!(500));
+    }
+
+    #[test]
+    fn test_open_position_without_closes() {
+        let executions = vec![execution(ExecutionSide::Buy, dec!(40), 100, 1)];
+
+        let lots =
+            TaxLotCalculator::calculate(&trade(TradeCategory::Long), &executions, LotMethod::Fifo);
+
+        assert!(lots.is_empty());
+    }
+}
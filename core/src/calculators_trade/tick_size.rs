@@ -0,0 +1,63 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
+
+pub struct TickSizeCalculator;
+
+impl TickSizeCalculator {
+    /// Returns true if the price is a multiple of the tick size.
+    /// A tick size that is zero or negative does not restrict any price.
+    pub fn is_on_grid(price: Decimal, tick_size: Decimal) -> bool {
+        if tick_size <= dec!(0) {
+            return true;
+        }
+        (price % tick_size).is_zero()
+    }
+
+    /// Rounds the price to the nearest multiple of the tick size.
+    /// Prices in the middle of two ticks are rounded away from zero.
+    pub fn round(price: Decimal, tick_size: Decimal) -> Decimal {
+        if tick_size <= dec!(0) {
+            return price;
+        }
+        (price / tick_size).round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+            * tick_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_on_grid() {
+        assert!(TickSizeCalculator::is_on_grid(dec!(40.05), dec!(0.05)));
+        assert!(TickSizeCalculator::is_on_grid(dec!(40), dec!(0.05)));
+        assert!(!TickSizeCalculator::is_on_grid(dec!(40.03), dec!(0.05)));
+        assert!(!TickSizeCalculator::is_on_grid(dec!(40.051), dec!(0.05)));
+    }
+
+    #[test]
+    fn test_is_on_grid_without_tick_size() {
+        assert!(TickSizeCalculator::is_on_grid(dec!(40.03), dec!(0)));
+    }
+
+    #[test]
+    fn test_round() {
+        assert_eq!(
+            TickSizeCalculator::round(dec!(40.03), dec!(0.05)),
+            dec!(40.05)
+        );
+        assert_eq!(
+            TickSizeCalculator::round(dec!(40.02), dec!(0.05)),
+            dec!(40.00)
+        );
+        assert_eq!(
+            TickSizeCalculator::round(dec!(40.025), dec!(0.05)),
+            dec!(40.05)
+        );
+        assert_eq!(
+            TickSizeCalculator::round(dec!(40.05), dec!(0.05)),
+            dec!(40.05)
+        );
+    }
+}
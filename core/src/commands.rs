@@ -1,5 +1,20 @@
+pub mod account;
+pub mod activity;
+pub mod advisory;
 pub mod balance;
+pub mod distribution;
+pub mod equity_snapshot;
+pub mod execution;
+pub mod export;
+pub mod grade;
+pub mod import;
+pub mod level;
 pub mod order;
+pub mod reconciliation;
 pub mod rule;
+pub mod statement;
+pub mod strategy;
+pub mod tax_lot;
+pub mod timeline;
 pub mod trade;
 pub mod transaction;
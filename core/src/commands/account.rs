@@ -0,0 +1,441 @@
+use crate::commands;
+use crate::validators;
+use model::{
+    Account, AccountBalance, AccountType, Currency, DatabaseFactory, Environment, Level, Rule,
+    Status, Trade, TradeCategory,
+};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::error::Error;
+use uuid::Uuid;
+
+/// Differences between the configuration of two accounts.
+#[derive(Debug, PartialEq)]
+pub struct ConfigDiff {
+    pub differences: Vec<ConfigDifference>,
+}
+
+impl ConfigDiff {
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+/// A setting that is configured differently in two accounts.
+/// `None` means that the setting is not configured in that account.
+#[derive(Debug, PartialEq)]
+pub struct ConfigDifference {
+    pub setting: String,
+    pub a: Option<String>,
+    pub b: Option<String>,
+}
+
+/// What was moved when an account was merged into another account.
+#[derive(Debug, PartialEq)]
+pub struct MergeReport {
+    /// The trades that were moved to the target account.
+    pub trades: Vec<Uuid>,
+
+    /// The number of transactions that were moved to the target account.
+    pub transactions: usize,
+
+    /// The number of rules that were moved. Rules that the target already has are not moved.
+    pub rules: usize,
+
+    /// The balances of the target account after the merge.
+    pub balances: Vec<AccountBalance>,
+}
+
+/// Creates a live account linked to a paper account. The paper account must
+/// have closed the trades that it requires before trading with real money.
+pub fn create_live(
+    name: &str,
+    description: &str,
+    taxes_percentage: Decimal,
+    earnings_percentage: Decimal,
+    paper_account: &Account,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Account, Box<dyn Error>> {
+    // 1. Count the closed trades of the paper account
+    let mut closed_trades = 0;
+    for status in [Status::ClosedTarget, Status::ClosedStopLoss] {
+        closed_trades += database
+            .trade_read()
+            .read_trades_with_status(paper_account.id, status)?
+            .len();
+    }
+
+    // 2. Validate that the paper account has enough closed trades
+    validators::account::can_go_live(paper_account, closed_trades)?;
+
+    // 3. Create the live account
+    database.account_write().create_live(
+        name,
+        description,
+        taxes_percentage,
+        earnings_percentage,
+        paper_account,
+    )
+}
+
+/// Promotes a paper account to a live account with the same taxes and earnings.
+pub fn promote(
+    paper_account: &Account,
+    name: &str,
+    description: &str,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Account, Box<dyn Error>> {
+    create_live(
+        name,
+        description,
+        paper_account.taxes_percentage,
+        paper_account.earnings_percentage,
+        paper_account,
+        database,
+    )
+}
+
+/// Creates an account linked to a parent account. Hedge accounts must have a parent.
+#[allow(clippy::too_many_arguments)]
+pub fn create_with_hierarchy(
+    name: &str,
+    description: &str,
+    environment: Environment,
+    taxes_percentage: Decimal,
+    earnings_percentage: Decimal,
+    account_type: AccountType,
+    parent_account_id: Option<Uuid>,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Account, Box<dyn Error>> {
+    // 1. Read the parent account and its ancestors
+    let parent = match parent_account_id {
+        Some(id) => database.account_read().id(id).ok(),
+        None => None,
+    };
+    let ancestors = match &parent {
+        Some(parent) => ancestors(parent, database)?,
+        None => vec![],
+    };
+
+    // 2. Validate the hierarchy
+    validators::account::can_link_to_parent(
+        account_type,
+        parent_account_id,
+        parent.as_ref(),
+        &ancestors,
+    )?;
+
+    // 3. Create the account
+    database.account_write().create_with_hierarchy(
+        name,
+        description,
+        environment,
+        taxes_percentage,
+        earnings_percentage,
+        account_type,
+        parent.map(|parent| parent.id),
+    )
+}
+
+/// Reads the parents of an account up to the root account.
+/// It stops at the first repeated account, so a cycle ends with the account that closes it.
+fn ancestors(
+    account: &Account,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Vec<Account>, Box<dyn Error>> {
+    let mut ancestors: Vec<Account> = Vec::new();
+    let mut current = database.account_read().parent_of(account.id)?;
+    while let Some(ancestor) = current {
+        let repeated = ancestor.id == account.id || ancestors.iter().any(|a| a.id == ancestor.id);
+        current = if repeated {
+            None
+        } else {
+            database.account_read().parent_of(ancestor.id)?
+        };
+        ancestors.push(ancestor);
+    }
+    Ok(ancestors)
+}
+
+/// Nets the filled quantities of the open trades of a symbol across a parent account
+/// and its hedge accounts. Longs are positive and shorts are negative.
+pub fn net_exposure(
+    parent_account_id: Uuid,
+    symbol: &str,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Decimal, Box<dyn Error>> {
+    // 1. Read the parent account and its hedge accounts
+    let parent = database.account_read().id(parent_account_id)?;
+    let mut account_ids = vec![parent.id];
+    account_ids.extend(
+        database
+            .account_read()
+            .children_of(parent.id)?
+            .into_iter()
+            .filter(|account| account.account_type == AccountType::Hedge)
+            .map(|account| account.id),
+    );
+
+    // 2. Net the filled quantities of the open trades in the symbol
+    let mut exposure = Decimal::ZERO;
+    for account_id in account_ids {
+        for status in [
+            Status::PartiallyFilled,
+            Status::Filled,
+            Status::ClosingPending,
+        ] {
+            for trade in database
+                .trade_read()
+                .read_trades_with_status(account_id, status)?
+                .iter()
+                .filter(|trade| trade.trading_vehicle.symbol.eq_ignore_ascii_case(symbol))
+            {
+                match trade.category {
+                    TradeCategory::Long => exposure += filled_quantity(trade),
+                    TradeCategory::Short => exposure -= filled_quantity(trade),
+                }
+            }
+        }
+    }
+
+    Ok(exposure)
+}
+
+/// The quantity of the entry of an open trade that is filled.
+pub fn filled_quantity(trade: &Trade) -> Decimal {
+    match trade.status {
+        Status::Filled => trade.entry.decimal_quantity(),
+        _ => trade.entry.decimal_filled_quantity(),
+    }
+}
+
+/// Merges an account into another account of the same environment.
+///
+/// The transactions, trades and rules of the source are moved to the target and the balances
+/// of the target are calculated again, so the balances of the same currency are summed.
+/// The rules of the source that the target already has are made inactive. The source is deleted.
+/// Nothing is merged while any of the accounts has money in open trades.
+pub fn merge(
+    source_id: Uuid,
+    target_id: Uuid,
+    database: &mut dyn DatabaseFactory,
+) -> Result<MergeReport, Box<dyn Error>> {
+    // 1. Read both accounts with their trades and balances
+    let source = database.account_read().id(source_id)?;
+    let target = database.account_read().id(target_id)?;
+    let source_trades = all_trades(source.id, database)?;
+    let target_trades = all_trades(target.id, database)?;
+    let source_balances = database.account_balance_read().for_account(source.id)?;
+    let target_balances = database.account_balance_read().for_account(target.id)?;
+    let balances: Vec<AccountBalance> = source_balances
+        .iter()
+        .chain(target_balances.iter())
+        .cloned()
+        .collect();
+
+    // 2. Validate that the accounts can be merged
+    validators::account::can_merge(&source, &target, &source_trades, &target_trades, &balances)?;
+
+    // 3. Read what is moved
+    let mut transactions = 0;
+    for balance in &source_balances {
+        transactions += database
+            .transaction_read()
+            .all_transactions(source.id, &balance.currency)?
+            .len();
+    }
+    let target_rules = database.rule_read().read_all_rules(target.id)?;
+    let (duplicated_rules, rules): (Vec<_>, Vec<_>) = database
+        .rule_read()
+        .read_all_rules(source.id)?
+        .into_iter()
+        .partition(|rule| {
+            target_rules
+                .iter()
+                .any(|target_rule| target_rule.name.to_string() == rule.name.to_string())
+        });
+
+    // 4. Move everything atomically and calculate the balances of the target again
+    database.begin_transaction()?;
+    let result = move_account(
+        &source,
+        &target,
+        &source_balances,
+        &target_balances,
+        &duplicated_rules,
+        database,
+    );
+    match result {
+        Ok(balances) => {
+            database.commit_transaction()?;
+            Ok(MergeReport {
+                trades: source_trades.iter().map(|trade| trade.id).collect(),
+                transactions,
+                rules: rules.len(),
+                balances,
+            })
+        }
+        Err(error) => {
+            database.rollback_transaction()?;
+            Err(error)
+        }
+    }
+}
+
+fn move_account(
+    source: &Account,
+    target: &Account,
+    source_balances: &[AccountBalance],
+    target_balances: &[AccountBalance],
+    duplicated_rules: &[Rule],
+    database: &mut dyn DatabaseFactory,
+) -> Result<Vec<AccountBalance>, Box<dyn Error>> {
+    for rule in duplicated_rules {
+        database.rule_write().make_rule_inactive(rule)?;
+    }
+    database.account_write().merge_into(source, target)?;
+
+    let mut currencies: Vec<Currency> = target_balances
+        .iter()
+        .map(|balance| balance.currency)
+        .collect();
+    for balance in source_balances {
+        if !currencies.contains(&balance.currency) {
+            database
+                .account_balance_write()
+                .create(target, &balance.currency)?;
+            currencies.push(balance.currency);
+        }
+    }
+
+    currencies
+        .iter()
+        .map(|currency| commands::balance::calculate_account(database, target, currency))
+        .collect()
+}
+
+/// The trades of an account in every status.
+pub fn all_trades(
+    account_id: Uuid,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Vec<Trade>, Box<dyn Error>> {
+    let mut trades = Vec::new();
+    for status in Status::all() {
+        trades.append(
+            &mut database
+                .trade_read()
+                .read_trades_with_status(account_id, status)?,
+        );
+    }
+    Ok(trades)
+}
+
+/// Compares the rules, distribution rules, thresholds and level of two accounts.
+pub fn diff_config(
+    a: Uuid,
+    b: Uuid,
+    database: &mut dyn DatabaseFactory,
+) -> Result<ConfigDiff, Box<dyn Error>> {
+    // 1. Read the settings of both accounts
+    let settings_a = settings(a, database)?;
+    let settings_b = settings(b, database)?;
+
+    // 2. Keep the settings that are different or only configured in one account
+    let mut names: Vec<&String> = settings_a.keys().chain(settings_b.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    let differences = names
+        .into_iter()
+        .filter(|name| settings_a.get(*name) != settings_b.get(*name))
+        .map(|name| ConfigDifference {
+            setting: name.clone(),
+            a: settings_a.get(name).cloned(),
+            b: settings_b.get(name).cloned(),
+        })
+        .collect();
+
+    Ok(ConfigDiff { differences })
+}
+
+fn settings(
+    account_id: Uuid,
+    database: &mut dyn DatabaseFactory,
+) -> Result<BTreeMap<String, String>, Box<dyn Error>> {
+    let account = database.account_read().id(account_id)?;
+    let mut settings = BTreeMap::new();
+
+    // Rules
+    for rule in database.rule_read().read_all_rules(account.id)? {
+        let mut value = format!("{} {}", rule.name.risk(), rule.level);
+        if !rule.active {
+            value.push_str(" inactive");
+        }
+        settings.insert(format!("rule.{}", rule.name), value);
+    }
+
+    // Distribution rules. Accounts without rules reinvest all the profit.
+    let distribution = database
+        .distribution_read()
+        .distribution_rules(account.id)
+        .unwrap_or_default();
+    settings.insert(
+        "distribution.earnings_percent".to_string(),
+        distribution.earnings_percent.to_string(),
+    );
+    settings.insert(
+        "distribution.tax_percent".to_string(),
+        distribution.tax_percent.to_string(),
+    );
+    settings.insert(
+        "distribution.reinvestment_percent".to_string(),
+        distribution.reinvestment_percent.to_string(),
+    );
+
+    // Thresholds
+    if let Some(notional) = account.confirm_above_notional {
+        settings.insert(
+            "threshold.confirm_above_notional".to_string(),
+            notional.to_string(),
+        );
+    }
+    if let Some(min_trades) = account.min_paper_trades_before_live {
+        settings.insert(
+            "threshold.min_paper_trades_before_live".to_string(),
+            min_trades.to_string(),
+        );
+    }
+    settings.insert(
+        "threshold.tick_size_policy".to_string(),
+        account.tick_size_policy.to_string(),
+    );
+    settings.insert(
+        "threshold.lot_method".to_string(),
+        account.lot_method.to_string(),
+    );
+    settings.insert(
+        "threshold.fee_model".to_string(),
+        match account.fee_model.amount() {
+            Some(amount) => format!("{} {}", account.fee_model, amount),
+            None => account.fee_model.to_string(),
+        },
+    );
+    let adjustment = database
+        .level_read()
+        .level_adjustment_rules(account.id)
+        .unwrap_or_default();
+    settings.insert(
+        "threshold.monthly_loss_downgrade_pct".to_string(),
+        adjustment.monthly_loss_downgrade_pct.to_string(),
+    );
+
+    // Level. Accounts that never had a level start with Level::DEFAULT.
+    let level = database
+        .level_read()
+        .level_for_account(account.id)
+        .map(|level| level.current_level)
+        .unwrap_or(Level::DEFAULT);
+    settings.insert("level".to_string(), level.to_string());
+
+    Ok(settings)
+}
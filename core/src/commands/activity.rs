@@ -0,0 +1,122 @@
+use crate::commands;
+use chrono::NaiveDateTime;
+use model::{DatabaseFactory, Status, Trade, TransactionCategory};
+use std::error::Error;
+use uuid::Uuid;
+
+/// Something that happened in an account, as shown in its activity feed.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ActivityItem {
+    pub timestamp: NaiveDateTime,
+    pub kind: ActivityKind,
+
+    /// The trade the activity belongs to, if any.
+    pub trade_id: Option<Uuid>,
+
+    /// A sentence that describes the activity to a human.
+    pub summary: String,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ActivityKind {
+    /// Money that moved in the account.
+    Transaction(TransactionCategory),
+
+    /// A trade that reached a status. The funding is the transaction that moves the money to the trade.
+    TradeStatus(Status),
+
+    /// A response of the broker for a trade.
+    BrokerLog,
+}
+
+/// The activity of an account from the newest to the oldest.
+///
+/// Only the activity strictly before `before` is returned, so the timestamp of the last item
+/// of a page is the cursor of the next one.
+pub fn account_activity_feed(
+    account_id: Uuid,
+    limit: usize,
+    before: Option<NaiveDateTime>,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Vec<ActivityItem>, Box<dyn Error>> {
+    // 1. Transactions of the account in every currency
+    let mut items: Vec<ActivityItem> = database
+        .transaction_read()
+        .all_account_transactions(account_id, None)?
+        .into_iter()
+        .map(|transaction| ActivityItem {
+            timestamp: transaction.created_at,
+            summary: match transaction.category.reason() {
+                Some(reason) => format!(
+                    "{} of {} {}: {}",
+                    transaction.category, transaction.amount, transaction.currency, reason
+                ),
+                None => format!(
+                    "{} of {} {}",
+                    transaction.category, transaction.amount, transaction.currency
+                ),
+            },
+            trade_id: transaction.category.trade_id(),
+            kind: ActivityKind::Transaction(transaction.category),
+        })
+        .collect();
+
+    // 2. Status changes and broker logs of the trades
+    for trade in commands::account::all_trades(account_id, database)? {
+        items.extend(status_changes(&trade));
+        items.extend(
+            database
+                .log_read()
+                .read_all_logs_for_trade(trade.id)?
+                .into_iter()
+                .map(|log| ActivityItem {
+                    timestamp: log.created_at,
+                    kind: ActivityKind::BrokerLog,
+                    trade_id: Some(trade.id),
+                    summary: format!(
+                        "Broker response for the {} trade of {}",
+                        trade.category, trade.trading_vehicle.symbol
+                    ),
+                }),
+        );
+    }
+
+    // 3. Newest first, and the page before the cursor
+    items.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    Ok(items
+        .into_iter()
+        .filter(|item| before.is_none_or(|before| item.timestamp < before))
+        .take(limit)
+        .collect())
+}
+
+fn status_changes(trade: &Trade) -> Vec<ActivityItem> {
+    let mut changes = vec![(trade.created_at, Status::New)];
+    if let Some(submitted_at) = trade.entry.submitted_at {
+        changes.push((submitted_at, Status::Submitted));
+    }
+    if let Some(filled_at) = trade.entry.filled_at {
+        changes.push((filled_at, Status::Filled));
+    }
+    if trade.status.is_terminal() {
+        changes.push((trade.closed_at(), trade.status));
+    }
+
+    changes
+        .into_iter()
+        .map(|(timestamp, status)| ActivityItem {
+            timestamp,
+            kind: ActivityKind::TradeStatus(status),
+            trade_id: Some(trade.id),
+            summary: format!(
+                "The {} trade of {} {}",
+                trade.category,
+                trade.trading_vehicle.symbol,
+                match status {
+                    Status::New => "was created".to_string(),
+                    status => format!("is {}", status),
+                }
+            ),
+        })
+        .collect()
+}
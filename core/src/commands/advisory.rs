@@ -0,0 +1,124 @@
+use crate::calculators_trade::ConcentrationCalculator;
+use crate::validators::{advisory, funding};
+use model::{AdvisoryThresholds, DatabaseFactory, Trade};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::error::Error;
+use uuid::Uuid;
+
+/// A threshold of the account that a trade is above.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Advisory {
+    pub kind: AdvisoryKind,
+
+    /// The percentage of the trade, in the same unit as the threshold.
+    pub value: Decimal,
+    pub threshold: Decimal,
+    pub message: String,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AdvisoryKind {
+    RiskPerTrade,
+    PositionConcentration,
+}
+
+/// Creates or updates the advisory thresholds of an account.
+/// The thresholds are percentages and they must be between 0 and 100.
+pub fn configure(
+    account_id: Uuid,
+    max_risk_per_trade_percent: Decimal,
+    max_position_concentration_percent: Decimal,
+    database: &mut dyn DatabaseFactory,
+) -> Result<AdvisoryThresholds, Box<dyn Error>> {
+    // 1. Validate the thresholds
+    advisory::can_configure(
+        max_risk_per_trade_percent,
+        max_position_concentration_percent,
+    )?;
+
+    // 2. Update the thresholds of the account or create them if they don't exist
+    let account = database.account_read().id(account_id)?;
+    match database.advisory_read().advisory_thresholds(account.id)? {
+        Some(thresholds) => database.advisory_write().update_advisory_thresholds(
+            &thresholds,
+            max_risk_per_trade_percent,
+            max_position_concentration_percent,
+        ),
+        None => database.advisory_write().create_advisory_thresholds(
+            account.id,
+            max_risk_per_trade_percent,
+            max_position_concentration_percent,
+        ),
+    }
+}
+
+/// The advisory thresholds of an account, or the default ones if it never configured them.
+pub fn thresholds(
+    account_id: Uuid,
+    database: &mut dyn DatabaseFactory,
+) -> Result<AdvisoryThresholds, Box<dyn Error>> {
+    Ok(database
+        .advisory_read()
+        .advisory_thresholds(account_id)?
+        .unwrap_or_else(|| AdvisoryThresholds::defaults(account_id)))
+}
+
+/// Checks a trade against the advisory thresholds stored for its account.
+/// The thresholds are read on every check, so a change applies to the next check.
+///
+/// The risk of the trade is a percentage of the capital available in its currency and the
+/// concentration is the share of the capital in open trades of the largest position once the
+/// trade is opened.
+pub fn check_trade(
+    trade: &Trade,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Vec<Advisory>, Box<dyn Error>> {
+    // 1. Read the thresholds of the account
+    let thresholds = thresholds(trade.account_id, database)?;
+    let mut advisories = vec![];
+
+    // 2. Compare the risk of the trade with the available capital
+    let balance = database
+        .account_balance_read()
+        .for_currency(trade.account_id, &trade.currency)?;
+    let risk = funding::capital_at_risk(trade);
+    let risk_percent = match balance.total_available > dec!(0) {
+        true => risk / balance.total_available * dec!(100),
+        false => dec!(100),
+    };
+    if risk_percent > thresholds.max_risk_per_trade_percent {
+        advisories.push(Advisory {
+            kind: AdvisoryKind::RiskPerTrade,
+            value: risk_percent,
+            threshold: thresholds.max_risk_per_trade_percent,
+            message: format!(
+                "The trade risks {}% of the available capital and the threshold is {}%",
+                risk_percent.round_dp(2),
+                thresholds.max_risk_per_trade_percent
+            ),
+        });
+    }
+
+    // 3. Compare the largest position with the capital in open trades
+    let open_trades = crate::commands::trade::read_open_trades(
+        trade.account_id,
+        &trade.currency,
+        database.trade_read().as_mut(),
+    )?;
+    let concentration = ConcentrationCalculator::max_concentration(trade, &open_trades);
+    if concentration > thresholds.max_position_concentration_percent {
+        advisories.push(Advisory {
+            kind: AdvisoryKind::PositionConcentration,
+            value: concentration,
+            threshold: thresholds.max_position_concentration_percent,
+            message: format!(
+                "The largest position would be {}% of the capital in open trades and the threshold is {}%",
+                concentration.round_dp(2),
+                thresholds.max_position_concentration_percent
+            ),
+        });
+    }
+
+    Ok(advisories)
+}
@@ -1,7 +1,9 @@
 use model::{Account, AccountBalance, Currency, DatabaseFactory, Trade, TradeBalance};
+use rust_decimal::Decimal;
 use std::error::Error;
 
 use crate::{
+    analytics::calculate_open_positions,
     calculators_account::{
         AccountCapitalAvailable, AccountCapitalBalance, AccountCapitalInApprovedTrades,
         AccountCapitalTaxable,
@@ -49,6 +51,26 @@ pub fn calculate_account(
     )
 }
 
+/// The equity of an account in a currency: its balance plus the open positions at their entry price.
+pub fn equity(
+    database: &mut dyn DatabaseFactory,
+    account: &Account,
+    currency: &Currency,
+) -> Result<(AccountBalance, Decimal), Box<dyn Error>> {
+    let balance = calculate_account(database, account, currency)?;
+    let trades: Vec<Trade> =
+        crate::commands::trade::read_trades_in_market(account.id, database.trade_read().as_mut())?
+            .into_iter()
+            .filter(|trade| trade.currency == *currency)
+            .collect();
+    let positions: Decimal = calculate_open_positions(&trades)
+        .iter()
+        .map(|position| position.entry_price * position.quantity)
+        .sum();
+    let equity = balance.total_balance + positions;
+    Ok((balance, equity))
+}
+
 pub fn calculate_trade(
     database: &mut dyn DatabaseFactory,
     trade: &Trade,
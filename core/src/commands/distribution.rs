@@ -0,0 +1,155 @@
+use crate::calculators_trade::TradePerformance;
+use crate::commands::balance;
+use crate::validators::distribution;
+use model::{
+    Account, DatabaseFactory, DistributionHistory, DistributionRules, Trade, TransactionCategory,
+};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::error::Error;
+use uuid::Uuid;
+
+/// Creates or updates how the profits of the trades of an account are distributed.
+///
+/// The percentages are accepted if they sum 1 within the tolerance. They are stored normalized
+/// to sum exactly 1: the difference is added to the largest percentage, or to the first of them
+/// if several are the largest.
+pub fn configure(
+    account_id: Uuid,
+    earnings_percent: Decimal,
+    tax_percent: Decimal,
+    reinvestment_percent: Decimal,
+    tolerance: Decimal,
+    database: &mut dyn DatabaseFactory,
+) -> Result<DistributionRules, Box<dyn Error>> {
+    // 1. Validate the percentages
+    distribution::can_configure(
+        earnings_percent,
+        tax_percent,
+        reinvestment_percent,
+        tolerance,
+    )?;
+
+    // 2. Normalize the percentages to sum exactly 1
+    let [earnings_percent, tax_percent, reinvestment_percent] =
+        normalize([earnings_percent, tax_percent, reinvestment_percent]);
+
+    // 3. Update the rules of the account or create them if they don't exist
+    let account = database.account_read().id(account_id)?;
+    match database.distribution_read().distribution_rules(account.id) {
+        Ok(rules) => database.distribution_write().update_distribution_rules(
+            &rules,
+            earnings_percent,
+            tax_percent,
+            reinvestment_percent,
+        ),
+        Err(_) => database.distribution_write().create_distribution_rules(
+            account.id,
+            earnings_percent,
+            tax_percent,
+            reinvestment_percent,
+        ),
+    }
+}
+
+fn normalize(mut percents: [Decimal; 3]) -> [Decimal; 3] {
+    let remainder = dec!(1) - percents.iter().sum::<Decimal>();
+    let mut largest = 0;
+    for (index, percent) in percents.iter().enumerate() {
+        if *percent > percents[largest] {
+            largest = index;
+        }
+    }
+    percents[largest] += remainder;
+    percents
+}
+
+/// Splits the profit of a closed trade in earnings, tax and reinvestment.
+///
+/// All the legs are written in a single database transaction: either all of
+/// them are written together with their DistributionHistory or none of them are.
+pub fn execute(
+    trade: &Trade,
+    database: &mut dyn DatabaseFactory,
+) -> Result<DistributionHistory, Box<dyn Error>> {
+    // 1. Validate that the trade has a profit that was not distributed yet
+    let profit = TradePerformance::calculate(trade.id, database.transaction_read().as_mut())?;
+    let history = database
+        .distribution_read()
+        .distribution_history(trade.account_id)?;
+    distribution::can_distribute(trade, profit, &history)?;
+
+    // 2. Calculate the legs. The reinvestment gets the remainder so nothing is lost.
+    let rules = database
+        .distribution_read()
+        .distribution_rules(trade.account_id)?;
+    let earnings = profit * rules.earnings_percent;
+    let tax = profit * rules.tax_percent;
+    let reinvestment = profit - earnings - tax;
+
+    // 3. Write all the legs atomically
+    let account = database.account_read().id(trade.account_id)?;
+    database.begin_transaction()?;
+    match write_legs(
+        trade,
+        &account,
+        profit,
+        earnings,
+        tax,
+        reinvestment,
+        database,
+    ) {
+        Ok(history) => {
+            database.commit_transaction()?;
+            Ok(history)
+        }
+        Err(error) => {
+            database.rollback_transaction()?;
+            Err(error)
+        }
+    }
+}
+
+fn write_legs(
+    trade: &Trade,
+    account: &Account,
+    profit: Decimal,
+    earnings: Decimal,
+    tax: Decimal,
+    reinvestment: Decimal,
+    database: &mut dyn DatabaseFactory,
+) -> Result<DistributionHistory, Box<dyn Error>> {
+    // 1. Create one transaction per leg
+    let legs = [
+        (earnings, TransactionCategory::PaymentEarnings(trade.id)),
+        (tax, TransactionCategory::PaymentTax(trade.id)),
+        (
+            reinvestment,
+            TransactionCategory::PaymentReinvestment(trade.id),
+        ),
+    ];
+    for (amount, category) in legs {
+        if amount > dec!(0) {
+            database.transaction_write().create_transaction(
+                account,
+                amount,
+                &trade.currency,
+                category,
+            )?;
+        }
+    }
+
+    // 2. Update trade balance and account balance
+    balance::calculate_trade(database, trade)?;
+    balance::calculate_account(database, account, &trade.currency)?;
+
+    // 3. Record the distribution
+    database.distribution_write().create_distribution_history(
+        account.id,
+        trade.id,
+        profit,
+        earnings,
+        tax,
+        reinvestment,
+    )
+}
@@ -0,0 +1,104 @@
+use crate::analytics::RealizedDrawdownCalculator;
+use chrono::NaiveDateTime;
+use model::{Currency, DatabaseFactory, EquitySnapshot, TransactionCategory};
+use rust_decimal::Decimal;
+use std::error::Error;
+use uuid::Uuid;
+
+/// Replaces the equity snapshots of every currency of an account with the ones
+/// calculated from all its transactions.
+pub fn rebuild(
+    account_id: Uuid,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Vec<EquitySnapshot>, Box<dyn Error>> {
+    let mut snapshots = Vec::new();
+
+    for balance in database.account_balance_read().for_account(account_id)? {
+        // 1. Calculate the equity curve from scratch
+        let transactions = database
+            .transaction_read()
+            .all_transactions(account_id, &balance.currency)?;
+        let curve = RealizedDrawdownCalculator::calculate_equity_curve(&transactions);
+
+        // 2. Remove the old snapshots
+        database
+            .equity_snapshot_write()
+            .delete_equity_snapshots(account_id, &balance.currency)?;
+
+        // 3. Store the new checkpoints
+        for (timestamp, value) in RealizedDrawdownCalculator::checkpoints(&curve) {
+            snapshots.push(database.equity_snapshot_write().create_equity_snapshot(
+                account_id,
+                &balance.currency,
+                timestamp,
+                value,
+            )?);
+        }
+    }
+
+    Ok(snapshots)
+}
+
+/// The realized equity curve of an account in a currency, starting from its equity snapshots.
+///
+/// Only the transactions created after the last snapshot are read, together with the opening
+/// of the trades that they close. An account with less than two snapshots did not realize any
+/// profit or loss when they were taken, so its curve is calculated from all its transactions.
+pub fn realized_equity_curve(
+    account_id: Uuid,
+    currency: &Currency,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Vec<(NaiveDateTime, Decimal)>, Box<dyn Error>> {
+    // 1. Read the snapshots
+    let snapshots = database
+        .equity_snapshot_read()
+        .read_equity_snapshots(account_id, currency)?;
+    let last = match snapshots.iter().max_by_key(|snapshot| snapshot.timestamp) {
+        Some(last) if snapshots.len() > 1 => last.timestamp,
+        _ => {
+            let transactions = database
+                .transaction_read()
+                .all_transactions(account_id, currency)?;
+            return Ok(RealizedDrawdownCalculator::calculate_equity_curve(
+                &transactions,
+            ));
+        }
+    };
+
+    // 2. Read the transactions after the last snapshot
+    let mut transactions = database
+        .transaction_read()
+        .all_transactions_after(account_id, currency, last)?;
+
+    // 3. Read the opening of the trades closed after the last snapshot
+    let closed: Vec<Uuid> = transactions
+        .iter()
+        .filter_map(|tx| match tx.category {
+            TransactionCategory::CloseTarget(trade_id)
+            | TransactionCategory::CloseSafetyStop(trade_id)
+            | TransactionCategory::CloseSafetyStopSlippage(trade_id) => Some(trade_id),
+            _ => None,
+        })
+        .collect();
+    for trade_id in closed {
+        let opened_after = transactions
+            .iter()
+            .any(|tx| tx.category == TransactionCategory::OpenTrade(trade_id));
+        if !opened_after {
+            transactions.extend(
+                database
+                    .transaction_read()
+                    .all_trade_transactions(trade_id)?
+                    .into_iter()
+                    .filter(|tx| tx.category == TransactionCategory::OpenTrade(trade_id)),
+            );
+        }
+    }
+
+    Ok(
+        RealizedDrawdownCalculator::calculate_equity_curve_from_snapshots(
+            &snapshots,
+            &transactions,
+        ),
+    )
+}
@@ -0,0 +1,134 @@
+use crate::commands;
+use crate::validators;
+use chrono::NaiveDateTime;
+use model::{
+    DatabaseFactory, DraftExecution, Execution, ExecutionSide, ExecutionSource, Order, Status,
+    Trade, TradeCategory,
+};
+use rust_decimal::Decimal;
+use std::error::Error;
+use uuid::Uuid;
+
+/// Result of ingesting the fills reported by a broker.
+#[derive(Debug, PartialEq)]
+pub struct ExecutionIngestion {
+    /// Executions created from the fills that were not ingested before.
+    pub executions: Vec<Execution>,
+
+    /// Fills that were skipped because an execution with the same idempotency key exists.
+    pub skipped_duplicates: usize,
+}
+
+/// Writes the fills reported by a broker as executions. A fill that the broker reports again,
+/// or twice in the same batch, has the same idempotency key and it is skipped.
+pub fn ingest(
+    drafts: &[DraftExecution],
+    database: &mut dyn DatabaseFactory,
+) -> Result<ExecutionIngestion, Box<dyn Error>> {
+    // 1. Create the executions that do not exist yet
+    let mut executions = Vec::new();
+    let mut skipped_duplicates = 0;
+    for draft in drafts {
+        let key = draft.idempotency_key();
+        if database.execution_read().execution_for_key(&key)?.is_some() {
+            skipped_duplicates += 1;
+            continue;
+        }
+        executions.push(database.execution_write().create_execution(draft)?);
+    }
+
+    // 2. Match the executions of the trades that changed in realized lots
+    let mut trade_ids: Vec<Uuid> = executions
+        .iter()
+        .map(|execution| execution.trade_id)
+        .collect();
+    trade_ids.sort();
+    trade_ids.dedup();
+    for trade_id in trade_ids {
+        let trade = database.trade_read().read_trade(trade_id)?;
+        commands::tax_lot::record_realized_lots(&trade, database)?;
+    }
+
+    Ok(ExecutionIngestion {
+        executions,
+        skipped_duplicates,
+    })
+}
+
+/// Writes an execution of a trade that was executed outside of Trust and
+/// recomputes the average filled price of its order from all its executions.
+pub fn backfill(
+    trade: &Trade,
+    side: ExecutionSide,
+    price: Decimal,
+    quantity: u64,
+    executed_at: NaiveDateTime,
+    source: ExecutionSource,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Execution, Box<dyn Error>> {
+    // 1. Validate that the side matches the status of the trade
+    validators::trade::can_backfill_execution(trade, side)?;
+
+    // 2. Create the execution for the order of that side
+    let order = order_for_side(trade, side);
+    let draft = DraftExecution {
+        broker_execution_id: None,
+        trade_id: trade.id,
+        order_id: order.id,
+        side,
+        price,
+        quantity,
+        executed_at,
+        source,
+    };
+    if database
+        .execution_read()
+        .execution_for_key(&draft.idempotency_key())?
+        .is_some()
+    {
+        return Err(format!(
+            "An execution of {} shares at {} was already recorded at {} for this order",
+            quantity, price, executed_at
+        )
+        .into());
+    }
+    let execution = database.execution_write().create_execution(&draft)?;
+
+    // 3. Recompute the average filled price of the order
+    let executions: Vec<Execution> = database
+        .execution_read()
+        .read_executions_for_trade(trade.id)?
+        .into_iter()
+        .filter(|execution| execution.order_id == order.id)
+        .collect();
+    let filled_quantity: u64 = executions.iter().map(|execution| execution.quantity).sum();
+    let notional: Decimal = executions
+        .iter()
+        .map(|execution| execution.price * Decimal::from(execution.quantity))
+        .sum();
+
+    let mut order = order.clone();
+    order.filled_quantity = filled_quantity;
+    if filled_quantity > 0 {
+        order.average_filled_price = Some(notional / Decimal::from(filled_quantity));
+    }
+    database.order_write().update(&order)?;
+
+    // 4. Match the executions of the trade in realized lots
+    commands::tax_lot::record_realized_lots(trade, database)?;
+
+    Ok(execution)
+}
+
+fn order_for_side(trade: &Trade, side: ExecutionSide) -> &Order {
+    let is_entry = matches!(
+        (trade.category, side),
+        (TradeCategory::Long, ExecutionSide::Buy) | (TradeCategory::Short, ExecutionSide::Sell)
+    );
+
+    match trade.status {
+        _ if is_entry => &trade.entry,
+        Status::ClosedStopLoss => &trade.safety_stop,
+        _ => &trade.target,
+    }
+}
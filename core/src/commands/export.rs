@@ -0,0 +1,62 @@
+use model::{DatabaseFactory, Execution, Status, Trade};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use uuid::Uuid;
+
+/// The version of the JSON of the exported trades. It changes when a field is renamed or removed.
+pub const TRADES_EXPORT_VERSION: u32 = 1;
+
+/// The trades of an account exported as JSON.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct TradesExport {
+    pub version: u32,
+    pub trades: Vec<ExportedTrade>,
+}
+
+/// A trade with its orders, balance and metadata, and the executions of its orders.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExportedTrade {
+    #[serde(flatten)]
+    pub trade: Trade,
+    pub executions: Vec<Execution>,
+}
+
+/// Exports the trades of an account as JSON, from the oldest to the newest.
+/// Without a status, the trades of every status are exported.
+/// Decimals are written as strings, so no precision is lost.
+pub fn export_trades_json(
+    account_id: Uuid,
+    status: Option<Status>,
+    database: &mut dyn DatabaseFactory,
+) -> Result<String, Box<dyn Error>> {
+    // 1. Read the trades
+    let statuses = match status {
+        Some(status) => vec![status],
+        None => Status::all(),
+    };
+    let mut trades = Vec::new();
+    for status in statuses {
+        trades.append(
+            &mut database
+                .trade_read()
+                .read_trades_with_status(account_id, status)?,
+        );
+    }
+    trades.sort_by_key(|trade| trade.created_at);
+
+    // 2. Read the executions of every trade
+    let mut exported = Vec::new();
+    for trade in trades {
+        let executions = database
+            .execution_read()
+            .read_executions_for_trade(trade.id)?;
+        exported.push(ExportedTrade { trade, executions });
+    }
+
+    // 3. Serialize the envelope
+    let export = TradesExport {
+        version: TRADES_EXPORT_VERSION,
+        trades: exported,
+    };
+    Ok(serde_json::to_string(&export)?)
+}
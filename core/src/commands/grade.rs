@@ -0,0 +1,44 @@
+use crate::calculators_trade::{TradeGradeCalculator, TradePerformance};
+use crate::commands::distribution;
+use model::{
+    DatabaseFactory, DistributionHistory, GradingWeightsPermille, Status, Trade, TradeGrade,
+};
+use rust_decimal_macros::dec;
+use std::error::Error;
+
+/// Finishes a trade that was closed in the broker: its profit is distributed
+/// if it has one and the account has distribution rules, and the trade is graded.
+pub fn close_with_auto_distribution(
+    trade: &Trade,
+    weights: &GradingWeightsPermille,
+    database: &mut dyn DatabaseFactory,
+) -> Result<(Option<DistributionHistory>, TradeGrade), Box<dyn Error>> {
+    // 1. Validate that the trade is closed
+    let trade = database.trade_read().read_trade(trade.id)?;
+    if trade.status != Status::ClosedTarget && trade.status != Status::ClosedStopLoss {
+        return Err(format!(
+            "Trade {} with status {} is not closed and it can not be graded",
+            trade.id, trade.status
+        )
+        .into());
+    }
+
+    // 2. Distribute the profit
+    let profit = TradePerformance::calculate(trade.id, database.transaction_read().as_mut())?;
+    let has_rules = database
+        .distribution_read()
+        .distribution_rules(trade.account_id)
+        .is_ok();
+    let history = if profit > dec!(0) && has_rules {
+        Some(distribution::execute(&trade, database)?)
+    } else {
+        None
+    };
+
+    // 3. Grade the trade with its updated balance
+    let trade = database.trade_read().read_trade(trade.id)?;
+    let grade = TradeGradeCalculator::calculate(&trade, weights)?;
+    let grade = database.trade_grade_write().create_trade_grade(&grade)?;
+
+    Ok((history, grade))
+}
@@ -0,0 +1,206 @@
+use crate::commands;
+use chrono::{NaiveDate, NaiveDateTime};
+use model::{Currency, DatabaseFactory, SteppableClock, Transaction, TransactionCategory};
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::io::Read;
+use std::str::FromStr;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Result of importing the transactions of a CSV.
+#[derive(Debug, PartialEq)]
+pub struct ImportReport {
+    /// Transactions created from the rows, in chronological order.
+    pub accepted: Vec<Transaction>,
+
+    /// Rows that were not imported, with the number of their line and the reason.
+    pub rejected: Vec<(usize, String)>,
+}
+
+/// A row of the CSV that was parsed.
+#[derive(Debug, PartialEq)]
+struct ImportRow {
+    line: usize,
+    date: NaiveDateTime,
+    category: TransactionCategory,
+    amount: Decimal,
+    currency: Currency,
+}
+
+/// Imports the transactions of a CSV with the columns `date,category,amount,currency`.
+///
+/// The rows are applied from the oldest to the newest and every transaction is written on its date,
+/// so a withdrawal is validated against the balance that the account had at that time.
+/// Rows that fail are rejected and the import continues, unless it is strict: then the first
+/// rejected row rolls back the whole import.
+pub fn import_transactions_csv(
+    account_id: Uuid,
+    reader: impl Read,
+    strict: bool,
+    database: &mut dyn DatabaseFactory,
+) -> Result<ImportReport, Box<dyn Error>> {
+    // 1. Parse the rows
+    let mut csv = String::new();
+    let mut reader = reader;
+    reader.read_to_string(&mut csv)?;
+
+    let mut rows = Vec::new();
+    let mut rejected = Vec::new();
+    for (index, line) in csv.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() || (index == 0 && line.trim().starts_with("date")) {
+            continue; // Blank lines and the header are not rows.
+        }
+        match parse_row(line_number, line) {
+            Ok(row) => rows.push(row),
+            Err(reason) => rejected.push((line_number, reason)),
+        }
+    }
+    if strict {
+        if let Some((line, reason)) = rejected.first() {
+            return Err(format!("Line {}: {}", line, reason).into());
+        }
+    }
+
+    // 2. Sort the rows in chronological order. Rows of the same date keep the order of the file.
+    rows.sort_by_key(|row| row.date);
+
+    // 3. Apply the rows atomically on their date
+    let clock = database.clock();
+    database.begin_transaction()?;
+    let result = apply_rows(account_id, &rows, strict, database);
+    database.set_clock(clock);
+
+    match result {
+        Ok((accepted, mut rejected_rows)) => {
+            database.commit_transaction()?;
+            rejected.append(&mut rejected_rows);
+            rejected.sort_by_key(|(line, _)| *line);
+            Ok(ImportReport { accepted, rejected })
+        }
+        Err(error) => {
+            database.rollback_transaction()?;
+            Err(error)
+        }
+    }
+}
+
+type AppliedRows = (Vec<Transaction>, Vec<(usize, String)>);
+
+fn apply_rows(
+    account_id: Uuid,
+    rows: &[ImportRow],
+    strict: bool,
+    database: &mut dyn DatabaseFactory,
+) -> Result<AppliedRows, Box<dyn Error>> {
+    let mut accepted = Vec::new();
+    let mut rejected = Vec::new();
+    for row in rows {
+        database.set_clock(Arc::new(SteppableClock::new(row.date)));
+        match commands::transaction::create(
+            database,
+            &row.category,
+            row.amount,
+            &row.currency,
+            account_id,
+        ) {
+            Ok((transaction, _)) => accepted.push(transaction),
+            Err(error) if strict => {
+                return Err(format!("Line {}: {}", row.line, error).into());
+            }
+            Err(error) => rejected.push((row.line, error.to_string())),
+        }
+    }
+    Ok((accepted, rejected))
+}
+
+fn parse_row(line: usize, row: &str) -> Result<ImportRow, String> {
+    let columns: Vec<&str> = row.split(',').map(|column| column.trim()).collect();
+    let [date, category, amount, currency] = columns[..] else {
+        return Err(format!(
+            "The row must have 4 columns: date, category, amount and currency. It has {}",
+            columns.len()
+        ));
+    };
+
+    let date = NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map(|date| date.and_hms_opt(0, 0, 0).unwrap())
+        })
+        .map_err(|_| format!("The date {} is not a valid date", date))?;
+    let category = match TransactionCategory::parse(category, None) {
+        Ok(
+            category @ (TransactionCategory::Deposit
+            | TransactionCategory::Withdrawal
+            | TransactionCategory::Dividend(None)
+            | TransactionCategory::Interest),
+        ) => category,
+        _ => {
+            return Err(format!(
+                "The category {} can not be imported. Only deposits, withdrawals, dividends and interest can be imported",
+                category
+            ))
+        }
+    };
+    let amount = Decimal::from_str(amount)
+        .map_err(|_| format!("The amount {} is not a valid number", amount))?;
+    let currency = Currency::from_str(currency)
+        .map_err(|_| format!("The currency {} is not supported", currency))?;
+
+    Ok(ImportRow {
+        line,
+        date,
+        category,
+        amount,
+        currency,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_row() {
+        let row = parse_row(2, "2023-01-15, deposit, 1000.50, USD").unwrap();
+
+        assert_eq!(row.line, 2);
+        assert_eq!(
+            row.date,
+            NaiveDate::from_ymd_opt(2023, 1, 15)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+        );
+        assert_eq!(row.category, TransactionCategory::Deposit);
+        assert_eq!(row.amount, dec!(1000.50));
+        assert_eq!(row.currency, Currency::USD);
+    }
+
+    #[test]
+    fn test_parse_row_with_time() {
+        let row = parse_row(2, "2023-01-15 10:30:00,withdrawal,100,EUR").unwrap();
+
+        assert_eq!(
+            row.date,
+            NaiveDate::from_ymd_opt(2023, 1, 15)
+                .unwrap()
+                .and_hms_opt(10, 30, 0)
+                .unwrap()
+        );
+        assert_eq!(row.category, TransactionCategory::Withdrawal);
+    }
+
+    #[test]
+    fn test_parse_invalid_rows() {
+        assert!(parse_row(1, "2023-01-15,deposit,100").is_err());
+        assert!(parse_row(1, "15/01/2023,deposit,100,USD").is_err());
+        assert!(parse_row(1, "2023-01-15,fund_trade,100,USD").is_err());
+        assert!(parse_row(1, "2023-01-15,withdrawal_tax,100,USD").is_err());
+        assert!(parse_row(1, "2023-01-15,deposit,a lot,USD").is_err());
+        assert!(parse_row(1, "2023-01-15,deposit,100,GBP").is_err());
+    }
+}
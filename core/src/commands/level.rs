@@ -0,0 +1,60 @@
+use crate::validators;
+use model::{DatabaseFactory, Level, LevelAdjustmentRules, LevelChange, LevelTrigger};
+use std::error::Error;
+use uuid::Uuid;
+
+/// Returns the level of an account.
+/// Accounts that never had a level start with Level::DEFAULT.
+/// Only primary accounts have a level.
+pub fn current(
+    account_id: Uuid,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Level, Box<dyn Error>> {
+    let account = database.account_read().id(account_id)?;
+    validators::account::can_have_level(&account)?;
+
+    match database.level_read().level_for_account(account_id) {
+        Ok(level) => Ok(level),
+        Err(_) => database
+            .level_write()
+            .create_level(account_id, Level::DEFAULT),
+    }
+}
+
+/// Moves an account to a new level and records the change in its history.
+pub fn change(
+    account_id: Uuid,
+    new_level: u8,
+    reason: &str,
+    trigger: &LevelTrigger,
+    database: &mut dyn DatabaseFactory,
+) -> Result<LevelChange, Box<dyn Error>> {
+    // 1. Read the current level
+    let level = current(account_id, database)?;
+
+    // 2. Validate the change
+    validators::level::can_change(&level, new_level, reason, trigger)?;
+
+    // 3. Update the level
+    database.level_write().update_level(&level, new_level)?;
+
+    // 4. Record the change
+    database.level_write().create_level_change(
+        account_id,
+        level.current_level,
+        new_level,
+        reason,
+        trigger,
+    )
+}
+
+pub fn update_adjustment_rules(
+    account_id: Uuid,
+    rules: &LevelAdjustmentRules,
+    database: &mut dyn DatabaseFactory,
+) -> Result<LevelAdjustmentRules, Box<dyn Error>> {
+    let level = current(account_id, database)?;
+    database
+        .level_write()
+        .update_level_adjustment_rules(&level, rules)
+}
@@ -1,13 +1,22 @@
 use model::{
-    Currency, DatabaseFactory, Order, OrderAction, OrderCategory, OrderWrite, ReadTradeDB, Trade,
-    TradeCategory,
+    Currency, DatabaseFactory, Order, OrderAction, OrderCategory, OrderRead, OrderWrite,
+    ReadTradeDB, Trade, TradeCategory,
 };
 use rust_decimal::Decimal;
 use uuid::Uuid;
 
+/// The three orders of a trade as they are stored.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TradeOrders {
+    pub entry: Order,
+    pub target: Order,
+    pub stop: Order,
+}
+
 pub fn create_stop(
     trading_vehicle_id: Uuid,
     quantity: i64,
+    quantity_decimal: Option<Decimal>,
     price: Decimal,
     currency: &Currency,
     category: &TradeCategory,
@@ -19,6 +28,7 @@ pub fn create_stop(
     database.order_write().create(
         &tv,
         quantity,
+        quantity_decimal,
         price,
         currency,
         &action_for_stop(category),
@@ -29,6 +39,7 @@ pub fn create_stop(
 pub fn create_entry(
     trading_vehicle_id: Uuid,
     quantity: i64,
+    quantity_decimal: Option<Decimal>,
     price: Decimal,
     currency: &Currency,
     category: &TradeCategory,
@@ -40,6 +51,7 @@ pub fn create_entry(
     database.order_write().create(
         &tv,
         quantity,
+        quantity_decimal,
         price,
         currency,
         &action_for_entry(category),
@@ -50,6 +62,7 @@ pub fn create_entry(
 pub fn create_target(
     trading_vehicle_id: Uuid,
     quantity: i64,
+    quantity_decimal: Option<Decimal>,
     price: Decimal,
     currency: &Currency,
     category: &TradeCategory,
@@ -64,6 +77,7 @@ pub fn create_target(
     database.order_write().create(
         &tv,
         quantity,
+        quantity_decimal,
         price,
         currency,
         &action,
@@ -75,7 +89,19 @@ pub fn update_order(
     order: &Order,
     database: &mut dyn DatabaseFactory,
 ) -> Result<Order, Box<dyn std::error::Error>> {
-    database.order_write().update(order)
+    let updated = database.order_write().update(order)?;
+
+    // The broker moves the stop price of a trailing stop, so its price is updated as well.
+    match (order.category, order.broker_order_id) {
+        (OrderCategory::TrailingStop { .. }, Some(broker_order_id))
+            if order.unit_price != updated.unit_price =>
+        {
+            database
+                .order_write()
+                .update_price(order, order.unit_price, broker_order_id)
+        }
+        _ => Ok(updated),
+    }
 }
 
 pub fn record_timestamp_filled(
@@ -115,6 +141,19 @@ pub fn modify(
     Ok(stop)
 }
 
+pub fn read_trade_orders(
+    trade_id: Uuid,
+    trade_database: &mut dyn ReadTradeDB,
+    order_database: &mut dyn OrderRead,
+) -> Result<TradeOrders, Box<dyn std::error::Error>> {
+    let trade = trade_database.read_trade(trade_id)?;
+    Ok(TradeOrders {
+        entry: order_database.for_id(trade.entry.id)?,
+        target: order_database.for_id(trade.target.id)?,
+        stop: order_database.for_id(trade.safety_stop.id)?,
+    })
+}
+
 fn action_for_stop(category: &TradeCategory) -> OrderAction {
     match category {
         TradeCategory::Long => OrderAction::Sell,
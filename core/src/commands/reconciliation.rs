@@ -0,0 +1,420 @@
+use crate::commands;
+use crate::validators::transaction;
+use chrono::NaiveDateTime;
+use model::{
+    Account, Broker, BrokerActivity, BrokerActivityCategory, BrokerPosition, Currency,
+    DatabaseFactory, DraftExecution, Execution, ExecutionSide, ExecutionSource, Order, OrderAction,
+    Status, Trade, TradeBalance, TradeCategory, Transaction, TransactionCategory,
+};
+use rust_decimal::Decimal;
+use std::error::Error;
+use uuid::Uuid;
+
+/// Result of reconciling the fees of an account with the broker activities.
+#[derive(Debug, PartialEq)]
+pub struct FeeReconciliation {
+    /// Executions created from the fills reported by the broker.
+    pub executions: Vec<Execution>,
+
+    /// Fills that were already ingested as executions and were skipped.
+    pub skipped_duplicates: usize,
+
+    /// Fees that were allocated to a trade.
+    pub allocated_fees: Vec<Transaction>,
+
+    /// Fees that could not be allocated to any trade and were charged to the account.
+    pub unallocated_fees: Vec<Transaction>,
+
+    /// Fees that were already charged and were skipped.
+    pub skipped_fees: usize,
+}
+
+/// Result of comparing the available balance of an account with the buying power in the broker.
+#[derive(Debug, PartialEq)]
+pub struct BalanceReconciliation {
+    /// The buying power reported by the broker.
+    pub broker_buying_power: Decimal,
+
+    /// The money available in the account, before any adjustment.
+    pub total_available: Decimal,
+
+    /// The buying power minus the available balance. It is positive when the broker has more money.
+    pub delta: Decimal,
+
+    /// The transaction that corrected the balance, if the balance was reconciled.
+    pub adjustment: Option<Transaction>,
+}
+
+/// Result of comparing the positions held by the broker with the open trades of an account.
+/// Nothing is corrected, it is only a report.
+#[derive(Debug, PartialEq)]
+pub struct PositionReconciliation {
+    /// Positions of the broker without an open trade in the same symbol and direction.
+    pub orphan_broker_positions: Vec<BrokerPosition>,
+
+    /// Open trades without a position of the broker in the same symbol and direction.
+    pub orphan_trades: Vec<Trade>,
+
+    /// Positions whose quantity is not the sum of the filled quantities of their open trades.
+    pub quantity_mismatches: Vec<PositionMismatch>,
+}
+
+/// A position of the broker that does not hold the quantity of its open trades.
+#[derive(Debug, PartialEq)]
+pub struct PositionMismatch {
+    pub symbol: String,
+    pub category: TradeCategory,
+    pub broker_quantity: Decimal,
+    pub local_quantity: Decimal,
+
+    /// The open trades of the position.
+    pub trade_ids: Vec<Uuid>,
+}
+
+/// Result of rebuilding the balance of a trade from its transactions.
+#[derive(Debug, PartialEq)]
+pub struct TradeBalanceRepair {
+    /// The rebuilt balance, that replaced the stored one.
+    pub balance: TradeBalance,
+
+    /// The rebuilt values minus the stored values. They are zero when the balance was consistent.
+    pub funding_delta: Decimal,
+    pub capital_in_market_delta: Decimal,
+    pub capital_out_market_delta: Decimal,
+    pub taxed_delta: Decimal,
+    pub total_performance_delta: Decimal,
+}
+
+/// A fill of one of the orders of a trade.
+struct TradeFill {
+    trade: Trade,
+    is_entry: bool,
+    transaction_time: NaiveDateTime,
+}
+
+/// Reconcile the fees of an account with the activities reported by the broker.
+///
+/// Fills are stored as executions of the trade that owns the order. Fees are reported
+/// without an order, so they are allocated to the trade with the nearest fill in the
+/// same day. Fees without a fill in the same day are charged to the account.
+///
+/// Fills and fees are keyed on the id of their activity in the broker, so reconciling
+/// the same period twice does not duplicate them.
+pub fn reconcile_fees(
+    account: &Account,
+    since: NaiveDateTime,
+    until: NaiveDateTime,
+    database: &mut dyn DatabaseFactory,
+    broker: &mut dyn Broker,
+) -> Result<FeeReconciliation, Box<dyn Error>> {
+    // 1. Read the activities of the account from the broker
+    let activities = broker.get_account_activities(account, since, until)?;
+
+    // 2. Read all the trades of the account that were submitted to the broker
+    let mut trades = Vec::new();
+    for status in Status::all() {
+        trades.extend(
+            database
+                .trade_read()
+                .read_trades_with_status(account.id, status)?
+                .into_iter()
+                .filter(|trade| trade.entry.broker_order_id.is_some()),
+        );
+    }
+
+    // 3. Ingest the fills as executions
+    let mut drafts = Vec::new();
+    let mut fills = Vec::new();
+    for activity in activities
+        .iter()
+        .filter(|activity| activity.category == BrokerActivityCategory::Fill)
+    {
+        let (trade, order) = match find_order(&trades, activity) {
+            Some(found) => found,
+            None => continue, // The fill does not belong to any trade of this account.
+        };
+
+        fills.push(TradeFill {
+            trade: trade.clone(),
+            is_entry: order.id == trade.entry.id,
+            transaction_time: activity.transaction_time,
+        });
+
+        drafts.push(DraftExecution {
+            broker_execution_id: Some(activity.id.clone()),
+            trade_id: trade.id,
+            order_id: order.id,
+            side: activity.side.unwrap_or(match order.action {
+                OrderAction::Buy => ExecutionSide::Buy,
+                OrderAction::Sell | OrderAction::Short => ExecutionSide::Sell,
+            }),
+            price: activity
+                .price
+                .or(order.average_filled_price)
+                .unwrap_or(order.unit_price),
+            quantity: activity.quantity.unwrap_or(order.quantity),
+            executed_at: activity.transaction_time,
+            source: ExecutionSource::Broker,
+        });
+    }
+
+    let ingestion = commands::execution::ingest(&drafts, database)?;
+
+    // 4. Allocate the fees to the nearest fill, unless they were already charged
+    let mut allocated_fees = Vec::new();
+    let mut unallocated_fees = Vec::new();
+    let mut skipped_fees = 0;
+    for activity in activities
+        .iter()
+        .filter(|activity| activity.category == BrokerActivityCategory::Fee)
+    {
+        let amount = match activity.amount {
+            Some(amount) if amount > Decimal::ZERO => amount,
+            _ => continue, // Nothing to allocate.
+        };
+
+        if database
+            .transaction_read()
+            .for_broker_activity(&activity.id)?
+            .is_some()
+        {
+            skipped_fees += 1;
+            continue;
+        }
+
+        match nearest_fill(&fills, activity.transaction_time) {
+            Some(fill) => {
+                let category = if fill.is_entry {
+                    TransactionCategory::FeeOpen(fill.trade.id)
+                } else {
+                    TransactionCategory::FeeClose(fill.trade.id)
+                };
+                let transaction = charge_fee(
+                    account,
+                    amount,
+                    &fill.trade.currency,
+                    category,
+                    &activity.id,
+                    database,
+                )?;
+                commands::balance::calculate_trade(database, &fill.trade)?;
+                allocated_fees.push(transaction);
+            }
+            None => {
+                let transaction = charge_fee(
+                    account,
+                    amount,
+                    &activity.currency,
+                    TransactionCategory::Fee,
+                    &activity.id,
+                    database,
+                )?;
+                unallocated_fees.push(transaction);
+            }
+        }
+    }
+
+    Ok(FeeReconciliation {
+        executions: ingestion.executions,
+        skipped_duplicates: ingestion.skipped_duplicates,
+        allocated_fees,
+        unallocated_fees,
+        skipped_fees,
+    })
+}
+
+// Charges a fee reported by the broker to the account and updates its balance.
+// The transaction is keyed on the activity, so the fee can only be charged once.
+fn charge_fee(
+    account: &Account,
+    amount: Decimal,
+    currency: &Currency,
+    category: TransactionCategory,
+    broker_activity_id: &str,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Transaction, Box<dyn Error>> {
+    let balance = database
+        .account_balance_read()
+        .for_currency(account.id, currency)?;
+    transaction::can_transfer_fee(&balance, amount)?;
+
+    let transaction = database.transaction_write().create_broker_transaction(
+        account,
+        amount,
+        currency,
+        category,
+        broker_activity_id,
+    )?;
+    commands::balance::calculate_account(database, account, currency)?;
+    Ok(transaction)
+}
+
+/// Compare the available balance of an account with the buying power reported by the broker.
+///
+/// The buying power is compared with the balance in the base currency of the account,
+/// which is the currency the broker account is held in.
+/// With `reconcile`, a drift is corrected with an adjustment transaction of the delta.
+/// The adjustment is a protected mutation, so the caller authorizes it before reconciling.
+pub fn reconcile_balance(
+    account_id: Uuid,
+    reconcile: bool,
+    database: &mut dyn DatabaseFactory,
+    broker: &mut dyn Broker,
+) -> Result<BalanceReconciliation, Box<dyn Error>> {
+    // 1. Read the buying power from the broker and the available balance of the account
+    let account = database.account_read().id(account_id)?;
+    let broker_buying_power = broker.account_buying_power(&account)?;
+    let total_available = database
+        .account_balance_read()
+        .for_currency(account.id, &account.base_currency)?
+        .total_available;
+    let delta = broker_buying_power - total_available;
+
+    // 2. Correct the balance with the delta, if it drifted
+    let mut adjustment = None;
+    if reconcile && !delta.is_zero() {
+        let (transaction, _) = commands::transaction::adjust(
+            database,
+            delta,
+            &account.base_currency,
+            account.id,
+            "Reconciliation with the buying power of the broker",
+        )?;
+        adjustment = Some(transaction);
+    }
+
+    Ok(BalanceReconciliation {
+        broker_buying_power,
+        total_available,
+        delta,
+        adjustment,
+    })
+}
+
+/// Compare the positions held by the broker with the trades of the account that hold a position.
+///
+/// The trades are grouped by symbol and direction, so a position opened by several trades
+/// matches when it holds the sum of their filled quantities.
+pub fn reconcile_positions(
+    account_id: Uuid,
+    database: &mut dyn DatabaseFactory,
+    broker: &mut dyn Broker,
+) -> Result<PositionReconciliation, Box<dyn Error>> {
+    // 1. Read the positions from the broker
+    let account = database.account_read().id(account_id)?;
+    let mut positions = broker.get_positions(&account)?;
+
+    // 2. Group the trades that hold a position by symbol and direction
+    let mut groups: Vec<(String, TradeCategory, Vec<Trade>)> = Vec::new();
+    for status in [
+        Status::PartiallyFilled,
+        Status::Filled,
+        Status::ClosingPending,
+    ] {
+        for trade in database
+            .trade_read()
+            .read_trades_with_status(account_id, status)?
+        {
+            let symbol = trade.trading_vehicle.symbol.to_uppercase();
+            match groups.iter_mut().find(|(group_symbol, category, _)| {
+                *group_symbol == symbol && *category == trade.category
+            }) {
+                Some((_, _, trades)) => trades.push(trade),
+                None => groups.push((symbol, trade.category, vec![trade])),
+            }
+        }
+    }
+
+    // 3. Match every group with the position of the broker
+    let mut reconciliation = PositionReconciliation {
+        orphan_broker_positions: vec![],
+        orphan_trades: vec![],
+        quantity_mismatches: vec![],
+    };
+    for (symbol, category, trades) in groups {
+        let position = positions.iter().position(|position| {
+            position.symbol.eq_ignore_ascii_case(&symbol) && position.category == category
+        });
+        let Some(index) = position else {
+            reconciliation.orphan_trades.extend(trades);
+            continue;
+        };
+
+        let position = positions.remove(index);
+        let local_quantity: Decimal = trades.iter().map(commands::account::filled_quantity).sum();
+        if position.quantity != local_quantity {
+            reconciliation.quantity_mismatches.push(PositionMismatch {
+                symbol,
+                category,
+                broker_quantity: position.quantity,
+                local_quantity,
+                trade_ids: trades.iter().map(|trade| trade.id).collect(),
+            });
+        }
+    }
+
+    // 4. The positions left do not have any trade
+    reconciliation.orphan_broker_positions = positions;
+
+    Ok(reconciliation)
+}
+
+/// Rebuild the balance of a trade from its transactions and overwrite the stored balance.
+///
+/// Every fill and every fee of the trade moves money with a transaction, so the funding, the fees
+/// and the performance are calculated again from them. The stored balance is not read, so a
+/// balance corrupted by past bugs, like a trade funded twice, is replaced.
+pub fn recompute_trade_balance(
+    trade_id: Uuid,
+    database: &mut dyn DatabaseFactory,
+) -> Result<TradeBalanceRepair, Box<dyn Error>> {
+    // 1. Read the stored balance
+    let trade = database.trade_read().read_trade(trade_id)?;
+    let previous = trade.balance.clone();
+
+    // 2. Rebuild and overwrite the balance atomically
+    database.begin_transaction()?;
+    let balance = match commands::balance::calculate_trade(database, &trade) {
+        Ok(balance) => {
+            database.commit_transaction()?;
+            balance
+        }
+        Err(error) => {
+            database.rollback_transaction()?;
+            return Err(error);
+        }
+    };
+
+    Ok(TradeBalanceRepair {
+        funding_delta: balance.funding - previous.funding,
+        capital_in_market_delta: balance.capital_in_market - previous.capital_in_market,
+        capital_out_market_delta: balance.capital_out_market - previous.capital_out_market,
+        taxed_delta: balance.taxed - previous.taxed,
+        total_performance_delta: balance.total_performance - previous.total_performance,
+        balance,
+    })
+}
+
+fn find_order<'a>(
+    trades: &'a [Trade],
+    activity: &BrokerActivity,
+) -> Option<(&'a Trade, &'a Order)> {
+    let order_id = activity.order_id?;
+    trades.iter().find_map(|trade| {
+        [&trade.entry, &trade.target, &trade.safety_stop]
+            .into_iter()
+            .find(|order| order.broker_order_id == Some(order_id))
+            .map(|order| (trade, order))
+    })
+}
+
+fn nearest_fill(fills: &[TradeFill], transaction_time: NaiveDateTime) -> Option<&TradeFill> {
+    fills
+        .iter()
+        .filter(|fill| fill.transaction_time.date() == transaction_time.date())
+        .min_by_key(|fill| {
+            (fill.transaction_time - transaction_time)
+                .num_seconds()
+                .abs()
+        })
+}
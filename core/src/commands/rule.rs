@@ -1,4 +1,17 @@
+use crate::validators::rule::RuleValidationErrorCode;
 use model::{Account, DatabaseFactory, Rule, RuleLevel, RuleName};
+use std::error::Error;
+use uuid::Uuid;
+
+/// What was copied when the rules of an account were cloned into another account.
+#[derive(Debug, PartialEq)]
+pub struct RuleCloneReport {
+    /// The rules created in the target account.
+    pub created: Vec<Rule>,
+
+    /// The rules of the source whose type the target already has. They are not copied.
+    pub skipped: Vec<Rule>,
+}
 
 pub fn create(
     database: &mut dyn DatabaseFactory,
@@ -7,6 +20,7 @@ pub fn create(
     description: &str,
     level: &RuleLevel,
 ) -> Result<Rule, Box<dyn std::error::Error>> {
+    crate::validators::rule::can_create_with_percentage(rule_name)?;
     crate::validators::rule::can_create(rule_name, account, database.rule_read().as_mut())?;
     database.rule_write().create_rule(
         account,
@@ -17,6 +31,60 @@ pub fn create(
     )
 }
 
+/// Copies the active rules of an account into another account, with the same level and description.
+/// The target keeps its own rule when it already has one of the same type.
+pub fn clone_rules(
+    source_account_id: Uuid,
+    target_account_id: Uuid,
+    database: &mut dyn DatabaseFactory,
+) -> Result<RuleCloneReport, Box<dyn Error>> {
+    // 1. Read the active rules of the source
+    database.account_read().id(source_account_id)?;
+    let target = database.account_read().id(target_account_id)?;
+    let rules = database.rule_read().read_all_rules(source_account_id)?;
+
+    // 2. Create the rules that the target does not have atomically
+    database.begin_transaction()?;
+    match create_missing_rules(rules, &target, database) {
+        Ok(report) => {
+            database.commit_transaction()?;
+            Ok(report)
+        }
+        Err(error) => {
+            database.rollback_transaction()?;
+            Err(error)
+        }
+    }
+}
+
+fn create_missing_rules(
+    rules: Vec<Rule>,
+    target: &Account,
+    database: &mut dyn DatabaseFactory,
+) -> Result<RuleCloneReport, Box<dyn Error>> {
+    let mut report = RuleCloneReport {
+        created: vec![],
+        skipped: vec![],
+    };
+    for rule in rules.into_iter().filter(|rule| rule.active) {
+        match crate::validators::rule::can_create(&rule.name, target, database.rule_read().as_mut())
+        {
+            Ok(()) => report.created.push(create(
+                database,
+                target,
+                &rule.name,
+                &rule.description,
+                &rule.level,
+            )?),
+            Err(error) if error.code == RuleValidationErrorCode::RuleAlreadyExistsInAccount => {
+                report.skipped.push(rule)
+            }
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(report)
+}
+
 /// Returns the priority for a given rule name.
 /// The priority is used to determine the order in which rules are applied.
 /// The lower the number, the higher the priority.
@@ -33,5 +101,9 @@ fn priority_for(name: &RuleName) -> u32 {
     match name {
         RuleName::RiskPerMonth(_) => 1,
         RuleName::RiskPerTrade(_) => 2,
+        RuleName::MaxPositionConcentration(_) => 3,
+        RuleName::OnePositionPerSymbolSide => 4,
+        RuleName::MinRewardRiskRatio(_) => 5,
+        RuleName::MaxConsecutiveLosses(_) => 6,
     }
 }
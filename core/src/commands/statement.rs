@@ -0,0 +1,366 @@
+use crate::calculators_account::AccountCapitalBalance;
+use chrono::{NaiveDate, NaiveDateTime};
+use model::{Currency, DatabaseFactory, Transaction, TransactionCategory};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::error::Error;
+use uuid::Uuid;
+
+/// Summary of all the movements of an account in a given month and currency.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AccountStatement {
+    pub account_id: Uuid,
+    pub currency: Currency,
+    pub year: i32,
+    pub month: u32,
+
+    /// Balance of the account at the first day of the month
+    pub opening_balance: Decimal,
+    pub deposits: Decimal,
+    pub withdrawals: Decimal,
+
+    /// Money paid into the account by its holdings
+    pub dividends: Decimal,
+
+    /// Interest paid to the account minus the interest charged to it
+    pub interest: Decimal,
+
+    /// Corrections of the balance to match the broker
+    pub adjustments: Decimal,
+
+    /// Money returned by closed trades minus the money used to open them
+    pub trade_pnl: Decimal,
+    pub fees: Decimal,
+    pub taxes: Decimal,
+    pub distributions: Decimal,
+
+    pub net_change: Decimal,
+
+    /// Balance of the account at the last day of the month
+    pub closing_balance: Decimal,
+}
+
+pub fn monthly(
+    account_id: Uuid,
+    year: i32,
+    month: u32,
+    currency: &Currency,
+    database: &mut dyn DatabaseFactory,
+) -> Result<AccountStatement, Box<dyn Error>> {
+    // 1. Read all the transactions of the account
+    let transactions = database
+        .transaction_read()
+        .all_transactions(account_id, currency)?;
+
+    // 2. Sum the movements of the month
+    let statement = summarize(account_id, year, month, currency, &transactions)?;
+
+    // 3. If nothing happened after the month, the closing balance must be the stored balance
+    let (_, end) = month_boundaries(year, month)?;
+    if !transactions.is_empty() && transactions.iter().all(|tx| tx.created_at < end) {
+        let stored = database
+            .account_balance_read()
+            .for_currency(account_id, currency)?;
+        if stored.total_balance != statement.closing_balance {
+            return Err(format!(
+                "Statement for account {} in {}-{} is inconsistent: the closing balance {} is not the balance {} of the account",
+                account_id, year, month, statement.closing_balance, stored.total_balance
+            )
+            .into());
+        }
+    }
+
+    Ok(statement)
+}
+
+fn summarize(
+    account_id: Uuid,
+    year: i32,
+    month: u32,
+    currency: &Currency,
+    transactions: &[Transaction],
+) -> Result<AccountStatement, Box<dyn Error>> {
+    // 1. Calculate the boundaries of the month
+    let (start, end) = month_boundaries(year, month)?;
+
+    // 2. Calculate the opening balance based on the transactions before the month
+    let opening_balance =
+        AccountCapitalBalance::of(transactions.iter().filter(|tx| tx.created_at < start));
+
+    // 3. Sum every category of the transactions that happened in the month
+    let mut statement = AccountStatement {
+        account_id,
+        currency: *currency,
+        year,
+        month,
+        opening_balance,
+        deposits: dec!(0),
+        withdrawals: dec!(0),
+        dividends: dec!(0),
+        interest: dec!(0),
+        adjustments: dec!(0),
+        trade_pnl: dec!(0),
+        fees: dec!(0),
+        taxes: dec!(0),
+        distributions: dec!(0),
+        net_change: dec!(0),
+        closing_balance: dec!(0),
+    };
+
+    for tx in transactions
+        .iter()
+        .filter(|tx| tx.created_at >= start && tx.created_at < end)
+    {
+        match tx.category {
+            TransactionCategory::Deposit => statement.deposits += tx.amount,
+            TransactionCategory::Withdrawal => statement.withdrawals += tx.amount,
+            TransactionCategory::Dividend(_) => statement.dividends += tx.amount,
+            TransactionCategory::Interest => statement.interest += tx.amount,
+            TransactionCategory::Adjustment { .. } => statement.adjustments += tx.amount,
+            TransactionCategory::OpenTrade(_) => statement.trade_pnl -= tx.amount,
+            TransactionCategory::CloseTarget(_)
+            | TransactionCategory::CloseSafetyStop(_)
+            | TransactionCategory::CloseSafetyStopSlippage(_) => statement.trade_pnl += tx.amount,
+            TransactionCategory::FeeOpen(_)
+            | TransactionCategory::FeeClose(_)
+            | TransactionCategory::Fee => statement.fees += tx.amount,
+            // Rebates are negative costs, so the fees of a month can be negative.
+            TransactionCategory::FeeRebate(_) => statement.fees -= tx.amount,
+            TransactionCategory::WithdrawalTax => statement.taxes += tx.amount,
+            TransactionCategory::WithdrawalEarnings => statement.distributions += tx.amount,
+            _ => {} // Movements between the account and its trades do not change the balance.
+        }
+    }
+
+    statement.net_change = statement.deposits - statement.withdrawals
+        + statement.dividends
+        + statement.interest
+        + statement.adjustments
+        + statement.trade_pnl
+        - statement.fees
+        - statement.taxes
+        - statement.distributions;
+
+    // 4. The closing balance is the opening balance plus the movements of the month
+    statement.closing_balance = opening_balance + statement.net_change;
+
+    Ok(statement)
+}
+
+fn month_boundaries(
+    year: i32,
+    month: u32,
+) -> Result<(NaiveDateTime, NaiveDateTime), Box<dyn Error>> {
+    let start = NaiveDate::from_ymd_opt(year, month, 1)
+        .ok_or_else(|| format!("Invalid month {}-{}", year, month))?;
+    let end = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)
+    }
+    .ok_or_else(|| format!("Invalid month {}-{}", year, month))?;
+
+    Ok((
+        start.and_hms_opt(0, 0, 0).unwrap(),
+        end.and_hms_opt(0, 0, 0).unwrap(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks::read_transaction_db_mocks::MockDatabase;
+    use model::ReadTransactionDB;
+
+    fn statement(
+        database: &mut MockDatabase,
+        year: i32,
+        month: u32,
+    ) -> Result<AccountStatement, Box<dyn Error>> {
+        let transactions = database.all_transactions(Uuid::new_v4(), &Currency::USD)?;
+        summarize(Uuid::new_v4(), year, month, &Currency::USD, &transactions)
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_statement_empty_month() {
+        let mut database = MockDatabase::new();
+
+        let statement = statement(&mut database, 2023, 5).unwrap();
+
+        assert_eq!(statement.opening_balance, dec!(0));
+        assert_eq!(statement.net_change, dec!(0));
+        assert_eq!(statement.closing_balance, dec!(0));
+    }
+
+    #[test]
+    fn test_statement_with_deposits_winning_trade_and_taxes() {
+        let mut database = MockDatabase::new();
+        let trade_id = Uuid::new_v4();
+
+        // Previous month
+        database.set_transaction_at(TransactionCategory::Deposit, dec!(1000), date(2023, 4, 10));
+
+        // Current month
+        database.set_transaction_at(TransactionCategory::Deposit, dec!(500), date(2023, 5, 1));
+        database.set_transaction_at(
+            TransactionCategory::FundTrade(trade_id),
+            dec!(400),
+            date(2023, 5, 2),
+        );
+        database.set_transaction_at(
+            TransactionCategory::OpenTrade(trade_id),
+            dec!(400),
+            date(2023, 5, 3),
+        );
+        database.set_transaction_at(
+            TransactionCategory::FeeOpen(trade_id),
+            dec!(1),
+            date(2023, 5, 3),
+        );
+        database.set_transaction_at(
+            TransactionCategory::CloseTarget(trade_id),
+            dec!(600),
+            date(2023, 5, 20),
+        );
+        database.set_transaction_at(
+            TransactionCategory::FeeClose(trade_id),
+            dec!(1),
+            date(2023, 5, 20),
+        );
+        database.set_transaction_at(
+            TransactionCategory::PaymentFromTrade(trade_id),
+            dec!(598),
+            date(2023, 5, 21),
+        );
+        database.set_transaction_at(
+            TransactionCategory::WithdrawalTax,
+            dec!(40),
+            date(2023, 5, 31),
+        );
+
+        // Next month
+        database.set_transaction_at(TransactionCategory::Withdrawal, dec!(100), date(2023, 6, 1));
+
+        let statement = statement(&mut database, 2023, 5).unwrap();
+
+        assert_eq!(statement.opening_balance, dec!(1000));
+        assert_eq!(statement.deposits, dec!(500));
+        assert_eq!(statement.withdrawals, dec!(0));
+        assert_eq!(statement.dividends, dec!(0));
+        assert_eq!(statement.trade_pnl, dec!(200));
+        assert_eq!(statement.fees, dec!(2));
+        assert_eq!(statement.taxes, dec!(40));
+        assert_eq!(statement.distributions, dec!(0));
+        assert_eq!(statement.net_change, dec!(658));
+        assert_eq!(statement.closing_balance, dec!(1658));
+    }
+
+    #[test]
+    fn test_statement_with_dividends() {
+        let mut database = MockDatabase::new();
+        database.set_transaction_at(TransactionCategory::Deposit, dec!(1000), date(2023, 4, 10));
+        database.set_transaction_at(
+            TransactionCategory::Dividend(None),
+            dec!(12),
+            date(2023, 4, 20),
+        );
+        database.set_transaction_at(
+            TransactionCategory::Dividend(Some(Uuid::new_v4())),
+            dec!(8),
+            date(2023, 5, 15),
+        );
+
+        let statement = statement(&mut database, 2023, 5).unwrap();
+
+        assert_eq!(statement.opening_balance, dec!(1012));
+        assert_eq!(statement.dividends, dec!(8));
+        assert_eq!(statement.net_change, dec!(8));
+        assert_eq!(statement.closing_balance, dec!(1020));
+    }
+
+    #[test]
+    fn test_statement_with_interest() {
+        let mut database = MockDatabase::new();
+        database.set_transaction_at(TransactionCategory::Deposit, dec!(1000), date(2023, 4, 10));
+        database.set_transaction_at(TransactionCategory::Interest, dec!(4), date(2023, 4, 30));
+        database.set_transaction_at(TransactionCategory::Interest, dec!(5), date(2023, 5, 1));
+        database.set_transaction_at(TransactionCategory::Interest, dec!(-7), date(2023, 5, 31));
+
+        let statement = statement(&mut database, 2023, 5).unwrap();
+
+        assert_eq!(statement.opening_balance, dec!(1004));
+        assert_eq!(statement.interest, dec!(-2));
+        assert_eq!(statement.net_change, dec!(-2));
+        assert_eq!(statement.closing_balance, dec!(1002));
+    }
+
+    #[test]
+    fn test_statement_with_adjustments() {
+        let mut database = MockDatabase::new();
+        database.set_transaction_at(TransactionCategory::Deposit, dec!(1000), date(2023, 5, 2));
+        let adjustment = || TransactionCategory::Adjustment {
+            reason: "Correction".to_string(),
+        };
+        database.set_transaction_at(adjustment(), dec!(30), date(2023, 5, 10));
+        database.set_transaction_at(adjustment(), dec!(-5), date(2023, 5, 20));
+
+        let statement = statement(&mut database, 2023, 5).unwrap();
+
+        assert_eq!(statement.adjustments, dec!(25));
+        assert_eq!(statement.net_change, dec!(1025));
+        assert_eq!(statement.closing_balance, dec!(1025));
+    }
+
+    #[test]
+    fn test_statement_with_rebates_above_fees() {
+        let mut database = MockDatabase::new();
+        let trade_id = Uuid::new_v4();
+        database.set_transaction_at(TransactionCategory::Deposit, dec!(1000), date(2023, 4, 10));
+        database.set_transaction_at(
+            TransactionCategory::FeeOpen(trade_id),
+            dec!(1),
+            date(2023, 5, 3),
+        );
+        database.set_transaction_at(
+            TransactionCategory::FeeClose(trade_id),
+            dec!(1),
+            date(2023, 5, 20),
+        );
+        database.set_transaction_at(
+            TransactionCategory::FeeRebate(trade_id),
+            dec!(3),
+            date(2023, 5, 20),
+        );
+
+        let statement = statement(&mut database, 2023, 5).unwrap();
+
+        assert_eq!(statement.fees, dec!(-1));
+        assert_eq!(statement.net_change, dec!(1));
+        assert_eq!(statement.closing_balance, dec!(1001));
+    }
+
+    #[test]
+    fn test_statement_december() {
+        let mut database = MockDatabase::new();
+        database.set_transaction_at(TransactionCategory::Deposit, dec!(100), date(2023, 12, 31));
+        database.set_transaction_at(TransactionCategory::Deposit, dec!(100), date(2024, 1, 1));
+
+        let statement = statement(&mut database, 2023, 12).unwrap();
+
+        assert_eq!(statement.deposits, dec!(100));
+        assert_eq!(statement.closing_balance, dec!(100));
+    }
+
+    #[test]
+    fn test_statement_invalid_month() {
+        let mut database = MockDatabase::new();
+        assert!(statement(&mut database, 2023, 13).is_err());
+    }
+}
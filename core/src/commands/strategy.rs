@@ -0,0 +1,72 @@
+use crate::analytics::{average_r_multiple, performance_stats, PerformanceStats};
+use model::{DatabaseFactory, Status, Strategy, Trade};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::error::Error;
+use uuid::Uuid;
+
+/// Calculates the performance of the closed trades of an account grouped by strategy.
+/// Strategies without closed trades and trades without a strategy are not included.
+pub fn performance_by_strategy(
+    account_id: Uuid,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Vec<(Strategy, PerformanceStats)>, Box<dyn Error>> {
+    // 1. Read the closed trades
+    let trades = closed_trades(account_id, database)?;
+
+    // 2. Group them by strategy
+    let mut performance = Vec::new();
+    for strategy in database.strategy_read().read_all_strategies()? {
+        let strategy_trades: Vec<_> = trades
+            .iter()
+            .filter(|trade| trade.strategy_id == Some(strategy.id))
+            .cloned()
+            .collect();
+        if !strategy_trades.is_empty() {
+            let stats = performance_stats(&strategy_trades);
+            performance.push((strategy, stats));
+        }
+    }
+
+    Ok(performance)
+}
+
+/// Calculates the average R-multiple of the closed trades of an account for each strategy.
+/// Trades without risk are excluded, so a strategy whose trades did not risk money has `None`.
+/// Strategies without closed trades and trades without a strategy are not included.
+pub fn average_r_by_strategy(
+    account_id: Uuid,
+    database: &mut dyn DatabaseFactory,
+) -> Result<HashMap<Uuid, Option<Decimal>>, Box<dyn Error>> {
+    // 1. Read the closed trades
+    let trades = closed_trades(account_id, database)?;
+
+    // 2. Group them by strategy
+    let mut grouped: HashMap<Uuid, Vec<Trade>> = HashMap::new();
+    for trade in trades {
+        if let Some(strategy_id) = trade.strategy_id {
+            grouped.entry(strategy_id).or_default().push(trade);
+        }
+    }
+
+    // 3. Average the R-multiples of each strategy
+    Ok(grouped
+        .into_iter()
+        .map(|(strategy_id, trades)| (strategy_id, average_r_multiple(&trades)))
+        .collect())
+}
+
+fn closed_trades(
+    account_id: Uuid,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Vec<Trade>, Box<dyn Error>> {
+    let mut trades = Vec::new();
+    for status in [Status::ClosedTarget, Status::ClosedStopLoss] {
+        trades.extend(
+            database
+                .trade_read()
+                .read_trades_with_status(account_id, status)?,
+        );
+    }
+    Ok(trades)
+}
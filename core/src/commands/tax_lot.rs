@@ -0,0 +1,27 @@
+use crate::calculators_trade::TaxLotCalculator;
+use model::{DatabaseFactory, RealizedLot, Trade};
+use std::error::Error;
+
+/// Matches again all the executions of a trade with the lot method of its account
+/// and replaces the realized lots of the trade with the result.
+pub fn record_realized_lots(
+    trade: &Trade,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Vec<RealizedLot>, Box<dyn Error>> {
+    // 1. Read the executions of the trade and the lot method of its account
+    let account = database.account_read().id(trade.account_id)?;
+    let executions = database
+        .execution_read()
+        .read_executions_for_trade(trade.id)?;
+
+    // 2. Match the closing executions against the opening ones
+    let lots = TaxLotCalculator::calculate(trade, &executions, account.lot_method);
+
+    // 3. Replace the lots of the trade, so executions backfilled out of order are matched again
+    database
+        .realized_lot_write()
+        .delete_realized_lots(trade.id)?;
+    lots.iter()
+        .map(|lot| database.realized_lot_write().create_realized_lot(lot))
+        .collect()
+}
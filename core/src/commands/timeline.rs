@@ -0,0 +1,134 @@
+use crate::commands::trade::{MODIFY_STOP_LOG, MODIFY_TARGET_LOG};
+use chrono::NaiveDateTime;
+use model::{BrokerLog, DatabaseFactory, Execution, TransactionCategory};
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Something that happened to a trade at a given moment.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TimelineEvent {
+    pub timestamp: NaiveDateTime,
+    pub kind: TimelineEventKind,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum TimelineEventKind {
+    Created,
+    Funded,
+    Submitted,
+
+    /// A response of the broker, for example the result of a sync.
+    BrokerLog(String),
+    Execution(Execution),
+    StopModified(Decimal),
+    TargetModified(Decimal),
+    ClosedTarget,
+    ClosedStopLoss,
+}
+
+impl TimelineEventKind {
+    /// The order of the events that happened at the same moment.
+    fn priority(&self) -> u8 {
+        match self {
+            TimelineEventKind::Created => 0,
+            TimelineEventKind::Funded => 1,
+            TimelineEventKind::Submitted => 2,
+            TimelineEventKind::BrokerLog(_) => 3,
+            TimelineEventKind::Execution(_) => 4,
+            TimelineEventKind::StopModified(_) => 5,
+            TimelineEventKind::TargetModified(_) => 5,
+            TimelineEventKind::ClosedTarget => 6,
+            TimelineEventKind::ClosedStopLoss => 6,
+        }
+    }
+}
+
+/// All the events of a trade sorted by timestamp, and by kind when they happened at the same moment.
+///
+/// The submission is the timestamp of the entry order. If the broker did not report it,
+/// the first broker log is used, given that submitting is the first call to the broker.
+pub fn trade_timeline(
+    trade_id: Uuid,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Vec<TimelineEvent>, Box<dyn Error>> {
+    // 1. Read everything that was recorded for the trade
+    let trade = database.trade_read().read_trade(trade_id)?;
+    let mut logs = database.log_read().read_all_logs_for_trade(trade_id)?;
+    logs.sort_by_key(|log| log.created_at);
+    let executions = database
+        .execution_read()
+        .read_executions_for_trade(trade_id)?;
+    let transactions = database
+        .transaction_read()
+        .all_trade_transactions(trade_id)?;
+
+    let mut events = vec![TimelineEvent {
+        timestamp: trade.created_at,
+        kind: TimelineEventKind::Created,
+    }];
+
+    // 2. Transactions of the trade
+    for transaction in transactions {
+        let kind = match transaction.category {
+            TransactionCategory::FundTrade(_) => TimelineEventKind::Funded,
+            TransactionCategory::CloseTarget(_) => TimelineEventKind::ClosedTarget,
+            TransactionCategory::CloseSafetyStop(_)
+            | TransactionCategory::CloseSafetyStopSlippage(_) => TimelineEventKind::ClosedStopLoss,
+            _ => continue, // The rest of the transactions are the money of the events above.
+        };
+        events.push(TimelineEvent {
+            timestamp: transaction.created_at,
+            kind,
+        });
+    }
+
+    // 3. Submission and broker logs
+    let mut logs = logs.into_iter();
+    match trade.entry.submitted_at {
+        Some(submitted_at) => events.push(TimelineEvent {
+            timestamp: submitted_at,
+            kind: TimelineEventKind::Submitted,
+        }),
+        None => {
+            if let Some(log) = logs.next() {
+                events.push(TimelineEvent {
+                    timestamp: log.created_at,
+                    kind: TimelineEventKind::Submitted,
+                });
+            }
+        }
+    }
+    events.extend(logs.map(|log| TimelineEvent {
+        timestamp: log.created_at,
+        kind: log_kind(&log),
+    }));
+
+    // 4. Executions
+    events.extend(executions.into_iter().map(|execution| TimelineEvent {
+        timestamp: execution.executed_at,
+        kind: TimelineEventKind::Execution(execution),
+    }));
+
+    // 5. Sort the events without changing the order of identical ones
+    events.sort_by_key(|event| (event.timestamp, event.kind.priority()));
+
+    Ok(events)
+}
+
+fn log_kind(log: &BrokerLog) -> TimelineEventKind {
+    let price = |prefix: &str| {
+        log.log
+            .strip_prefix(prefix)
+            .and_then(|price| Decimal::from_str(price.trim()).ok())
+    };
+
+    if let Some(price) = price(MODIFY_STOP_LOG) {
+        TimelineEventKind::StopModified(price)
+    } else if let Some(price) = price(MODIFY_TARGET_LOG) {
+        TimelineEventKind::TargetModified(price)
+    } else {
+        TimelineEventKind::BrokerLog(log.log.clone())
+    }
+}
@@ -1,56 +1,109 @@
+use crate::calculators_trade::{TickSizeCalculator, TradeFees};
 use crate::commands;
+use crate::validators::funding::RuleWarning;
 use model::{
-    Account, AccountBalance, Broker, BrokerLog, DatabaseFactory, DraftTrade, Order, OrderStatus,
-    Status, Trade, TradeBalance, Transaction,
+    Account, AccountBalance, Broker, BrokerError, BrokerErrorCode, BrokerLog, BrokerValidation,
+    Currency, DatabaseFactory, DraftTrade, Order, OrderCategory, OrderStatus, ReadTradeDB, Status,
+    TickSizePolicy, TimeInForce, Trade, TradeBalance, Transaction,
 };
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::error::Error;
+use std::thread;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
 
 pub fn create_trade(
     trade: DraftTrade,
     stop_price: Decimal,
     entry_price: Decimal,
     target_price: Decimal,
+    confirmation: Option<&str>,
     database: &mut dyn DatabaseFactory,
 ) -> Result<Trade, Box<dyn std::error::Error>> {
-    // 1. Create Stop-loss Order
+    // 1. Read the account again, the draft may have an old copy of its settings. Then round
+    //    the prices to the tick size if the account allows it
+    let account = database.account_read().id(trade.account.id)?;
+    let (stop_price, entry_price, target_price) =
+        match (trade.trading_vehicle.tick_size, account.tick_size_policy) {
+            (Some(tick_size), TickSizePolicy::Round) => (
+                TickSizeCalculator::round(stop_price, tick_size),
+                TickSizeCalculator::round(entry_price, tick_size),
+                TickSizeCalculator::round(target_price, tick_size),
+            ),
+            _ => (stop_price, entry_price, target_price),
+        };
+
+    // 2. Validate the prices, that big trades are confirmed, the rules, that the strategy exists,
+    //    that the reference is unique in the account and the length of the thesis
+    crate::validators::trade::can_create_at_prices(&trade, stop_price, entry_price, target_price)?;
+    crate::validators::trade::can_create(&trade, &account, entry_price, confirmation)?;
+    let rules = database.rule_read().read_all_rules(trade.account.id)?;
+    crate::validators::trade::can_create_with_rules(
+        &trade,
+        stop_price,
+        entry_price,
+        target_price,
+        &rules,
+    )?;
+    if let Some(strategy_id) = trade.strategy_id {
+        database.strategy_read().read_strategy(strategy_id)?;
+    }
+    if let Some(reference) = &trade.reference {
+        let existing = database
+            .trade_read()
+            .trade_by_reference(trade.account.id, reference)?;
+        crate::validators::trade::can_use_reference(reference, existing.as_ref())?;
+    }
+    if let Some(thesis) = &trade.thesis {
+        crate::validators::trade::can_use_thesis(thesis)?;
+    }
+
+    // 3. Create Stop-loss Order
     let stop = commands::order::create_stop(
         trade.trading_vehicle.id,
         trade.quantity,
+        trade.quantity_decimal,
         stop_price,
         &trade.currency,
         &trade.category,
         database,
     )?;
 
-    // 2. Create Entry Order
+    // 4. Create Entry Order
     let entry = commands::order::create_entry(
         trade.trading_vehicle.id,
         trade.quantity,
+        trade.quantity_decimal,
         entry_price,
         &trade.currency,
         &trade.category,
         database,
     )?;
 
-    // 3. Create Target Order
+    // 5. Create Target Order
     let target = commands::order::create_target(
         trade.trading_vehicle.id,
         trade.quantity,
+        trade.quantity_decimal,
         target_price,
         &trade.currency,
         &trade.category,
         database,
     )?;
 
-    // 4. Create Trade
+    // 6. Create Trade
     let draft = DraftTrade {
         account: trade.account,
         trading_vehicle: trade.trading_vehicle,
         quantity: trade.quantity,
+        quantity_decimal: trade.quantity_decimal,
         currency: trade.currency,
         category: trade.category,
+        strategy_id: trade.strategy_id,
+        reference: trade.reference,
+        thesis: trade.thesis,
+        context: trade.context,
     };
 
     database
@@ -65,7 +118,7 @@ pub fn update_status(
 ) -> Result<(Trade, Option<Transaction>), Box<dyn Error>> {
     match status {
         Status::Filled if trade.status == Status::Submitted => {
-            let (trade, tx) = fill_trade(trade, dec!(0), database)?;
+            let (trade, tx) = fill_trade(trade, None, database)?;
             return Ok((trade, Some(tx)));
         }
         Status::Filled if trade.status == Status::Filled => {
@@ -77,14 +130,14 @@ pub fn update_status(
         Status::ClosedStopLoss => {
             if trade.status == Status::Submitted {
                 // We also update the trade entry
-                fill_trade(trade, dec!(0), database)?;
+                fill_trade(trade, None, database)?;
             }
 
             // We only update the trade target once
             let trade = database.trade_read().read_trade(trade.id)?;
             if trade.status == Status::Filled {
                 // We also update the trade stop loss
-                let (trade, _) = stop_executed(&trade, dec!(0), database)?;
+                let (trade, _) = stop_executed(&trade, None, database)?;
                 let (tx, _, _) = commands::transaction::transfer_to_account_from(&trade, database)?;
 
                 return Ok((trade, Some(tx)));
@@ -96,15 +149,18 @@ pub fn update_status(
         Status::ClosedTarget => {
             if trade.status == Status::Submitted {
                 // We also update the trade entry
-                fill_trade(trade, dec!(0), database)?;
+                fill_trade(trade, None, database)?;
             }
 
             // We only update the trade target once
             let trade = database.trade_read().read_trade(trade.id)?;
-            if trade.status == Status::Filled || trade.status == Status::Canceled {
-                // It can be canceled if the target was updated.
+            if trade.status == Status::Filled
+                || trade.status == Status::Canceled
+                || trade.status == Status::ClosingPending
+            {
+                // It can be canceled if the target was updated, or closing if it was closed at a limit.
                 // We also update the trade stop loss
-                let (trade, _) = target_executed(&trade, dec!(0), database)?;
+                let (trade, _) = target_executed(&trade, None, database)?;
                 let (tx, _, _) = commands::transaction::transfer_to_account_from(&trade, database)?;
 
                 return Ok((trade, Some(tx)));
@@ -113,6 +169,9 @@ pub fn update_status(
         Status::Submitted if trade.status == Status::Submitted => {
             return Ok((trade.clone(), None));
         }
+        Status::ClosingPending if trade.status == Status::ClosingPending => {
+            return Ok((trade.clone(), None)); // The limit order is not filled yet.
+        }
         _ => {
             return Err(format!("Status can not be updated in trade: {:?}", status).into());
         }
@@ -120,13 +179,57 @@ pub fn update_status(
     unimplemented!()
 }
 
+/// Creates a new trade with the same setup as a previous one, for example to re-enter after a stop-out.
+/// Only the setup is copied: the new trade has its own orders, without broker state, balance or executions.
+pub fn clone_trade(
+    source_trade_id: Uuid,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Trade, Box<dyn std::error::Error>> {
+    // 1. Read the setup of the source trade
+    let source = database.trade_read().read_trade(source_trade_id)?;
+    let account = database.account_read().id(source.account_id)?;
+
+    // 2. Create the trade, so the prices are validated again
+    let sector = source.sector;
+    let draft = DraftTrade {
+        account,
+        trading_vehicle: source.trading_vehicle,
+        quantity: source.entry.quantity as i64,
+        quantity_decimal: source.entry.quantity_decimal,
+        currency: source.currency,
+        category: source.category,
+        strategy_id: source.strategy_id,
+        reference: None,
+        thesis: source.thesis,
+        context: source.context,
+    };
+    let trade = create_trade(
+        draft,
+        source.safety_stop.unit_price,
+        source.entry.unit_price,
+        source.target.unit_price,
+        None,
+        database,
+    )?;
+
+    // 3. Keep the sector, so the clone is grouped with the source
+    match sector {
+        Some(sector) => database
+            .trade_write()
+            .update_trade_sector(&trade, Some(sector)),
+        None => Ok(trade),
+    }
+}
+
+/// Fills the entry of a trade. Without a fee, the fee is calculated with the fee model of the account.
 pub fn fill_trade(
     trade: &Trade,
-    fee: Decimal,
+    fee: Option<Decimal>,
     database: &mut dyn DatabaseFactory,
 ) -> Result<(Trade, Transaction), Box<dyn Error>> {
     // Create Transaction to pay for fees
-    if fee > dec!(0) {
+    let fee = fee_of_fill(trade, &trade.entry, fee, database)?;
+    if fee != dec!(0) {
         commands::transaction::transfer_opening_fee(fee, trade, database)?;
     }
 
@@ -150,11 +253,12 @@ pub fn fill_trade(
 
 pub fn target_executed(
     trade: &Trade,
-    fee: Decimal,
+    fee: Option<Decimal>,
     database: &mut dyn DatabaseFactory,
 ) -> Result<(Trade, Transaction), Box<dyn Error>> {
     // 1. Create Transaction to pay for fees
-    if fee > dec!(0) {
+    let fee = fee_of_fill(trade, &trade.target, fee, database)?;
+    if fee != dec!(0) {
         commands::transaction::transfer_closing_fee(fee, trade, database)?;
     }
 
@@ -178,11 +282,12 @@ pub fn target_executed(
 
 pub fn stop_executed(
     trade: &Trade,
-    fee: Decimal,
+    fee: Option<Decimal>,
     database: &mut dyn DatabaseFactory,
 ) -> Result<(Trade, Transaction), Box<dyn Error>> {
     // 1. Create Transaction to pay for fees
-    if fee > dec!(0) {
+    let fee = fee_of_fill(trade, &trade.safety_stop, fee, database)?;
+    if fee != dec!(0) {
         commands::transaction::transfer_closing_fee(fee, trade, database)?;
     }
 
@@ -204,9 +309,26 @@ pub fn stop_executed(
     Ok((trade, tx))
 }
 
+/// The fee that was entered for a fill or, without it, the fee of the fee model of the account.
+/// Fees are never negative, so they are rejected when they are paid.
+fn fee_of_fill(
+    trade: &Trade,
+    order: &Order,
+    fee: Option<Decimal>,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Decimal, Box<dyn Error>> {
+    match fee {
+        Some(fee) => Ok(fee),
+        None => {
+            let account = database.account_read().id(trade.account_id)?;
+            TradeFees::from_model(&account.fee_model, order)
+        }
+    }
+}
+
 pub fn stop_acquired(
     trade: &Trade,
-    fee: Decimal,
+    fee: Option<Decimal>,
     database: &mut dyn DatabaseFactory,
 ) -> Result<(Transaction, Transaction, TradeBalance, AccountBalance), Box<dyn std::error::Error>> {
     let (trade, tx_stop) = stop_executed(trade, fee, database)?;
@@ -217,7 +339,7 @@ pub fn stop_acquired(
 
 pub fn target_acquired(
     trade: &Trade,
-    fee: Decimal,
+    fee: Option<Decimal>,
     database: &mut dyn DatabaseFactory,
 ) -> Result<(Transaction, Transaction, TradeBalance, AccountBalance), Box<dyn std::error::Error>> {
     let (trade, tx_target) = target_executed(trade, fee, database)?;
@@ -269,6 +391,245 @@ pub fn cancel_submitted(
     Ok((trade_o, account_o, tx))
 }
 
+/// The trade and the result of canceling it, with the error as text if it failed.
+pub type BulkCancelResult = (Uuid, Result<(), String>);
+
+/// Cancels all the trades of an account with a status, funded or submitted.
+/// Every trade is canceled on its own, so a trade that fails does not stop the others.
+/// The funds of every canceled trade are transferred back to the account.
+pub fn bulk_cancel(
+    account_id: Uuid,
+    status: Status,
+    database: &mut dyn DatabaseFactory,
+    broker: &mut dyn Broker,
+) -> Result<Vec<BulkCancelResult>, Box<dyn Error>> {
+    // 1. Verify that the trades with the status can be canceled
+    crate::validators::trade::can_bulk_cancel(status)?;
+
+    // 2. Read the trades to cancel
+    let trades = database
+        .trade_read()
+        .read_trades_with_status(account_id, status)?;
+
+    // 3. Cancel every trade and keep the result of each one
+    let results = trades
+        .iter()
+        .map(|trade| {
+            let result = match status {
+                Status::Submitted => cancel_submitted(trade, database, broker),
+                _ => cancel_funded(trade, database),
+            };
+            (
+                trade.id,
+                result.map(|_| ()).map_err(|error| error.to_string()),
+            )
+        })
+        .collect();
+
+    Ok(results)
+}
+
+/// The trade and the balance once it was closed, with the error as text if it failed.
+pub type BulkCloseResult = (Uuid, Result<TradeBalance, String>);
+
+/// Closes all the filled trades of an account with a market order, for example at the end of the day.
+/// Every trade is closed on its own, so a trade that fails does not stop the others.
+pub fn close_all_market(
+    account_id: Uuid,
+    database: &mut dyn DatabaseFactory,
+    broker: &mut dyn Broker,
+) -> Result<Vec<BulkCloseResult>, Box<dyn Error>> {
+    // 1. Read the trades that are in the market
+    let trades = database
+        .trade_read()
+        .read_trades_with_status(account_id, Status::Filled)?;
+
+    // 2. Close every trade and keep the result of each one
+    let results = trades
+        .iter()
+        .map(|trade| {
+            (
+                trade.id,
+                close(trade, database, broker)
+                    .map(|(balance, _)| balance)
+                    .map_err(|error| error.to_string()),
+            )
+        })
+        .collect();
+
+    Ok(results)
+}
+
+/// Result of canceling every working order of an account in the broker.
+#[derive(Debug, PartialEq)]
+pub struct EmergencyCancelReport {
+    /// Broker ids of the orders that the broker canceled.
+    pub canceled_orders: Vec<Uuid>,
+
+    /// The trades with a canceled order and their status once they were synced,
+    /// with the error as text if the sync failed.
+    pub trades: Vec<(Uuid, Result<Status, String>)>,
+}
+
+/// Cancels every working order of an account in the broker first, and then syncs the trades
+/// that had one of the canceled orders. A submitted trade whose entry was canceled is canceled
+/// and its funds are transferred back to the account. A filled trade is still in the market,
+/// so it keeps its status without the canceled stop or target. A closing trade whose closing
+/// order was canceled is still in the market too, so it is filled again.
+pub fn emergency_cancel_all(
+    account: &Account,
+    database: &mut dyn DatabaseFactory,
+    broker: &mut dyn Broker,
+) -> Result<EmergencyCancelReport, Box<dyn Error>> {
+    // 1. Cancel all the orders in the broker
+    let canceled_orders = broker.cancel_all_orders(account)?;
+
+    // 2. Find the trades that had a canceled order
+    let mut trades = Vec::new();
+    for status in [
+        Status::Submitted,
+        Status::PartiallyFilled,
+        Status::Filled,
+        Status::ClosingPending,
+    ] {
+        trades.append(
+            &mut database
+                .trade_read()
+                .read_trades_with_status(account.id, status)?,
+        );
+    }
+    trades.retain(|trade| {
+        [&trade.entry, &trade.safety_stop, &trade.target]
+            .iter()
+            .any(|order| {
+                order
+                    .broker_order_id
+                    .is_some_and(|id| canceled_orders.contains(&id))
+            })
+    });
+
+    // 3. Sync every trade on its own, so a trade that fails does not stop the others
+    let trades = trades
+        .iter()
+        .map(|trade| {
+            (
+                trade.id,
+                reconcile_canceled(trade, account, database, broker)
+                    .map_err(|error| error.to_string()),
+            )
+        })
+        .collect();
+
+    Ok(EmergencyCancelReport {
+        canceled_orders,
+        trades,
+    })
+}
+
+/// Repairs the orders of the trades of an account that are not closed and whose orders are done
+/// in the broker while they still work locally. It returns the ids of the repaired orders.
+///
+/// An order that the broker canceled, expired or rejected is saved with the status of the broker.
+/// If the broker already closed the trade, the orders that still work in the broker are canceled
+/// there, so no leg is left behind. Unlike a sync, the status and the money of the trades are
+/// not changed, and the orders that were filled are left for the next sync.
+pub fn orphan_order_cleanup(
+    account: &Account,
+    database: &mut dyn DatabaseFactory,
+    broker: &mut dyn Broker,
+) -> Result<Vec<Uuid>, Box<dyn Error>> {
+    // 1. Read the trades that have orders in the broker
+    let mut trades = Vec::new();
+    for status in [
+        Status::Submitted,
+        Status::PartiallyFilled,
+        Status::Filled,
+        Status::ClosingPending,
+    ] {
+        trades.append(
+            &mut database
+                .trade_read()
+                .read_trades_with_status(account.id, status)?,
+        );
+    }
+
+    let mut repaired = Vec::new();
+    for trade in trades {
+        // 2. Ask the broker for the orders of the trade
+        let (status, orders, _) = broker.sync_trade(&trade, account)?;
+
+        for local in [&trade.entry, &trade.safety_stop, &trade.target] {
+            if !is_working(local) {
+                continue;
+            }
+            let remote = orders.iter().find(|order| order.id == local.id);
+
+            // 3. Save the orders that are done in the broker without being filled
+            if let Some(remote) = remote.filter(|remote| {
+                matches!(
+                    remote.status,
+                    OrderStatus::Canceled | OrderStatus::Expired | OrderStatus::Rejected
+                )
+            }) {
+                let mut order = local.clone();
+                order.status = remote.status;
+                order.cancelled_at = remote.cancelled_at;
+                order.expired_at = remote.expired_at;
+                database.order_write().update(&order)?;
+                repaired.push(order.id);
+                continue;
+            }
+
+            // 4. Cancel the legs that still work in the broker of a trade that it closed
+            if status.is_terminal() && remote.is_none_or(is_working) {
+                broker.cancel_order(local, account)?;
+                let mut order = local.clone();
+                order.status = OrderStatus::Canceled;
+                order.cancelled_at = Some(database.clock().now());
+                database.order_write().update(&order)?;
+                repaired.push(order.id);
+            }
+        }
+    }
+
+    Ok(repaired)
+}
+
+fn reconcile_canceled(
+    trade: &Trade,
+    account: &Account,
+    database: &mut dyn DatabaseFactory,
+    broker: &mut dyn Broker,
+) -> Result<Status, Box<dyn Error>> {
+    sync_with_broker(trade, account, database, broker)?;
+
+    let trade = database.trade_read().read_trade(trade.id)?;
+    if trade.status == Status::Submitted && trade.entry.status == OrderStatus::Canceled {
+        database
+            .trade_write()
+            .update_trade_status(Status::Canceled, &trade)?;
+        commands::transaction::transfer_to_account_from(&trade, database)?;
+        return Ok(Status::Canceled);
+    }
+    if trade.status == Status::ClosingPending && trade.target.status == OrderStatus::Canceled {
+        database
+            .trade_write()
+            .update_trade_status(Status::Filled, &trade)?;
+        return Ok(Status::Filled);
+    }
+    Ok(trade.status)
+}
+
+/// The prefix of the log written when the price of the stop is modified, followed by the new price.
+pub const MODIFY_STOP_LOG: &str = "modify_stop";
+
+/// The prefix of the log written when the price of the target is modified, followed by the new price.
+pub const MODIFY_TARGET_LOG: &str = "modify_target";
+
+/// The prefix of the log written when an order could not be replaced and it was modified instead,
+/// followed by the id of the order and the milliseconds that the broker took to modify it.
+pub const NAKED_WINDOW_LOG: &str = "naked_window";
+
 pub fn modify_stop(
     trade: &Trade,
     account: &Account,
@@ -279,8 +640,16 @@ pub fn modify_stop(
     // 1. Verify trade can be modified
     crate::validators::trade::can_modify_stop(trade, new_stop_price)?;
 
-    // 2. Update Trade on the broker
-    let new_broker_id = broker.modify_stop(trade, account, new_stop_price)?;
+    // 2. Replace the stop on the broker, or modify it if the broker can not replace it
+    let new_broker_id = replace_order(
+        &trade.safety_stop,
+        trade,
+        account,
+        new_stop_price,
+        |broker| broker.modify_stop(trade, account, new_stop_price),
+        broker,
+        database,
+    )?;
 
     // 3. Modify stop order
     commands::order::modify(
@@ -290,12 +659,98 @@ pub fn modify_stop(
         &mut *database.order_write(),
     )?;
 
-    // 4. Refresh Trade
+    // 4. Save log in the DB, the broker does not return one for modifications
+    database
+        .log_write()
+        .create_log(&format!("{} {}", MODIFY_STOP_LOG, new_stop_price), trade)?;
+
+    // 5. Refresh Trade
     let trade = database.trade_read().read_trade(trade.id)?;
 
     Ok(trade)
 }
 
+/// Makes the safety stop of a trade that was not submitted a trailing stop, that follows the price
+/// by a percent or by a price. The stop price is kept until the broker moves it.
+pub fn trail_stop(
+    trade: &Trade,
+    trail_percent: Option<Decimal>,
+    trail_price: Option<Decimal>,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Trade, Box<dyn std::error::Error>> {
+    // 1. Verify the stop can trail the price
+    crate::validators::trade::can_trail_stop(trade, trail_percent, trail_price)?;
+
+    // 2. Save the trail in the stop order
+    let mut stop = trade.safety_stop.clone();
+    stop.category = OrderCategory::TrailingStop {
+        trail_percent,
+        trail_price,
+    };
+    database.order_write().update(&stop)?;
+
+    // 3. Refresh Trade
+    database.trade_read().read_trade(trade.id)
+}
+
+/// Changes how long the entry of a trade that was not submitted works in the broker.
+pub fn set_entry_time_in_force(
+    trade: &Trade,
+    time_in_force: TimeInForce,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Trade, Box<dyn std::error::Error>> {
+    // 1. Verify the entry can use the time in force
+    crate::validators::trade::can_use_time_in_force(trade, time_in_force)?;
+
+    // 2. Save the time in force in the entry order
+    let mut entry = trade.entry.clone();
+    entry.time_in_force = time_in_force;
+    database.order_write().update(&entry)?;
+
+    // 3. Refresh Trade
+    database.trade_read().read_trade(trade.id)
+}
+
+/// Replaces an order of a trade on the broker in a single request.
+///
+/// If the broker can not replace the order, it falls back to `modify`, which may cancel the
+/// order and place a new one. The time the position could be without the order is saved in a log.
+/// Any other error of the broker is returned without modifying the order.
+fn replace_order(
+    order: &Order,
+    trade: &Trade,
+    account: &Account,
+    new_price: Decimal,
+    modify: impl FnOnce(&mut dyn Broker) -> Result<Uuid, Box<dyn Error>>,
+    broker: &mut dyn Broker,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Uuid, Box<dyn Error>> {
+    match broker.replace_order(order, account, new_price, order.quantity) {
+        Ok(broker_id) => Ok(broker_id),
+        Err(error) if is_replace_not_supported(error.as_ref()) => {
+            let start = Instant::now();
+            let broker_id = modify(broker)?;
+            database.log_write().create_log(
+                &format!(
+                    "{} {} {}ms",
+                    NAKED_WINDOW_LOG,
+                    order.id,
+                    start.elapsed().as_millis()
+                ),
+                trade,
+            )?;
+            Ok(broker_id)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+fn is_replace_not_supported(error: &(dyn Error + 'static)) -> bool {
+    error
+        .downcast_ref::<BrokerError>()
+        .is_some_and(|error| error.code == BrokerErrorCode::ReplaceNotSupported)
+}
+
 pub fn modify_target(
     trade: &Trade,
     account: &Account,
@@ -306,8 +761,16 @@ pub fn modify_target(
     // 1. Verify trade can be modified
     crate::validators::trade::can_modify_target(trade)?;
 
-    // 2. Update Trade on the broker
-    let new_broker_id = broker.modify_target(trade, account, new_price)?;
+    // 2. Replace the target on the broker, or modify it if the broker can not replace it
+    let new_broker_id = replace_order(
+        &trade.target,
+        trade,
+        account,
+        new_price,
+        |broker| broker.modify_target(trade, account, new_price),
+        broker,
+        database,
+    )?;
 
     // 3. Modify stop order
     commands::order::modify(
@@ -317,42 +780,108 @@ pub fn modify_target(
         &mut *database.order_write(),
     )?;
 
-    // 4. Refresh Trade
+    // 4. Save log in the DB, the broker does not return one for modifications
+    database
+        .log_write()
+        .create_log(&format!("{} {}", MODIFY_TARGET_LOG, new_price), trade)?;
+
+    // 5. Refresh Trade
     let trade = database.trade_read().read_trade(trade.id)?;
 
     Ok(trade)
 }
 
+/// Revises the thesis and the context of a trade. A value that is not given is kept.
+/// The previous thesis and context are kept in the thesis history.
+pub fn revise_thesis(
+    trade: &Trade,
+    thesis: Option<String>,
+    context: Option<String>,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Trade, Box<dyn std::error::Error>> {
+    // 1. Verify that the trade is still open and that the thesis is valid
+    crate::validators::trade::can_revise_thesis(trade)?;
+    if let Some(thesis) = &thesis {
+        crate::validators::trade::can_use_thesis(thesis)?;
+    }
+
+    // 2. Update the thesis and the context, keeping the previous ones in the history
+    let thesis = thesis.or_else(|| trade.thesis.clone());
+    let context = context.or_else(|| trade.context.clone());
+    database
+        .trade_write()
+        .update_trade_thesis(trade, thesis, context)
+}
+
+/// The funded trade with its transaction, balances and the breaches of the rules with level warning.
+pub type FundedTrade = (
+    Trade,
+    Transaction,
+    AccountBalance,
+    TradeBalance,
+    Vec<RuleWarning>,
+);
+
 pub fn fund(
     trade: &Trade,
     database: &mut dyn DatabaseFactory,
-) -> Result<(Trade, Transaction, AccountBalance, TradeBalance), Box<dyn std::error::Error>> {
+) -> Result<FundedTrade, Box<dyn std::error::Error>> {
     // 1. Validate that trade can be funded
-    crate::validators::funding::can_fund(trade, database)?;
+    let warnings = crate::validators::funding::can_fund(trade, database)?;
 
     // 2. Update trade status to funded
-    database
+    let trade = database
         .trade_write()
         .update_trade_status(Status::Funded, trade)?;
 
     // 3. Create transaction to fund the trade
     let (transaction, account_balance, trade_balance) =
-        commands::transaction::transfer_to_fund_trade(trade, database)?;
+        commands::transaction::transfer_to_fund_trade(&trade, database)?;
 
     // 4. Return data objects
-    Ok((trade.clone(), transaction, account_balance, trade_balance))
+    Ok((trade, transaction, account_balance, trade_balance, warnings))
+}
+
+/// The trade id, whether it can be funded and the reason why it can not.
+pub type FundableTrade = (Uuid, bool, Option<String>);
+
+/// Checks every new trade of an account against the funding validations without funding it.
+pub fn fundable(
+    account_id: Uuid,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Vec<FundableTrade>, Box<dyn Error>> {
+    let trades = database
+        .trade_read()
+        .read_trades_with_status(account_id, Status::New)?;
+
+    Ok(trades
+        .iter()
+        .map(
+            |trade| match crate::validators::funding::can_fund(trade, database) {
+                Ok(_) => (trade.id, true, None),
+                Err(error) => (trade.id, false, Some(error.message)),
+            },
+        )
+        .collect())
 }
 
+/// Submits a funded trade to the broker. Unless it is forced, a trade that duplicates another
+/// trade submitted inside the duplicate trade window of the account is rejected.
 pub fn submit(
     trade: &Trade,
+    force: bool,
     database: &mut dyn DatabaseFactory,
     broker: &mut dyn Broker,
 ) -> Result<(Trade, BrokerLog), Box<dyn std::error::Error>> {
-    // 1. Validate that Trade can be submitted
+    // 1. Validate that Trade can be submitted and that it is not a duplicate
     crate::validators::trade::can_submit(trade)?;
+    let account = database.account_read().id(trade.account_id)?;
+    if let (Some(window_seconds), false) = (account.duplicate_trade_window_seconds, force) {
+        let duplicate = recent_duplicate(trade, window_seconds, database)?;
+        crate::validators::trade::can_submit_without_duplicate(trade, duplicate.as_ref())?;
+    }
 
     // 2. Submit trade to broker
-    let account = database.account_read().id(trade.account_id)?;
     let (log, order_id) = broker.submit_trade(trade, &account)?;
 
     // 3. Save log in the DB
@@ -364,9 +893,11 @@ pub fn submit(
         .update_trade_status(Status::Submitted, trade)?;
 
     // 5. Update internal orders orders to submitted
-    database
-        .order_write()
-        .submit_of(&trade.safety_stop, order_id.stop)?;
+    if let Some(stop_id) = order_id.stop {
+        database
+            .order_write()
+            .submit_of(&trade.safety_stop, stop_id)?;
+    }
     database
         .order_write()
         .submit_of(&trade.entry, order_id.entry)?;
@@ -381,6 +912,55 @@ pub fn submit(
     Ok((trade, log))
 }
 
+/// Another trade of the account with the same symbol, direction, entry price and quantity
+/// that was submitted in the last `window_seconds`, whatever happened to it afterwards.
+/// A trade that was canceled is not a duplicate.
+fn recent_duplicate(
+    trade: &Trade,
+    window_seconds: u32,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Option<Trade>, Box<dyn Error>> {
+    let since = database.clock().now() - chrono::Duration::seconds(i64::from(window_seconds));
+    let trades = database.trade_read().read_trades_for_symbol(
+        trade.account_id,
+        &trade.trading_vehicle.symbol,
+        &[
+            Status::Submitted,
+            Status::PartiallyFilled,
+            Status::Filled,
+            Status::ClosingPending,
+            Status::ClosedStopLoss,
+            Status::ClosedTarget,
+        ],
+    )?;
+
+    Ok(trades.into_iter().find(|other| {
+        other.id != trade.id
+            && other.category == trade.category
+            && other.entry.unit_price == trade.entry.unit_price
+            && other.entry.quantity == trade.entry.quantity
+            && other.entry.quantity_decimal == trade.entry.quantity_decimal
+            && other
+                .entry
+                .submitted_at
+                .is_some_and(|submitted_at| submitted_at >= since)
+    }))
+}
+
+/// Validates a trade with the broker without submitting it.
+/// Nothing is stored, so the trade and the account are not modified.
+pub fn validate_with_broker(
+    trade: &Trade,
+    account: &Account,
+    broker: &mut dyn Broker,
+) -> Result<BrokerValidation, Box<dyn std::error::Error>> {
+    // 1. Validate that Trade could be submitted
+    crate::validators::trade::can_submit(trade)?;
+
+    // 2. Validate trade with the broker
+    broker.validate_trade(trade, account)
+}
+
 pub fn sync_with_broker(
     trade: &Trade,
     account: &Account,
@@ -402,12 +982,50 @@ pub fn sync_with_broker(
     let trade = database.trade_read().read_trade(trade.id)?; // We need to read the trade again to get the updated orders
     update_status(&trade, status, database)?;
 
-    // 5. Update Account Overview
+    // 5. Record when the trade was synced
+    let synced_at = database.clock().now();
+    database
+        .trade_write()
+        .update_trade_last_synced_at(&trade, synced_at)?;
+
+    // 6. Update Account Overview
     commands::balance::calculate_account(database, account, &trade.currency)?;
 
     Ok((status, orders, log))
 }
 
+/// Syncs the trade with the broker every `interval` until it reaches a terminal status
+/// or the `timeout` elapses. It returns the last state of the trade.
+///
+/// Every poll is a complete sync, so stopping the process between polls leaves the trade
+/// in the same state as a manual sync would.
+pub fn poll_until_settled(
+    trade: &Trade,
+    account: &Account,
+    interval: Duration,
+    timeout: Duration,
+    database: &mut dyn DatabaseFactory,
+    broker: &mut dyn Broker,
+) -> Result<Trade, Box<dyn std::error::Error>> {
+    let started_at = Instant::now();
+    let mut trade = trade.clone();
+
+    loop {
+        // 1. Sync Trade with Broker
+        sync_with_broker(&trade, account, database, broker)?;
+
+        // 2. Stop polling when the trade is settled or there is no time for another poll.
+        //    Otherwise the next sync starts from the trade that was just synced
+        trade = database.trade_read().read_trade(trade.id)?;
+        if trade.status.is_terminal() || started_at.elapsed() + interval > timeout {
+            return Ok(trade);
+        }
+
+        // 3. Wait before polling again
+        thread::sleep(interval);
+    }
+}
+
 pub fn close(
     trade: &Trade,
     database: &mut dyn DatabaseFactory,
@@ -416,25 +1034,179 @@ pub fn close(
     // 1. Verify trade can be closed
     crate::validators::trade::can_close(trade)?;
 
-    // 2. Submit a market order to close the trade
+    // 2. Cancel the stop and the target that are still working in the broker,
+    //    so they can not open an opposite position once the trade is flat
     let account = database.account_read().id(trade.account_id)?;
+    cancel_working_legs(trade, &account, broker)?;
+
+    // 3. Submit a market order to close the trade
     let (target_order, log) = broker.close_trade(trade, &account)?;
 
-    // 3. Save log in the database
+    // 4. Save log in the database
     database.log_write().create_log(log.log.as_str(), trade)?;
 
-    // 4. Update Order Target with the filled price and new ID
+    // 5. Update Order Target with the filled price and new ID
     commands::order::update_order(&target_order, database)?;
 
-    // 5. Update Trade Status
+    // 6. Update Trade Status
     database
         .trade_write()
         .update_trade_status(Status::Canceled, trade)?;
 
-    // 6. Cancel Stop-loss Order
+    // 7. Cancel Stop-loss Order
     let mut stop_order = trade.safety_stop.clone();
     stop_order.status = OrderStatus::Canceled;
+    stop_order.cancelled_at = Some(database.clock().now());
     database.order_write().update(&stop_order)?;
 
     Ok((trade.balance.clone(), log))
 }
+
+/// Closes a filled trade with a limit order at `limit_price`.
+/// The trade is closing until a sync finds the limit order filled, then it is closed like a target.
+pub fn close_at_limit(
+    trade: &Trade,
+    account: &Account,
+    limit_price: Decimal,
+    database: &mut dyn DatabaseFactory,
+    broker: &mut dyn Broker,
+) -> Result<(TradeBalance, BrokerLog), Box<dyn std::error::Error>> {
+    // 1. Verify trade can be closed at the limit
+    crate::validators::trade::can_close_at_limit(trade, limit_price)?;
+
+    // 2. Cancel the stop and the target that are still working in the broker
+    cancel_working_legs(trade, account, broker)?;
+
+    // 3. Submit a limit order to close the trade
+    let (target_order, log) = broker.close_trade_limit(trade, account, limit_price)?;
+
+    // 4. Save log in the database
+    database.log_write().create_log(log.log.as_str(), trade)?;
+
+    // 5. Update Order Target with the limit price and new ID
+    commands::order::update_order(&target_order, database)?;
+    if let Some(broker_order_id) = target_order.broker_order_id {
+        database
+            .order_write()
+            .update_price(&target_order, limit_price, broker_order_id)?;
+    }
+
+    // 6. Update Trade Status
+    database
+        .trade_write()
+        .update_trade_status(Status::ClosingPending, trade)?;
+
+    // 7. Cancel Stop-loss Order
+    let mut stop_order = trade.safety_stop.clone();
+    stop_order.status = OrderStatus::Canceled;
+    stop_order.cancelled_at = Some(database.clock().now());
+    database.order_write().update(&stop_order)?;
+
+    Ok((trade.balance.clone(), log))
+}
+
+/// Cancels the stop and the target of a trade that are still working in the broker.
+/// The broker may cancel the other leg of the bracket on its own once the first one is
+/// canceled, so an order that is not working anymore is already canceled.
+fn cancel_working_legs(
+    trade: &Trade,
+    account: &Account,
+    broker: &mut dyn Broker,
+) -> Result<(), Box<dyn Error>> {
+    for order in [&trade.safety_stop, &trade.target] {
+        if !is_working(order) {
+            continue;
+        }
+        match broker.cancel_order(order, account) {
+            Ok(()) => {}
+            Err(error)
+                if error
+                    .downcast_ref::<BrokerError>()
+                    .is_some_and(|error| error.code == BrokerErrorCode::OrderNotWorking) => {}
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(())
+}
+
+/// An order that was submitted to the broker and that can still be executed.
+fn is_working(order: &Order) -> bool {
+    order.broker_order_id.is_some()
+        && !matches!(
+            order.status,
+            OrderStatus::Filled
+                | OrderStatus::Canceled
+                | OrderStatus::Expired
+                | OrderStatus::Rejected
+        )
+}
+
+/// Returns the trades of an account that are funded, submitted, filled or closing in the given currency.
+pub fn read_open_trades(
+    account_id: Uuid,
+    currency: &Currency,
+    database: &mut dyn ReadTradeDB,
+) -> Result<Vec<Trade>, Box<dyn Error>> {
+    let mut trades = Vec::new();
+    for status in [
+        Status::Funded,
+        Status::Submitted,
+        Status::PartiallyFilled,
+        Status::Filled,
+        Status::ClosingPending,
+    ] {
+        trades.extend(
+            database
+                .read_trades_with_status(account_id, status)?
+                .into_iter()
+                .filter(|trade| trade.currency == *currency),
+        );
+    }
+    Ok(trades)
+}
+
+/// Returns the trades of an account with orders working in the broker that were not synced
+/// in the last `staleness`. Trades that were never synced always need a sync.
+pub fn read_trades_needing_sync(
+    account_id: Uuid,
+    staleness: chrono::Duration,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Vec<Trade>, Box<dyn Error>> {
+    let stale_before = database.clock().now() - staleness;
+    let mut trades = Vec::new();
+    for status in [
+        Status::Submitted,
+        Status::PartiallyFilled,
+        Status::Filled,
+        Status::ClosingPending,
+    ] {
+        trades.extend(
+            database
+                .trade_read()
+                .read_trades_with_status(account_id, status)?
+                .into_iter()
+                .filter(|trade| {
+                    trade
+                        .last_synced_at
+                        .is_none_or(|synced_at| synced_at < stale_before)
+                }),
+        );
+    }
+    Ok(trades)
+}
+
+/// Returns the trades of an account that have shares in the market.
+pub fn read_trades_in_market(
+    account_id: Uuid,
+    database: &mut dyn ReadTradeDB,
+) -> Result<Vec<Trade>, Box<dyn Error>> {
+    let mut trades = Vec::new();
+    for status in [
+        Status::PartiallyFilled,
+        Status::Filled,
+        Status::ClosingPending,
+    ] {
+        trades.extend(database.read_trades_with_status(account_id, status)?);
+    }
+    Ok(trades)
+}
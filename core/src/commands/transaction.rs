@@ -15,7 +15,7 @@ use crate::{
     },
 };
 
-use super::balance;
+use super::{balance, trade};
 
 pub fn create(
     database: &mut dyn DatabaseFactory,
@@ -24,6 +24,8 @@ pub fn create(
     currency: &Currency,
     account_id: Uuid,
 ) -> Result<(Transaction, AccountBalance), Box<dyn Error>> {
+    transaction::can_create_with_amount(category, amount)?;
+
     match category {
         TransactionCategory::Deposit => {
             return deposit(database, amount, currency, account_id);
@@ -31,6 +33,16 @@ pub fn create(
         TransactionCategory::Withdrawal => {
             return withdraw(database, amount, currency, account_id);
         }
+        TransactionCategory::Dividend(_) => {
+            return dividend(database, category, amount, currency, account_id);
+        }
+        TransactionCategory::Interest => {
+            return interest(database, amount, currency, account_id);
+        }
+        TransactionCategory::Adjustment { .. } => Err(
+            "Adjustments require protected-mode authorization, create them with create_adjustment"
+                .into(),
+        ),
         TransactionCategory::WithdrawalTax => {
             unimplemented!("WithdrawalTax is not implemented yet")
         }
@@ -38,7 +50,7 @@ pub fn create(
             unimplemented!("WithdrawalEarnings is not implemented yet")
         }
         default => {
-            let message = format!("Manually creating transaction category {:?} is not allowed. Only Withdrawals and deposits are allowed", default);
+            let message = format!("Manually creating transaction category {:?} is not allowed. Only Withdrawals, deposits, dividends and interest are allowed", default);
             Err(message.into())
         }
     }
@@ -88,6 +100,103 @@ fn deposit(
     }
 }
 
+fn dividend(
+    database: &mut dyn DatabaseFactory,
+    category: &TransactionCategory,
+    amount: Decimal,
+    currency: &Currency,
+    account_id: Uuid,
+) -> Result<(Transaction, AccountBalance), Box<dyn Error>> {
+    let account = database.account_read().id(account_id)?;
+
+    // Validate that the dividend is positive and the account holds the currency
+    transaction::can_transfer_dividend(
+        amount,
+        currency,
+        account_id,
+        database.account_balance_read().as_mut(),
+    )?;
+
+    // Create transaction
+    let transaction = database.transaction_write().create_transaction(
+        &account,
+        amount,
+        currency,
+        category.clone(),
+    )?;
+
+    // Update account balance
+    let updated_balance = balance::calculate_account(database, &account, currency)?;
+
+    Ok((transaction, updated_balance))
+}
+
+fn interest(
+    database: &mut dyn DatabaseFactory,
+    amount: Decimal,
+    currency: &Currency,
+    account_id: Uuid,
+) -> Result<(Transaction, AccountBalance), Box<dyn Error>> {
+    let account = database.account_read().id(account_id)?;
+
+    // Validate that the account holds the currency and can pay the charge, if it is one
+    transaction::can_transfer_interest(
+        amount,
+        currency,
+        account_id,
+        database.account_balance_read().as_mut(),
+    )?;
+
+    // Create transaction with the signed amount
+    let transaction = database.transaction_write().create_transaction(
+        &account,
+        amount,
+        currency,
+        TransactionCategory::Interest,
+    )?;
+
+    // Update account balance
+    let updated_balance = balance::calculate_account(database, &account, currency)?;
+
+    Ok((transaction, updated_balance))
+}
+
+/// Creates a manual correction of the balance of an account, for example for a mistake found in an audit.
+/// The amount is signed and the reason is stored with the transaction.
+pub fn adjust(
+    database: &mut dyn DatabaseFactory,
+    amount: Decimal,
+    currency: &Currency,
+    account_id: Uuid,
+    reason: &str,
+) -> Result<(Transaction, AccountBalance), Box<dyn Error>> {
+    let account = database.account_read().id(account_id)?;
+
+    // 1. Validate that the adjustment has a reason and the account can absorb it
+    transaction::can_adjust(
+        amount,
+        reason,
+        currency,
+        account_id,
+        database.account_balance_read().as_mut(),
+    )?;
+
+    // 2. Create transaction with the signed amount
+    let transaction = database.transaction_write().create_transaction(
+        &account,
+        amount,
+        currency,
+        TransactionCategory::Adjustment {
+            reason: reason.trim().to_string(),
+        },
+    )?;
+
+    // 3. Update account balance
+    let updated_balance = balance::calculate_account(database, &account, currency)?;
+
+    Ok((transaction, updated_balance))
+}
+
 fn withdraw(
     database: &mut dyn DatabaseFactory,
     amount: Decimal,
@@ -118,6 +227,109 @@ fn withdraw(
     Ok((transaction, updated_balance))
 }
 
+/// Withdraws earnings from an account without touching the capital reserved for its open trades.
+/// The reserve of a trade is the capital at risk between its entry and its stop, so the account
+/// can still fund the trade again if the stop is hit. The withdrawal is written as a withdrawal
+/// of earnings, so the statement reports it as a distribution.
+pub fn withdraw_earnings(
+    database: &mut dyn DatabaseFactory,
+    amount: Decimal,
+    currency: &Currency,
+    account_id: Uuid,
+) -> Result<(Transaction, AccountBalance), Box<dyn Error>> {
+    // 1. Read the available capital
+    let account = database.account_read().id(account_id)?;
+    let available = database
+        .account_balance_read()
+        .for_currency(account_id, currency)?
+        .total_available;
+
+    // 2. Reserve the capital at risk of the open trades
+    let reserved: Decimal =
+        trade::read_open_trades(account_id, currency, database.trade_read().as_mut())?
+            .iter()
+            .map(|trade| {
+                (trade.entry.unit_price - trade.safety_stop.unit_price).abs()
+                    * trade.entry.decimal_quantity()
+            })
+            .sum();
+
+    // 3. Validate that the withdrawal leaves the reserve in the account
+    transaction::can_withdraw_earnings(amount, available, reserved)?;
+
+    // 4. Create transaction
+    let transaction = database.transaction_write().create_transaction(
+        &account,
+        amount,
+        currency,
+        TransactionCategory::WithdrawalEarnings,
+    )?;
+
+    // 5. Update account balance
+    let updated_balance = balance::calculate_account(database, &account, currency)?;
+
+    Ok((transaction, updated_balance))
+}
+
+/// Undoes the last transaction of an account with a compensating transaction.
+/// Transactions are never deleted, so the history keeps the mistake and its reversal.
+pub fn undo_last(
+    account_id: Uuid,
+    currency: &Currency,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Transaction, Box<dyn Error>> {
+    // 1. Find the last transaction that was not undone and that is not undoing another one
+    let account = database.account_read().id(account_id)?;
+    let mut transactions = database
+        .transaction_read()
+        .all_transactions(account.id, currency)?;
+    transactions.sort_by_key(|tx| tx.created_at);
+    let reversed: Vec<Uuid> = transactions.iter().filter_map(|tx| tx.reverses).collect();
+    let last = transactions
+        .into_iter()
+        .rev()
+        .find(|tx| tx.reverses.is_none() && !reversed.contains(&tx.id))
+        .ok_or_else(|| {
+            format!(
+                "Account {} has no transactions in {} to undo",
+                account.id, currency
+            )
+        })?;
+
+    // 2. Validate that the transaction can be undone
+    let trade = match last.category {
+        TransactionCategory::FundTrade(trade_id) => {
+            Some(database.trade_read().read_trade(trade_id)?)
+        }
+        _ => None,
+    };
+    transaction::can_undo(&last, trade.as_ref())?;
+
+    // 3. Compensate the transaction with the opposite movement
+    let category = match last.category {
+        TransactionCategory::Withdrawal | TransactionCategory::WithdrawalEarnings => {
+            TransactionCategory::Deposit
+        }
+        _ => {
+            transaction::can_transfer_withdraw(
+                last.amount,
+                currency,
+                account.id,
+                database.account_balance_read().as_mut(),
+            )?;
+            TransactionCategory::Withdrawal
+        }
+    };
+    let reversal = database
+        .transaction_write()
+        .create_reversal(&last, category)?;
+
+    // 4. Update account balance
+    balance::calculate_account(database, &account, currency)?;
+
+    Ok(reversal)
+}
+
 pub fn transfer_to_fund_trade(
     trade: &Trade,
     database: &mut dyn DatabaseFactory,
@@ -128,7 +340,7 @@ pub fn transfer_to_fund_trade(
     // 2. Create transaction
     let account = database.account_read().id(trade.account_id)?;
 
-    let trade_total = trade.entry.unit_price * Decimal::from(trade.entry.quantity);
+    let trade_total = trade.entry.unit_price * trade.entry.decimal_quantity();
 
     let transaction = database.transaction_write().create_transaction(
         &account,
@@ -151,7 +363,7 @@ pub fn transfer_to_fill_trade(
     let account = database.account_read().id(trade.account_id)?;
 
     // 1. Calculate the total amount of the trade
-    let total = trade.entry.average_filled_price.unwrap() * Decimal::from(trade.entry.quantity);
+    let total = trade.entry.average_filled_price.unwrap() * trade.entry.decimal_quantity();
 
     // 2. Validate that the trade has enough funds to fill the trade
     transaction::can_transfer_fill(trade, total)?;
@@ -166,7 +378,7 @@ pub fn transfer_to_fill_trade(
 
     // 4. If there is a difference between the unit_price and the average_filled_price
     // then we should create a transaction to transfer the difference to the account.
-    let mut total_difference = total - trade.entry.unit_price * Decimal::from(trade.entry.quantity);
+    let mut total_difference = total - trade.entry.unit_price * trade.entry.decimal_quantity();
     total_difference.set_sign_positive(true);
 
     if total_difference > dec!(0) {
@@ -235,13 +447,37 @@ pub fn transfer_closing_fee(
     Ok((transaction, balance))
 }
 
+pub fn transfer_rebate(
+    rebate: Decimal,
+    trade: &Trade,
+    database: &mut dyn DatabaseFactory,
+) -> Result<(Transaction, AccountBalance), Box<dyn Error>> {
+    // 1. Validate the rebate. It is paid by the broker, so no funds are needed.
+    transaction::can_transfer_rebate(rebate)?;
+
+    // 2. Create transaction
+    let account = database.account_read().id(trade.account_id)?;
+    let transaction = database.transaction_write().create_transaction(
+        &account,
+        rebate,
+        &trade.currency,
+        TransactionCategory::FeeRebate(trade.id),
+    )?;
+
+    // 3. Update trade balance and account balance
+    balance::calculate_trade(database, trade)?;
+    let balance = balance::calculate_account(database, &account, &trade.currency)?;
+
+    Ok((transaction, balance))
+}
+
 pub fn transfer_to_close_target(
     trade: &Trade,
     database: &mut dyn DatabaseFactory,
 ) -> Result<(Transaction, TradeBalance), Box<dyn Error>> {
     let account = database.account_read().id(trade.account_id)?;
 
-    let total = trade.target.average_filled_price.unwrap() * Decimal::from(trade.entry.quantity);
+    let total = trade.target.average_filled_price.unwrap() * trade.entry.decimal_quantity();
 
     // 1. Validate that the closing is possible
     transaction::can_transfer_close(total)?;
@@ -268,15 +504,14 @@ pub fn transfer_to_close_stop(
     let account = database.account_read().id(trade.account_id)?;
 
     // 1. Calculate the total amount of the trade
-    let total =
-        trade.safety_stop.average_filled_price.unwrap() * Decimal::from(trade.entry.quantity);
+    let total = trade.safety_stop.average_filled_price.unwrap() * trade.entry.decimal_quantity();
 
     // 2. Validate that the closing is possible
     transaction::can_transfer_close(total)?;
 
     // 3. If the stop was lower than the planned price, then we should create a transaction
     // with category slippage. For more information see: https://www.investopedia.com/terms/s/slippage.asp
-    let category = if total > trade.safety_stop.unit_price * Decimal::from(trade.entry.quantity) {
+    let category = if total > trade.safety_stop.unit_price * trade.entry.decimal_quantity() {
         TransactionCategory::CloseSafetyStopSlippage(trade.id)
     } else {
         TransactionCategory::CloseSafetyStop(trade.id)
@@ -1,15 +1,55 @@
-use calculators_trade::QuantityCalculator;
+use calculators_trade::{QuantityCalculator, RiskCalculator};
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
 use model::{
-    Account, AccountBalance, Broker, BrokerLog, Currency, DatabaseFactory, DraftTrade, Environment,
-    Order, Rule, RuleLevel, RuleName, Status, Trade, TradeBalance, TradingVehicle,
-    TradingVehicleCategory, Transaction, TransactionCategory,
+    Account, AccountBalance, AccountType, AdvisoryThresholds, Broker, BrokerLog, BrokerValidation,
+    Clock, Currency, DatabaseFactory, DistributionHistory, DistributionRules, DraftExecution,
+    DraftTrade, Environment, EquitySnapshot, Execution, ExecutionSide, ExecutionSource, FeeModel,
+    GradingWeightsPermille, Level, LevelAdjustmentRules, LevelChange, LevelTrigger, LotMethod,
+    MarketBar, MarketPriceProvider, Order, RealizedLot, Rule, RuleLevel, RuleName, Status,
+    Strategy, ThesisRevision, TickSizePolicy, TimeInForce, Trade, TradeBalance, TradeGrade,
+    TradingVehicle, TradingVehicleCategory, Transaction, TransactionCategory,
 };
 use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
+pub use calculators_risk::{MarginStatus, SimulationResult};
+pub use calculators_trade::{CorrelationCluster, SuggestedSize, TradePlan};
+pub use commands::account::{ConfigDiff, ConfigDifference, MergeReport};
+pub use commands::activity::{ActivityItem, ActivityKind};
+pub use commands::advisory::{Advisory, AdvisoryKind};
+pub use commands::execution::ExecutionIngestion;
+pub use commands::export::{ExportedTrade, TradesExport, TRADES_EXPORT_VERSION};
+pub use commands::import::ImportReport;
+pub use commands::order::TradeOrders;
+pub use commands::reconciliation::{
+    BalanceReconciliation, FeeReconciliation, PositionMismatch, PositionReconciliation,
+    TradeBalanceRepair,
+};
+pub use commands::rule::RuleCloneReport;
+pub use commands::statement::AccountStatement;
+pub use commands::timeline::{TimelineEvent, TimelineEventKind};
+pub use commands::trade::{
+    BulkCancelResult, BulkCloseResult, EmergencyCancelReport, FundableTrade, FundedTrade,
+};
+pub use validators::distribution::MAX_DISTRIBUTION_TOLERANCE;
+pub use validators::funding::RuleWarning;
+
 pub struct TrustFacade {
     factory: Box<dyn DatabaseFactory>,
     broker: Box<dyn Broker>,
+
+    /// The token of the protected mutation that is authorized, if any.
+    protected_authorization: Option<Uuid>,
+}
+
+/// A single-use authorization of a protected mutation, given by `authorize_protected_mutation`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ProtectedAuthorization {
+    token: Uuid,
 }
 
 /// Trust is the main entry point for interacting with the core library.
@@ -18,7 +58,67 @@ pub struct TrustFacade {
 impl TrustFacade {
     /// Creates a new instance of Trust.
     pub fn new(factory: Box<dyn DatabaseFactory>, broker: Box<dyn Broker>) -> Self {
-        TrustFacade { factory, broker }
+        TrustFacade {
+            factory,
+            broker,
+            protected_authorization: None,
+        }
+    }
+
+    /// Creates a facade that writes its timestamps with a clock instead of the system clock,
+    /// for example to backtest with historical data.
+    pub fn new_with_clock(
+        mut factory: Box<dyn DatabaseFactory>,
+        broker: Box<dyn Broker>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        factory.set_clock(clock);
+        TrustFacade {
+            factory,
+            broker,
+            protected_authorization: None,
+        }
+    }
+
+    /// Authorizes one protected mutation, for example a manual adjustment of a balance.
+    ///
+    /// The token is single-use: it is consumed when the mutation commits. A mutation that fails
+    /// leaves it intact, so the mutation can be retried with the same token.
+    /// Authorizing again replaces the previous token.
+    pub fn authorize_protected_mutation(&mut self) -> ProtectedAuthorization {
+        let token = Uuid::new_v4();
+        self.protected_authorization = Some(token);
+        ProtectedAuthorization { token }
+    }
+
+    /// Runs a mutation that requires protected-mode authorization in a database transaction.
+    /// The token is consumed only after the transaction commits. If the mutation fails,
+    /// its writes are rolled back and the token is still valid.
+    fn protected<T>(
+        &mut self,
+        authorization: &ProtectedAuthorization,
+        mutation: impl FnOnce(&mut Self) -> Result<T, Box<dyn std::error::Error>>,
+    ) -> Result<T, Box<dyn std::error::Error>> {
+        // 1. Validate that the token is the one authorized and it was not used
+        if self.protected_authorization != Some(authorization.token) {
+            return Err("This operation requires protected-mode authorization, call authorize_protected_mutation first".into());
+        }
+
+        // 2. Run the mutation atomically
+        self.factory.begin_transaction()?;
+        match mutation(self) {
+            Ok(value) => {
+                self.factory.commit_transaction()?;
+
+                // 3. Consume the token once the mutation is committed
+                self.protected_authorization = None;
+                Ok(value)
+            }
+            Err(error) => {
+                self.factory.rollback_transaction()?;
+                Err(error)
+            }
+        }
     }
 
     /// Creates a new account.
@@ -39,6 +139,71 @@ impl TrustFacade {
         )
     }
 
+    /// Creates a live account linked to the paper account where the trading was validated.
+    pub fn create_live_account(
+        &mut self,
+        name: &str,
+        description: &str,
+        taxes_percentage: Decimal,
+        earnings_percentage: Decimal,
+        paper_account: &Account,
+    ) -> Result<Account, Box<dyn std::error::Error>> {
+        commands::account::create_live(
+            name,
+            description,
+            taxes_percentage,
+            earnings_percentage,
+            paper_account,
+            &mut *self.factory,
+        )
+    }
+
+    /// Promotes a paper account to a live account. The paper account must have
+    /// the closed trades set with `set_min_paper_trades_before_live`.
+    pub fn promote_account(
+        &mut self,
+        paper_account: &Account,
+        name: &str,
+        description: &str,
+    ) -> Result<Account, Box<dyn std::error::Error>> {
+        commands::account::promote(paper_account, name, description, &mut *self.factory)
+    }
+
+    /// Creates an account linked to a parent account. Hedge accounts must have a parent,
+    /// and their positions are netted with the parent in `net_exposure`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_account_with_hierarchy(
+        &mut self,
+        name: &str,
+        description: &str,
+        environment: Environment,
+        taxes_percentage: Decimal,
+        earnings_percentage: Decimal,
+        account_type: AccountType,
+        parent_account_id: Option<Uuid>,
+    ) -> Result<Account, Box<dyn std::error::Error>> {
+        commands::account::create_with_hierarchy(
+            name,
+            description,
+            environment,
+            taxes_percentage,
+            earnings_percentage,
+            account_type,
+            parent_account_id,
+            &mut *self.factory,
+        )
+    }
+
+    /// The net filled quantity of a symbol across a parent account and its hedge accounts.
+    /// Longs are positive and shorts are negative.
+    pub fn net_exposure(
+        &mut self,
+        parent_account_id: Uuid,
+        symbol: &str,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        commands::account::net_exposure(parent_account_id, symbol, &mut *self.factory)
+    }
+
     pub fn search_account(&mut self, name: &str) -> Result<Account, Box<dyn std::error::Error>> {
         self.factory.account_read().for_name(name)
     }
@@ -47,6 +212,26 @@ impl TrustFacade {
         self.factory.account_read().all()
     }
 
+    /// Lists the differences between the rules, distribution rules, thresholds and
+    /// level of two accounts. Accounts with the same configuration have an empty diff.
+    pub fn diff_account_config(
+        &mut self,
+        a: Uuid,
+        b: Uuid,
+    ) -> Result<ConfigDiff, Box<dyn std::error::Error>> {
+        commands::account::diff_config(a, b, &mut *self.factory)
+    }
+
+    /// Moves the transactions, trades and rules of an account to another account and
+    /// deletes it. The balances of the same currency are summed.
+    pub fn merge_accounts(
+        &mut self,
+        source_id: Uuid,
+        target_id: Uuid,
+    ) -> Result<MergeReport, Box<dyn std::error::Error>> {
+        commands::account::merge(source_id, target_id, &mut *self.factory)
+    }
+
     pub fn search_all_rules(
         &mut self,
         account_id: Uuid,
@@ -64,6 +249,70 @@ impl TrustFacade {
         commands::transaction::create(&mut *self.factory, category, amount, currency, account.id)
     }
 
+    /// Creates a manual correction of the balance of an account. The amount is signed and it must
+    /// have a reason for the auditors. It requires a token of `authorize_protected_mutation`.
+    pub fn create_adjustment(
+        &mut self,
+        account: &Account,
+        amount: Decimal,
+        currency: &Currency,
+        reason: &str,
+        authorization: &ProtectedAuthorization,
+    ) -> Result<(Transaction, AccountBalance), Box<dyn std::error::Error>> {
+        let account_id = account.id;
+        self.protected(authorization, |trust| {
+            commands::transaction::adjust(&mut *trust.factory, amount, currency, account_id, reason)
+        })
+    }
+
+    /// Withdraws earnings from an account. Only the available capital that is not reserved
+    /// for the open trades of the account can be withdrawn.
+    pub fn withdraw_earnings(
+        &mut self,
+        account_id: Uuid,
+        amount: Decimal,
+        currency: &Currency,
+    ) -> Result<(Transaction, AccountBalance), Box<dyn std::error::Error>> {
+        commands::transaction::withdraw_earnings(&mut *self.factory, amount, currency, account_id)
+    }
+
+    /// Returns the transactions of an account in a currency, or in every currency without one,
+    /// from the oldest to the newest.
+    pub fn get_account_transactions(
+        &mut self,
+        account_id: Uuid,
+        currency: Option<&Currency>,
+    ) -> Result<Vec<Transaction>, Box<dyn std::error::Error>> {
+        let mut transactions = self
+            .factory
+            .transaction_read()
+            .all_account_transactions(account_id, currency)?;
+        transactions.sort_by_key(|transaction| transaction.created_at);
+        Ok(transactions)
+    }
+
+    /// Imports the deposits, withdrawals, dividends and interest of a CSV with the columns
+    /// `date,category,amount,currency`. The rows are applied in chronological order in a single
+    /// database transaction. With `strict` the first rejected row aborts the whole import.
+    pub fn import_transactions_csv(
+        &mut self,
+        account_id: Uuid,
+        reader: impl std::io::Read,
+        strict: bool,
+    ) -> Result<ImportReport, Box<dyn std::error::Error>> {
+        commands::import::import_transactions_csv(account_id, reader, strict, &mut *self.factory)
+    }
+
+    /// Undoes the last deposit, withdrawal or dividend of an account with a compensating
+    /// transaction that references it. The transaction that is undone is not deleted.
+    pub fn undo_last_transaction(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+    ) -> Result<Transaction, Box<dyn std::error::Error>> {
+        commands::transaction::undo_last(account_id, currency, &mut *self.factory)
+    }
+
     pub fn search_balance(
         &mut self,
         account_id: Uuid,
@@ -81,6 +330,34 @@ impl TrustFacade {
         self.factory.account_balance_read().for_account(account_id)
     }
 
+    /// The statement of an account in a month. Without a currency, it is in the base currency of the account.
+    pub fn account_statement(
+        &mut self,
+        account_id: Uuid,
+        year: i32,
+        month: u32,
+        currency: Option<&Currency>,
+    ) -> Result<AccountStatement, Box<dyn std::error::Error>> {
+        let currency = match currency {
+            Some(currency) => *currency,
+            None => self.factory.account_read().id(account_id)?.base_currency,
+        };
+        commands::statement::monthly(account_id, year, month, &currency, &mut *self.factory)
+    }
+
+    /// The balance of an account plus its open positions at their entry price.
+    /// Without a currency, it is in the base currency of the account.
+    pub fn total_equity_in(
+        &mut self,
+        account_id: Uuid,
+        currency: Option<&Currency>,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let account = self.factory.account_read().id(account_id)?;
+        let currency = currency.copied().unwrap_or(account.base_currency);
+        let (_, equity) = commands::balance::equity(&mut *self.factory, &account, &currency)?;
+        Ok(equity)
+    }
+
     pub fn create_rule(
         &mut self,
         account: &Account,
@@ -91,6 +368,16 @@ impl TrustFacade {
         commands::rule::create(&mut *self.factory, account, name, description, level)
     }
 
+    /// Copies the active rules of an account into another account. The rules whose type
+    /// the target already has are skipped and reported.
+    pub fn clone_account_rules(
+        &mut self,
+        source_account_id: Uuid,
+        target_account_id: Uuid,
+    ) -> Result<RuleCloneReport, Box<dyn std::error::Error>> {
+        commands::rule::clone_rules(source_account_id, target_account_id, &mut *self.factory)
+    }
+
     pub fn deactivate_rule(&mut self, rule: &Rule) -> Result<Rule, Box<dyn std::error::Error>> {
         self.factory.rule_write().make_rule_inactive(rule)
     }
@@ -102,16 +389,42 @@ impl TrustFacade {
         self.factory.rule_read().read_all_rules(account_id)
     }
 
+    /// Creates a trading vehicle with the symbol trimmed and in uppercase.
+    /// If the broker already has a trading vehicle with the same symbol and category, it is returned instead.
     pub fn create_trading_vehicle(
         &mut self,
         symbol: &str,
         isin: &str,
         category: &TradingVehicleCategory,
         broker: &str,
+    ) -> Result<TradingVehicle, Box<dyn std::error::Error>> {
+        let symbol = validators::trading_vehicle::can_create(symbol, broker)?;
+        self.factory.trading_vehicle_write().create_trading_vehicle(
+            &symbol,
+            isin,
+            category,
+            broker.trim(),
+        )
+    }
+
+    pub fn set_quote_currency(
+        &mut self,
+        trading_vehicle: &TradingVehicle,
+        currency: Option<Currency>,
     ) -> Result<TradingVehicle, Box<dyn std::error::Error>> {
         self.factory
             .trading_vehicle_write()
-            .create_trading_vehicle(symbol, isin, category, broker)
+            .update_quote_currency(trading_vehicle, currency)
+    }
+
+    pub fn set_tick_size(
+        &mut self,
+        trading_vehicle: &TradingVehicle,
+        tick_size: Option<Decimal>,
+    ) -> Result<TradingVehicle, Box<dyn std::error::Error>> {
+        self.factory
+            .trading_vehicle_write()
+            .update_tick_size(trading_vehicle, tick_size)
     }
 
     pub fn search_trading_vehicles(
@@ -122,6 +435,67 @@ impl TrustFacade {
             .read_all_trading_vehicles()
     }
 
+    pub fn create_strategy(
+        &mut self,
+        name: &str,
+        description: &str,
+        version: u16,
+        entry_description: &str,
+        stop_description: &str,
+        target_description: &str,
+    ) -> Result<Strategy, Box<dyn std::error::Error>> {
+        self.factory.strategy_write().create_strategy(
+            name,
+            description,
+            version,
+            entry_description,
+            stop_description,
+            target_description,
+        )
+    }
+
+    pub fn search_strategies(&mut self) -> Result<Vec<Strategy>, Box<dyn std::error::Error>> {
+        self.factory.strategy_read().read_all_strategies()
+    }
+
+    /// Calculates the performance of the closed trades of an account for each strategy.
+    pub fn performance_by_strategy(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Vec<(Strategy, analytics::PerformanceStats)>, Box<dyn std::error::Error>> {
+        commands::strategy::performance_by_strategy(account_id, &mut *self.factory)
+    }
+
+    /// Calculates the performance of the trades of an account that were closed in the days of a window
+    /// that ends on a date, both included. A window without closed trades has zeroed statistics.
+    pub fn rolling_performance(
+        &mut self,
+        account_id: Uuid,
+        window_days: u32,
+        as_of: NaiveDate,
+    ) -> Result<analytics::PerformanceStats, Box<dyn std::error::Error>> {
+        if window_days == 0 {
+            return Err("The window must have at least one day".into());
+        }
+        let until = as_of.succ_opt().ok_or("The date is out of range")?;
+        let from = until - chrono::Duration::days(i64::from(window_days));
+        let trades = self.factory.trade_read().read_closed_trades_between(
+            account_id,
+            from.and_hms_opt(0, 0, 0).unwrap(),
+            until.and_hms_opt(0, 0, 0).unwrap(),
+        )?;
+        Ok(analytics::performance_stats(&trades))
+    }
+
+    /// Calculates the average R-multiple of the closed trades of an account for each strategy,
+    /// to show which playbooks actually produce R.
+    pub fn average_r_by_strategy(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<HashMap<Uuid, Option<Decimal>>, Box<dyn std::error::Error>> {
+        commands::strategy::average_r_by_strategy(account_id, &mut *self.factory)
+    }
+
     pub fn calculate_maximum_quantity(
         &mut self,
         account_id: Uuid,
@@ -138,119 +512,1136 @@ impl TrustFacade {
         )
     }
 
-    pub fn create_trade(
+    /// The largest quantity that could be added right now to the position of an account in a
+    /// symbol without breaching its capital, risk or per-symbol rules.
+    pub fn max_additional_quantity(
         &mut self,
-        trade: DraftTrade,
+        account_id: Uuid,
+        symbol: &str,
+        entry_price: Decimal,
         stop_price: Decimal,
+        currency: &Currency,
+    ) -> Result<i64, Box<dyn std::error::Error>> {
+        QuantityCalculator::max_additional_quantity(
+            account_id,
+            symbol,
+            entry_price,
+            stop_price,
+            currency,
+            &mut *self.factory,
+        )
+    }
+
+    /// Previews the quantity, capital and risk of a trade with candidate prices. See `TradePlan`.
+    /// Nothing is written to the database.
+    pub fn what_if_quantity(
+        &mut self,
+        account_id: Uuid,
         entry_price: Decimal,
+        stop_price: Decimal,
         target_price: Decimal,
-    ) -> Result<Trade, Box<dyn std::error::Error>> {
-        commands::trade::create_trade(
-            trade,
-            stop_price,
+        currency: &Currency,
+    ) -> Result<TradePlan, Box<dyn std::error::Error>> {
+        QuantityCalculator::trade_plan(
+            account_id,
             entry_price,
+            stop_price,
             target_price,
+            currency,
             &mut *self.factory,
         )
     }
 
-    pub fn search_trades(
+    /// The quantity of a trade sized with a fraction, above 0 and at most 1, of the Kelly criterion
+    /// of a win probability and a reward per unit of risk. Without an edge the quantity is zero.
+    #[allow(clippy::too_many_arguments)]
+    pub fn position_sizing_kelly(
         &mut self,
         account_id: Uuid,
-        status: Status,
-    ) -> Result<Vec<Trade>, Box<dyn std::error::Error>> {
-        self.factory
-            .trade_read()
-            .read_trades_with_status(account_id, status)
+        win_prob: Decimal,
+        reward_risk: Decimal,
+        fraction: Decimal,
+        entry_price: Decimal,
+        stop_price: Decimal,
+        currency: &Currency,
+    ) -> Result<i64, Box<dyn std::error::Error>> {
+        QuantityCalculator::kelly_quantity(
+            account_id,
+            win_prob,
+            reward_risk,
+            fraction,
+            entry_price,
+            stop_price,
+            currency,
+            &mut *self.factory,
+        )
     }
 
-    // Trade Steps
+    /// Suggests the quantity of a new trade. See `SuggestedSize`.
+    pub fn suggested_quantity(
+        &mut self,
+        account_id: Uuid,
+        entry_price: Decimal,
+        stop_price: Decimal,
+        currency: &Currency,
+    ) -> Result<SuggestedSize, Box<dyn std::error::Error>> {
+        QuantityCalculator::suggested_quantity(
+            account_id,
+            entry_price,
+            stop_price,
+            currency,
+            &mut *self.factory,
+        )
+    }
 
-    pub fn fund_trade(
+    pub fn annualized_return(
         &mut self,
-        trade: &Trade,
-    ) -> Result<(Trade, Transaction, AccountBalance, TradeBalance), Box<dyn std::error::Error>>
-    {
-        commands::trade::fund(trade, &mut *self.factory)
+        account_id: Uuid,
+        currency: &Currency,
+    ) -> Result<Option<Decimal>, Box<dyn std::error::Error>> {
+        let transactions = self
+            .factory
+            .transaction_read()
+            .all_transactions(account_id, currency)?;
+        let curve = analytics::equity_curve(&transactions);
+        Ok(analytics::annualized_return(&curve))
     }
 
-    pub fn submit_trade(
+    /// Estimates the risk of ruin of an account from its closed trades.
+    /// The risk fraction is the part of the capital risked in each trade, e.g. 0.02.
+    pub fn risk_of_ruin(
         &mut self,
-        trade: &Trade,
-    ) -> Result<(Trade, BrokerLog), Box<dyn std::error::Error>> {
-        commands::trade::submit(trade, &mut *self.factory, &mut *self.broker)
+        account_id: Uuid,
+        risk_fraction: Decimal,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let mut trades = Vec::new();
+        for status in [Status::ClosedTarget, Status::ClosedStopLoss] {
+            trades.extend(
+                self.factory
+                    .trade_read()
+                    .read_trades_with_status(account_id, status)?,
+            );
+        }
+        if trades.is_empty() {
+            return Err(format!("Account {} has no closed trades", account_id).into());
+        }
+
+        let stats = analytics::performance_stats(&trades);
+        if stats.losing_trades == 0 {
+            return Ok(dec!(0));
+        }
+        let win_rate = stats.win_rate / dec!(100);
+        let payoff = stats.average_win / -stats.average_loss;
+        Ok(analytics::risk_of_ruin(win_rate, payoff, risk_fraction))
     }
 
-    pub fn sync_trade(
+    pub fn capital_utilization(
         &mut self,
-        trade: &Trade,
-        account: &Account,
-    ) -> Result<(Status, Vec<Order>, BrokerLog), Box<dyn std::error::Error>> {
-        commands::trade::sync_with_broker(trade, account, &mut *self.factory, &mut *self.broker)
+        account_id: Uuid,
+        currency: &Currency,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, Decimal)>, Box<dyn std::error::Error>> {
+        let transactions = self
+            .factory
+            .transaction_read()
+            .all_transactions(account_id, currency)?;
+        Ok(analytics::capital_utilization(&transactions, from, to))
     }
 
-    pub fn fill_trade(
+    /// Calculates the concentration groups of the open positions of an account at the
+    /// end of every day of the last `days` days, today included.
+    pub fn concentration_timeseries(
         &mut self,
-        trade: &Trade,
-        fee: Decimal,
-    ) -> Result<(Trade, Transaction), Box<dyn std::error::Error>> {
-        commands::trade::fill_trade(trade, fee, self.factory.as_mut())
+        account_id: Uuid,
+        field: analytics::MetadataField,
+        days: u32,
+    ) -> Result<analytics::ConcentrationTimeseries, Box<dyn std::error::Error>> {
+        if days == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut trades = Vec::new();
+        for status in Status::all() {
+            trades.extend(
+                self.factory
+                    .trade_read()
+                    .read_trades_with_status(account_id, status)?,
+            );
+        }
+        let mut transactions = Vec::new();
+        for currency in Currency::all() {
+            transactions.extend(
+                self.factory
+                    .transaction_read()
+                    .all_transactions(account_id, &currency)?,
+            );
+        }
+
+        let to = self.factory.clock().now().date();
+        let from = to - chrono::Duration::days(i64::from(days) - 1);
+        Ok(analytics::concentration_timeseries(
+            &trades,
+            &transactions,
+            field,
+            from,
+            to,
+        ))
     }
 
-    pub fn stop_trade(
+    pub fn rebuild_equity_snapshots(
         &mut self,
-        trade: &Trade,
-        fee: Decimal,
-    ) -> Result<(Transaction, Transaction, TradeBalance, AccountBalance), Box<dyn std::error::Error>>
-    {
-        commands::trade::stop_acquired(trade, fee, &mut *self.factory)
+        account_id: Uuid,
+    ) -> Result<Vec<EquitySnapshot>, Box<dyn std::error::Error>> {
+        commands::equity_snapshot::rebuild(account_id, &mut *self.factory)
     }
 
-    pub fn close_trade(
+    /// Calculates the drawdown of the realized profit and loss of an account starting from its
+    /// equity snapshots. Only the transactions created after the last snapshot are read.
+    pub fn realized_drawdown(
         &mut self,
-        trade: &Trade,
-    ) -> Result<(TradeBalance, BrokerLog), Box<dyn std::error::Error>> {
-        commands::trade::close(trade, &mut *self.factory, &mut *self.broker)
+        account_id: Uuid,
+        currency: &Currency,
+    ) -> Result<analytics::DrawdownMetrics, Box<dyn std::error::Error>> {
+        let curve = commands::equity_snapshot::realized_equity_curve(
+            account_id,
+            currency,
+            &mut *self.factory,
+        )?;
+        Ok(analytics::RealizedDrawdownCalculator::calculate_metrics(
+            &curve,
+        ))
     }
 
-    pub fn cancel_funded_trade(
+    pub fn level(&mut self, account_id: Uuid) -> Result<Level, Box<dyn std::error::Error>> {
+        commands::level::current(account_id, &mut *self.factory)
+    }
+
+    pub fn level_history(
         &mut self,
-        trade: &Trade,
-    ) -> Result<(TradeBalance, AccountBalance, Transaction), Box<dyn std::error::Error>> {
-        commands::trade::cancel_funded(trade, &mut *self.factory)
+        account_id: Uuid,
+    ) -> Result<Vec<LevelChange>, Box<dyn std::error::Error>> {
+        self.factory
+            .level_read()
+            .level_changes_for_account(account_id)
     }
 
-    pub fn cancel_submitted_trade(
+    pub fn change_level(
         &mut self,
-        trade: &Trade,
-    ) -> Result<(TradeBalance, AccountBalance, Transaction), Box<dyn std::error::Error>> {
-        commands::trade::cancel_submitted(trade, &mut *self.factory, &mut *self.broker)
+        account_id: Uuid,
+        new_level: u8,
+        reason: &str,
+        trigger: &LevelTrigger,
+    ) -> Result<LevelChange, Box<dyn std::error::Error>> {
+        commands::level::change(account_id, new_level, reason, trigger, &mut *self.factory)
     }
 
-    pub fn target_acquired(
+    pub fn update_level_adjustment_rules(
         &mut self,
-        trade: &Trade,
-        fee: Decimal,
-    ) -> Result<(Transaction, Transaction, TradeBalance, AccountBalance), Box<dyn std::error::Error>>
-    {
-        commands::trade::target_acquired(trade, fee, &mut *self.factory)
+        account_id: Uuid,
+        rules: &LevelAdjustmentRules,
+    ) -> Result<LevelAdjustmentRules, Box<dyn std::error::Error>> {
+        commands::level::update_adjustment_rules(account_id, rules, &mut *self.factory)
     }
 
-    pub fn modify_stop(
+    /// Downgrades the level of an account when it breaches its LevelAdjustmentRules.
+    /// Returns the change in the level, or None if the account did not change.
+    pub fn evaluate_level_adjustments(
         &mut self,
-        trade: &Trade,
-        account: &Account,
-        new_stop_price: Decimal,
-    ) -> Result<Trade, Box<dyn std::error::Error>> {
-        commands::trade::modify_stop(
-            trade,
-            account,
-            new_stop_price,
-            &mut *self.broker,
+        account_id: Uuid,
+    ) -> Result<Option<LevelChange>, Box<dyn std::error::Error>> {
+        services::LevelAdjustmentService::evaluate(account_id, &mut *self.factory)
+    }
+
+    /// The percentages are fractions of the profit of each trade and they must sum 1 within the tolerance,
+    /// which can not be above `MAX_DISTRIBUTION_TOLERANCE`. They are stored normalized to sum exactly 1,
+    /// adding the difference to the largest percentage.
+    pub fn configure_distribution(
+        &mut self,
+        account_id: Uuid,
+        earnings_percent: Decimal,
+        tax_percent: Decimal,
+        reinvestment_percent: Decimal,
+        tolerance: Decimal,
+    ) -> Result<DistributionRules, Box<dyn std::error::Error>> {
+        commands::distribution::configure(
+            account_id,
+            earnings_percent,
+            tax_percent,
+            reinvestment_percent,
+            tolerance,
+            &mut *self.factory,
+        )
+    }
+
+    /// Splits the profit of a closed trade following the DistributionRules of its account.
+    /// Nothing is written unless all the legs of the distribution are written.
+    pub fn execute_distribution(
+        &mut self,
+        trade: &Trade,
+    ) -> Result<DistributionHistory, Box<dyn std::error::Error>> {
+        commands::distribution::execute(trade, &mut *self.factory)
+    }
+
+    /// Finishes a trade that was closed in the broker. The profit is distributed if the
+    /// account has distribution rules, and the trade is graded with the default weights.
+    pub fn close_trade_with_auto_distribution(
+        &mut self,
+        trade: &Trade,
+    ) -> Result<(Option<DistributionHistory>, TradeGrade), Box<dyn std::error::Error>> {
+        commands::grade::close_with_auto_distribution(
+            trade,
+            &GradingWeightsPermille::default(),
+            &mut *self.factory,
+        )
+    }
+
+    pub fn trade_grade(
+        &mut self,
+        trade_id: Uuid,
+    ) -> Result<TradeGrade, Box<dyn std::error::Error>> {
+        self.factory.trade_grade_read().trade_grade(trade_id)
+    }
+
+    /// The thresholds are percentages between 0 and 100. They are stored per account and
+    /// read on every advisory check.
+    pub fn configure_advisory_thresholds(
+        &mut self,
+        account_id: Uuid,
+        max_risk_per_trade_percent: Decimal,
+        max_position_concentration_percent: Decimal,
+    ) -> Result<AdvisoryThresholds, Box<dyn std::error::Error>> {
+        commands::advisory::configure(
+            account_id,
+            max_risk_per_trade_percent,
+            max_position_concentration_percent,
+            &mut *self.factory,
+        )
+    }
+
+    /// The advisory thresholds of an account, or the default ones if it never configured them.
+    pub fn advisory_thresholds(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<AdvisoryThresholds, Box<dyn std::error::Error>> {
+        commands::advisory::thresholds(account_id, &mut *self.factory)
+    }
+
+    /// Lists the advisory thresholds of the account of the trade that the trade is above.
+    /// An advisory never blocks the trade.
+    pub fn advisory_check_trade(
+        &mut self,
+        trade: &Trade,
+    ) -> Result<Vec<Advisory>, Box<dyn std::error::Error>> {
+        commands::advisory::check_trade(trade, &mut *self.factory)
+    }
+
+    pub fn distribution_history(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Vec<DistributionHistory>, Box<dyn std::error::Error>> {
+        self.factory
+            .distribution_read()
+            .distribution_history(account_id)
+    }
+
+    pub fn open_positions(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Vec<analytics::OpenPosition>, Box<dyn std::error::Error>> {
+        let trades =
+            commands::trade::read_trades_in_market(account_id, self.factory.trade_read().as_mut())?;
+        Ok(analytics::calculate_open_positions(&trades))
+    }
+
+    /// Sums the capital at risk of the open positions of an account by the sector of their trades.
+    /// Trades without a sector are grouped as unclassified, and the sectors with more risk go first.
+    pub fn account_risk_heatmap(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Vec<(String, Decimal)>, Box<dyn std::error::Error>> {
+        let trades =
+            commands::trade::read_trades_in_market(account_id, self.factory.trade_read().as_mut())?;
+        Ok(analytics::risk_heatmap(&trades))
+    }
+
+    /// Sums the realized profit and loss of the closed trades of an account in a currency
+    /// by the asset class of their trading vehicle, with the number of trades of each one.
+    /// The asset classes that made more money go first.
+    pub fn pnl_by_asset_class(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+    ) -> Result<Vec<analytics::AssetClassPnl>, Box<dyn std::error::Error>> {
+        let trades: Vec<Trade> = self
+            .factory
+            .trade_read()
+            .read_closed_trades(account_id)?
+            .into_iter()
+            .filter(|trade| trade.currency == *currency)
+            .collect();
+        Ok(analytics::pnl_by_asset_class(&trades))
+    }
+
+    /// Consolidates the open positions of an account by trading vehicle.
+    pub fn aggregated_positions(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Vec<analytics::AggregatedPosition>, Box<dyn std::error::Error>> {
+        let positions = self.open_positions(account_id)?;
+        Ok(analytics::aggregate_positions(&positions))
+    }
+
+    /// Groups the open positions of an account by the symbols whose correlation is above the threshold.
+    pub fn correlated_clusters(
+        &mut self,
+        account_id: Uuid,
+        correlation: &HashMap<(String, String), Decimal>,
+        threshold: Decimal,
+    ) -> Result<Vec<CorrelationCluster>, Box<dyn std::error::Error>> {
+        let positions = self.open_positions(account_id)?;
+        Ok(
+            calculators_trade::ConcentrationCalculator::calculate_correlated_clusters(
+                &positions,
+                correlation,
+                threshold,
+            ),
+        )
+    }
+
+    /// Calculates the long and short notional of the open positions in a currency.
+    pub fn directional_exposure(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+        prices: &dyn MarketPriceProvider,
+    ) -> Result<analytics::DirectionalExposure, Box<dyn std::error::Error>> {
+        let positions: Vec<analytics::OpenPosition> = self
+            .open_positions(account_id)?
+            .into_iter()
+            .filter(|position| position.currency == *currency)
+            .collect();
+        Ok(analytics::directional_exposure(&positions, prices))
+    }
+
+    /// Calculates the beta of the open positions relative to the returns of a benchmark.
+    /// The returns of every open trading vehicle must cover the same periods as the benchmark.
+    pub fn portfolio_beta(
+        &mut self,
+        account_id: Uuid,
+        benchmark_returns: &[Decimal],
+        position_returns: HashMap<String, Vec<Decimal>>,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let positions = self.open_positions(account_id)?;
+        calculators_risk::portfolio_beta(&positions, benchmark_returns, &position_returns)
+    }
+
+    /// Calculates the historical value at risk of the open positions at a confidence between 0 and 1.
+    pub fn value_at_risk(
+        &mut self,
+        account_id: Uuid,
+        historical_returns: HashMap<String, Vec<Decimal>>,
+        confidence: Decimal,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let positions = self.open_positions(account_id)?;
+        calculators_risk::value_at_risk(&positions, &historical_returns, confidence)
+    }
+
+    /// Calculates the maintenance margin of the open positions of an account in a currency,
+    /// and if its equity is below it. The positions are valued at their mark, by symbol,
+    /// or at their entry price if there is no mark. The maintenance percentage is between 0 and 100.
+    pub fn account_margin_status(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+        maintenance_pct: Decimal,
+        marks: &HashMap<String, Decimal>,
+    ) -> Result<MarginStatus, Box<dyn std::error::Error>> {
+        let balance = self
+            .factory
+            .account_balance_read()
+            .for_currency(account_id, currency)?;
+        let positions: Vec<analytics::OpenPosition> = self
+            .open_positions(account_id)?
+            .into_iter()
+            .filter(|position| position.currency == *currency)
+            .collect();
+        calculators_risk::margin_status(&positions, marks, balance.total_balance, maintenance_pct)
+    }
+
+    /// Simulates the open trades of an account in a currency hitting their target, with a probability
+    /// between 0 and 1, or their safety stop. The same seed always returns the same result.
+    pub fn simulate_trade_outcomes(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+        iterations: u32,
+        win_prob: Decimal,
+        seed: u64,
+    ) -> Result<SimulationResult, Box<dyn std::error::Error>> {
+        let account = self.factory.account_read().id(account_id)?;
+        let (_, equity) = commands::balance::equity(&mut *self.factory, &account, currency)?;
+        let trades: Vec<Trade> =
+            commands::trade::read_trades_in_market(account_id, self.factory.trade_read().as_mut())?
+                .into_iter()
+                .filter(|trade| trade.currency == *currency)
+                .collect();
+        calculators_risk::simulate_trade_outcomes(&trades, equity, iterations, win_prob, seed)
+    }
+
+    pub fn risk_budget_remaining(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        RiskCalculator::calculate_risk_budget_remaining(account_id, currency, &mut *self.factory)
+    }
+
+    pub fn create_trade(
+        &mut self,
+        trade: DraftTrade,
+        stop_price: Decimal,
+        entry_price: Decimal,
+        target_price: Decimal,
+    ) -> Result<Trade, Box<dyn std::error::Error>> {
+        commands::trade::create_trade(
+            trade,
+            stop_price,
+            entry_price,
+            target_price,
+            None,
             &mut *self.factory,
         )
     }
 
+    /// Creates a new trade with the setup of a previous trade: the trading vehicle, category,
+    /// quantity, prices and strategy. The new trade starts as New with its own orders.
+    pub fn clone_trade(
+        &mut self,
+        source_trade_id: Uuid,
+    ) -> Result<Trade, Box<dyn std::error::Error>> {
+        commands::trade::clone_trade(source_trade_id, &mut *self.factory)
+    }
+
+    /// Creates a trade whose notional is above the account's confirmation threshold.
+    /// The token is the one returned by `trade_confirmation_token`.
+    pub fn create_confirmed_trade(
+        &mut self,
+        trade: DraftTrade,
+        stop_price: Decimal,
+        entry_price: Decimal,
+        target_price: Decimal,
+        confirmation_token: &str,
+    ) -> Result<Trade, Box<dyn std::error::Error>> {
+        commands::trade::create_trade(
+            trade,
+            stop_price,
+            entry_price,
+            target_price,
+            Some(confirmation_token),
+            &mut *self.factory,
+        )
+    }
+
+    pub fn trade_confirmation_token(&self, trade: &DraftTrade, entry_price: Decimal) -> String {
+        validators::trade::confirmation_token(trade, entry_price)
+    }
+
+    pub fn set_confirm_above_notional(
+        &mut self,
+        account: &Account,
+        notional: Option<Decimal>,
+    ) -> Result<Account, Box<dyn std::error::Error>> {
+        self.factory
+            .account_write()
+            .update_confirm_above_notional(account, notional)
+    }
+
+    pub fn set_tick_size_policy(
+        &mut self,
+        account: &Account,
+        policy: TickSizePolicy,
+    ) -> Result<Account, Box<dyn std::error::Error>> {
+        self.factory
+            .account_write()
+            .update_tick_size_policy(account, policy)
+    }
+
+    /// The currency of the calculations that are not given one. The balances are not changed.
+    pub fn set_account_base_currency(
+        &mut self,
+        account: &Account,
+        currency: &Currency,
+    ) -> Result<Account, Box<dyn std::error::Error>> {
+        self.factory
+            .account_write()
+            .update_base_currency(account, currency)
+    }
+
+    /// The seconds in which submitting a trade like another one that was submitted is rejected
+    /// unless it is forced. `None` turns the check off.
+    pub fn set_duplicate_trade_window(
+        &mut self,
+        account: &Account,
+        window_seconds: Option<u32>,
+    ) -> Result<Account, Box<dyn std::error::Error>> {
+        self.factory
+            .account_write()
+            .update_duplicate_trade_window(account, window_seconds)
+    }
+
+    /// Which opening executions are matched first when the position of a trade is closed.
+    pub fn set_lot_method(
+        &mut self,
+        account: &Account,
+        method: LotMethod,
+    ) -> Result<Account, Box<dyn std::error::Error>> {
+        self.factory
+            .account_write()
+            .update_lot_method(account, method)
+    }
+
+    /// How the fees of the fills are calculated when they are not entered manually.
+    pub fn set_fee_model(
+        &mut self,
+        account: &Account,
+        model: FeeModel,
+    ) -> Result<Account, Box<dyn std::error::Error>> {
+        validators::account::can_use_fee_model(&model)?;
+        self.factory
+            .account_write()
+            .update_fee_model(account, model)
+    }
+
+    pub fn set_min_paper_trades_before_live(
+        &mut self,
+        account: &Account,
+        min_trades: Option<u32>,
+    ) -> Result<Account, Box<dyn std::error::Error>> {
+        self.factory
+            .account_write()
+            .update_min_paper_trades_before_live(account, min_trades)
+    }
+
+    pub fn search_trades(
+        &mut self,
+        account_id: Uuid,
+        status: Status,
+    ) -> Result<Vec<Trade>, Box<dyn std::error::Error>> {
+        self.factory
+            .trade_read()
+            .read_trades_with_status(account_id, status)
+    }
+
+    /// Searches the trades of an account on a symbol, from the newest to the oldest.
+    /// The symbol is not case sensitive. Without statuses, the trades of every status are returned.
+    pub fn search_trades_by_symbol(
+        &mut self,
+        account_id: Uuid,
+        symbol: &str,
+        statuses: &[Status],
+    ) -> Result<Vec<Trade>, Box<dyn std::error::Error>> {
+        let statuses = match statuses.is_empty() {
+            true => Status::all(),
+            false => statuses.to_vec(),
+        };
+        self.factory.trade_read().read_trades_for_symbol(
+            account_id,
+            &symbol.trim().to_uppercase(),
+            &statuses,
+        )
+    }
+
+    /// Exports the trades of an account with their orders, balances and executions as JSON:
+    /// `{"version":1,"trades":[...]}`. Without a status, the trades of every status are exported.
+    pub fn export_trades_json(
+        &mut self,
+        account_id: Uuid,
+        status: Option<Status>,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        commands::export::export_trades_json(account_id, status, &mut *self.factory)
+    }
+
+    /// Searches a page of the trades of an account, ordered by creation date.
+    /// It also returns the total number of trades that match the filters, so callers can paginate.
+    pub fn search_trades_paged(
+        &mut self,
+        account_id: Uuid,
+        status: Status,
+        limit: u32,
+        offset: u32,
+        created_after: Option<NaiveDateTime>,
+        created_before: Option<NaiveDateTime>,
+    ) -> Result<(Vec<Trade>, u64), Box<dyn std::error::Error>> {
+        self.factory.trade_read().read_trades_with_status_paged(
+            account_id,
+            status,
+            limit,
+            offset,
+            created_after,
+            created_before,
+        )
+    }
+
+    /// Searches the trade of an account with a reference, e.g. "AAPL-2024-001".
+    pub fn trade_by_reference(
+        &mut self,
+        account_id: Uuid,
+        reference: &str,
+    ) -> Result<Trade, Box<dyn std::error::Error>> {
+        self.factory
+            .trade_read()
+            .trade_by_reference(account_id, reference)?
+            .ok_or_else(|| {
+                format!(
+                    "No trade of account {} has the reference {}",
+                    account_id, reference
+                )
+                .into()
+            })
+    }
+
+    pub fn trade_orders(
+        &mut self,
+        trade_id: Uuid,
+    ) -> Result<TradeOrders, Box<dyn std::error::Error>> {
+        commands::order::read_trade_orders(
+            trade_id,
+            &mut *self.factory.trade_read(),
+            &mut *self.factory.order_read(),
+        )
+    }
+
+    // Trade Steps
+
+    /// Funds a trade. Only the rules with level error block the funding,
+    /// the breaches of the rules with level warning are returned with the funded trade.
+    pub fn fund_trade(&mut self, trade: &Trade) -> Result<FundedTrade, Box<dyn std::error::Error>> {
+        commands::trade::fund(trade, &mut *self.factory)
+    }
+
+    /// Lists the new trades of an account with whether they can be funded right now.
+    /// Trades that can not be funded come with the reason that blocks them.
+    pub fn fundable_trades(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Vec<FundableTrade>, Box<dyn std::error::Error>> {
+        commands::trade::fundable(account_id, &mut *self.factory)
+    }
+
+    /// Submits a funded trade. If the account has a duplicate trade window, a trade with the same
+    /// symbol, direction, entry price and quantity as another one submitted inside it is rejected.
+    pub fn submit_trade(
+        &mut self,
+        trade: &Trade,
+    ) -> Result<(Trade, BrokerLog), Box<dyn std::error::Error>> {
+        commands::trade::submit(trade, false, &mut *self.factory, &mut *self.broker)
+    }
+
+    /// Submits a funded trade even if it duplicates a trade that was submitted recently.
+    pub fn force_submit_trade(
+        &mut self,
+        trade: &Trade,
+    ) -> Result<(Trade, BrokerLog), Box<dyn std::error::Error>> {
+        commands::trade::submit(trade, true, &mut *self.factory, &mut *self.broker)
+    }
+
+    /// Validates a funded trade with the broker without placing any order.
+    pub fn validate_trade_with_broker(
+        &mut self,
+        trade: &Trade,
+        account: &Account,
+    ) -> Result<BrokerValidation, Box<dyn std::error::Error>> {
+        commands::trade::validate_with_broker(trade, account, &mut *self.broker)
+    }
+
+    /// The trades of an account with orders working in the broker that were not synced in the
+    /// last `staleness`, so a scheduler only syncs those.
+    pub fn trades_needing_sync(
+        &mut self,
+        account_id: Uuid,
+        staleness: chrono::Duration,
+    ) -> Result<Vec<Trade>, Box<dyn std::error::Error>> {
+        commands::trade::read_trades_needing_sync(account_id, staleness, &mut *self.factory)
+    }
+
+    pub fn sync_trade(
+        &mut self,
+        trade: &Trade,
+        account: &Account,
+    ) -> Result<(Status, Vec<Order>, BrokerLog), Box<dyn std::error::Error>> {
+        commands::trade::sync_with_broker(trade, account, &mut *self.factory, &mut *self.broker)
+    }
+
+    /// Syncs the trade with the broker until it reaches a terminal status or the timeout elapses.
+    /// Use it when the broker can not push updates of the trade.
+    pub fn poll_until_settled(
+        &mut self,
+        trade: &Trade,
+        account: &Account,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<Trade, Box<dyn std::error::Error>> {
+        commands::trade::poll_until_settled(
+            trade,
+            account,
+            interval,
+            timeout,
+            &mut *self.factory,
+            &mut *self.broker,
+        )
+    }
+
+    /// Fills the entry of a trade. Without a fee, the fee is calculated with the fee model of the account.
+    pub fn fill_trade(
+        &mut self,
+        trade: &Trade,
+        fee: Option<Decimal>,
+    ) -> Result<(Trade, Transaction), Box<dyn std::error::Error>> {
+        commands::trade::fill_trade(trade, fee, self.factory.as_mut())
+    }
+
+    pub fn stop_trade(
+        &mut self,
+        trade: &Trade,
+        fee: Option<Decimal>,
+    ) -> Result<(Transaction, Transaction, TradeBalance, AccountBalance), Box<dyn std::error::Error>>
+    {
+        commands::trade::stop_acquired(trade, fee, &mut *self.factory)
+    }
+
+    pub fn close_trade(
+        &mut self,
+        trade: &Trade,
+    ) -> Result<(TradeBalance, BrokerLog), Box<dyn std::error::Error>> {
+        commands::trade::close(trade, &mut *self.factory, &mut *self.broker)
+    }
+
+    /// Closes a filled trade with a limit order. The trade is closing until a sync finds the order filled.
+    pub fn close_trade_at_limit(
+        &mut self,
+        trade: &Trade,
+        account: &Account,
+        limit_price: Decimal,
+    ) -> Result<(TradeBalance, BrokerLog), Box<dyn std::error::Error>> {
+        commands::trade::close_at_limit(
+            trade,
+            account,
+            limit_price,
+            &mut *self.factory,
+            &mut *self.broker,
+        )
+    }
+
+    pub fn cancel_funded_trade(
+        &mut self,
+        trade: &Trade,
+    ) -> Result<(TradeBalance, AccountBalance, Transaction), Box<dyn std::error::Error>> {
+        commands::trade::cancel_funded(trade, &mut *self.factory)
+    }
+
+    pub fn cancel_submitted_trade(
+        &mut self,
+        trade: &Trade,
+    ) -> Result<(TradeBalance, AccountBalance, Transaction), Box<dyn std::error::Error>> {
+        commands::trade::cancel_submitted(trade, &mut *self.factory, &mut *self.broker)
+    }
+
+    /// Cancels all the funded or submitted trades of an account, for example during a market halt.
+    /// It returns the result of every trade, so a trade that fails does not stop the others.
+    pub fn bulk_cancel_trades(
+        &mut self,
+        account_id: Uuid,
+        status: Status,
+    ) -> Result<Vec<BulkCancelResult>, Box<dyn std::error::Error>> {
+        commands::trade::bulk_cancel(account_id, status, &mut *self.factory, &mut *self.broker)
+    }
+
+    /// Closes all the filled trades of an account at market, for example at the end of the day.
+    /// It returns the result of every trade, so a trade that fails does not stop the others.
+    pub fn close_all_positions_market(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Vec<BulkCloseResult>, Box<dyn std::error::Error>> {
+        commands::trade::close_all_market(account_id, &mut *self.factory, &mut *self.broker)
+    }
+
+    /// Cancels every working order of an account in the broker, and then syncs the trades
+    /// that had one of the canceled orders. Unlike `bulk_cancel_trades`, the broker acts first,
+    /// so the orders that Trust does not know are canceled too.
+    pub fn emergency_cancel_all(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<EmergencyCancelReport, Box<dyn std::error::Error>> {
+        let account = self.factory.account_read().id(account_id)?;
+        commands::trade::emergency_cancel_all(&account, &mut *self.factory, &mut *self.broker)
+    }
+
+    /// Repairs the orders of the open trades of an account that are done in the broker
+    /// but still work locally, and cancels the legs left in the broker of the trades it closed.
+    /// It does not replace a sync, the trades keep their status.
+    pub fn orphan_order_cleanup(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Vec<Uuid>, Box<dyn std::error::Error>> {
+        let account = self.factory.account_read().id(account_id)?;
+        commands::trade::orphan_order_cleanup(&account, &mut *self.factory, &mut *self.broker)
+    }
+
+    pub fn target_acquired(
+        &mut self,
+        trade: &Trade,
+        fee: Option<Decimal>,
+    ) -> Result<(Transaction, Transaction, TradeBalance, AccountBalance), Box<dyn std::error::Error>>
+    {
+        commands::trade::target_acquired(trade, fee, &mut *self.factory)
+    }
+
+    /// Records a rebate paid by the broker for a trade, for example for providing liquidity.
+    /// Rebates are subtracted from the fees of the trade, so its net fees can be negative.
+    pub fn record_rebate(
+        &mut self,
+        trade: &Trade,
+        rebate: Decimal,
+    ) -> Result<(Transaction, AccountBalance), Box<dyn std::error::Error>> {
+        commands::transaction::transfer_rebate(rebate, trade, &mut *self.factory)
+    }
+
+    /// The fees of a trade minus its rebates.
+    pub fn trade_net_fees(&mut self, trade: &Trade) -> Result<Decimal, Box<dyn std::error::Error>> {
+        calculators_trade::TradeFees::calculate(trade.id, self.factory.transaction_read().as_mut())
+    }
+
+    /// The exit price at which a trade breaks even once its net fees are paid.
+    pub fn trade_breakeven(
+        &mut self,
+        trade: &Trade,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        calculators_trade::TradeFees::breakeven(trade, self.factory.transaction_read().as_mut())
+    }
+
+    pub fn reconcile_fees(
+        &mut self,
+        account: &Account,
+        since: NaiveDateTime,
+        until: NaiveDateTime,
+    ) -> Result<FeeReconciliation, Box<dyn std::error::Error>> {
+        commands::reconciliation::reconcile_fees(
+            account,
+            since,
+            until,
+            &mut *self.factory,
+            &mut *self.broker,
+        )
+    }
+
+    /// Compares the positions held by the broker with the open trades of an account.
+    /// It reports the positions without a trade, the trades without a position and
+    /// the positions with another quantity. Nothing is corrected.
+    pub fn reconcile_positions(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<PositionReconciliation, Box<dyn std::error::Error>> {
+        commands::reconciliation::reconcile_positions(
+            account_id,
+            &mut *self.factory,
+            &mut *self.broker,
+        )
+    }
+
+    /// Compares the available balance in the base currency of an account with the buying power in the broker.
+    /// With `reconcile`, the drift is written as an adjustment transaction, which requires
+    /// a token of `authorize_protected_mutation` like any other adjustment.
+    pub fn reconcile_account_balance(
+        &mut self,
+        account_id: Uuid,
+        reconcile: bool,
+        authorization: Option<&ProtectedAuthorization>,
+    ) -> Result<BalanceReconciliation, Box<dyn std::error::Error>> {
+        if !reconcile {
+            return commands::reconciliation::reconcile_balance(
+                account_id,
+                false,
+                &mut *self.factory,
+                &mut *self.broker,
+            );
+        }
+
+        let authorization = authorization.ok_or(
+            "Reconciling the balance requires protected-mode authorization, call authorize_protected_mutation first",
+        )?;
+        self.protected(authorization, |trust| {
+            commands::reconciliation::reconcile_balance(
+                account_id,
+                true,
+                &mut *trust.factory,
+                &mut *trust.broker,
+            )
+        })
+    }
+
+    /// Rebuilds the balance of a trade from its transactions and overwrites the stored balance.
+    /// The repair returns the differences with the balance that was stored.
+    pub fn recompute_trade_balance(
+        &mut self,
+        trade_id: Uuid,
+    ) -> Result<TradeBalanceRepair, Box<dyn std::error::Error>> {
+        commands::reconciliation::recompute_trade_balance(trade_id, &mut *self.factory)
+    }
+
+    /// Sets the sector of a trade, or removes it if it is not given or blank.
+    pub fn set_trade_sector(
+        &mut self,
+        trade_id: Uuid,
+        sector: Option<&str>,
+    ) -> Result<Trade, Box<dyn std::error::Error>> {
+        let trade = self.factory.trade_read().read_trade(trade_id)?;
+        let sector = sector
+            .map(|sector| sector.trim().to_lowercase())
+            .filter(|sector| !sector.is_empty());
+        self.factory
+            .trade_write()
+            .update_trade_sector(&trade, sector)
+    }
+
+    /// Revises the thesis and the context of a trade that is not closed yet.
+    /// A value that is not given is kept, and the previous ones are kept in the thesis history.
+    pub fn revise_trade_thesis(
+        &mut self,
+        trade_id: Uuid,
+        thesis: Option<String>,
+        context: Option<String>,
+    ) -> Result<Trade, Box<dyn std::error::Error>> {
+        let trade = self.factory.trade_read().read_trade(trade_id)?;
+        commands::trade::revise_thesis(&trade, thesis, context, &mut *self.factory)
+    }
+
+    /// Returns the previous theses of a trade, from the oldest to the newest.
+    pub fn thesis_history(
+        &mut self,
+        trade_id: Uuid,
+    ) -> Result<Vec<ThesisRevision>, Box<dyn std::error::Error>> {
+        self.factory.trade_read().thesis_history(trade_id)
+    }
+
+    /// Returns a page of the activity of an account: its transactions, the status changes of its trades
+    /// and the responses of the broker, from the newest to the oldest. Pass the timestamp of the
+    /// last item of a page as `before` to read the next one.
+    pub fn account_activity_feed(
+        &mut self,
+        account_id: Uuid,
+        limit: usize,
+        before: Option<NaiveDateTime>,
+    ) -> Result<Vec<ActivityItem>, Box<dyn std::error::Error>> {
+        commands::activity::account_activity_feed(account_id, limit, before, &mut *self.factory)
+    }
+
+    /// Returns everything that happened to a trade in chronological order.
+    pub fn trade_timeline(
+        &mut self,
+        trade_id: Uuid,
+    ) -> Result<Vec<TimelineEvent>, Box<dyn std::error::Error>> {
+        commands::timeline::trade_timeline(trade_id, &mut *self.factory)
+    }
+
+    /// Returns how far the price moved against a trade and in its favor while it was open,
+    /// from the bars of its trading vehicle. Bars outside of the trade are ignored.
+    pub fn trade_mae_mfe(
+        &mut self,
+        trade_id: Uuid,
+        ticks: &[MarketBar],
+    ) -> Result<analytics::MaeMfe, Box<dyn std::error::Error>> {
+        let trade = self.factory.trade_read().read_trade(trade_id)?;
+        analytics::mae_mfe(&trade, ticks).ok_or_else(|| {
+            format!(
+                "Trade {} has no bars between the fill of its entry and its close",
+                trade_id
+            )
+            .into()
+        })
+    }
+
+    /// Writes the fills pushed by a broker as executions. Fills that were already written,
+    /// for example because the broker sent the same event twice, are skipped and counted.
+    pub fn ingest_executions(
+        &mut self,
+        drafts: &[DraftExecution],
+    ) -> Result<ExecutionIngestion, Box<dyn std::error::Error>> {
+        commands::execution::ingest(drafts, &mut *self.factory)
+    }
+
+    /// Backfills an execution of a trade that was executed outside of Trust, for
+    /// example from a CSV import. The average filled price of the order is
+    /// recomputed from all its executions.
+    pub fn backfill_execution(
+        &mut self,
+        trade_id: Uuid,
+        side: ExecutionSide,
+        price: Decimal,
+        quantity: u64,
+        executed_at: NaiveDateTime,
+        source: ExecutionSource,
+    ) -> Result<Execution, Box<dyn std::error::Error>> {
+        let trade = self.factory.trade_read().read_trade(trade_id)?;
+        commands::execution::backfill(
+            &trade,
+            side,
+            price,
+            quantity,
+            executed_at,
+            source,
+            &mut *self.factory,
+        )
+    }
+
+    /// The lots of an account that were closed in a year, from the oldest to the newest close.
+    pub fn realized_lots(
+        &mut self,
+        account_id: Uuid,
+        year: i32,
+    ) -> Result<Vec<RealizedLot>, Box<dyn std::error::Error>> {
+        let lots = self.factory.realized_lot_read().realized_lots(account_id)?;
+        Ok(lots
+            .into_iter()
+            .filter(|lot| lot.closed_at.year() == year)
+            .collect())
+    }
+
+    pub fn modify_stop(
+        &mut self,
+        trade: &Trade,
+        account: &Account,
+        new_stop_price: Decimal,
+    ) -> Result<Trade, Box<dyn std::error::Error>> {
+        commands::trade::modify_stop(
+            trade,
+            account,
+            new_stop_price,
+            &mut *self.broker,
+            &mut *self.factory,
+        )
+    }
+
+    /// Makes the stop of a trade that was not submitted trail the price by a percent or by a price.
+    pub fn trail_stop(
+        &mut self,
+        trade: &Trade,
+        trail_percent: Option<Decimal>,
+        trail_price: Option<Decimal>,
+    ) -> Result<Trade, Box<dyn std::error::Error>> {
+        commands::trade::trail_stop(trade, trail_percent, trail_price, &mut *self.factory)
+    }
+
+    /// Changes how long the entry of a trade that was not submitted works in the broker.
+    /// It is good until canceled by default.
+    pub fn set_entry_time_in_force(
+        &mut self,
+        trade: &Trade,
+        time_in_force: TimeInForce,
+    ) -> Result<Trade, Box<dyn std::error::Error>> {
+        commands::trade::set_entry_time_in_force(trade, time_in_force, &mut *self.factory)
+    }
+
     pub fn modify_target(
         &mut self,
         trade: &Trade,
@@ -267,8 +1658,12 @@ impl TrustFacade {
     }
 }
 
+pub mod analytics;
 mod calculators_account;
+pub mod calculators_performance;
+mod calculators_risk;
 mod calculators_trade;
 mod commands;
 mod mocks;
+mod services;
 mod validators;
@@ -27,16 +27,27 @@ pub mod read_transaction_db_mocks {
 
         pub fn set_transaction(&mut self, category: TransactionCategory, amount: Decimal) {
             let now: chrono::NaiveDateTime = Utc::now().naive_utc();
+            self.set_transaction_at(category, amount, now);
+        }
+
+        pub fn set_transaction_at(
+            &mut self,
+            category: TransactionCategory,
+            amount: Decimal,
+            created_at: chrono::NaiveDateTime,
+        ) {
             let currency = Currency::USD;
             let transaction = Transaction {
                 id: Uuid::new_v4(),
-                created_at: now,
-                updated_at: now,
+                created_at,
+                updated_at: created_at,
                 deleted_at: None,
                 account_id: self.account_id,
                 amount,
                 currency,
                 category,
+                reverses: None,
+                broker_activity_id: None,
             };
             self.transactions.push(transaction);
         }
@@ -67,6 +78,12 @@ pub mod read_transaction_db_mocks {
                 ),
                 category: TradeCategory::Long,
                 account_id: self.account_id,
+                strategy_id: None,
+                reference: None,
+                thesis: None,
+                context: None,
+                sector: None,
+                last_synced_at: None,
                 balance: TradeBalance::default(),
             };
 
@@ -151,6 +168,67 @@ pub mod read_transaction_db_mocks {
         ) -> Result<Vec<Transaction>, Box<dyn Error>> {
             Ok(self.transactions.clone())
         }
+
+        fn all_account_transactions(
+            &mut self,
+            _account_id: Uuid,
+            currency: Option<&Currency>,
+        ) -> Result<Vec<Transaction>, Box<dyn Error>> {
+            Ok(self
+                .transactions
+                .iter()
+                .filter(|tx| currency.is_none_or(|currency| tx.currency == *currency))
+                .cloned()
+                .collect())
+        }
+
+        fn all_transactions_after(
+            &mut self,
+            _account_id: Uuid,
+            _currency: &Currency,
+            after: chrono::NaiveDateTime,
+        ) -> Result<Vec<Transaction>, Box<dyn Error>> {
+            Ok(self
+                .transactions
+                .iter()
+                .filter(|tx| tx.created_at > after)
+                .cloned()
+                .collect())
+        }
+
+        fn between(
+            &mut self,
+            _account_id: Uuid,
+            _currency: &Currency,
+            from: chrono::NaiveDateTime,
+            to: chrono::NaiveDateTime,
+            categories: Option<&[TransactionCategory]>,
+        ) -> Result<Vec<Transaction>, Box<dyn Error>> {
+            Ok(self
+                .transactions
+                .iter()
+                .filter(|tx| tx.created_at >= from && tx.created_at < to)
+                .filter(|tx| {
+                    categories.is_none_or(|categories| {
+                        categories
+                            .iter()
+                            .any(|category| category.key() == tx.category.key())
+                    })
+                })
+                .cloned()
+                .collect())
+        }
+
+        fn for_broker_activity(
+            &mut self,
+            broker_activity_id: &str,
+        ) -> Result<Option<Transaction>, Box<dyn Error>> {
+            Ok(self
+                .transactions
+                .iter()
+                .find(|tx| tx.broker_activity_id.as_deref() == Some(broker_activity_id))
+                .cloned())
+        }
     }
 
     #[cfg(test)]
@@ -171,8 +249,63 @@ pub mod read_transaction_db_mocks {
             Ok(self.trades.clone())
         }
 
+        fn read_trades_for_symbol(
+            &mut self,
+            _account_id: Uuid,
+            _symbol: &str,
+            _statuses: &[Status],
+        ) -> Result<Vec<Trade>, Box<dyn Error>> {
+            unimplemented!()
+        }
+
+        fn read_closed_trades(&mut self, _account_id: Uuid) -> Result<Vec<Trade>, Box<dyn Error>> {
+            unimplemented!()
+        }
+
+        fn read_closed_trades_between(
+            &mut self,
+            _account_id: Uuid,
+            _closed_from: chrono::NaiveDateTime,
+            _closed_until: chrono::NaiveDateTime,
+        ) -> Result<Vec<Trade>, Box<dyn Error>> {
+            unimplemented!()
+        }
+
+        fn read_trades_with_status_paged(
+            &mut self,
+            _account_id: Uuid,
+            _status: Status,
+            _limit: u32,
+            _offset: u32,
+            _created_after: Option<chrono::NaiveDateTime>,
+            _created_before: Option<chrono::NaiveDateTime>,
+        ) -> Result<(Vec<Trade>, u64), Box<dyn Error>> {
+            unimplemented!()
+        }
+
         fn read_trade(&mut self, _id: Uuid) -> Result<Trade, Box<dyn Error>> {
             Ok(self.trades.first().unwrap().clone())
         }
+
+        fn trade_by_reference(
+            &mut self,
+            account_id: Uuid,
+            reference: &str,
+        ) -> Result<Option<Trade>, Box<dyn Error>> {
+            Ok(self
+                .trades
+                .iter()
+                .find(|trade| {
+                    trade.account_id == account_id && trade.reference.as_deref() == Some(reference)
+                })
+                .cloned())
+        }
+
+        fn thesis_history(
+            &mut self,
+            _trade_id: Uuid,
+        ) -> Result<Vec<model::ThesisRevision>, Box<dyn Error>> {
+            unimplemented!()
+        }
     }
 }
@@ -0,0 +1,3 @@
+mod level_adjustment;
+
+pub use level_adjustment::LevelAdjustmentService;
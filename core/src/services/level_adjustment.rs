@@ -0,0 +1,176 @@
+use crate::analytics::RealizedDrawdownCalculator;
+use crate::commands;
+use chrono::NaiveDateTime;
+use model::{Currency, DatabaseFactory, Level, LevelAdjustmentRules, LevelChange, LevelTrigger};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::error::Error;
+use uuid::Uuid;
+
+pub struct LevelAdjustmentService;
+
+impl LevelAdjustmentService {
+    /// Downgrades an account one level when the current drawdown of its realized profit and loss
+    /// exceeds the `monthly_loss_downgrade_pct` of its LevelAdjustmentRules. Deposits, withdrawals
+    /// and the money moved into trades are not losses, so they never downgrade an account.
+    ///
+    /// An account is only downgraded once per breach. The equity curve needs to
+    /// reach its peak again before a new breach can downgrade the account.
+    pub fn evaluate(
+        account_id: Uuid,
+        database: &mut dyn DatabaseFactory,
+    ) -> Result<Option<LevelChange>, Box<dyn Error>> {
+        // 1. Read the level of the account and its rules
+        let level = commands::level::current(account_id, database)?;
+        if level.current_level == Level::MIN {
+            return Ok(None);
+        }
+        let rules = database.level_read().level_adjustment_rules(account_id)?;
+        let changes = database
+            .level_read()
+            .level_changes_for_account(account_id)?;
+
+        // 2. Look for a drawdown breach in any of the currencies of the account
+        for balance in database.account_balance_read().for_account(account_id)? {
+            let curve = commands::equity_snapshot::realized_equity_curve(
+                account_id,
+                &balance.currency,
+                database,
+            )?;
+
+            // 3. Downgrade the account one level
+            if let Some(reason) = drawdown_breach(&curve, &rules, &changes, &balance.currency) {
+                let change = commands::level::change(
+                    account_id,
+                    level.current_level - 1,
+                    &reason,
+                    &LevelTrigger::DrawdownBreach,
+                    database,
+                )?;
+                return Ok(Some(change));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Returns the reason of the downgrade if the curve is in a drawdown that exceeds
+/// the rules and that did not downgrade the account yet.
+fn drawdown_breach(
+    curve: &[(NaiveDateTime, Decimal)],
+    rules: &LevelAdjustmentRules,
+    changes: &[LevelChange],
+    currency: &Currency,
+) -> Option<String> {
+    let metrics = RealizedDrawdownCalculator::calculate_metrics(curve);
+    if metrics.peak <= dec!(0) {
+        return None;
+    }
+
+    let drawdown_percentage = metrics.current_drawdown / metrics.peak * dec!(100);
+    if drawdown_percentage <= rules.monthly_loss_downgrade_pct {
+        return None;
+    }
+
+    // The drawdown started the last time that the curve was at its peak.
+    let started_at = curve
+        .iter()
+        .rev()
+        .find(|(_, balance)| *balance == metrics.peak)
+        .map(|(timestamp, _)| *timestamp)?;
+    let already_downgraded = changes.iter().any(|change| {
+        change.trigger == LevelTrigger::DrawdownBreach && change.changed_at >= started_at
+    });
+    if already_downgraded {
+        return None;
+    }
+
+    Some(format!(
+        "Drawdown of {:.2}% in {} exceeds the maximum of {}%",
+        drawdown_percentage, currency, rules.monthly_loss_downgrade_pct
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn curve(now: NaiveDateTime) -> Vec<(NaiveDateTime, Decimal)> {
+        vec![
+            (now - Duration::days(3), dec!(10000)),
+            (now - Duration::days(2), dec!(12000)),
+            (now - Duration::days(1), dec!(11000)),
+        ]
+    }
+
+    #[test]
+    fn test_drawdown_breach() {
+        let now = Utc::now().naive_utc();
+        let rules = LevelAdjustmentRules {
+            monthly_loss_downgrade_pct: dec!(5),
+        };
+
+        let reason = drawdown_breach(&curve(now), &rules, &[], &Currency::USD);
+
+        assert_eq!(
+            reason,
+            Some("Drawdown of 8.33% in USD exceeds the maximum of 5%".to_string())
+        );
+    }
+
+    #[test]
+    fn test_drawdown_within_limit() {
+        let now = Utc::now().naive_utc();
+        let rules = LevelAdjustmentRules {
+            monthly_loss_downgrade_pct: dec!(10),
+        };
+
+        assert_eq!(
+            drawdown_breach(&curve(now), &rules, &[], &Currency::USD),
+            None
+        );
+        assert_eq!(drawdown_breach(&[], &rules, &[], &Currency::USD), None);
+    }
+
+    #[test]
+    fn test_drawdown_breach_already_downgraded() {
+        let now = Utc::now().naive_utc();
+        let rules = LevelAdjustmentRules::default();
+        let change = LevelChange {
+            trigger: LevelTrigger::DrawdownBreach,
+            changed_at: now,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            drawdown_breach(&curve(now), &rules, &[change], &Currency::USD),
+            None
+        );
+    }
+
+    #[test]
+    fn test_drawdown_breach_after_a_new_peak() {
+        let now = Utc::now().naive_utc();
+        let rules = LevelAdjustmentRules::default();
+        let old_change = LevelChange {
+            trigger: LevelTrigger::DrawdownBreach,
+            changed_at: now - Duration::days(4),
+            ..Default::default()
+        };
+        let manual_change = LevelChange {
+            trigger: LevelTrigger::ManualOverride,
+            changed_at: now,
+            ..Default::default()
+        };
+
+        assert!(drawdown_breach(
+            &curve(now),
+            &rules,
+            &[old_change, manual_change],
+            &Currency::USD
+        )
+        .is_some());
+    }
+}
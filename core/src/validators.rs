@@ -1,6 +1,11 @@
+pub mod account;
+pub mod advisory;
+pub mod distribution;
 pub mod funding;
+pub mod level;
 pub mod rule;
 pub mod trade;
+pub mod trading_vehicle;
 pub mod transaction;
 
 pub use transaction::{TransactionValidationError, TransactionValidationErrorCode};
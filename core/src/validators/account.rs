@@ -0,0 +1,432 @@
+use model::{Account, AccountBalance, AccountType, Environment, FeeModel, Status, Trade};
+use rust_decimal::Decimal;
+use std::error::Error;
+use uuid::Uuid;
+
+type AccountValidationResult = Result<(), Box<AccountValidationError>>;
+
+// Validate that the paper account has closed enough trades to trade with real money
+pub fn can_go_live(paper_account: &Account, closed_trades: usize) -> AccountValidationResult {
+    if paper_account.environment != Environment::Paper {
+        return Err(Box::new(AccountValidationError {
+            code: AccountValidationErrorCode::NotPaperAccount,
+            message: format!(
+                "Account {} is not a paper account and it can not be promoted to live",
+                paper_account.name
+            ),
+        }));
+    }
+
+    match paper_account.min_paper_trades_before_live {
+        Some(min_trades) if closed_trades < min_trades as usize => {
+            Err(Box::new(AccountValidationError {
+                code: AccountValidationErrorCode::NotEnoughPaperTrades,
+                message: format!(
+                    "Paper account {} has {} closed trades and it needs {} before trading live",
+                    paper_account.name, closed_trades, min_trades
+                ),
+            }))
+        }
+        _ => Ok(()),
+    }
+}
+
+// Validate that the parent exists, that hedge accounts have a parent, that the parent
+// is not a hedge account itself and that the parent and its ancestors do not form a cycle
+pub fn can_link_to_parent(
+    account_type: AccountType,
+    parent_account_id: Option<Uuid>,
+    parent: Option<&Account>,
+    ancestors: &[Account],
+) -> AccountValidationResult {
+    let parent = match (parent_account_id, parent) {
+        (Some(id), None) => {
+            return Err(Box::new(AccountValidationError {
+                code: AccountValidationErrorCode::ParentNotFound,
+                message: format!("The parent account {} does not exist", id),
+            }))
+        }
+        (_, parent) => parent,
+    };
+
+    match parent {
+        None if account_type == AccountType::Hedge => Err(Box::new(AccountValidationError {
+            code: AccountValidationErrorCode::HedgeWithoutParent,
+            message: "A hedge account must be linked to a parent account".to_string(),
+        })),
+        Some(parent) if parent.account_type == AccountType::Hedge => {
+            Err(Box::new(AccountValidationError {
+                code: AccountValidationErrorCode::ParentIsHedge,
+                message: format!(
+                    "Account {} is a hedge account and it can not be the parent of another account",
+                    parent.name
+                ),
+            }))
+        }
+        Some(parent) => {
+            let mut ids = vec![parent.id];
+            for ancestor in ancestors {
+                if ids.contains(&ancestor.id) {
+                    return Err(Box::new(AccountValidationError {
+                        code: AccountValidationErrorCode::CycleInHierarchy,
+                        message: format!(
+                            "The ancestors of account {} form a cycle with account {}",
+                            parent.name, ancestor.name
+                        ),
+                    }));
+                }
+                ids.push(ancestor.id);
+            }
+            Ok(())
+        }
+        None => Ok(()),
+    }
+}
+
+// Validate that the account tracks a level. Only primary accounts have levels.
+pub fn can_have_level(account: &Account) -> AccountValidationResult {
+    if account.account_type != AccountType::Primary {
+        return Err(Box::new(AccountValidationError {
+            code: AccountValidationErrorCode::LevelNotAllowed,
+            message: format!(
+                "Account {} is a {} account and only primary accounts have a level",
+                account.name, account.account_type
+            ),
+        }));
+    }
+    Ok(())
+}
+
+// Validate that a fee model can only charge fees. Brokers that pay for fills pay rebates.
+pub fn can_use_fee_model(model: &FeeModel) -> AccountValidationResult {
+    match model.amount() {
+        Some(amount) if amount < Decimal::ZERO => Err(Box::new(AccountValidationError {
+            code: AccountValidationErrorCode::NegativeFee,
+            message: format!(
+                "The fee model {} can not have a negative amount: {}",
+                model, amount
+            ),
+        })),
+        _ => Ok(()),
+    }
+}
+
+// Validate that everything of the source account can be moved to the target account.
+// Both accounts must be in the same environment and no money can be in open trades,
+// because the balances are calculated again once the transactions are moved.
+pub fn can_merge(
+    source: &Account,
+    target: &Account,
+    source_trades: &[Trade],
+    target_trades: &[Trade],
+    balances: &[AccountBalance],
+) -> AccountValidationResult {
+    if source.id == target.id {
+        return Err(Box::new(AccountValidationError {
+            code: AccountValidationErrorCode::MergeIntoItself,
+            message: format!("Account {} can not be merged into itself", source.name),
+        }));
+    }
+
+    if source.environment != target.environment {
+        return Err(Box::new(AccountValidationError {
+            code: AccountValidationErrorCode::MergeOfDifferentEnvironments,
+            message: format!(
+                "Account {} is a {} account and account {} is a {} account",
+                source.name, source.environment, target.name, target.environment
+            ),
+        }));
+    }
+
+    if target.parent_account_id == Some(source.id) {
+        return Err(Box::new(AccountValidationError {
+            code: AccountValidationErrorCode::MergeIntoChild,
+            message: format!(
+                "Account {} is the parent of account {} and it can not be merged into it",
+                source.name, target.name
+            ),
+        }));
+    }
+
+    if let Some(trade) = source_trades
+        .iter()
+        .chain(target_trades)
+        .find(|trade| is_open(trade))
+    {
+        return Err(Box::new(AccountValidationError {
+            code: AccountValidationErrorCode::MergeWithOpenTrades,
+            message: format!(
+                "Trade {} is {} and accounts with open trades can not be merged",
+                trade.id, trade.status
+            ),
+        }));
+    }
+
+    if let Some(balance) = balances
+        .iter()
+        .find(|balance| !balance.total_in_trade.is_zero())
+    {
+        return Err(Box::new(AccountValidationError {
+            code: AccountValidationErrorCode::MergeWithMoneyInTrade,
+            message: format!(
+                "Account {} has {} {} in trades and it can not be merged",
+                balance.account_id, balance.total_in_trade, balance.currency
+            ),
+        }));
+    }
+
+    if let Some(reference) = source_trades
+        .iter()
+        .filter_map(|trade| trade.reference.as_ref())
+        .find(|reference| {
+            target_trades
+                .iter()
+                .any(|trade| trade.reference.as_ref() == Some(reference))
+        })
+    {
+        return Err(Box::new(AccountValidationError {
+            code: AccountValidationErrorCode::MergeWithDuplicatedReference,
+            message: format!(
+                "The reference {} is used by a trade of both accounts",
+                reference
+            ),
+        }));
+    }
+
+    Ok(())
+}
+
+// The trades whose money is in the market or reserved for it.
+fn is_open(trade: &Trade) -> bool {
+    matches!(
+        trade.status,
+        Status::Funded
+            | Status::Submitted
+            | Status::PartiallyFilled
+            | Status::Filled
+            | Status::ClosingPending
+    )
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AccountValidationError {
+    pub code: AccountValidationErrorCode,
+    pub message: String,
+}
+
+impl std::fmt::Display for AccountValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "AccountValidationError: {}", self.message)
+    }
+}
+
+impl Error for AccountValidationError {
+    fn description(&self) -> &str {
+        &self.message
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AccountValidationErrorCode {
+    NotPaperAccount,
+    NotEnoughPaperTrades,
+    HedgeWithoutParent,
+    ParentIsHedge,
+    ParentNotFound,
+    CycleInHierarchy,
+    LevelNotAllowed,
+    MergeIntoItself,
+    MergeOfDifferentEnvironments,
+    MergeIntoChild,
+    MergeWithOpenTrades,
+    MergeWithMoneyInTrade,
+    MergeWithDuplicatedReference,
+    NegativeFee,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_can_go_live_without_gate() {
+        assert!(can_go_live(&Account::default(), 0).is_ok());
+    }
+
+    #[test]
+    fn test_can_go_live_with_gate() {
+        let account = Account {
+            min_paper_trades_before_live: Some(10),
+            ..Default::default()
+        };
+        let error = can_go_live(&account, 9).unwrap_err();
+        assert_eq!(error.code, AccountValidationErrorCode::NotEnoughPaperTrades);
+        assert!(can_go_live(&account, 10).is_ok());
+    }
+
+    #[test]
+    fn test_can_go_live_from_live_account() {
+        let account = Account {
+            environment: Environment::Live,
+            ..Default::default()
+        };
+        let error = can_go_live(&account, 0).unwrap_err();
+        assert_eq!(error.code, AccountValidationErrorCode::NotPaperAccount);
+    }
+
+    #[test]
+    fn test_can_link_to_parent() {
+        let parent = Account::default();
+        assert!(can_link_to_parent(AccountType::Primary, None, None, &[]).is_ok());
+        assert!(
+            can_link_to_parent(AccountType::Hedge, Some(parent.id), Some(&parent), &[]).is_ok()
+        );
+
+        let error = can_link_to_parent(AccountType::Hedge, None, None, &[]).unwrap_err();
+        assert_eq!(error.code, AccountValidationErrorCode::HedgeWithoutParent);
+
+        let hedge = Account {
+            account_type: AccountType::Hedge,
+            ..Default::default()
+        };
+        let error =
+            can_link_to_parent(AccountType::Hedge, Some(hedge.id), Some(&hedge), &[]).unwrap_err();
+        assert_eq!(error.code, AccountValidationErrorCode::ParentIsHedge);
+    }
+
+    #[test]
+    fn test_can_link_to_parent_not_found() {
+        let error =
+            can_link_to_parent(AccountType::Hedge, Some(Uuid::new_v4()), None, &[]).unwrap_err();
+        assert_eq!(error.code, AccountValidationErrorCode::ParentNotFound);
+    }
+
+    #[test]
+    fn test_can_link_to_parent_with_cycle() {
+        let parent = Account::default();
+        let grandparent = Account {
+            parent_account_id: Some(parent.id),
+            ..Default::default()
+        };
+        let error = can_link_to_parent(
+            AccountType::Primary,
+            Some(parent.id),
+            Some(&parent),
+            &[grandparent.clone(), parent.clone()],
+        )
+        .unwrap_err();
+        assert_eq!(error.code, AccountValidationErrorCode::CycleInHierarchy);
+        assert!(can_link_to_parent(
+            AccountType::Primary,
+            Some(parent.id),
+            Some(&parent),
+            &[grandparent]
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_can_have_level() {
+        assert!(can_have_level(&Account::default()).is_ok());
+
+        let hedge = Account {
+            account_type: AccountType::Hedge,
+            ..Default::default()
+        };
+        let error = can_have_level(&hedge).unwrap_err();
+        assert_eq!(error.code, AccountValidationErrorCode::LevelNotAllowed);
+    }
+
+    #[test]
+    fn test_can_use_fee_model() {
+        assert!(can_use_fee_model(&FeeModel::None).is_ok());
+        assert!(can_use_fee_model(&FeeModel::Fixed(Decimal::ZERO)).is_ok());
+        assert!(can_use_fee_model(&FeeModel::PerShare(rust_decimal_macros::dec!(0.005))).is_ok());
+
+        let error =
+            can_use_fee_model(&FeeModel::Percent(rust_decimal_macros::dec!(-0.1))).unwrap_err();
+        assert_eq!(error.code, AccountValidationErrorCode::NegativeFee);
+    }
+
+    #[test]
+    fn test_can_merge() {
+        let source = Account::default();
+        let target = Account::default();
+        let closed = Trade {
+            status: Status::ClosedTarget,
+            reference: Some("first".to_string()),
+            ..Default::default()
+        };
+        assert!(can_merge(&source, &target, std::slice::from_ref(&closed), &[], &[]).is_ok());
+
+        let error = can_merge(&source, &source, &[], &[], &[]).unwrap_err();
+        assert_eq!(error.code, AccountValidationErrorCode::MergeIntoItself);
+
+        let live = Account {
+            environment: Environment::Live,
+            ..Default::default()
+        };
+        let error = can_merge(&source, &live, &[], &[], &[]).unwrap_err();
+        assert_eq!(
+            error.code,
+            AccountValidationErrorCode::MergeOfDifferentEnvironments
+        );
+
+        let child = Account {
+            parent_account_id: Some(source.id),
+            ..Default::default()
+        };
+        let error = can_merge(&source, &child, &[], &[], &[]).unwrap_err();
+        assert_eq!(error.code, AccountValidationErrorCode::MergeIntoChild);
+    }
+
+    #[test]
+    fn test_can_merge_with_open_trades_or_money_in_trade() {
+        let source = Account::default();
+        let target = Account::default();
+        let funded = Trade {
+            status: Status::Funded,
+            ..Default::default()
+        };
+        let error = can_merge(&source, &target, &[], &[funded], &[]).unwrap_err();
+        assert_eq!(error.code, AccountValidationErrorCode::MergeWithOpenTrades);
+
+        let new = Trade {
+            status: Status::New,
+            ..Default::default()
+        };
+        assert!(can_merge(&source, &target, &[new], &[], &[]).is_ok());
+
+        let balance = AccountBalance {
+            total_in_trade: rust_decimal_macros::dec!(10),
+            ..Default::default()
+        };
+        let error = can_merge(&source, &target, &[], &[], &[balance]).unwrap_err();
+        assert_eq!(
+            error.code,
+            AccountValidationErrorCode::MergeWithMoneyInTrade
+        );
+    }
+
+    #[test]
+    fn test_can_merge_with_duplicated_reference() {
+        let trade = |reference: &str| Trade {
+            status: Status::ClosedStopLoss,
+            reference: Some(reference.to_string()),
+            ..Default::default()
+        };
+
+        let error = can_merge(
+            &Account::default(),
+            &Account::default(),
+            &[trade("first"), trade("second")],
+            &[trade("second")],
+            &[],
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.code,
+            AccountValidationErrorCode::MergeWithDuplicatedReference
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::error::Error;
+
+type AdvisoryValidationResult = Result<(), Box<AdvisoryValidationError>>;
+
+// Validate that the thresholds are percentages between 0 and 100
+pub fn can_configure(
+    max_risk_per_trade_percent: Decimal,
+    max_position_concentration_percent: Decimal,
+) -> AdvisoryValidationResult {
+    for threshold in [
+        max_risk_per_trade_percent,
+        max_position_concentration_percent,
+    ] {
+        if threshold < dec!(0) || threshold > dec!(100) {
+            return Err(Box::new(AdvisoryValidationError {
+                code: AdvisoryValidationErrorCode::ThresholdOutOfRange,
+                message: format!("The threshold {}% must be between 0% and 100%", threshold),
+            }));
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AdvisoryValidationError {
+    pub code: AdvisoryValidationErrorCode,
+    pub message: String,
+}
+
+impl std::fmt::Display for AdvisoryValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "AdvisoryValidationError: {}", self.message)
+    }
+}
+
+impl Error for AdvisoryValidationError {
+    fn description(&self) -> &str {
+        &self.message
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AdvisoryValidationErrorCode {
+    ThresholdOutOfRange,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_can_configure() {
+        assert!(can_configure(dec!(2), dec!(25)).is_ok());
+        assert!(can_configure(dec!(0), dec!(100)).is_ok());
+    }
+
+    #[test]
+    fn test_can_configure_out_of_range() {
+        for (risk, concentration) in [
+            (dec!(-1), dec!(25)),
+            (dec!(2), dec!(-0.5)),
+            (dec!(100.1), dec!(25)),
+            (dec!(2), dec!(150)),
+        ] {
+            let error = can_configure(risk, concentration).unwrap_err();
+            assert_eq!(error.code, AdvisoryValidationErrorCode::ThresholdOutOfRange);
+        }
+    }
+}
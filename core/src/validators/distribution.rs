@@ -0,0 +1,252 @@
+use model::{DistributionHistory, Status, Trade};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::error::Error;
+
+type DistributionValidationResult = Result<(), Box<DistributionValidationError>>;
+
+/// The largest difference from 1 that the sum of the percentages of a distribution can have.
+/// It is enough for rounded percentages like thirds, and it is never enough to store percentages
+/// that do not split the profit, like 0, 0 and 0 normalized to all earnings.
+pub const MAX_DISTRIBUTION_TOLERANCE: Decimal = dec!(0.01);
+
+// Validate that the percentages are fractions of the profit that sum 1, give or take the tolerance
+pub fn can_configure(
+    earnings_percent: Decimal,
+    tax_percent: Decimal,
+    reinvestment_percent: Decimal,
+    tolerance: Decimal,
+) -> DistributionValidationResult {
+    if tolerance < dec!(0) || tolerance > MAX_DISTRIBUTION_TOLERANCE {
+        return Err(Box::new(DistributionValidationError {
+            code: DistributionValidationErrorCode::ToleranceOutOfRange,
+            message: format!(
+                "The tolerance {} must be between 0 and {}",
+                tolerance, MAX_DISTRIBUTION_TOLERANCE
+            ),
+        }));
+    }
+
+    for percent in [earnings_percent, tax_percent, reinvestment_percent] {
+        if percent < dec!(0) || percent > dec!(1) {
+            return Err(Box::new(DistributionValidationError {
+                code: DistributionValidationErrorCode::PercentageOutOfRange,
+                message: format!("The percentage {} must be between 0 and 1", percent),
+            }));
+        }
+    }
+
+    let total = earnings_percent + tax_percent + reinvestment_percent;
+    if (total - dec!(1)).abs() > tolerance {
+        return Err(Box::new(DistributionValidationError {
+            code: DistributionValidationErrorCode::PercentagesMustSumOne,
+            message: format!(
+                "The percentages of earnings, tax and reinvestment must sum 1 within {} and they sum {}",
+                tolerance, total
+            ),
+        }));
+    }
+
+    Ok(())
+}
+
+// Validate that the trade is closed with a profit that was not distributed yet
+pub fn can_distribute(
+    trade: &Trade,
+    profit: Decimal,
+    history: &[DistributionHistory],
+) -> DistributionValidationResult {
+    if trade.status != Status::ClosedTarget && trade.status != Status::ClosedStopLoss {
+        return Err(Box::new(DistributionValidationError {
+            code: DistributionValidationErrorCode::TradeNotClosed,
+            message: format!(
+                "Trade {} with status {} is not closed and its profit can not be distributed",
+                trade.id, trade.status
+            ),
+        }));
+    }
+
+    if profit <= dec!(0) {
+        return Err(Box::new(DistributionValidationError {
+            code: DistributionValidationErrorCode::NoProfitToDistribute,
+            message: format!("Trade {} has no profit to distribute: {}", trade.id, profit),
+        }));
+    }
+
+    if history.iter().any(|entry| entry.trade_id == trade.id) {
+        return Err(Box::new(DistributionValidationError {
+            code: DistributionValidationErrorCode::AlreadyDistributed,
+            message: format!(
+                "The profit of trade {} has already been distributed",
+                trade.id
+            ),
+        }));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DistributionValidationError {
+    pub code: DistributionValidationErrorCode,
+    pub message: String,
+}
+
+impl std::fmt::Display for DistributionValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "DistributionValidationError: {}", self.message)
+    }
+}
+
+impl Error for DistributionValidationError {
+    fn description(&self) -> &str {
+        &self.message
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DistributionValidationErrorCode {
+    ToleranceOutOfRange,
+    PercentageOutOfRange,
+    PercentagesMustSumOne,
+    TradeNotClosed,
+    NoProfitToDistribute,
+    AlreadyDistributed,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_can_configure() {
+        assert!(can_configure(dec!(0.3), dec!(0.2), dec!(0.5), dec!(0)).is_ok());
+        assert!(can_configure(dec!(0), dec!(0), dec!(1), dec!(0)).is_ok());
+    }
+
+    #[test]
+    fn test_can_configure_not_summing_one() {
+        let error = can_configure(dec!(0.3), dec!(0.2), dec!(0.4), dec!(0)).unwrap_err();
+        assert_eq!(
+            error.code,
+            DistributionValidationErrorCode::PercentagesMustSumOne
+        );
+    }
+
+    #[test]
+    fn test_can_configure_within_tolerance() {
+        assert!(can_configure(dec!(0.3333), dec!(0.3333), dec!(0.3334), dec!(0.001)).is_ok());
+        assert!(can_configure(dec!(0.3333), dec!(0.3333), dec!(0.3333), dec!(0.001)).is_ok());
+        assert!(can_configure(dec!(0.3333), dec!(0.3333), dec!(0.3333), dec!(0)).is_err());
+
+        let error = can_configure(dec!(0.30), dec!(0.25), dec!(0.40), dec!(0.001)).unwrap_err();
+        assert_eq!(
+            error.code,
+            DistributionValidationErrorCode::PercentagesMustSumOne
+        );
+    }
+
+    #[test]
+    fn test_can_configure_tolerance_out_of_range() {
+        assert!(can_configure(dec!(0.3), dec!(0.2), dec!(0.5), MAX_DISTRIBUTION_TOLERANCE).is_ok());
+
+        let error = can_configure(dec!(0.3), dec!(0.2), dec!(0.5), dec!(0.011)).unwrap_err();
+        assert_eq!(
+            error.code,
+            DistributionValidationErrorCode::ToleranceOutOfRange
+        );
+        assert_eq!(
+            error.message,
+            "The tolerance 0.011 must be between 0 and 0.01"
+        );
+
+        let error = can_configure(dec!(0.3), dec!(0.2), dec!(0.5), dec!(-0.001)).unwrap_err();
+        assert_eq!(
+            error.code,
+            DistributionValidationErrorCode::ToleranceOutOfRange
+        );
+    }
+
+    #[test]
+    fn test_can_configure_all_zero() {
+        let error =
+            can_configure(dec!(0), dec!(0), dec!(0), MAX_DISTRIBUTION_TOLERANCE).unwrap_err();
+        assert_eq!(
+            error.code,
+            DistributionValidationErrorCode::PercentagesMustSumOne
+        );
+
+        let error = can_configure(dec!(0), dec!(0), dec!(0), dec!(1)).unwrap_err();
+        assert_eq!(
+            error.code,
+            DistributionValidationErrorCode::ToleranceOutOfRange
+        );
+    }
+
+    #[test]
+    fn test_can_configure_negative_percentage() {
+        let error = can_configure(dec!(-0.5), dec!(0.5), dec!(1), dec!(0)).unwrap_err();
+        assert_eq!(
+            error.code,
+            DistributionValidationErrorCode::PercentageOutOfRange
+        );
+    }
+
+    #[test]
+    fn test_can_distribute() {
+        let trade = Trade {
+            status: Status::ClosedTarget,
+            ..Default::default()
+        };
+        assert!(can_distribute(&trade, dec!(100), &[]).is_ok());
+    }
+
+    #[test]
+    fn test_can_distribute_not_closed() {
+        let trade = Trade {
+            status: Status::Filled,
+            ..Default::default()
+        };
+        let error = can_distribute(&trade, dec!(100), &[]).unwrap_err();
+        assert_eq!(error.code, DistributionValidationErrorCode::TradeNotClosed);
+    }
+
+    #[test]
+    fn test_can_distribute_without_profit() {
+        let trade = Trade {
+            status: Status::ClosedStopLoss,
+            ..Default::default()
+        };
+        let error = can_distribute(&trade, dec!(-50), &[]).unwrap_err();
+        assert_eq!(
+            error.code,
+            DistributionValidationErrorCode::NoProfitToDistribute
+        );
+    }
+
+    #[test]
+    fn test_can_distribute_twice() {
+        let trade = Trade {
+            status: Status::ClosedTarget,
+            ..Default::default()
+        };
+        let history = DistributionHistory {
+            id: uuid::Uuid::new_v4(),
+            created_at: chrono::Utc::now().naive_utc(),
+            updated_at: chrono::Utc::now().naive_utc(),
+            deleted_at: None,
+            account_id: trade.account_id,
+            trade_id: trade.id,
+            original_amount: dec!(100),
+            earnings_amount: dec!(0),
+            tax_amount: dec!(0),
+            reinvestment_amount: dec!(100),
+            distributed_at: chrono::Utc::now().naive_utc(),
+        };
+        let error = can_distribute(&trade, dec!(100), &[history]).unwrap_err();
+        assert_eq!(
+            error.code,
+            DistributionValidationErrorCode::AlreadyDistributed
+        );
+    }
+}
@@ -1,14 +1,18 @@
-use crate::calculators_trade::RiskCalculator;
-use model::{AccountBalance, DatabaseFactory, Rule, RuleName, Trade};
+use crate::calculators_trade::{ConcentrationCalculator, RiskCalculator};
+use model::{AccountBalance, DatabaseFactory, Rule, RuleLevel, RuleName, Trade};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::error::Error;
+use tracing::warn;
 use uuid::Uuid;
 
 type FundingValidationResult = Result<(), Box<FundValidationError>>;
+type RulesValidationResult = Result<Vec<RuleWarning>, Box<FundValidationError>>;
 
-// Validate if trade can be funded by checking account balance, available capital and rules
-pub fn can_fund(trade: &Trade, database: &mut dyn DatabaseFactory) -> FundingValidationResult {
+// Validate if trade can be funded by checking account balance, available capital and rules.
+// Only the breaches of rules with level error block the funding, the breaches of rules with
+// level warning are returned so they can be shown next to the funded trade.
+pub fn can_fund(trade: &Trade, database: &mut dyn DatabaseFactory) -> RulesValidationResult {
     // 1.  Get account balance
     let account = database.account_read().id(trade.account_id).unwrap();
 
@@ -35,7 +39,7 @@ pub fn can_fund(trade: &Trade, database: &mut dyn DatabaseFactory) -> FundingVal
 }
 
 fn validate_enough_capital(trade: &Trade, balance: &AccountBalance) -> FundingValidationResult {
-    match balance.total_available >= trade.entry.unit_price * Decimal::from(trade.entry.quantity) {
+    match balance.total_available >= trade.entry.unit_price * trade.entry.decimal_quantity() {
         true => Ok(()),
         false => Err(Box::new(FundValidationError {
             code: FundValidationErrorCode::NotEnoughFunds,
@@ -44,7 +48,7 @@ fn validate_enough_capital(trade: &Trade, balance: &AccountBalance) -> FundingVa
                 trade.account_id,
                 trade.currency,
                 balance.total_available,
-                trade.entry.unit_price * Decimal::from(trade.entry.quantity)
+                trade.entry.unit_price * trade.entry.decimal_quantity()
             ),
         })),
     }
@@ -63,14 +67,15 @@ fn validate_rules(
     trade: &Trade,
     account_balance: &AccountBalance,
     database: &mut dyn DatabaseFactory,
-) -> FundingValidationResult {
+) -> RulesValidationResult {
     // Get rules by priority
     let rules = sorted_rules(trade.account_id, database);
     let mut risk_per_month = dec!(100.0); // Default to 100% of the available capital
+    let mut warnings = vec![];
 
     // Match rules by name
     for rule in rules {
-        match rule.name {
+        let result = match rule.name {
             RuleName::RiskPerMonth(risk) => {
                 risk_per_month = RiskCalculator::calculate_max_percentage_to_risk_current_month(
                     risk,
@@ -79,20 +84,78 @@ fn validate_rules(
                     database,
                 )
                 .unwrap();
+
+                // The losses of the month are only the ones of the trades in the same currency.
+                let remaining =
+                    RiskCalculator::calculate_risk_budget_remaining_for_trade(trade, database)
+                        .unwrap();
+                validate_risk_budget_per_month(trade, remaining)
             }
-            RuleName::RiskPerTrade(risk) => {
-                validate_risk_per_trade(
+            RuleName::RiskPerTrade(risk) => validate_risk_per_trade(
+                trade,
+                account_balance,
+                Decimal::from_f32_retain(risk).unwrap(),
+                risk_per_month,
+            ),
+            RuleName::MaxPositionConcentration(limit) => {
+                let concentration = ConcentrationCalculator::max_concentration(
                     trade,
-                    account_balance,
-                    Decimal::from_f32_retain(risk).unwrap(),
-                    risk_per_month,
-                )?;
+                    &open_trades(trade, database),
+                );
+                validate_max_position_concentration(
+                    concentration,
+                    Decimal::from_f32_retain(limit).unwrap(),
+                )
+            }
+            RuleName::OnePositionPerSymbolSide => {
+                validate_one_position_per_symbol_side(trade, &open_trades(trade, database))
             }
+            RuleName::MinRewardRiskRatio(_) => Ok(()), // Validated when the trade is created.
+            RuleName::MaxConsecutiveLosses(limit) => {
+                let closed_trades = database
+                    .trade_read()
+                    .read_closed_trades(trade.account_id)
+                    .unwrap_or_else(|_| vec![]);
+                validate_max_consecutive_losses(consecutive_losses(&closed_trades), limit)
+            }
+        };
+
+        if let Some(warning) = apply_level(rule.name, &rule.level, result)? {
+            warnings.push(warning);
         }
     }
 
-    // If no rule is violated, return Ok
-    Ok(())
+    // If no rule with level error is violated, return the warnings
+    Ok(warnings)
+}
+
+// Decides what a breach of a rule means given its level. A breach of a rule with level error
+// blocks the funding, a breach of a rule with level warning is returned to the caller and
+// a breach of a rule with level advice is only logged.
+fn apply_level(
+    rule: RuleName,
+    level: &RuleLevel,
+    result: FundingValidationResult,
+) -> Result<Option<RuleWarning>, Box<FundValidationError>> {
+    let error = match result {
+        Ok(()) => return Ok(None),
+        Err(error) => error,
+    };
+
+    match level {
+        RuleLevel::Error => Err(error),
+        RuleLevel::Warning => {
+            warn!("{}", error.message);
+            Ok(Some(RuleWarning {
+                rule,
+                message: error.message,
+            }))
+        }
+        RuleLevel::Advice => {
+            warn!("{}", error.message);
+            Ok(None)
+        }
+    }
 }
 
 // This function validates a trade based on the given risk parameters and account balance.
@@ -119,8 +182,7 @@ fn validate_risk_per_trade(
     let maximum_risk = account_balance.total_available * (risk / dec!(100.0));
 
     // Calculate the total amount that will be risked in this trade.
-    let total_risk = (trade.entry.unit_price - trade.safety_stop.unit_price)
-        * Decimal::from(trade.entry.quantity);
+    let total_risk = capital_at_risk(trade);
 
     // Check if the risk per trade limit has been exceeded.
     if total_risk > maximum_risk {
@@ -138,6 +200,120 @@ fn validate_risk_per_trade(
     Ok(())
 }
 
+// The money that is lost if the trade hits its safety stop. A long loses from the entry down
+// to the stop and a short from the entry up to the stop, so both directions are validated alike.
+pub(crate) fn capital_at_risk(trade: &Trade) -> Decimal {
+    (trade.entry.unit_price - trade.safety_stop.unit_price).abs() * trade.entry.decimal_quantity()
+}
+
+// This function validates that the risk of a trade fits in what is left of the monthly budget
+// of its currency, once the open trades and the losses of this month are subtracted.
+fn validate_risk_budget_per_month(trade: &Trade, remaining: Decimal) -> FundingValidationResult {
+    let total_risk = capital_at_risk(trade);
+
+    if total_risk > remaining {
+        return Err(Box::new(FundValidationError {
+            code: FundValidationErrorCode::RiskPerMonthExceeded,
+            message: format!(
+                "Risk per month exceeded for {}, the budget left this month is {}, trade is attempting to risk {}",
+                trade.currency, remaining, total_risk,
+            ),
+        }));
+    }
+
+    Ok(())
+}
+
+// Returns the trades of the account that are open in the same currency.
+fn open_trades(trade: &Trade, database: &mut dyn DatabaseFactory) -> Vec<Trade> {
+    crate::commands::trade::read_open_trades(
+        trade.account_id,
+        &trade.currency,
+        database.trade_read().as_mut(),
+    )
+    .unwrap_or_else(|_| vec![])
+}
+
+// This function validates the projected concentration of the largest position against the limit.
+fn validate_max_position_concentration(
+    concentration: Decimal,
+    limit: Decimal,
+) -> FundingValidationResult {
+    if concentration <= limit {
+        return Ok(());
+    }
+
+    let message = format!(
+        "Max position concentration exceeded, maximum concentration is {}%, trade is attempting to concentrate {}%",
+        limit,
+        concentration.round_dp(2),
+    );
+
+    Err(Box::new(FundValidationError {
+        code: FundValidationErrorCode::MaxPositionConcentrationExceeded,
+        message,
+    }))
+}
+
+// This function validates that there is no other open trade in the same trading vehicle and direction.
+// A trade in the opposite direction is allowed, given that it hedges the open position.
+fn validate_one_position_per_symbol_side(
+    trade: &Trade,
+    open_trades: &[Trade],
+) -> FundingValidationResult {
+    let duplicated = open_trades.iter().any(|open_trade| {
+        open_trade.id != trade.id
+            && open_trade.trading_vehicle.id == trade.trading_vehicle.id
+            && open_trade.category == trade.category
+    });
+    if !duplicated {
+        return Ok(());
+    }
+
+    let message = format!(
+        "One position per symbol side exceeded, there is already an open {} trade for {}",
+        trade.category, trade.trading_vehicle.symbol,
+    );
+
+    Err(Box::new(FundValidationError {
+        code: FundValidationErrorCode::OnePositionPerSymbolSideExceeded,
+        message,
+    }))
+}
+
+// Counts the losing trades in a row since the last trade that did not lose money.
+// The closed trades must be sorted from the newest to the oldest close.
+fn consecutive_losses(closed_trades: &[Trade]) -> u32 {
+    closed_trades
+        .iter()
+        .take_while(|trade| trade.balance.total_performance < dec!(0))
+        .count() as u32
+}
+
+// This function validates that the current losing streak did not reach the limit.
+fn validate_max_consecutive_losses(streak: u32, limit: u32) -> FundingValidationResult {
+    if streak < limit {
+        return Ok(());
+    }
+
+    let message = format!(
+        "Max consecutive losses reached, the last {} closed trades were losses and the maximum is {}. Take a break until you are ready to trade again",
+        streak, limit,
+    );
+
+    Err(Box::new(FundValidationError {
+        code: FundValidationErrorCode::MaxConsecutiveLossesExceeded,
+        message,
+    }))
+}
+
+/// A breach of a rule with level warning. It does not block the funding of the trade.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RuleWarning {
+    pub rule: RuleName,
+    pub message: String,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct FundValidationError {
     pub code: FundValidationErrorCode,
@@ -159,13 +335,16 @@ impl Error for FundValidationError {
 pub enum FundValidationErrorCode {
     RiskPerTradeExceeded,
     RiskPerMonthExceeded,
+    MaxPositionConcentrationExceeded,
+    OnePositionPerSymbolSideExceeded,
+    MaxConsecutiveLossesExceeded,
     NotEnoughFunds,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use model::Order;
+    use model::{Order, TradeCategory};
     use uuid::Uuid;
 
     #[test]
@@ -266,6 +445,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_risk_budget_per_month() {
+        let trade = Trade {
+            currency: model::Currency::EUR,
+            entry: Order {
+                unit_price: dec!(10),
+                quantity: 50,
+                ..Default::default()
+            },
+            safety_stop: Order {
+                unit_price: dec!(9),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert!(validate_risk_budget_per_month(&trade, dec!(50)).is_ok());
+        assert_eq!(
+            validate_risk_budget_per_month(&trade, dec!(49.5)),
+            Err(Box::new(FundValidationError {
+                code: FundValidationErrorCode::RiskPerMonthExceeded,
+                message: "Risk per month exceeded for EUR, the budget left this month is 49.5, trade is attempting to risk 50".to_string(),
+            }))
+        );
+    }
+
     #[test]
     fn test_risk_per_trade_exceeded() {
         let trade = Trade {
@@ -294,4 +499,196 @@ mod tests {
             }))
         );
     }
+
+    #[test]
+    fn test_risk_per_trade_exceeded_by_short() {
+        let trade = |category, stop_price| Trade {
+            category,
+            entry: Order {
+                unit_price: dec!(10),
+                quantity: 5,
+                ..Default::default()
+            },
+            safety_stop: Order {
+                unit_price: stop_price,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let account_balance = AccountBalance {
+            total_available: dec!(100),
+            ..Default::default()
+        };
+        let long = trade(TradeCategory::Long, dec!(9));
+        let short = trade(TradeCategory::Short, dec!(11));
+
+        let expected = Err(Box::new(FundValidationError {
+            code: FundValidationErrorCode::RiskPerTradeExceeded,
+            message: "Risk per trade exceeded for risk per trade rule, maximum that can be at risk is 3.00, trade is attempting to risk 5".to_string(),
+        }));
+        assert_eq!(
+            validate_risk_per_trade(&long, &account_balance, dec!(3), dec!(5.1)),
+            expected
+        );
+        assert_eq!(
+            validate_risk_per_trade(&short, &account_balance, dec!(3), dec!(5.1)),
+            expected
+        );
+        assert!(validate_risk_per_trade(&short, &account_balance, dec!(5), dec!(6.2)).is_ok());
+    }
+
+    #[test]
+    fn test_max_position_concentration_first_position_exceeded() {
+        assert_eq!(
+            validate_max_position_concentration(dec!(100), dec!(40)),
+            Err(Box::new(FundValidationError {
+                code: FundValidationErrorCode::MaxPositionConcentrationExceeded,
+                message: "Max position concentration exceeded, maximum concentration is 40%, trade is attempting to concentrate 100%".to_string(),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_max_position_concentration_diversified_success() {
+        let concentration = dec!(10000) / dec!(35000) * dec!(100);
+        assert!(validate_max_position_concentration(concentration, dec!(40)).is_ok());
+    }
+
+    #[test]
+    fn test_max_position_concentration_warning_allows_funding() {
+        let result = validate_max_position_concentration(dec!(100), dec!(40));
+        assert_eq!(
+            apply_level(RuleName::MaxPositionConcentration(40.0), &RuleLevel::Warning, result),
+            Ok(Some(RuleWarning {
+                rule: RuleName::MaxPositionConcentration(40.0),
+                message: "Max position concentration exceeded, maximum concentration is 40%, trade is attempting to concentrate 100%".to_string(),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_one_position_per_symbol_side_same_side_exceeded() {
+        let open_trade = Trade {
+            category: TradeCategory::Long,
+            ..Default::default()
+        };
+        let trade = Trade {
+            category: TradeCategory::Long,
+            trading_vehicle: open_trade.trading_vehicle.clone(),
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_one_position_per_symbol_side(&trade, &[open_trade]),
+            Err(Box::new(FundValidationError {
+                code: FundValidationErrorCode::OnePositionPerSymbolSideExceeded,
+                message: "One position per symbol side exceeded, there is already an open long trade for AAPL".to_string(),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_one_position_per_symbol_side_opposite_side_success() {
+        let open_trade = Trade {
+            category: TradeCategory::Long,
+            ..Default::default()
+        };
+        let trade = Trade {
+            category: TradeCategory::Short,
+            trading_vehicle: open_trade.trading_vehicle.clone(),
+            ..Default::default()
+        };
+        assert!(validate_one_position_per_symbol_side(&trade, &[open_trade]).is_ok());
+    }
+
+    #[test]
+    fn test_one_position_per_symbol_side_other_symbol_success() {
+        let open_trade = Trade {
+            category: TradeCategory::Long,
+            ..Default::default()
+        };
+        let trade = Trade {
+            category: TradeCategory::Long,
+            ..Default::default()
+        };
+        assert!(validate_one_position_per_symbol_side(&trade, &[open_trade]).is_ok());
+    }
+
+    fn closed_trade(performance: Decimal) -> Trade {
+        Trade {
+            balance: model::TradeBalance {
+                total_performance: performance,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_consecutive_losses_since_last_win() {
+        let trades = [
+            closed_trade(dec!(-100)),
+            closed_trade(dec!(-50)),
+            closed_trade(dec!(200)),
+            closed_trade(dec!(-10)),
+        ];
+        assert_eq!(consecutive_losses(&trades), 2);
+        assert_eq!(consecutive_losses(&trades[2..]), 0);
+        assert_eq!(consecutive_losses(&[]), 0);
+    }
+
+    #[test]
+    fn test_max_consecutive_losses_reached() {
+        assert_eq!(
+            validate_max_consecutive_losses(3, 3),
+            Err(Box::new(FundValidationError {
+                code: FundValidationErrorCode::MaxConsecutiveLossesExceeded,
+                message: "Max consecutive losses reached, the last 3 closed trades were losses and the maximum is 3. Take a break until you are ready to trade again".to_string(),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_max_consecutive_losses_below_limit_success() {
+        assert!(validate_max_consecutive_losses(2, 3).is_ok());
+    }
+
+    #[test]
+    fn test_max_consecutive_losses_warning_allows_funding() {
+        let result = validate_max_consecutive_losses(5, 3);
+        let warning = apply_level(
+            RuleName::MaxConsecutiveLosses(3),
+            &RuleLevel::Warning,
+            result,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(warning.rule, RuleName::MaxConsecutiveLosses(3));
+    }
+
+    #[test]
+    fn test_apply_level() {
+        let rule = RuleName::RiskPerTrade(2.0);
+        let breach = || {
+            Err(Box::new(FundValidationError {
+                code: FundValidationErrorCode::RiskPerTradeExceeded,
+                message: "Risk per trade exceeded".to_string(),
+            }))
+        };
+
+        assert_eq!(apply_level(rule, &RuleLevel::Error, Ok(())), Ok(None));
+        assert_eq!(
+            apply_level(rule, &RuleLevel::Error, breach())
+                .unwrap_err()
+                .code,
+            FundValidationErrorCode::RiskPerTradeExceeded
+        );
+        assert_eq!(
+            apply_level(rule, &RuleLevel::Warning, breach()),
+            Ok(Some(RuleWarning {
+                rule,
+                message: "Risk per trade exceeded".to_string(),
+            }))
+        );
+        assert_eq!(apply_level(rule, &RuleLevel::Advice, breach()), Ok(None));
+    }
 }
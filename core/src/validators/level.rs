@@ -0,0 +1,133 @@
+use model::{Level, LevelTrigger};
+use std::error::Error;
+
+type LevelValidationResult = Result<(), Box<LevelValidationError>>;
+
+// Validate that an account can move from its level to a new level.
+// Every change must explain why it happened, so the history of levels can be audited.
+pub fn can_change(
+    level: &Level,
+    new_level: u8,
+    reason: &str,
+    trigger: &LevelTrigger,
+) -> LevelValidationResult {
+    if !(Level::MIN..=Level::MAX).contains(&new_level) {
+        return Err(Box::new(LevelValidationError {
+            code: LevelValidationErrorCode::LevelOutOfRange,
+            message: format!(
+                "The level {} must be between {} and {}",
+                new_level,
+                Level::MIN,
+                Level::MAX
+            ),
+        }));
+    }
+
+    if new_level == level.current_level {
+        return Err(Box::new(LevelValidationError {
+            code: LevelValidationErrorCode::SameLevel,
+            message: format!("The account is already in level {}", new_level),
+        }));
+    }
+
+    if reason.trim().is_empty() {
+        return Err(Box::new(LevelValidationError {
+            code: LevelValidationErrorCode::EmptyReason,
+            message: "The reason of a level change can not be empty".to_string(),
+        }));
+    }
+
+    if let LevelTrigger::Custom(name) = trigger {
+        if name.trim().is_empty() {
+            return Err(Box::new(LevelValidationError {
+                code: LevelValidationErrorCode::EmptyCustomTrigger,
+                message: "The name of a custom trigger can not be empty".to_string(),
+            }));
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+pub struct LevelValidationError {
+    pub code: LevelValidationErrorCode,
+    pub message: String,
+}
+
+impl std::fmt::Display for LevelValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "LevelValidationError: {}", self.message)
+    }
+}
+
+impl Error for LevelValidationError {
+    fn description(&self) -> &str {
+        &self.message
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LevelValidationErrorCode {
+    LevelOutOfRange,
+    SameLevel,
+    EmptyReason,
+    EmptyCustomTrigger,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_can_change() {
+        let level = Level::default();
+        assert!(can_change(&level, 2, "Too many losses", &LevelTrigger::ManualOverride).is_ok());
+        assert!(can_change(
+            &level,
+            Level::MAX,
+            "Approved by my coach",
+            &LevelTrigger::Custom("coach".to_string())
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_can_change_out_of_range() {
+        let level = Level::default();
+        let error = can_change(&level, 5, "reason", &LevelTrigger::ManualOverride).unwrap_err();
+        assert_eq!(error.code, LevelValidationErrorCode::LevelOutOfRange);
+    }
+
+    #[test]
+    fn test_can_change_to_the_same_level() {
+        let level = Level::default();
+        let error = can_change(
+            &level,
+            Level::DEFAULT,
+            "reason",
+            &LevelTrigger::ManualOverride,
+        )
+        .unwrap_err();
+        assert_eq!(error.code, LevelValidationErrorCode::SameLevel);
+    }
+
+    #[test]
+    fn test_can_change_with_empty_reason() {
+        let level = Level::default();
+        for reason in ["", "   \t\n"] {
+            let error = can_change(&level, 2, reason, &LevelTrigger::ManualOverride).unwrap_err();
+            assert_eq!(error.code, LevelValidationErrorCode::EmptyReason);
+        }
+    }
+
+    #[test]
+    fn test_can_change_with_empty_custom_trigger() {
+        let level = Level::default();
+        for name in ["", "  "] {
+            let error = can_change(&level, 2, "reason", &LevelTrigger::Custom(name.to_string()))
+                .unwrap_err();
+            assert_eq!(error.code, LevelValidationErrorCode::EmptyCustomTrigger);
+        }
+    }
+}
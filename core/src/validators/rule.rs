@@ -8,7 +8,12 @@ pub fn can_create(
     account: &Account,
     database: &mut dyn ReadRuleDB,
 ) -> RuleValidationResult {
-    if database.rule_for_account(account.id, rule).is_ok() {
+    // Validate that there is no other active rule of the same type in the account
+    let rules = database.read_all_rules(account.id).unwrap_or_default();
+    if rules
+        .iter()
+        .any(|existing| std::mem::discriminant(&existing.name) == std::mem::discriminant(rule))
+    {
         Err(Box::new(RuleValidationError {
             code: RuleValidationErrorCode::RuleAlreadyExistsInAccount,
             message: format!(
@@ -21,10 +26,29 @@ pub fn can_create(
     }
 }
 
+pub fn can_create_with_percentage(rule: &RuleName) -> RuleValidationResult {
+    // Validate that the risk percentages are a number between 0 and 100
+    match rule {
+        RuleName::RiskPerTrade(value) | RuleName::RiskPerMonth(value)
+            if !value.is_finite() || *value < 0.0 || *value > 100.0 =>
+        {
+            Err(Box::new(RuleValidationError {
+                code: RuleValidationErrorCode::InvalidRulePercentage,
+                message: format!(
+                    "Rule {} must be a percentage between 0 and 100 and it is {}",
+                    rule, value
+                ),
+            }))
+        }
+        _ => Ok(()),
+    }
+}
+
 #[derive(Debug, PartialEq)]
 
 pub enum RuleValidationErrorCode {
     RuleAlreadyExistsInAccount,
+    InvalidRulePercentage,
 }
 
 #[derive(Debug)]
@@ -44,3 +68,58 @@ impl Error for RuleValidationError {
         &self.message
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_can_create_with_valid_percentage() {
+        for rule in [
+            RuleName::RiskPerTrade(0.0),
+            RuleName::RiskPerTrade(2.0),
+            RuleName::RiskPerMonth(100.0),
+            RuleName::MinRewardRiskRatio(150.0),
+        ] {
+            assert!(can_create_with_percentage(&rule).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_can_create_with_negative_percentage() {
+        let result = can_create_with_percentage(&RuleName::RiskPerTrade(-1.0));
+        assert_eq!(
+            result.unwrap_err().code,
+            RuleValidationErrorCode::InvalidRulePercentage
+        );
+    }
+
+    #[test]
+    fn test_can_create_with_percentage_over_100() {
+        let result = can_create_with_percentage(&RuleName::RiskPerMonth(100.1));
+        assert_eq!(
+            result.unwrap_err().code,
+            RuleValidationErrorCode::InvalidRulePercentage
+        );
+    }
+
+    #[test]
+    fn test_can_create_with_nan_percentage() {
+        let result = can_create_with_percentage(&RuleName::RiskPerTrade(f32::NAN));
+        assert_eq!(
+            result.unwrap_err().code,
+            RuleValidationErrorCode::InvalidRulePercentage
+        );
+    }
+
+    #[test]
+    fn test_can_create_with_infinite_percentage() {
+        for value in [f32::INFINITY, f32::NEG_INFINITY] {
+            let result = can_create_with_percentage(&RuleName::RiskPerMonth(value));
+            assert_eq!(
+                result.unwrap_err().code,
+                RuleValidationErrorCode::InvalidRulePercentage
+            );
+        }
+    }
+}
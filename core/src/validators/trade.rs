@@ -1,12 +1,213 @@
-use model::{Status, Trade, TradeCategory};
+use crate::calculators_trade::TickSizeCalculator;
+use model::{
+    Account, DraftTrade, ExecutionSide, OrderCategory, Rule, RuleLevel, RuleName, Status,
+    TimeInForce, Trade, TradeCategory, TradingVehicleCategory,
+};
 use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
 use std::error::Error;
+use tracing::warn;
 
 type TradeValidationResult = Result<(), Box<TradeValidationError>>;
 
+/// Token that must be given back to create a trade above the account's
+/// confirmation threshold. It is bound to the symbol, quantity and entry price,
+/// so a confirmation can not be reused for a different trade.
+pub fn confirmation_token(trade: &DraftTrade, entry_price: Decimal) -> String {
+    format!(
+        "{}-{}-{}",
+        trade.trading_vehicle.symbol,
+        trade.decimal_quantity(),
+        entry_price
+    )
+}
+
+pub fn can_create(
+    trade: &DraftTrade,
+    account: &Account,
+    entry_price: Decimal,
+    confirmation: Option<&str>,
+) -> TradeValidationResult {
+    validate_quote_currency(trade)?;
+    validate_fractional_quantity(trade)?;
+    validate_confirmation(trade, account, entry_price, confirmation)
+}
+
+fn validate_quote_currency(trade: &DraftTrade) -> TradeValidationResult {
+    match trade.trading_vehicle.quote_currency {
+        Some(currency) if currency != trade.currency => Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::CurrencyMismatch,
+            message: format!(
+                "Trading vehicle {} is quoted in {}, it can not be traded in {}",
+                trade.trading_vehicle.symbol, currency, trade.currency
+            ),
+        })),
+        _ => Ok(()),
+    }
+}
+
+// Only crypto can be traded in fractions, the rest of trading vehicles keep the integer quantity
+fn validate_fractional_quantity(trade: &DraftTrade) -> TradeValidationResult {
+    let quantity = match trade.quantity_decimal {
+        Some(quantity) => quantity,
+        None => return Ok(()),
+    };
+
+    if trade.trading_vehicle.category != TradingVehicleCategory::Crypto {
+        return Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::FractionalQuantityNotAllowed,
+            message: format!(
+                "Trading vehicle {} is a {}, only crypto can be traded in fractions",
+                trade.trading_vehicle.symbol, trade.trading_vehicle.category
+            ),
+        }));
+    }
+
+    if quantity <= Decimal::ZERO {
+        return Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::FractionalQuantityNotAllowed,
+            message: format!("The quantity {} must be greater than 0", quantity),
+        }));
+    }
+
+    Ok(())
+}
+
+// The threshold is taken from the stored account, not from the snapshot of the draft
+fn validate_confirmation(
+    trade: &DraftTrade,
+    account: &Account,
+    entry_price: Decimal,
+    confirmation: Option<&str>,
+) -> TradeValidationResult {
+    let threshold = match account.confirm_above_notional {
+        Some(threshold) => threshold,
+        None => return Ok(()),
+    };
+
+    let notional = entry_price * trade.decimal_quantity();
+    if notional <= threshold {
+        return Ok(());
+    }
+
+    match confirmation {
+        Some(confirmation) if confirmation == confirmation_token(trade, entry_price) => Ok(()),
+        _ => Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::ConfirmationRequired,
+            message: format!(
+                "Trade notional {} is above the confirmation threshold {} of the account, the trade must be confirmed",
+                notional, threshold
+            ),
+        })),
+    }
+}
+
+/// Validates that the stop, entry and target prices are multiples of the
+/// tick size of the trading vehicle. Otherwise an order may never be filled.
+pub fn can_create_at_prices(
+    trade: &DraftTrade,
+    stop_price: Decimal,
+    entry_price: Decimal,
+    target_price: Decimal,
+) -> TradeValidationResult {
+    let tick_size = match trade.trading_vehicle.tick_size {
+        Some(tick_size) => tick_size,
+        None => return Ok(()),
+    };
+
+    for (name, price) in [
+        ("stop", stop_price),
+        ("entry", entry_price),
+        ("target", target_price),
+    ] {
+        if !TickSizeCalculator::is_on_grid(price, tick_size) {
+            return Err(Box::new(TradeValidationError {
+                code: TradeValidationErrorCode::PriceNotOnTickSize,
+                message: format!(
+                    "The {} price {} of {} is not a multiple of its tick size {}",
+                    name, price, trade.trading_vehicle.symbol, tick_size
+                ),
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// Validates the trade against the rules of the account that are applied when a trade is created.
+pub fn can_create_with_rules(
+    trade: &DraftTrade,
+    stop_price: Decimal,
+    entry_price: Decimal,
+    target_price: Decimal,
+    rules: &[Rule],
+) -> TradeValidationResult {
+    for rule in rules {
+        if let RuleName::MinRewardRiskRatio(ratio) = rule.name {
+            validate_min_reward_risk_ratio(
+                trade,
+                stop_price,
+                entry_price,
+                target_price,
+                Decimal::from_f32_retain(ratio).unwrap(),
+                &rule.level,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+// Validate that the reward of the trade per unit of risk is at least the minimum ratio.
+// Only rules with level error block the creation, other levels just log a warning.
+fn validate_min_reward_risk_ratio(
+    trade: &DraftTrade,
+    stop_price: Decimal,
+    entry_price: Decimal,
+    target_price: Decimal,
+    minimum: Decimal,
+    level: &RuleLevel,
+) -> TradeValidationResult {
+    let (reward, risk) = match trade.category {
+        TradeCategory::Long => (target_price - entry_price, entry_price - stop_price),
+        TradeCategory::Short => (entry_price - target_price, stop_price - entry_price),
+    };
+
+    // A trade without risk has no ratio, so it can not be compared with the minimum.
+    if risk <= dec!(0) {
+        return Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::StopPriceNotValid,
+            message: format!(
+                "The {} trade of {} has no risk with the entry at {} and the stop at {}, so its reward/risk ratio can not be calculated",
+                trade.category, trade.trading_vehicle.symbol, entry_price, stop_price
+            ),
+        }));
+    }
+
+    let ratio = reward / risk;
+    if ratio >= minimum {
+        return Ok(());
+    }
+
+    let message = format!(
+        "Min reward/risk ratio not met, the minimum ratio is {}:1 and the trade is {}:1",
+        minimum,
+        ratio.round_dp(2).normalize(),
+    );
+
+    match level {
+        RuleLevel::Error => Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::MinRewardRiskRatioNotMet,
+            message,
+        })),
+        RuleLevel::Warning | RuleLevel::Advice => {
+            warn!("{}", message);
+            Ok(())
+        }
+    }
+}
+
 pub fn can_submit(trade: &Trade) -> TradeValidationResult {
     match trade.status {
-        Status::Funded => Ok(()),
+        Status::Funded => can_rest_stop_with(trade.entry.time_in_force),
         _ => Err(Box::new(TradeValidationError {
             code: TradeValidationErrorCode::TradeNotFunded,
             message: format!(
@@ -17,6 +218,54 @@ pub fn can_submit(trade: &Trade) -> TradeValidationResult {
     }
 }
 
+// Validate that no other trade of the account like this one was submitted recently
+pub fn can_submit_without_duplicate(
+    trade: &Trade,
+    duplicate: Option<&Trade>,
+) -> TradeValidationResult {
+    match duplicate {
+        Some(duplicate) => Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::DuplicateTrade,
+            message: format!(
+                "Trade with id {} duplicates trade {}, submitted at {}. Force the submit if both are intended",
+                trade.id,
+                duplicate.id,
+                duplicate.entry.submitted_at.unwrap_or_default()
+            ),
+        })),
+        None => Ok(()),
+    }
+}
+
+// Validate that the entry of a trade that was not submitted can use the time in force
+pub fn can_use_time_in_force(trade: &Trade, time_in_force: TimeInForce) -> TradeValidationResult {
+    if !matches!(trade.status, Status::New | Status::Funded) {
+        return Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::TradeAlreadySubmitted,
+            message: format!(
+                "Trade with id {} was already submitted, its time in force can not be changed",
+                trade.id
+            ),
+        }));
+    }
+    can_rest_stop_with(time_in_force)
+}
+
+// The stop of a trade rests in the broker until the entry is filled. An entry that is canceled
+// if it is not filled immediately would leave the stop without a position to protect.
+fn can_rest_stop_with(time_in_force: TimeInForce) -> TradeValidationResult {
+    if time_in_force.is_immediate() {
+        return Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::TimeInForceNotValid,
+            message: format!(
+                "The entry can not be {} because the stop of the trade rests in the broker",
+                time_in_force
+            ),
+        }));
+    }
+    Ok(())
+}
+
 pub fn can_close(trade: &Trade) -> TradeValidationResult {
     match trade.status {
         Status::Filled => Ok(()),
@@ -27,6 +276,26 @@ pub fn can_close(trade: &Trade) -> TradeValidationResult {
     }
 }
 
+// A filled trade can be closed at a positive limit on the side of the stop where it is still open.
+// A long below its stop, or a short above it, would be closed by the stop before the limit.
+pub fn can_close_at_limit(trade: &Trade, limit_price: Decimal) -> TradeValidationResult {
+    can_close(trade)?;
+
+    if limit_price <= dec!(0)
+        || trade.category == TradeCategory::Long && limit_price <= trade.safety_stop.unit_price
+        || trade.category == TradeCategory::Short && limit_price >= trade.safety_stop.unit_price
+    {
+        return Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::LimitPriceNotValid,
+            message: format!(
+                "The limit {} to close the {} trade must be positive and on the side of the stop {} where the trade is open",
+                limit_price, trade.category, trade.safety_stop.unit_price
+            ),
+        }));
+    }
+    Ok(())
+}
+
 pub fn can_cancel_funded(trade: &Trade) -> TradeValidationResult {
     match trade.status {
         Status::Funded => Ok(()),
@@ -54,6 +323,16 @@ pub fn can_cancel_submitted(trade: &Trade) -> TradeValidationResult {
 }
 
 pub fn can_modify_stop(trade: &Trade, new_price_stop: Decimal) -> TradeValidationResult {
+    if let OrderCategory::TrailingStop { .. } = trade.safety_stop.category {
+        return Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::StopPriceNotValid,
+            message: format!(
+                "The stop of trade {} is a trailing stop. It follows the price and it can not be moved",
+                trade.id
+            ),
+        }));
+    }
+
     if trade.category == TradeCategory::Long && trade.safety_stop.unit_price > new_price_stop
         || trade.category == TradeCategory::Short && trade.safety_stop.unit_price < new_price_stop
     {
@@ -78,6 +357,49 @@ pub fn can_modify_stop(trade: &Trade, new_price_stop: Decimal) -> TradeValidatio
     }
 }
 
+// Validate that the stop of a trade that was not submitted can trail the price by a percent or by a price.
+// Exactly one of them is set, and the trail can not give the trade more room than its stop.
+pub fn can_trail_stop(
+    trade: &Trade,
+    trail_percent: Option<Decimal>,
+    trail_price: Option<Decimal>,
+) -> TradeValidationResult {
+    if !matches!(trade.status, Status::New | Status::Funded) {
+        return Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::TradeAlreadySubmitted,
+            message: format!(
+                "Trade with id {} was already submitted, its stop can not trail the price",
+                trade.id
+            ),
+        }));
+    }
+
+    let trail = match (trail_percent, trail_price) {
+        (Some(percent), None) if percent > dec!(0) && percent < dec!(100) => {
+            trade.entry.unit_price * percent / dec!(100)
+        }
+        (None, Some(price)) if price > dec!(0) => price,
+        _ => {
+            return Err(Box::new(TradeValidationError {
+                code: TradeValidationErrorCode::TrailNotValid,
+                message: "The stop must trail the price by a positive percent below 100 or by a positive price, exactly one of them".to_string(),
+            }))
+        }
+    };
+
+    let room = (trade.entry.unit_price - trade.safety_stop.unit_price).abs();
+    if trail > room {
+        return Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::TrailNotValid,
+            message: format!(
+                "The trail of {} gives the trade more room than its stop. The stop is {} away from the entry",
+                trail, room
+            ),
+        }));
+    }
+    Ok(())
+}
+
 pub fn can_modify_target(trade: &Trade) -> TradeValidationResult {
     match trade.status {
         Status::Filled => Ok(()),
@@ -91,12 +413,109 @@ pub fn can_modify_target(trade: &Trade) -> TradeValidationResult {
     }
 }
 
+// Validate that the side of an execution matches the entry of the trade or, once the
+// trade is closed, its exit.
+pub fn can_backfill_execution(trade: &Trade, side: ExecutionSide) -> TradeValidationResult {
+    let entry_side = match trade.category {
+        TradeCategory::Long => ExecutionSide::Buy,
+        TradeCategory::Short => ExecutionSide::Sell,
+    };
+    let is_closed = trade.status == Status::ClosedTarget || trade.status == Status::ClosedStopLoss;
+
+    if side == entry_side || is_closed {
+        return Ok(());
+    }
+
+    Err(Box::new(TradeValidationError {
+        code: TradeValidationErrorCode::ExecutionSideNotValid,
+        message: format!(
+            "Trade with id {} is a {} trade with status {}, a {} execution can only be backfilled once it is closed",
+            trade.id, trade.category, trade.status, side
+        ),
+    }))
+}
+
+// Validate that the trades with the status can be canceled all together
+pub fn can_bulk_cancel(status: Status) -> TradeValidationResult {
+    match status {
+        Status::Funded | Status::Submitted => Ok(()),
+        _ => Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::StatusNotCancelable,
+            message: format!(
+                "Trades with status {} can not be canceled, only funded or submitted trades",
+                status
+            ),
+        })),
+    }
+}
+
+// Validate that the reference is not blank and that no other trade of the account uses it
+pub fn can_use_reference(reference: &str, existing: Option<&Trade>) -> TradeValidationResult {
+    model::validate_reference(reference).map_err(|error| {
+        Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::ReferenceNotValid,
+            message: error.message,
+        })
+    })?;
+
+    match existing {
+        Some(trade) => Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::ReferenceAlreadyExists,
+            message: format!(
+                "The reference {} is already used by trade {} in this account",
+                reference, trade.id
+            ),
+        })),
+        None => Ok(()),
+    }
+}
+
+// Validate that the thesis is not longer than the documented limit
+pub fn can_use_thesis(thesis: &str) -> TradeValidationResult {
+    model::validate_thesis(thesis).map_err(|error| {
+        Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::ThesisTooLong,
+            message: error.message,
+        })
+    })
+}
+
+// Validate that the trade is not closed, so its thesis can still be revised
+pub fn can_revise_thesis(trade: &Trade) -> TradeValidationResult {
+    if trade.status.is_terminal() {
+        return Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::TradeClosed,
+            message: format!(
+                "Trade with id {} is {} and its thesis can not be revised",
+                trade.id, trade.status
+            ),
+        }));
+    }
+    Ok(())
+}
+
 #[derive(Debug, PartialEq)]
 
 pub enum TradeValidationErrorCode {
     TradeNotFunded,
     TradeNotFilled,
     StopPriceNotValid,
+    ConfirmationRequired,
+    CurrencyMismatch,
+    PriceNotOnTickSize,
+    FractionalQuantityNotAllowed,
+    ExecutionSideNotValid,
+    MinRewardRiskRatioNotMet,
+    ReferenceNotValid,
+    ReferenceAlreadyExists,
+    ThesisTooLong,
+    TradeClosed,
+    StatusNotCancelable,
+    LimitPriceNotValid,
+    TradeAlreadySubmitted,
+    TrailNotValid,
+    TimeInForceNotValid,
+    DuplicateTrade,
 }
 
 #[derive(Debug)]
@@ -120,8 +539,139 @@ impl Error for TradeValidationError {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use model::{Currency, Order, TradingVehicle};
     use rust_decimal_macros::dec;
 
+    fn draft(confirm_above_notional: Option<Decimal>) -> DraftTrade {
+        DraftTrade {
+            account: Account {
+                confirm_above_notional,
+                ..Default::default()
+            },
+            trading_vehicle: TradingVehicle::default(),
+            quantity: 100,
+            quantity_decimal: None,
+            currency: Currency::USD,
+            category: TradeCategory::Long,
+            strategy_id: None,
+            reference: None,
+            thesis: None,
+            context: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_create_without_threshold() {
+        assert!(can_create(&draft(None), &Account::default(), dec!(1000), None).is_ok());
+    }
+
+    #[test]
+    fn test_validate_create_below_threshold() {
+        let trade = draft(Some(dec!(10000)));
+        assert!(can_create(&trade, &trade.account, dec!(100), None).is_ok());
+    }
+
+    #[test]
+    fn test_validate_create_above_threshold_requires_token() {
+        let trade = draft(Some(dec!(10000)));
+        let result = can_create(&trade, &trade.account, dec!(101), None);
+        assert_eq!(
+            result.unwrap_err().code,
+            TradeValidationErrorCode::ConfirmationRequired
+        );
+
+        let result = can_create(&trade, &trade.account, dec!(101), Some("wrong-token"));
+        assert!(result.is_err());
+
+        let token = confirmation_token(&trade, dec!(101));
+        assert_eq!(token, "AAPL-100-101");
+        assert!(can_create(&trade, &trade.account, dec!(101), Some(&token)).is_ok());
+    }
+
+    #[test]
+    fn test_validate_create_threshold_of_the_stored_account() {
+        let trade = draft(None);
+        let account = Account {
+            confirm_above_notional: Some(dec!(10000)),
+            ..Default::default()
+        };
+
+        let error = can_create(&trade, &account, dec!(101), None).unwrap_err();
+
+        assert_eq!(error.code, TradeValidationErrorCode::ConfirmationRequired);
+        assert_eq!(
+            error.message,
+            "Trade notional 10100 is above the confirmation threshold 10000 of the account, the trade must be confirmed"
+        );
+        assert!(!error
+            .message
+            .contains(&confirmation_token(&trade, dec!(101))));
+    }
+
+    #[test]
+    fn test_validate_create_quote_currency_mismatch() {
+        let mut trade = draft(None);
+        trade.trading_vehicle.quote_currency = Some(Currency::EUR);
+
+        let result = can_create(&trade, &trade.account, dec!(100), None);
+
+        let error = result.unwrap_err();
+        assert_eq!(error.code, TradeValidationErrorCode::CurrencyMismatch);
+        assert_eq!(
+            error.message,
+            "Trading vehicle AAPL is quoted in EUR, it can not be traded in USD"
+        );
+    }
+
+    #[test]
+    fn test_validate_create_quote_currency_match() {
+        let mut trade = draft(None);
+        trade.trading_vehicle.quote_currency = Some(Currency::USD);
+        assert!(can_create(&trade, &trade.account, dec!(100), None).is_ok());
+    }
+
+    #[test]
+    fn test_validate_create_fractional_quantity() {
+        let mut trade = draft(None);
+        trade.quantity_decimal = Some(dec!(0.25));
+
+        let error = can_create(&trade, &trade.account, dec!(100), None).unwrap_err();
+        assert_eq!(
+            error.code,
+            TradeValidationErrorCode::FractionalQuantityNotAllowed
+        );
+
+        trade.trading_vehicle.category = TradingVehicleCategory::Crypto;
+        assert!(can_create(&trade, &trade.account, dec!(100), None).is_ok());
+
+        trade.quantity_decimal = Some(dec!(0));
+        assert!(can_create(&trade, &trade.account, dec!(100), None).is_err());
+    }
+
+    #[test]
+    fn test_validate_create_at_prices_off_tick_size() {
+        let mut trade = draft(None);
+        trade.trading_vehicle.tick_size = Some(dec!(0.05));
+
+        let result = can_create_at_prices(&trade, dec!(38), dec!(40), dec!(50.03));
+
+        let error = result.unwrap_err();
+        assert_eq!(error.code, TradeValidationErrorCode::PriceNotOnTickSize);
+        assert_eq!(
+            error.message,
+            "The target price 50.03 of AAPL is not a multiple of its tick size 0.05"
+        );
+    }
+
+    #[test]
+    fn test_validate_create_at_prices_on_tick_size() {
+        let mut trade = draft(None);
+        assert!(can_create_at_prices(&trade, dec!(38.01), dec!(40), dec!(50.03)).is_ok());
+
+        trade.trading_vehicle.tick_size = Some(dec!(0.05));
+        assert!(can_create_at_prices(&trade, dec!(38), dec!(40.05), dec!(50.1)).is_ok());
+    }
+
     #[test]
     fn test_validate_submit_funded() {
         let trade = Trade {
@@ -131,6 +681,59 @@ mod tests {
         assert!(can_submit(&trade).is_ok());
     }
 
+    #[test]
+    fn test_validate_submit_with_immediate_entry() {
+        for time_in_force in [TimeInForce::ImmediateOrCancel, TimeInForce::FillOrKill] {
+            let trade = Trade {
+                status: Status::Funded,
+                entry: Order {
+                    time_in_force,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let error = can_submit(&trade).unwrap_err();
+            assert_eq!(error.code, TradeValidationErrorCode::TimeInForceNotValid);
+        }
+    }
+
+    #[test]
+    fn test_validate_submit_without_duplicate() {
+        let trade = Trade::default();
+        assert!(can_submit_without_duplicate(&trade, None).is_ok());
+
+        let duplicate = Trade::default();
+        let error = can_submit_without_duplicate(&trade, Some(&duplicate)).unwrap_err();
+        assert_eq!(error.code, TradeValidationErrorCode::DuplicateTrade);
+    }
+
+    #[test]
+    fn test_validate_time_in_force() {
+        let trade = Trade {
+            status: Status::Funded,
+            ..Default::default()
+        };
+        for time_in_force in [
+            TimeInForce::Day,
+            TimeInForce::UntilCanceled,
+            TimeInForce::UntilMarketOpen,
+            TimeInForce::UntilMarketClose,
+        ] {
+            assert!(can_use_time_in_force(&trade, time_in_force).is_ok());
+        }
+        for time_in_force in [TimeInForce::ImmediateOrCancel, TimeInForce::FillOrKill] {
+            let error = can_use_time_in_force(&trade, time_in_force).unwrap_err();
+            assert_eq!(error.code, TradeValidationErrorCode::TimeInForceNotValid);
+        }
+
+        let submitted = Trade {
+            status: Status::Submitted,
+            ..Default::default()
+        };
+        let error = can_use_time_in_force(&submitted, TimeInForce::Day).unwrap_err();
+        assert_eq!(error.code, TradeValidationErrorCode::TradeAlreadySubmitted);
+    }
+
     #[test]
     fn test_validate_submit_not_funded() {
         let trade = Trade {
@@ -158,6 +761,39 @@ mod tests {
         assert!(can_close(&trade).is_err());
     }
 
+    #[test]
+    fn test_validate_close_at_limit() {
+        let trade = |category, stop_price, status| Trade {
+            category,
+            status,
+            safety_stop: Order {
+                unit_price: stop_price,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let long = trade(TradeCategory::Long, dec!(38), Status::Filled);
+        let short = trade(TradeCategory::Short, dec!(42), Status::Filled);
+
+        assert!(can_close_at_limit(&long, dec!(45)).is_ok());
+        assert!(can_close_at_limit(&short, dec!(35)).is_ok());
+        for (trade, limit_price) in [
+            (&long, dec!(38)),
+            (&long, dec!(30)),
+            (&short, dec!(42)),
+            (&short, dec!(50)),
+            (&short, dec!(0)),
+            (&short, dec!(-1)),
+        ] {
+            let error = can_close_at_limit(trade, limit_price).unwrap_err();
+            assert_eq!(error.code, TradeValidationErrorCode::LimitPriceNotValid);
+        }
+
+        let submitted = trade(TradeCategory::Long, dec!(38), Status::Submitted);
+        let error = can_close_at_limit(&submitted, dec!(45)).unwrap_err();
+        assert_eq!(error.code, TradeValidationErrorCode::TradeNotFilled);
+    }
+
     #[test]
     fn test_validate_cancel_funded() {
         let trade = Trade {
@@ -218,6 +854,62 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_validate_modify_trailing_stop() {
+        let trade = Trade {
+            status: Status::Filled,
+            safety_stop: Order {
+                category: OrderCategory::TrailingStop {
+                    trail_percent: Some(dec!(2)),
+                    trail_price: None,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let error = can_modify_stop(&trade, dec!(10)).unwrap_err();
+        assert_eq!(error.code, TradeValidationErrorCode::StopPriceNotValid);
+    }
+
+    #[test]
+    fn test_validate_trail_stop() {
+        let trade = Trade {
+            status: Status::Funded,
+            entry: Order {
+                unit_price: dec!(40),
+                ..Default::default()
+            },
+            safety_stop: Order {
+                unit_price: dec!(38),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert!(can_trail_stop(&trade, Some(dec!(5)), None).is_ok());
+        assert!(can_trail_stop(&trade, None, Some(dec!(1.5))).is_ok());
+
+        for (percent, price) in [
+            (None, None),
+            (Some(dec!(2)), Some(dec!(1))),
+            (Some(dec!(0)), None),
+            (Some(dec!(100)), None),
+            (None, Some(dec!(-1))),
+            (Some(dec!(5.1)), None),
+            (None, Some(dec!(2.5))),
+        ] {
+            let error = can_trail_stop(&trade, percent, price).unwrap_err();
+            assert_eq!(error.code, TradeValidationErrorCode::TrailNotValid);
+        }
+
+        let submitted = Trade {
+            status: Status::Submitted,
+            ..trade
+        };
+        let error = can_trail_stop(&submitted, Some(dec!(2)), None).unwrap_err();
+        assert_eq!(error.code, TradeValidationErrorCode::TradeAlreadySubmitted);
+    }
+
     #[test]
     fn test_validate_modify_stop_risking_more_money_long() {
         let trade = Trade {
@@ -327,4 +1019,145 @@ mod tests {
         let result = can_modify_target(&trade);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_validate_backfill_execution() {
+        let trade = Trade {
+            status: Status::Filled,
+            category: TradeCategory::Long,
+            ..Default::default()
+        };
+        assert!(can_backfill_execution(&trade, ExecutionSide::Buy).is_ok());
+        let error = can_backfill_execution(&trade, ExecutionSide::Sell).unwrap_err();
+        assert_eq!(error.code, TradeValidationErrorCode::ExecutionSideNotValid);
+
+        let trade = Trade {
+            status: Status::ClosedTarget,
+            category: TradeCategory::Long,
+            ..Default::default()
+        };
+        assert!(can_backfill_execution(&trade, ExecutionSide::Sell).is_ok());
+    }
+
+    fn min_reward_risk_ratio(ratio: f32, level: RuleLevel) -> Rule {
+        Rule {
+            id: uuid::Uuid::new_v4(),
+            created_at: chrono::Utc::now().naive_utc(),
+            updated_at: chrono::Utc::now().naive_utc(),
+            deleted_at: None,
+            name: RuleName::MinRewardRiskRatio(ratio),
+            description: "description".to_string(),
+            priority: 5,
+            level,
+            account_id: uuid::Uuid::new_v4(),
+            active: true,
+        }
+    }
+
+    #[test]
+    fn test_validate_min_reward_risk_ratio_long() {
+        let rules = [min_reward_risk_ratio(2.0, RuleLevel::Error)];
+        let trade = draft(None);
+
+        // Risk 2 and reward 3
+        let error =
+            can_create_with_rules(&trade, dec!(38), dec!(40), dec!(43), &rules).unwrap_err();
+        assert_eq!(
+            error.code,
+            TradeValidationErrorCode::MinRewardRiskRatioNotMet
+        );
+        assert!(error.message.contains("1.5:1"));
+
+        // Risk 2 and reward 6
+        assert!(can_create_with_rules(&trade, dec!(38), dec!(40), dec!(46), &rules).is_ok());
+    }
+
+    #[test]
+    fn test_validate_min_reward_risk_ratio_short() {
+        let rules = [min_reward_risk_ratio(2.0, RuleLevel::Error)];
+        let trade = DraftTrade {
+            category: TradeCategory::Short,
+            ..draft(None)
+        };
+
+        let error =
+            can_create_with_rules(&trade, dec!(42), dec!(40), dec!(37), &rules).unwrap_err();
+        assert_eq!(
+            error.code,
+            TradeValidationErrorCode::MinRewardRiskRatioNotMet
+        );
+        assert!(can_create_with_rules(&trade, dec!(42), dec!(40), dec!(34), &rules).is_ok());
+    }
+
+    #[test]
+    fn test_validate_min_reward_risk_ratio_without_risk() {
+        let rules = [min_reward_risk_ratio(2.0, RuleLevel::Error)];
+        let error =
+            can_create_with_rules(&draft(None), dec!(40), dec!(40), dec!(46), &rules).unwrap_err();
+        assert_eq!(error.code, TradeValidationErrorCode::StopPriceNotValid);
+    }
+
+    #[test]
+    fn test_validate_min_reward_risk_ratio_warning() {
+        let rules = [min_reward_risk_ratio(2.0, RuleLevel::Warning)];
+        assert!(can_create_with_rules(&draft(None), dec!(38), dec!(40), dec!(43), &rules).is_ok());
+        assert!(can_create_with_rules(&draft(None), dec!(38), dec!(40), dec!(43), &[]).is_ok());
+    }
+
+    #[test]
+    fn test_validate_reference() {
+        assert!(can_use_reference("AAPL-2024-001", None).is_ok());
+
+        let error = can_use_reference("  ", None).unwrap_err();
+        assert_eq!(error.code, TradeValidationErrorCode::ReferenceNotValid);
+
+        let existing = Trade::default();
+        let error = can_use_reference("AAPL-2024-001", Some(&existing)).unwrap_err();
+        assert_eq!(error.code, TradeValidationErrorCode::ReferenceAlreadyExists);
+    }
+
+    #[test]
+    fn test_validate_bulk_cancel() {
+        assert!(can_bulk_cancel(Status::Funded).is_ok());
+        assert!(can_bulk_cancel(Status::Submitted).is_ok());
+
+        for status in [Status::New, Status::Filled, Status::Canceled] {
+            let error = can_bulk_cancel(status).unwrap_err();
+            assert_eq!(error.code, TradeValidationErrorCode::StatusNotCancelable);
+        }
+    }
+
+    #[test]
+    fn test_validate_thesis_length() {
+        assert!(can_use_thesis("").is_ok());
+        assert!(can_use_thesis(&"a".repeat(200)).is_ok());
+        // The limit counts characters, not bytes.
+        assert!(can_use_thesis(&"é".repeat(200)).is_ok());
+
+        let error = can_use_thesis(&"a".repeat(201)).unwrap_err();
+        assert_eq!(error.code, TradeValidationErrorCode::ThesisTooLong);
+    }
+
+    #[test]
+    fn test_validate_revise_thesis() {
+        for status in [
+            Status::New,
+            Status::Funded,
+            Status::Submitted,
+            Status::Filled,
+        ] {
+            let trade = Trade {
+                status,
+                ..Default::default()
+            };
+            assert!(can_revise_thesis(&trade).is_ok());
+        }
+
+        let trade = Trade {
+            status: Status::ClosedTarget,
+            ..Default::default()
+        };
+        let error = can_revise_thesis(&trade).unwrap_err();
+        assert_eq!(error.code, TradeValidationErrorCode::TradeClosed);
+    }
 }
@@ -0,0 +1,144 @@
+use std::error::Error;
+
+/// The maximum number of characters of the symbol of a trading vehicle.
+pub const MAX_SYMBOL_LENGTH: usize = 12;
+
+// Validate that the symbol is not blank, that it is not too long and that it only has
+// letters, digits and the separators used by the exchanges. The broker is the exchange
+// where the trading vehicle is traded, so it can not be blank either.
+//
+// It returns the normalized symbol: trimmed and in uppercase.
+pub fn can_create(
+    symbol: &str,
+    broker: &str,
+) -> Result<String, Box<TradingVehicleValidationError>> {
+    let symbol = symbol.trim().to_uppercase();
+
+    if symbol.is_empty() {
+        return Err(Box::new(TradingVehicleValidationError {
+            code: TradingVehicleValidationErrorCode::SymbolEmpty,
+            message: "The symbol of a trading vehicle can not be blank".to_string(),
+        }));
+    }
+
+    let length = symbol.chars().count();
+    if length > MAX_SYMBOL_LENGTH {
+        return Err(Box::new(TradingVehicleValidationError {
+            code: TradingVehicleValidationErrorCode::SymbolTooLong,
+            message: format!(
+                "The symbol {} has {} characters and it can have at most {}",
+                symbol, length, MAX_SYMBOL_LENGTH
+            ),
+        }));
+    }
+
+    if !symbol
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '/'))
+    {
+        return Err(Box::new(TradingVehicleValidationError {
+            code: TradingVehicleValidationErrorCode::SymbolNotValid,
+            message: format!(
+                "The symbol {} can only have letters, digits, '.', '-' and '/'",
+                symbol
+            ),
+        }));
+    }
+
+    if broker.trim().is_empty() {
+        return Err(Box::new(TradingVehicleValidationError {
+            code: TradingVehicleValidationErrorCode::BrokerEmpty,
+            message: format!("The exchange of {} can not be blank", symbol),
+        }));
+    }
+
+    Ok(symbol)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TradingVehicleValidationErrorCode {
+    SymbolEmpty,
+    SymbolTooLong,
+    SymbolNotValid,
+    BrokerEmpty,
+}
+
+#[derive(Debug)]
+pub struct TradingVehicleValidationError {
+    pub code: TradingVehicleValidationErrorCode,
+    pub message: String,
+}
+
+impl std::fmt::Display for TradingVehicleValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "TradingVehicleValidationError: {}", self.message)
+    }
+}
+
+impl Error for TradingVehicleValidationError {
+    fn description(&self) -> &str {
+        &self.message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(symbol: &str, broker: &str) -> TradingVehicleValidationErrorCode {
+        can_create(symbol, broker).unwrap_err().code
+    }
+
+    #[test]
+    fn test_validate_symbol_normalized() {
+        assert_eq!(can_create(" aapl ", "NASDAQ").unwrap(), "AAPL");
+        assert_eq!(can_create("brk.b", "NYSE").unwrap(), "BRK.B");
+        assert_eq!(can_create("BTC/USD", "alpaca").unwrap(), "BTC/USD");
+        assert_eq!(
+            can_create("ABCDEFGHIJKL", "NASDAQ").unwrap(),
+            "ABCDEFGHIJKL"
+        );
+    }
+
+    #[test]
+    fn test_validate_empty_symbol() {
+        assert_eq!(
+            code("", "NASDAQ"),
+            TradingVehicleValidationErrorCode::SymbolEmpty
+        );
+        assert_eq!(
+            code(" \t\n", "NASDAQ"),
+            TradingVehicleValidationErrorCode::SymbolEmpty
+        );
+    }
+
+    #[test]
+    fn test_validate_symbol_too_long() {
+        assert_eq!(
+            code("ABCDEFGHIJKLM", "NASDAQ"),
+            TradingVehicleValidationErrorCode::SymbolTooLong
+        );
+    }
+
+    #[test]
+    fn test_validate_symbol_charset() {
+        for symbol in ["AA PL", "AAPL$", "ÄPPL", "AAPL;--"] {
+            assert_eq!(
+                code(symbol, "NASDAQ"),
+                TradingVehicleValidationErrorCode::SymbolNotValid
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_empty_broker() {
+        assert_eq!(
+            code("AAPL", ""),
+            TradingVehicleValidationErrorCode::BrokerEmpty
+        );
+        assert_eq!(
+            code("AAPL", "  "),
+            TradingVehicleValidationErrorCode::BrokerEmpty
+        );
+    }
+}
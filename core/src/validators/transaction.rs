@@ -1,4 +1,6 @@
-use model::{AccountBalance, AccountBalanceRead, Currency, Status, Trade};
+use model::{
+    AccountBalance, AccountBalanceRead, Currency, Status, Trade, Transaction, TransactionCategory,
+};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::error::Error;
@@ -49,6 +51,16 @@ pub fn can_transfer_fee(account: &AccountBalance, fee: Decimal) -> TransactionVa
     Ok(())
 }
 
+pub fn can_transfer_rebate(rebate: Decimal) -> TransactionValidationResult {
+    if rebate <= dec!(0) {
+        return Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::RebateMustBePositive,
+            message: "Rebate must be positive".to_string(),
+        }));
+    }
+    Ok(())
+}
+
 pub fn can_transfer_close(total: Decimal) -> TransactionValidationResult {
     if total <= dec!(0) {
         return Err(Box::new(TransactionValidationError {
@@ -59,13 +71,47 @@ pub fn can_transfer_close(total: Decimal) -> TransactionValidationResult {
     Ok(())
 }
 
+// Validate that the money moved by the user is strictly positive. The direction of the transaction
+// is given by its category. The transactions created internally for trades are validated on their own.
+pub fn can_create_with_amount(
+    category: &TransactionCategory,
+    amount: Decimal,
+) -> TransactionValidationResult {
+    // Interest is signed, a negative amount is a charge of the broker.
+    if *category == TransactionCategory::Interest {
+        return match amount.is_zero() {
+            true => Err(Box::new(TransactionValidationError {
+                code: TransactionValidationErrorCode::AmountOfInterestMustNotBeZero,
+                message: "Amount of interest must not be zero".to_string(),
+            })),
+            false => Ok(()),
+        };
+    }
+
+    if amount > dec!(0) {
+        return Ok(());
+    }
+
+    match category {
+        TransactionCategory::Deposit => Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::AmountOfDepositMustBePositive,
+            message: format!("Amount of deposit must be positive and it is {}", amount),
+        })),
+        TransactionCategory::Withdrawal => Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::AmountOfWithdrawalMustBePositive,
+            message: format!("Amount of withdrawal must be positive and it is {}", amount),
+        })),
+        _ => Ok(()),
+    }
+}
+
 pub fn can_transfer_deposit(
     amount: Decimal,
     currency: &Currency,
     account_id: Uuid,
     database: &mut dyn AccountBalanceRead,
 ) -> TransactionValidationResult {
-    if amount.is_sign_negative() {
+    if amount.is_sign_negative() | amount.is_zero() {
         Err(Box::new(TransactionValidationError {
             code: TransactionValidationErrorCode::AmountOfDepositMustBePositive,
             message: "Amount of deposit must be positive".to_string(),
@@ -81,6 +127,96 @@ pub fn can_transfer_deposit(
     }
 }
 
+pub fn can_transfer_dividend(
+    amount: Decimal,
+    currency: &Currency,
+    account_id: Uuid,
+    database: &mut dyn AccountBalanceRead,
+) -> TransactionValidationResult {
+    if amount.is_sign_negative() | amount.is_zero() {
+        Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::AmountOfDividendMustBePositive,
+            message: "Amount of dividend must be positive".to_string(),
+        }))
+    } else {
+        match database.for_currency(account_id, currency) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(Box::new(TransactionValidationError {
+                code: TransactionValidationErrorCode::OverviewNotFound,
+                message: "Overview not found. It can be that the user never created a deposit on this currency".to_string(),
+            })),
+        }
+    }
+}
+
+// A credit of interest only needs the account to hold the currency.
+// A charge of interest can not be larger than the money available in the account.
+pub fn can_transfer_interest(
+    amount: Decimal,
+    currency: &Currency,
+    account_id: Uuid,
+    database: &mut dyn AccountBalanceRead,
+) -> TransactionValidationResult {
+    match database.for_currency(account_id, currency) {
+        Ok(balance) => {
+            if balance.total_available + amount >= dec!(0) {
+                Ok(())
+            } else {
+                Err(Box::new(TransactionValidationError {
+                    code: TransactionValidationErrorCode::NotEnoughFunds,
+                    message: format!(
+                        "Interest charge of {} is greater than the available amount {}",
+                        -amount, balance.total_available
+                    ),
+                }))
+            }
+        }
+        Err(_) => Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::OverviewNotFound,
+            message: "Overview not found. It can be that the user never created a deposit on this currency".to_string(),
+        })),
+    }
+}
+
+// Validate a manual adjustment of the balance. It is signed, so only zero is rejected, and it must
+// explain why the balance is corrected. A negative adjustment can not remove more than the available amount.
+pub fn can_adjust(
+    amount: Decimal,
+    reason: &str,
+    currency: &Currency,
+    account_id: Uuid,
+    database: &mut dyn AccountBalanceRead,
+) -> TransactionValidationResult {
+    if amount.is_zero() {
+        return Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::AmountOfAdjustmentMustNotBeZero,
+            message: "Amount of adjustment must not be zero".to_string(),
+        }));
+    }
+
+    if reason.trim().is_empty() {
+        return Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::AdjustmentReasonMustNotBeEmpty,
+            message: "Adjustment must have a reason".to_string(),
+        }));
+    }
+
+    match database.for_currency(account_id, currency) {
+        Ok(balance) if balance.total_available + amount >= dec!(0) => Ok(()),
+        Ok(balance) => Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::NotEnoughFunds,
+            message: format!(
+                "Adjustment of {} is greater than the available amount {}",
+                amount, balance.total_available
+            ),
+        })),
+        Err(_) => Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::OverviewNotFound,
+            message: "Overview not found. It can be that the user never created a deposit on this currency".to_string(),
+        })),
+    }
+}
+
 pub fn can_transfer_withdraw(
     amount: Decimal,
     currency: &Currency,
@@ -113,18 +249,94 @@ pub fn can_transfer_withdraw(
     }
 }
 
+// Validate that a withdrawal of earnings leaves in the account the capital reserved for its open trades.
+// The withdrawable amount can be smaller than the available amount, so the available is not enough.
+pub fn can_withdraw_earnings(
+    amount: Decimal,
+    available: Decimal,
+    reserved: Decimal,
+) -> TransactionValidationResult {
+    if amount.is_sign_negative() | amount.is_zero() {
+        return Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::AmountOfWithdrawalMustBePositive,
+            message: "Amount of withdrawal must be positive".to_string(),
+        }));
+    }
+
+    let withdrawable = (available - reserved).max(dec!(0));
+    if amount > withdrawable {
+        return Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::WithdrawalAmountIsGreaterThanWithdrawableAmount,
+            message: format!(
+                "Withdrawal amount {} is greater than the withdrawable amount {}. {} of the available {} is reserved for open trades",
+                amount, withdrawable, reserved, available
+            ),
+        }));
+    }
+    Ok(())
+}
+
+// Only the movements that the user creates can be undone.
+// The trade is the one funded by the transaction, if any.
+pub fn can_undo(transaction: &Transaction, trade: Option<&Trade>) -> TransactionValidationResult {
+    match (&transaction.category, trade) {
+        (
+            TransactionCategory::Deposit
+            | TransactionCategory::Withdrawal
+            | TransactionCategory::WithdrawalEarnings
+            | TransactionCategory::Dividend(_),
+            _,
+        ) => Ok(()),
+        (TransactionCategory::FundTrade(_), Some(trade)) if is_open(trade) => {
+            Err(Box::new(TransactionValidationError {
+                code: TransactionValidationErrorCode::TransactionFundsOpenTrade,
+                message: format!(
+                    "Transaction {} funds the trade {} that is still open. Cancel the trade instead",
+                    transaction.id, trade.id
+                ),
+            }))
+        }
+        (category, _) => Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::TransactionCanNotBeUndone,
+            message: format!(
+                "Transaction {} with category {} can not be undone. Only deposits, withdrawals and dividends can be undone",
+                transaction.id, category
+            ),
+        })),
+    }
+}
+
+fn is_open(trade: &Trade) -> bool {
+    !matches!(
+        trade.status,
+        Status::ClosedTarget
+            | Status::ClosedStopLoss
+            | Status::Canceled
+            | Status::Expired
+            | Status::Rejected
+    )
+}
+
 #[derive(Debug, PartialEq)]
 pub enum TransactionValidationErrorCode {
     AmountOfWithdrawalMustBePositive,
     AmountOfDepositMustBePositive,
+    AmountOfDividendMustBePositive,
+    AmountOfInterestMustNotBeZero,
+    AmountOfAdjustmentMustNotBeZero,
+    AdjustmentReasonMustNotBeEmpty,
     WithdrawalAmountIsGreaterThanAvailableAmount,
+    WithdrawalAmountIsGreaterThanWithdrawableAmount,
     OverviewNotFound,
     OverviewForWithdrawNotFound,
     NotEnoughFunds,
     WrongTradeStatus,
     FillingMustBePositive,
     FeeMustBePositive,
+    RebateMustBePositive,
     ClosingMustBePositive,
+    TransactionFundsOpenTrade,
+    TransactionCanNotBeUndone,
 }
 
 #[derive(Debug, PartialEq)]
@@ -150,6 +362,76 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_can_undo_deposit() {
+        let transaction = Transaction::new(
+            Uuid::new_v4(),
+            TransactionCategory::Deposit,
+            &Currency::USD,
+            dec!(100),
+        );
+        assert!(can_undo(&transaction, None).is_ok());
+    }
+
+    #[test]
+    fn test_can_undo_funding_of_open_trade() {
+        let trade = Trade {
+            status: Status::Filled,
+            ..Default::default()
+        };
+        let transaction = Transaction::new(
+            trade.account_id,
+            TransactionCategory::FundTrade(trade.id),
+            &Currency::USD,
+            dec!(100),
+        );
+        assert_eq!(
+            can_undo(&transaction, Some(&trade)).unwrap_err().code,
+            TransactionValidationErrorCode::TransactionFundsOpenTrade
+        );
+    }
+
+    #[test]
+    fn test_can_undo_funding_of_closed_trade() {
+        let trade = Trade {
+            status: Status::Canceled,
+            ..Default::default()
+        };
+        let transaction = Transaction::new(
+            trade.account_id,
+            TransactionCategory::FundTrade(trade.id),
+            &Currency::USD,
+            dec!(100),
+        );
+        assert_eq!(
+            can_undo(&transaction, Some(&trade)).unwrap_err().code,
+            TransactionValidationErrorCode::TransactionCanNotBeUndone
+        );
+    }
+
+    #[test]
+    fn test_can_withdraw_earnings() {
+        assert!(can_withdraw_earnings(dec!(800), dec!(1000), dec!(200)).is_ok());
+        assert_eq!(
+            can_withdraw_earnings(dec!(801), dec!(1000), dec!(200))
+                .unwrap_err()
+                .code,
+            TransactionValidationErrorCode::WithdrawalAmountIsGreaterThanWithdrawableAmount
+        );
+        assert_eq!(
+            can_withdraw_earnings(dec!(1), dec!(100), dec!(200))
+                .unwrap_err()
+                .code,
+            TransactionValidationErrorCode::WithdrawalAmountIsGreaterThanWithdrawableAmount
+        );
+        assert_eq!(
+            can_withdraw_earnings(dec!(0), dec!(1000), dec!(0))
+                .unwrap_err()
+                .code,
+            TransactionValidationErrorCode::AmountOfWithdrawalMustBePositive
+        );
+    }
+
     #[test]
     fn test_validate_fill_with_enough_funds() {
         let trade = Trade {
@@ -279,6 +561,62 @@ mod tests {
         assert!(can_transfer_fee(&account, fee).is_err());
     }
 
+    #[test]
+    fn test_validate_rebate() {
+        assert_eq!(can_transfer_rebate(dec!(0.5)), Ok(()));
+        assert_eq!(
+            can_transfer_rebate(dec!(0)).unwrap_err().code,
+            TransactionValidationErrorCode::RebateMustBePositive
+        );
+    }
+
+    #[test]
+    fn test_can_create_deposit_with_amount() {
+        assert!(can_create_with_amount(&TransactionCategory::Deposit, dec!(0.01)).is_ok());
+
+        for amount in [dec!(0), dec!(-100)] {
+            let error = can_create_with_amount(&TransactionCategory::Deposit, amount).unwrap_err();
+            assert_eq!(
+                error.code,
+                TransactionValidationErrorCode::AmountOfDepositMustBePositive
+            );
+        }
+    }
+
+    #[test]
+    fn test_can_create_withdrawal_with_amount() {
+        assert!(can_create_with_amount(&TransactionCategory::Withdrawal, dec!(10)).is_ok());
+
+        let error = can_create_with_amount(&TransactionCategory::Withdrawal, dec!(0)).unwrap_err();
+        assert_eq!(
+            error.code,
+            TransactionValidationErrorCode::AmountOfWithdrawalMustBePositive
+        );
+        assert_eq!(
+            error.message,
+            "Amount of withdrawal must be positive and it is 0"
+        );
+    }
+
+    #[test]
+    fn test_can_create_interest_with_amount() {
+        for amount in [dec!(12.5), dec!(-3)] {
+            assert!(can_create_with_amount(&TransactionCategory::Interest, amount).is_ok());
+        }
+
+        let error = can_create_with_amount(&TransactionCategory::Interest, dec!(0)).unwrap_err();
+        assert_eq!(
+            error.code,
+            TransactionValidationErrorCode::AmountOfInterestMustNotBeZero
+        );
+    }
+
+    #[test]
+    fn test_can_create_trade_transaction_with_amount() {
+        let category = TransactionCategory::FundTrade(Uuid::new_v4());
+        assert!(can_create_with_amount(&category, dec!(-100)).is_ok());
+    }
+
     #[test]
     fn test_validate_close_success() {
         let result = can_transfer_close(dec!(10));
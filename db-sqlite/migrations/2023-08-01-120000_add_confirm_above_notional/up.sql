@@ -0,0 +1 @@
+ALTER TABLE accounts ADD COLUMN confirm_above_notional TEXT;
@@ -0,0 +1,31 @@
+CREATE TABLE "executions" (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at			DATETIME NOT NULL,
+	updated_at			DATETIME NOT NULL,
+	deleted_at			DATETIME,
+	broker_execution_id	TEXT,
+	trade_id			TEXT NOT NULL REFERENCES trades (id),
+	order_id			TEXT NOT NULL REFERENCES orders (id),
+	side 				TEXT CHECK(side IN ('buy', 'sell')) NOT NULL,
+	price				TEXT NOT NULL,
+	quantity			INTEGER NOT NULL,
+	executed_at			DATETIME NOT NULL,
+	source 				TEXT CHECK(source IN ('broker')) NOT NULL
+);
+
+-- SQLite can not alter a CHECK constraint, so the table is rebuilt to accept account level fees.
+CREATE TABLE transactions_new (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at		DATETIME NOT NULL,
+	updated_at		DATETIME NOT NULL,
+	deleted_at		DATETIME,
+	currency 		TEXT CHECK(currency IN ('EUR', 'USD', 'BTC')) NOT NULL,
+	category 		TEXT CHECK(category IN ('deposit', 'withdrawal', 'payment_from_trade', 'fund_trade', 'open_trade', 'close_target', "close_safety_stop", "close_safety_stop_slippage", "fee_open", "fee_close", "payment_earnings", "withdrawal_earnings", "payment_tax", "withdrawal_tax", "fee")) NOT NULL,
+	amount			TEXT NOT NULL,
+	account_id 		TEXT NOT NULL REFERENCES accounts(id),
+	trade_id		TEXT REFERENCES trades (uuid)
+);
+
+INSERT INTO transactions_new SELECT * FROM transactions;
+DROP TABLE transactions;
+ALTER TABLE transactions_new RENAME TO transactions;
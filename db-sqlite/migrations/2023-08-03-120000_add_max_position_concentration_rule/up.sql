@@ -0,0 +1,18 @@
+-- SQLite can not alter a CHECK constraint, so the table is rebuilt to accept the concentration rule.
+CREATE TABLE rules_new (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at		DATETIME NOT NULL,
+	updated_at		DATETIME NOT NULL,
+	deleted_at		DATETIME,
+	name 			TEXT CHECK(name IN ('risk_per_trade', 'risk_per_month', 'max_position_concentration')) NOT NULL,
+	risk			INTEGER NOT NULL,
+	description		TEXT NOT NULL,
+	priority		INTEGER NOT NULL,
+	level 			TEXT CHECK(level IN ('advice', 'warning', 'error')) NOT NULL,
+	account_id 		TEXT NOT NULL REFERENCES accounts(id),
+	active			BOOLEAN NOT NULL
+);
+
+INSERT INTO rules_new SELECT * FROM rules;
+DROP TABLE rules;
+ALTER TABLE rules_new RENAME TO rules;
@@ -0,0 +1 @@
+DROP TABLE "equity_snapshots";
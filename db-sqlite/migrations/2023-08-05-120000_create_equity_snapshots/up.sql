@@ -0,0 +1,10 @@
+CREATE TABLE "equity_snapshots" (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at		DATETIME NOT NULL,
+	updated_at		DATETIME NOT NULL,
+	deleted_at		DATETIME,
+	account_id 		TEXT NOT NULL REFERENCES accounts(id),
+	currency 		TEXT CHECK(currency IN ('EUR', 'USD', 'BTC')) NOT NULL,
+	timestamp		DATETIME NOT NULL,
+	balance			TEXT NOT NULL
+);
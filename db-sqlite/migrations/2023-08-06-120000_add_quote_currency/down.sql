@@ -0,0 +1 @@
+ALTER TABLE trading_vehicles DROP COLUMN quote_currency;
@@ -0,0 +1 @@
+ALTER TABLE trading_vehicles ADD COLUMN quote_currency TEXT CHECK(quote_currency IN ('EUR', 'USD', 'BTC'));
@@ -0,0 +1,2 @@
+DROP TABLE "level_changes";
+DROP TABLE "levels";
@@ -0,0 +1,22 @@
+CREATE TABLE "levels" (
+	id 				TEXT NOT NULL PRIMARY KEY,
+	created_at			DATETIME NOT NULL,
+	updated_at			DATETIME NOT NULL,
+	deleted_at			DATETIME,
+	account_id 			TEXT NOT NULL UNIQUE REFERENCES accounts(id),
+	current_level			INTEGER NOT NULL CHECK(current_level BETWEEN 0 AND 4),
+	monthly_loss_downgrade_pct	TEXT NOT NULL
+);
+
+CREATE TABLE "level_changes" (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at		DATETIME NOT NULL,
+	updated_at		DATETIME NOT NULL,
+	deleted_at		DATETIME,
+	account_id 		TEXT NOT NULL REFERENCES accounts(id),
+	old_level		INTEGER NOT NULL,
+	new_level		INTEGER NOT NULL,
+	reason			TEXT NOT NULL,
+	trigger			TEXT NOT NULL,
+	changed_at		DATETIME NOT NULL
+);
@@ -0,0 +1,2 @@
+ALTER TABLE trades DROP COLUMN strategy_id;
+DROP TABLE "strategies";
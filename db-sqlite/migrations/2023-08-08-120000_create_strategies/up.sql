@@ -0,0 +1,15 @@
+CREATE TABLE "strategies" (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at		DATETIME NOT NULL,
+	updated_at		DATETIME NOT NULL,
+	deleted_at		DATETIME,
+	name 			TEXT NOT NULL,
+	description		TEXT NOT NULL,
+	version			INTEGER NOT NULL,
+	entry_description	TEXT NOT NULL,
+	stop_description	TEXT NOT NULL,
+	target_description	TEXT NOT NULL,
+	UNIQUE(name, version)
+);
+
+ALTER TABLE trades ADD COLUMN strategy_id TEXT REFERENCES strategies(id);
@@ -0,0 +1,2 @@
+ALTER TABLE accounts DROP COLUMN tick_size_policy;
+ALTER TABLE trading_vehicles DROP COLUMN tick_size;
@@ -0,0 +1,2 @@
+ALTER TABLE trading_vehicles ADD COLUMN tick_size TEXT;
+ALTER TABLE accounts ADD COLUMN tick_size_policy TEXT CHECK(tick_size_policy IN ('reject', 'round')) NOT NULL DEFAULT 'reject';
@@ -0,0 +1,42 @@
+CREATE TABLE "distribution_rules" (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at		DATETIME NOT NULL,
+	updated_at		DATETIME NOT NULL,
+	deleted_at		DATETIME,
+	account_id 		TEXT NOT NULL UNIQUE REFERENCES accounts(id),
+	earnings_percent	TEXT NOT NULL,
+	tax_percent		TEXT NOT NULL,
+	reinvestment_percent	TEXT NOT NULL
+);
+
+CREATE TABLE "distribution_history" (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at		DATETIME NOT NULL,
+	updated_at		DATETIME NOT NULL,
+	deleted_at		DATETIME,
+	account_id 		TEXT NOT NULL REFERENCES accounts(id),
+	trade_id		TEXT NOT NULL REFERENCES trades (id),
+	original_amount		TEXT NOT NULL,
+	earnings_amount		TEXT NOT NULL,
+	tax_amount		TEXT NOT NULL,
+	reinvestment_amount	TEXT NOT NULL,
+	distributed_at		DATETIME NOT NULL
+);
+
+-- SQLite can not alter a CHECK constraint, so the table is rebuilt to accept reinvestments.
+CREATE TABLE transactions_new (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at		DATETIME NOT NULL,
+	updated_at		DATETIME NOT NULL,
+	deleted_at		DATETIME,
+	currency 		TEXT CHECK(currency IN ('EUR', 'USD', 'BTC')) NOT NULL,
+	category 		TEXT CHECK(category IN ('deposit', 'withdrawal', 'payment_from_trade', 'fund_trade', 'open_trade', 'close_target', "close_safety_stop", "close_safety_stop_slippage", "fee_open", "fee_close", "payment_earnings", "withdrawal_earnings", "payment_tax", "withdrawal_tax", "fee", "dividend", "payment_reinvestment")) NOT NULL,
+	amount			TEXT NOT NULL,
+	account_id 		TEXT NOT NULL REFERENCES accounts(id),
+	trade_id		TEXT REFERENCES trades (uuid),
+	trading_vehicle_id	TEXT REFERENCES trading_vehicles (id)
+);
+
+INSERT INTO transactions_new SELECT * FROM transactions;
+DROP TABLE transactions;
+ALTER TABLE transactions_new RENAME TO transactions;
@@ -0,0 +1 @@
+ALTER TABLE transactions ADD COLUMN reverses TEXT REFERENCES transactions (id);
@@ -0,0 +1,2 @@
+ALTER TABLE accounts DROP COLUMN paper_account_id;
+ALTER TABLE accounts DROP COLUMN min_paper_trades_before_live;
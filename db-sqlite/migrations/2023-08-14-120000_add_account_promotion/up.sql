@@ -0,0 +1,2 @@
+ALTER TABLE accounts ADD COLUMN min_paper_trades_before_live INTEGER;
+ALTER TABLE accounts ADD COLUMN paper_account_id TEXT REFERENCES accounts (id);
@@ -0,0 +1,18 @@
+CREATE TABLE executions_old (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at			DATETIME NOT NULL,
+	updated_at			DATETIME NOT NULL,
+	deleted_at			DATETIME,
+	broker_execution_id	TEXT,
+	trade_id			TEXT NOT NULL REFERENCES trades (id),
+	order_id			TEXT NOT NULL REFERENCES orders (id),
+	side 				TEXT CHECK(side IN ('buy', 'sell')) NOT NULL,
+	price				TEXT NOT NULL,
+	quantity			INTEGER NOT NULL,
+	executed_at			DATETIME NOT NULL,
+	source 				TEXT CHECK(source IN ('broker')) NOT NULL
+);
+
+INSERT INTO executions_old SELECT * FROM executions WHERE source != 'manual';
+DROP TABLE executions;
+ALTER TABLE executions_old RENAME TO executions;
@@ -0,0 +1,19 @@
+-- SQLite can not alter a CHECK constraint, so the table is rebuilt to accept manual executions.
+CREATE TABLE executions_new (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at			DATETIME NOT NULL,
+	updated_at			DATETIME NOT NULL,
+	deleted_at			DATETIME,
+	broker_execution_id	TEXT,
+	trade_id			TEXT NOT NULL REFERENCES trades (id),
+	order_id			TEXT NOT NULL REFERENCES orders (id),
+	side 				TEXT CHECK(side IN ('buy', 'sell')) NOT NULL,
+	price				TEXT NOT NULL,
+	quantity			INTEGER NOT NULL,
+	executed_at			DATETIME NOT NULL,
+	source 				TEXT CHECK(source IN ('broker', 'manual')) NOT NULL
+);
+
+INSERT INTO executions_new SELECT * FROM executions;
+DROP TABLE executions;
+ALTER TABLE executions_new RENAME TO executions;
@@ -0,0 +1,2 @@
+DROP INDEX trades_account_id_reference;
+ALTER TABLE trades DROP COLUMN reference;
@@ -0,0 +1,3 @@
+ALTER TABLE trades ADD COLUMN reference TEXT;
+
+CREATE UNIQUE INDEX trades_account_id_reference ON trades (account_id, reference);
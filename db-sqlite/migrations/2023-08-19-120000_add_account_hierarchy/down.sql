@@ -0,0 +1,2 @@
+ALTER TABLE accounts DROP COLUMN parent_account_id;
+ALTER TABLE accounts DROP COLUMN account_type;
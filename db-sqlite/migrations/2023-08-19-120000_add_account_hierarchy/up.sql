@@ -0,0 +1,2 @@
+ALTER TABLE accounts ADD COLUMN account_type TEXT CHECK(account_type IN ('primary', 'hedge')) NOT NULL DEFAULT 'primary';
+ALTER TABLE accounts ADD COLUMN parent_account_id TEXT REFERENCES accounts(id);
@@ -0,0 +1 @@
+ALTER TABLE accounts DROP COLUMN cancel_orders_on_close;
@@ -0,0 +1,2 @@
+DROP TABLE trade_grades;
+ALTER TABLE trades DROP COLUMN thesis;
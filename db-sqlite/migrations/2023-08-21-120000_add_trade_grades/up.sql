@@ -0,0 +1,14 @@
+ALTER TABLE trades ADD COLUMN thesis TEXT;
+
+CREATE TABLE "trade_grades" (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at		DATETIME NOT NULL,
+	updated_at		DATETIME NOT NULL,
+	deleted_at		DATETIME,
+	trade_id		TEXT NOT NULL UNIQUE REFERENCES trades (id),
+	risk_management		INTEGER NOT NULL,
+	execution		INTEGER NOT NULL,
+	outcome			INTEGER NOT NULL,
+	documentation		INTEGER NOT NULL,
+	overall			INTEGER NOT NULL
+);
@@ -0,0 +1,2 @@
+DROP TABLE thesis_history;
+ALTER TABLE trades DROP COLUMN context;
@@ -0,0 +1,11 @@
+ALTER TABLE trades ADD COLUMN context TEXT;
+
+CREATE TABLE "thesis_history" (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at		DATETIME NOT NULL,
+	updated_at		DATETIME NOT NULL,
+	deleted_at		DATETIME,
+	trade_id		TEXT NOT NULL REFERENCES trades (id),
+	thesis			TEXT,
+	context			TEXT
+);
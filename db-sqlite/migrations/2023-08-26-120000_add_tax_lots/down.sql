@@ -0,0 +1,2 @@
+DROP TABLE realized_lots;
+ALTER TABLE accounts DROP COLUMN lot_method;
@@ -0,0 +1,18 @@
+ALTER TABLE accounts ADD COLUMN lot_method TEXT CHECK(lot_method IN ('fifo', 'lifo')) NOT NULL DEFAULT 'fifo';
+
+CREATE TABLE "realized_lots" (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at		DATETIME NOT NULL,
+	updated_at		DATETIME NOT NULL,
+	deleted_at		DATETIME,
+	account_id		TEXT NOT NULL REFERENCES accounts (id),
+	trade_id		TEXT NOT NULL REFERENCES trades (id),
+	open_execution_id	TEXT NOT NULL REFERENCES executions (id),
+	close_execution_id	TEXT NOT NULL REFERENCES executions (id),
+	opened_at		DATETIME NOT NULL,
+	closed_at		DATETIME NOT NULL,
+	quantity		INTEGER NOT NULL,
+	cost_basis		TEXT NOT NULL,
+	proceeds		TEXT NOT NULL,
+	gain			TEXT NOT NULL
+);
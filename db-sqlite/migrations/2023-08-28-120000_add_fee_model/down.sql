@@ -0,0 +1,2 @@
+ALTER TABLE accounts DROP COLUMN fee_model_amount;
+ALTER TABLE accounts DROP COLUMN fee_model;
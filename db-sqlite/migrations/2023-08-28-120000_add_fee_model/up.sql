@@ -0,0 +1,2 @@
+ALTER TABLE accounts ADD COLUMN fee_model TEXT CHECK(fee_model IN ('none', 'per_share', 'percent', 'fixed')) NOT NULL DEFAULT 'none';
+ALTER TABLE accounts ADD COLUMN fee_model_amount TEXT;
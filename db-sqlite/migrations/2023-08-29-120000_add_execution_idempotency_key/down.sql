@@ -0,0 +1,2 @@
+DROP INDEX executions_idempotency_key;
+ALTER TABLE executions DROP COLUMN idempotency_key;
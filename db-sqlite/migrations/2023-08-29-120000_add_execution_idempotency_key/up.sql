@@ -0,0 +1,9 @@
+ALTER TABLE executions ADD COLUMN idempotency_key TEXT NOT NULL DEFAULT '';
+
+-- Executions written before the key existed keep a key of their own, so they are never duplicates.
+UPDATE executions SET idempotency_key = CASE
+	WHEN broker_execution_id IS NULL THEN 'execution:' || id
+	ELSE 'broker:' || broker_execution_id
+END;
+
+CREATE UNIQUE INDEX executions_idempotency_key ON executions (idempotency_key);
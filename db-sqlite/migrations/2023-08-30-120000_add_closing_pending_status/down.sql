@@ -0,0 +1,32 @@
+CREATE TABLE trades_old (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at			DATETIME NOT NULL,
+	updated_at			DATETIME NOT NULL,
+	deleted_at			DATETIME,
+	category 			TEXT CHECK(category IN ('long', 'short')) NOT NULL,
+	status 				TEXT CHECK(status IN ('new', 'funded', 'submitted' , 'partially_filled', 'filled', 'canceled', 'expired', 'rejected', 'closed_stop_loss', 'closed_target')) NOT NULL,
+	currency 			TEXT CHECK(currency IN ('USD', 'EUR', 'BTC')) NOT NULL,
+	trading_vehicle_id	TEXT NOT NULL REFERENCES trading_vehicles (id),
+	safety_stop_id 		TEXT NOT NULL REFERENCES orders (id),
+	entry_id 			TEXT NOT NULL REFERENCES orders (id),
+	target_id 			TEXT NOT NULL REFERENCES orders (id),
+	account_id 			TEXT NOT NULL REFERENCES accounts (id),
+	balance_id 		TEXT NOT NULL REFERENCES trades_balances (id),
+	strategy_id			TEXT REFERENCES strategies(id),
+	reference			TEXT,
+	thesis				TEXT,
+	context				TEXT,
+	sector				TEXT
+);
+
+-- The trades that are closing at a limit are still filled.
+INSERT INTO trades_old (id, created_at, updated_at, deleted_at, category, status, currency, trading_vehicle_id, safety_stop_id, entry_id, target_id, account_id, balance_id, strategy_id, reference, thesis, context, sector)
+SELECT id, created_at, updated_at, deleted_at, category,
+	CASE status WHEN 'closing_pending' THEN 'filled' ELSE status END,
+	currency, trading_vehicle_id, safety_stop_id, entry_id, target_id, account_id, balance_id, strategy_id, reference, thesis, context, sector
+FROM trades;
+DROP INDEX trades_account_id_reference;
+DROP TABLE trades;
+ALTER TABLE trades_old RENAME TO trades;
+
+CREATE UNIQUE INDEX trades_account_id_reference ON trades (account_id, reference);
@@ -0,0 +1 @@
+DROP INDEX transactions_account_id_currency_created_at;
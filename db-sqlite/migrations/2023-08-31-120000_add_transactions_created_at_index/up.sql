@@ -0,0 +1 @@
+CREATE INDEX transactions_account_id_currency_created_at ON transactions (account_id, currency, created_at);
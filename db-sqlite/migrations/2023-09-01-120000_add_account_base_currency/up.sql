@@ -0,0 +1 @@
+ALTER TABLE accounts ADD COLUMN base_currency TEXT CHECK(base_currency IN ('USD', 'EUR', 'BTC')) NOT NULL DEFAULT 'USD';
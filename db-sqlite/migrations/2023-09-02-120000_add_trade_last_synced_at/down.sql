@@ -0,0 +1 @@
+ALTER TABLE trades DROP COLUMN last_synced_at;
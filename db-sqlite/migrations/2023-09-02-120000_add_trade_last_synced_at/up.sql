@@ -0,0 +1 @@
+ALTER TABLE trades ADD COLUMN last_synced_at DATETIME;
@@ -0,0 +1 @@
+ALTER TABLE accounts DROP COLUMN duplicate_trade_window_seconds;
@@ -0,0 +1 @@
+ALTER TABLE accounts ADD COLUMN duplicate_trade_window_seconds INTEGER;
@@ -0,0 +1 @@
+ALTER TABLE orders DROP COLUMN filled_quantity_decimal;
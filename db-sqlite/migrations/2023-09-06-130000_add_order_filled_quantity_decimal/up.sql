@@ -0,0 +1 @@
+ALTER TABLE orders ADD COLUMN filled_quantity_decimal TEXT;
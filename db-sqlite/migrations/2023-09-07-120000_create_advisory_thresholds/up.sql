@@ -0,0 +1,9 @@
+CREATE TABLE "advisory_thresholds" (
+	id 					TEXT NOT NULL PRIMARY KEY,
+	created_at				DATETIME NOT NULL,
+	updated_at				DATETIME NOT NULL,
+	deleted_at				DATETIME,
+	account_id 				TEXT NOT NULL UNIQUE REFERENCES accounts(id),
+	max_risk_per_trade_percent		TEXT NOT NULL,
+	max_position_concentration_percent	TEXT NOT NULL
+);
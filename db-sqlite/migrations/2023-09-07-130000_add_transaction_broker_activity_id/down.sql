@@ -0,0 +1,3 @@
+DROP INDEX transactions_broker_activity_id;
+
+ALTER TABLE transactions DROP COLUMN broker_activity_id;
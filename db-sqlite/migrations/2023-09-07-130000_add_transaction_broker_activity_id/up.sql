@@ -0,0 +1,3 @@
+ALTER TABLE transactions ADD COLUMN broker_activity_id TEXT;
+
+CREATE UNIQUE INDEX transactions_broker_activity_id ON transactions (broker_activity_id);
@@ -0,0 +1 @@
+ALTER TABLE accounts ADD COLUMN cancel_orders_on_close BOOLEAN NOT NULL DEFAULT 1;
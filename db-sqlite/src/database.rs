@@ -1,9 +1,12 @@
 use crate::workers::{
-    AccountBalanceDB, AccountDB, BrokerLogDB, WorkerOrder, WorkerRule, WorkerTrade,
-    WorkerTradingVehicle, WorkerTransaction,
+    AccountBalanceDB, AccountDB, BrokerLogDB, WorkerAdvisory, WorkerDistribution,
+    WorkerEquitySnapshot, WorkerExecution, WorkerLevel, WorkerOrder, WorkerRealizedLot, WorkerRule,
+    WorkerStrategy, WorkerThesisHistory, WorkerTrade, WorkerTradeGrade, WorkerTradingVehicle,
+    WorkerTransaction,
 };
+use chrono::NaiveDateTime;
+use diesel::connection::{AnsiTransactionManager, TransactionManager};
 use diesel::prelude::*;
-use model::DraftTrade;
 use model::Status;
 use model::{
     database::{AccountWrite, WriteAccountBalanceDB},
@@ -13,86 +16,175 @@ use model::{
     TradingVehicleCategory, Transaction, TransactionCategory, WriteRuleDB, WriteTradeDB,
     WriteTradingVehicleDB, WriteTransactionDB,
 };
+use model::{
+    AdvisoryThresholds, DistributionHistory, DistributionRules, DraftExecution, DraftTrade,
+    EquitySnapshot, Execution, Level, LevelAdjustmentRules, LevelChange, LevelTrigger,
+    ReadAdvisoryDB, ReadDistributionDB, ReadEquitySnapshotDB, ReadExecutionDB, ReadLevelDB,
+    ReadStrategyDB, Strategy, WriteAdvisoryDB, WriteDistributionDB, WriteEquitySnapshotDB,
+    WriteExecutionDB, WriteLevelDB, WriteStrategyDB,
+};
+use model::{
+    Clock, ReadRealizedLotDB, ReadTradeGradeDB, RealizedLot, SystemClock, ThesisRevision,
+    TradeGrade, WriteRealizedLotDB, WriteTradeGradeDB,
+};
 use rust_decimal::Decimal;
 use std::error::Error;
 use std::sync::Arc;
 use std::sync::Mutex;
 use uuid::Uuid;
 
+#[derive(Clone)]
 pub struct SqliteDatabase {
     connection: Arc<Mutex<SqliteConnection>>,
+    clock: Arc<dyn Clock>,
 }
 
 impl DatabaseFactory for SqliteDatabase {
     fn account_read(&self) -> Box<dyn AccountRead> {
         Box::new(AccountDB {
             connection: self.connection.clone(),
+            clock: self.clock.clone(),
         })
     }
 
     fn account_write(&self) -> Box<dyn AccountWrite> {
         Box::new(AccountDB {
             connection: self.connection.clone(),
+            clock: self.clock.clone(),
         })
     }
 
     fn log_read(&self) -> Box<dyn model::ReadBrokerLogsDB> {
         Box::new(BrokerLogDB {
             connection: self.connection.clone(),
+            clock: self.clock.clone(),
         })
     }
 
     fn log_write(&self) -> Box<dyn model::WriteBrokerLogsDB> {
         Box::new(BrokerLogDB {
             connection: self.connection.clone(),
+            clock: self.clock.clone(),
         })
     }
 
     fn account_balance_read(&self) -> Box<dyn AccountBalanceRead> {
         Box::new(AccountBalanceDB {
             connection: self.connection.clone(),
+            clock: self.clock.clone(),
         })
     }
 
     fn account_balance_write(&self) -> Box<dyn AccountBalanceWrite> {
         Box::new(AccountBalanceDB {
             connection: self.connection.clone(),
+            clock: self.clock.clone(),
         })
     }
 
     fn order_read(&self) -> Box<dyn OrderRead> {
-        Box::new(SqliteDatabase::new_from(self.connection.clone()))
+        Box::new(self.clone())
     }
     fn order_write(&self) -> Box<dyn OrderWrite> {
-        Box::new(SqliteDatabase::new_from(self.connection.clone()))
+        Box::new(self.clone())
     }
 
     fn transaction_read(&self) -> Box<dyn ReadTransactionDB> {
-        Box::new(SqliteDatabase::new_from(self.connection.clone()))
+        Box::new(self.clone())
     }
     fn transaction_write(&self) -> Box<dyn WriteTransactionDB> {
-        Box::new(SqliteDatabase::new_from(self.connection.clone()))
+        Box::new(self.clone())
     }
     fn trade_read(&self) -> Box<dyn ReadTradeDB> {
-        Box::new(SqliteDatabase::new_from(self.connection.clone()))
+        Box::new(self.clone())
     }
     fn trade_write(&self) -> Box<dyn WriteTradeDB> {
-        Box::new(SqliteDatabase::new_from(self.connection.clone()))
+        Box::new(self.clone())
     }
     fn trade_balance_write(&self) -> Box<dyn WriteAccountBalanceDB> {
-        Box::new(SqliteDatabase::new_from(self.connection.clone()))
+        Box::new(self.clone())
     }
     fn rule_read(&self) -> Box<dyn ReadRuleDB> {
-        Box::new(SqliteDatabase::new_from(self.connection.clone()))
+        Box::new(self.clone())
     }
     fn rule_write(&self) -> Box<dyn WriteRuleDB> {
-        Box::new(SqliteDatabase::new_from(self.connection.clone()))
+        Box::new(self.clone())
     }
     fn trading_vehicle_read(&self) -> Box<dyn ReadTradingVehicleDB> {
-        Box::new(SqliteDatabase::new_from(self.connection.clone()))
+        Box::new(self.clone())
     }
     fn trading_vehicle_write(&self) -> Box<dyn WriteTradingVehicleDB> {
-        Box::new(SqliteDatabase::new_from(self.connection.clone()))
+        Box::new(self.clone())
+    }
+    fn execution_read(&self) -> Box<dyn ReadExecutionDB> {
+        Box::new(self.clone())
+    }
+    fn execution_write(&self) -> Box<dyn WriteExecutionDB> {
+        Box::new(self.clone())
+    }
+    fn equity_snapshot_read(&self) -> Box<dyn ReadEquitySnapshotDB> {
+        Box::new(self.clone())
+    }
+    fn equity_snapshot_write(&self) -> Box<dyn WriteEquitySnapshotDB> {
+        Box::new(self.clone())
+    }
+    fn level_read(&self) -> Box<dyn ReadLevelDB> {
+        Box::new(self.clone())
+    }
+    fn level_write(&self) -> Box<dyn WriteLevelDB> {
+        Box::new(self.clone())
+    }
+    fn strategy_read(&self) -> Box<dyn ReadStrategyDB> {
+        Box::new(self.clone())
+    }
+    fn strategy_write(&self) -> Box<dyn WriteStrategyDB> {
+        Box::new(self.clone())
+    }
+    fn distribution_read(&self) -> Box<dyn ReadDistributionDB> {
+        Box::new(self.clone())
+    }
+    fn distribution_write(&self) -> Box<dyn WriteDistributionDB> {
+        Box::new(self.clone())
+    }
+    fn trade_grade_read(&self) -> Box<dyn ReadTradeGradeDB> {
+        Box::new(self.clone())
+    }
+    fn trade_grade_write(&self) -> Box<dyn WriteTradeGradeDB> {
+        Box::new(self.clone())
+    }
+    fn realized_lot_read(&self) -> Box<dyn ReadRealizedLotDB> {
+        Box::new(self.clone())
+    }
+    fn realized_lot_write(&self) -> Box<dyn WriteRealizedLotDB> {
+        Box::new(self.clone())
+    }
+    fn advisory_read(&self) -> Box<dyn ReadAdvisoryDB> {
+        Box::new(self.clone())
+    }
+    fn advisory_write(&self) -> Box<dyn WriteAdvisoryDB> {
+        Box::new(self.clone())
+    }
+
+    fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
+    fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    // All the readers and writers share the same connection, so the transaction
+    // wraps whatever they write. Nested transactions are written as savepoints.
+    fn begin_transaction(&self) -> Result<(), Box<dyn Error>> {
+        AnsiTransactionManager::begin_transaction(&mut *self.connection.lock().unwrap())?;
+        Ok(())
+    }
+    fn commit_transaction(&self) -> Result<(), Box<dyn Error>> {
+        AnsiTransactionManager::commit_transaction(&mut *self.connection.lock().unwrap())?;
+        Ok(())
+    }
+    fn rollback_transaction(&self) -> Result<(), Box<dyn Error>> {
+        AnsiTransactionManager::rollback_transaction(&mut *self.connection.lock().unwrap())?;
+        Ok(())
     }
 }
 
@@ -101,11 +193,15 @@ impl SqliteDatabase {
         let connection: SqliteConnection = Self::establish_connection(url);
         SqliteDatabase {
             connection: Arc::new(Mutex::new(connection)),
+            clock: Arc::new(SystemClock),
         }
     }
 
     pub fn new_from(connection: Arc<Mutex<SqliteConnection>>) -> Self {
-        SqliteDatabase { connection }
+        SqliteDatabase {
+            connection,
+            clock: Arc::new(SystemClock),
+        }
     }
 
     #[doc(hidden)]
@@ -117,22 +213,20 @@ impl SqliteDatabase {
         connection.begin_test_transaction().unwrap();
         SqliteDatabase {
             connection: Arc::new(Mutex::new(connection)),
+            clock: Arc::new(SystemClock),
         }
     }
 
     /// Establish a connection to the SQLite database.
     fn establish_connection(database_url: &str) -> SqliteConnection {
-        let db_exists = std::path::Path::new(database_url).exists();
         // Use the database URL to establish a connection to the SQLite database
         let mut connection = SqliteConnection::establish(database_url)
             .unwrap_or_else(|_| panic!("Error connecting to {}", database_url));
 
-        // Run migrations only if it is a new DB
-        if !db_exists {
-            use diesel_migrations::*;
-            pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
-            connection.run_pending_migrations(MIGRATIONS).unwrap();
-        }
+        // Run the pending migrations. Existing databases need the new tables and columns too.
+        use diesel_migrations::*;
+        pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+        connection.run_pending_migrations(MIGRATIONS).unwrap();
 
         connection
     }
@@ -143,6 +237,7 @@ impl OrderWrite for SqliteDatabase {
         &mut self,
         trading_vehicle: &TradingVehicle,
         quantity: i64,
+        quantity_decimal: Option<Decimal>,
         price: Decimal,
         currency: &Currency,
         action: &OrderAction,
@@ -150,9 +245,11 @@ impl OrderWrite for SqliteDatabase {
     ) -> Result<Order, Box<dyn Error>> {
         WorkerOrder::create(
             &mut self.connection.lock().unwrap(),
+            self.clock.now(),
             price,
             currency,
             quantity,
+            quantity_decimal,
             action,
             category,
             trading_vehicle,
@@ -160,23 +257,36 @@ impl OrderWrite for SqliteDatabase {
     }
 
     fn update(&mut self, order: &Order) -> Result<Order, Box<dyn Error>> {
-        WorkerOrder::update(&mut self.connection.lock().unwrap(), order)
+        WorkerOrder::update(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            order,
+        )
     }
 
     fn submit_of(&mut self, order: &Order, broker_order_id: Uuid) -> Result<Order, Box<dyn Error>> {
         WorkerOrder::update_submitted_at(
             &mut self.connection.lock().unwrap(),
+            self.clock.now(),
             order,
             broker_order_id,
         )
     }
 
     fn filling_of(&mut self, order: &Order) -> Result<Order, Box<dyn Error>> {
-        WorkerOrder::update_filled_at(&mut self.connection.lock().unwrap(), order)
+        WorkerOrder::update_filled_at(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            order,
+        )
     }
 
     fn closing_of(&mut self, order: &Order) -> Result<Order, Box<dyn Error>> {
-        WorkerOrder::update_closed_at(&mut self.connection.lock().unwrap(), order)
+        WorkerOrder::update_closed_at(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            order,
+        )
     }
     fn update_price(
         &mut self,
@@ -186,6 +296,7 @@ impl OrderWrite for SqliteDatabase {
     ) -> Result<Order, Box<dyn Error>> {
         WorkerOrder::update_price(
             &mut self.connection.lock().unwrap(),
+            self.clock.now(),
             order,
             price,
             new_broker_id,
@@ -203,12 +314,45 @@ impl WriteTransactionDB for SqliteDatabase {
     ) -> Result<Transaction, Box<dyn Error>> {
         WorkerTransaction::create_transaction(
             &mut self.connection.lock().unwrap(),
+            self.clock.now(),
             account.id,
             amount,
             currency,
             category,
         )
     }
+
+    fn create_broker_transaction(
+        &mut self,
+        account: &Account,
+        amount: rust_decimal::Decimal,
+        currency: &Currency,
+        category: TransactionCategory,
+        broker_activity_id: &str,
+    ) -> Result<Transaction, Box<dyn Error>> {
+        WorkerTransaction::create_broker_transaction(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            account.id,
+            amount,
+            currency,
+            category,
+            broker_activity_id,
+        )
+    }
+
+    fn create_reversal(
+        &mut self,
+        transaction: &Transaction,
+        category: TransactionCategory,
+    ) -> Result<Transaction, Box<dyn Error>> {
+        WorkerTransaction::create_reversal(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            transaction,
+            category,
+        )
+    }
 }
 
 impl ReadTransactionDB for SqliteDatabase {
@@ -287,6 +431,7 @@ impl ReadTransactionDB for SqliteDatabase {
     ) -> Result<Vec<Transaction>, Box<dyn Error>> {
         WorkerTransaction::read_all_transaction_excluding_current_month_and_taxes(
             &mut self.connection.lock().unwrap(),
+            self.clock.now(),
             account_id,
             currency,
         )
@@ -296,6 +441,18 @@ impl ReadTransactionDB for SqliteDatabase {
         &mut self,
         account_id: Uuid,
         currency: &Currency,
+    ) -> Result<Vec<Transaction>, Box<dyn Error>> {
+        WorkerTransaction::read_all_transactions(
+            &mut self.connection.lock().unwrap(),
+            account_id,
+            Some(currency),
+        )
+    }
+
+    fn all_account_transactions(
+        &mut self,
+        account_id: Uuid,
+        currency: Option<&Currency>,
     ) -> Result<Vec<Transaction>, Box<dyn Error>> {
         WorkerTransaction::read_all_transactions(
             &mut self.connection.lock().unwrap(),
@@ -303,6 +460,48 @@ impl ReadTransactionDB for SqliteDatabase {
             currency,
         )
     }
+
+    fn all_transactions_after(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+        after: NaiveDateTime,
+    ) -> Result<Vec<Transaction>, Box<dyn Error>> {
+        WorkerTransaction::read_all_transactions_after(
+            &mut self.connection.lock().unwrap(),
+            account_id,
+            currency,
+            after,
+        )
+    }
+
+    fn between(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        categories: Option<&[TransactionCategory]>,
+    ) -> Result<Vec<Transaction>, Box<dyn Error>> {
+        WorkerTransaction::read_all_transactions_between(
+            &mut self.connection.lock().unwrap(),
+            account_id,
+            currency,
+            from,
+            to,
+            categories,
+        )
+    }
+
+    fn for_broker_activity(
+        &mut self,
+        broker_activity_id: &str,
+    ) -> Result<Option<Transaction>, Box<dyn Error>> {
+        WorkerTransaction::read_for_broker_activity(
+            &mut self.connection.lock().unwrap(),
+            broker_activity_id,
+        )
+    }
 }
 
 impl ReadRuleDB for SqliteDatabase {
@@ -334,6 +533,7 @@ impl WriteRuleDB for SqliteDatabase {
     ) -> Result<model::Rule, Box<dyn Error>> {
         WorkerRule::create(
             &mut self.connection.lock().unwrap(),
+            self.clock.now(),
             name,
             description,
             priority,
@@ -357,12 +557,39 @@ impl WriteTradingVehicleDB for SqliteDatabase {
     ) -> Result<TradingVehicle, Box<dyn Error>> {
         WorkerTradingVehicle::create(
             &mut self.connection.lock().unwrap(),
+            self.clock.now(),
             symbol,
             isin,
             category,
             broker,
         )
     }
+
+    fn update_quote_currency(
+        &mut self,
+        trading_vehicle: &TradingVehicle,
+        currency: Option<Currency>,
+    ) -> Result<TradingVehicle, Box<dyn Error>> {
+        WorkerTradingVehicle::update_quote_currency(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            trading_vehicle,
+            currency,
+        )
+    }
+
+    fn update_tick_size(
+        &mut self,
+        trading_vehicle: &TradingVehicle,
+        tick_size: Option<Decimal>,
+    ) -> Result<TradingVehicle, Box<dyn Error>> {
+        WorkerTradingVehicle::update_tick_size(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            trading_vehicle,
+            tick_size,
+        )
+    }
 }
 
 impl ReadTradingVehicleDB for SqliteDatabase {
@@ -385,6 +612,7 @@ impl WriteTradeDB for SqliteDatabase {
     ) -> Result<Trade, Box<dyn Error>> {
         WorkerTrade::create(
             &mut self.connection.lock().unwrap(),
+            self.clock.now(),
             draft,
             stop,
             entry,
@@ -397,7 +625,53 @@ impl WriteTradeDB for SqliteDatabase {
         status: Status,
         trade: &Trade,
     ) -> Result<Trade, Box<dyn Error>> {
-        WorkerTrade::update_trade_status(&mut self.connection.lock().unwrap(), status, trade)
+        WorkerTrade::update_trade_status(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            status,
+            trade,
+        )
+    }
+
+    fn update_trade_thesis(
+        &mut self,
+        trade: &Trade,
+        thesis: Option<String>,
+        context: Option<String>,
+    ) -> Result<Trade, Box<dyn Error>> {
+        WorkerTrade::update_trade_thesis(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            trade,
+            thesis,
+            context,
+        )
+    }
+
+    fn update_trade_sector(
+        &mut self,
+        trade: &Trade,
+        sector: Option<String>,
+    ) -> Result<Trade, Box<dyn Error>> {
+        WorkerTrade::update_trade_sector(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            trade,
+            sector,
+        )
+    }
+
+    fn update_trade_last_synced_at(
+        &mut self,
+        trade: &Trade,
+        synced_at: NaiveDateTime,
+    ) -> Result<Trade, Box<dyn Error>> {
+        WorkerTrade::update_trade_last_synced_at(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            trade,
+            synced_at,
+        )
     }
 }
 
@@ -406,6 +680,22 @@ impl ReadTradeDB for SqliteDatabase {
         WorkerTrade::read_trade(&mut self.connection.lock().unwrap(), id)
     }
 
+    fn thesis_history(&mut self, trade_id: Uuid) -> Result<Vec<ThesisRevision>, Box<dyn Error>> {
+        WorkerThesisHistory::read_for_trade(&mut self.connection.lock().unwrap(), trade_id)
+    }
+
+    fn trade_by_reference(
+        &mut self,
+        account_id: Uuid,
+        reference: &str,
+    ) -> Result<Option<Trade>, Box<dyn Error>> {
+        WorkerTrade::read_trade_by_reference(
+            &mut self.connection.lock().unwrap(),
+            account_id,
+            reference,
+        )
+    }
+
     fn all_open_trades_for_currency(
         &mut self,
         account_id: Uuid,
@@ -429,6 +719,58 @@ impl ReadTradeDB for SqliteDatabase {
             status,
         )
     }
+
+    fn read_trades_for_symbol(
+        &mut self,
+        account_id: Uuid,
+        symbol: &str,
+        statuses: &[Status],
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        WorkerTrade::read_trades_for_symbol(
+            &mut self.connection.lock().unwrap(),
+            account_id,
+            symbol,
+            statuses,
+        )
+    }
+
+    fn read_closed_trades(&mut self, account_id: Uuid) -> Result<Vec<Trade>, Box<dyn Error>> {
+        WorkerTrade::read_closed_trades(&mut self.connection.lock().unwrap(), account_id)
+    }
+
+    fn read_closed_trades_between(
+        &mut self,
+        account_id: Uuid,
+        closed_from: NaiveDateTime,
+        closed_until: NaiveDateTime,
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        WorkerTrade::read_closed_trades_between(
+            &mut self.connection.lock().unwrap(),
+            account_id,
+            closed_from,
+            closed_until,
+        )
+    }
+
+    fn read_trades_with_status_paged(
+        &mut self,
+        account_id: Uuid,
+        status: Status,
+        limit: u32,
+        offset: u32,
+        created_after: Option<NaiveDateTime>,
+        created_before: Option<NaiveDateTime>,
+    ) -> Result<(Vec<Trade>, u64), Box<dyn Error>> {
+        WorkerTrade::read_trades_with_status_paged(
+            &mut self.connection.lock().unwrap(),
+            account_id,
+            status,
+            limit,
+            offset,
+            created_after,
+            created_before,
+        )
+    }
 }
 
 impl WriteAccountBalanceDB for SqliteDatabase {
@@ -443,6 +785,7 @@ impl WriteAccountBalanceDB for SqliteDatabase {
     ) -> Result<TradeBalance, Box<dyn Error>> {
         WorkerTrade::update_trade_balance(
             &mut self.connection.lock().unwrap(),
+            self.clock.now(),
             trade,
             funding,
             capital_in_market,
@@ -458,3 +801,340 @@ impl OrderRead for SqliteDatabase {
         WorkerOrder::read(&mut self.connection.lock().unwrap(), id)
     }
 }
+
+impl WriteExecutionDB for SqliteDatabase {
+    fn create_execution(&mut self, draft: &DraftExecution) -> Result<Execution, Box<dyn Error>> {
+        WorkerExecution::create(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            draft,
+        )
+    }
+}
+
+impl ReadExecutionDB for SqliteDatabase {
+    fn read_executions_for_trade(
+        &mut self,
+        trade_id: Uuid,
+    ) -> Result<Vec<Execution>, Box<dyn Error>> {
+        WorkerExecution::read_all_for_trade(&mut self.connection.lock().unwrap(), trade_id)
+    }
+
+    fn execution_for_key(
+        &mut self,
+        idempotency_key: &str,
+    ) -> Result<Option<Execution>, Box<dyn Error>> {
+        WorkerExecution::read_for_key(&mut self.connection.lock().unwrap(), idempotency_key)
+    }
+}
+
+impl WriteEquitySnapshotDB for SqliteDatabase {
+    fn create_equity_snapshot(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+        timestamp: NaiveDateTime,
+        balance: Decimal,
+    ) -> Result<EquitySnapshot, Box<dyn Error>> {
+        WorkerEquitySnapshot::create(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            account_id,
+            currency,
+            timestamp,
+            balance,
+        )
+    }
+
+    fn delete_equity_snapshots(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+    ) -> Result<(), Box<dyn Error>> {
+        WorkerEquitySnapshot::delete_all(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            account_id,
+            currency,
+        )
+    }
+}
+
+impl ReadEquitySnapshotDB for SqliteDatabase {
+    fn read_equity_snapshots(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+    ) -> Result<Vec<EquitySnapshot>, Box<dyn Error>> {
+        WorkerEquitySnapshot::read_all(&mut self.connection.lock().unwrap(), account_id, currency)
+    }
+}
+
+impl WriteLevelDB for SqliteDatabase {
+    fn create_level(
+        &mut self,
+        account_id: Uuid,
+        current_level: u8,
+    ) -> Result<Level, Box<dyn Error>> {
+        WorkerLevel::create(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            account_id,
+            current_level,
+        )
+    }
+
+    fn update_level(&mut self, level: &Level, new_level: u8) -> Result<Level, Box<dyn Error>> {
+        WorkerLevel::update_level(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            level,
+            new_level,
+        )
+    }
+
+    fn create_level_change(
+        &mut self,
+        account_id: Uuid,
+        old_level: u8,
+        new_level: u8,
+        reason: &str,
+        trigger: &LevelTrigger,
+    ) -> Result<LevelChange, Box<dyn Error>> {
+        WorkerLevel::create_change(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            account_id,
+            old_level,
+            new_level,
+            reason,
+            trigger,
+        )
+    }
+
+    fn update_level_adjustment_rules(
+        &mut self,
+        level: &Level,
+        rules: &LevelAdjustmentRules,
+    ) -> Result<LevelAdjustmentRules, Box<dyn Error>> {
+        WorkerLevel::update_adjustment_rules(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            level,
+            rules,
+        )
+    }
+}
+
+impl ReadLevelDB for SqliteDatabase {
+    fn level_for_account(&mut self, account_id: Uuid) -> Result<Level, Box<dyn Error>> {
+        WorkerLevel::read_for_account(&mut self.connection.lock().unwrap(), account_id)
+    }
+
+    fn level_changes_for_account(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Vec<LevelChange>, Box<dyn Error>> {
+        WorkerLevel::read_changes_for_account(&mut self.connection.lock().unwrap(), account_id)
+    }
+
+    fn level_adjustment_rules(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<LevelAdjustmentRules, Box<dyn Error>> {
+        WorkerLevel::read_adjustment_rules(&mut self.connection.lock().unwrap(), account_id)
+    }
+}
+
+impl WriteStrategyDB for SqliteDatabase {
+    fn create_strategy(
+        &mut self,
+        name: &str,
+        description: &str,
+        version: u16,
+        entry_description: &str,
+        stop_description: &str,
+        target_description: &str,
+    ) -> Result<Strategy, Box<dyn Error>> {
+        WorkerStrategy::create(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            name,
+            description,
+            version,
+            entry_description,
+            stop_description,
+            target_description,
+        )
+    }
+}
+
+impl ReadStrategyDB for SqliteDatabase {
+    fn read_strategy(&mut self, id: Uuid) -> Result<Strategy, Box<dyn Error>> {
+        WorkerStrategy::read(&mut self.connection.lock().unwrap(), id)
+    }
+
+    fn read_all_strategies(&mut self) -> Result<Vec<Strategy>, Box<dyn Error>> {
+        WorkerStrategy::read_all(&mut self.connection.lock().unwrap())
+    }
+}
+
+impl WriteDistributionDB for SqliteDatabase {
+    fn create_distribution_rules(
+        &mut self,
+        account_id: Uuid,
+        earnings_percent: Decimal,
+        tax_percent: Decimal,
+        reinvestment_percent: Decimal,
+    ) -> Result<DistributionRules, Box<dyn Error>> {
+        WorkerDistribution::create_rules(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            account_id,
+            earnings_percent,
+            tax_percent,
+            reinvestment_percent,
+        )
+    }
+
+    fn update_distribution_rules(
+        &mut self,
+        rules: &DistributionRules,
+        earnings_percent: Decimal,
+        tax_percent: Decimal,
+        reinvestment_percent: Decimal,
+    ) -> Result<DistributionRules, Box<dyn Error>> {
+        WorkerDistribution::update_rules(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            rules,
+            earnings_percent,
+            tax_percent,
+            reinvestment_percent,
+        )
+    }
+
+    fn create_distribution_history(
+        &mut self,
+        account_id: Uuid,
+        trade_id: Uuid,
+        original_amount: Decimal,
+        earnings_amount: Decimal,
+        tax_amount: Decimal,
+        reinvestment_amount: Decimal,
+    ) -> Result<DistributionHistory, Box<dyn Error>> {
+        WorkerDistribution::create_history(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            account_id,
+            trade_id,
+            original_amount,
+            earnings_amount,
+            tax_amount,
+            reinvestment_amount,
+        )
+    }
+}
+
+impl ReadDistributionDB for SqliteDatabase {
+    fn distribution_rules(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<DistributionRules, Box<dyn Error>> {
+        WorkerDistribution::read_rules(&mut self.connection.lock().unwrap(), account_id)
+    }
+
+    fn distribution_history(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Vec<DistributionHistory>, Box<dyn Error>> {
+        WorkerDistribution::read_history_for_account(
+            &mut self.connection.lock().unwrap(),
+            account_id,
+        )
+    }
+}
+
+impl WriteAdvisoryDB for SqliteDatabase {
+    fn create_advisory_thresholds(
+        &mut self,
+        account_id: Uuid,
+        max_risk_per_trade_percent: Decimal,
+        max_position_concentration_percent: Decimal,
+    ) -> Result<AdvisoryThresholds, Box<dyn Error>> {
+        WorkerAdvisory::create_thresholds(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            account_id,
+            max_risk_per_trade_percent,
+            max_position_concentration_percent,
+        )
+    }
+
+    fn update_advisory_thresholds(
+        &mut self,
+        thresholds: &AdvisoryThresholds,
+        max_risk_per_trade_percent: Decimal,
+        max_position_concentration_percent: Decimal,
+    ) -> Result<AdvisoryThresholds, Box<dyn Error>> {
+        WorkerAdvisory::update_thresholds(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            thresholds,
+            max_risk_per_trade_percent,
+            max_position_concentration_percent,
+        )
+    }
+}
+
+impl ReadAdvisoryDB for SqliteDatabase {
+    fn advisory_thresholds(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Option<AdvisoryThresholds>, Box<dyn Error>> {
+        WorkerAdvisory::read_thresholds(&mut self.connection.lock().unwrap(), account_id)
+    }
+}
+
+impl WriteTradeGradeDB for SqliteDatabase {
+    fn create_trade_grade(&mut self, grade: &TradeGrade) -> Result<TradeGrade, Box<dyn Error>> {
+        WorkerTradeGrade::create(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            grade,
+        )
+    }
+}
+
+impl ReadTradeGradeDB for SqliteDatabase {
+    fn trade_grade(&mut self, trade_id: Uuid) -> Result<TradeGrade, Box<dyn Error>> {
+        WorkerTradeGrade::read_for_trade(&mut self.connection.lock().unwrap(), trade_id)
+    }
+}
+
+impl WriteRealizedLotDB for SqliteDatabase {
+    fn create_realized_lot(&mut self, lot: &RealizedLot) -> Result<RealizedLot, Box<dyn Error>> {
+        WorkerRealizedLot::create(&mut self.connection.lock().unwrap(), self.clock.now(), lot)
+    }
+
+    fn delete_realized_lots(&mut self, trade_id: Uuid) -> Result<(), Box<dyn Error>> {
+        WorkerRealizedLot::delete_all_for_trade(
+            &mut self.connection.lock().unwrap(),
+            self.clock.now(),
+            trade_id,
+        )
+    }
+}
+
+impl ReadRealizedLotDB for SqliteDatabase {
+    fn realized_lots_for_trade(
+        &mut self,
+        trade_id: Uuid,
+    ) -> Result<Vec<RealizedLot>, Box<dyn Error>> {
+        WorkerRealizedLot::read_all_for_trade(&mut self.connection.lock().unwrap(), trade_id)
+    }
+
+    fn realized_lots(&mut self, account_id: Uuid) -> Result<Vec<RealizedLot>, Box<dyn Error>> {
+        WorkerRealizedLot::read_all_for_account(&mut self.connection.lock().unwrap(), account_id)
+    }
+}
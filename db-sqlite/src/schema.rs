@@ -9,6 +9,17 @@ diesel::table! {
         environment -> Text,
         taxes_percentage -> Text,
         earnings_percentage -> Text,
+        confirm_above_notional -> Nullable<Text>,
+        tick_size_policy -> Text,
+        min_paper_trades_before_live -> Nullable<Integer>,
+        paper_account_id -> Nullable<Text>,
+        account_type -> Text,
+        parent_account_id -> Nullable<Text>,
+        lot_method -> Text,
+        fee_model -> Text,
+        fee_model_amount -> Nullable<Text>,
+        base_currency -> Text,
+        duplicate_trade_window_seconds -> Nullable<Integer>,
     }
 }
 
@@ -35,7 +46,7 @@ diesel::table! {
         updated_at -> Timestamp,
         deleted_at -> Nullable<Timestamp>,
         name -> Text,
-        risk -> Integer,
+        risk -> Float,
         description -> Text,
         priority -> Integer,
         level -> Text,
@@ -55,6 +66,10 @@ diesel::table! {
         amount -> Text,
         account_id -> Text,
         trade_id -> Nullable<Text>,
+        trading_vehicle_id -> Nullable<Text>,
+        reverses -> Nullable<Text>,
+        reason -> Nullable<Text>,
+        broker_activity_id -> Nullable<Text>,
     }
 }
 
@@ -68,6 +83,8 @@ diesel::table! {
         isin -> Text,
         category -> Text,
         broker -> Text,
+        quote_currency -> Nullable<Text>,
+        tick_size -> Nullable<Text>,
     }
 }
 
@@ -96,6 +113,8 @@ diesel::table! {
         expired_at -> Nullable<Timestamp>,
         cancelled_at -> Nullable<Timestamp>,
         closed_at -> Nullable<Timestamp>,
+        quantity_decimal -> Nullable<Text>,
+        filled_quantity_decimal -> Nullable<Text>,
     }
 }
 
@@ -114,6 +133,12 @@ diesel::table! {
         target_id -> Text,
         account_id -> Text,
         balance_id -> Text,
+        strategy_id -> Nullable<Text>,
+        reference -> Nullable<Text>,
+        thesis -> Nullable<Text>,
+        context -> Nullable<Text>,
+        sector -> Nullable<Text>,
+        last_synced_at -> Nullable<Timestamp>,
     }
 }
 
@@ -143,11 +168,187 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    executions (id) {
+        id -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        deleted_at -> Nullable<Timestamp>,
+        broker_execution_id -> Nullable<Text>,
+        trade_id -> Text,
+        order_id -> Text,
+        side -> Text,
+        price -> Text,
+        quantity -> BigInt,
+        executed_at -> Timestamp,
+        source -> Text,
+        idempotency_key -> Text,
+    }
+}
+
+diesel::table! {
+    equity_snapshots (id) {
+        id -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        deleted_at -> Nullable<Timestamp>,
+        account_id -> Text,
+        currency -> Text,
+        timestamp -> Timestamp,
+        balance -> Text,
+    }
+}
+
+diesel::table! {
+    levels (id) {
+        id -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        deleted_at -> Nullable<Timestamp>,
+        account_id -> Text,
+        current_level -> Integer,
+        monthly_loss_downgrade_pct -> Text,
+    }
+}
+
+diesel::table! {
+    level_changes (id) {
+        id -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        deleted_at -> Nullable<Timestamp>,
+        account_id -> Text,
+        old_level -> Integer,
+        new_level -> Integer,
+        reason -> Text,
+        trigger -> Text,
+        changed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    strategies (id) {
+        id -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        deleted_at -> Nullable<Timestamp>,
+        name -> Text,
+        description -> Text,
+        version -> Integer,
+        entry_description -> Text,
+        stop_description -> Text,
+        target_description -> Text,
+    }
+}
+
+diesel::table! {
+    distribution_rules (id) {
+        id -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        deleted_at -> Nullable<Timestamp>,
+        account_id -> Text,
+        earnings_percent -> Text,
+        tax_percent -> Text,
+        reinvestment_percent -> Text,
+    }
+}
+
+diesel::table! {
+    distribution_history (id) {
+        id -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        deleted_at -> Nullable<Timestamp>,
+        account_id -> Text,
+        trade_id -> Text,
+        original_amount -> Text,
+        earnings_amount -> Text,
+        tax_amount -> Text,
+        reinvestment_amount -> Text,
+        distributed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    advisory_thresholds (id) {
+        id -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        deleted_at -> Nullable<Timestamp>,
+        account_id -> Text,
+        max_risk_per_trade_percent -> Text,
+        max_position_concentration_percent -> Text,
+    }
+}
+
+diesel::table! {
+    trade_grades (id) {
+        id -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        deleted_at -> Nullable<Timestamp>,
+        trade_id -> Text,
+        risk_management -> Integer,
+        execution -> Integer,
+        outcome -> Integer,
+        documentation -> Integer,
+        overall -> Integer,
+    }
+}
+
+diesel::table! {
+    thesis_history (id) {
+        id -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        deleted_at -> Nullable<Timestamp>,
+        trade_id -> Text,
+        thesis -> Nullable<Text>,
+        context -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    realized_lots (id) {
+        id -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        deleted_at -> Nullable<Timestamp>,
+        account_id -> Text,
+        trade_id -> Text,
+        open_execution_id -> Text,
+        close_execution_id -> Text,
+        opened_at -> Timestamp,
+        closed_at -> Timestamp,
+        quantity -> BigInt,
+        cost_basis -> Text,
+        proceeds -> Text,
+        gain -> Text,
+    }
+}
+
 diesel::joinable!(transactions -> accounts (account_id));
+diesel::joinable!(transactions -> trading_vehicles (trading_vehicle_id));
 diesel::joinable!(accounts_balances -> accounts (account_id));
 diesel::joinable!(orders -> trading_vehicles (trading_vehicle_id));
 diesel::joinable!(trades -> accounts (account_id));
 diesel::joinable!(trades -> trades_balances (balance_id));
 diesel::joinable!(trades -> trading_vehicles (trading_vehicle_id));
 diesel::joinable!(trades -> orders (safety_stop_id));
+diesel::joinable!(trades -> strategies (strategy_id));
 diesel::joinable!(logs -> trades (trade_id));
+diesel::joinable!(executions -> trades (trade_id));
+diesel::joinable!(equity_snapshots -> accounts (account_id));
+diesel::joinable!(levels -> accounts (account_id));
+diesel::joinable!(level_changes -> accounts (account_id));
+diesel::joinable!(distribution_rules -> accounts (account_id));
+diesel::joinable!(distribution_history -> accounts (account_id));
+diesel::joinable!(distribution_history -> trades (trade_id));
+diesel::joinable!(advisory_thresholds -> accounts (account_id));
+diesel::joinable!(trade_grades -> trades (trade_id));
+diesel::joinable!(thesis_history -> trades (trade_id));
+diesel::joinable!(realized_lots -> accounts (account_id));
+diesel::joinable!(realized_lots -> trades (trade_id));
+
+diesel::allow_tables_to_appear_in_same_query!(trades, trading_vehicles);
@@ -1,17 +1,35 @@
 mod account_balance;
 mod accounts;
 mod broker_logs;
+mod worker_advisory;
+mod worker_distribution;
+mod worker_equity_snapshot;
+mod worker_execution;
+mod worker_level;
 mod worker_order;
+mod worker_realized_lot;
 mod worker_rule;
+mod worker_strategy;
+mod worker_thesis_history;
 mod worker_trade;
+mod worker_trade_grade;
 mod worker_trading_vehicle;
 mod worker_transaction;
 
 pub use account_balance::AccountBalanceDB;
 pub use accounts::AccountDB;
 pub use broker_logs::BrokerLogDB;
+pub use worker_advisory::WorkerAdvisory;
+pub use worker_distribution::WorkerDistribution;
+pub use worker_equity_snapshot::WorkerEquitySnapshot;
+pub use worker_execution::WorkerExecution;
+pub use worker_level::WorkerLevel;
 pub use worker_order::WorkerOrder;
+pub use worker_realized_lot::WorkerRealizedLot;
 pub use worker_rule::WorkerRule;
+pub use worker_strategy::WorkerStrategy;
+pub use worker_thesis_history::WorkerThesisHistory;
 pub use worker_trade::WorkerTrade;
+pub use worker_trade_grade::WorkerTradeGrade;
 pub use worker_trading_vehicle::WorkerTradingVehicle;
 pub use worker_transaction::WorkerTransaction;
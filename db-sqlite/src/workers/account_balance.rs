@@ -2,7 +2,7 @@ use crate::schema::accounts_balances;
 use chrono::{NaiveDateTime, Utc};
 use diesel::prelude::*;
 use diesel::SqliteConnection;
-use model::{Account, AccountBalance, AccountBalanceRead, AccountBalanceWrite, Currency};
+use model::{Account, AccountBalance, AccountBalanceRead, AccountBalanceWrite, Clock, Currency};
 use rust_decimal::Decimal;
 use std::error::Error;
 use std::sync::Arc;
@@ -12,6 +12,7 @@ use uuid::Uuid;
 
 pub struct AccountBalanceDB {
     pub connection: Arc<Mutex<SqliteConnection>>,
+    pub clock: Arc<dyn Clock>,
 }
 
 impl AccountBalanceWrite for AccountBalanceDB {
@@ -20,7 +21,10 @@ impl AccountBalanceWrite for AccountBalanceDB {
         account: &Account,
         currency: &Currency,
     ) -> Result<AccountBalance, Box<dyn Error>> {
+        let now = self.clock.now();
         let new_account_balance = NewAccountBalance {
+            created_at: now,
+            updated_at: now,
             account_id: account.id.to_string(),
             currency: currency.to_string(),
             ..Default::default()
@@ -51,7 +55,7 @@ impl AccountBalanceWrite for AccountBalanceDB {
         let balance = diesel::update(accounts_balances::table)
             .filter(accounts_balances::id.eq(&balance.id.to_string()))
             .set((
-                accounts_balances::updated_at.eq(Utc::now().naive_utc()),
+                accounts_balances::updated_at.eq(self.clock.now()),
                 accounts_balances::total_balance.eq(total_balance.to_string()),
                 accounts_balances::total_available.eq(total_available.to_string()),
                 accounts_balances::total_in_trade.eq(total_in_trade.to_string()),
@@ -1,8 +1,11 @@
-use crate::schema::accounts;
-use chrono::{NaiveDateTime, Utc};
+use crate::schema::{accounts, accounts_balances, realized_lots, rules, trades, transactions};
+use chrono::NaiveDateTime;
 use diesel::prelude::*;
 use model::AccountRead;
-use model::{Account, AccountWrite, Environment};
+use model::{
+    Account, AccountType, AccountWrite, Clock, Currency, Environment, FeeModel, LotMethod,
+    TickSizePolicy,
+};
 use rust_decimal::Decimal;
 use std::error::Error;
 use std::str::FromStr;
@@ -13,6 +16,7 @@ use uuid::Uuid;
 
 pub struct AccountDB {
     pub connection: Arc<Mutex<SqliteConnection>>,
+    pub clock: Arc<dyn Clock>,
 }
 
 impl AccountWrite for AccountDB {
@@ -23,9 +27,291 @@ impl AccountWrite for AccountDB {
         environment: Environment,
         taxes_percentage: Decimal,
         earnings_percentage: Decimal,
+    ) -> Result<Account, Box<dyn Error>> {
+        self.insert(
+            name,
+            description,
+            environment,
+            taxes_percentage,
+            earnings_percentage,
+            None,
+            AccountType::default(),
+            None,
+        )
+    }
+
+    fn create_live(
+        &mut self,
+        name: &str,
+        description: &str,
+        taxes_percentage: Decimal,
+        earnings_percentage: Decimal,
+        paper_account: &Account,
+    ) -> Result<Account, Box<dyn Error>> {
+        self.insert(
+            name,
+            description,
+            Environment::Live,
+            taxes_percentage,
+            earnings_percentage,
+            Some(paper_account.id),
+            AccountType::default(),
+            None,
+        )
+    }
+
+    fn create_with_hierarchy(
+        &mut self,
+        name: &str,
+        description: &str,
+        environment: Environment,
+        taxes_percentage: Decimal,
+        earnings_percentage: Decimal,
+        account_type: AccountType,
+        parent_account_id: Option<Uuid>,
+    ) -> Result<Account, Box<dyn Error>> {
+        self.insert(
+            name,
+            description,
+            environment,
+            taxes_percentage,
+            earnings_percentage,
+            None,
+            account_type,
+            parent_account_id,
+        )
+    }
+
+    fn update_confirm_above_notional(
+        &mut self,
+        account: &Account,
+        notional: Option<Decimal>,
+    ) -> Result<Account, Box<dyn Error>> {
+        let connection: &mut SqliteConnection = &mut self.connection.lock().unwrap();
+
+        let account = diesel::update(accounts::table)
+            .filter(accounts::id.eq(account.id.to_string()))
+            .set((
+                accounts::updated_at.eq(self.clock.now()),
+                accounts::confirm_above_notional.eq(notional.map(|notional| notional.to_string())),
+            ))
+            .get_result::<AccountSQLite>(connection)
+            .map(|account| account.domain_model())
+            .map_err(|error| {
+                error!("Error updating account confirmation threshold: {:?}", error);
+                error
+            })?;
+        Ok(account)
+    }
+
+    fn update_tick_size_policy(
+        &mut self,
+        account: &Account,
+        policy: TickSizePolicy,
+    ) -> Result<Account, Box<dyn Error>> {
+        let connection: &mut SqliteConnection = &mut self.connection.lock().unwrap();
+
+        let account = diesel::update(accounts::table)
+            .filter(accounts::id.eq(account.id.to_string()))
+            .set((
+                accounts::updated_at.eq(self.clock.now()),
+                accounts::tick_size_policy.eq(policy.to_string()),
+            ))
+            .get_result::<AccountSQLite>(connection)
+            .map(|account| account.domain_model())
+            .map_err(|error| {
+                error!("Error updating account tick size policy: {:?}", error);
+                error
+            })?;
+        Ok(account)
+    }
+
+    fn update_min_paper_trades_before_live(
+        &mut self,
+        account: &Account,
+        min_trades: Option<u32>,
+    ) -> Result<Account, Box<dyn Error>> {
+        let connection: &mut SqliteConnection = &mut self.connection.lock().unwrap();
+
+        let account = diesel::update(accounts::table)
+            .filter(accounts::id.eq(account.id.to_string()))
+            .set((
+                accounts::updated_at.eq(self.clock.now()),
+                accounts::min_paper_trades_before_live.eq(min_trades.map(|min| min as i32)),
+            ))
+            .get_result::<AccountSQLite>(connection)
+            .map(|account| account.domain_model())
+            .map_err(|error| {
+                error!("Error updating account minimum paper trades: {:?}", error);
+                error
+            })?;
+        Ok(account)
+    }
+
+    fn update_lot_method(
+        &mut self,
+        account: &Account,
+        method: LotMethod,
+    ) -> Result<Account, Box<dyn Error>> {
+        let connection: &mut SqliteConnection = &mut self.connection.lock().unwrap();
+
+        let account = diesel::update(accounts::table)
+            .filter(accounts::id.eq(account.id.to_string()))
+            .set((
+                accounts::updated_at.eq(self.clock.now()),
+                accounts::lot_method.eq(method.to_string()),
+            ))
+            .get_result::<AccountSQLite>(connection)
+            .map(|account| account.domain_model())
+            .map_err(|error| {
+                error!("Error updating account lot method: {:?}", error);
+                error
+            })?;
+        Ok(account)
+    }
+
+    fn update_fee_model(
+        &mut self,
+        account: &Account,
+        model: FeeModel,
+    ) -> Result<Account, Box<dyn Error>> {
+        let connection: &mut SqliteConnection = &mut self.connection.lock().unwrap();
+
+        let account = diesel::update(accounts::table)
+            .filter(accounts::id.eq(account.id.to_string()))
+            .set((
+                accounts::updated_at.eq(self.clock.now()),
+                accounts::fee_model.eq(model.to_string()),
+                accounts::fee_model_amount.eq(model.amount().map(|amount| amount.to_string())),
+            ))
+            .get_result::<AccountSQLite>(connection)
+            .map(|account| account.domain_model())
+            .map_err(|error| {
+                error!("Error updating account fee model: {:?}", error);
+                error
+            })?;
+        Ok(account)
+    }
+
+    fn update_base_currency(
+        &mut self,
+        account: &Account,
+        currency: &Currency,
+    ) -> Result<Account, Box<dyn Error>> {
+        let connection: &mut SqliteConnection = &mut self.connection.lock().unwrap();
+
+        let account = diesel::update(accounts::table)
+            .filter(accounts::id.eq(account.id.to_string()))
+            .set((
+                accounts::updated_at.eq(self.clock.now()),
+                accounts::base_currency.eq(currency.to_string()),
+            ))
+            .get_result::<AccountSQLite>(connection)
+            .map(|account| account.domain_model())
+            .map_err(|error| {
+                error!("Error updating account base currency: {:?}", error);
+                error
+            })?;
+        Ok(account)
+    }
+
+    fn update_duplicate_trade_window(
+        &mut self,
+        account: &Account,
+        window_seconds: Option<u32>,
+    ) -> Result<Account, Box<dyn Error>> {
+        let connection: &mut SqliteConnection = &mut self.connection.lock().unwrap();
+
+        let account = diesel::update(accounts::table)
+            .filter(accounts::id.eq(account.id.to_string()))
+            .set((
+                accounts::updated_at.eq(self.clock.now()),
+                accounts::duplicate_trade_window_seconds
+                    .eq(window_seconds.map(|seconds| seconds as i32)),
+            ))
+            .get_result::<AccountSQLite>(connection)
+            .map(|account| account.domain_model())
+            .map_err(|error| {
+                error!("Error updating account duplicate trade window: {:?}", error);
+                error
+            })?;
+        Ok(account)
+    }
+
+    fn merge_into(&mut self, source: &Account, target: &Account) -> Result<(), Box<dyn Error>> {
+        let connection: &mut SqliteConnection = &mut self.connection.lock().unwrap();
+        let now = self.clock.now();
+        let source_id = source.id.to_string();
+        let target_id = target.id.to_string();
+
+        diesel::update(transactions::table)
+            .filter(transactions::account_id.eq(&source_id))
+            .set((
+                transactions::account_id.eq(&target_id),
+                transactions::updated_at.eq(now),
+            ))
+            .execute(connection)?;
+        diesel::update(trades::table)
+            .filter(trades::account_id.eq(&source_id))
+            .set((
+                trades::account_id.eq(&target_id),
+                trades::updated_at.eq(now),
+            ))
+            .execute(connection)?;
+        diesel::update(rules::table)
+            .filter(rules::account_id.eq(&source_id))
+            .set((rules::account_id.eq(&target_id), rules::updated_at.eq(now)))
+            .execute(connection)?;
+        diesel::update(realized_lots::table)
+            .filter(realized_lots::account_id.eq(&source_id))
+            .set((
+                realized_lots::account_id.eq(&target_id),
+                realized_lots::updated_at.eq(now),
+            ))
+            .execute(connection)?;
+        diesel::update(accounts::table)
+            .filter(accounts::parent_account_id.eq(&source_id))
+            .set((
+                accounts::parent_account_id.eq(&target_id),
+                accounts::updated_at.eq(now),
+            ))
+            .execute(connection)?;
+
+        diesel::update(accounts_balances::table)
+            .filter(accounts_balances::account_id.eq(&source_id))
+            .filter(accounts_balances::deleted_at.is_null())
+            .set((
+                accounts_balances::deleted_at.eq(now),
+                accounts_balances::updated_at.eq(now),
+            ))
+            .execute(connection)?;
+        diesel::update(accounts::table)
+            .filter(accounts::id.eq(&source_id))
+            .set((accounts::deleted_at.eq(now), accounts::updated_at.eq(now)))
+            .execute(connection)
+            .map_err(|error| {
+                error!("Error merging account: {:?}", error);
+                error
+            })?;
+        Ok(())
+    }
+}
+
+impl AccountDB {
+    #[allow(clippy::too_many_arguments)]
+    fn insert(
+        &mut self,
+        name: &str,
+        description: &str,
+        environment: Environment,
+        taxes_percentage: Decimal,
+        earnings_percentage: Decimal,
+        paper_account_id: Option<Uuid>,
+        account_type: AccountType,
+        parent_account_id: Option<Uuid>,
     ) -> Result<Account, Box<dyn Error>> {
         let uuid = Uuid::new_v4().to_string();
-        let now = Utc::now().naive_utc();
+        let now = self.clock.now();
 
         let new_account = NewAccount {
             id: uuid,
@@ -37,6 +323,17 @@ impl AccountWrite for AccountDB {
             environment: environment.to_string(),
             taxes_percentage: taxes_percentage.to_string(),
             earnings_percentage: earnings_percentage.to_string(),
+            confirm_above_notional: None,
+            tick_size_policy: TickSizePolicy::default().to_string(),
+            min_paper_trades_before_live: None,
+            paper_account_id: paper_account_id.map(|id| id.to_string()),
+            account_type: account_type.to_string(),
+            parent_account_id: parent_account_id.map(|id| id.to_string()),
+            lot_method: LotMethod::default().to_string(),
+            fee_model: FeeModel::default().to_string(),
+            fee_model_amount: None,
+            base_currency: Currency::default().to_string(),
+            duplicate_trade_window_seconds: None,
         };
 
         let connection: &mut SqliteConnection = &mut self.connection.lock().unwrap();
@@ -58,6 +355,7 @@ impl AccountRead for AccountDB {
         let connection: &mut SqliteConnection = &mut self.connection.lock().unwrap();
 
         let account = accounts::table
+            .filter(accounts::deleted_at.is_null())
             .filter(accounts::name.eq(name.to_lowercase()))
             .first::<AccountSQLite>(connection)
             .map(|account| account.domain_model())
@@ -99,6 +397,32 @@ impl AccountRead for AccountDB {
             })?;
         Ok(accounts)
     }
+
+    fn children_of(&mut self, parent_id: Uuid) -> Result<Vec<Account>, Box<dyn Error>> {
+        let connection: &mut SqliteConnection = &mut self.connection.lock().unwrap();
+        let accounts = accounts::table
+            .filter(accounts::deleted_at.is_null())
+            .filter(accounts::parent_account_id.eq(parent_id.to_string()))
+            .load::<AccountSQLite>(connection)
+            .map(|accounts| {
+                accounts
+                    .into_iter()
+                    .map(|account| account.domain_model())
+                    .collect()
+            })
+            .map_err(|error| {
+                error!("Error reading children accounts: {:?}", error);
+                error
+            })?;
+        Ok(accounts)
+    }
+
+    fn parent_of(&mut self, account_id: Uuid) -> Result<Option<Account>, Box<dyn Error>> {
+        match self.id(account_id)?.parent_account_id {
+            Some(parent_id) => Ok(Some(self.id(parent_id)?)),
+            None => Ok(None),
+        }
+    }
 }
 
 #[derive(Queryable, Identifiable, AsChangeset, Insertable)]
@@ -115,6 +439,17 @@ pub struct AccountSQLite {
     pub environment: String,
     pub taxes_percentage: String,
     pub earnings_percentage: String,
+    pub confirm_above_notional: Option<String>,
+    pub tick_size_policy: String,
+    pub min_paper_trades_before_live: Option<i32>,
+    pub paper_account_id: Option<String>,
+    pub account_type: String,
+    pub parent_account_id: Option<String>,
+    pub lot_method: String,
+    pub fee_model: String,
+    pub fee_model_amount: Option<String>,
+    pub base_currency: String,
+    pub duplicate_trade_window_seconds: Option<i32>,
 }
 
 impl AccountSQLite {
@@ -129,6 +464,29 @@ impl AccountSQLite {
             environment: Environment::from_str(&self.environment).unwrap(),
             taxes_percentage: Decimal::from_str(&self.taxes_percentage).unwrap(),
             earnings_percentage: Decimal::from_str(&self.earnings_percentage).unwrap(),
+            confirm_above_notional: self
+                .confirm_above_notional
+                .map(|notional| Decimal::from_str(&notional).unwrap()),
+            tick_size_policy: TickSizePolicy::from_str(&self.tick_size_policy).unwrap(),
+            min_paper_trades_before_live: self.min_paper_trades_before_live.map(|min| min as u32),
+            paper_account_id: self
+                .paper_account_id
+                .map(|id| Uuid::parse_str(&id).unwrap()),
+            account_type: AccountType::from_str(&self.account_type).unwrap(),
+            parent_account_id: self
+                .parent_account_id
+                .map(|id| Uuid::parse_str(&id).unwrap()),
+            lot_method: LotMethod::from_str(&self.lot_method).unwrap(),
+            fee_model: FeeModel::parse(
+                &self.fee_model,
+                self.fee_model_amount
+                    .map(|amount| Decimal::from_str(&amount).unwrap()),
+            )
+            .unwrap(),
+            base_currency: Currency::from_str(&self.base_currency).unwrap(),
+            duplicate_trade_window_seconds: self
+                .duplicate_trade_window_seconds
+                .map(|seconds| seconds as u32),
         }
     }
 }
@@ -146,6 +504,17 @@ struct NewAccount {
     environment: String,
     taxes_percentage: String,
     earnings_percentage: String,
+    confirm_above_notional: Option<String>,
+    tick_size_policy: String,
+    min_paper_trades_before_live: Option<i32>,
+    paper_account_id: Option<String>,
+    account_type: String,
+    parent_account_id: Option<String>,
+    lot_method: String,
+    fee_model: String,
+    fee_model_amount: Option<String>,
+    base_currency: String,
+    duplicate_trade_window_seconds: Option<i32>,
 }
 
 #[cfg(test)]
@@ -153,7 +522,7 @@ mod tests {
     use super::*;
     use crate::SqliteDatabase;
     use diesel_migrations::*;
-    use model::DatabaseFactory;
+    use model::{DatabaseFactory, SystemClock};
     use rust_decimal_macros::dec;
     pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
     // Declare a test database connection
@@ -173,6 +542,7 @@ mod tests {
         let conn: SqliteConnection = establish_connection();
         let mut db = AccountDB {
             connection: Arc::new(Mutex::new(conn)),
+            clock: Arc::new(SystemClock),
         };
         // Create a new account record
         let account = db
@@ -190,10 +560,24 @@ mod tests {
         assert_eq!(account.deleted_at, None);
     }
     #[test]
+    fn test_create_backtest_account() {
+        let conn = establish_connection();
+        let mut db = AccountDB {
+            connection: Arc::new(Mutex::new(conn)),
+            clock: Arc::new(SystemClock),
+        };
+        let account = db
+            .create("Backtest", "", Environment::Backtest, dec!(20), dec!(80))
+            .expect("Error creating account");
+        assert_eq!(account.environment, Environment::Backtest);
+        assert_eq!(db.id(account.id).unwrap(), account);
+    }
+    #[test]
     fn test_read_account() {
         let conn = establish_connection();
         let mut db = AccountDB {
             connection: Arc::new(Mutex::new(conn)),
+            clock: Arc::new(SystemClock),
         };
         // Create a new account record
         let created_account = db
@@ -216,6 +600,7 @@ mod tests {
         let conn = establish_connection();
         let mut db = AccountDB {
             connection: Arc::new(Mutex::new(conn)),
+            clock: Arc::new(SystemClock),
         };
         // Create a new account record
         let created_account = db
@@ -236,6 +621,7 @@ mod tests {
         let conn = establish_connection();
         let mut db = AccountDB {
             connection: Arc::new(Mutex::new(conn)),
+            clock: Arc::new(SystemClock),
         };
         let name = "Test Account";
         // Create a new account record
@@ -262,11 +648,209 @@ mod tests {
         let conn = establish_connection();
         let mut db = AccountDB {
             connection: Arc::new(Mutex::new(conn)),
+            clock: Arc::new(SystemClock),
         };
         db.for_name("Non existent account")
             .expect_err("Account should not be found");
     }
     #[test]
+    fn test_update_confirm_above_notional() {
+        let conn = establish_connection();
+        let mut db = AccountDB {
+            connection: Arc::new(Mutex::new(conn)),
+            clock: Arc::new(SystemClock),
+        };
+        let account = db
+            .create(
+                "Test Account",
+                "This is a test account",
+                Environment::Paper,
+                dec!(20),
+                dec!(80),
+            )
+            .expect("Error creating account");
+        assert_eq!(account.confirm_above_notional, None);
+
+        let updated = db
+            .update_confirm_above_notional(&account, Some(dec!(10000)))
+            .expect("Error updating account");
+        assert_eq!(updated.confirm_above_notional, Some(dec!(10000)));
+        assert_eq!(db.id(account.id).unwrap(), updated);
+
+        let updated = db
+            .update_confirm_above_notional(&account, None)
+            .expect("Error updating account");
+        assert_eq!(updated.confirm_above_notional, None);
+    }
+    #[test]
+    fn test_update_tick_size_policy() {
+        let conn = establish_connection();
+        let mut db = AccountDB {
+            connection: Arc::new(Mutex::new(conn)),
+            clock: Arc::new(SystemClock),
+        };
+        let account = db
+            .create(
+                "Test Account",
+                "This is a test account",
+                Environment::Paper,
+                dec!(20),
+                dec!(80),
+            )
+            .expect("Error creating account");
+        assert_eq!(account.tick_size_policy, TickSizePolicy::Reject);
+
+        let updated = db
+            .update_tick_size_policy(&account, TickSizePolicy::Round)
+            .expect("Error updating account");
+        assert_eq!(updated.tick_size_policy, TickSizePolicy::Round);
+        assert_eq!(db.id(account.id).unwrap(), updated);
+    }
+    #[test]
+    fn test_update_lot_method() {
+        let conn = establish_connection();
+        let mut db = AccountDB {
+            connection: Arc::new(Mutex::new(conn)),
+            clock: Arc::new(SystemClock),
+        };
+        let account = db
+            .create("Test Account", "", Environment::Paper, dec!(20), dec!(80))
+            .expect("Error creating account");
+        assert_eq!(account.lot_method, LotMethod::Fifo);
+
+        let updated = db
+            .update_lot_method(&account, LotMethod::Lifo)
+            .expect("Error updating account");
+        assert_eq!(updated.lot_method, LotMethod::Lifo);
+        assert_eq!(db.id(account.id).unwrap(), updated);
+    }
+    #[test]
+    fn test_update_fee_model() {
+        let conn = establish_connection();
+        let mut db = AccountDB {
+            connection: Arc::new(Mutex::new(conn)),
+            clock: Arc::new(SystemClock),
+        };
+        let account = db
+            .create("Test Account", "", Environment::Paper, dec!(20), dec!(80))
+            .expect("Error creating account");
+        assert_eq!(account.fee_model, FeeModel::None);
+
+        let updated = db
+            .update_fee_model(&account, FeeModel::PerShare(dec!(0.005)))
+            .expect("Error updating account");
+        assert_eq!(updated.fee_model, FeeModel::PerShare(dec!(0.005)));
+        assert_eq!(db.id(account.id).unwrap(), updated);
+
+        let updated = db
+            .update_fee_model(&account, FeeModel::None)
+            .expect("Error updating account");
+        assert_eq!(updated.fee_model, FeeModel::None);
+    }
+    #[test]
+    fn test_update_base_currency() {
+        let conn = establish_connection();
+        let mut db = AccountDB {
+            connection: Arc::new(Mutex::new(conn)),
+            clock: Arc::new(SystemClock),
+        };
+        let account = db
+            .create("Test Account", "", Environment::Paper, dec!(20), dec!(80))
+            .expect("Error creating account");
+        assert_eq!(account.base_currency, Currency::USD);
+
+        let updated = db
+            .update_base_currency(&account, &Currency::EUR)
+            .expect("Error updating account");
+        assert_eq!(updated.base_currency, Currency::EUR);
+        assert_eq!(db.id(account.id).unwrap(), updated);
+    }
+    #[test]
+    fn test_update_duplicate_trade_window() {
+        let conn = establish_connection();
+        let mut db = AccountDB {
+            connection: Arc::new(Mutex::new(conn)),
+            clock: Arc::new(SystemClock),
+        };
+        let account = db
+            .create("Test Account", "", Environment::Paper, dec!(20), dec!(80))
+            .expect("Error creating account");
+        assert_eq!(account.duplicate_trade_window_seconds, None);
+
+        let updated = db
+            .update_duplicate_trade_window(&account, Some(60))
+            .expect("Error updating account");
+        assert_eq!(updated.duplicate_trade_window_seconds, Some(60));
+        assert_eq!(db.id(account.id).unwrap(), updated);
+
+        let updated = db
+            .update_duplicate_trade_window(&account, None)
+            .expect("Error updating account");
+        assert_eq!(updated.duplicate_trade_window_seconds, None);
+    }
+    #[test]
+    fn test_create_live_account() {
+        let conn = establish_connection();
+        let mut db = AccountDB {
+            connection: Arc::new(Mutex::new(conn)),
+            clock: Arc::new(SystemClock),
+        };
+        let paper = db
+            .create(
+                "paper",
+                "validation",
+                Environment::Paper,
+                dec!(20),
+                dec!(80),
+            )
+            .expect("Error creating account");
+        let paper = db
+            .update_min_paper_trades_before_live(&paper, Some(30))
+            .expect("Error updating account");
+        assert_eq!(paper.min_paper_trades_before_live, Some(30));
+
+        let live = db
+            .create_live("live", "real money", dec!(25), dec!(75), &paper)
+            .expect("Error creating account");
+        assert_eq!(live.environment, Environment::Live);
+        assert_eq!(live.paper_account_id, Some(paper.id));
+        assert_eq!(live.min_paper_trades_before_live, None);
+        assert_eq!(db.id(live.id).unwrap(), live);
+    }
+    #[test]
+    fn test_create_account_with_hierarchy() {
+        let conn = establish_connection();
+        let mut db = AccountDB {
+            connection: Arc::new(Mutex::new(conn)),
+            clock: Arc::new(SystemClock),
+        };
+        let parent = db
+            .create("parent", "main", Environment::Paper, dec!(20), dec!(80))
+            .expect("Error creating account");
+        assert_eq!(parent.account_type, AccountType::Primary);
+        assert_eq!(parent.parent_account_id, None);
+
+        let hedge = db
+            .create_with_hierarchy(
+                "hedge",
+                "hedges the parent",
+                Environment::Paper,
+                dec!(20),
+                dec!(80),
+                AccountType::Hedge,
+                Some(parent.id),
+            )
+            .expect("Error creating account");
+        assert_eq!(hedge.account_type, AccountType::Hedge);
+        assert_eq!(hedge.parent_account_id, Some(parent.id));
+        assert_eq!(db.id(hedge.id).unwrap(), hedge);
+
+        assert_eq!(db.children_of(parent.id).unwrap(), vec![hedge.clone()]);
+        assert!(db.children_of(hedge.id).unwrap().is_empty());
+        assert_eq!(db.parent_of(hedge.id).unwrap(), Some(parent.clone()));
+        assert_eq!(db.parent_of(parent.id).unwrap(), None);
+    }
+    #[test]
     fn test_read_all_accounts() {
         let db = create_factory(establish_connection());
         let created_accounts = vec![
@@ -1,7 +1,7 @@
 use crate::schema::logs;
-use chrono::{NaiveDateTime, Utc};
+use chrono::NaiveDateTime;
 use diesel::prelude::*;
-use model::{BrokerLog, ReadBrokerLogsDB, Trade, WriteBrokerLogsDB};
+use model::{BrokerLog, Clock, ReadBrokerLogsDB, Trade, WriteBrokerLogsDB};
 use std::error::Error;
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -10,12 +10,13 @@ use uuid::Uuid;
 
 pub struct BrokerLogDB {
     pub connection: Arc<Mutex<SqliteConnection>>,
+    pub clock: Arc<dyn Clock>,
 }
 
 impl WriteBrokerLogsDB for BrokerLogDB {
     fn create_log(&mut self, log: &str, trade: &Trade) -> Result<BrokerLog, Box<dyn Error>> {
         let uuid = Uuid::new_v4().to_string();
-        let now = Utc::now().naive_utc();
+        let now = self.clock.now();
 
         let new_account = NewBrokerLogs {
             id: uuid,
@@ -99,6 +100,7 @@ struct NewBrokerLogs {
 mod tests {
     use super::*;
     use diesel_migrations::*;
+    use model::SystemClock;
     pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
     // Declare a test database connection
     fn establish_connection() -> SqliteConnection {
@@ -114,6 +116,7 @@ mod tests {
         let conn: SqliteConnection = establish_connection();
         let mut db = BrokerLogDB {
             connection: Arc::new(Mutex::new(conn)),
+            clock: Arc::new(SystemClock),
         };
 
         let trade = Trade::default();
@@ -132,6 +135,7 @@ mod tests {
         let conn: SqliteConnection = establish_connection();
         let mut db = BrokerLogDB {
             connection: Arc::new(Mutex::new(conn)),
+            clock: Arc::new(SystemClock),
         };
 
         let trade = Trade::default();
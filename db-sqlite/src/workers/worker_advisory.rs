@@ -0,0 +1,188 @@
+use crate::schema::advisory_thresholds;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use model::AdvisoryThresholds;
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+use tracing::error;
+use uuid::Uuid;
+
+pub struct WorkerAdvisory;
+
+impl WorkerAdvisory {
+    pub fn create_thresholds(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        account_id: Uuid,
+        max_risk_per_trade_percent: Decimal,
+        max_position_concentration_percent: Decimal,
+    ) -> Result<AdvisoryThresholds, Box<dyn Error>> {
+        let new_thresholds = NewAdvisoryThresholds {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            account_id: account_id.to_string(),
+            max_risk_per_trade_percent: max_risk_per_trade_percent.to_string(),
+            max_position_concentration_percent: max_position_concentration_percent.to_string(),
+        };
+
+        let thresholds = diesel::insert_into(advisory_thresholds::table)
+            .values(&new_thresholds)
+            .get_result::<AdvisoryThresholdsSQLite>(connection)
+            .map(|thresholds| thresholds.domain_model())
+            .map_err(|error| {
+                error!("Error creating advisory thresholds: {:?}", error);
+                error
+            })?;
+        Ok(thresholds)
+    }
+
+    pub fn read_thresholds(
+        connection: &mut SqliteConnection,
+        account_id: Uuid,
+    ) -> Result<Option<AdvisoryThresholds>, Box<dyn Error>> {
+        let thresholds = advisory_thresholds::table
+            .filter(advisory_thresholds::deleted_at.is_null())
+            .filter(advisory_thresholds::account_id.eq(account_id.to_string()))
+            .first::<AdvisoryThresholdsSQLite>(connection)
+            .optional()
+            .map(|thresholds| thresholds.map(|thresholds| thresholds.domain_model()))
+            .map_err(|error| {
+                error!("Error reading advisory thresholds: {:?}", error);
+                error
+            })?;
+        Ok(thresholds)
+    }
+
+    pub fn update_thresholds(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        thresholds: &AdvisoryThresholds,
+        max_risk_per_trade_percent: Decimal,
+        max_position_concentration_percent: Decimal,
+    ) -> Result<AdvisoryThresholds, Box<dyn Error>> {
+        let thresholds = diesel::update(advisory_thresholds::table)
+            .filter(advisory_thresholds::id.eq(thresholds.id.to_string()))
+            .set((
+                advisory_thresholds::updated_at.eq(now),
+                advisory_thresholds::max_risk_per_trade_percent
+                    .eq(max_risk_per_trade_percent.to_string()),
+                advisory_thresholds::max_position_concentration_percent
+                    .eq(max_position_concentration_percent.to_string()),
+            ))
+            .get_result::<AdvisoryThresholdsSQLite>(connection)
+            .map(|thresholds| thresholds.domain_model())
+            .map_err(|error| {
+                error!("Error updating advisory thresholds: {:?}", error);
+                error
+            })?;
+        Ok(thresholds)
+    }
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Insertable)]
+#[diesel(table_name = advisory_thresholds)]
+#[diesel(treat_none_as_null = true)]
+struct AdvisoryThresholdsSQLite {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    account_id: String,
+    max_risk_per_trade_percent: String,
+    max_position_concentration_percent: String,
+}
+
+impl AdvisoryThresholdsSQLite {
+    fn domain_model(self) -> AdvisoryThresholds {
+        AdvisoryThresholds {
+            id: Uuid::parse_str(&self.id).unwrap(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            deleted_at: self.deleted_at,
+            account_id: Uuid::parse_str(&self.account_id).unwrap(),
+            max_risk_per_trade_percent: Decimal::from_str(&self.max_risk_per_trade_percent)
+                .unwrap(),
+            max_position_concentration_percent: Decimal::from_str(
+                &self.max_position_concentration_percent,
+            )
+            .unwrap(),
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = advisory_thresholds)]
+#[diesel(treat_none_as_null = true)]
+struct NewAdvisoryThresholds {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    account_id: String,
+    max_risk_per_trade_percent: String,
+    max_position_concentration_percent: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use diesel_migrations::*;
+    use rust_decimal_macros::dec;
+
+    pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+    // Declare a test database connection
+    fn establish_connection() -> SqliteConnection {
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        // This will run the necessary migrations.
+        connection.run_pending_migrations(MIGRATIONS).unwrap();
+        connection.begin_test_transaction().unwrap();
+        connection
+    }
+
+    #[test]
+    fn test_create_and_update_thresholds() {
+        let mut conn = establish_connection();
+        let account_id = Uuid::new_v4();
+
+        let thresholds = WorkerAdvisory::create_thresholds(
+            &mut conn,
+            Utc::now().naive_utc(),
+            account_id,
+            dec!(1.5),
+            dec!(30),
+        )
+        .expect("Error creating advisory thresholds");
+        assert_eq!(thresholds.account_id, account_id);
+        assert_eq!(thresholds.max_risk_per_trade_percent, dec!(1.5));
+        assert_eq!(thresholds.max_position_concentration_percent, dec!(30));
+
+        let updated = WorkerAdvisory::update_thresholds(
+            &mut conn,
+            Utc::now().naive_utc(),
+            &thresholds,
+            dec!(1),
+            dec!(20),
+        )
+        .unwrap();
+        assert_eq!(updated.max_risk_per_trade_percent, dec!(1));
+        assert_eq!(updated.max_position_concentration_percent, dec!(20));
+        assert_eq!(
+            WorkerAdvisory::read_thresholds(&mut conn, account_id).unwrap(),
+            Some(updated)
+        );
+    }
+
+    #[test]
+    fn test_read_thresholds_not_configured() {
+        let mut conn = establish_connection();
+        assert_eq!(
+            WorkerAdvisory::read_thresholds(&mut conn, Uuid::new_v4()).unwrap(),
+            None
+        );
+    }
+}
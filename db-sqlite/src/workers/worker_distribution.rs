@@ -0,0 +1,331 @@
+use crate::schema::{distribution_history, distribution_rules};
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use model::{DistributionHistory, DistributionRules};
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+use tracing::error;
+use uuid::Uuid;
+
+pub struct WorkerDistribution;
+
+impl WorkerDistribution {
+    pub fn create_rules(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        account_id: Uuid,
+        earnings_percent: Decimal,
+        tax_percent: Decimal,
+        reinvestment_percent: Decimal,
+    ) -> Result<DistributionRules, Box<dyn Error>> {
+        let new_rules = NewDistributionRules {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            account_id: account_id.to_string(),
+            earnings_percent: earnings_percent.to_string(),
+            tax_percent: tax_percent.to_string(),
+            reinvestment_percent: reinvestment_percent.to_string(),
+        };
+
+        let rules = diesel::insert_into(distribution_rules::table)
+            .values(&new_rules)
+            .get_result::<DistributionRulesSQLite>(connection)
+            .map(|rules| rules.domain_model())
+            .map_err(|error| {
+                error!("Error creating distribution rules: {:?}", error);
+                error
+            })?;
+        Ok(rules)
+    }
+
+    pub fn read_rules(
+        connection: &mut SqliteConnection,
+        account_id: Uuid,
+    ) -> Result<DistributionRules, Box<dyn Error>> {
+        let rules = distribution_rules::table
+            .filter(distribution_rules::deleted_at.is_null())
+            .filter(distribution_rules::account_id.eq(account_id.to_string()))
+            .first::<DistributionRulesSQLite>(connection)
+            .map(|rules| rules.domain_model())
+            .map_err(|error| {
+                error!("Error reading distribution rules: {:?}", error);
+                error
+            })?;
+        Ok(rules)
+    }
+
+    pub fn update_rules(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        rules: &DistributionRules,
+        earnings_percent: Decimal,
+        tax_percent: Decimal,
+        reinvestment_percent: Decimal,
+    ) -> Result<DistributionRules, Box<dyn Error>> {
+        let rules = diesel::update(distribution_rules::table)
+            .filter(distribution_rules::id.eq(rules.id.to_string()))
+            .set((
+                distribution_rules::updated_at.eq(now),
+                distribution_rules::earnings_percent.eq(earnings_percent.to_string()),
+                distribution_rules::tax_percent.eq(tax_percent.to_string()),
+                distribution_rules::reinvestment_percent.eq(reinvestment_percent.to_string()),
+            ))
+            .get_result::<DistributionRulesSQLite>(connection)
+            .map(|rules| rules.domain_model())
+            .map_err(|error| {
+                error!("Error updating distribution rules: {:?}", error);
+                error
+            })?;
+        Ok(rules)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_history(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        account_id: Uuid,
+        trade_id: Uuid,
+        original_amount: Decimal,
+        earnings_amount: Decimal,
+        tax_amount: Decimal,
+        reinvestment_amount: Decimal,
+    ) -> Result<DistributionHistory, Box<dyn Error>> {
+        let new_history = NewDistributionHistory {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            account_id: account_id.to_string(),
+            trade_id: trade_id.to_string(),
+            original_amount: original_amount.to_string(),
+            earnings_amount: earnings_amount.to_string(),
+            tax_amount: tax_amount.to_string(),
+            reinvestment_amount: reinvestment_amount.to_string(),
+            distributed_at: now,
+        };
+
+        let history = diesel::insert_into(distribution_history::table)
+            .values(&new_history)
+            .get_result::<DistributionHistorySQLite>(connection)
+            .map(|history| history.domain_model())
+            .map_err(|error| {
+                error!("Error creating distribution history: {:?}", error);
+                error
+            })?;
+        Ok(history)
+    }
+
+    pub fn read_history_for_account(
+        connection: &mut SqliteConnection,
+        account_id: Uuid,
+    ) -> Result<Vec<DistributionHistory>, Box<dyn Error>> {
+        let history = distribution_history::table
+            .filter(distribution_history::deleted_at.is_null())
+            .filter(distribution_history::account_id.eq(account_id.to_string()))
+            .order(distribution_history::distributed_at.asc())
+            .load::<DistributionHistorySQLite>(connection)
+            .map(|history: Vec<DistributionHistorySQLite>| {
+                history
+                    .into_iter()
+                    .map(|entry| entry.domain_model())
+                    .collect()
+            })
+            .map_err(|error| {
+                error!("Error reading distribution history: {:?}", error);
+                error
+            })?;
+        Ok(history)
+    }
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Insertable)]
+#[diesel(table_name = distribution_rules)]
+#[diesel(treat_none_as_null = true)]
+struct DistributionRulesSQLite {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    account_id: String,
+    earnings_percent: String,
+    tax_percent: String,
+    reinvestment_percent: String,
+}
+
+impl DistributionRulesSQLite {
+    fn domain_model(self) -> DistributionRules {
+        DistributionRules {
+            id: Uuid::parse_str(&self.id).unwrap(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            deleted_at: self.deleted_at,
+            account_id: Uuid::parse_str(&self.account_id).unwrap(),
+            earnings_percent: Decimal::from_str(&self.earnings_percent).unwrap(),
+            tax_percent: Decimal::from_str(&self.tax_percent).unwrap(),
+            reinvestment_percent: Decimal::from_str(&self.reinvestment_percent).unwrap(),
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = distribution_rules)]
+#[diesel(treat_none_as_null = true)]
+struct NewDistributionRules {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    account_id: String,
+    earnings_percent: String,
+    tax_percent: String,
+    reinvestment_percent: String,
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Insertable)]
+#[diesel(table_name = distribution_history)]
+#[diesel(treat_none_as_null = true)]
+struct DistributionHistorySQLite {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    account_id: String,
+    trade_id: String,
+    original_amount: String,
+    earnings_amount: String,
+    tax_amount: String,
+    reinvestment_amount: String,
+    distributed_at: NaiveDateTime,
+}
+
+impl DistributionHistorySQLite {
+    fn domain_model(self) -> DistributionHistory {
+        DistributionHistory {
+            id: Uuid::parse_str(&self.id).unwrap(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            deleted_at: self.deleted_at,
+            account_id: Uuid::parse_str(&self.account_id).unwrap(),
+            trade_id: Uuid::parse_str(&self.trade_id).unwrap(),
+            original_amount: Decimal::from_str(&self.original_amount).unwrap(),
+            earnings_amount: Decimal::from_str(&self.earnings_amount).unwrap(),
+            tax_amount: Decimal::from_str(&self.tax_amount).unwrap(),
+            reinvestment_amount: Decimal::from_str(&self.reinvestment_amount).unwrap(),
+            distributed_at: self.distributed_at,
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = distribution_history)]
+#[diesel(treat_none_as_null = true)]
+struct NewDistributionHistory {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    account_id: String,
+    trade_id: String,
+    original_amount: String,
+    earnings_amount: String,
+    tax_amount: String,
+    reinvestment_amount: String,
+    distributed_at: NaiveDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use diesel_migrations::*;
+    use rust_decimal_macros::dec;
+
+    pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+    // Declare a test database connection
+    fn establish_connection() -> SqliteConnection {
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        // This will run the necessary migrations.
+        connection.run_pending_migrations(MIGRATIONS).unwrap();
+        connection.begin_test_transaction().unwrap();
+        connection
+    }
+
+    #[test]
+    fn test_create_and_update_rules() {
+        let mut conn = establish_connection();
+        let account_id = Uuid::new_v4();
+
+        let rules = WorkerDistribution::create_rules(
+            &mut conn,
+            Utc::now().naive_utc(),
+            account_id,
+            dec!(0.3),
+            dec!(0.2),
+            dec!(0.5),
+        )
+        .expect("Error creating distribution rules");
+        assert_eq!(rules.account_id, account_id);
+        assert_eq!(rules.earnings_percent, dec!(0.3));
+        assert_eq!(rules.tax_percent, dec!(0.2));
+        assert_eq!(rules.reinvestment_percent, dec!(0.5));
+
+        let updated = WorkerDistribution::update_rules(
+            &mut conn,
+            Utc::now().naive_utc(),
+            &rules,
+            dec!(0),
+            dec!(0.25),
+            dec!(0.75),
+        )
+        .unwrap();
+        assert_eq!(updated.tax_percent, dec!(0.25));
+        assert_eq!(
+            WorkerDistribution::read_rules(&mut conn, account_id).unwrap(),
+            updated
+        );
+    }
+
+    #[test]
+    fn test_read_rules_not_found() {
+        let mut conn = establish_connection();
+        assert!(WorkerDistribution::read_rules(&mut conn, Uuid::new_v4()).is_err());
+    }
+
+    #[test]
+    fn test_create_and_read_history() {
+        let mut conn = establish_connection();
+        let account_id = Uuid::new_v4();
+
+        let history = WorkerDistribution::create_history(
+            &mut conn,
+            Utc::now().naive_utc(),
+            account_id,
+            Uuid::new_v4(),
+            dec!(1000),
+            dec!(300),
+            dec!(200),
+            dec!(500),
+        )
+        .expect("Error creating distribution history");
+        WorkerDistribution::create_history(
+            &mut conn,
+            Utc::now().naive_utc(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            dec!(1000),
+            dec!(300),
+            dec!(200),
+            dec!(500),
+        )
+        .unwrap();
+
+        assert_eq!(
+            WorkerDistribution::read_history_for_account(&mut conn, account_id).unwrap(),
+            vec![history]
+        );
+    }
+}
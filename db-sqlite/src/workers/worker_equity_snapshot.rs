@@ -0,0 +1,228 @@
+use crate::schema::equity_snapshots;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use model::{Currency, EquitySnapshot};
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+use tracing::error;
+use uuid::Uuid;
+
+pub struct WorkerEquitySnapshot;
+
+impl WorkerEquitySnapshot {
+    pub fn create(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        account_id: Uuid,
+        currency: &Currency,
+        timestamp: NaiveDateTime,
+        balance: Decimal,
+    ) -> Result<EquitySnapshot, Box<dyn Error>> {
+        let new_snapshot = NewEquitySnapshot {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            account_id: account_id.to_string(),
+            currency: currency.to_string(),
+            timestamp,
+            balance: balance.to_string(),
+        };
+
+        let snapshot = diesel::insert_into(equity_snapshots::table)
+            .values(&new_snapshot)
+            .get_result::<EquitySnapshotSQLite>(connection)
+            .map(|snapshot| snapshot.domain_model())
+            .map_err(|error| {
+                error!("Error creating equity snapshot: {:?}", error);
+                error
+            })?;
+        Ok(snapshot)
+    }
+
+    pub fn delete_all(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        account_id: Uuid,
+        currency: &Currency,
+    ) -> Result<(), Box<dyn Error>> {
+        diesel::update(equity_snapshots::table)
+            .filter(equity_snapshots::deleted_at.is_null())
+            .filter(equity_snapshots::account_id.eq(account_id.to_string()))
+            .filter(equity_snapshots::currency.eq(currency.to_string()))
+            .set((
+                equity_snapshots::deleted_at.eq(now),
+                equity_snapshots::updated_at.eq(now),
+            ))
+            .execute(connection)
+            .map_err(|error| {
+                error!("Error deleting equity snapshots: {:?}", error);
+                error
+            })?;
+        Ok(())
+    }
+
+    pub fn read_all(
+        connection: &mut SqliteConnection,
+        account_id: Uuid,
+        currency: &Currency,
+    ) -> Result<Vec<EquitySnapshot>, Box<dyn Error>> {
+        let snapshots = equity_snapshots::table
+            .filter(equity_snapshots::deleted_at.is_null())
+            .filter(equity_snapshots::account_id.eq(account_id.to_string()))
+            .filter(equity_snapshots::currency.eq(currency.to_string()))
+            .order(equity_snapshots::timestamp.asc())
+            .load::<EquitySnapshotSQLite>(connection)
+            .map(|snapshots: Vec<EquitySnapshotSQLite>| {
+                snapshots
+                    .into_iter()
+                    .map(|snapshot| snapshot.domain_model())
+                    .collect()
+            })
+            .map_err(|error| {
+                error!("Error reading equity snapshots: {:?}", error);
+                error
+            })?;
+        Ok(snapshots)
+    }
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Insertable)]
+#[diesel(table_name = equity_snapshots)]
+#[diesel(treat_none_as_null = true)]
+struct EquitySnapshotSQLite {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    account_id: String,
+    currency: String,
+    timestamp: NaiveDateTime,
+    balance: String,
+}
+
+impl EquitySnapshotSQLite {
+    fn domain_model(self) -> EquitySnapshot {
+        EquitySnapshot {
+            id: Uuid::parse_str(&self.id).unwrap(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            deleted_at: self.deleted_at,
+            account_id: Uuid::parse_str(&self.account_id).unwrap(),
+            currency: Currency::from_str(&self.currency).unwrap(),
+            timestamp: self.timestamp,
+            balance: Decimal::from_str(&self.balance).unwrap(),
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = equity_snapshots)]
+#[diesel(treat_none_as_null = true)]
+struct NewEquitySnapshot {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    account_id: String,
+    currency: String,
+    timestamp: NaiveDateTime,
+    balance: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use chrono::Utc;
+    use diesel_migrations::*;
+    use rust_decimal_macros::dec;
+
+    pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+    // Declare a test database connection
+    fn establish_connection() -> SqliteConnection {
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        // This will run the necessary migrations.
+        connection.run_pending_migrations(MIGRATIONS).unwrap();
+        connection.begin_test_transaction().unwrap();
+        connection
+    }
+
+    #[test]
+    fn test_create_equity_snapshot() {
+        let mut conn = establish_connection();
+        let now = Utc::now().naive_utc();
+        let account_id = Uuid::new_v4();
+
+        let snapshot = WorkerEquitySnapshot::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            account_id,
+            &Currency::USD,
+            now,
+            dec!(1000.5),
+        )
+        .expect("Error creating equity snapshot");
+
+        assert_eq!(snapshot.account_id, account_id);
+        assert_eq!(snapshot.currency, Currency::USD);
+        assert_eq!(snapshot.timestamp, now);
+        assert_eq!(snapshot.balance, dec!(1000.5));
+        assert_eq!(snapshot.deleted_at, None);
+    }
+
+    #[test]
+    fn test_read_and_delete_equity_snapshots() {
+        let mut conn = establish_connection();
+        let now = Utc::now().naive_utc();
+        let account_id = Uuid::new_v4();
+
+        let second = WorkerEquitySnapshot::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            account_id,
+            &Currency::USD,
+            now + Duration::days(1),
+            dec!(900),
+        )
+        .unwrap();
+        let first = WorkerEquitySnapshot::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            account_id,
+            &Currency::USD,
+            now,
+            dec!(1000),
+        )
+        .unwrap();
+        WorkerEquitySnapshot::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            account_id,
+            &Currency::EUR,
+            now,
+            dec!(10),
+        )
+        .unwrap();
+
+        let snapshots = WorkerEquitySnapshot::read_all(&mut conn, account_id, &Currency::USD)
+            .expect("Error reading equity snapshots");
+        assert_eq!(snapshots, vec![first, second]);
+
+        WorkerEquitySnapshot::delete_all(
+            &mut conn,
+            Utc::now().naive_utc(),
+            account_id,
+            &Currency::USD,
+        )
+        .unwrap();
+        let snapshots =
+            WorkerEquitySnapshot::read_all(&mut conn, account_id, &Currency::USD).unwrap();
+        assert!(snapshots.is_empty());
+        let snapshots =
+            WorkerEquitySnapshot::read_all(&mut conn, account_id, &Currency::EUR).unwrap();
+        assert_eq!(snapshots.len(), 1);
+    }
+}
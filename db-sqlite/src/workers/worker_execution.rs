@@ -0,0 +1,260 @@
+use crate::schema::executions;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use model::{DraftExecution, Execution, ExecutionSide, ExecutionSource};
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+use tracing::error;
+use uuid::Uuid;
+
+pub struct WorkerExecution;
+
+impl WorkerExecution {
+    pub fn create(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        draft: &DraftExecution,
+    ) -> Result<Execution, Box<dyn Error>> {
+        let new_execution = NewExecution {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            broker_execution_id: draft.broker_execution_id.clone(),
+            trade_id: draft.trade_id.to_string(),
+            order_id: draft.order_id.to_string(),
+            side: draft.side.to_string(),
+            price: draft.price.to_string(),
+            quantity: draft.quantity as i64,
+            executed_at: draft.executed_at,
+            source: draft.source.to_string(),
+            idempotency_key: draft.idempotency_key(),
+        };
+
+        let execution = diesel::insert_into(executions::table)
+            .values(&new_execution)
+            .get_result::<ExecutionSQLite>(connection)
+            .map(|execution| execution.domain_model())
+            .map_err(|error| {
+                error!("Error creating execution: {:?}", error);
+                error
+            })?;
+        Ok(execution)
+    }
+
+    pub fn read_all_for_trade(
+        connection: &mut SqliteConnection,
+        trade_id: Uuid,
+    ) -> Result<Vec<Execution>, Box<dyn Error>> {
+        let executions = executions::table
+            .filter(executions::deleted_at.is_null())
+            .filter(executions::trade_id.eq(trade_id.to_string()))
+            .order(executions::executed_at.asc())
+            .load::<ExecutionSQLite>(connection)
+            .map(|executions: Vec<ExecutionSQLite>| {
+                executions
+                    .into_iter()
+                    .map(|execution| execution.domain_model())
+                    .collect()
+            })
+            .map_err(|error| {
+                error!("Error reading executions: {:?}", error);
+                error
+            })?;
+        Ok(executions)
+    }
+
+    pub fn read_for_key(
+        connection: &mut SqliteConnection,
+        idempotency_key: &str,
+    ) -> Result<Option<Execution>, Box<dyn Error>> {
+        let execution = executions::table
+            .filter(executions::deleted_at.is_null())
+            .filter(executions::idempotency_key.eq(idempotency_key))
+            .first::<ExecutionSQLite>(connection)
+            .optional()
+            .map(|execution| execution.map(|execution| execution.domain_model()))
+            .map_err(|error| {
+                error!("Error reading execution: {:?}", error);
+                error
+            })?;
+        Ok(execution)
+    }
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Insertable)]
+#[diesel(table_name = executions)]
+#[diesel(treat_none_as_null = true)]
+struct ExecutionSQLite {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    broker_execution_id: Option<String>,
+    trade_id: String,
+    order_id: String,
+    side: String,
+    price: String,
+    quantity: i64,
+    executed_at: NaiveDateTime,
+    source: String,
+    idempotency_key: String,
+}
+
+impl ExecutionSQLite {
+    fn domain_model(self) -> Execution {
+        Execution {
+            id: Uuid::parse_str(&self.id).unwrap(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            deleted_at: self.deleted_at,
+            broker_execution_id: self.broker_execution_id,
+            trade_id: Uuid::parse_str(&self.trade_id).unwrap(),
+            order_id: Uuid::parse_str(&self.order_id).unwrap(),
+            side: ExecutionSide::from_str(&self.side).unwrap(),
+            price: Decimal::from_str(&self.price).unwrap(),
+            quantity: self.quantity as u64,
+            executed_at: self.executed_at,
+            source: ExecutionSource::from_str(&self.source).unwrap(),
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = executions)]
+#[diesel(treat_none_as_null = true)]
+struct NewExecution {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    broker_execution_id: Option<String>,
+    trade_id: String,
+    order_id: String,
+    side: String,
+    price: String,
+    quantity: i64,
+    executed_at: NaiveDateTime,
+    source: String,
+    idempotency_key: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use chrono::Utc;
+    use diesel_migrations::*;
+    use rust_decimal_macros::dec;
+
+    pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+    // Declare a test database connection
+    fn establish_connection() -> SqliteConnection {
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        // This will run the necessary migrations.
+        connection.run_pending_migrations(MIGRATIONS).unwrap();
+        connection.begin_test_transaction().unwrap();
+        connection
+    }
+
+    fn draft(trade_id: Uuid, executed_at: NaiveDateTime) -> DraftExecution {
+        DraftExecution {
+            broker_execution_id: Some(format!("20230801000000000::{}", Uuid::new_v4())),
+            trade_id,
+            order_id: Uuid::new_v4(),
+            side: ExecutionSide::Buy,
+            price: dec!(40.1),
+            quantity: 10,
+            executed_at,
+            source: ExecutionSource::Broker,
+        }
+    }
+
+    #[test]
+    fn test_create_execution() {
+        let mut conn = establish_connection();
+        let now = Utc::now().naive_utc();
+        let draft = draft(Uuid::new_v4(), now);
+
+        let execution = WorkerExecution::create(&mut conn, Utc::now().naive_utc(), &draft)
+            .expect("Error creating");
+
+        assert_eq!(execution.broker_execution_id, draft.broker_execution_id);
+        assert_eq!(execution.trade_id, draft.trade_id);
+        assert_eq!(execution.order_id, draft.order_id);
+        assert_eq!(execution.side, ExecutionSide::Buy);
+        assert_eq!(execution.price, dec!(40.1));
+        assert_eq!(execution.quantity, 10);
+        assert_eq!(execution.executed_at, now);
+        assert_eq!(execution.source, ExecutionSource::Broker);
+        assert_eq!(execution.deleted_at, None);
+    }
+
+    #[test]
+    fn test_read_executions_for_trade() {
+        let mut conn = establish_connection();
+        let now = Utc::now().naive_utc();
+        let trade_id = Uuid::new_v4();
+
+        let second = WorkerExecution::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            &draft(trade_id, now + Duration::minutes(1)),
+        )
+        .unwrap();
+        let first =
+            WorkerExecution::create(&mut conn, Utc::now().naive_utc(), &draft(trade_id, now))
+                .unwrap();
+        WorkerExecution::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            &draft(Uuid::new_v4(), now),
+        )
+        .unwrap();
+
+        let executions = WorkerExecution::read_all_for_trade(&mut conn, trade_id).unwrap();
+
+        assert_eq!(executions, vec![first, second]);
+    }
+
+    #[test]
+    fn test_read_execution_for_key() {
+        let mut conn = establish_connection();
+        let draft = draft(Uuid::new_v4(), Utc::now().naive_utc());
+        let execution = WorkerExecution::create(&mut conn, Utc::now().naive_utc(), &draft).unwrap();
+
+        assert_eq!(
+            WorkerExecution::read_for_key(&mut conn, &draft.idempotency_key()).unwrap(),
+            Some(execution)
+        );
+        assert_eq!(
+            WorkerExecution::read_for_key(&mut conn, "broker:unknown").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_create_execution_twice() {
+        let mut conn = establish_connection();
+        let now = Utc::now().naive_utc();
+        let with_broker_id = draft(Uuid::new_v4(), now);
+        let without_broker_id = DraftExecution {
+            broker_execution_id: None,
+            ..draft(Uuid::new_v4(), now)
+        };
+
+        for draft in [with_broker_id, without_broker_id] {
+            WorkerExecution::create(&mut conn, Utc::now().naive_utc(), &draft).unwrap();
+            WorkerExecution::create(&mut conn, Utc::now().naive_utc(), &draft)
+                .expect_err("The same fill can not be written twice");
+            assert_eq!(
+                WorkerExecution::read_all_for_trade(&mut conn, draft.trade_id)
+                    .unwrap()
+                    .len(),
+                1
+            );
+        }
+    }
+}
@@ -0,0 +1,382 @@
+use crate::schema::{level_changes, levels};
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use model::{Level, LevelAdjustmentRules, LevelChange, LevelTrigger};
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+use tracing::error;
+use uuid::Uuid;
+
+pub struct WorkerLevel;
+
+impl WorkerLevel {
+    pub fn create(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        account_id: Uuid,
+        current_level: u8,
+    ) -> Result<Level, Box<dyn Error>> {
+        let new_level = NewLevel {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            account_id: account_id.to_string(),
+            current_level: current_level as i32,
+            monthly_loss_downgrade_pct: LevelAdjustmentRules::default()
+                .monthly_loss_downgrade_pct
+                .to_string(),
+        };
+
+        let level = diesel::insert_into(levels::table)
+            .values(&new_level)
+            .get_result::<LevelSQLite>(connection)
+            .map(|level| level.domain_model())
+            .map_err(|error| {
+                error!("Error creating level: {:?}", error);
+                error
+            })?;
+        Ok(level)
+    }
+
+    pub fn read_for_account(
+        connection: &mut SqliteConnection,
+        account_id: Uuid,
+    ) -> Result<Level, Box<dyn Error>> {
+        let level = WorkerLevel::read_sqlite(connection, account_id)?.domain_model();
+        Ok(level)
+    }
+
+    pub fn update_level(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        level: &Level,
+        new_level: u8,
+    ) -> Result<Level, Box<dyn Error>> {
+        let level = diesel::update(levels::table)
+            .filter(levels::id.eq(level.id.to_string()))
+            .set((
+                levels::updated_at.eq(now),
+                levels::current_level.eq(new_level as i32),
+            ))
+            .get_result::<LevelSQLite>(connection)
+            .map(|level| level.domain_model())
+            .map_err(|error| {
+                error!("Error updating level: {:?}", error);
+                error
+            })?;
+        Ok(level)
+    }
+
+    pub fn read_adjustment_rules(
+        connection: &mut SqliteConnection,
+        account_id: Uuid,
+    ) -> Result<LevelAdjustmentRules, Box<dyn Error>> {
+        let rules = WorkerLevel::read_sqlite(connection, account_id)?.adjustment_rules();
+        Ok(rules)
+    }
+
+    pub fn update_adjustment_rules(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        level: &Level,
+        rules: &LevelAdjustmentRules,
+    ) -> Result<LevelAdjustmentRules, Box<dyn Error>> {
+        let rules = diesel::update(levels::table)
+            .filter(levels::id.eq(level.id.to_string()))
+            .set((
+                levels::updated_at.eq(now),
+                levels::monthly_loss_downgrade_pct.eq(rules.monthly_loss_downgrade_pct.to_string()),
+            ))
+            .get_result::<LevelSQLite>(connection)
+            .map(|level| level.adjustment_rules())
+            .map_err(|error| {
+                error!("Error updating level adjustment rules: {:?}", error);
+                error
+            })?;
+        Ok(rules)
+    }
+
+    pub fn create_change(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        account_id: Uuid,
+        old_level: u8,
+        new_level: u8,
+        reason: &str,
+        trigger: &LevelTrigger,
+    ) -> Result<LevelChange, Box<dyn Error>> {
+        let new_change = NewLevelChange {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            account_id: account_id.to_string(),
+            old_level: old_level as i32,
+            new_level: new_level as i32,
+            reason: reason.to_string(),
+            trigger: trigger.to_string(),
+            changed_at: now,
+        };
+
+        let change = diesel::insert_into(level_changes::table)
+            .values(&new_change)
+            .get_result::<LevelChangeSQLite>(connection)
+            .map(|change| change.domain_model())
+            .map_err(|error| {
+                error!("Error creating level change: {:?}", error);
+                error
+            })?;
+        Ok(change)
+    }
+
+    pub fn read_changes_for_account(
+        connection: &mut SqliteConnection,
+        account_id: Uuid,
+    ) -> Result<Vec<LevelChange>, Box<dyn Error>> {
+        let changes = level_changes::table
+            .filter(level_changes::deleted_at.is_null())
+            .filter(level_changes::account_id.eq(account_id.to_string()))
+            .order(level_changes::changed_at.asc())
+            .load::<LevelChangeSQLite>(connection)
+            .map(|changes: Vec<LevelChangeSQLite>| {
+                changes
+                    .into_iter()
+                    .map(|change| change.domain_model())
+                    .collect()
+            })
+            .map_err(|error| {
+                error!("Error reading level changes: {:?}", error);
+                error
+            })?;
+        Ok(changes)
+    }
+
+    fn read_sqlite(
+        connection: &mut SqliteConnection,
+        account_id: Uuid,
+    ) -> Result<LevelSQLite, Box<dyn Error>> {
+        let level = levels::table
+            .filter(levels::deleted_at.is_null())
+            .filter(levels::account_id.eq(account_id.to_string()))
+            .first::<LevelSQLite>(connection)
+            .map_err(|error| {
+                error!("Error reading level: {:?}", error);
+                error
+            })?;
+        Ok(level)
+    }
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Insertable)]
+#[diesel(table_name = levels)]
+#[diesel(treat_none_as_null = true)]
+struct LevelSQLite {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    account_id: String,
+    current_level: i32,
+    monthly_loss_downgrade_pct: String,
+}
+
+impl LevelSQLite {
+    fn domain_model(self) -> Level {
+        Level {
+            id: Uuid::parse_str(&self.id).unwrap(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            deleted_at: self.deleted_at,
+            account_id: Uuid::parse_str(&self.account_id).unwrap(),
+            current_level: self.current_level as u8,
+        }
+    }
+
+    fn adjustment_rules(self) -> LevelAdjustmentRules {
+        LevelAdjustmentRules {
+            monthly_loss_downgrade_pct: Decimal::from_str(&self.monthly_loss_downgrade_pct)
+                .unwrap(),
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = levels)]
+#[diesel(treat_none_as_null = true)]
+struct NewLevel {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    account_id: String,
+    current_level: i32,
+    monthly_loss_downgrade_pct: String,
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Insertable)]
+#[diesel(table_name = level_changes)]
+#[diesel(treat_none_as_null = true)]
+struct LevelChangeSQLite {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    account_id: String,
+    old_level: i32,
+    new_level: i32,
+    reason: String,
+    trigger: String,
+    changed_at: NaiveDateTime,
+}
+
+impl LevelChangeSQLite {
+    fn domain_model(self) -> LevelChange {
+        LevelChange {
+            id: Uuid::parse_str(&self.id).unwrap(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            deleted_at: self.deleted_at,
+            account_id: Uuid::parse_str(&self.account_id).unwrap(),
+            old_level: self.old_level as u8,
+            new_level: self.new_level as u8,
+            reason: self.reason,
+            trigger: LevelTrigger::from_str(&self.trigger).unwrap(),
+            changed_at: self.changed_at,
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = level_changes)]
+#[diesel(treat_none_as_null = true)]
+struct NewLevelChange {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    account_id: String,
+    old_level: i32,
+    new_level: i32,
+    reason: String,
+    trigger: String,
+    changed_at: NaiveDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use diesel_migrations::*;
+    use rust_decimal_macros::dec;
+
+    pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+    // Declare a test database connection
+    fn establish_connection() -> SqliteConnection {
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        // This will run the necessary migrations.
+        connection.run_pending_migrations(MIGRATIONS).unwrap();
+        connection.begin_test_transaction().unwrap();
+        connection
+    }
+
+    #[test]
+    fn test_create_and_update_level() {
+        let mut conn = establish_connection();
+        let account_id = Uuid::new_v4();
+
+        let level = WorkerLevel::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            account_id,
+            Level::DEFAULT,
+        )
+        .expect("Error creating level");
+        assert_eq!(level.account_id, account_id);
+        assert_eq!(level.current_level, Level::DEFAULT);
+
+        let updated =
+            WorkerLevel::update_level(&mut conn, Utc::now().naive_utc(), &level, 1).unwrap();
+        assert_eq!(updated.current_level, 1);
+        assert_eq!(
+            WorkerLevel::read_for_account(&mut conn, account_id).unwrap(),
+            updated
+        );
+    }
+
+    #[test]
+    fn test_read_level_not_found() {
+        let mut conn = establish_connection();
+        assert!(WorkerLevel::read_for_account(&mut conn, Uuid::new_v4()).is_err());
+    }
+
+    #[test]
+    fn test_update_adjustment_rules() {
+        let mut conn = establish_connection();
+        let account_id = Uuid::new_v4();
+        let level = WorkerLevel::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            account_id,
+            Level::DEFAULT,
+        )
+        .unwrap();
+
+        assert_eq!(
+            WorkerLevel::read_adjustment_rules(&mut conn, account_id).unwrap(),
+            LevelAdjustmentRules::default()
+        );
+
+        let rules = LevelAdjustmentRules {
+            monthly_loss_downgrade_pct: dec!(7.5),
+        };
+        WorkerLevel::update_adjustment_rules(&mut conn, Utc::now().naive_utc(), &level, &rules)
+            .unwrap();
+        assert_eq!(
+            WorkerLevel::read_adjustment_rules(&mut conn, account_id).unwrap(),
+            rules
+        );
+    }
+
+    #[test]
+    fn test_create_and_read_level_changes() {
+        let mut conn = establish_connection();
+        let account_id = Uuid::new_v4();
+
+        let first = WorkerLevel::create_change(
+            &mut conn,
+            Utc::now().naive_utc(),
+            account_id,
+            3,
+            2,
+            "Drawdown",
+            &LevelTrigger::DrawdownBreach,
+        )
+        .expect("Error creating level change");
+        let second = WorkerLevel::create_change(
+            &mut conn,
+            Utc::now().naive_utc(),
+            account_id,
+            2,
+            4,
+            "Coach approved",
+            &LevelTrigger::Custom("coach".to_string()),
+        )
+        .unwrap();
+        WorkerLevel::create_change(
+            &mut conn,
+            Utc::now().naive_utc(),
+            Uuid::new_v4(),
+            3,
+            2,
+            "Other account",
+            &LevelTrigger::ManualOverride,
+        )
+        .unwrap();
+
+        let changes = WorkerLevel::read_changes_for_account(&mut conn, account_id).unwrap();
+        assert_eq!(changes, vec![first, second]);
+    }
+}
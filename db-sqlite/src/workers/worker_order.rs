@@ -13,22 +13,31 @@ use uuid::Uuid;
 
 pub struct WorkerOrder;
 impl WorkerOrder {
+    #[allow(clippy::too_many_arguments)]
     pub fn create(
         connection: &mut SqliteConnection,
+        now: NaiveDateTime,
         unit_price: Decimal,
         currency: &Currency,
         quantity: i64,
+        quantity_decimal: Option<Decimal>,
         action: &OrderAction,
         category: &OrderCategory,
         trading_vehicle: &TradingVehicle,
     ) -> Result<Order, Box<dyn Error>> {
+        let (trailing_percentage, trailing_price) = trail(category);
         let new_order = NewOrder {
             quantity,
+            quantity_decimal: quantity_decimal.map(|quantity| quantity.to_string()),
             unit_price: unit_price.to_string(),
             category: category.to_string(),
             currency: currency.to_string(),
             trading_vehicle_id: trading_vehicle.id.to_string(),
             action: action.to_string(),
+            trailing_percentage,
+            trailing_price,
+            created_at: now,
+            updated_at: now,
             ..Default::default()
         };
 
@@ -57,9 +66,10 @@ impl WorkerOrder {
 
     pub fn update(
         connection: &mut SqliteConnection,
+        now: NaiveDateTime,
         order: &Order,
     ) -> Result<Order, Box<dyn Error>> {
-        let now: NaiveDateTime = Utc::now().naive_utc();
+        let (trailing_percentage, trailing_price) = trail(&order.category);
         diesel::update(orders::table)
             .filter(orders::id.eq(&order.id.to_string()))
             .set((
@@ -67,12 +77,18 @@ impl WorkerOrder {
                 orders::broker_order_id.eq(order.broker_order_id.map(|id| id.to_string())),
                 orders::status.eq(order.status.to_string()),
                 orders::filled_quantity.eq(order.filled_quantity as i64),
+                orders::filled_quantity_decimal.eq(order
+                    .filled_quantity_decimal
+                    .map(|quantity| quantity.to_string())),
                 orders::average_filled_price
                     .eq(order.average_filled_price.map(|price| price.to_string())),
                 orders::submitted_at.eq(order.submitted_at),
                 orders::filled_at.eq(order.filled_at),
                 orders::expired_at.eq(order.expired_at),
                 orders::category.eq(order.category.to_string()),
+                orders::time_in_force.eq(order.time_in_force.to_string()),
+                orders::trailing_percentage.eq(trailing_percentage),
+                orders::trailing_price.eq(trailing_price),
                 orders::cancelled_at.eq(order.cancelled_at),
                 orders::closed_at.eq(order.closed_at),
             ))
@@ -83,11 +99,11 @@ impl WorkerOrder {
 
     pub fn update_price(
         connection: &mut SqliteConnection,
+        now: NaiveDateTime,
         order: &Order,
         new_price: Decimal,
         new_broker_id: Uuid,
     ) -> Result<Order, Box<dyn Error>> {
-        let now: NaiveDateTime = Utc::now().naive_utc();
         diesel::update(orders::table)
             .filter(orders::id.eq(&order.id.to_string()))
             .set((
@@ -102,10 +118,10 @@ impl WorkerOrder {
 
     pub fn update_submitted_at(
         connection: &mut SqliteConnection,
+        now: NaiveDateTime,
         order: &Order,
         broker_order_id: Uuid,
     ) -> Result<Order, Box<dyn Error>> {
-        let now = Utc::now().naive_utc();
         diesel::update(orders::table)
             .filter(orders::id.eq(&order.id.to_string()))
             .set((
@@ -120,9 +136,9 @@ impl WorkerOrder {
 
     pub fn update_filled_at(
         connection: &mut SqliteConnection,
+        now: NaiveDateTime,
         order: &Order,
     ) -> Result<Order, Box<dyn Error>> {
-        let now = Utc::now().naive_utc();
         diesel::update(orders::table)
             .filter(orders::id.eq(&order.id.to_string()))
             .set((orders::filled_at.eq(now), orders::updated_at.eq(now)))
@@ -133,9 +149,9 @@ impl WorkerOrder {
 
     pub fn update_closed_at(
         connection: &mut SqliteConnection,
+        now: NaiveDateTime,
         order: &Order,
     ) -> Result<Order, Box<dyn Error>> {
-        let now = Utc::now().naive_utc();
         diesel::update(orders::table)
             .filter(orders::id.eq(&order.id.to_string()))
             .set((orders::closed_at.eq(now), orders::updated_at.eq(now)))
@@ -145,6 +161,20 @@ impl WorkerOrder {
     }
 }
 
+/// The trail of a trailing stop is saved in its own columns, the other orders do not have one.
+fn trail(category: &OrderCategory) -> (Option<String>, Option<String>) {
+    match category {
+        OrderCategory::TrailingStop {
+            trail_percent,
+            trail_price,
+        } => (
+            trail_percent.map(|percent| percent.to_string()),
+            trail_price.map(|price| price.to_string()),
+        ),
+        _ => (None, None),
+    }
+}
+
 #[derive(Queryable, Identifiable, AsChangeset, Insertable)]
 #[diesel(table_name = orders)]
 struct OrderSQLite {
@@ -171,10 +201,23 @@ struct OrderSQLite {
     expired_at: Option<NaiveDateTime>,
     cancelled_at: Option<NaiveDateTime>,
     closed_at: Option<NaiveDateTime>,
+    quantity_decimal: Option<String>,
+    filled_quantity_decimal: Option<String>,
 }
 
 impl OrderSQLite {
     fn domain_model(self, _connection: &mut SqliteConnection) -> Order {
+        let trailing_percent = self
+            .trailing_percentage
+            .map(|p| Decimal::from_str(&p).unwrap());
+        let trailing_price = self.trailing_price.map(|p| Decimal::from_str(&p).unwrap());
+        let category = match OrderCategory::from_str(&self.category).unwrap() {
+            OrderCategory::TrailingStop { .. } => OrderCategory::TrailingStop {
+                trail_percent: trailing_percent,
+                trail_price: trailing_price,
+            },
+            category => category,
+        };
         Order {
             id: Uuid::parse_str(&self.id).unwrap(),
             broker_order_id: self.broker_order_id.map(|id| Uuid::parse_str(&id).unwrap()),
@@ -184,16 +227,20 @@ impl OrderSQLite {
             unit_price: Decimal::from_str(self.unit_price.as_str()).unwrap(),
             currency: Currency::from_str(self.currency.as_str()).unwrap(),
             quantity: self.quantity as u64,
+            quantity_decimal: self
+                .quantity_decimal
+                .map(|quantity| Decimal::from_str(&quantity).unwrap()),
             action: OrderAction::from_str(&self.action).unwrap(),
-            category: OrderCategory::from_str(&self.category).unwrap(),
+            category,
             status: OrderStatus::from_str(&self.status).unwrap(),
             trading_vehicle_id: Uuid::parse_str(&self.trading_vehicle_id).unwrap(),
             time_in_force: TimeInForce::from_str(&self.time_in_force).unwrap(),
-            trailing_percent: self
-                .trailing_percentage
-                .map(|p| Decimal::from_str(&p).unwrap()),
-            trailing_price: self.trailing_price.map(|p| Decimal::from_str(&p).unwrap()),
+            trailing_percent,
+            trailing_price,
             filled_quantity: self.filled_quantity as u64,
+            filled_quantity_decimal: self
+                .filled_quantity_decimal
+                .map(|quantity| Decimal::from_str(&quantity).unwrap()),
             average_filled_price: self
                 .average_filled_price
                 .map(|p| Decimal::from_str(&p).unwrap()),
@@ -234,6 +281,8 @@ struct NewOrder {
     expired_at: Option<NaiveDateTime>,
     cancelled_at: Option<NaiveDateTime>,
     closed_at: Option<NaiveDateTime>,
+    quantity_decimal: Option<String>,
+    filled_quantity_decimal: Option<String>,
 }
 
 impl Default for NewOrder {
@@ -263,6 +312,8 @@ impl Default for NewOrder {
             expired_at: None,
             cancelled_at: None,
             closed_at: None,
+            quantity_decimal: None,
+            filled_quantity_decimal: None,
         }
     }
 }
@@ -272,6 +323,7 @@ mod tests {
     use crate::workers::WorkerTradingVehicle;
 
     use super::*;
+    use chrono::Utc;
     use diesel_migrations::*;
     use model::{Currency, TradingVehicleCategory};
     use rust_decimal_macros::dec;
@@ -293,6 +345,7 @@ mod tests {
 
         let trading_vehicle = WorkerTradingVehicle::create(
             &mut conn,
+            Utc::now().naive_utc(),
             "AAPL",
             "isin",
             &TradingVehicleCategory::Crypto,
@@ -303,9 +356,11 @@ mod tests {
         // Create a new order record
         let order = WorkerOrder::create(
             &mut conn,
+            Utc::now().naive_utc(),
             dec!(150.00),
             &Currency::USD,
             100,
+            None,
             &OrderAction::Buy,
             &OrderCategory::Limit,
             &trading_vehicle,
@@ -321,5 +376,117 @@ mod tests {
         assert_eq!(order.closed_at, None);
         assert_eq!(order.created_at, order.updated_at);
         assert_eq!(order.deleted_at, None);
+        assert_eq!(order.quantity_decimal, None);
+    }
+
+    #[test]
+    fn test_create_fractional_order() {
+        let mut conn = establish_connection();
+
+        let trading_vehicle = WorkerTradingVehicle::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            "BTC",
+            "BTC",
+            &TradingVehicleCategory::Crypto,
+            "alpaca",
+        )
+        .unwrap();
+
+        let order = WorkerOrder::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            dec!(30000),
+            &Currency::USD,
+            0,
+            Some(dec!(0.25)),
+            &OrderAction::Buy,
+            &OrderCategory::Limit,
+            &trading_vehicle,
+        )
+        .expect("Error creating order");
+
+        assert_eq!(order.quantity_decimal, Some(dec!(0.25)));
+        assert_eq!(order.decimal_quantity(), dec!(0.25));
+        assert_eq!(WorkerOrder::read(&mut conn, order.id).unwrap(), order);
+    }
+
+    #[test]
+    fn test_create_and_update_trailing_stop_order() {
+        let mut conn = establish_connection();
+
+        let trading_vehicle = WorkerTradingVehicle::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            "AAPL",
+            "isin",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
+        )
+        .unwrap();
+
+        let category = OrderCategory::TrailingStop {
+            trail_percent: Some(dec!(2.5)),
+            trail_price: None,
+        };
+        let order = WorkerOrder::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            dec!(38),
+            &Currency::USD,
+            100,
+            None,
+            &OrderAction::Sell,
+            &category,
+            &trading_vehicle,
+        )
+        .expect("Error creating order");
+
+        assert_eq!(order.category, category);
+        assert_eq!(order.trailing_percent, Some(dec!(2.5)));
+        assert_eq!(order.trailing_price, None);
+
+        // The trail is kept when the order is updated
+        let mut accepted = order.clone();
+        accepted.status = OrderStatus::Accepted;
+        let accepted = WorkerOrder::update(&mut conn, Utc::now().naive_utc(), &accepted).unwrap();
+        assert_eq!(accepted.category, category);
+        assert_eq!(accepted.trailing_percent, Some(dec!(2.5)));
+        assert_eq!(WorkerOrder::read(&mut conn, order.id).unwrap(), accepted);
+    }
+
+    #[test]
+    fn test_update_time_in_force() {
+        let mut conn = establish_connection();
+
+        let trading_vehicle = WorkerTradingVehicle::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            "AAPL",
+            "isin",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
+        )
+        .unwrap();
+        let order = WorkerOrder::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            dec!(40),
+            &Currency::USD,
+            100,
+            None,
+            &OrderAction::Buy,
+            &OrderCategory::Limit,
+            &trading_vehicle,
+        )
+        .expect("Error creating order");
+        assert_eq!(order.time_in_force, TimeInForce::UntilCanceled);
+
+        let mut day = order.clone();
+        day.time_in_force = TimeInForce::Day;
+        let day = WorkerOrder::update(&mut conn, Utc::now().naive_utc(), &day).unwrap();
+
+        assert_eq!(day.time_in_force, TimeInForce::Day);
+        assert_eq!(WorkerOrder::read(&mut conn, order.id).unwrap(), day);
     }
 }
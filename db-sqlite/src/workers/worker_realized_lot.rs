@@ -0,0 +1,239 @@
+use crate::schema::realized_lots;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use model::RealizedLot;
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+use tracing::error;
+use uuid::Uuid;
+
+pub struct WorkerRealizedLot;
+
+impl WorkerRealizedLot {
+    pub fn create(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        lot: &RealizedLot,
+    ) -> Result<RealizedLot, Box<dyn Error>> {
+        let new_lot = RealizedLotSQLite {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            account_id: lot.account_id.to_string(),
+            trade_id: lot.trade_id.to_string(),
+            open_execution_id: lot.open_execution_id.to_string(),
+            close_execution_id: lot.close_execution_id.to_string(),
+            opened_at: lot.opened_at,
+            closed_at: lot.closed_at,
+            quantity: lot.quantity as i64,
+            cost_basis: lot.cost_basis.to_string(),
+            proceeds: lot.proceeds.to_string(),
+            gain: lot.gain.to_string(),
+        };
+
+        let lot = diesel::insert_into(realized_lots::table)
+            .values(&new_lot)
+            .get_result::<RealizedLotSQLite>(connection)
+            .map(|lot| lot.domain_model())
+            .map_err(|error| {
+                error!("Error creating realized lot: {:?}", error);
+                error
+            })?;
+        Ok(lot)
+    }
+
+    pub fn delete_all_for_trade(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        trade_id: Uuid,
+    ) -> Result<(), Box<dyn Error>> {
+        diesel::update(realized_lots::table)
+            .filter(realized_lots::deleted_at.is_null())
+            .filter(realized_lots::trade_id.eq(trade_id.to_string()))
+            .set((
+                realized_lots::deleted_at.eq(now),
+                realized_lots::updated_at.eq(now),
+            ))
+            .execute(connection)
+            .map_err(|error| {
+                error!("Error deleting realized lots: {:?}", error);
+                error
+            })?;
+        Ok(())
+    }
+
+    pub fn read_all_for_trade(
+        connection: &mut SqliteConnection,
+        trade_id: Uuid,
+    ) -> Result<Vec<RealizedLot>, Box<dyn Error>> {
+        let lots = realized_lots::table
+            .filter(realized_lots::deleted_at.is_null())
+            .filter(realized_lots::trade_id.eq(trade_id.to_string()))
+            .order((
+                realized_lots::closed_at.asc(),
+                realized_lots::created_at.asc(),
+            ))
+            .load::<RealizedLotSQLite>(connection)
+            .map(|lots: Vec<RealizedLotSQLite>| {
+                lots.into_iter().map(|lot| lot.domain_model()).collect()
+            })
+            .map_err(|error| {
+                error!("Error reading realized lots: {:?}", error);
+                error
+            })?;
+        Ok(lots)
+    }
+
+    pub fn read_all_for_account(
+        connection: &mut SqliteConnection,
+        account_id: Uuid,
+    ) -> Result<Vec<RealizedLot>, Box<dyn Error>> {
+        let lots = realized_lots::table
+            .filter(realized_lots::deleted_at.is_null())
+            .filter(realized_lots::account_id.eq(account_id.to_string()))
+            .order((
+                realized_lots::closed_at.asc(),
+                realized_lots::created_at.asc(),
+            ))
+            .load::<RealizedLotSQLite>(connection)
+            .map(|lots: Vec<RealizedLotSQLite>| {
+                lots.into_iter().map(|lot| lot.domain_model()).collect()
+            })
+            .map_err(|error| {
+                error!("Error reading realized lots: {:?}", error);
+                error
+            })?;
+        Ok(lots)
+    }
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Insertable)]
+#[diesel(table_name = realized_lots)]
+#[diesel(treat_none_as_null = true)]
+struct RealizedLotSQLite {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    account_id: String,
+    trade_id: String,
+    open_execution_id: String,
+    close_execution_id: String,
+    opened_at: NaiveDateTime,
+    closed_at: NaiveDateTime,
+    quantity: i64,
+    cost_basis: String,
+    proceeds: String,
+    gain: String,
+}
+
+impl RealizedLotSQLite {
+    fn domain_model(self) -> RealizedLot {
+        RealizedLot {
+            id: Uuid::parse_str(&self.id).unwrap(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            deleted_at: self.deleted_at,
+            account_id: Uuid::parse_str(&self.account_id).unwrap(),
+            trade_id: Uuid::parse_str(&self.trade_id).unwrap(),
+            open_execution_id: Uuid::parse_str(&self.open_execution_id).unwrap(),
+            close_execution_id: Uuid::parse_str(&self.close_execution_id).unwrap(),
+            opened_at: self.opened_at,
+            closed_at: self.closed_at,
+            quantity: self.quantity as u64,
+            cost_basis: Decimal::from_str(&self.cost_basis).unwrap(),
+            proceeds: Decimal::from_str(&self.proceeds).unwrap(),
+            gain: Decimal::from_str(&self.gain).unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use chrono::Utc;
+    use diesel_migrations::*;
+    use rust_decimal_macros::dec;
+
+    pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+    // Declare a test database connection
+    fn establish_connection() -> SqliteConnection {
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        // This will run the necessary migrations.
+        connection.run_pending_migrations(MIGRATIONS).unwrap();
+        connection.begin_test_transaction().unwrap();
+        connection
+    }
+
+    #[test]
+    fn test_create_and_read_realized_lots() {
+        let mut conn = establish_connection();
+        let account_id = Uuid::new_v4();
+        let trade_id = Uuid::new_v4();
+        let day = |day| {
+            NaiveDate::from_ymd_opt(2023, 8, day)
+                .unwrap()
+                .and_hms_opt(15, 0, 0)
+                .unwrap()
+        };
+
+        let later = WorkerRealizedLot::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            &RealizedLot {
+                account_id,
+                trade_id,
+                opened_at: day(1),
+                closed_at: day(10),
+                quantity: 20,
+                cost_basis: dec!(860),
+                proceeds: dec!(900),
+                gain: dec!(40),
+                ..Default::default()
+            },
+        )
+        .expect("Error creating realized lot");
+        let earlier = WorkerRealizedLot::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            &RealizedLot {
+                account_id,
+                trade_id,
+                opened_at: day(1),
+                closed_at: day(5),
+                quantity: 100,
+                cost_basis: dec!(4000),
+                proceeds: dec!(4500),
+                gain: dec!(500),
+                ..Default::default()
+            },
+        )
+        .expect("Error creating realized lot");
+
+        assert_eq!(later.quantity, 20);
+        assert_eq!(later.cost_basis, dec!(860));
+        assert_eq!(
+            WorkerRealizedLot::read_all_for_trade(&mut conn, trade_id).unwrap(),
+            vec![earlier.clone(), later.clone()]
+        );
+        assert_eq!(
+            WorkerRealizedLot::read_all_for_account(&mut conn, account_id).unwrap(),
+            vec![earlier, later]
+        );
+        assert!(
+            WorkerRealizedLot::read_all_for_account(&mut conn, Uuid::new_v4())
+                .unwrap()
+                .is_empty()
+        );
+
+        WorkerRealizedLot::delete_all_for_trade(&mut conn, Utc::now().naive_utc(), trade_id)
+            .unwrap();
+        assert!(WorkerRealizedLot::read_all_for_trade(&mut conn, trade_id)
+            .unwrap()
+            .is_empty());
+    }
+}
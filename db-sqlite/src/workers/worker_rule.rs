@@ -1,5 +1,5 @@
 use crate::schema::rules;
-use chrono::{NaiveDateTime, Utc};
+use chrono::NaiveDateTime;
 use diesel::prelude::*;
 use model::{Account, Rule, RuleLevel, RuleName};
 use std::error::Error;
@@ -10,6 +10,7 @@ pub struct WorkerRule;
 impl WorkerRule {
     pub fn create(
         connection: &mut SqliteConnection,
+        now: NaiveDateTime,
         name: &RuleName,
         description: &str,
         priority: u32,
@@ -17,7 +18,6 @@ impl WorkerRule {
         account: &Account,
     ) -> Result<Rule, Box<dyn Error>> {
         let uuid = Uuid::new_v4().to_string();
-        let now = Utc::now().naive_utc();
 
         let new_rule = NewRule {
             id: uuid,
@@ -25,7 +25,7 @@ impl WorkerRule {
             updated_at: now,
             deleted_at: None,
             name: name.to_string(),
-            risk: name.risk() as i32,
+            risk: name.risk(),
             description: description.to_string(),
             priority: priority as i32,
             level: level.to_string(),
@@ -110,7 +110,7 @@ struct RuleSQLite {
     updated_at: NaiveDateTime,
     deleted_at: Option<NaiveDateTime>,
     name: String,
-    risk: i32,
+    risk: f32,
     description: String,
     priority: i32,
     level: String,
@@ -121,8 +121,7 @@ struct RuleSQLite {
 impl RuleSQLite {
     fn domain_model(self) -> Rule {
         use std::str::FromStr;
-        let name =
-            RuleName::parse(&self.name, self.risk as f32).expect("Failed to parse rule name");
+        let name = RuleName::parse(&self.name, self.risk).expect("Failed to parse rule name");
         Rule {
             id: Uuid::parse_str(&self.id).unwrap(),
             created_at: self.created_at,
@@ -146,10 +145,66 @@ struct NewRule {
     updated_at: NaiveDateTime,
     deleted_at: Option<NaiveDateTime>,
     name: String,
-    risk: i32,
+    risk: f32,
     description: String,
     priority: i32,
     level: String,
     account_id: String,
     active: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SqliteDatabase;
+    use diesel_migrations::*;
+    use model::{DatabaseFactory, Environment};
+    use rust_decimal_macros::dec;
+    use std::sync::{Arc, Mutex};
+
+    pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+    fn create_factory() -> Box<dyn DatabaseFactory> {
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        connection.run_pending_migrations(MIGRATIONS).unwrap();
+        connection.begin_test_transaction().unwrap();
+        Box::new(SqliteDatabase::new_from(Arc::new(Mutex::new(connection))))
+    }
+
+    #[test]
+    fn test_create_rule_with_fractional_risk() {
+        let db = create_factory();
+        let account = db
+            .account_write()
+            .create("Test Account", "", Environment::Paper, dec!(0), dec!(0))
+            .unwrap();
+
+        for name in [
+            RuleName::MinRewardRiskRatio(2.5),
+            RuleName::MaxPositionConcentration(12.5),
+            RuleName::RiskPerTrade(1.5),
+        ] {
+            let rule = db
+                .rule_write()
+                .create_rule(&account, &name, "", 1, &RuleLevel::Error)
+                .unwrap();
+            assert_eq!(rule.name, name);
+        }
+
+        let names: Vec<RuleName> = db
+            .rule_read()
+            .read_all_rules(account.id)
+            .unwrap()
+            .into_iter()
+            .map(|rule| rule.name)
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                RuleName::MinRewardRiskRatio(2.5),
+                RuleName::MaxPositionConcentration(12.5),
+                RuleName::RiskPerTrade(1.5),
+            ]
+        );
+    }
+}
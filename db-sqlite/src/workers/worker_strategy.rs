@@ -0,0 +1,242 @@
+use crate::schema::strategies;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use model::Strategy;
+use std::error::Error;
+use tracing::error;
+use uuid::Uuid;
+
+pub struct WorkerStrategy;
+
+impl WorkerStrategy {
+    #[allow(clippy::too_many_arguments)]
+    pub fn create(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        name: &str,
+        description: &str,
+        version: u16,
+        entry_description: &str,
+        stop_description: &str,
+        target_description: &str,
+    ) -> Result<Strategy, Box<dyn Error>> {
+        let new_strategy = NewStrategy {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            name: name.to_string(),
+            description: description.to_string(),
+            version: version as i32,
+            entry_description: entry_description.to_string(),
+            stop_description: stop_description.to_string(),
+            target_description: target_description.to_string(),
+        };
+
+        let strategy = diesel::insert_into(strategies::table)
+            .values(&new_strategy)
+            .get_result::<StrategySQLite>(connection)
+            .map(|strategy| strategy.domain_model())
+            .map_err(|error| {
+                error!("Error creating strategy: {:?}", error);
+                error
+            })?;
+        Ok(strategy)
+    }
+
+    pub fn read(connection: &mut SqliteConnection, id: Uuid) -> Result<Strategy, Box<dyn Error>> {
+        let strategy = strategies::table
+            .filter(strategies::id.eq(id.to_string()))
+            .filter(strategies::deleted_at.is_null())
+            .first::<StrategySQLite>(connection)
+            .map(|strategy| strategy.domain_model())
+            .map_err(|error| {
+                error!("Error reading strategy: {:?}", error);
+                error
+            })?;
+        Ok(strategy)
+    }
+
+    pub fn read_all(connection: &mut SqliteConnection) -> Result<Vec<Strategy>, Box<dyn Error>> {
+        let strategies = strategies::table
+            .filter(strategies::deleted_at.is_null())
+            .order(strategies::created_at.asc())
+            .load::<StrategySQLite>(connection)
+            .map(|strategies: Vec<StrategySQLite>| {
+                strategies
+                    .into_iter()
+                    .map(|strategy| strategy.domain_model())
+                    .collect()
+            })
+            .map_err(|error| {
+                error!("Error reading strategies: {:?}", error);
+                error
+            })?;
+        Ok(strategies)
+    }
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Insertable)]
+#[diesel(table_name = strategies)]
+#[diesel(treat_none_as_null = true)]
+struct StrategySQLite {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    name: String,
+    description: String,
+    version: i32,
+    entry_description: String,
+    stop_description: String,
+    target_description: String,
+}
+
+impl StrategySQLite {
+    fn domain_model(self) -> Strategy {
+        Strategy {
+            id: Uuid::parse_str(&self.id).unwrap(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            deleted_at: self.deleted_at,
+            name: self.name,
+            description: self.description,
+            version: self.version as u16,
+            entry_description: self.entry_description,
+            stop_description: self.stop_description,
+            target_description: self.target_description,
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = strategies)]
+#[diesel(treat_none_as_null = true)]
+struct NewStrategy {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    name: String,
+    description: String,
+    version: i32,
+    entry_description: String,
+    stop_description: String,
+    target_description: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use diesel_migrations::*;
+
+    pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+    // Declare a test database connection
+    fn establish_connection() -> SqliteConnection {
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        // This will run the necessary migrations.
+        connection.run_pending_migrations(MIGRATIONS).unwrap();
+        connection.begin_test_transaction().unwrap();
+        connection
+    }
+
+    #[test]
+    fn test_create_strategy() {
+        let mut conn = establish_connection();
+
+        let strategy = WorkerStrategy::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            "Bullish divergence on RSI",
+            "Divergence between price and RSI",
+            1,
+            "Buy in pullback",
+            "Below the last low",
+            "Previous high",
+        )
+        .expect("Error creating strategy");
+
+        assert_eq!(strategy.name, "Bullish divergence on RSI");
+        assert_eq!(strategy.description, "Divergence between price and RSI");
+        assert_eq!(strategy.version, 1);
+        assert_eq!(strategy.entry_description, "Buy in pullback");
+        assert_eq!(strategy.stop_description, "Below the last low");
+        assert_eq!(strategy.target_description, "Previous high");
+        assert_eq!(
+            WorkerStrategy::read(&mut conn, strategy.id).unwrap(),
+            strategy
+        );
+    }
+
+    #[test]
+    fn test_create_strategy_same_version() {
+        let mut conn = establish_connection();
+
+        WorkerStrategy::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            "Breakout",
+            "",
+            1,
+            "",
+            "",
+            "",
+        )
+        .unwrap();
+        WorkerStrategy::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            "Breakout",
+            "",
+            2,
+            "",
+            "",
+            "",
+        )
+        .unwrap();
+        WorkerStrategy::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            "Breakout",
+            "",
+            1,
+            "",
+            "",
+            "",
+        )
+        .expect_err("The same version of a strategy can not be created twice");
+    }
+
+    #[test]
+    fn test_read_all_strategies() {
+        let mut conn = establish_connection();
+
+        let first = WorkerStrategy::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            "Breakout",
+            "",
+            1,
+            "",
+            "",
+            "",
+        )
+        .unwrap();
+        let second = WorkerStrategy::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            "Pullback",
+            "",
+            1,
+            "",
+            "",
+            "",
+        )
+        .unwrap();
+
+        let strategies = WorkerStrategy::read_all(&mut conn).unwrap();
+        assert_eq!(strategies, vec![first, second]);
+    }
+}
@@ -0,0 +1,139 @@
+use crate::schema::thesis_history;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use model::{ThesisRevision, Trade};
+use std::error::Error;
+use tracing::error;
+use uuid::Uuid;
+
+pub struct WorkerThesisHistory;
+
+impl WorkerThesisHistory {
+    /// Records the thesis and the context that the trade has before they are revised.
+    pub fn create(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        trade: &Trade,
+    ) -> Result<ThesisRevision, diesel::result::Error> {
+        let new_revision = ThesisRevisionSQLite {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            trade_id: trade.id.to_string(),
+            thesis: trade.thesis.clone(),
+            context: trade.context.clone(),
+        };
+
+        diesel::insert_into(thesis_history::table)
+            .values(&new_revision)
+            .get_result::<ThesisRevisionSQLite>(connection)
+            .map(|revision| revision.domain_model())
+            .map_err(|error| {
+                error!("Error creating thesis revision: {:?}", error);
+                error
+            })
+    }
+
+    pub fn read_for_trade(
+        connection: &mut SqliteConnection,
+        trade_id: Uuid,
+    ) -> Result<Vec<ThesisRevision>, Box<dyn Error>> {
+        let revisions = thesis_history::table
+            .filter(thesis_history::deleted_at.is_null())
+            .filter(thesis_history::trade_id.eq(trade_id.to_string()))
+            .order(thesis_history::created_at.asc())
+            .load::<ThesisRevisionSQLite>(connection)
+            .map(|revisions| {
+                revisions
+                    .into_iter()
+                    .map(|revision| revision.domain_model())
+                    .collect()
+            })
+            .map_err(|error| {
+                error!("Error reading thesis history: {:?}", error);
+                error
+            })?;
+        Ok(revisions)
+    }
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Insertable)]
+#[diesel(table_name = thesis_history)]
+#[diesel(treat_none_as_null = true)]
+struct ThesisRevisionSQLite {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    trade_id: String,
+    thesis: Option<String>,
+    context: Option<String>,
+}
+
+impl ThesisRevisionSQLite {
+    fn domain_model(self) -> ThesisRevision {
+        ThesisRevision {
+            id: Uuid::parse_str(&self.id).unwrap(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            deleted_at: self.deleted_at,
+            trade_id: Uuid::parse_str(&self.trade_id).unwrap(),
+            thesis: self.thesis,
+            context: self.context,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use diesel_migrations::*;
+
+    pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+    // Declare a test database connection
+    fn establish_connection() -> SqliteConnection {
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        // This will run the necessary migrations.
+        connection.run_pending_migrations(MIGRATIONS).unwrap();
+        connection.begin_test_transaction().unwrap();
+        connection
+    }
+
+    #[test]
+    fn test_create_and_read_thesis_history() {
+        let mut conn = establish_connection();
+        let trade = Trade {
+            thesis: Some("Breakout above the range".to_string()),
+            ..Default::default()
+        };
+
+        let first = WorkerThesisHistory::create(&mut conn, Utc::now().naive_utc(), &trade)
+            .expect("Error creating thesis revision");
+        let second = WorkerThesisHistory::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            &Trade {
+                thesis: None,
+                context: Some("Earnings next week".to_string()),
+                ..trade.clone()
+            },
+        )
+        .expect("Error creating thesis revision");
+
+        assert_eq!(first.trade_id, trade.id);
+        assert_eq!(first.thesis, trade.thesis);
+        assert_eq!(first.context, None);
+        assert_eq!(
+            WorkerThesisHistory::read_for_trade(&mut conn, trade.id).unwrap(),
+            vec![first, second]
+        );
+        assert!(
+            WorkerThesisHistory::read_for_trade(&mut conn, Uuid::new_v4())
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -1,6 +1,7 @@
-use crate::schema::{trades, trades_balances};
+use crate::schema::{trades, trades_balances, trading_vehicles};
 use chrono::{NaiveDateTime, Utc};
 use diesel::prelude::*;
+use diesel::sqlite::Sqlite;
 use model::{Currency, DraftTrade, Status};
 use model::{Order, Trade, TradeBalance, TradeCategory};
 use rust_decimal::Decimal;
@@ -9,19 +10,19 @@ use std::str::FromStr;
 use tracing::error;
 use uuid::Uuid;
 
-use super::{WorkerOrder, WorkerTradingVehicle};
+use super::{WorkerOrder, WorkerThesisHistory, WorkerTradingVehicle};
 pub struct WorkerTrade;
 
 impl WorkerTrade {
     pub fn create(
         connection: &mut SqliteConnection,
+        now: NaiveDateTime,
         draft: DraftTrade,
         safety_stop: &Order,
         entry: &Order,
         target: &Order,
     ) -> Result<Trade, Box<dyn Error>> {
         let id = Uuid::new_v4().to_string();
-        let now = Utc::now().naive_utc();
 
         let balance = WorkerTrade::create_balance(connection, &draft.currency, now)?;
 
@@ -39,6 +40,12 @@ impl WorkerTrade {
             target_id: target.id.to_string(),
             account_id: draft.account.id.to_string(),
             balance_id: balance.id.to_string(),
+            strategy_id: draft.strategy_id.map(|id| id.to_string()),
+            reference: draft.reference,
+            thesis: draft.thesis,
+            context: draft.context,
+            sector: None,
+            last_synced_at: None,
         };
 
         let trade = diesel::insert_into(trades::table)
@@ -77,6 +84,25 @@ impl WorkerTrade {
         Ok(trade)
     }
 
+    pub fn read_trade_by_reference(
+        connection: &mut SqliteConnection,
+        account_id: Uuid,
+        reference: &str,
+    ) -> Result<Option<Trade>, Box<dyn Error>> {
+        let trade = trades::table
+            .filter(trades::deleted_at.is_null())
+            .filter(trades::account_id.eq(account_id.to_string()))
+            .filter(trades::reference.eq(reference))
+            .first::<TradeSQLite>(connection)
+            .optional()
+            .map(|trade| trade.map(|trade| trade.domain_model(connection)))
+            .map_err(|error| {
+                error!("Error reading trade by reference: {:?}", error);
+                error
+            })?;
+        Ok(trade)
+    }
+
     pub fn read_all_funded_trades_for_currency(
         connection: &mut SqliteConnection,
         account_id: Uuid,
@@ -124,6 +150,141 @@ impl WorkerTrade {
         Ok(trades)
     }
 
+    /// Reads the trades of a symbol with any of the statuses, from the newest to the oldest.
+    pub fn read_trades_for_symbol(
+        connection: &mut SqliteConnection,
+        account_id: Uuid,
+        symbol: &str,
+        statuses: &[Status],
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        let statuses: Vec<String> = statuses.iter().map(|status| status.to_string()).collect();
+        let trades: Vec<Trade> = trades::table
+            .inner_join(trading_vehicles::table)
+            .filter(trades::deleted_at.is_null())
+            .filter(trades::account_id.eq(account_id.to_string()))
+            .filter(trading_vehicles::symbol.eq(symbol))
+            .filter(trades::status.eq_any(statuses))
+            .order(trades::created_at.desc())
+            .select(trades::all_columns)
+            .load::<TradeSQLite>(connection)
+            .map(|trades: Vec<TradeSQLite>| {
+                trades
+                    .into_iter()
+                    .map(|trade| trade.domain_model(connection))
+                    .collect()
+            })
+            .map_err(|error| {
+                error!("Error reading trades: {:?}", error);
+                error
+            })?;
+        Ok(trades)
+    }
+
+    /// Reads the trades closed by the stop or the target, from the newest to the oldest close.
+    pub fn read_closed_trades(
+        connection: &mut SqliteConnection,
+        account_id: Uuid,
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        let closed =
+            [Status::ClosedStopLoss, Status::ClosedTarget].map(|status| status.to_string());
+        let mut trades: Vec<Trade> = trades::table
+            .filter(trades::deleted_at.is_null())
+            .filter(trades::account_id.eq(account_id.to_string()))
+            .filter(trades::status.eq_any(closed))
+            .load::<TradeSQLite>(connection)
+            .map(|trades: Vec<TradeSQLite>| {
+                trades
+                    .into_iter()
+                    .map(|trade| trade.domain_model(connection))
+                    .collect()
+            })
+            .map_err(|error| {
+                error!("Error reading trades: {:?}", error);
+                error
+            })?;
+        // The close time is stored in the orders, so the trades are sorted once they are read.
+        trades.sort_by_key(|trade| std::cmp::Reverse(trade.closed_at()));
+        Ok(trades)
+    }
+
+    pub fn read_closed_trades_between(
+        connection: &mut SqliteConnection,
+        account_id: Uuid,
+        closed_from: NaiveDateTime,
+        closed_until: NaiveDateTime,
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        let mut trades = WorkerTrade::read_closed_trades(connection, account_id)?;
+        trades.retain(|trade| trade.closed_at() >= closed_from && trade.closed_at() < closed_until);
+        Ok(trades)
+    }
+
+    /// Reads a page of the trades with a status, ordered by creation date.
+    /// It also returns the total number of trades that match the filters.
+    pub fn read_trades_with_status_paged(
+        connection: &mut SqliteConnection,
+        account_id: Uuid,
+        status: Status,
+        limit: u32,
+        offset: u32,
+        created_after: Option<NaiveDateTime>,
+        created_before: Option<NaiveDateTime>,
+    ) -> Result<(Vec<Trade>, u64), Box<dyn Error>> {
+        let total = WorkerTrade::trades_with_status_between(
+            account_id,
+            status,
+            created_after,
+            created_before,
+        )
+        .count()
+        .get_result::<i64>(connection)
+        .map_err(|error| {
+            error!("Error counting trades: {:?}", error);
+            error
+        })?;
+
+        let trades: Vec<Trade> = WorkerTrade::trades_with_status_between(
+            account_id,
+            status,
+            created_after,
+            created_before,
+        )
+        .order(trades::created_at.asc())
+        .limit(limit as i64)
+        .offset(offset as i64)
+        .load::<TradeSQLite>(connection)
+        .map(|trades: Vec<TradeSQLite>| {
+            trades
+                .into_iter()
+                .map(|trade| trade.domain_model(connection))
+                .collect()
+        })
+        .map_err(|error| {
+            error!("Error reading trades: {:?}", error);
+            error
+        })?;
+        Ok((trades, total as u64))
+    }
+
+    fn trades_with_status_between(
+        account_id: Uuid,
+        status: Status,
+        created_after: Option<NaiveDateTime>,
+        created_before: Option<NaiveDateTime>,
+    ) -> trades::BoxedQuery<'static, Sqlite> {
+        let mut query = trades::table
+            .filter(trades::deleted_at.is_null())
+            .filter(trades::account_id.eq(account_id.to_string()))
+            .filter(trades::status.eq(status.to_string()))
+            .into_boxed();
+        if let Some(created_after) = created_after {
+            query = query.filter(trades::created_at.gt(created_after));
+        }
+        if let Some(created_before) = created_before {
+            query = query.filter(trades::created_at.lt(created_before));
+        }
+        query
+    }
+
     pub fn read_all_trades_with_status_currency(
         connection: &mut SqliteConnection,
         account_id: Uuid,
@@ -170,8 +331,10 @@ impl WorkerTrade {
         Ok(balance)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn update_trade_balance(
         connection: &mut SqliteConnection,
+        now: NaiveDateTime,
         trade: &Trade,
         funding: Decimal,
         capital_in_market: Decimal,
@@ -182,7 +345,7 @@ impl WorkerTrade {
         let balance = diesel::update(trades_balances::table)
             .filter(trades_balances::id.eq(&trade.balance.id.to_string()))
             .set((
-                trades_balances::updated_at.eq(Utc::now().naive_utc()),
+                trades_balances::updated_at.eq(now),
                 trades_balances::funding.eq(funding.to_string()),
                 trades_balances::capital_in_market.eq(capital_in_market.to_string()),
                 trades_balances::capital_out_market.eq(capital_out_market.to_string()),
@@ -200,10 +363,10 @@ impl WorkerTrade {
 
     pub fn update_trade_status(
         connection: &mut SqliteConnection,
+        now: NaiveDateTime,
         status: Status,
         trade: &Trade,
     ) -> Result<Trade, Box<dyn Error>> {
-        let now = Utc::now().naive_utc();
         let trade = diesel::update(trades::table)
             .filter(trades::id.eq(trade.id.to_string()))
             .set((
@@ -218,6 +381,73 @@ impl WorkerTrade {
             })?;
         Ok(trade)
     }
+
+    /// Updates the thesis and the context of a trade.
+    /// The previous values are kept in the thesis history in the same transaction.
+    pub fn update_trade_thesis(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        trade: &Trade,
+        thesis: Option<String>,
+        context: Option<String>,
+    ) -> Result<Trade, Box<dyn Error>> {
+        let trade = connection.transaction::<_, diesel::result::Error, _>(|connection| {
+            WorkerThesisHistory::create(connection, now, trade)?;
+
+            diesel::update(trades::table)
+                .filter(trades::id.eq(trade.id.to_string()))
+                .set((
+                    trades::updated_at.eq(now),
+                    trades::thesis.eq(thesis),
+                    trades::context.eq(context),
+                ))
+                .get_result::<TradeSQLite>(connection)
+                .map_err(|error| {
+                    error!("Error updating trade thesis: {:?}", error);
+                    error
+                })
+        })?;
+        Ok(trade.domain_model(connection))
+    }
+
+    pub fn update_trade_sector(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        trade: &Trade,
+        sector: Option<String>,
+    ) -> Result<Trade, Box<dyn Error>> {
+        let trade = diesel::update(trades::table)
+            .filter(trades::id.eq(trade.id.to_string()))
+            .set((trades::updated_at.eq(now), trades::sector.eq(sector)))
+            .get_result::<TradeSQLite>(connection)
+            .map(|trade| trade.domain_model(connection))
+            .map_err(|error| {
+                error!("Error updating trade sector: {:?}", error);
+                error
+            })?;
+        Ok(trade)
+    }
+
+    pub fn update_trade_last_synced_at(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        trade: &Trade,
+        synced_at: NaiveDateTime,
+    ) -> Result<Trade, Box<dyn Error>> {
+        let trade = diesel::update(trades::table)
+            .filter(trades::id.eq(trade.id.to_string()))
+            .set((
+                trades::updated_at.eq(now),
+                trades::last_synced_at.eq(synced_at),
+            ))
+            .get_result::<TradeSQLite>(connection)
+            .map(|trade| trade.domain_model(connection))
+            .map_err(|error| {
+                error!("Error updating trade last synced at: {:?}", error);
+                error
+            })?;
+        Ok(trade)
+    }
 }
 
 // Trade
@@ -238,6 +468,12 @@ struct TradeSQLite {
     target_id: String,
     account_id: String,
     balance_id: String,
+    strategy_id: Option<String>,
+    reference: Option<String>,
+    thesis: Option<String>,
+    context: Option<String>,
+    sector: Option<String>,
+    last_synced_at: Option<NaiveDateTime>,
 }
 
 impl TradeSQLite {
@@ -270,6 +506,12 @@ impl TradeSQLite {
             entry,
             target: targets,
             account_id: Uuid::parse_str(&self.account_id).unwrap(),
+            strategy_id: self.strategy_id.map(|id| Uuid::parse_str(&id).unwrap()),
+            reference: self.reference,
+            thesis: self.thesis,
+            context: self.context,
+            sector: self.sector,
+            last_synced_at: self.last_synced_at,
             balance,
         }
     }
@@ -292,6 +534,12 @@ struct NewTrade {
     entry_id: String,
     account_id: String,
     balance_id: String,
+    strategy_id: Option<String>,
+    reference: Option<String>,
+    thesis: Option<String>,
+    context: Option<String>,
+    sector: Option<String>,
+    last_synced_at: Option<NaiveDateTime>,
 }
 
 #[derive(Queryable, Identifiable, AsChangeset, Insertable)]
@@ -0,0 +1,137 @@
+use crate::schema::trade_grades;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use model::TradeGrade;
+use std::error::Error;
+use tracing::error;
+use uuid::Uuid;
+
+pub struct WorkerTradeGrade;
+
+impl WorkerTradeGrade {
+    pub fn create(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        grade: &TradeGrade,
+    ) -> Result<TradeGrade, Box<dyn Error>> {
+        let new_grade = TradeGradeSQLite {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            trade_id: grade.trade_id.to_string(),
+            risk_management: i32::from(grade.risk_management),
+            execution: i32::from(grade.execution),
+            outcome: i32::from(grade.outcome),
+            documentation: i32::from(grade.documentation),
+            overall: i32::from(grade.overall),
+        };
+
+        let grade = diesel::insert_into(trade_grades::table)
+            .values(&new_grade)
+            .get_result::<TradeGradeSQLite>(connection)
+            .map(|grade| grade.domain_model())
+            .map_err(|error| {
+                error!("Error creating trade grade: {:?}", error);
+                error
+            })?;
+        Ok(grade)
+    }
+
+    pub fn read_for_trade(
+        connection: &mut SqliteConnection,
+        trade_id: Uuid,
+    ) -> Result<TradeGrade, Box<dyn Error>> {
+        let grade = trade_grades::table
+            .filter(trade_grades::deleted_at.is_null())
+            .filter(trade_grades::trade_id.eq(trade_id.to_string()))
+            .first::<TradeGradeSQLite>(connection)
+            .map(|grade| grade.domain_model())
+            .map_err(|error| {
+                error!("Error reading trade grade: {:?}", error);
+                error
+            })?;
+        Ok(grade)
+    }
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Insertable)]
+#[diesel(table_name = trade_grades)]
+#[diesel(treat_none_as_null = true)]
+struct TradeGradeSQLite {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    trade_id: String,
+    risk_management: i32,
+    execution: i32,
+    outcome: i32,
+    documentation: i32,
+    overall: i32,
+}
+
+impl TradeGradeSQLite {
+    fn domain_model(self) -> TradeGrade {
+        TradeGrade {
+            id: Uuid::parse_str(&self.id).unwrap(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            deleted_at: self.deleted_at,
+            trade_id: Uuid::parse_str(&self.trade_id).unwrap(),
+            risk_management: self.risk_management as u8,
+            execution: self.execution as u8,
+            outcome: self.outcome as u8,
+            documentation: self.documentation as u8,
+            overall: self.overall as u8,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use diesel_migrations::*;
+
+    pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+    // Declare a test database connection
+    fn establish_connection() -> SqliteConnection {
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        // This will run the necessary migrations.
+        connection.run_pending_migrations(MIGRATIONS).unwrap();
+        connection.begin_test_transaction().unwrap();
+        connection
+    }
+
+    #[test]
+    fn test_create_and_read_trade_grade() {
+        let mut conn = establish_connection();
+        let trade_id = Uuid::new_v4();
+
+        let grade = WorkerTradeGrade::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            &TradeGrade {
+                trade_id,
+                risk_management: 100,
+                execution: 50,
+                outcome: 0,
+                documentation: 100,
+                overall: 60,
+                ..Default::default()
+            },
+        )
+        .expect("Error creating trade grade");
+
+        assert_eq!(grade.trade_id, trade_id);
+        assert_eq!(grade.execution, 50);
+        assert_eq!(grade.overall, 60);
+        assert_eq!(
+            WorkerTradeGrade::read_for_trade(&mut conn, trade_id).unwrap(),
+            grade
+        );
+        assert!(WorkerTradeGrade::read_for_trade(&mut conn, Uuid::new_v4()).is_err());
+    }
+}
@@ -2,23 +2,41 @@ use std::error::Error;
 use std::str::FromStr;
 
 use crate::schema::trading_vehicles;
-use chrono::{NaiveDateTime, Utc};
+use chrono::NaiveDateTime;
 use diesel::prelude::*;
-use model::{TradingVehicle, TradingVehicleCategory};
+use model::{Currency, TradingVehicle, TradingVehicleCategory};
+use rust_decimal::Decimal;
 use tracing::error;
 use uuid::Uuid;
 
 pub struct WorkerTradingVehicle;
 impl WorkerTradingVehicle {
+    /// Creates a trading vehicle, or returns the existing one if the broker already
+    /// has a trading vehicle with the same symbol and category.
     pub fn create(
         connection: &mut SqliteConnection,
+        now: NaiveDateTime,
         symbol: &str,
         isin: &str,
         category: &TradingVehicleCategory,
         broker: &str,
     ) -> Result<TradingVehicle, Box<dyn Error>> {
+        let existing = trading_vehicles::table
+            .filter(trading_vehicles::deleted_at.is_null())
+            .filter(trading_vehicles::symbol.eq(symbol.to_uppercase()))
+            .filter(trading_vehicles::category.eq(category.to_string()))
+            .filter(trading_vehicles::broker.eq(broker.to_lowercase()))
+            .first::<TradingVehicleSQLite>(connection)
+            .optional()
+            .map_err(|error| {
+                error!("Error reading trading vehicle: {:?}", error);
+                error
+            })?;
+        if let Some(tv) = existing {
+            return Ok(tv.domain_model());
+        }
+
         let id = Uuid::new_v4().to_string();
-        let now = Utc::now().naive_utc();
 
         let new_trading_vehicle = NewTradingVehicle {
             id,
@@ -29,6 +47,8 @@ impl WorkerTradingVehicle {
             isin: isin.to_uppercase(),
             category: category.to_string(),
             broker: broker.to_lowercase(),
+            quote_currency: None,
+            tick_size: None,
         };
 
         let tv = diesel::insert_into(trading_vehicles::table)
@@ -73,6 +93,48 @@ impl WorkerTradingVehicle {
             })?;
         Ok(tv)
     }
+
+    pub fn update_quote_currency(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        trading_vehicle: &TradingVehicle,
+        currency: Option<Currency>,
+    ) -> Result<TradingVehicle, Box<dyn Error>> {
+        let tv = diesel::update(trading_vehicles::table)
+            .filter(trading_vehicles::id.eq(trading_vehicle.id.to_string()))
+            .set((
+                trading_vehicles::updated_at.eq(now),
+                trading_vehicles::quote_currency.eq(currency.map(|currency| currency.to_string())),
+            ))
+            .get_result::<TradingVehicleSQLite>(connection)
+            .map(|tv| tv.domain_model())
+            .map_err(|error| {
+                error!("Error updating trading vehicle quote currency: {:?}", error);
+                error
+            })?;
+        Ok(tv)
+    }
+
+    pub fn update_tick_size(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        trading_vehicle: &TradingVehicle,
+        tick_size: Option<Decimal>,
+    ) -> Result<TradingVehicle, Box<dyn Error>> {
+        let tv = diesel::update(trading_vehicles::table)
+            .filter(trading_vehicles::id.eq(trading_vehicle.id.to_string()))
+            .set((
+                trading_vehicles::updated_at.eq(now),
+                trading_vehicles::tick_size.eq(tick_size.map(|tick_size| tick_size.to_string())),
+            ))
+            .get_result::<TradingVehicleSQLite>(connection)
+            .map(|tv| tv.domain_model())
+            .map_err(|error| {
+                error!("Error updating trading vehicle tick size: {:?}", error);
+                error
+            })?;
+        Ok(tv)
+    }
 }
 
 #[derive(Queryable, Identifiable, AsChangeset, Insertable)]
@@ -86,6 +148,8 @@ struct TradingVehicleSQLite {
     isin: String,
     category: String,
     broker: String,
+    quote_currency: Option<String>,
+    tick_size: Option<String>,
 }
 
 impl TradingVehicleSQLite {
@@ -99,6 +163,12 @@ impl TradingVehicleSQLite {
             isin: self.isin,
             category: TradingVehicleCategory::from_str(&self.category).unwrap(),
             broker: self.broker,
+            quote_currency: self
+                .quote_currency
+                .map(|currency| Currency::from_str(&currency).unwrap()),
+            tick_size: self
+                .tick_size
+                .map(|tick_size| Decimal::from_str(&tick_size).unwrap()),
         }
     }
 }
@@ -114,11 +184,15 @@ pub struct NewTradingVehicle {
     isin: String,
     category: String,
     broker: String,
+    quote_currency: Option<String>,
+    tick_size: Option<String>,
 }
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Utc;
     use diesel_migrations::*;
+    use rust_decimal_macros::dec;
 
     pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
 
@@ -133,6 +207,7 @@ mod tests {
     fn create_apple_trading_vehicle(conn: &mut SqliteConnection) -> TradingVehicle {
         WorkerTradingVehicle::create(
             conn,
+            Utc::now().naive_utc(),
             "AAPl",
             "uS0378331005",
             &TradingVehicleCategory::Fiat,
@@ -162,7 +237,8 @@ mod tests {
         create_apple_trading_vehicle(&mut conn);
         WorkerTradingVehicle::create(
             &mut conn,
-            "AAPl",
+            Utc::now().naive_utc(),
+            "APPLE",
             "uS0378331005",
             &TradingVehicleCategory::Fiat,
             "NASDAQ",
@@ -170,12 +246,43 @@ mod tests {
         .expect_err("Error creating trading_vehicle with same isin");
     }
 
+    #[test]
+    fn test_create_duplicated_trading_vehicle() {
+        let mut conn = establish_connection();
+        let trading_vehicle = create_apple_trading_vehicle(&mut conn);
+
+        let duplicated = WorkerTradingVehicle::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            "aapl",
+            "US0378331005",
+            &TradingVehicleCategory::Fiat,
+            "nasdaq",
+        )
+        .expect("Error creating duplicated trading_vehicle");
+        assert_eq!(duplicated, trading_vehicle);
+
+        // The same symbol in another category is another trading vehicle
+        let other = WorkerTradingVehicle::create(
+            &mut conn,
+            Utc::now().naive_utc(),
+            "AAPL",
+            "AAPL",
+            &TradingVehicleCategory::Crypto,
+            "NASDAQ",
+        )
+        .unwrap();
+        assert_ne!(other.id, trading_vehicle.id);
+        assert_eq!(WorkerTradingVehicle::read_all(&mut conn).unwrap().len(), 2);
+    }
+
     #[test]
     fn test_read_trading_vehicle() {
         let mut conn = establish_connection();
 
         WorkerTradingVehicle::create(
             &mut conn,
+            Utc::now().naive_utc(),
             "TSLA",
             "US88160R1014",
             &TradingVehicleCategory::Fiat,
@@ -190,4 +297,60 @@ mod tests {
 
         assert_eq!(read_trading_vehicles.len(), 2);
     }
+
+    #[test]
+    fn test_update_quote_currency() {
+        let mut conn = establish_connection();
+        let trading_vehicle = create_apple_trading_vehicle(&mut conn);
+        assert_eq!(trading_vehicle.quote_currency, None);
+
+        let updated = WorkerTradingVehicle::update_quote_currency(
+            &mut conn,
+            Utc::now().naive_utc(),
+            &trading_vehicle,
+            Some(Currency::EUR),
+        )
+        .expect("Error updating quote currency");
+        assert_eq!(updated.quote_currency, Some(Currency::EUR));
+
+        let read = WorkerTradingVehicle::read(&mut conn, trading_vehicle.id).unwrap();
+        assert_eq!(read.quote_currency, Some(Currency::EUR));
+
+        let updated = WorkerTradingVehicle::update_quote_currency(
+            &mut conn,
+            Utc::now().naive_utc(),
+            &trading_vehicle,
+            None,
+        )
+        .unwrap();
+        assert_eq!(updated.quote_currency, None);
+    }
+
+    #[test]
+    fn test_update_tick_size() {
+        let mut conn = establish_connection();
+        let trading_vehicle = create_apple_trading_vehicle(&mut conn);
+        assert_eq!(trading_vehicle.tick_size, None);
+
+        let updated = WorkerTradingVehicle::update_tick_size(
+            &mut conn,
+            Utc::now().naive_utc(),
+            &trading_vehicle,
+            Some(dec!(0.05)),
+        )
+        .expect("Error updating tick size");
+        assert_eq!(updated.tick_size, Some(dec!(0.05)));
+
+        let read = WorkerTradingVehicle::read(&mut conn, trading_vehicle.id).unwrap();
+        assert_eq!(read.tick_size, Some(dec!(0.05)));
+
+        let updated = WorkerTradingVehicle::update_tick_size(
+            &mut conn,
+            Utc::now().naive_utc(),
+            &trading_vehicle,
+            None,
+        )
+        .unwrap();
+        assert_eq!(updated.tick_size, None);
+    }
 }
@@ -1,5 +1,5 @@
 use crate::schema::transactions;
-use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime};
 use diesel::prelude::*;
 use model::{Currency, Status, Transaction, TransactionCategory};
 use rust_decimal::Decimal;
@@ -15,13 +15,68 @@ pub struct WorkerTransaction;
 impl WorkerTransaction {
     pub fn create_transaction(
         connection: &mut SqliteConnection,
+        now: NaiveDateTime,
         account_id: Uuid,
         amount: Decimal,
         currency: &Currency,
         category: TransactionCategory,
     ) -> Result<Transaction, Box<dyn Error>> {
-        let now = Utc::now().naive_utc();
+        WorkerTransaction::insert(
+            connection, now, account_id, amount, currency, category, None, None,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_broker_transaction(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        account_id: Uuid,
+        amount: Decimal,
+        currency: &Currency,
+        category: TransactionCategory,
+        broker_activity_id: &str,
+    ) -> Result<Transaction, Box<dyn Error>> {
+        WorkerTransaction::insert(
+            connection,
+            now,
+            account_id,
+            amount,
+            currency,
+            category,
+            None,
+            Some(broker_activity_id.to_string()),
+        )
+    }
+
+    pub fn create_reversal(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        transaction: &Transaction,
+        category: TransactionCategory,
+    ) -> Result<Transaction, Box<dyn Error>> {
+        WorkerTransaction::insert(
+            connection,
+            now,
+            transaction.account_id,
+            transaction.amount,
+            &transaction.currency,
+            category,
+            Some(transaction.id),
+            None,
+        )
+    }
 
+    #[allow(clippy::too_many_arguments)]
+    fn insert(
+        connection: &mut SqliteConnection,
+        now: NaiveDateTime,
+        account_id: Uuid,
+        amount: Decimal,
+        currency: &Currency,
+        category: TransactionCategory,
+        reverses: Option<Uuid>,
+        broker_activity_id: Option<String>,
+    ) -> Result<Transaction, Box<dyn Error>> {
         let new_transaction = NewTransaction {
             id: Uuid::new_v4().to_string(),
             created_at: now,
@@ -32,6 +87,10 @@ impl WorkerTransaction {
             account_id: account_id.to_string(),
             amount: amount.to_string(),
             trade_id: category.trade_id().map(|uuid| uuid.to_string()),
+            trading_vehicle_id: category.trading_vehicle_id().map(|uuid| uuid.to_string()),
+            reverses: reverses.map(|uuid| uuid.to_string()),
+            reason: category.reason().map(|reason| reason.to_string()),
+            broker_activity_id,
         };
 
         let transaction = diesel::insert_into(transactions::table)
@@ -46,14 +105,45 @@ impl WorkerTransaction {
     }
 
     pub fn read_all_transactions(
+        connection: &mut SqliteConnection,
+        account_id: Uuid,
+        currency: Option<&Currency>,
+    ) -> Result<Vec<Transaction>, Box<dyn Error>> {
+        let mut query = transactions::table
+            .filter(transactions::deleted_at.is_null())
+            .filter(transactions::account_id.eq(account_id.to_string()))
+            .into_boxed();
+        if let Some(currency) = currency {
+            query = query.filter(transactions::currency.eq(currency.to_string()));
+        }
+
+        let transactions = query
+            .load::<TransactionSQLite>(connection)
+            .map(|transactions: Vec<TransactionSQLite>| {
+                transactions
+                    .into_iter()
+                    .map(|tx| tx.domain_model())
+                    .collect()
+            })
+            .map_err(|error| {
+                error!("Error reading all transactions: {:?}", error);
+                error
+            })?;
+        Ok(transactions)
+    }
+
+    pub fn read_all_transactions_after(
         connection: &mut SqliteConnection,
         account_id: Uuid,
         currency: &Currency,
+        after: NaiveDateTime,
     ) -> Result<Vec<Transaction>, Box<dyn Error>> {
         let transactions = transactions::table
             .filter(transactions::deleted_at.is_null())
             .filter(transactions::account_id.eq(account_id.to_string()))
             .filter(transactions::currency.eq(currency.to_string()))
+            .filter(transactions::created_at.gt(after))
+            .order(transactions::created_at.asc())
             .load::<TransactionSQLite>(connection)
             .map(|transactions: Vec<TransactionSQLite>| {
                 transactions
@@ -62,7 +152,60 @@ impl WorkerTransaction {
                     .collect()
             })
             .map_err(|error| {
-                error!("Error reading all transactions: {:?}", error);
+                error!("Error reading transactions after a date: {:?}", error);
+                error
+            })?;
+        Ok(transactions)
+    }
+
+    pub fn read_for_broker_activity(
+        connection: &mut SqliteConnection,
+        broker_activity_id: &str,
+    ) -> Result<Option<Transaction>, Box<dyn Error>> {
+        let transaction = transactions::table
+            .filter(transactions::deleted_at.is_null())
+            .filter(transactions::broker_activity_id.eq(broker_activity_id))
+            .first::<TransactionSQLite>(connection)
+            .optional()
+            .map(|transaction| transaction.map(|tx| tx.domain_model()))
+            .map_err(|error| {
+                error!("Error reading transaction of broker activity: {:?}", error);
+                error
+            })?;
+        Ok(transaction)
+    }
+
+    pub fn read_all_transactions_between(
+        connection: &mut SqliteConnection,
+        account_id: Uuid,
+        currency: &Currency,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        categories: Option<&[TransactionCategory]>,
+    ) -> Result<Vec<Transaction>, Box<dyn Error>> {
+        let mut query = transactions::table
+            .filter(transactions::deleted_at.is_null())
+            .filter(transactions::account_id.eq(account_id.to_string()))
+            .filter(transactions::currency.eq(currency.to_string()))
+            .filter(transactions::created_at.ge(from))
+            .filter(transactions::created_at.lt(to))
+            .into_boxed();
+        if let Some(categories) = categories {
+            let keys: Vec<&str> = categories.iter().map(|category| category.key()).collect();
+            query = query.filter(transactions::category.eq_any(keys));
+        }
+
+        let transactions = query
+            .order(transactions::created_at.asc())
+            .load::<TransactionSQLite>(connection)
+            .map(|transactions: Vec<TransactionSQLite>| {
+                transactions
+                    .into_iter()
+                    .map(|tx| tx.domain_model())
+                    .collect()
+            })
+            .map_err(|error| {
+                error!("Error reading transactions between dates: {:?}", error);
                 error
             })?;
         Ok(transactions)
@@ -86,6 +229,12 @@ impl WorkerTransaction {
             currency,
             TransactionCategory::Withdrawal,
         )?;
+        let tx_withdrawal_earnings = WorkerTransaction::read_all_account_transactions_for_category(
+            connection,
+            account_id,
+            currency,
+            TransactionCategory::WithdrawalEarnings,
+        )?;
 
         let tx_fee_open = WorkerTransaction::read_all_account_transactions_for_category(
             connection,
@@ -101,6 +250,13 @@ impl WorkerTransaction {
             TransactionCategory::FeeClose(Uuid::new_v4()),
         )?;
 
+        let tx_fee_rebate = WorkerTransaction::read_all_account_transactions_for_category(
+            connection,
+            account_id,
+            currency,
+            TransactionCategory::FeeRebate(Uuid::new_v4()),
+        )?;
+
         let tx_output = WorkerTransaction::read_all_account_transactions_for_category(
             connection,
             account_id,
@@ -114,13 +270,49 @@ impl WorkerTransaction {
             currency,
             TransactionCategory::PaymentFromTrade(Uuid::new_v4()),
         )?;
+
+        let tx_fee = WorkerTransaction::read_all_account_transactions_for_category(
+            connection,
+            account_id,
+            currency,
+            TransactionCategory::Fee,
+        )?;
+
+        let tx_dividend = WorkerTransaction::read_all_account_transactions_for_category(
+            connection,
+            account_id,
+            currency,
+            TransactionCategory::Dividend(None),
+        )?;
+
+        let tx_interest = WorkerTransaction::read_all_account_transactions_for_category(
+            connection,
+            account_id,
+            currency,
+            TransactionCategory::Interest,
+        )?;
+
+        let tx_adjustment = WorkerTransaction::read_all_account_transactions_for_category(
+            connection,
+            account_id,
+            currency,
+            TransactionCategory::Adjustment {
+                reason: String::new(),
+            },
+        )?;
         Ok(tx_deposit
             .into_iter()
             .chain(tx_withdrawal.into_iter())
+            .chain(tx_withdrawal_earnings.into_iter())
             .chain(tx_fee_open.into_iter())
             .chain(tx_fee_close.into_iter())
+            .chain(tx_fee_rebate.into_iter())
             .chain(tx_output.into_iter())
             .chain(tx_input.into_iter())
+            .chain(tx_fee.into_iter())
+            .chain(tx_dividend.into_iter())
+            .chain(tx_interest.into_iter())
+            .chain(tx_adjustment.into_iter())
             .collect())
     }
 
@@ -284,49 +476,97 @@ impl WorkerTransaction {
 
     pub fn read_all_transaction_excluding_current_month_and_taxes(
         connection: &mut SqliteConnection,
+        now: NaiveDateTime,
         account_id: Uuid,
         currency: &Currency,
     ) -> Result<Vec<Transaction>, Box<dyn Error>> {
         let tx_deposits = WorkerTransaction::read_all_transaction_beginning_of_the_month(
             connection,
+            now,
             account_id,
             currency,
             TransactionCategory::Deposit,
         )?;
         let tx_withdrawals = WorkerTransaction::read_all_transaction_beginning_of_the_month(
             connection,
+            now,
             account_id,
             currency,
             TransactionCategory::Withdrawal,
         )?;
+        let tx_withdrawals_earnings =
+            WorkerTransaction::read_all_transaction_beginning_of_the_month(
+                connection,
+                now,
+                account_id,
+                currency,
+                TransactionCategory::WithdrawalEarnings,
+            )?;
         let tx_outputs = WorkerTransaction::read_all_transaction_beginning_of_the_month(
             connection,
+            now,
             account_id,
             currency,
             TransactionCategory::FundTrade(Uuid::new_v4()),
         )?;
         let tx_inputs = WorkerTransaction::read_all_transaction_beginning_of_the_month(
             connection,
+            now,
             account_id,
             currency,
             TransactionCategory::PaymentFromTrade(Uuid::new_v4()),
         )?;
+        let tx_fees = WorkerTransaction::read_all_transaction_beginning_of_the_month(
+            connection,
+            now,
+            account_id,
+            currency,
+            TransactionCategory::Fee,
+        )?;
+        let tx_dividends = WorkerTransaction::read_all_transaction_beginning_of_the_month(
+            connection,
+            now,
+            account_id,
+            currency,
+            TransactionCategory::Dividend(None),
+        )?;
+        let tx_interest = WorkerTransaction::read_all_transaction_beginning_of_the_month(
+            connection,
+            now,
+            account_id,
+            currency,
+            TransactionCategory::Interest,
+        )?;
+        let tx_adjustments = WorkerTransaction::read_all_transaction_beginning_of_the_month(
+            connection,
+            now,
+            account_id,
+            currency,
+            TransactionCategory::Adjustment {
+                reason: String::new(),
+            },
+        )?;
 
         Ok(tx_deposits
             .into_iter()
             .chain(tx_withdrawals.into_iter())
+            .chain(tx_withdrawals_earnings.into_iter())
             .chain(tx_outputs.into_iter())
             .chain(tx_inputs.into_iter())
+            .chain(tx_fees.into_iter())
+            .chain(tx_dividends.into_iter())
+            .chain(tx_interest.into_iter())
+            .chain(tx_adjustments.into_iter())
             .collect())
     }
 
     fn read_all_transaction_beginning_of_the_month(
         connection: &mut SqliteConnection,
+        now: NaiveDateTime,
         account_id: Uuid,
         currency: &Currency,
         category: TransactionCategory,
     ) -> Result<Vec<Transaction>, Box<dyn Error>> {
-        let now = Utc::now().naive_utc();
         let first_day_of_month = NaiveDate::from_ymd_opt(now.year(), now.month(), 1).unwrap();
         let first_day_of_month = NaiveDateTime::new(
             first_day_of_month,
@@ -366,17 +606,32 @@ pub struct TransactionSQLite {
     pub amount: String,
     pub account_id: String,
     pub trade_id: Option<String>,
+    pub trading_vehicle_id: Option<String>,
+    pub reverses: Option<String>,
+    pub reason: Option<String>,
+    pub broker_activity_id: Option<String>,
 }
 
 impl TransactionSQLite {
     fn domain_model(&self) -> Transaction {
-        let category = TransactionCategory::parse(
+        let category = match TransactionCategory::parse(
             &self.category,
             self.trade_id
                 .clone()
                 .map(|uuid| Uuid::parse_str(&uuid).unwrap()),
         )
-        .unwrap();
+        .unwrap()
+        {
+            TransactionCategory::Dividend(_) => TransactionCategory::Dividend(
+                self.trading_vehicle_id
+                    .clone()
+                    .map(|uuid| Uuid::parse_str(&uuid).unwrap()),
+            ),
+            TransactionCategory::Adjustment { .. } => TransactionCategory::Adjustment {
+                reason: self.reason.clone().unwrap_or_default(),
+            },
+            category => category,
+        };
 
         Transaction {
             id: Uuid::parse_str(&self.id).unwrap(),
@@ -387,6 +642,11 @@ impl TransactionSQLite {
             currency: Currency::from_str(&self.currency).unwrap(),
             amount: Decimal::from_str(&self.amount).unwrap(),
             account_id: Uuid::parse_str(&self.account_id).unwrap(),
+            reverses: self
+                .reverses
+                .clone()
+                .map(|uuid| Uuid::parse_str(&uuid).unwrap()),
+            broker_activity_id: self.broker_activity_id.clone(),
         }
     }
 }
@@ -403,6 +663,10 @@ pub struct NewTransaction {
     pub amount: String,
     pub account_id: String,
     pub trade_id: Option<String>,
+    pub trading_vehicle_id: Option<String>,
+    pub reverses: Option<String>,
+    pub reason: Option<String>,
+    pub broker_activity_id: Option<String>,
 }
 
 #[cfg(test)]
@@ -464,6 +728,58 @@ mod tests {
         assert_eq!(tx.deleted_at, None);
     }
 
+    #[test]
+    fn test_create_broker_transaction() {
+        let db = create_factory();
+        let account = db
+            .account_write()
+            .create(
+                "Test Account",
+                "This is a test account",
+                Environment::Paper,
+                dec!(0.0),
+                dec!(0.0),
+            )
+            .expect("Error creating account");
+
+        let tx = db
+            .transaction_write()
+            .create_broker_transaction(
+                &account,
+                dec!(0.05),
+                &Currency::USD,
+                TransactionCategory::Fee,
+                "20230612000000000::fee",
+            )
+            .expect("Error creating transaction");
+        assert_eq!(
+            tx.broker_activity_id,
+            Some("20230612000000000::fee".to_string())
+        );
+        assert_eq!(
+            db.transaction_read()
+                .for_broker_activity("20230612000000000::fee")
+                .unwrap(),
+            Some(tx)
+        );
+        assert_eq!(
+            db.transaction_read().for_broker_activity("other").unwrap(),
+            None
+        );
+
+        // An activity can only be written once
+        assert!(db
+            .transaction_write()
+            .create_broker_transaction(
+                &account,
+                dec!(0.05),
+                &Currency::USD,
+                TransactionCategory::Fee,
+                "20230612000000000::fee",
+            )
+            .is_err());
+    }
+
     #[test]
     fn test_create_transaction_with_trade_id() {
         let db = create_factory();
@@ -497,4 +813,253 @@ mod tests {
         assert_eq!(tx.category, TransactionCategory::FundTrade(trade_id));
         assert_eq!(tx.deleted_at, None);
     }
+
+    #[test]
+    fn test_create_adjustment_with_reason() {
+        let db = create_factory();
+
+        let account = db
+            .account_write()
+            .create(
+                "Test Account 4",
+                "This is a test account",
+                Environment::Paper,
+                dec!(0.0),
+                dec!(0.0),
+            )
+            .expect("Error creating account");
+        let category = TransactionCategory::Adjustment {
+            reason: "Broker charged a fee twice".to_string(),
+        };
+        let adjustment = db
+            .transaction_write()
+            .create_transaction(&account, dec!(-2.5), &Currency::USD, category.clone())
+            .expect("Error creating transaction");
+
+        assert_eq!(adjustment.category, category);
+        assert_eq!(adjustment.amount, dec!(-2.5));
+
+        let transactions = db
+            .transaction_read()
+            .all_transactions(account.id, &Currency::USD)
+            .expect("Error reading transactions");
+        assert_eq!(transactions, vec![adjustment]);
+    }
+
+    #[test]
+    fn test_create_dividend_with_trading_vehicle() {
+        let db = create_factory();
+
+        let trading_vehicle_id = Uuid::new_v4();
+
+        // Create a new account record
+        let account = db
+            .account_write()
+            .create(
+                "Test Account 3",
+                "This is a test account",
+                Environment::Paper,
+                dec!(0.0),
+                dec!(0.0),
+            )
+            .expect("Error creating account");
+        let deposit = db
+            .transaction_write()
+            .create_transaction(
+                &account,
+                dec!(1000),
+                &Currency::USD,
+                TransactionCategory::Deposit,
+            )
+            .expect("Error creating transaction");
+        let dividend = db
+            .transaction_write()
+            .create_transaction(
+                &account,
+                dec!(12.5),
+                &Currency::USD,
+                TransactionCategory::Dividend(Some(trading_vehicle_id)),
+            )
+            .expect("Error creating transaction");
+
+        assert_eq!(
+            dividend.category,
+            TransactionCategory::Dividend(Some(trading_vehicle_id))
+        );
+        assert_eq!(dividend.amount, dec!(12.5));
+
+        let transactions = db
+            .transaction_read()
+            .all_transactions(account.id, &Currency::USD)
+            .expect("Error reading transactions");
+        assert_eq!(transactions.len(), 2);
+        assert!(transactions.contains(&deposit));
+        assert!(transactions.contains(&dividend));
+    }
+
+    #[test]
+    fn test_create_reversal() {
+        let db = create_factory();
+
+        // Create a new account record
+        let account = db
+            .account_write()
+            .create(
+                "Test Account 3",
+                "This is a test account",
+                Environment::Paper,
+                dec!(0.0),
+                dec!(0.0),
+            )
+            .expect("Error creating account");
+        let deposit = db
+            .transaction_write()
+            .create_transaction(
+                &account,
+                dec!(1000),
+                &Currency::USD,
+                TransactionCategory::Deposit,
+            )
+            .expect("Error creating transaction");
+        let reversal = db
+            .transaction_write()
+            .create_reversal(&deposit, TransactionCategory::Withdrawal)
+            .expect("Error creating reversal");
+
+        assert_eq!(deposit.reverses, None);
+        assert_eq!(reversal.reverses, Some(deposit.id));
+        assert_eq!(reversal.category, TransactionCategory::Withdrawal);
+        assert_eq!(reversal.amount, dec!(1000));
+        assert_eq!(reversal.account_id, account.id);
+
+        let transactions = db
+            .transaction_read()
+            .all_transactions(account.id, &Currency::USD)
+            .expect("Error reading transactions");
+        assert!(transactions.contains(&reversal));
+    }
+
+    #[test]
+    fn test_read_transactions_after() {
+        let mut conn = establish_connection();
+        let account_id = Uuid::new_v4();
+        let day = |day| {
+            NaiveDate::from_ymd_opt(2023, 8, day)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+        };
+        let mut create = |now, currency| {
+            WorkerTransaction::create_transaction(
+                &mut conn,
+                now,
+                account_id,
+                dec!(100),
+                currency,
+                TransactionCategory::Deposit,
+            )
+            .expect("Error creating transaction")
+        };
+
+        create(day(1), &Currency::USD);
+        create(day(2), &Currency::USD);
+        let after = create(day(3), &Currency::USD);
+        create(day(3), &Currency::EUR);
+
+        // The checkpoint itself is excluded
+        let transactions = WorkerTransaction::read_all_transactions_after(
+            &mut conn,
+            account_id,
+            &Currency::USD,
+            day(2),
+        )
+        .unwrap();
+        assert_eq!(transactions, vec![after]);
+    }
+
+    #[test]
+    fn test_read_transactions_between() {
+        let mut conn = establish_connection();
+        let account_id = Uuid::new_v4();
+        let day = |day| {
+            NaiveDate::from_ymd_opt(2023, 8, day)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+        };
+        let mut create = |now, category| {
+            WorkerTransaction::create_transaction(
+                &mut conn,
+                now,
+                account_id,
+                dec!(100),
+                &Currency::USD,
+                category,
+            )
+            .expect("Error creating transaction")
+        };
+
+        let before = create(day(1), TransactionCategory::Deposit);
+        let from = create(day(2), TransactionCategory::Deposit);
+        let funding = create(day(3), TransactionCategory::FundTrade(Uuid::new_v4()));
+        let withdrawal = create(
+            day(4) - chrono::Duration::seconds(1),
+            TransactionCategory::Withdrawal,
+        );
+        let to = create(day(4), TransactionCategory::Deposit);
+        let other_currency = WorkerTransaction::create_transaction(
+            &mut conn,
+            day(3),
+            account_id,
+            dec!(100),
+            &Currency::EUR,
+            TransactionCategory::Deposit,
+        )
+        .unwrap();
+
+        // The range includes its start and excludes its end
+        let transactions = WorkerTransaction::read_all_transactions_between(
+            &mut conn,
+            account_id,
+            &Currency::USD,
+            day(2),
+            day(4),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            transactions,
+            vec![from.clone(), funding.clone(), withdrawal.clone()]
+        );
+        assert!(!transactions.contains(&before));
+        assert!(!transactions.contains(&to));
+        assert!(!transactions.contains(&other_currency));
+
+        // Only the transactions of the categories, of any trade
+        let transactions = WorkerTransaction::read_all_transactions_between(
+            &mut conn,
+            account_id,
+            &Currency::USD,
+            day(1),
+            day(5),
+            Some(&[
+                TransactionCategory::FundTrade(Uuid::new_v4()),
+                TransactionCategory::Withdrawal,
+            ]),
+        )
+        .unwrap();
+        assert_eq!(transactions, vec![funding, withdrawal]);
+
+        // An empty range has no transactions
+        assert!(WorkerTransaction::read_all_transactions_between(
+            &mut conn,
+            account_id,
+            &Currency::USD,
+            day(2),
+            day(2),
+            None,
+        )
+        .unwrap()
+        .is_empty());
+    }
 }
@@ -1,4 +1,5 @@
 use crate::currency::Currency;
+use crate::tax_lot::LotMethod;
 use chrono::NaiveDateTime;
 use chrono::Utc;
 use rust_decimal::Decimal;
@@ -25,6 +26,40 @@ pub struct Account {
     pub environment: Environment,
     pub taxes_percentage: Decimal,
     pub earnings_percentage: Decimal,
+
+    /// Trades with a notional above this amount require an explicit confirmation
+    /// before they can be created. `None` means that no confirmation is required.
+    pub confirm_above_notional: Option<Decimal>,
+
+    /// What to do with trade prices that are not a multiple of the tick size of the trading vehicle.
+    pub tick_size_policy: TickSizePolicy,
+
+    /// Closed trades that this paper account needs before it can be promoted
+    /// to a live account. `None` means that it can be promoted at any time.
+    pub min_paper_trades_before_live: Option<u32>,
+
+    /// The paper account that this live account was promoted from.
+    pub paper_account_id: Option<Uuid>,
+
+    /// Whether the account trades on its own or hedges the positions of its parent account.
+    pub account_type: AccountType,
+
+    /// The account that this hedge account belongs to. `None` for accounts without a parent.
+    pub parent_account_id: Option<Uuid>,
+
+    /// Which opening executions are matched first when the position of a trade is closed.
+    pub lot_method: LotMethod,
+
+    /// How the fees of the fills of the trades are calculated when they are not entered manually.
+    pub fee_model: FeeModel,
+
+    /// The currency of the calculations of the account that are not given a currency, like its equity.
+    pub base_currency: Currency,
+
+    /// Submitting a trade with the same symbol, direction, entry price and quantity as another trade
+    /// submitted in the last this many seconds is rejected unless it is forced. `None` means that
+    /// duplicated trades are not checked.
+    pub duplicate_trade_window_seconds: Option<u32>,
 }
 
 /// AccountBalance entity (read-only)
@@ -92,6 +127,16 @@ impl Default for Account {
             environment: Environment::Paper,
             taxes_percentage: Decimal::default(),
             earnings_percentage: Decimal::default(),
+            confirm_above_notional: None,
+            tick_size_policy: TickSizePolicy::default(),
+            min_paper_trades_before_live: None,
+            paper_account_id: None,
+            account_type: AccountType::default(),
+            parent_account_id: None,
+            lot_method: LotMethod::default(),
+            fee_model: FeeModel::default(),
+            base_currency: Currency::default(),
+            duplicate_trade_window_seconds: None,
         }
     }
 }
@@ -119,11 +164,14 @@ impl Default for AccountBalance {
 pub enum Environment {
     Paper,
     Live,
+
+    /// Replays historical data with a clock that is moved by the caller.
+    Backtest,
 }
 
 impl Environment {
     pub fn all() -> Vec<Environment> {
-        vec![Environment::Paper, Environment::Live]
+        vec![Environment::Paper, Environment::Live, Environment::Backtest]
     }
 }
 
@@ -132,6 +180,7 @@ impl Display for Environment {
         match *self {
             Environment::Paper => write!(f, "paper"),
             Environment::Live => write!(f, "live"),
+            Environment::Backtest => write!(f, "backtest"),
         }
     }
 }
@@ -144,7 +193,133 @@ impl std::str::FromStr for Environment {
         match environment {
             "paper" => Ok(Environment::Paper),
             "live" => Ok(Environment::Live),
+            "backtest" => Ok(Environment::Backtest),
             _ => Err(EnvironmentParseError),
         }
     }
 }
+
+/// TickSizePolicy enum - how an account handles prices that are off the tick grid
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TickSizePolicy {
+    /// Trades with prices off the tick grid are not created.
+    #[default]
+    Reject,
+
+    /// Prices off the tick grid are rounded to the nearest tick.
+    Round,
+}
+
+impl Display for TickSizePolicy {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            TickSizePolicy::Reject => write!(f, "reject"),
+            TickSizePolicy::Round => write!(f, "round"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TickSizePolicyParseError;
+impl std::str::FromStr for TickSizePolicy {
+    type Err = TickSizePolicyParseError;
+    fn from_str(policy: &str) -> Result<Self, Self::Err> {
+        match policy {
+            "reject" => Ok(TickSizePolicy::Reject),
+            "round" => Ok(TickSizePolicy::Round),
+            _ => Err(TickSizePolicyParseError),
+        }
+    }
+}
+
+/// FeeModel enum - how the broker charges the fills of an account
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FeeModel {
+    /// The broker does not charge fees.
+    #[default]
+    None,
+
+    /// An amount for every share that is filled.
+    PerShare(Decimal),
+
+    /// A percentage of the notional that is filled.
+    Percent(Decimal),
+
+    /// The same amount for every fill.
+    Fixed(Decimal),
+}
+
+impl FeeModel {
+    /// The amount or the percentage of the model. `None` for accounts without fees.
+    pub fn amount(&self) -> Option<Decimal> {
+        match *self {
+            FeeModel::None => None,
+            FeeModel::PerShare(amount) | FeeModel::Percent(amount) | FeeModel::Fixed(amount) => {
+                Some(amount)
+            }
+        }
+    }
+
+    pub fn parse(model: &str, amount: Option<Decimal>) -> Result<Self, FeeModelParseError> {
+        match (model, amount) {
+            ("none", _) => Ok(FeeModel::None),
+            ("per_share", Some(amount)) => Ok(FeeModel::PerShare(amount)),
+            ("percent", Some(amount)) => Ok(FeeModel::Percent(amount)),
+            ("fixed", Some(amount)) => Ok(FeeModel::Fixed(amount)),
+            _ => Err(FeeModelParseError),
+        }
+    }
+}
+
+impl Display for FeeModel {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            FeeModel::None => write!(f, "none"),
+            FeeModel::PerShare(_) => write!(f, "per_share"),
+            FeeModel::Percent(_) => write!(f, "percent"),
+            FeeModel::Fixed(_) => write!(f, "fixed"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FeeModelParseError;
+
+/// AccountType enum - how an account relates to the other accounts
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum AccountType {
+    /// An account that trades on its own.
+    #[default]
+    Primary,
+
+    /// An account that hedges the positions of its parent account.
+    Hedge,
+}
+
+impl AccountType {
+    pub fn all() -> Vec<AccountType> {
+        vec![AccountType::Primary, AccountType::Hedge]
+    }
+}
+
+impl Display for AccountType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            AccountType::Primary => write!(f, "primary"),
+            AccountType::Hedge => write!(f, "hedge"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AccountTypeParseError;
+impl std::str::FromStr for AccountType {
+    type Err = AccountTypeParseError;
+    fn from_str(account_type: &str) -> Result<Self, Self::Err> {
+        match account_type {
+            "primary" => Ok(AccountType::Primary),
+            "hedge" => Ok(AccountType::Hedge),
+            _ => Err(AccountTypeParseError),
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use chrono::NaiveDateTime;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+/// AdvisoryThresholds entity - the limits above which the advisory check flags a trade.
+/// An advisory never blocks a trade. The thresholds are percentages between 0 and 100.
+#[derive(PartialEq, Debug, Clone)]
+pub struct AdvisoryThresholds {
+    pub id: Uuid,
+
+    // Entity timestamps
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+
+    // Entity fields
+    pub account_id: Uuid,
+
+    /// Percentage of the available capital of the account that a trade can risk.
+    pub max_risk_per_trade_percent: Decimal,
+
+    /// Percentage of the capital in open trades that the largest position can take.
+    pub max_position_concentration_percent: Decimal,
+}
+
+impl AdvisoryThresholds {
+    /// The thresholds of an account that never configured them.
+    pub fn defaults(account_id: Uuid) -> AdvisoryThresholds {
+        AdvisoryThresholds {
+            account_id,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for AdvisoryThresholds {
+    fn default() -> Self {
+        let now = Utc::now().naive_utc();
+        AdvisoryThresholds {
+            id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            account_id: Uuid::new_v4(),
+            max_risk_per_trade_percent: dec!(2),
+            max_position_concentration_percent: dec!(25),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Account, Order, Status, Trade};
+use crate::{Account, Currency, ExecutionSide, Order, Status, Trade, TradeCategory};
 use chrono::NaiveDateTime;
 use rust_decimal::Decimal;
 use std::error::Error;
@@ -32,11 +32,94 @@ impl Default for BrokerLog {
 }
 
 pub struct OrderIds {
-    pub stop: Uuid,
+    /// `None` if the broker submits the stop later, once the entry is filled.
+    pub stop: Option<Uuid>,
     pub entry: Uuid,
     pub target: Uuid,
 }
 
+/// An activity of the account reported by the broker.
+/// For example: a fill of an order or a regulatory fee charged to the account.
+#[derive(PartialEq, Debug, Clone)]
+pub struct BrokerActivity {
+    /// The id of the activity in the broker.
+    pub id: String,
+    pub category: BrokerActivityCategory,
+
+    /// The broker order id. Only present for fills.
+    pub order_id: Option<Uuid>,
+    pub side: Option<ExecutionSide>,
+    pub price: Option<Decimal>,
+    pub quantity: Option<u64>,
+
+    /// The amount of money charged to the account. Only present for fees, as a positive number.
+    pub amount: Option<Decimal>,
+    pub currency: Currency,
+    pub transaction_time: NaiveDateTime,
+}
+
+/// The result of validating a trade with the broker without placing any order.
+#[derive(PartialEq, Debug, Clone)]
+pub struct BrokerValidation {
+    /// The buying power that the trade would use if it is submitted.
+    pub buying_power_impact: Decimal,
+
+    /// The reasons why the broker would reject the trade. It is empty if the trade would be accepted.
+    pub rejections: Vec<String>,
+}
+
+impl BrokerValidation {
+    pub fn is_valid(&self) -> bool {
+        self.rejections.is_empty()
+    }
+}
+
+/// A position that the broker holds for the account.
+#[derive(PartialEq, Debug, Clone)]
+pub struct BrokerPosition {
+    pub symbol: String,
+    pub category: TradeCategory,
+
+    /// The number of shares of the position. It is positive for both long and short positions.
+    pub quantity: Decimal,
+    pub average_entry_price: Decimal,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum BrokerActivityCategory {
+    Fill,
+    Fee,
+    Other,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum BrokerErrorCode {
+    /// The broker can not replace this kind of order, it has to be canceled and placed again.
+    ReplaceNotSupported,
+
+    /// The order is not working in the broker, for example because it was never submitted.
+    OrderNotWorking,
+}
+
+/// An error of the broker that the caller can act on.
+#[derive(Debug)]
+pub struct BrokerError {
+    pub code: BrokerErrorCode,
+    pub message: String,
+}
+
+impl std::fmt::Display for BrokerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "BrokerError: {}", self.message)
+    }
+}
+
+impl Error for BrokerError {
+    fn description(&self) -> &str {
+        &self.message
+    }
+}
+
 pub trait Broker {
     fn submit_trade(
         &self,
@@ -44,6 +127,14 @@ pub trait Broker {
         account: &Account,
     ) -> Result<(BrokerLog, OrderIds), Box<dyn Error>>;
 
+    /// Validate a trade with the broker without submitting any order.
+    /// It must not change the state of the account in the broker.
+    fn validate_trade(
+        &self,
+        trade: &Trade,
+        account: &Account,
+    ) -> Result<BrokerValidation, Box<dyn Error>>;
+
     fn sync_trade(
         &self,
         trade: &Trade,
@@ -51,8 +142,8 @@ pub trait Broker {
     ) -> Result<(Status, Vec<Order>, BrokerLog), Box<dyn Error>>;
 
     /// Manually Close a trade
-    /// The target will be cancelled and a new target will be created
-    /// with the market price. The goal is to close the trade as soon as possible.
+    /// A new target will be created with the market price. The goal is to close the trade as soon as possible.
+    /// The working stop and target are canceled before with `cancel_order`.
     /// The return value is the new target order.
     fn close_trade(
         &self,
@@ -60,10 +151,41 @@ pub trait Broker {
         account: &Account,
     ) -> Result<(Order, BrokerLog), Box<dyn Error>>;
 
+    /// Close a trade with a limit order at a price.
+    /// Like `close_trade`, the working stop and target are canceled before with `cancel_order`.
+    /// The return value is the new target order, that is working until the limit is reached.
+    fn close_trade_limit(
+        &self,
+        trade: &Trade,
+        account: &Account,
+        limit_price: Decimal,
+    ) -> Result<(Order, BrokerLog), Box<dyn Error>>;
+
     // Cancel a trade that has been submitted
     // The order should not be filled
     fn cancel_trade(&self, trade: &Trade, account: &Account) -> Result<(), Box<dyn Error>>;
 
+    /// Cancel a single working order, for example the stop of a trade that is closed manually.
+    fn cancel_order(&self, order: &Order, account: &Account) -> Result<(), Box<dyn Error>>;
+
+    /// Cancel every working order of the account in the broker, including the orders
+    /// that were not created by Trust. The return value is the broker ids of the canceled orders.
+    fn cancel_all_orders(&self, account: &Account) -> Result<Vec<Uuid>, Box<dyn Error>>;
+
+    /// Replace a working order with a new price and quantity in a single request,
+    /// so the position is never left without the order in the market.
+    /// The return value is the broker id of the new order.
+    /// It fails with `BrokerErrorCode::ReplaceNotSupported` if the order can not be replaced.
+    fn replace_order(
+        &self,
+        order: &Order,
+        account: &Account,
+        new_price: Decimal,
+        new_quantity: u64,
+    ) -> Result<Uuid, Box<dyn Error>>;
+
+    /// Modify the stop of a trade. The broker may cancel the stop and place a new one,
+    /// so `replace_order` is preferred when the broker supports it.
     fn modify_stop(
         &self,
         trade: &Trade,
@@ -71,10 +193,29 @@ pub trait Broker {
         new_stop_price: Decimal,
     ) -> Result<Uuid, Box<dyn Error>>;
 
+    /// Modify the target of a trade. Like `modify_stop`, the broker may cancel the target
+    /// and place a new one.
     fn modify_target(
         &self,
         trade: &Trade,
         account: &Account,
         new_price: Decimal,
     ) -> Result<Uuid, Box<dyn Error>>;
+
+    /// Read the activities of the account between two dates.
+    /// It is used to reconcile fills and fees that are not reported with the orders.
+    fn get_account_activities(
+        &self,
+        account: &Account,
+        since: NaiveDateTime,
+        until: NaiveDateTime,
+    ) -> Result<Vec<BrokerActivity>, Box<dyn Error>>;
+
+    /// Read the buying power of the account in the broker.
+    /// It is used to find how far the balance of the account drifted from the broker.
+    fn account_buying_power(&self, account: &Account) -> Result<Decimal, Box<dyn Error>>;
+
+    /// Read the positions that the broker holds for the account.
+    /// It is used to find positions that are not tracked by a trade or trades without a position.
+    fn get_positions(&self, account: &Account) -> Result<Vec<BrokerPosition>, Box<dyn Error>>;
 }
@@ -0,0 +1,90 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use std::sync::{Arc, Mutex};
+
+/// Clock trait - where the timestamps that are written come from.
+///
+/// Trust uses the system clock. Backtests and tests use a clock that only
+/// moves when it is told to, so time windows like the current month can be
+/// crossed without waiting.
+pub trait Clock {
+    fn now(&self) -> NaiveDateTime;
+}
+
+/// The time of the system in UTC.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> NaiveDateTime {
+        Utc::now().naive_utc()
+    }
+}
+
+/// A clock that is stopped at a time until it is set or advanced.
+/// The clones share the same time, so a clone can move the clock of a facade.
+#[derive(Debug, Clone)]
+pub struct SteppableClock {
+    now: Arc<Mutex<NaiveDateTime>>,
+}
+
+impl SteppableClock {
+    pub fn new(now: NaiveDateTime) -> Self {
+        SteppableClock {
+            now: Arc::new(Mutex::new(now)),
+        }
+    }
+
+    pub fn set(&self, now: NaiveDateTime) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.lock().unwrap();
+        *now += duration;
+    }
+}
+
+impl Clock for SteppableClock {
+    fn now(&self) -> NaiveDateTime {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn start() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2023, 1, 31)
+            .unwrap()
+            .and_hms_opt(23, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_steppable_clock_does_not_move_by_itself() {
+        let clock = SteppableClock::new(start());
+
+        assert_eq!(clock.now(), start());
+        assert_eq!(clock.now(), start());
+    }
+
+    #[test]
+    fn test_steppable_clock_advance_and_set() {
+        let clock = SteppableClock::new(start());
+        let shared = clock.clone();
+
+        shared.advance(Duration::hours(2));
+        assert_eq!(
+            clock.now(),
+            NaiveDate::from_ymd_opt(2023, 2, 1)
+                .unwrap()
+                .and_hms_opt(1, 0, 0)
+                .unwrap()
+        );
+
+        shared.set(start());
+        assert_eq!(clock.now(), start());
+    }
+}
@@ -4,6 +4,7 @@ use std::fmt;
 #[derive(PartialEq, Debug, Hash, Eq, Clone, Copy)]
 #[non_exhaustive] // This enum may be extended in the future
 #[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Currency {
     #[default]
     USD,
@@ -1,12 +1,17 @@
 use crate::{
-    Account, AccountBalance, BrokerLog, Currency, Environment, Order, OrderAction, OrderCategory,
-    Rule, RuleLevel, RuleName, Status, Trade, TradeBalance, TradeCategory, TradingVehicle,
+    Account, AccountBalance, AccountType, AdvisoryThresholds, BrokerLog, Clock, Currency,
+    DistributionHistory, DistributionRules, Environment, EquitySnapshot, Execution, ExecutionSide,
+    ExecutionSource, FeeModel, Level, LevelAdjustmentRules, LevelChange, LevelTrigger, LotMethod,
+    Order, OrderAction, OrderCategory, RealizedLot, Rule, RuleLevel, RuleName, Status, Strategy,
+    ThesisRevision, TickSizePolicy, Trade, TradeBalance, TradeCategory, TradeGrade, TradingVehicle,
     TradingVehicleCategory, Transaction, TransactionCategory,
 };
+use chrono::NaiveDateTime;
 use rust_decimal::Decimal;
 use uuid::Uuid;
 
 use std::error::Error;
+use std::sync::Arc;
 
 /// Database trait with all the methods that are needed to interact with the database.
 ///
@@ -38,12 +43,44 @@ pub trait DatabaseFactory {
     fn trading_vehicle_write(&self) -> Box<dyn WriteTradingVehicleDB>;
     fn log_read(&self) -> Box<dyn ReadBrokerLogsDB>;
     fn log_write(&self) -> Box<dyn WriteBrokerLogsDB>;
+    fn execution_read(&self) -> Box<dyn ReadExecutionDB>;
+    fn execution_write(&self) -> Box<dyn WriteExecutionDB>;
+    fn equity_snapshot_read(&self) -> Box<dyn ReadEquitySnapshotDB>;
+    fn equity_snapshot_write(&self) -> Box<dyn WriteEquitySnapshotDB>;
+    fn level_read(&self) -> Box<dyn ReadLevelDB>;
+    fn level_write(&self) -> Box<dyn WriteLevelDB>;
+    fn strategy_read(&self) -> Box<dyn ReadStrategyDB>;
+    fn strategy_write(&self) -> Box<dyn WriteStrategyDB>;
+    fn distribution_read(&self) -> Box<dyn ReadDistributionDB>;
+    fn distribution_write(&self) -> Box<dyn WriteDistributionDB>;
+    fn trade_grade_read(&self) -> Box<dyn ReadTradeGradeDB>;
+    fn trade_grade_write(&self) -> Box<dyn WriteTradeGradeDB>;
+    fn realized_lot_read(&self) -> Box<dyn ReadRealizedLotDB>;
+    fn realized_lot_write(&self) -> Box<dyn WriteRealizedLotDB>;
+    fn advisory_read(&self) -> Box<dyn ReadAdvisoryDB>;
+    fn advisory_write(&self) -> Box<dyn WriteAdvisoryDB>;
+
+    /// The clock of the timestamps of the transactions, the orders and the level changes.
+    fn clock(&self) -> Arc<dyn Clock>;
+    fn set_clock(&mut self, clock: Arc<dyn Clock>);
+
+    /// Starts a database transaction. Everything that is written until the
+    /// transaction is committed or rolled back is written all or nothing.
+    fn begin_transaction(&self) -> Result<(), Box<dyn Error>>;
+    fn commit_transaction(&self) -> Result<(), Box<dyn Error>>;
+    fn rollback_transaction(&self) -> Result<(), Box<dyn Error>>;
 }
 // TODO: Rename
 pub trait AccountRead {
     fn for_name(&mut self, name: &str) -> Result<Account, Box<dyn Error>>;
     fn id(&mut self, id: Uuid) -> Result<Account, Box<dyn Error>>;
     fn all(&mut self) -> Result<Vec<Account>, Box<dyn Error>>;
+
+    /// The accounts that are linked to a parent account.
+    fn children_of(&mut self, parent_id: Uuid) -> Result<Vec<Account>, Box<dyn Error>>;
+
+    /// The parent of an account. `None` if the account does not have a parent.
+    fn parent_of(&mut self, account_id: Uuid) -> Result<Option<Account>, Box<dyn Error>>;
 }
 
 pub trait AccountWrite {
@@ -55,6 +92,75 @@ pub trait AccountWrite {
         taxes_percentage: Decimal,
         earnings_percentage: Decimal,
     ) -> Result<Account, Box<dyn Error>>;
+
+    fn update_confirm_above_notional(
+        &mut self,
+        account: &Account,
+        notional: Option<Decimal>,
+    ) -> Result<Account, Box<dyn Error>>;
+
+    fn update_tick_size_policy(
+        &mut self,
+        account: &Account,
+        policy: TickSizePolicy,
+    ) -> Result<Account, Box<dyn Error>>;
+
+    fn update_min_paper_trades_before_live(
+        &mut self,
+        account: &Account,
+        min_trades: Option<u32>,
+    ) -> Result<Account, Box<dyn Error>>;
+
+    fn update_lot_method(
+        &mut self,
+        account: &Account,
+        method: LotMethod,
+    ) -> Result<Account, Box<dyn Error>>;
+
+    fn update_fee_model(
+        &mut self,
+        account: &Account,
+        model: FeeModel,
+    ) -> Result<Account, Box<dyn Error>>;
+
+    fn update_base_currency(
+        &mut self,
+        account: &Account,
+        currency: &Currency,
+    ) -> Result<Account, Box<dyn Error>>;
+
+    fn update_duplicate_trade_window(
+        &mut self,
+        account: &Account,
+        window_seconds: Option<u32>,
+    ) -> Result<Account, Box<dyn Error>>;
+
+    /// Creates a live account that is linked to the paper account where it was validated.
+    fn create_live(
+        &mut self,
+        name: &str,
+        description: &str,
+        taxes_percentage: Decimal,
+        earnings_percentage: Decimal,
+        paper_account: &Account,
+    ) -> Result<Account, Box<dyn Error>>;
+
+    /// Creates an account linked to a parent account, for example a hedge account.
+    #[allow(clippy::too_many_arguments)]
+    fn create_with_hierarchy(
+        &mut self,
+        name: &str,
+        description: &str,
+        environment: Environment,
+        taxes_percentage: Decimal,
+        earnings_percentage: Decimal,
+        account_type: AccountType,
+        parent_account_id: Option<Uuid>,
+    ) -> Result<Account, Box<dyn Error>>;
+
+    /// Moves the transactions, trades, rules and realized lots of an account to another account.
+    /// The children of the source are linked to the target, and the source and its balances are deleted.
+    fn merge_into(&mut self, source: &Account, target: &Account) -> Result<(), Box<dyn Error>>;
 }
 
 pub trait AccountBalanceRead {
@@ -89,10 +195,12 @@ pub trait OrderRead {
 }
 
 pub trait OrderWrite {
+    #[allow(clippy::too_many_arguments)]
     fn create(
         &mut self,
         trading_vehicle: &TradingVehicle,
         quantity: i64,
+        quantity_decimal: Option<Decimal>,
         price: Decimal,
         currency: &Currency,
         action: &OrderAction,
@@ -155,6 +263,39 @@ pub trait ReadTransactionDB {
         account_id: Uuid,
         currency: &Currency,
     ) -> Result<Vec<Transaction>, Box<dyn Error>>;
+
+    /// The transactions of an account in a currency, or in every currency without one.
+    fn all_account_transactions(
+        &mut self,
+        account_id: Uuid,
+        currency: Option<&Currency>,
+    ) -> Result<Vec<Transaction>, Box<dyn Error>>;
+
+    /// The transactions of an account in a currency created after `after`, from the oldest to the newest.
+    fn all_transactions_after(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+        after: NaiveDateTime,
+    ) -> Result<Vec<Transaction>, Box<dyn Error>>;
+
+    /// The transactions of an account created in `[from, to)`, from the oldest to the newest.
+    /// With categories, only the transactions of those categories are read. The trade of a
+    /// category is ignored, so `FundTrade` of any trade matches every funding.
+    fn between(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        categories: Option<&[TransactionCategory]>,
+    ) -> Result<Vec<Transaction>, Box<dyn Error>>;
+
+    /// The transaction written for an activity of the broker, if any.
+    fn for_broker_activity(
+        &mut self,
+        broker_activity_id: &str,
+    ) -> Result<Option<Transaction>, Box<dyn Error>>;
 }
 
 pub trait WriteTransactionDB {
@@ -165,6 +306,23 @@ pub trait WriteTransactionDB {
         currency: &Currency,
         category: TransactionCategory,
     ) -> Result<Transaction, Box<dyn Error>>;
+
+    /// Creates a transaction for an activity of the broker. An activity can only be written once.
+    fn create_broker_transaction(
+        &mut self,
+        account: &Account,
+        amount: Decimal,
+        currency: &Currency,
+        category: TransactionCategory,
+        broker_activity_id: &str,
+    ) -> Result<Transaction, Box<dyn Error>>;
+
+    /// Creates a transaction with the same amount of the given transaction that compensates it.
+    fn create_reversal(
+        &mut self,
+        transaction: &Transaction,
+        category: TransactionCategory,
+    ) -> Result<Transaction, Box<dyn Error>>;
 }
 
 // Trade DB
@@ -182,15 +340,77 @@ pub trait ReadTradeDB {
         status: Status,
     ) -> Result<Vec<Trade>, Box<dyn Error>>;
 
+    /// Reads a page of the trades with a status, ordered by creation date.
+    /// The dates are exclusive. It also returns the total number of trades that match the filters.
+    fn read_trades_with_status_paged(
+        &mut self,
+        account_id: Uuid,
+        status: Status,
+        limit: u32,
+        offset: u32,
+        created_after: Option<NaiveDateTime>,
+        created_before: Option<NaiveDateTime>,
+    ) -> Result<(Vec<Trade>, u64), Box<dyn Error>>;
+
     fn read_trade(&mut self, id: Uuid) -> Result<Trade, Box<dyn Error>>;
+
+    /// Reads the trade of an account with a reference. References are unique per account.
+    /// Returns None if no trade of the account uses the reference.
+    fn trade_by_reference(
+        &mut self,
+        account_id: Uuid,
+        reference: &str,
+    ) -> Result<Option<Trade>, Box<dyn Error>>;
+
+    /// The trades of an account on a symbol with any of the statuses, from the newest to the oldest.
+    fn read_trades_for_symbol(
+        &mut self,
+        account_id: Uuid,
+        symbol: &str,
+        statuses: &[Status],
+    ) -> Result<Vec<Trade>, Box<dyn Error>>;
+
+    /// The trades of an account closed by the stop or the target, from the newest to the oldest close.
+    fn read_closed_trades(&mut self, account_id: Uuid) -> Result<Vec<Trade>, Box<dyn Error>>;
+
+    /// The trades of an account closed by the stop or the target from a date until another one,
+    /// from the newest to the oldest close. The first date is inclusive and the last one exclusive.
+    fn read_closed_trades_between(
+        &mut self,
+        account_id: Uuid,
+        closed_from: NaiveDateTime,
+        closed_until: NaiveDateTime,
+    ) -> Result<Vec<Trade>, Box<dyn Error>>;
+
+    /// The previous theses of a trade, from the oldest to the newest.
+    fn thesis_history(&mut self, trade_id: Uuid) -> Result<Vec<ThesisRevision>, Box<dyn Error>>;
 }
 
+#[derive(Debug)]
 pub struct DraftTrade {
     pub account: Account,
     pub trading_vehicle: TradingVehicle,
     pub quantity: i64,
+    /// Fractional quantity for crypto trading vehicles. When it is set it is
+    /// used instead of `quantity`.
+    pub quantity_decimal: Option<Decimal>,
     pub currency: Currency,
     pub category: TradeCategory,
+    pub strategy_id: Option<Uuid>,
+    /// Optional human-readable reference, unique per account.
+    pub reference: Option<String>,
+    /// Optional reason why the trade is taken.
+    pub thesis: Option<String>,
+    /// Optional market conditions around the trade.
+    pub context: Option<String>,
+}
+
+impl DraftTrade {
+    /// The quantity of the trade with full precision.
+    pub fn decimal_quantity(&self) -> Decimal {
+        self.quantity_decimal
+            .unwrap_or_else(|| Decimal::from(self.quantity))
+    }
 }
 
 pub trait WriteTradeDB {
@@ -207,6 +427,27 @@ pub trait WriteTradeDB {
         status: Status,
         trade: &Trade,
     ) -> Result<Trade, Box<dyn Error>>;
+
+    /// Replaces the thesis and the context of a trade.
+    /// The previous ones are kept in the thesis history of the trade.
+    fn update_trade_thesis(
+        &mut self,
+        trade: &Trade,
+        thesis: Option<String>,
+        context: Option<String>,
+    ) -> Result<Trade, Box<dyn Error>>;
+
+    fn update_trade_sector(
+        &mut self,
+        trade: &Trade,
+        sector: Option<String>,
+    ) -> Result<Trade, Box<dyn Error>>;
+
+    fn update_trade_last_synced_at(
+        &mut self,
+        trade: &Trade,
+        synced_at: NaiveDateTime,
+    ) -> Result<Trade, Box<dyn Error>>;
 }
 
 pub trait WriteAccountBalanceDB {
@@ -251,6 +492,8 @@ pub trait ReadTradingVehicleDB {
 }
 
 pub trait WriteTradingVehicleDB {
+    /// Returns the existing trading vehicle with the same symbol, category and broker
+    /// instead of creating a duplicate.
     fn create_trading_vehicle(
         &mut self,
         symbol: &str,
@@ -258,6 +501,18 @@ pub trait WriteTradingVehicleDB {
         category: &TradingVehicleCategory,
         broker: &str,
     ) -> Result<TradingVehicle, Box<dyn Error>>;
+
+    fn update_quote_currency(
+        &mut self,
+        trading_vehicle: &TradingVehicle,
+        currency: Option<Currency>,
+    ) -> Result<TradingVehicle, Box<dyn Error>>;
+
+    fn update_tick_size(
+        &mut self,
+        trading_vehicle: &TradingVehicle,
+        tick_size: Option<Decimal>,
+    ) -> Result<TradingVehicle, Box<dyn Error>>;
 }
 
 pub trait WriteBrokerLogsDB {
@@ -268,3 +523,218 @@ pub trait ReadBrokerLogsDB {
     fn read_all_logs_for_trade(&mut self, trade_id: Uuid)
         -> Result<Vec<BrokerLog>, Box<dyn Error>>;
 }
+
+pub struct DraftExecution {
+    pub broker_execution_id: Option<String>,
+    pub trade_id: Uuid,
+    pub order_id: Uuid,
+    pub side: ExecutionSide,
+    pub price: Decimal,
+    pub quantity: u64,
+    pub executed_at: NaiveDateTime,
+    pub source: ExecutionSource,
+}
+
+impl DraftExecution {
+    /// The key that identifies a fill, so a fill that is reported twice is written once.
+    /// It is the id of the execution in the broker or, without it, the order, price,
+    /// quantity and time of the fill.
+    pub fn idempotency_key(&self) -> String {
+        match &self.broker_execution_id {
+            Some(id) => format!("broker:{}", id),
+            None => format!(
+                "fill:{}:{}:{}:{}",
+                self.order_id,
+                self.price.normalize(),
+                self.quantity,
+                self.executed_at.format("%Y-%m-%dT%H:%M:%S%.f")
+            ),
+        }
+    }
+}
+
+pub trait WriteExecutionDB {
+    fn create_execution(&mut self, draft: &DraftExecution) -> Result<Execution, Box<dyn Error>>;
+}
+
+pub trait ReadExecutionDB {
+    fn read_executions_for_trade(
+        &mut self,
+        trade_id: Uuid,
+    ) -> Result<Vec<Execution>, Box<dyn Error>>;
+
+    /// Reads the execution that was written for a draft with the same idempotency key.
+    fn execution_for_key(
+        &mut self,
+        idempotency_key: &str,
+    ) -> Result<Option<Execution>, Box<dyn Error>>;
+}
+
+pub trait WriteEquitySnapshotDB {
+    fn create_equity_snapshot(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+        timestamp: NaiveDateTime,
+        balance: Decimal,
+    ) -> Result<EquitySnapshot, Box<dyn Error>>;
+
+    fn delete_equity_snapshots(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+    ) -> Result<(), Box<dyn Error>>;
+}
+
+pub trait ReadEquitySnapshotDB {
+    fn read_equity_snapshots(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+    ) -> Result<Vec<EquitySnapshot>, Box<dyn Error>>;
+}
+
+pub trait WriteLevelDB {
+    fn create_level(
+        &mut self,
+        account_id: Uuid,
+        current_level: u8,
+    ) -> Result<Level, Box<dyn Error>>;
+
+    fn update_level(&mut self, level: &Level, new_level: u8) -> Result<Level, Box<dyn Error>>;
+
+    fn create_level_change(
+        &mut self,
+        account_id: Uuid,
+        old_level: u8,
+        new_level: u8,
+        reason: &str,
+        trigger: &LevelTrigger,
+    ) -> Result<LevelChange, Box<dyn Error>>;
+
+    fn update_level_adjustment_rules(
+        &mut self,
+        level: &Level,
+        rules: &LevelAdjustmentRules,
+    ) -> Result<LevelAdjustmentRules, Box<dyn Error>>;
+}
+
+pub trait ReadLevelDB {
+    fn level_for_account(&mut self, account_id: Uuid) -> Result<Level, Box<dyn Error>>;
+
+    fn level_changes_for_account(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Vec<LevelChange>, Box<dyn Error>>;
+
+    fn level_adjustment_rules(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<LevelAdjustmentRules, Box<dyn Error>>;
+}
+
+pub trait WriteStrategyDB {
+    fn create_strategy(
+        &mut self,
+        name: &str,
+        description: &str,
+        version: u16,
+        entry_description: &str,
+        stop_description: &str,
+        target_description: &str,
+    ) -> Result<Strategy, Box<dyn Error>>;
+}
+
+pub trait ReadStrategyDB {
+    fn read_strategy(&mut self, id: Uuid) -> Result<Strategy, Box<dyn Error>>;
+    fn read_all_strategies(&mut self) -> Result<Vec<Strategy>, Box<dyn Error>>;
+}
+
+pub trait WriteDistributionDB {
+    fn create_distribution_rules(
+        &mut self,
+        account_id: Uuid,
+        earnings_percent: Decimal,
+        tax_percent: Decimal,
+        reinvestment_percent: Decimal,
+    ) -> Result<DistributionRules, Box<dyn Error>>;
+
+    fn update_distribution_rules(
+        &mut self,
+        rules: &DistributionRules,
+        earnings_percent: Decimal,
+        tax_percent: Decimal,
+        reinvestment_percent: Decimal,
+    ) -> Result<DistributionRules, Box<dyn Error>>;
+
+    fn create_distribution_history(
+        &mut self,
+        account_id: Uuid,
+        trade_id: Uuid,
+        original_amount: Decimal,
+        earnings_amount: Decimal,
+        tax_amount: Decimal,
+        reinvestment_amount: Decimal,
+    ) -> Result<DistributionHistory, Box<dyn Error>>;
+}
+
+pub trait ReadDistributionDB {
+    fn distribution_rules(&mut self, account_id: Uuid)
+        -> Result<DistributionRules, Box<dyn Error>>;
+
+    fn distribution_history(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Vec<DistributionHistory>, Box<dyn Error>>;
+}
+
+// Advisory thresholds
+pub trait WriteAdvisoryDB {
+    fn create_advisory_thresholds(
+        &mut self,
+        account_id: Uuid,
+        max_risk_per_trade_percent: Decimal,
+        max_position_concentration_percent: Decimal,
+    ) -> Result<AdvisoryThresholds, Box<dyn Error>>;
+
+    fn update_advisory_thresholds(
+        &mut self,
+        thresholds: &AdvisoryThresholds,
+        max_risk_per_trade_percent: Decimal,
+        max_position_concentration_percent: Decimal,
+    ) -> Result<AdvisoryThresholds, Box<dyn Error>>;
+}
+
+pub trait ReadAdvisoryDB {
+    /// Returns None if the account never configured its thresholds.
+    fn advisory_thresholds(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Option<AdvisoryThresholds>, Box<dyn Error>>;
+}
+
+// Trade grades
+pub trait WriteTradeGradeDB {
+    fn create_trade_grade(&mut self, grade: &TradeGrade) -> Result<TradeGrade, Box<dyn Error>>;
+}
+
+pub trait ReadTradeGradeDB {
+    fn trade_grade(&mut self, trade_id: Uuid) -> Result<TradeGrade, Box<dyn Error>>;
+}
+
+// Tax lots
+pub trait WriteRealizedLotDB {
+    fn create_realized_lot(&mut self, lot: &RealizedLot) -> Result<RealizedLot, Box<dyn Error>>;
+
+    fn delete_realized_lots(&mut self, trade_id: Uuid) -> Result<(), Box<dyn Error>>;
+}
+
+pub trait ReadRealizedLotDB {
+    fn realized_lots_for_trade(
+        &mut self,
+        trade_id: Uuid,
+    ) -> Result<Vec<RealizedLot>, Box<dyn Error>>;
+
+    /// The realized lots of an account, from the oldest to the newest close.
+    fn realized_lots(&mut self, account_id: Uuid) -> Result<Vec<RealizedLot>, Box<dyn Error>>;
+}
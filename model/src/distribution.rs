@@ -0,0 +1,70 @@
+use chrono::NaiveDateTime;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+/// DistributionRules entity - how the profit of a closed trade is split.
+/// The percentages are fractions of the profit and they sum 1.
+#[derive(PartialEq, Debug, Clone)]
+pub struct DistributionRules {
+    pub id: Uuid,
+
+    // Entity timestamps
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+
+    // Entity fields
+    pub account_id: Uuid,
+
+    /// Part of the profit that is earmarked to be withdrawn as earnings.
+    pub earnings_percent: Decimal,
+
+    /// Part of the profit that is earmarked to pay taxes.
+    pub tax_percent: Decimal,
+
+    /// Part of the profit that is kept in the account to be traded again.
+    pub reinvestment_percent: Decimal,
+}
+
+impl Default for DistributionRules {
+    fn default() -> Self {
+        let now = Utc::now().naive_utc();
+        DistributionRules {
+            id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            account_id: Uuid::new_v4(),
+            earnings_percent: dec!(0),
+            tax_percent: dec!(0),
+            reinvestment_percent: dec!(1),
+        }
+    }
+}
+
+/// DistributionHistory entity - an entry of the profits that have been distributed.
+/// It is only written when all the legs of the distribution have been written.
+#[derive(PartialEq, Debug, Clone)]
+pub struct DistributionHistory {
+    pub id: Uuid,
+
+    // Entity timestamps
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+
+    // Entity fields
+    pub account_id: Uuid,
+    pub trade_id: Uuid,
+
+    /// The profit of the trade that has been distributed.
+    pub original_amount: Decimal,
+    pub earnings_amount: Decimal,
+    pub tax_amount: Decimal,
+    pub reinvestment_amount: Decimal,
+
+    /// The moment when the profit was distributed.
+    pub distributed_at: NaiveDateTime,
+}
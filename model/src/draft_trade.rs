@@ -0,0 +1,238 @@
+use crate::{Account, Currency, DraftTrade, TradeCategory, TradingVehicle, MAX_THESIS_LENGTH};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::error::Error;
+use uuid::Uuid;
+
+/// Builds a `DraftTrade` with only the fields that are known, and validates it before the trade is created.
+#[derive(Debug, Clone)]
+pub struct DraftTradeBuilder {
+    account: Account,
+    trading_vehicle: TradingVehicle,
+    quantity: i64,
+    quantity_decimal: Option<Decimal>,
+    currency: Currency,
+    category: TradeCategory,
+    strategy_id: Option<Uuid>,
+    reference: Option<String>,
+    thesis: Option<String>,
+}
+
+impl DraftTradeBuilder {
+    /// A long trade in USD without quantity and without metadata.
+    pub fn new(account: Account, trading_vehicle: TradingVehicle) -> Self {
+        DraftTradeBuilder {
+            account,
+            trading_vehicle,
+            quantity: 0,
+            quantity_decimal: None,
+            currency: Currency::default(),
+            category: TradeCategory::default(),
+            strategy_id: None,
+            reference: None,
+            thesis: None,
+        }
+    }
+
+    pub fn quantity(mut self, quantity: i64) -> Self {
+        self.quantity = quantity;
+        self
+    }
+
+    /// Fractional quantity for crypto trading vehicles. It is used instead of `quantity`.
+    pub fn quantity_decimal(mut self, quantity: Decimal) -> Self {
+        self.quantity_decimal = Some(quantity);
+        self
+    }
+
+    pub fn currency(mut self, currency: Currency) -> Self {
+        self.currency = currency;
+        self
+    }
+
+    pub fn category(mut self, category: TradeCategory) -> Self {
+        self.category = category;
+        self
+    }
+
+    pub fn strategy(mut self, strategy_id: Uuid) -> Self {
+        self.strategy_id = Some(strategy_id);
+        self
+    }
+
+    pub fn reference(mut self, reference: &str) -> Self {
+        self.reference = Some(reference.to_string());
+        self
+    }
+
+    pub fn thesis(mut self, thesis: &str) -> Self {
+        self.thesis = Some(thesis.to_string());
+        self
+    }
+
+    /// Validates that the quantity is positive, that the reference is not blank and that
+    /// the thesis is not longer than `MAX_THESIS_LENGTH` characters.
+    pub fn build(self) -> Result<DraftTrade, Box<DraftTradeError>> {
+        let quantity = self
+            .quantity_decimal
+            .unwrap_or_else(|| Decimal::from(self.quantity));
+        if quantity <= dec!(0) {
+            return Err(Box::new(DraftTradeError {
+                code: DraftTradeErrorCode::QuantityMustBePositive,
+                message: format!(
+                    "The quantity of a trade must be positive and it is {}",
+                    quantity
+                ),
+            }));
+        }
+        if let Some(reference) = &self.reference {
+            validate_reference(reference)?;
+        }
+        if let Some(thesis) = &self.thesis {
+            validate_thesis(thesis)?;
+        }
+
+        Ok(DraftTrade {
+            account: self.account,
+            trading_vehicle: self.trading_vehicle,
+            quantity: self.quantity,
+            quantity_decimal: self.quantity_decimal,
+            currency: self.currency,
+            category: self.category,
+            strategy_id: self.strategy_id,
+            reference: self.reference,
+            thesis: self.thesis,
+            context: None,
+        })
+    }
+}
+
+/// Validates that the reference of a trade is not blank.
+pub fn validate_reference(reference: &str) -> Result<(), Box<DraftTradeError>> {
+    if reference.trim().is_empty() {
+        return Err(Box::new(DraftTradeError {
+            code: DraftTradeErrorCode::ReferenceNotValid,
+            message: "The reference of a trade can not be blank".to_string(),
+        }));
+    }
+    Ok(())
+}
+
+/// Validates that the thesis of a trade is not longer than `MAX_THESIS_LENGTH` characters.
+pub fn validate_thesis(thesis: &str) -> Result<(), Box<DraftTradeError>> {
+    let length = thesis.chars().count();
+    if length > MAX_THESIS_LENGTH {
+        return Err(Box::new(DraftTradeError {
+            code: DraftTradeErrorCode::ThesisTooLong,
+            message: format!(
+                "The thesis of a trade can have at most {} characters and it has {}",
+                MAX_THESIS_LENGTH, length
+            ),
+        }));
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DraftTradeErrorCode {
+    QuantityMustBePositive,
+    ReferenceNotValid,
+    ThesisTooLong,
+}
+
+/// Why a `DraftTradeBuilder` could not build a draft.
+#[derive(Debug, PartialEq)]
+pub struct DraftTradeError {
+    pub code: DraftTradeErrorCode,
+    pub message: String,
+}
+
+impl std::fmt::Display for DraftTradeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "DraftTradeError: {}", self.message)
+    }
+}
+
+impl Error for DraftTradeError {
+    fn description(&self) -> &str {
+        &self.message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builder() -> DraftTradeBuilder {
+        DraftTradeBuilder::new(Account::default(), TradingVehicle::default())
+    }
+
+    #[test]
+    fn test_build_draft_trade() {
+        let strategy_id = Uuid::new_v4();
+
+        let draft = builder()
+            .quantity(100)
+            .currency(Currency::EUR)
+            .category(TradeCategory::Short)
+            .strategy(strategy_id)
+            .reference("AAPL-2024-001")
+            .thesis("Breakout above the range")
+            .build()
+            .unwrap();
+
+        assert_eq!(draft.quantity, 100);
+        assert_eq!(draft.quantity_decimal, None);
+        assert_eq!(draft.currency, Currency::EUR);
+        assert_eq!(draft.category, TradeCategory::Short);
+        assert_eq!(draft.strategy_id, Some(strategy_id));
+        assert_eq!(draft.reference, Some("AAPL-2024-001".to_string()));
+        assert_eq!(draft.thesis, Some("Breakout above the range".to_string()));
+    }
+
+    #[test]
+    fn test_build_draft_trade_with_defaults() {
+        let draft = builder().quantity_decimal(dec!(0.5)).build().unwrap();
+
+        assert_eq!(draft.decimal_quantity(), dec!(0.5));
+        assert_eq!(draft.currency, Currency::USD);
+        assert_eq!(draft.category, TradeCategory::Long);
+        assert_eq!(draft.strategy_id, None);
+        assert_eq!(draft.reference, None);
+        assert_eq!(draft.thesis, None);
+    }
+
+    #[test]
+    fn test_build_draft_trade_with_zero_quantity() {
+        let error = builder().build().unwrap_err();
+        assert_eq!(error.code, DraftTradeErrorCode::QuantityMustBePositive);
+
+        let error = builder().quantity(-5).build().unwrap_err();
+        assert_eq!(error.code, DraftTradeErrorCode::QuantityMustBePositive);
+    }
+
+    #[test]
+    fn test_build_draft_trade_with_blank_reference() {
+        let error = builder().quantity(1).reference("  ").build().unwrap_err();
+
+        assert_eq!(error.code, DraftTradeErrorCode::ReferenceNotValid);
+    }
+
+    #[test]
+    fn test_build_draft_trade_with_thesis_too_long() {
+        let thesis = "a".repeat(MAX_THESIS_LENGTH + 1);
+
+        let error = builder().quantity(1).thesis(&thesis).build().unwrap_err();
+
+        assert_eq!(error.code, DraftTradeErrorCode::ThesisTooLong);
+        assert_eq!(
+            error.message,
+            "The thesis of a trade can have at most 200 characters and it has 201"
+        );
+        assert!(builder()
+            .quantity(1)
+            .thesis(&"a".repeat(MAX_THESIS_LENGTH))
+            .build()
+            .is_ok());
+    }
+}
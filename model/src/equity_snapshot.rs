@@ -0,0 +1,44 @@
+use crate::Currency;
+use chrono::NaiveDateTime;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+/// EquitySnapshot entity - represents the balance of an account at a given moment.
+/// Snapshots are checkpoints of the equity curve, so it does not need to be
+/// recalculated from all the transactions of the account.
+#[derive(PartialEq, Debug, Clone)]
+pub struct EquitySnapshot {
+    pub id: Uuid,
+
+    // Entity timestamps
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+
+    // Entity fields
+    pub account_id: Uuid,
+    pub currency: Currency,
+
+    /// The moment of the equity curve that the snapshot represents.
+    pub timestamp: NaiveDateTime,
+
+    /// The balance of the account at that moment.
+    pub balance: Decimal,
+}
+
+impl Default for EquitySnapshot {
+    fn default() -> Self {
+        let now = Utc::now().naive_utc();
+        EquitySnapshot {
+            id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            account_id: Uuid::new_v4(),
+            currency: Currency::default(),
+            timestamp: now,
+            balance: Decimal::default(),
+        }
+    }
+}
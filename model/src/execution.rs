@@ -0,0 +1,163 @@
+use chrono::NaiveDateTime;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use std::fmt;
+use uuid::Uuid;
+
+/// Execution entity - represents a single fill of an order reported by the broker.
+/// An order can be filled with one or more executions.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Execution {
+    pub id: Uuid,
+
+    // Entity timestamps
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+
+    // Entity fields
+    /// The id of the execution in the broker, if the broker reported one.
+    pub broker_execution_id: Option<String>,
+
+    /// The trade that the execution belongs to.
+    pub trade_id: Uuid,
+
+    /// The order that was filled by this execution.
+    pub order_id: Uuid,
+
+    pub side: ExecutionSide,
+    pub price: Decimal,
+    pub quantity: u64,
+
+    /// When the execution happened in the market.
+    pub executed_at: NaiveDateTime,
+
+    /// Where the execution comes from.
+    pub source: ExecutionSource,
+}
+
+impl Default for Execution {
+    fn default() -> Self {
+        let now = Utc::now().naive_utc();
+        Execution {
+            id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            broker_execution_id: None,
+            trade_id: Uuid::new_v4(),
+            order_id: Uuid::new_v4(),
+            side: ExecutionSide::Buy,
+            price: Decimal::default(),
+            quantity: 0,
+            executed_at: now,
+            source: ExecutionSource::Broker,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ExecutionSide {
+    Buy,
+    Sell,
+}
+
+impl fmt::Display for ExecutionSide {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExecutionSide::Buy => write!(f, "buy"),
+            ExecutionSide::Sell => write!(f, "sell"),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct ExecutionSideParseError;
+
+impl std::str::FromStr for ExecutionSide {
+    type Err = ExecutionSideParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "buy" => Ok(ExecutionSide::Buy),
+            "sell" => Ok(ExecutionSide::Sell),
+            _ => Err(ExecutionSideParseError),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ExecutionSource {
+    /// The execution was reported by the broker.
+    Broker,
+    /// The execution was backfilled by hand, for example from a CSV import
+    /// of trades that were executed outside of Trust.
+    Manual,
+}
+
+impl fmt::Display for ExecutionSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExecutionSource::Broker => write!(f, "broker"),
+            ExecutionSource::Manual => write!(f, "manual"),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct ExecutionSourceParseError;
+
+impl std::str::FromStr for ExecutionSource {
+    type Err = ExecutionSourceParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "broker" => Ok(ExecutionSource::Broker),
+            "manual" => Ok(ExecutionSource::Manual),
+            _ => Err(ExecutionSourceParseError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_execution_side_from_string() {
+        assert_eq!(ExecutionSide::from_str("buy"), Ok(ExecutionSide::Buy));
+        assert_eq!(ExecutionSide::from_str("sell"), Ok(ExecutionSide::Sell));
+        assert_eq!(
+            ExecutionSide::from_str("short"),
+            Err(ExecutionSideParseError)
+        );
+    }
+
+    #[test]
+    fn test_execution_side_to_string() {
+        assert_eq!(ExecutionSide::Buy.to_string(), "buy");
+        assert_eq!(ExecutionSide::Sell.to_string(), "sell");
+    }
+
+    #[test]
+    fn test_execution_source_from_string() {
+        assert_eq!(
+            ExecutionSource::from_str("broker"),
+            Ok(ExecutionSource::Broker)
+        );
+        assert_eq!(
+            ExecutionSource::from_str("manual"),
+            Ok(ExecutionSource::Manual)
+        );
+        assert_eq!(
+            ExecutionSource::from_str("unknown"),
+            Err(ExecutionSourceParseError)
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use chrono::NaiveDateTime;
+use chrono::Utc;
+use std::error::Error;
+use uuid::Uuid;
+
+/// TradeGrade entity - how well a closed trade followed the process.
+/// Every score goes from 0 to 100 and the overall score is weighted with GradingWeightsPermille.
+#[derive(PartialEq, Debug, Clone)]
+pub struct TradeGrade {
+    pub id: Uuid,
+
+    // Entity timestamps
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+
+    // Entity fields
+    pub trade_id: Uuid,
+    pub risk_management: u8,
+    pub execution: u8,
+    pub outcome: u8,
+    pub documentation: u8,
+    pub overall: u8,
+}
+
+impl Default for TradeGrade {
+    fn default() -> Self {
+        let now = Utc::now().naive_utc();
+        TradeGrade {
+            id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            trade_id: Uuid::new_v4(),
+            risk_management: 0,
+            execution: 0,
+            outcome: 0,
+            documentation: 0,
+            overall: 0,
+        }
+    }
+}
+
+/// GradingWeightsPermille - how much every area counts in the grade of a trade.
+/// The weights are parts per thousand of the grade and they sum exactly 1000.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct GradingWeightsPermille {
+    /// Respecting the stop and the size of the trade.
+    pub risk_management: u16,
+
+    /// Getting in and out of the trade at the planned prices.
+    pub execution: u16,
+
+    /// The result of the trade in units of risk.
+    pub outcome: u16,
+
+    /// Recording why the trade was taken.
+    pub documentation: u16,
+}
+
+impl Default for GradingWeightsPermille {
+    fn default() -> Self {
+        GradingWeightsPermille {
+            risk_management: 300,
+            execution: 200,
+            outcome: 300,
+            documentation: 200,
+        }
+    }
+}
+
+impl GradingWeightsPermille {
+    pub const TOTAL: u32 = 1000;
+
+    fn weights(&self) -> [u16; 4] {
+        [
+            self.risk_management,
+            self.execution,
+            self.outcome,
+            self.documentation,
+        ]
+    }
+
+    /// Validates that the weights sum exactly 1000.
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        let total = self
+            .weights()
+            .iter()
+            .try_fold(0u32, |total, weight| total.checked_add(u32::from(*weight)))
+            .ok_or("The sum of the grading weights overflows")?;
+
+        if total != Self::TOTAL {
+            return Err(format!(
+                "The grading weights must sum {} and they sum {}",
+                Self::TOTAL,
+                total
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// The overall score of the scores of every area, from 0 to 100.
+    /// The weights must be valid, otherwise the result is not bounded.
+    pub fn weigh(&self, grade: &TradeGrade) -> u32 {
+        let scores = [
+            grade.risk_management,
+            grade.execution,
+            grade.outcome,
+            grade.documentation,
+        ];
+        self.weights()
+            .iter()
+            .zip(scores)
+            .map(|(weight, score)| u32::from(*weight) * u32::from(score))
+            .sum::<u32>()
+            / Self::TOTAL
+    }
+
+    /// Scales the weights so they sum 1000 keeping their proportions.
+    /// The permille lost by rounding down goes to the weights with the largest remainders,
+    /// in the order of the fields when the remainders are the same.
+    pub fn normalize(&self) -> Result<GradingWeightsPermille, Box<dyn Error>> {
+        let weights = self.weights().map(u32::from);
+        let total: u32 = weights.iter().sum();
+        if total == 0 {
+            return Err("The grading weights can not be normalized if they are all zero".into());
+        }
+
+        let mut scaled = weights.map(|weight| weight * Self::TOTAL / total);
+        let mut remainders: Vec<(usize, u32)> = weights
+            .iter()
+            .enumerate()
+            .map(|(index, weight)| (index, weight * Self::TOTAL % total))
+            .collect();
+        remainders.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let missing = Self::TOTAL - scaled.iter().sum::<u32>();
+        for (index, _) in remainders.iter().take(missing as usize) {
+            scaled[*index] += 1;
+        }
+
+        // Every scaled weight is at most 1000, so it fits in a u16.
+        let [risk_management, execution, outcome, documentation] =
+            scaled.map(|weight| weight as u16);
+        Ok(GradingWeightsPermille {
+            risk_management,
+            execution,
+            outcome,
+            documentation,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weights(values: [u16; 4]) -> GradingWeightsPermille {
+        GradingWeightsPermille {
+            risk_management: values[0],
+            execution: values[1],
+            outcome: values[2],
+            documentation: values[3],
+        }
+    }
+
+    #[test]
+    fn test_validate_exactly_1000() {
+        assert!(GradingWeightsPermille::default().validate().is_ok());
+        assert!(weights([1000, 0, 0, 0]).validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_over_1000() {
+        assert!(weights([2000, 0, 0, 0]).validate().is_err());
+        assert!(weights([500, 500, 500, 500]).validate().is_err());
+        assert!(weights([1000, 1, 0, 0]).validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_under_1000() {
+        assert!(weights([0, 0, 0, 0]).validate().is_err());
+        assert!(weights([250, 250, 250, 249]).validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_max_weights() {
+        assert!(weights([u16::MAX; 4]).validate().is_err());
+        assert!(weights([u16::MAX, 0, 0, 0]).validate().is_err());
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            weights([500, 500, 500, 500]).normalize().unwrap(),
+            weights([250, 250, 250, 250])
+        );
+        assert_eq!(
+            weights([1, 1, 1, 0]).normalize().unwrap(),
+            weights([334, 333, 333, 0])
+        );
+        assert_eq!(
+            weights([u16::MAX; 4]).normalize().unwrap(),
+            weights([250, 250, 250, 250])
+        );
+
+        let normalized = weights([2000, 7, 13, 1]).normalize().unwrap();
+        assert!(normalized.validate().is_ok());
+    }
+
+    #[test]
+    fn test_weigh() {
+        let grade = TradeGrade {
+            risk_management: 100,
+            execution: 50,
+            outcome: 0,
+            documentation: 100,
+            ..Default::default()
+        };
+        // 300 * 100 + 200 * 50 + 300 * 0 + 200 * 100
+        assert_eq!(GradingWeightsPermille::default().weigh(&grade), 60);
+    }
+
+    #[test]
+    fn test_normalize_all_zero() {
+        assert!(weights([0, 0, 0, 0]).normalize().is_err());
+    }
+}
@@ -0,0 +1,171 @@
+use chrono::NaiveDateTime;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+/// Level entity - represents how much an account is allowed to trade.
+/// Level 0 is the most restricted one and Level::MAX the least restricted one.
+/// Accounts are moved between levels manually or automatically when they
+/// breach one of their LevelAdjustmentRules.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Level {
+    pub id: Uuid,
+
+    // Entity timestamps
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+
+    // Entity fields
+    pub account_id: Uuid,
+    pub current_level: u8,
+}
+
+impl Level {
+    /// The most restricted level.
+    pub const MIN: u8 = 0;
+
+    /// The least restricted level.
+    pub const MAX: u8 = 4;
+
+    /// The level that new accounts start with.
+    pub const DEFAULT: u8 = 3;
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        let now = Utc::now().naive_utc();
+        Level {
+            id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            account_id: Uuid::new_v4(),
+            current_level: Level::DEFAULT,
+        }
+    }
+}
+
+/// LevelChange entity - an entry of the history of levels of an account.
+#[derive(PartialEq, Debug, Clone)]
+pub struct LevelChange {
+    pub id: Uuid,
+
+    // Entity timestamps
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+
+    // Entity fields
+    pub account_id: Uuid,
+    pub old_level: u8,
+    pub new_level: u8,
+
+    /// Human readable explanation of why the level changed.
+    pub reason: String,
+
+    /// What caused the level to change.
+    pub trigger: LevelTrigger,
+
+    /// The moment when the level changed.
+    pub changed_at: NaiveDateTime,
+}
+
+impl Default for LevelChange {
+    fn default() -> Self {
+        let now = Utc::now().naive_utc();
+        LevelChange {
+            id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            account_id: Uuid::new_v4(),
+            old_level: Level::DEFAULT,
+            new_level: Level::DEFAULT,
+            reason: "".to_string(),
+            trigger: LevelTrigger::ManualOverride,
+            changed_at: now,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum LevelTrigger {
+    /// The level was changed by the user.
+    ManualOverride,
+
+    /// The drawdown of the account exceeded the limit of its LevelAdjustmentRules.
+    DrawdownBreach,
+
+    /// The level was changed by a trigger that is not part of Trust.
+    Custom(String),
+}
+
+impl std::fmt::Display for LevelTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LevelTrigger::ManualOverride => write!(f, "manual_override"),
+            LevelTrigger::DrawdownBreach => write!(f, "drawdown_breach"),
+            LevelTrigger::Custom(name) => write!(f, "custom:{}", name),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct LevelTriggerParseError;
+
+impl std::str::FromStr for LevelTrigger {
+    type Err = LevelTriggerParseError;
+    fn from_str(trigger: &str) -> Result<Self, Self::Err> {
+        match trigger {
+            "manual_override" => Ok(LevelTrigger::ManualOverride),
+            "drawdown_breach" => Ok(LevelTrigger::DrawdownBreach),
+            _ => match trigger.strip_prefix("custom:") {
+                Some(name) => Ok(LevelTrigger::Custom(name.to_string())),
+                None => Err(LevelTriggerParseError),
+            },
+        }
+    }
+}
+
+/// Rules that automatically move an account between levels.
+#[derive(PartialEq, Debug, Clone)]
+pub struct LevelAdjustmentRules {
+    /// Percentage of drawdown from the peak of the equity curve
+    /// that downgrades the account one level.
+    pub monthly_loss_downgrade_pct: Decimal,
+}
+
+impl Default for LevelAdjustmentRules {
+    fn default() -> Self {
+        LevelAdjustmentRules {
+            monthly_loss_downgrade_pct: dec!(5),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_trigger_round_trip() {
+        let triggers = vec![
+            LevelTrigger::ManualOverride,
+            LevelTrigger::DrawdownBreach,
+            LevelTrigger::Custom("coach".to_string()),
+        ];
+        for trigger in triggers {
+            assert_eq!(trigger.to_string().parse::<LevelTrigger>(), Ok(trigger));
+        }
+    }
+
+    #[test]
+    fn test_level_trigger_parse_invalid() {
+        assert_eq!(
+            "invalid".parse::<LevelTrigger>(),
+            Err(LevelTriggerParseError)
+        );
+    }
+}
@@ -1,9 +1,20 @@
 mod account;
+mod advisory;
 mod broker;
+mod clock;
 mod currency;
+mod distribution;
+mod draft_trade;
+mod equity_snapshot;
+mod execution;
+mod grading;
+mod level;
+mod market_data;
+mod market_price;
 mod order;
 mod rule;
 mod strategy;
+mod tax_lot;
 mod trade;
 mod trading_vehicle;
 mod transaction;
@@ -11,18 +22,37 @@ mod transaction;
 pub mod database;
 
 // Re-export the types from the model crate.
-pub use account::{Account, AccountBalance, Environment};
-pub use broker::{Broker, BrokerLog, OrderIds};
+pub use account::{Account, AccountBalance, AccountType, Environment, FeeModel, TickSizePolicy};
+pub use advisory::AdvisoryThresholds;
+pub use broker::{
+    Broker, BrokerActivity, BrokerActivityCategory, BrokerError, BrokerErrorCode, BrokerLog,
+    BrokerPosition, BrokerValidation, OrderIds,
+};
+pub use clock::{Clock, SteppableClock, SystemClock};
 pub use currency::Currency;
 pub use database::{
     AccountBalanceRead, AccountBalanceWrite, AccountRead, AccountWrite, DatabaseFactory,
-    DraftTrade, OrderRead, OrderWrite, ReadBrokerLogsDB, ReadRuleDB, ReadTradeDB,
-    ReadTradingVehicleDB, ReadTransactionDB, WriteBrokerLogsDB, WriteRuleDB, WriteTradeDB,
-    WriteTradingVehicleDB, WriteTransactionDB,
+    DraftExecution, DraftTrade, OrderRead, OrderWrite, ReadAdvisoryDB, ReadBrokerLogsDB,
+    ReadDistributionDB, ReadEquitySnapshotDB, ReadExecutionDB, ReadLevelDB, ReadRealizedLotDB,
+    ReadRuleDB, ReadStrategyDB, ReadTradeDB, ReadTradeGradeDB, ReadTradingVehicleDB,
+    ReadTransactionDB, WriteAdvisoryDB, WriteBrokerLogsDB, WriteDistributionDB,
+    WriteEquitySnapshotDB, WriteExecutionDB, WriteLevelDB, WriteRealizedLotDB, WriteRuleDB,
+    WriteStrategyDB, WriteTradeDB, WriteTradeGradeDB, WriteTradingVehicleDB, WriteTransactionDB,
+};
+pub use distribution::{DistributionHistory, DistributionRules};
+pub use draft_trade::{
+    validate_reference, validate_thesis, DraftTradeBuilder, DraftTradeError, DraftTradeErrorCode,
 };
+pub use equity_snapshot::EquitySnapshot;
+pub use execution::{Execution, ExecutionSide, ExecutionSource};
+pub use grading::{GradingWeightsPermille, TradeGrade};
+pub use level::{Level, LevelAdjustmentRules, LevelChange, LevelTrigger, LevelTriggerParseError};
+pub use market_data::{BarTimeframe, MarketBar};
+pub use market_price::MarketPriceProvider;
 pub use order::{Order, OrderAction, OrderCategory, OrderStatus, TimeInForce};
 pub use rule::{Rule, RuleLevel, RuleName};
 pub use strategy::Strategy;
-pub use trade::{Status, Trade, TradeBalance, TradeCategory};
+pub use tax_lot::{LotMethod, RealizedLot};
+pub use trade::{Status, ThesisRevision, Trade, TradeBalance, TradeCategory, MAX_THESIS_LENGTH};
 pub use trading_vehicle::{TradingVehicle, TradingVehicleCategory};
 pub use transaction::{Transaction, TransactionCategory};
@@ -0,0 +1,184 @@
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use std::fmt;
+
+/// MarketBar entity - represents the prices of a trading vehicle during a period of time.
+#[derive(PartialEq, Debug, Clone)]
+pub struct MarketBar {
+    /// The start of the period of the bar.
+    pub timestamp: NaiveDateTime,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume: Decimal,
+}
+
+/// The length of the period of a bar.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum BarTimeframe {
+    OneMinute,
+    FiveMinutes,
+    FifteenMinutes,
+    OneHour,
+    OneDay,
+}
+
+impl BarTimeframe {
+    /// The length of the period in seconds.
+    pub fn seconds(&self) -> i64 {
+        match self {
+            BarTimeframe::OneMinute => 60,
+            BarTimeframe::FiveMinutes => 5 * 60,
+            BarTimeframe::FifteenMinutes => 15 * 60,
+            BarTimeframe::OneHour => 60 * 60,
+            BarTimeframe::OneDay => 24 * 60 * 60,
+        }
+    }
+
+    /// The start of the period that contains the timestamp.
+    fn period_start(&self, timestamp: NaiveDateTime) -> i64 {
+        timestamp.timestamp().div_euclid(self.seconds()) * self.seconds()
+    }
+}
+
+impl fmt::Display for BarTimeframe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BarTimeframe::OneMinute => write!(f, "1m"),
+            BarTimeframe::FiveMinutes => write!(f, "5m"),
+            BarTimeframe::FifteenMinutes => write!(f, "15m"),
+            BarTimeframe::OneHour => write!(f, "1h"),
+            BarTimeframe::OneDay => write!(f, "1d"),
+        }
+    }
+}
+
+impl MarketBar {
+    /// Aggregates bars of a timeframe into bars of a coarser timeframe.
+    /// The open is the open of the first bar of the period, the close the close of the last one,
+    /// the high and low are the extremes of the period and the volumes are added.
+    /// Periods without bars are not in the result, so gaps in the input stay gaps.
+    pub fn resample(
+        bars: &[MarketBar],
+        from: BarTimeframe,
+        to: BarTimeframe,
+    ) -> Result<Vec<MarketBar>, Box<dyn std::error::Error>> {
+        if to.seconds() < from.seconds() {
+            return Err(format!("Bars of {} can not be resampled into {}", from, to).into());
+        }
+        if to.seconds() % from.seconds() != 0 {
+            return Err(format!("{} is not a multiple of {}", to, from).into());
+        }
+
+        let mut bars = bars.to_vec();
+        bars.sort_by_key(|bar| bar.timestamp);
+
+        let mut resampled: Vec<(i64, MarketBar)> = Vec::new();
+        for bar in bars {
+            let start = to.period_start(bar.timestamp);
+            match resampled.last_mut() {
+                Some((period, current)) if *period == start => {
+                    current.high = current.high.max(bar.high);
+                    current.low = current.low.min(bar.low);
+                    current.close = bar.close;
+                    current.volume += bar.volume;
+                }
+                _ => {
+                    let timestamp = NaiveDateTime::from_timestamp_opt(start, 0)
+                        .ok_or_else(|| format!("Invalid period start {}", start))?;
+                    resampled.push((start, MarketBar { timestamp, ..bar }));
+                }
+            }
+        }
+
+        Ok(resampled.into_iter().map(|(_, bar)| bar).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use rust_decimal_macros::dec;
+
+    fn bar(hour: u32, minute: u32, price: Decimal) -> MarketBar {
+        MarketBar {
+            timestamp: NaiveDate::from_ymd_opt(2023, 8, 21)
+                .unwrap()
+                .and_hms_opt(hour, minute, 0)
+                .unwrap(),
+            open: price,
+            high: price + dec!(1),
+            low: price - dec!(1),
+            close: price + dec!(0.5),
+            volume: dec!(100),
+        }
+    }
+
+    #[test]
+    fn test_resample_five_minutes_into_one_hour() {
+        let bars: Vec<MarketBar> = (0..12)
+            .map(|i| bar(14, i * 5, dec!(40) + Decimal::from(i)))
+            .collect();
+
+        let resampled =
+            MarketBar::resample(&bars, BarTimeframe::FiveMinutes, BarTimeframe::OneHour).unwrap();
+
+        assert_eq!(
+            resampled,
+            vec![MarketBar {
+                timestamp: bars[0].timestamp,
+                open: dec!(40),
+                high: dec!(52),
+                low: dec!(39),
+                close: dec!(51.5),
+                volume: dec!(1200),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_resample_with_gaps() {
+        let bars = vec![bar(14, 0, dec!(40)), bar(16, 30, dec!(42))];
+
+        let resampled =
+            MarketBar::resample(&bars, BarTimeframe::FiveMinutes, BarTimeframe::OneHour).unwrap();
+
+        assert_eq!(resampled.len(), 2);
+        assert_eq!(resampled[0].timestamp, bar(14, 0, dec!(0)).timestamp);
+        assert_eq!(resampled[1].timestamp, bar(16, 0, dec!(0)).timestamp);
+        assert_eq!(resampled[1].open, dec!(42));
+    }
+
+    #[test]
+    fn test_resample_unordered_bars() {
+        let bars = vec![bar(14, 5, dec!(41)), bar(14, 0, dec!(40))];
+
+        let resampled =
+            MarketBar::resample(&bars, BarTimeframe::FiveMinutes, BarTimeframe::OneHour).unwrap();
+
+        assert_eq!(resampled[0].open, dec!(40));
+        assert_eq!(resampled[0].close, dec!(41.5));
+    }
+
+    #[test]
+    fn test_resample_into_finer_timeframe() {
+        let bars = vec![bar(14, 0, dec!(40))];
+
+        assert!(
+            MarketBar::resample(&bars, BarTimeframe::OneHour, BarTimeframe::FiveMinutes).is_err()
+        );
+    }
+
+    #[test]
+    fn test_resample_into_same_timeframe() {
+        let bars = vec![bar(14, 0, dec!(40)), bar(14, 5, dec!(41))];
+
+        let resampled =
+            MarketBar::resample(&bars, BarTimeframe::FiveMinutes, BarTimeframe::FiveMinutes)
+                .unwrap();
+
+        assert_eq!(resampled, bars);
+    }
+}
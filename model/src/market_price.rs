@@ -0,0 +1,9 @@
+use crate::TradingVehicle;
+use rust_decimal::Decimal;
+
+/// Source of the current market prices of the trading vehicles.
+/// It can be implemented by a broker, a market data feed or a static list of prices.
+pub trait MarketPriceProvider {
+    /// Returns the latest price of the trading vehicle or None if it is not known.
+    fn latest_price(&self, trading_vehicle: &TradingVehicle) -> Option<Decimal>;
+}
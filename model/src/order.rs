@@ -11,6 +11,7 @@ use crate::Currency;
 /// Orders can be entries to the market or exits from the market.
 /// Orders are part of a trade entries and exits.
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Order {
     pub id: Uuid,
 
@@ -31,6 +32,10 @@ pub struct Order {
     /// The quantity of the order
     pub quantity: u64,
 
+    /// The fractional quantity of the order. Only crypto can be traded in
+    /// fractions, for those orders it is used instead of `quantity`.
+    pub quantity_decimal: Option<Decimal>,
+
     /// The trading vehicle ID - the asset that is traded
     pub trading_vehicle_id: Uuid,
 
@@ -55,6 +60,10 @@ pub struct Order {
     /// The quantity of the order
     pub filled_quantity: u64,
 
+    /// The fractional filled quantity of the order. Only crypto can be traded in
+    /// fractions, for those orders it is used instead of `filled_quantity`.
+    pub filled_quantity_decimal: Option<Decimal>,
+
     /// The average filled price of the order
     pub average_filled_price: Option<Decimal>,
 
@@ -79,8 +88,26 @@ pub struct Order {
     pub closed_at: Option<NaiveDateTime>,
 }
 
+impl Order {
+    /// The quantity of the order with full precision. It is the fractional
+    /// quantity when the order has one and the integer quantity otherwise.
+    pub fn decimal_quantity(&self) -> Decimal {
+        self.quantity_decimal
+            .unwrap_or_else(|| Decimal::from(self.quantity))
+    }
+
+    /// The filled quantity of the order with full precision. It is the fractional
+    /// filled quantity when the order has one and the integer filled quantity otherwise.
+    pub fn decimal_filled_quantity(&self) -> Decimal {
+        self.filled_quantity_decimal
+            .unwrap_or_else(|| Decimal::from(self.filled_quantity))
+    }
+}
+
 /// The category of the order - market, limit, stop, etc. It depends on the exchange.
 #[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum OrderCategory {
     /// Market order - buy or sell at the current market price. The order is executed immediately.
     Market,
@@ -88,10 +115,19 @@ pub enum OrderCategory {
     Limit,
     /// Stop order - buy or sell at a specific price or worse. The order is executed when the price is reached.
     Stop,
+    /// Trailing stop order - a stop that follows the price by a percent or by an amount. The stop only moves
+    /// in favor of the trade, and the order is executed at market when the price is reached.
+    /// Exactly one of the percent and the price is set.
+    TrailingStop {
+        trail_percent: Option<Decimal>,
+        trail_price: Option<Decimal>,
+    },
 }
 
 /// The action of the order - buy, sell, short, etc.
 #[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum OrderAction {
     /// Sell an asset that you own
     Sell,
@@ -102,6 +138,8 @@ pub enum OrderAction {
 }
 
 #[derive(PartialEq, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum TimeInForce {
     /// The order is good for the day, and it will be canceled
     /// automatically at the end of Regular Trading Hours if unfilled.
@@ -115,10 +153,26 @@ pub enum TimeInForce {
     /// This order is eligible to execute only in the market closing
     /// auction. Any unfilled orders after the close will be canceled.
     UntilMarketClose,
+    /// The part of the order that can not be filled immediately is canceled.
+    ImmediateOrCancel,
+    /// The order is canceled unless it can be filled immediately and completely.
+    FillOrKill,
+}
+
+impl TimeInForce {
+    /// If the order is canceled unless it is filled as soon as it reaches the market.
+    pub fn is_immediate(&self) -> bool {
+        matches!(
+            self,
+            TimeInForce::ImmediateOrCancel | TimeInForce::FillOrKill
+        )
+    }
 }
 
 /// The status an order can have.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum OrderStatus {
     /// The order has been received by Broker, and routed to exchanges for
     /// execution. This is the usual initial state of an order.
@@ -242,6 +296,7 @@ impl std::fmt::Display for OrderCategory {
             OrderCategory::Market => write!(f, "market"),
             OrderCategory::Limit => write!(f, "limit"),
             OrderCategory::Stop => write!(f, "stop"),
+            OrderCategory::TrailingStop { .. } => write!(f, "trailing_stop"),
         }
     }
 }
@@ -253,6 +308,8 @@ impl std::fmt::Display for TimeInForce {
             TimeInForce::UntilCanceled => write!(f, "until_canceled"),
             TimeInForce::UntilMarketOpen => write!(f, "until_market_open"),
             TimeInForce::UntilMarketClose => write!(f, "until_market_close"),
+            TimeInForce::ImmediateOrCancel => write!(f, "immediate_or_cancel"),
+            TimeInForce::FillOrKill => write!(f, "fill_or_kill"),
         }
     }
 }
@@ -267,6 +324,8 @@ impl std::str::FromStr for TimeInForce {
             "until_canceled" => Ok(TimeInForce::UntilCanceled),
             "until_market_open" => Ok(TimeInForce::UntilMarketOpen),
             "until_market_close" => Ok(TimeInForce::UntilMarketClose),
+            "immediate_or_cancel" => Ok(TimeInForce::ImmediateOrCancel),
+            "fill_or_kill" => Ok(TimeInForce::FillOrKill),
             _ => Err(TimeInForceParseError),
         }
     }
@@ -282,6 +341,11 @@ impl std::str::FromStr for OrderCategory {
             "market" => Ok(OrderCategory::Market),
             "limit" => Ok(OrderCategory::Limit),
             "stop" => Ok(OrderCategory::Stop),
+            // The trail is not part of the name, it is read with the rest of the order.
+            "trailing_stop" => Ok(OrderCategory::TrailingStop {
+                trail_percent: None,
+                trail_price: None,
+            }),
             _ => Err(OrderCategoryParseError),
         }
     }
@@ -330,7 +394,9 @@ impl Default for Order {
             status: OrderStatus::New,
             time_in_force: TimeInForce::default(),
             quantity: 10,
+            quantity_decimal: None,
             filled_quantity: 0,
+            filled_quantity_decimal: None,
             average_filled_price: None,
             extended_hours: false,
             submitted_at: None,
@@ -348,6 +414,19 @@ impl Default for Order {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_decimal_quantity() {
+        let order = Order::default();
+        assert_eq!(order.decimal_quantity(), dec!(10));
+
+        let order = Order {
+            quantity: 0,
+            quantity_decimal: Some(dec!(0.25)),
+            ..Default::default()
+        };
+        assert_eq!(order.decimal_quantity(), dec!(0.25));
+    }
+
     #[test]
     fn test_order_category_parse() {
         assert_eq!("market".parse::<OrderCategory>(), Ok(OrderCategory::Market));
@@ -361,6 +440,16 @@ mod tests {
         assert_eq!(format!("{}", OrderCategory::Market), "market");
         assert_eq!(format!("{}", OrderCategory::Limit), "limit");
         assert_eq!(format!("{}", OrderCategory::Stop), "stop");
+        assert_eq!(
+            format!(
+                "{}",
+                OrderCategory::TrailingStop {
+                    trail_percent: Some(dec!(2.5)),
+                    trail_price: None
+                }
+            ),
+            "trailing_stop"
+        );
     }
 
     #[test]
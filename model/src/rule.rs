@@ -64,6 +64,43 @@ pub enum RuleName {
     ///
     /// It is recommended not to set this rule to more than 6% of the account.
     RiskPerMonth(f32),
+
+    /// The maximum percentage of the capital in open trades that a single position can use
+    /// This rule is used to limit the concentration of an account in a single trading vehicle
+    /// If the projected concentration is higher than the maximum, the trade will not be funded.
+    /// For example:
+    ///
+    /// 1. You have three open positions of 10_000 each in AAPL, MSFT and TSLA.
+    /// 2. You want to fund a new trade of 10_000 in AAPL.
+    /// 3. AAPL would be 20_000 of 40_000 in open positions, so the concentration is 50%.
+    /// 4. If the maximum concentration is 40%, the trade will be rejected.
+    MaxPositionConcentration(f32),
+
+    /// Only one open trade per trading vehicle and direction
+    /// This rule is used to avoid doubling a directional bet by mistake
+    /// If there is an open long trade in a trading vehicle, another long trade will not be funded.
+    /// A short trade in the same trading vehicle will be funded, given that it hedges the position.
+    OnePositionPerSymbolSide,
+
+    /// The minimum reward per unit of risk that a trade must offer
+    /// This rule is used to avoid trades that do not pay enough for the risk that they take
+    /// If the reward/risk ratio of a trade is lower than the minimum, the trade will not be created.
+    /// For example:
+    ///
+    /// 1. Buy a stock for 40 with a stop at 38 and a target at 43.
+    /// 2. The reward is 3 per share and the risk is 2 per share, so the ratio is 1.5:1.
+    /// 3. If the minimum ratio is 2:1, the trade will be rejected.
+    MinRewardRiskRatio(f32),
+
+    /// The maximum number of losing trades in a row before taking a break
+    /// This rule is used to stop trading after a losing streak
+    /// If the most recent closed trades lost money that many times in a row, no trade will be funded.
+    /// For example:
+    ///
+    /// 1. The maximum is 3 consecutive losses.
+    /// 2. Your last three closed trades were losses, so a new trade will not be funded.
+    /// 3. Once a trade closes with a profit, the streak is reset and trades can be funded again.
+    MaxConsecutiveLosses(u32),
 }
 
 // Implementations
@@ -79,13 +116,24 @@ impl fmt::Display for RuleName {
         match self {
             RuleName::RiskPerTrade(_) => write!(f, "risk_per_trade"),
             RuleName::RiskPerMonth(_) => write!(f, "risk_per_month"),
+            RuleName::MaxPositionConcentration(_) => write!(f, "max_position_concentration"),
+            RuleName::OnePositionPerSymbolSide => write!(f, "one_position_per_symbol_side"),
+            RuleName::MinRewardRiskRatio(_) => write!(f, "min_reward_risk_ratio"),
+            RuleName::MaxConsecutiveLosses(_) => write!(f, "max_consecutive_losses"),
         }
     }
 }
 
 impl RuleName {
     pub fn all() -> Vec<RuleName> {
-        vec![RuleName::RiskPerTrade(0.0), RuleName::RiskPerMonth(0.0)]
+        vec![
+            RuleName::RiskPerTrade(0.0),
+            RuleName::RiskPerMonth(0.0),
+            RuleName::MaxPositionConcentration(0.0),
+            RuleName::OnePositionPerSymbolSide,
+            RuleName::MinRewardRiskRatio(0.0),
+            RuleName::MaxConsecutiveLosses(0),
+        ]
     }
 }
 
@@ -94,6 +142,10 @@ impl RuleName {
         match self {
             RuleName::RiskPerTrade(value) => *value,
             RuleName::RiskPerMonth(value) => *value,
+            RuleName::MaxPositionConcentration(value) => *value,
+            RuleName::OnePositionPerSymbolSide => 0.0,
+            RuleName::MinRewardRiskRatio(value) => *value,
+            RuleName::MaxConsecutiveLosses(value) => *value as f32,
         }
     }
 }
@@ -106,6 +158,10 @@ impl RuleName {
         match s {
             "risk_per_trade" => Ok(RuleName::RiskPerTrade(risk)),
             "risk_per_month" => Ok(RuleName::RiskPerMonth(risk)),
+            "max_position_concentration" => Ok(RuleName::MaxPositionConcentration(risk)),
+            "one_position_per_symbol_side" => Ok(RuleName::OnePositionPerSymbolSide),
+            "min_reward_risk_ratio" => Ok(RuleName::MinRewardRiskRatio(risk)),
+            "max_consecutive_losses" => Ok(RuleName::MaxConsecutiveLosses(risk as u32)),
             _ => Err(RuleNameParseError),
         }
     }
@@ -165,6 +221,14 @@ mod tests {
         assert_eq!(result, Ok(RuleName::RiskPerTrade(2.0)));
         let result = RuleName::parse("risk_per_month", 2.0);
         assert_eq!(result, Ok(RuleName::RiskPerMonth(2.0)));
+        let result = RuleName::parse("max_position_concentration", 40.0);
+        assert_eq!(result, Ok(RuleName::MaxPositionConcentration(40.0)));
+        let result = RuleName::parse("one_position_per_symbol_side", 0.0);
+        assert_eq!(result, Ok(RuleName::OnePositionPerSymbolSide));
+        let result = RuleName::parse("min_reward_risk_ratio", 2.0);
+        assert_eq!(result, Ok(RuleName::MinRewardRiskRatio(2.0)));
+        let result = RuleName::parse("max_consecutive_losses", 3.0);
+        assert_eq!(result, Ok(RuleName::MaxConsecutiveLosses(3)));
         let result = RuleName::parse("invalid", 0.0);
         assert_eq!(result, Err(RuleNameParseError));
     }
@@ -0,0 +1,108 @@
+use chrono::NaiveDateTime;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use std::fmt::{self, Display, Formatter};
+use uuid::Uuid;
+
+/// LotMethod enum - which opening executions are matched first when a position is closed
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LotMethod {
+    /// The oldest opening executions are closed first.
+    #[default]
+    Fifo,
+
+    /// The newest opening executions are closed first.
+    Lifo,
+}
+
+impl Display for LotMethod {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            LotMethod::Fifo => write!(f, "fifo"),
+            LotMethod::Lifo => write!(f, "lifo"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LotMethodParseError;
+impl std::str::FromStr for LotMethod {
+    type Err = LotMethodParseError;
+    fn from_str(method: &str) -> Result<Self, Self::Err> {
+        match method {
+            "fifo" => Ok(LotMethod::Fifo),
+            "lifo" => Ok(LotMethod::Lifo),
+            _ => Err(LotMethodParseError),
+        }
+    }
+}
+
+/// RealizedLot entity - the part of an opening execution that was closed by a closing execution.
+/// An opening execution that is closed by several executions is split in several lots.
+#[derive(PartialEq, Debug, Clone)]
+pub struct RealizedLot {
+    pub id: Uuid,
+
+    // Entity timestamps
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+
+    // Entity fields
+    pub account_id: Uuid,
+    pub trade_id: Uuid,
+    pub open_execution_id: Uuid,
+    pub close_execution_id: Uuid,
+
+    /// When the opening execution was executed.
+    pub opened_at: NaiveDateTime,
+
+    /// When the closing execution was executed.
+    pub closed_at: NaiveDateTime,
+    pub quantity: u64,
+
+    /// The money paid to buy the quantity of the lot.
+    pub cost_basis: Decimal,
+
+    /// The money received when the quantity of the lot was sold.
+    pub proceeds: Decimal,
+
+    /// The proceeds minus the cost basis.
+    pub gain: Decimal,
+}
+
+impl Default for RealizedLot {
+    fn default() -> Self {
+        let now = Utc::now().naive_utc();
+        RealizedLot {
+            id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            account_id: Uuid::new_v4(),
+            trade_id: Uuid::new_v4(),
+            open_execution_id: Uuid::new_v4(),
+            close_execution_id: Uuid::new_v4(),
+            opened_at: now,
+            closed_at: now,
+            quantity: 0,
+            cost_basis: Decimal::ZERO,
+            proceeds: Decimal::ZERO,
+            gain: Decimal::ZERO,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_lot_method_parse() {
+        assert_eq!(LotMethod::from_str("fifo").unwrap(), LotMethod::Fifo);
+        assert_eq!(LotMethod::from_str("lifo").unwrap(), LotMethod::Lifo);
+        assert!(LotMethod::from_str("average").is_err());
+        assert_eq!(LotMethod::default(), LotMethod::Fifo);
+    }
+}
@@ -9,6 +9,7 @@ use uuid::Uuid;
 /// Trade entity - represents a single trade.
 /// Trade is the most important entity of the trust model.
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Trade {
     pub id: Uuid,
 
@@ -46,12 +47,43 @@ pub struct Trade {
     /// The account that the trade is associated with
     pub account_id: Uuid,
 
+    /// The strategy that was followed to open the trade, if any
+    pub strategy_id: Option<Uuid>,
+
+    /// A human-readable reference to cross-reference the trade with external journals, e.g. "AAPL-2024-001".
+    /// It is unique per account.
+    pub reference: Option<String>,
+
+    /// Why the trade was taken, written before entering it. At most 200 characters.
+    pub thesis: Option<String>,
+
+    /// The market conditions around the trade, for example the trend of the index.
+    pub context: Option<String>,
+
+    /// The sector of the economy of the trading vehicle, for example "technology".
+    /// It is used to see where the risk of an account is concentrated.
+    pub sector: Option<String>,
+
+    /// When the trade was last synced with the broker. `None` if it was never synced.
+    pub last_synced_at: Option<NaiveDateTime>,
+
     /// The balance of the trade - It is a cache of the calculations of the trade.
     /// It is a snapshot of the trade. It should be updated every time the trade is updated.
     /// WARNING: It is read-only and it can be out of sync if the trade is open.
     pub balance: TradeBalance,
 }
 
+impl Trade {
+    /// When the trade was closed by the stop or the target.
+    /// If the broker did not report it, the last update of the trade is used.
+    pub fn closed_at(&self) -> NaiveDateTime {
+        self.safety_stop
+            .closed_at
+            .or(self.target.closed_at)
+            .unwrap_or(self.updated_at)
+    }
+}
+
 impl std::fmt::Display for Trade {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -71,6 +103,8 @@ impl std::fmt::Display for Trade {
 
 /// The status an order can have.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Status {
     /// The trade has been created and waiting for
     /// funding. This is the usual initial state of trade.
@@ -84,6 +118,8 @@ pub enum Status {
     PartiallyFilled,
     /// The trade has been completely filled.
     Filled,
+    /// An order to close the trade at a limit price was submitted and it is not filled yet.
+    ClosingPending,
     /// The trade has been closed by the broker in the stop.
     ClosedStopLoss,
     /// The trade has been closed by the broker in the target.
@@ -104,6 +140,7 @@ impl Status {
             Status::Submitted,
             Status::PartiallyFilled,
             Status::Filled,
+            Status::ClosingPending,
             Status::ClosedStopLoss,
             Status::ClosedTarget,
             Status::Canceled,
@@ -111,6 +148,18 @@ impl Status {
             Status::Rejected,
         ]
     }
+
+    /// Returns true if the trade can not change its status anymore.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            Status::ClosedStopLoss
+                | Status::ClosedTarget
+                | Status::Canceled
+                | Status::Expired
+                | Status::Rejected
+        )
+    }
 }
 
 impl std::fmt::Display for Status {
@@ -121,6 +170,7 @@ impl std::fmt::Display for Status {
             Status::Submitted => "submitted",
             Status::PartiallyFilled => "partially_filled",
             Status::Filled => "filled",
+            Status::ClosingPending => "closing_pending",
             Status::Canceled => "canceled",
             Status::Expired => "expired",
             Status::Rejected => "rejected",
@@ -142,6 +192,7 @@ impl std::str::FromStr for Status {
             "submitted" => Ok(Status::Submitted),
             "partially_filled" => Ok(Status::PartiallyFilled),
             "filled" => Ok(Status::Filled),
+            "closing_pending" => Ok(Status::ClosingPending),
             "canceled" => Ok(Status::Canceled),
             "expired" => Ok(Status::Expired),
             "rejected" => Ok(Status::Rejected),
@@ -154,6 +205,8 @@ impl std::str::FromStr for Status {
 
 /// The category of the trade - Being a bull or a bear
 #[derive(PartialEq, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum TradeCategory {
     /// Long trade - Bull - buy an asset and sell it later at a higher price
     #[default]
@@ -190,6 +243,7 @@ impl std::str::FromStr for TradeCategory {
 }
 
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TradeBalance {
     pub id: Uuid,
 
@@ -217,6 +271,25 @@ pub struct TradeBalance {
     pub total_performance: Decimal,
 }
 
+/// The maximum number of characters of the thesis of a trade.
+pub const MAX_THESIS_LENGTH: usize = 200;
+
+/// ThesisRevision entity - the thesis and context of a trade before they were revised.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ThesisRevision {
+    pub id: Uuid,
+
+    // Entity timestamps
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+
+    // Entity fields
+    pub trade_id: Uuid,
+    pub thesis: Option<String>,
+    pub context: Option<String>,
+}
+
 impl Default for Trade {
     fn default() -> Self {
         let now = Utc::now().naive_utc();
@@ -233,6 +306,12 @@ impl Default for Trade {
             entry: Order::default(),
             target: Order::default(),
             account_id: Uuid::new_v4(),
+            strategy_id: None,
+            reference: None,
+            thesis: None,
+            context: None,
+            sector: None,
+            last_synced_at: None,
             balance: TradeBalance::default(),
         }
     }
@@ -1,9 +1,12 @@
+use crate::Currency;
 use chrono::NaiveDateTime;
 use chrono::Utc;
+use rust_decimal::Decimal;
 use uuid::Uuid;
 
 /// TradingVehicle entity. Like a Stock, Crypto, Fiat, Future, etc.
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TradingVehicle {
     pub id: Uuid,
 
@@ -24,11 +27,21 @@ pub struct TradingVehicle {
 
     /// The broker that is used to trade the trading vehicle. For example: Coinbase, Binance, NASDAQ etc.
     pub broker: String,
+
+    /// The currency in which the trading vehicle is quoted. For example: EUR for a stock listed in XETRA.
+    /// If it is set, trades of this trading vehicle must use the same currency.
+    pub quote_currency: Option<Currency>,
+
+    /// The minimum price movement of the trading vehicle. For example: 0.01 for most US stocks.
+    /// If it is set, the prices of the trades must be a multiple of it.
+    pub tick_size: Option<Decimal>,
 }
 
 /// TradingVehicleCategory enum - represents the type of the trading vehicle
 #[derive(PartialEq, Debug, Clone, Copy)]
 #[non_exhaustive] // This enum may be extended in the future
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum TradingVehicleCategory {
     /// Cryptocurrency like BTC, ETH, etc.
     Crypto,
@@ -102,6 +115,8 @@ impl Default for TradingVehicle {
             isin: "AAPL".to_string(),
             category: TradingVehicleCategory::Stock,
             broker: "NASDAQ".to_string(),
+            quote_currency: None,
+            tick_size: None,
         }
     }
 }
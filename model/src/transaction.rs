@@ -26,10 +26,18 @@ pub struct Transaction {
 
     /// The account ID - the account that the transaction is related to
     pub account_id: Uuid,
+
+    /// The transaction that this transaction compensates.
+    /// Transactions are never deleted, mistakes are undone with a compensating transaction.
+    pub reverses: Option<Uuid>,
+
+    /// The activity of the broker that the transaction was written for, if any.
+    /// An activity is only written once, so reconciling it again does not duplicate it.
+    pub broker_activity_id: Option<String>,
 }
 
 /// TransactionCategory enum - represents the type of the transaction
-#[derive(PartialEq, Debug, Clone, Copy)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum TransactionCategory {
     /// Deposit - money deposited into the account
     Deposit,
@@ -69,6 +77,11 @@ pub enum TransactionCategory {
     /// Money transferred from a trade to the broker as a fee to close the trade.
     FeeClose(Uuid),
 
+    /// Money paid by the broker into a trade as a rebate, for example for providing liquidity.
+    /// It is a negative cost: it is subtracted from the fees of the trade, so they can be negative.
+    /// The Uuid is the trade ID.
+    FeeRebate(Uuid),
+
     /// Money transferred into the account from a trade.
     /// This is a special case of Input to not use the money that should be paid to the tax authorities.
     /// /// The Uuid is the trade ID that incurred into tax liability.
@@ -84,6 +97,27 @@ pub enum TransactionCategory {
 
     /// Money transferred out an account to enjoy earnings.
     WithdrawalEarnings,
+
+    /// Money of a trade that is kept in the account to be traded again.
+    /// The Uuid is the trade ID.
+    PaymentReinvestment(Uuid),
+
+    /// Money charged by the broker that can not be attributed to a trade.
+    /// For example: regulatory fees reported without an order.
+    Fee,
+
+    /// Money paid into the account by a holding, for example a cash dividend.
+    /// The Uuid is the optional trading vehicle ID that paid the dividend.
+    Dividend(Option<Uuid>),
+
+    /// Interest paid by the broker on the cash of the account, or charged for a margin loan.
+    /// It is signed: a positive amount is a credit and a negative amount is a charge.
+    Interest,
+
+    /// Correction of the balance, made manually or to match the buying power reported by the broker.
+    /// It is signed: a positive amount is added to the account and a negative amount is removed.
+    /// The reason explains the correction to whoever audits the account.
+    Adjustment { reason: String },
 }
 
 impl TransactionCategory {
@@ -99,10 +133,30 @@ impl TransactionCategory {
             TransactionCategory::CloseSafetyStopSlippage(id) => Some(*id),
             TransactionCategory::FeeOpen(id) => Some(*id),
             TransactionCategory::FeeClose(id) => Some(*id),
+            TransactionCategory::FeeRebate(id) => Some(*id),
             TransactionCategory::PaymentEarnings(id) => Some(*id),
             TransactionCategory::WithdrawalEarnings => None,
+            TransactionCategory::PaymentReinvestment(id) => Some(*id),
             TransactionCategory::PaymentTax(id) => Some(*id),
             TransactionCategory::WithdrawalTax => None,
+            TransactionCategory::Fee => None,
+            TransactionCategory::Dividend(_) => None,
+            TransactionCategory::Interest => None,
+            TransactionCategory::Adjustment { .. } => None,
+        }
+    }
+
+    pub fn trading_vehicle_id(&self) -> Option<Uuid> {
+        match self {
+            TransactionCategory::Dividend(id) => *id,
+            _ => None,
+        }
+    }
+
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            TransactionCategory::Adjustment { reason } => Some(reason),
+            _ => None,
         }
     }
 
@@ -118,10 +172,16 @@ impl TransactionCategory {
             TransactionCategory::CloseSafetyStopSlippage(_) => "close_safety_stop_slippage",
             TransactionCategory::FeeOpen(_) => "fee_open",
             TransactionCategory::FeeClose(_) => "fee_close",
+            TransactionCategory::FeeRebate(_) => "fee_rebate",
             TransactionCategory::PaymentEarnings(_) => "payment_earnings",
             TransactionCategory::WithdrawalEarnings => "withdrawal_earnings",
+            TransactionCategory::PaymentReinvestment(_) => "payment_reinvestment",
             TransactionCategory::PaymentTax(_) => "payment_tax",
             TransactionCategory::WithdrawalTax => "withdrawal_tax",
+            TransactionCategory::Fee => "fee",
+            TransactionCategory::Dividend(_) => "dividend",
+            TransactionCategory::Interest => "interest",
+            TransactionCategory::Adjustment { .. } => "adjustment",
         }
     }
 }
@@ -143,10 +203,16 @@ impl std::fmt::Display for TransactionCategory {
             }
             TransactionCategory::FeeOpen(_) => write!(f, "fee_open"),
             TransactionCategory::FeeClose(_) => write!(f, "fee_close"),
+            TransactionCategory::FeeRebate(_) => write!(f, "fee_rebate"),
             TransactionCategory::PaymentEarnings(_) => write!(f, "payment_earnings"),
             TransactionCategory::WithdrawalEarnings => write!(f, "withdrawal_earnings"),
+            TransactionCategory::PaymentReinvestment(_) => write!(f, "payment_reinvestment"),
             TransactionCategory::PaymentTax(_) => write!(f, "payment_tax"),
             TransactionCategory::WithdrawalTax => write!(f, "withdrawal_tax"),
+            TransactionCategory::Fee => write!(f, "fee"),
+            TransactionCategory::Dividend(_) => write!(f, "dividend"),
+            TransactionCategory::Interest => write!(f, "interest"),
+            TransactionCategory::Adjustment { .. } => write!(f, "adjustment"),
         }
     }
 }
@@ -168,6 +234,8 @@ impl Transaction {
             category,
             currency: *currency,
             amount: price,
+            reverses: None,
+            broker_activity_id: None,
         }
     }
 }
@@ -200,7 +268,29 @@ impl TransactionCategory {
                     Err(TransactionCategoryParseError)
                 }
             }
+            "payment_earnings" => {
+                if let Some(trade_id) = trade_id {
+                    Ok(TransactionCategory::PaymentEarnings(trade_id))
+                } else {
+                    Err(TransactionCategoryParseError)
+                }
+            }
+            "payment_reinvestment" => {
+                if let Some(trade_id) = trade_id {
+                    Ok(TransactionCategory::PaymentReinvestment(trade_id))
+                } else {
+                    Err(TransactionCategoryParseError)
+                }
+            }
             "withdrawal_tax" => Ok(TransactionCategory::WithdrawalTax),
+            "withdrawal_earnings" => Ok(TransactionCategory::WithdrawalEarnings),
+            "fee" => Ok(TransactionCategory::Fee),
+            "dividend" => Ok(TransactionCategory::Dividend(None)),
+            "interest" => Ok(TransactionCategory::Interest),
+            // The reason is read with the rest of the transaction.
+            "adjustment" => Ok(TransactionCategory::Adjustment {
+                reason: String::new(),
+            }),
             "open_trade" => {
                 if let Some(trade_id) = trade_id {
                     Ok(TransactionCategory::OpenTrade(trade_id))
@@ -243,6 +333,13 @@ impl TransactionCategory {
                     Err(TransactionCategoryParseError)
                 }
             }
+            "fee_rebate" => {
+                if let Some(trade_id) = trade_id {
+                    Ok(TransactionCategory::FeeRebate(trade_id))
+                } else {
+                    Err(TransactionCategoryParseError)
+                }
+            }
             _ => Err(TransactionCategoryParseError),
         }
     }
@@ -252,6 +349,75 @@ impl TransactionCategory {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_transaction_category_from_string_fee() {
+        let result = TransactionCategory::parse("fee", None)
+            .expect("Failed to parse TransactionCategory from string");
+        assert_eq!(result, TransactionCategory::Fee);
+        assert_eq!(result.trade_id(), None);
+        assert_eq!(result.to_string(), "fee");
+    }
+
+    #[test]
+    fn test_transaction_category_from_string_dividend() {
+        let result = TransactionCategory::parse("dividend", None)
+            .expect("Failed to parse TransactionCategory from string");
+        assert_eq!(result, TransactionCategory::Dividend(None));
+        assert_eq!(result.trade_id(), None);
+        assert_eq!(result.to_string(), "dividend");
+    }
+
+    #[test]
+    fn test_transaction_category_from_string_interest() {
+        let result = TransactionCategory::parse("interest", None)
+            .expect("Failed to parse TransactionCategory from string");
+        assert_eq!(result, TransactionCategory::Interest);
+        assert_eq!(result.trade_id(), None);
+        assert_eq!(result.to_string(), "interest");
+    }
+
+    #[test]
+    fn test_transaction_category_from_string_adjustment() {
+        let result = TransactionCategory::parse("adjustment", None)
+            .expect("Failed to parse TransactionCategory from string");
+        assert_eq!(
+            result,
+            TransactionCategory::Adjustment {
+                reason: String::new()
+            }
+        );
+        assert_eq!(result.trade_id(), None);
+        assert_eq!(result.to_string(), "adjustment");
+    }
+
+    #[test]
+    fn test_transaction_category_dividend_trading_vehicle() {
+        let id = Uuid::new_v4();
+        let category = TransactionCategory::Dividend(Some(id));
+        assert_eq!(category.trading_vehicle_id(), Some(id));
+        assert_eq!(category.trade_id(), None);
+        assert_eq!(TransactionCategory::Deposit.trading_vehicle_id(), None);
+    }
+
+    #[test]
+    fn test_transaction_category_from_string_payment_reinvestment() {
+        let id = Uuid::new_v4();
+        let result = TransactionCategory::parse("payment_reinvestment", Some(id))
+            .expect("Failed to parse TransactionCategory from string");
+        assert_eq!(result, TransactionCategory::PaymentReinvestment(id));
+        assert_eq!(result.trade_id(), Some(id));
+        assert_eq!(result.to_string(), "payment_reinvestment");
+        assert!(TransactionCategory::parse("payment_reinvestment", None).is_err());
+    }
+
+    #[test]
+    fn test_transaction_category_from_string_payment_earnings() {
+        let id = Uuid::new_v4();
+        let result = TransactionCategory::parse("payment_earnings", Some(id))
+            .expect("Failed to parse TransactionCategory from string");
+        assert_eq!(result, TransactionCategory::PaymentEarnings(id));
+    }
+
     #[test]
     fn test_transaction_category_from_string_deposit() {
         let result = TransactionCategory::parse("deposit", None)
@@ -313,6 +479,17 @@ mod tests {
         assert_eq!(result, TransactionCategory::FeeClose(id));
     }
 
+    #[test]
+    fn test_transaction_category_from_string_fee_rebate() {
+        let id = Uuid::new_v4();
+        let result = TransactionCategory::parse("fee_rebate", Some(id))
+            .expect("Failed to parse TransactionCategory from string");
+        assert_eq!(result, TransactionCategory::FeeRebate(id));
+        assert_eq!(result.trade_id(), Some(id));
+        assert_eq!(result.to_string(), "fee_rebate");
+        assert!(TransactionCategory::parse("fee_rebate", None).is_err());
+    }
+
     #[test]
     fn test_transaction_category_from_invalid_string() {
         TransactionCategory::parse("Invalid", None)